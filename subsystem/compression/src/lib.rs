@@ -0,0 +1,180 @@
+//! Host-native gzip and Zstandard codecs backing the `selium::compress` hostcalls.
+
+use std::{
+    future::Future,
+    io::{Read, Write},
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use selium_abi::{CompressionAlgorithm, CompressionLevel, ErrorCode};
+use selium_kernel::{
+    drivers::compress::CompressionCapability,
+    guest_data::{GuestError, GuestResult},
+};
+
+/// Codecs linked into the host. Work runs on Tokio's blocking pool, so compressing a large
+/// payload does not stall the guests sharing a runtime thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeCompression;
+
+impl CompressionCapability for NativeCompression {
+    fn deflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        level: CompressionLevel,
+        data: Vec<u8>,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static {
+        blocking(move || deflate(algorithm, level, &data))
+    }
+
+    fn inflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        data: Vec<u8>,
+        max_len: usize,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static {
+        blocking(move || inflate(algorithm, &data, max_len))
+    }
+}
+
+async fn blocking<F>(work: F) -> GuestResult<Vec<u8>>
+where
+    F: FnOnce() -> GuestResult<Vec<u8>> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|err| GuestError::Subsystem(err.to_string()))?
+}
+
+fn deflate(
+    algorithm: CompressionAlgorithm,
+    level: CompressionLevel,
+    data: &[u8],
+) -> GuestResult<Vec<u8>> {
+    let compressed = match algorithm {
+        CompressionAlgorithm::Gzip => {
+            let level = match level {
+                CompressionLevel::Fastest => Compression::fast(),
+                CompressionLevel::Default => Compression::default(),
+                CompressionLevel::Best => Compression::best(),
+            };
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|()| encoder.finish())
+        }
+        CompressionAlgorithm::Zstd => {
+            let level = match level {
+                CompressionLevel::Fastest => 1,
+                CompressionLevel::Default => zstd::DEFAULT_COMPRESSION_LEVEL,
+                CompressionLevel::Best => 19,
+            };
+            zstd::bulk::compress(data, level)
+        }
+    };
+    compressed.map_err(|err| GuestError::Subsystem(format!("{algorithm:?} compression: {err}")))
+}
+
+fn inflate(algorithm: CompressionAlgorithm, data: &[u8], max_len: usize) -> GuestResult<Vec<u8>> {
+    let limit = u64::try_from(max_len).unwrap_or(u64::MAX).saturating_add(1);
+    let mut inflated = Vec::new();
+    let read = match algorithm {
+        CompressionAlgorithm::Gzip => GzDecoder::new(data).take(limit).read_to_end(&mut inflated),
+        CompressionAlgorithm::Zstd => zstd::stream::read::Decoder::new(data)
+            .and_then(|decoder| decoder.take(limit).read_to_end(&mut inflated)),
+    };
+    read.map_err(|err| {
+        GuestError::Coded(
+            ErrorCode::InvalidArgument,
+            format!("malformed {algorithm:?} payload: {err}"),
+        )
+    })?;
+    if inflated.len() > max_len {
+        return Err(GuestError::Coded(
+            ErrorCode::PayloadTooLarge,
+            format!("{algorithm:?} payload inflates to more than {max_len} bytes"),
+        ));
+    }
+    Ok(inflated)
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{CompressDeflate, CompressInflate};
+    use selium_kernel::{drivers::compress::operations, testing::BlockingKernelClient};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn payloads_round_trip_through_each_algorithm() {
+        let data = b"selium ".repeat(1_000);
+        for algorithm in CompressionAlgorithm::ALL {
+            for level in CompressionLevel::ALL {
+                let compressed = NativeCompression
+                    .deflate(algorithm, level, data.clone())
+                    .await
+                    .expect("deflate");
+                assert!(compressed.len() < data.len(), "{algorithm:?} at {level:?}");
+                let inflated = NativeCompression
+                    .inflate(algorithm, compressed, data.len())
+                    .await
+                    .expect("inflate");
+                assert_eq!(inflated, data);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn inflating_is_bounded_and_rejects_malformed_input() {
+        let data = vec![0; 4_096];
+        for algorithm in CompressionAlgorithm::ALL {
+            let compressed = NativeCompression
+                .deflate(algorithm, CompressionLevel::Default, data.clone())
+                .await
+                .expect("deflate");
+            let err = NativeCompression
+                .inflate(algorithm, compressed, data.len() - 1)
+                .await
+                .expect_err("exceeds limit");
+            assert_eq!(err.code(), ErrorCode::PayloadTooLarge);
+
+            let err = NativeCompression
+                .inflate(algorithm, b"not compressed".to_vec(), data.len())
+                .await
+                .expect_err("malformed");
+            assert_eq!(err.code(), ErrorCode::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn hostcalls_round_trip_and_bound_inflation_at_the_output_limit() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let (deflate, inflate) = operations(NativeCompression);
+        let max_output = selium_abi::hostcall_contract!(COMPRESS_INFLATE).max_output();
+        let data = b"selium ".repeat(1_000);
+        let bomb = vec![0; max_output + 1];
+
+        for algorithm in CompressionAlgorithm::ALL {
+            let compressed = client
+                .call(
+                    &deflate,
+                    &CompressDeflate {
+                        algorithm,
+                        level: CompressionLevel::Default,
+                        data: data.clone(),
+                    },
+                )
+                .expect("deflate");
+            // The bomb is larger than the hostcall accepts, so it is built without one.
+            let bomb = super::deflate(algorithm, CompressionLevel::Best, &bomb).expect("bomb");
+            assert!(bomb.len() < max_output / 100, "{algorithm:?}");
+
+            let mut decompress =
+                |data: Vec<u8>| client.call(&inflate, &CompressInflate { algorithm, data });
+            assert_eq!(decompress(compressed.clone()).expect("inflate"), data);
+            let err = decompress(bomb).expect_err("bomb");
+            assert_eq!(err.code(), ErrorCode::PayloadTooLarge, "{algorithm:?}");
+            let truncated = compressed[..compressed.len() / 2].to_vec();
+            let err = decompress(truncated).expect_err("truncated");
+            assert_eq!(err.code(), ErrorCode::InvalidArgument, "{algorithm:?}");
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Content-addressed cache in front of any module store.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+use ring::digest::{SHA256, SHA256_OUTPUT_LEN, digest};
+use selium_kernel::drivers::module_store::{
+    ModuleRevision, ModuleStoreError, ModuleStoreReadCapability,
+};
+use tracing::{debug, warn};
+
+type Digest = [u8; SHA256_OUTPUT_LEN];
+
+const ENTRY_EXTENSION: &str = "wasm";
+
+/// Decorator that keeps the modules read from `inner` on disk, named by their SHA-256 digest.
+///
+/// Each module id is mapped to the digest of the bytes last read for it and the revision `inner`
+/// reported for them, and later reads are served from the cache for as long as `inner` reports
+/// the same revision. A module that was replaced, or whose revision `inner` cannot report, is
+/// read from `inner` again. A cached copy is hashed again on every read; one that no longer
+/// matches its digest has been altered on disk and is discarded in favour of a fresh read from
+/// `inner`. Failing to write an entry is never fatal, it only costs the next read another fetch.
+pub struct ContentCache<S> {
+    inner: S,
+    dir: PathBuf,
+    index: Mutex<HashMap<String, Entry>>,
+    counters: CacheCounters,
+}
+
+/// Counters of how reads through a [`ContentCache`] were served. Clones share the same
+/// counters.
+#[derive(Clone, Debug, Default)]
+pub struct CacheCounters {
+    shared: Arc<Counters>,
+}
+
+/// Counters describing how reads through a [`ContentCache`] were served.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheMetrics {
+    /// Reads served from a verified cache entry.
+    pub hits: u64,
+    /// Reads fetched from the wrapped store.
+    pub misses: u64,
+    /// Cache entries discarded because they were missing or no longer matched their digest.
+    pub integrity_failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    integrity_failures: AtomicU64,
+}
+
+/// The cached copy of one module.
+#[derive(Clone, Debug)]
+struct Entry {
+    revision: ModuleRevision,
+    digest: Digest,
+}
+
+impl<S> ContentCache<S> {
+    /// Cache modules read from `inner` in `dir`; the directory is created on first write.
+    pub fn new(inner: S, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            index: Mutex::default(),
+            counters: CacheCounters::default(),
+        }
+    }
+
+    /// Directory holding the cached modules.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Counters accumulated since the cache was created.
+    pub fn metrics(&self) -> CacheMetrics {
+        self.counters.snapshot()
+    }
+
+    /// The cache's counters, to be reported elsewhere as the cache is used.
+    pub fn counters(&self) -> &CacheCounters {
+        &self.counters
+    }
+
+    /// Forget the content cached for `module_id`, so that the next read fetches it again, e.g.
+    /// after the module has been redeployed.
+    pub fn evict(&self, module_id: &str) {
+        self.index.lock().remove(module_id);
+    }
+
+    /// Read the entry for `expected` if it is present and intact.
+    fn load(&self, module_id: &str, expected: &Digest) -> Option<Vec<u8>> {
+        let path = self.entry_path(expected);
+        let failure = match fs::read(&path) {
+            Ok(bytes) if sha256(&bytes) == *expected => return Some(bytes),
+            Ok(_) => "digest mismatch".to_string(),
+            Err(err) => err.to_string(),
+        };
+
+        warn!(module_id, path = %path.display(), failure, "discarding cached module");
+        self.counters
+            .shared
+            .integrity_failures
+            .fetch_add(1, Ordering::Relaxed);
+        if let Err(err) = fs::remove_file(&path)
+            && err.kind() != io::ErrorKind::NotFound
+        {
+            warn!(path = %path.display(), %err, "failed to remove cached module");
+        }
+        None
+    }
+
+    /// Write the entry next to its final path and rename it into place, so concurrent readers
+    /// never observe a partially written entry.
+    fn store(&self, digest: &Digest, bytes: &[u8]) -> io::Result<()> {
+        let path = self.entry_path(digest);
+        fs::create_dir_all(&self.dir)?;
+        let staging = path.with_extension(format!("{ENTRY_EXTENSION}.{}.tmp", std::process::id()));
+        fs::write(&staging, bytes)?;
+        fs::rename(&staging, path)
+    }
+
+    fn entry_path(&self, digest: &Digest) -> PathBuf {
+        let name: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        self.dir.join(name).with_extension(ENTRY_EXTENSION)
+    }
+}
+
+impl CacheCounters {
+    /// Values of the counters at this moment.
+    pub fn snapshot(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.shared.hits.load(Ordering::Relaxed),
+            misses: self.shared.misses.load(Ordering::Relaxed),
+            integrity_failures: self.shared.integrity_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<S> ModuleStoreReadCapability for ContentCache<S>
+where
+    S: ModuleStoreReadCapability,
+{
+    fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError> {
+        // Ask for the revision before reading, so that a module replaced in between is cached
+        // under the older revision and read again next time, never the other way round.
+        let revision = self.inner.revision(module_id);
+        let cached = self.index.lock().get(module_id).cloned();
+        if let Some(entry) = cached {
+            if revision.as_ref() == Some(&entry.revision) {
+                if let Some(bytes) = self.load(module_id, &entry.digest) {
+                    self.counters.shared.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(bytes);
+                }
+            } else {
+                debug!(module_id, "module changed in the store; fetching it again");
+            }
+            self.index.lock().remove(module_id);
+        }
+
+        self.counters.shared.misses.fetch_add(1, Ordering::Relaxed);
+        let bytes = self.inner.read(module_id)?;
+        let Some(revision) = revision else {
+            return Ok(bytes);
+        };
+        let digest = sha256(&bytes);
+        match self.store(&digest, &bytes) {
+            Ok(()) => {
+                debug!(module_id, "cached module");
+                self.index
+                    .lock()
+                    .insert(module_id.to_string(), Entry { revision, digest });
+            }
+            Err(err) => warn!(module_id, %err, "failed to cache module"),
+        }
+        Ok(bytes)
+    }
+
+    fn revision(&self, module_id: &str) -> Option<ModuleRevision> {
+        self.inner.revision(module_id)
+    }
+}
+
+fn sha256(bytes: &[u8]) -> Digest {
+    let mut out = [0; SHA256_OUTPUT_LEN];
+    out.copy_from_slice(digest(&SHA256, bytes).as_ref());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_testing::FakeModuleStore;
+
+    use super::*;
+    use crate::{FilesystemStore, FilesystemStoreReadDriver};
+
+    #[test]
+    fn tampered_entries_are_refetched() {
+        let dir = std::env::temp_dir().join(format!("selium-content-cache-{}", std::process::id()));
+        let cache = ContentCache::new(
+            FakeModuleStore::new().with_module("echo.wasm", b"module".to_vec()),
+            &dir,
+        );
+
+        assert_eq!(cache.read("echo.wasm").expect("fetch"), b"module");
+        assert_eq!(cache.read("echo.wasm").expect("cached"), b"module");
+        assert_eq!(cache.inner.recorder().calls().len(), 1);
+
+        fs::write(cache.entry_path(&sha256(b"module")), b"tampered").expect("tamper entry");
+        assert_eq!(cache.read("echo.wasm").expect("refetch"), b"module");
+        assert_eq!(cache.inner.recorder().calls().len(), 2);
+
+        cache.evict("echo.wasm");
+        assert_eq!(cache.read("echo.wasm").expect("refetch"), b"module");
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 1,
+                misses: 3,
+                integrity_failures: 1,
+            }
+        );
+
+        fs::remove_dir_all(&dir).expect("remove cache dir");
+    }
+
+    #[test]
+    fn replaced_modules_are_read_again() {
+        let dir = std::env::temp_dir().join(format!("selium-cache-replace-{}", std::process::id()));
+        let modules = dir.join("modules");
+        fs::create_dir_all(&modules).expect("create modules dir");
+        fs::write(modules.join("echo.wasm"), b"first").expect("write module");
+        let cache = ContentCache::new(
+            FilesystemStoreReadDriver::new(FilesystemStore::new(&modules)),
+            dir.join("cache"),
+        );
+
+        assert_eq!(cache.read("echo.wasm").expect("fetch"), b"first");
+        assert_eq!(cache.read("echo.wasm").expect("cached"), b"first");
+        fs::write(modules.join("echo.wasm"), b"replaced").expect("replace module");
+        assert_eq!(cache.read("echo.wasm").expect("refetch"), b"replaced");
+        assert_eq!(cache.read("echo.wasm").expect("cached"), b"replaced");
+        assert_eq!(
+            cache.counters().snapshot(),
+            CacheMetrics {
+                hits: 2,
+                misses: 2,
+                integrity_failures: 0,
+            }
+        );
+
+        let fake = ContentCache::new(
+            FakeModuleStore::new().with_module("echo.wasm", b"first".to_vec()),
+            dir.join("fake-cache"),
+        );
+        assert_eq!(fake.read("echo.wasm").expect("fetch"), b"first");
+        fake.inner.insert("echo.wasm", b"second".to_vec());
+        assert_eq!(fake.read("echo.wasm").expect("refetch"), b"second");
+        assert_eq!(fake.inner.recorder().calls().len(), 2);
+
+        fs::remove_dir_all(&dir).expect("remove dirs");
+    }
+}
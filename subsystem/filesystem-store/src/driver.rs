@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use selium_kernel::drivers::module_store::{ModuleStoreError, ModuleStoreReadCapability};
+use selium_kernel::drivers::module_store::{
+    ModuleRevision, ModuleStoreError, ModuleStoreReadCapability,
+};
 
 use crate::FilesystemStore;
 
@@ -18,4 +20,8 @@ impl ModuleStoreReadCapability for FilesystemStoreReadDriver {
     fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError> {
         self.inner.fetch(module_id)
     }
+
+    fn revision(&self, module_id: &str) -> Option<ModuleRevision> {
+        self.inner.revision(module_id)
+    }
 }
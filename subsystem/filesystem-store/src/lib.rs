@@ -1,40 +1,207 @@
 use std::{
-    fs::File,
-    io::Read,
+    collections::HashSet,
+    ffi::OsString,
+    fs,
     path::{Path, PathBuf},
 };
 
+mod cache;
 mod driver;
+mod sandbox;
+mod watch;
+pub use cache::{CacheCounters, CacheMetrics, ContentCache};
 pub use driver::FilesystemStoreReadDriver;
-use path_security::validate_path;
-use selium_kernel::drivers::module_store::ModuleStoreError;
+use ring::signature::{ED25519, UnparsedPublicKey};
+pub use sandbox::{Mount, MountAccess, Sandbox, SandboxError, SandboxPolicy, Sandboxes};
+use selium_kernel::drivers::module_store::{ModuleRevision, ModuleStoreError};
+use tracing::warn;
+
+/// Extension appended to a module's file name to locate its detached signature.
+pub const SIGNATURE_EXTENSION: &str = "sig";
+/// Length in bytes of an ed25519 public key in a trust root.
+pub const PUBLIC_KEY_LEN: usize = 32;
 
 pub struct FilesystemStore {
-    base_dir: PathBuf,
+    modules: Sandbox,
+    trust_root: Option<TrustRoot>,
+    allowed_digests: Option<HashSet<blake3::Hash>>,
+}
+
+/// Ed25519 public keys whose signatures are accepted on modules.
+#[derive(Clone, Debug, Default)]
+pub struct TrustRoot {
+    keys: Vec<[u8; PUBLIC_KEY_LEN]>,
 }
 
 impl FilesystemStore {
     pub fn new(base_dir: impl AsRef<Path>) -> Self {
         Self {
-            base_dir: base_dir.as_ref().into(),
+            modules: Sandbox::new(
+                base_dir.as_ref(),
+                SandboxPolicy {
+                    root: MountAccess::ReadOnly,
+                    mounts: Vec::new(),
+                },
+            ),
+            trust_root: None,
+            allowed_digests: None,
         }
     }
 
+    /// Refuse modules that lack a detached signature verifying against `trust_root`.
+    ///
+    /// The signature for `foo.wasm` is read from `foo.wasm.sig`, as written by
+    /// `selium-runtime sign-module`.
+    pub fn with_trust_root(mut self, trust_root: TrustRoot) -> Self {
+        self.trust_root = Some(trust_root);
+        self
+    }
+
+    /// Refuse modules whose BLAKE3 digest is not one of `digests`, in addition to any signature
+    /// check.
+    pub fn with_allowed_digests(mut self, digests: impl IntoIterator<Item = blake3::Hash>) -> Self {
+        self.allowed_digests = Some(digests.into_iter().collect());
+        self
+    }
+
+    /// Read the module at `path`, enforcing the store's digest allow-list and trust root.
+    /// Rejected modules are logged as audit warnings.
     pub fn fetch(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, ModuleStoreError> {
-        let fq_path = validate_path(path.as_ref(), &self.base_dir).map_err(|e| {
-            ModuleStoreError::InvalidPath(
-                self.base_dir.as_path().join(path.as_ref()),
-                e.to_string(),
-            )
-        })?;
-
-        let mut fh =
-            File::open(fq_path).map_err(|e| ModuleStoreError::Filesystem(e.to_string()))?;
+        let module = self.read(path.as_ref())?;
+        self.admit(path.as_ref(), &module).inspect_err(
+            |err| warn!(module = %path.as_ref().display(), %err, "audit: module refused"),
+        )?;
+        Ok(module)
+    }
+
+    /// Modification time and length of the module at `path`, or `None` if it cannot be read.
+    pub fn revision(&self, path: impl AsRef<Path>) -> Option<ModuleRevision> {
+        let host_path = self.modules.resolve(path, MountAccess::ReadOnly).ok()?;
+        let metadata = fs::metadata(host_path).ok()?;
+        Some(ModuleRevision::Modified(
+            metadata.modified().ok()?,
+            metadata.len(),
+        ))
+    }
+
+    fn admit(&self, path: &Path, module: &[u8]) -> Result<(), ModuleStoreError> {
+        if let Some(allowed) = &self.allowed_digests {
+            let digest = blake3::hash(module);
+            if !allowed.contains(&digest) {
+                return Err(ModuleStoreError::Policy(
+                    self.modules.root().join(path),
+                    format!("digest {} is not allowed", digest.to_hex()),
+                ));
+            }
+        }
+
+        if let Some(trust_root) = &self.trust_root {
+            let sig_path = signature_path(path);
+            let signature = self.read(&sig_path).map_err(|err| {
+                ModuleStoreError::Signature(self.modules.root().join(path), err.to_string())
+            })?;
+            if !trust_root.verify(module, &signature) {
+                return Err(ModuleStoreError::Signature(
+                    self.modules.root().join(path),
+                    "no trusted key verifies the signature".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, ModuleStoreError> {
         // @todo Set memory limit!
-        let mut buf = Vec::new();
-        fh.read_to_end(&mut buf)
-            .map_err(|e| ModuleStoreError::Filesystem(e.to_string()))?;
+        self.modules.read(path).map_err(|err| match err {
+            SandboxError::Escape(path, reason) => ModuleStoreError::InvalidPath(path, reason),
+            SandboxError::Access { path, granted } => {
+                ModuleStoreError::Policy(path, format!("store grants only {granted} access"))
+            }
+            SandboxError::Io(_, err) => ModuleStoreError::Filesystem(err.to_string()),
+            SandboxError::DuplicateMount(_) => ModuleStoreError::Filesystem(err.to_string()),
+        })
+    }
+}
+
+impl TrustRoot {
+    /// Trust signatures made by any of `keys`.
+    pub fn new(keys: impl IntoIterator<Item = [u8; PUBLIC_KEY_LEN]>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// Whether any trusted key verifies `signature` over `message`.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.keys.iter().any(|key| {
+            UnparsedPublicKey::new(&ED25519, key)
+                .verify(message, signature)
+                .is_ok()
+        })
+    }
+}
+
+/// Path of the detached signature for the module at `module`.
+pub fn signature_path(module: &Path) -> PathBuf {
+    let mut name = module.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    module.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    #[test]
+    fn unsigned_and_tampered_modules_are_refused() {
+        let dir = std::env::temp_dir().join(format!("selium-fs-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create store dir");
+        let key = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).expect("signing key");
+        let mut public = [0; PUBLIC_KEY_LEN];
+        public.copy_from_slice(key.public_key().as_ref());
+        let store = FilesystemStore::new(&dir).with_trust_root(TrustRoot::new([public]));
+
+        fs::write(dir.join("unsigned.wasm"), b"module").expect("write module");
+        assert!(matches!(
+            store.fetch("unsigned.wasm"),
+            Err(ModuleStoreError::Signature(_, _))
+        ));
+
+        fs::write(dir.join("signed.wasm"), b"module").expect("write module");
+        fs::write(dir.join("signed.wasm.sig"), key.sign(b"module")).expect("write signature");
+        assert_eq!(store.fetch("signed.wasm").expect("verified"), b"module");
+
+        fs::write(dir.join("signed.wasm"), b"tampered").expect("tamper module");
+        assert!(matches!(
+            store.fetch("signed.wasm"),
+            Err(ModuleStoreError::Signature(_, _))
+        ));
+
+        fs::remove_dir_all(&dir).expect("remove store dir");
+    }
+
+    #[test]
+    fn modules_outside_the_digest_allow_list_are_refused() {
+        let dir = std::env::temp_dir().join(format!("selium-fs-digests-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create store dir");
+        fs::write(dir.join("allowed.wasm"), b"allowed").expect("write module");
+        fs::write(dir.join("other.wasm"), b"other").expect("write module");
+        let store = FilesystemStore::new(&dir).with_allowed_digests([blake3::hash(b"allowed")]);
+
+        assert_eq!(store.fetch("allowed.wasm").expect("allowed"), b"allowed");
+        let err = store.fetch("other.wasm").expect_err("not allowed");
+        assert!(matches!(err, ModuleStoreError::Policy(_, _)));
+        assert!(
+            err.to_string()
+                .contains(&blake3::hash(b"other").to_hex().to_string())
+        );
 
-        Ok(buf)
+        fs::remove_dir_all(&dir).expect("remove store dir");
     }
 }
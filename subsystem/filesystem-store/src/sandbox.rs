@@ -0,0 +1,410 @@
+//! Per-process filesystem sandboxes.
+//!
+//! Every process that is assigned a [`Sandbox`] gets its own directory, named after its module
+//! label, under the provider's sandboxes directory. A [`SandboxPolicy`] decides whether the
+//! guest may write to that directory and which host directories are mounted into it, each
+//! read-only or read-write. Guest paths are resolved by the provider, so a guest can neither
+//! escape its sandbox nor write through a read-only mount, whatever the driver asking. The
+//! module store reads modules through a read-only [`Sandbox`] over its modules directory.
+
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use parking_lot::RwLock;
+use path_security::validate_path;
+use selium_kernel::{
+    guest_data::GuestError,
+    registry::{Registry, RegistryError, ResourceId, ResourceType},
+};
+use thiserror::Error;
+
+/// Whether a guest may modify what it can see.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MountAccess {
+    /// Files may be read but not created, changed or removed.
+    ReadOnly,
+    /// Files may be read, created, changed and removed.
+    #[default]
+    ReadWrite,
+}
+
+/// A host directory exposed inside a sandbox under its own name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mount {
+    name: String,
+    source: PathBuf,
+    access: MountAccess,
+}
+
+/// How a process's sandbox is laid out.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SandboxPolicy {
+    /// Access to the process's own sandbox directory.
+    pub root: MountAccess,
+    /// Host directories mounted into the sandbox.
+    pub mounts: Vec<Mount>,
+}
+
+/// The filesystem view of a single process.
+#[derive(Debug)]
+pub struct Sandbox {
+    root: PathBuf,
+    policy: SandboxPolicy,
+}
+
+/// Errors raised while resolving or accessing paths in a [`Sandbox`].
+#[derive(Debug, Error)]
+pub enum SandboxError {
+    /// The path leaves the directory it resolves in.
+    #[error("{} escapes the sandbox: {}", .0.display(), .1)]
+    Escape(PathBuf, String),
+    /// The sandbox grants less access to the path than was asked for.
+    #[error("{} is only granted {granted} access", .path.display())]
+    Access { path: PathBuf, granted: MountAccess },
+    /// Two mounts of a policy would be visible under the same name.
+    #[error("more than one mount is named `{0}`")]
+    DuplicateMount(String),
+    /// The host filesystem failed.
+    #[error("{}: {}", .0.display(), .1)]
+    Io(PathBuf, #[source] io::Error),
+}
+
+/// Sandboxes of running processes, each rooted in its own subdirectory of a base directory.
+/// Clones share the sandboxes assigned.
+#[derive(Clone, Debug)]
+pub struct Sandboxes {
+    base_dir: PathBuf,
+    assigned: Arc<RwLock<HashMap<ResourceId, Arc<Sandbox>>>>,
+}
+
+impl MountAccess {
+    /// Whether this access grants everything `requested` does.
+    pub fn permits(self, requested: MountAccess) -> bool {
+        self == MountAccess::ReadWrite || requested == MountAccess::ReadOnly
+    }
+}
+
+impl FromStr for MountAccess {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ro" => Ok(Self::ReadOnly),
+            "rw" => Ok(Self::ReadWrite),
+            other => Err(format!("unknown access `{other}`; expected `ro` or `rw`")),
+        }
+    }
+}
+
+impl fmt::Display for MountAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReadOnly => "ro",
+            Self::ReadWrite => "rw",
+        })
+    }
+}
+
+impl Mount {
+    /// Mount the host directory `source` with `access`, visible to the guest under the final
+    /// component of `source`.
+    pub fn new(source: impl Into<PathBuf>, access: MountAccess) -> Result<Self, String> {
+        let source = source.into();
+        let name = match source.components().next_back() {
+            Some(Component::Normal(name)) => name
+                .to_str()
+                .ok_or_else(|| format!("mount {} is not valid UTF-8", source.display()))?
+                .to_string(),
+            _ => return Err(format!("mount {} has no directory name", source.display())),
+        };
+        Ok(Self {
+            name,
+            source,
+            access,
+        })
+    }
+
+    /// Name the mount is visible under inside the sandbox.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Host directory behind the mount.
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Access the guest has to the mount.
+    pub fn access(&self) -> MountAccess {
+        self.access
+    }
+}
+
+impl FromStr for Mount {
+    type Err = String;
+
+    /// Parse `path[:ro|:rw]`; mounts are read-only unless marked `rw`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(':') {
+            Some((source, access)) => Self::new(source, access.parse()?),
+            None => Self::new(s, MountAccess::ReadOnly),
+        }
+    }
+}
+
+impl SandboxPolicy {
+    /// Name shared by more than one of the policy's mounts, if any. Mounts are named after the
+    /// final component of their source, so `a/data` and `b/data` collide.
+    pub fn duplicate_mount(&self) -> Option<&str> {
+        self.mounts.iter().enumerate().find_map(|(index, mount)| {
+            self.mounts[..index]
+                .iter()
+                .any(|earlier| earlier.name == mount.name)
+                .then_some(mount.name.as_str())
+        })
+    }
+}
+
+impl Sandbox {
+    /// Confine paths to `root`, laid out by `policy`.
+    pub fn new(root: impl Into<PathBuf>, policy: SandboxPolicy) -> Self {
+        Self {
+            root: root.into(),
+            policy,
+        }
+    }
+
+    /// Host directory holding the process's own files.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Layout the sandbox enforces.
+    pub fn policy(&self) -> &SandboxPolicy {
+        &self.policy
+    }
+
+    /// Resolve the guest path `path` to a host path, refusing paths that leave the sandbox or
+    /// ask for more `access` than the sandbox grants. Paths whose first component names a mount
+    /// resolve inside that mount; all others resolve inside the sandbox directory.
+    pub fn resolve(
+        &self,
+        path: impl AsRef<Path>,
+        access: MountAccess,
+    ) -> Result<PathBuf, SandboxError> {
+        let path = path.as_ref();
+        let mount = path.components().next().and_then(|first| {
+            self.policy
+                .mounts
+                .iter()
+                .find(|mount| Path::new(&mount.name) == Path::new(first.as_os_str()))
+        });
+        let (base, granted, relative) = match mount {
+            Some(mount) => (
+                mount.source.as_path(),
+                mount.access,
+                path.strip_prefix(&mount.name).unwrap_or(path),
+            ),
+            None => (self.root.as_path(), self.policy.root, path),
+        };
+
+        if !granted.permits(access) {
+            return Err(SandboxError::Access {
+                path: base.join(relative),
+                granted,
+            });
+        }
+        if relative.as_os_str().is_empty() {
+            return Ok(base.to_path_buf());
+        }
+        validate_path(relative, base)
+            .map_err(|err| SandboxError::Escape(base.join(relative), err.to_string()))
+    }
+
+    /// Read the file at the guest path `path`.
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, SandboxError> {
+        let host_path = self.resolve(path, MountAccess::ReadOnly)?;
+        fs::read(&host_path).map_err(|err| SandboxError::Io(host_path, err))
+    }
+
+    /// Write `contents` to the file at the guest path `path`, replacing it if it exists.
+    pub fn write(&self, path: impl AsRef<Path>, contents: &[u8]) -> Result<(), SandboxError> {
+        let host_path = self.resolve(path, MountAccess::ReadWrite)?;
+        fs::write(&host_path, contents).map_err(|err| SandboxError::Io(host_path, err))
+    }
+}
+
+impl Sandboxes {
+    /// Keep sandboxes in subdirectories of `base_dir`.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            assigned: Arc::default(),
+        }
+    }
+
+    /// Forget the sandbox of each process as it is removed from `registry`, leaving its
+    /// directory in place.
+    pub fn release_removed(&self, registry: &Registry) -> Result<(), RegistryError> {
+        let assigned = Arc::clone(&self.assigned);
+        registry.on_remove(ResourceType::Process, move |process| {
+            assigned.write().remove(&process.id);
+        })
+    }
+
+    /// Give `process_id` the sandbox of the module `label`, laid out by `policy`, creating its
+    /// directory if needed. Processes of the same label share a directory, so files survive
+    /// restarts.
+    pub fn assign(
+        &self,
+        process_id: ResourceId,
+        label: &str,
+        policy: SandboxPolicy,
+    ) -> Result<Arc<Sandbox>, SandboxError> {
+        if let Some(name) = policy.duplicate_mount() {
+            return Err(SandboxError::DuplicateMount(name.to_string()));
+        }
+        let root = self.base_dir.join(directory_name(label));
+        fs::create_dir_all(&root).map_err(|err| SandboxError::Io(root.clone(), err))?;
+        let root = root
+            .canonicalize()
+            .map_err(|err| SandboxError::Io(root.clone(), err))?;
+        let sandbox = Arc::new(Sandbox::new(root, policy));
+
+        self.assigned
+            .write()
+            .insert(process_id, Arc::clone(&sandbox));
+        Ok(sandbox)
+    }
+
+    /// Sandbox assigned to `process_id`; processes without one have no filesystem access.
+    pub fn get(&self, process_id: ResourceId) -> Option<Arc<Sandbox>> {
+        self.assigned.read().get(&process_id).cloned()
+    }
+}
+
+impl From<SandboxError> for GuestError {
+    fn from(value: SandboxError) -> Self {
+        match value {
+            SandboxError::Escape(..) | SandboxError::Access { .. } => GuestError::PermissionDenied,
+            SandboxError::Io(_, err) if err.kind() == io::ErrorKind::NotFound => {
+                GuestError::NotFound
+            }
+            SandboxError::Io(..) | SandboxError::DuplicateMount(_) => {
+                GuestError::Subsystem(value.to_string())
+            }
+        }
+    }
+}
+
+/// Directory name for the module `label`: ASCII letters, digits, `-` and `_` are kept and every
+/// other byte is percent-encoded, so that a label can neither name a path outside the sandboxes
+/// directory nor share a directory with another label.
+fn directory_name(label: &str) -> String {
+    if label.is_empty() {
+        return "%".to_string();
+    }
+    let mut name = String::with_capacity(label.len());
+    for byte in label.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            name.push(char::from(byte));
+        } else {
+            name.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sandboxes_confine_paths_and_enforce_mount_access() {
+        let dir = std::env::temp_dir().join(format!("selium-sandbox-{}", std::process::id()));
+        let assets = dir.join("assets");
+        fs::create_dir_all(&assets).expect("create assets");
+        fs::write(assets.join("logo.txt"), b"logo").expect("write asset");
+        let registry = Registry::new();
+        let process = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+
+        let sandboxes = Sandboxes::new(dir.join("sandboxes"));
+        sandboxes
+            .release_removed(&registry)
+            .expect("release on removal");
+        let policy = SandboxPolicy {
+            root: MountAccess::ReadWrite,
+            mounts: vec![format!("{}:ro", assets.display()).parse().expect("mount")],
+        };
+        let sandbox = sandboxes
+            .assign(process, "../echo.wasm", policy)
+            .expect("assign sandbox");
+        assert!(sandbox.root().ends_with("sandboxes/%2E%2E%2Fecho%2Ewasm"));
+        assert!(sandboxes.get(process).is_some());
+
+        sandbox
+            .write("state.txt", b"state")
+            .expect("write own file");
+        assert_eq!(sandbox.read("state.txt").expect("read own file"), b"state");
+        assert_eq!(
+            sandbox.read("assets/logo.txt").expect("read mount"),
+            b"logo"
+        );
+        assert!(matches!(
+            sandbox.write("assets/logo.txt", b"defaced"),
+            Err(SandboxError::Access {
+                granted: MountAccess::ReadOnly,
+                ..
+            })
+        ));
+        assert!(matches!(
+            sandbox.read("../../assets/logo.txt"),
+            Err(SandboxError::Escape(_, _))
+        ));
+
+        registry.discard(process);
+        assert!(sandboxes.get(process).is_none());
+
+        fs::remove_dir_all(&dir).expect("remove sandbox dir");
+    }
+
+    #[test]
+    fn labels_get_distinct_directories_and_mounts_distinct_names() {
+        let dir = std::env::temp_dir().join(format!("selium-sandbox-names-{}", std::process::id()));
+        let registry = Registry::new();
+        let sandboxes = Sandboxes::new(&dir);
+        let assign = |label: &str, policy: SandboxPolicy| {
+            let process = registry
+                .reserve(None, ResourceType::Process)
+                .expect("reserve process");
+            sandboxes.assign(process, label, policy)
+        };
+
+        let dotted = assign("echo.wasm", SandboxPolicy::default()).expect("assign sandbox");
+        let underscored = assign("echo_wasm", SandboxPolicy::default()).expect("assign sandbox");
+        assert_ne!(dotted.root(), underscored.root());
+        assert_ne!(directory_name(""), directory_name("_"));
+
+        let policy = SandboxPolicy {
+            root: MountAccess::ReadWrite,
+            mounts: vec![
+                Mount::new(dir.join("a/data"), MountAccess::ReadOnly).expect("mount"),
+                Mount::new(dir.join("b/data"), MountAccess::ReadWrite).expect("mount"),
+            ],
+        };
+        assert_eq!(policy.duplicate_mount(), Some("data"));
+        assert!(matches!(
+            assign("echo.wasm", policy),
+            Err(SandboxError::DuplicateMount(name)) if name == "data"
+        ));
+
+        fs::remove_dir_all(&dir).expect("remove sandbox dir");
+    }
+}
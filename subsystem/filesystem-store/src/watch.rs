@@ -0,0 +1,284 @@
+//! Change notifications for paths inside a sandbox.
+//!
+//! The watched path is rescanned on the host every [`SCAN_INTERVAL`], comparing each file's
+//! modification time and length with the previous scan, so guests are told about dropped,
+//! changed and deleted files instead of re-reading them to find out.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use futures_util::{Stream, StreamExt, future, stream};
+use selium_abi::{FsEvent, FsEventKind};
+use selium_kernel::{
+    drivers::fs::FilesystemWatchCapability,
+    guest_data::{GuestError, GuestResult},
+    registry::ResourceId,
+};
+
+use crate::{MountAccess, Sandboxes};
+
+/// How often a watched path is rescanned.
+const SCAN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification time and length of each file under a watched path, keyed by its path relative
+/// to the watched one.
+type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// A watched path and the changes found but not yet handed to the guest.
+struct Watcher {
+    host_path: PathBuf,
+    guest_path: String,
+    recursive: bool,
+    snapshot: Snapshot,
+    pending: VecDeque<FsEvent>,
+}
+
+impl FilesystemWatchCapability for Sandboxes {
+    fn watch(
+        &self,
+        process: Option<ResourceId>,
+        path: String,
+        recursive: bool,
+    ) -> impl Stream<Item = GuestResult<FsEvent>> + Send + 'static {
+        let host_path = process
+            .and_then(|process| self.get(process))
+            .ok_or(GuestError::PermissionDenied)
+            .and_then(|sandbox| {
+                sandbox
+                    .resolve(&path, MountAccess::ReadOnly)
+                    .map_err(GuestError::from)
+            });
+        match host_path {
+            Ok(host_path) => watch(host_path, path, recursive).left_stream(),
+            Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+        }
+    }
+}
+
+/// Stream the changes to `host_path` made from now on, reporting them under the guest's
+/// `guest_path`.
+fn watch(
+    host_path: PathBuf,
+    guest_path: String,
+    recursive: bool,
+) -> impl Stream<Item = GuestResult<FsEvent>> + Send + 'static {
+    let watcher = Watcher {
+        snapshot: scan(&host_path, recursive),
+        host_path,
+        guest_path,
+        recursive,
+        pending: VecDeque::new(),
+    };
+    stream::unfold(watcher, |mut watcher| async move {
+        loop {
+            if let Some(event) = watcher.pending.pop_front() {
+                return Some((Ok(event), watcher));
+            }
+            tokio::time::sleep(SCAN_INTERVAL).await;
+
+            let host_path = watcher.host_path.clone();
+            let recursive = watcher.recursive;
+            let scanned = tokio::task::spawn_blocking(move || scan(&host_path, recursive)).await;
+            let after = match scanned {
+                Ok(after) => after,
+                Err(err) => return Some((Err(GuestError::Subsystem(err.to_string())), watcher)),
+            };
+            let events = changes(&watcher.snapshot, &after)
+                .map(|(kind, relative)| FsEvent {
+                    kind,
+                    path: event_path(&watcher.guest_path, relative),
+                })
+                .collect::<Vec<_>>();
+            watcher.pending.extend(events);
+            watcher.snapshot = after;
+        }
+    })
+}
+
+/// Snapshot the file at `root`, or the files in the directory at `root`. A path that does not
+/// exist yet has an empty snapshot.
+fn scan(root: &Path, recursive: bool) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    match fs::metadata(root) {
+        Ok(metadata) if metadata.is_dir() => {
+            scan_dir(root, Path::new(""), recursive, &mut snapshot)
+        }
+        Ok(metadata) => {
+            snapshot.insert(PathBuf::new(), stamp(&metadata));
+        }
+        Err(_) => {}
+    }
+    snapshot
+}
+
+fn scan_dir(dir: &Path, relative: &Path, recursive: bool, snapshot: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        // Entry metadata does not follow symlinks, so a link cannot lead the scan out of the
+        // sandbox.
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = relative.join(entry.file_name());
+        if !metadata.is_dir() {
+            snapshot.insert(path, stamp(&metadata));
+        } else if recursive {
+            scan_dir(&entry.path(), &path, recursive, snapshot);
+        }
+    }
+}
+
+fn stamp(metadata: &Metadata) -> (Option<SystemTime>, u64) {
+    (metadata.modified().ok(), metadata.len())
+}
+
+/// Changes between two snapshots, in path order.
+fn changes<'a>(
+    before: &'a Snapshot,
+    after: &'a Snapshot,
+) -> impl Iterator<Item = (FsEventKind, &'a Path)> {
+    let removed = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .map(|path| (FsEventKind::Removed, path.as_path()));
+    let created_or_modified = after
+        .iter()
+        .filter_map(|(path, stamp)| match before.get(path) {
+            None => Some((FsEventKind::Created, path.as_path())),
+            Some(previous) if previous != stamp => Some((FsEventKind::Modified, path.as_path())),
+            Some(_) => None,
+        });
+    removed.chain(created_or_modified)
+}
+
+/// Sandbox path of the file at `relative` under the watched `watched` path.
+fn event_path(watched: &str, relative: &Path) -> String {
+    if relative.as_os_str().is_empty() {
+        watched.to_string()
+    } else {
+        Path::new(watched)
+            .join(relative)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use selium_kernel::registry::{Registry, ResourceType};
+
+    use super::*;
+    use crate::SandboxPolicy;
+
+    #[tokio::test]
+    async fn dropped_changed_and_removed_files_are_reported() {
+        let dir = std::env::temp_dir().join(format!("selium-watch-{}", std::process::id()));
+        let registry = Registry::new();
+        let process = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let sandboxes = Sandboxes::new(&dir);
+        let sandbox = sandboxes
+            .assign(process, "watcher", SandboxPolicy::default())
+            .expect("assign sandbox");
+        fs::create_dir_all(sandbox.root().join("inbox/nested")).expect("create inbox");
+
+        let mut events = Box::pin(sandboxes.watch(Some(process), "inbox".to_string(), false));
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(5), events.next())
+                .await
+                .expect("event before timeout")
+                .expect("stream open")
+                .expect("event")
+        };
+        sandbox.write("inbox/order.json", b"{}").expect("drop file");
+        sandbox
+            .write("inbox/nested/ignored.json", b"{}")
+            .expect("drop nested file");
+        assert_eq!(
+            next().await,
+            FsEvent {
+                kind: FsEventKind::Created,
+                path: "inbox/order.json".to_string(),
+            }
+        );
+
+        sandbox
+            .write("inbox/order.json", b"{\"id\":1}")
+            .expect("change file");
+        assert_eq!(next().await.kind, FsEventKind::Modified);
+
+        fs::remove_file(sandbox.root().join("inbox/order.json")).expect("remove file");
+        assert_eq!(next().await.kind, FsEventKind::Removed);
+
+        let other = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let mut denied = Box::pin(sandboxes.watch(Some(other), "inbox".to_string(), false));
+        let err = denied
+            .next()
+            .await
+            .expect("error item")
+            .expect_err("process without a sandbox");
+        assert!(matches!(err, GuestError::PermissionDenied));
+
+        fs::remove_dir_all(&dir).expect("remove sandbox dir");
+    }
+
+    #[tokio::test]
+    async fn recursive_watches_stay_inside_the_sandbox() {
+        let dir = std::env::temp_dir().join(format!("selium-watch-escape-{}", std::process::id()));
+        let registry = Registry::new();
+        let process = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let sandboxes = Arc::new(Sandboxes::new(&dir));
+        let sandbox = sandboxes
+            .assign(process, "watcher", SandboxPolicy::default())
+            .expect("assign sandbox");
+        fs::create_dir_all(sandbox.root().join("inbox/nested")).expect("create inbox");
+        fs::write(dir.join("outside.txt"), b"host").expect("write outside file");
+
+        let mut events = Box::pin(sandboxes.watch(Some(process), "inbox".to_string(), true));
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(5), events.next())
+                .await
+                .expect("event before timeout")
+                .expect("stream open")
+                .expect("event")
+        };
+        sandbox
+            .write("inbox/nested/order.json", b"{}")
+            .expect("drop nested file");
+        assert_eq!(
+            next().await,
+            FsEvent {
+                kind: FsEventKind::Created,
+                path: "inbox/nested/order.json".to_string(),
+            }
+        );
+        fs::remove_file(sandbox.root().join("inbox/nested/order.json")).expect("remove file");
+        assert_eq!(next().await.kind, FsEventKind::Removed);
+
+        for path in ["../outside.txt", "inbox/../../outside.txt", "/etc"] {
+            let mut denied = Box::pin(sandboxes.watch(Some(process), path.to_string(), false));
+            let err = denied
+                .next()
+                .await
+                .expect("error item")
+                .expect_err("path outside the sandbox");
+            assert!(matches!(err, GuestError::PermissionDenied), "{path}");
+            assert!(denied.next().await.is_none());
+        }
+
+        fs::remove_dir_all(&dir).expect("remove sandbox dir");
+    }
+}
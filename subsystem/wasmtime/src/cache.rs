@@ -0,0 +1,215 @@
+//! On-disk cache of precompiled (`.cwasm`) modules.
+//!
+//! Deserialising a precompiled module runs the native code it holds, so an entry is only trusted
+//! if it carries a tag computed under the cache's secret key. Anything else found in the cache
+//! directory, written by another process or copied from elsewhere, is discarded and recompiled.
+
+use std::{
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use tracing::{debug, warn};
+use wasmtime::{Engine, Module};
+
+use crate::Error;
+
+const CACHE_EXTENSION: &str = "cwasm";
+/// Length of the tag each entry starts with.
+const TAG_LEN: usize = blake3::OUT_LEN;
+
+/// Directory of serialised modules keyed by content digest and engine compatibility.
+///
+/// Each entry is a keyed BLAKE3 tag over its digest and artefact, followed by the artefact.
+/// Cache failures are never fatal: an unreadable, stale or unauthenticated entry falls back to
+/// compiling the module from source, and a failed write only costs the next spawn another
+/// compilation.
+pub struct ModuleCache {
+    dir: PathBuf,
+    key: [u8; blake3::KEY_LEN],
+}
+
+/// Adapter that feeds [`Hash`] implementations into a BLAKE3 digest.
+struct DigestHasher(blake3::Hasher);
+
+impl ModuleCache {
+    /// Create a cache rooted at `dir` whose entries are authenticated with `key`; the directory
+    /// is created on first write. The key must stay secret from anyone able to write `dir`.
+    pub fn new(dir: impl Into<PathBuf>, key: [u8; blake3::KEY_LEN]) -> Self {
+        Self {
+            dir: dir.into(),
+            key,
+        }
+    }
+
+    /// Directory holding the cached artefacts.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load the precompiled form of `bytes`, compiling and caching it on a miss.
+    pub fn load_or_compile(&self, engine: &Engine, bytes: &[u8]) -> Result<Module, Error> {
+        let digest = self.digest(engine, bytes);
+        let path = self.entry_path(&digest);
+
+        // Read the entry once and deserialise the bytes that were checked, so the file cannot
+        // be swapped in between.
+        if let Ok(entry) = fs::read(&path) {
+            match self.authenticate(&digest, &entry) {
+                Some(artefact) => {
+                    // SAFETY: the tag proves `artefact` was written by `store` under this cache's
+                    // key, from `Module::serialize` output for this digest, which covers the
+                    // engine's compatibility hash.
+                    match unsafe { Module::deserialize(engine, artefact) } {
+                        Ok(module) => {
+                            debug!(path = %path.display(), "loaded precompiled module");
+                            return Ok(module);
+                        }
+                        Err(err) => {
+                            warn!(path = %path.display(), %err, "discarding unusable precompiled module")
+                        }
+                    }
+                }
+                None => {
+                    warn!(path = %path.display(), "discarding unauthenticated precompiled module")
+                }
+            }
+        }
+
+        let module = Module::from_binary(engine, bytes)?;
+        if let Err(err) = self.store(&digest, &path, &module) {
+            warn!(path = %path.display(), %err, "failed to cache precompiled module");
+        }
+        Ok(module)
+    }
+
+    fn digest(&self, engine: &Engine, bytes: &[u8]) -> blake3::Hash {
+        let mut hasher = DigestHasher(blake3::Hasher::new());
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        hasher.0.update(bytes);
+        hasher.0.finalize()
+    }
+
+    fn entry_path(&self, digest: &blake3::Hash) -> PathBuf {
+        self.dir
+            .join(digest.to_hex().as_str())
+            .with_extension(CACHE_EXTENSION)
+    }
+
+    /// Tag binding `artefact` to the entry for `digest`, so an authentic entry cannot be
+    /// replayed under another module's name.
+    fn tag(&self, digest: &blake3::Hash, artefact: &[u8]) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        hasher.update(digest.as_bytes());
+        hasher.update(artefact);
+        hasher.finalize()
+    }
+
+    /// The artefact of `entry` if its tag is valid for `digest`.
+    fn authenticate<'a>(&self, digest: &blake3::Hash, entry: &'a [u8]) -> Option<&'a [u8]> {
+        let (tag, artefact) = entry.split_at_checked(TAG_LEN)?;
+        let tag = blake3::Hash::from_bytes(tag.try_into().ok()?);
+        // `Hash` equality is constant time.
+        (tag == self.tag(digest, artefact)).then_some(artefact)
+    }
+
+    /// Write the tagged artefact next to its final path and rename it into place, so concurrent
+    /// spawns never observe a partially written entry.
+    fn store(&self, digest: &blake3::Hash, path: &Path, module: &Module) -> io::Result<()> {
+        let artefact = module.serialize().map_err(io::Error::other)?;
+        let mut entry = Vec::with_capacity(TAG_LEN + artefact.len());
+        entry.extend_from_slice(self.tag(digest, &artefact).as_bytes());
+        entry.extend_from_slice(&artefact);
+        fs::create_dir_all(&self.dir)?;
+        let staging = path.with_extension(format!("{CACHE_EXTENSION}.{}.tmp", std::process::id()));
+        fs::write(&staging, entry)?;
+        fs::rename(&staging, path)
+    }
+}
+
+impl Hasher for DigestHasher {
+    fn finish(&self) -> u64 {
+        let digest = self.0.finalize();
+        let mut prefix = [0; 8];
+        prefix.copy_from_slice(&digest.as_bytes()[..8]);
+        u64::from_le_bytes(prefix)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn cache_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("selium-module-cache-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn modules_are_cached_by_digest() {
+        let dir = cache_dir("digest");
+        let cache = ModuleCache::new(&dir, [1; blake3::KEY_LEN]);
+        let engine = Engine::default();
+
+        cache
+            .load_or_compile(&engine, MODULE)
+            .expect("compile module");
+        let entry = cache.entry_path(&cache.digest(&engine, MODULE));
+        assert!(entry.is_file());
+        cache
+            .load_or_compile(&engine, MODULE)
+            .expect("load cached module");
+
+        fs::remove_dir_all(&dir).expect("remove cache dir");
+    }
+
+    #[test]
+    fn entries_without_a_valid_tag_are_recompiled() {
+        let dir = cache_dir("tag");
+        let cache = ModuleCache::new(&dir, [1; blake3::KEY_LEN]);
+        let engine = Engine::default();
+        let digest = cache.digest(&engine, MODULE);
+        let path = cache.entry_path(&digest);
+
+        // An entry written under another key is not trusted, and is replaced.
+        ModuleCache::new(&dir, [2; blake3::KEY_LEN])
+            .load_or_compile(&engine, MODULE)
+            .expect("compile module");
+        assert!(
+            cache
+                .authenticate(&digest, &fs::read(&path).expect("read"))
+                .is_none()
+        );
+        cache
+            .load_or_compile(&engine, MODULE)
+            .expect("recompile module");
+        assert!(
+            cache
+                .authenticate(&digest, &fs::read(&path).expect("read"))
+                .is_some()
+        );
+
+        // So is a tampered one.
+        let mut entry = fs::read(&path).expect("read");
+        *entry.last_mut().expect("artefact") ^= 1;
+        fs::write(&path, &entry).expect("tamper");
+        assert!(cache.authenticate(&digest, &entry).is_none());
+        cache
+            .load_or_compile(&engine, MODULE)
+            .expect("recompile module");
+        assert!(
+            cache
+                .authenticate(&digest, &fs::read(&path).expect("read"))
+                .is_some()
+        );
+
+        fs::remove_dir_all(&dir).expect("remove cache dir");
+    }
+}
@@ -0,0 +1,287 @@
+//! Instantiation path for guests built against the Wasm component model.
+//!
+//! Component guests import the `selium:host/hostcalls` interface described in `wit/selium.wit`
+//! instead of the per-hostcall `create`/`poll`/`drop` imports used by core modules. Each call
+//! names a hostcall and blocks the calling guest task until the driver resolves, so guests
+//! written with idiomatic component tooling need no hand-rolled async runtime.
+
+use std::{collections::HashMap, future::poll_fn, sync::Arc, task::Poll};
+
+use selium_abi::{
+    AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, AbiValue, CapabilitySet,
+    EntrypointInvocation, ErrorCode,
+};
+use selium_kernel::{
+    KernelError,
+    drivers::process::{EntrypointInvocationExt, ProcessUsage},
+    guest_data::GuestError,
+    operation::{CallState, LinkableOperation},
+    registry::{InstanceRegistry, Registry, ResourceId},
+};
+use wasmtime::component::{Component, HasSelf, Linker, Val};
+
+use crate::{
+    Error, ExecutionLimits, WasmRuntime, completion_value, with_reported_panic, with_trap_reason,
+};
+
+wasmtime::component::bindgen!({
+    path: "wit",
+    world: "guest",
+    imports: { default: async },
+});
+
+use selium::host::hostcalls::{self, DriverError};
+
+/// Component layer and version, following the `\0asm` magic of a component binary.
+const COMPONENT_HEADER: [u8; 4] = [0x0d, 0x00, 0x01, 0x00];
+
+/// Hostcalls a component instance may invoke, keyed by hostcall name.
+struct ComponentHostcalls {
+    operations: HashMap<&'static str, Arc<dyn LinkableOperation>>,
+}
+
+/// Abandons a call's shared state if the guest task waiting on it is torn down first.
+struct AbandonOnDrop(Option<CallState>);
+
+impl hostcalls::Host for InstanceRegistry {
+    async fn linked(&mut self) -> Vec<String> {
+        let mut names = self
+            .extension::<ComponentHostcalls>()
+            .map(|hostcalls| {
+                hostcalls
+                    .operations
+                    .keys()
+                    .map(|name| name.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    async fn call(&mut self, name: String, input: Vec<u8>) -> Result<Vec<u8>, DriverError> {
+        let operation = self
+            .extension::<ComponentHostcalls>()
+            .and_then(|hostcalls| hostcalls.operations.get(name.as_str()).cloned())
+            .ok_or_else(|| DriverError {
+                code: ErrorCode::NotFound.code(),
+                message: format!("hostcall `{name}` is not linked"),
+            })?;
+
+        let state = operation.invoke(self, &input).map_err(|err| {
+            let err = GuestError::from(err);
+            DriverError {
+                code: err.code().code(),
+                message: err.to_string(),
+            }
+        })?;
+
+        wait(state).await.map_err(|err| DriverError {
+            code: err.code().code(),
+            message: err.to_string(),
+        })
+    }
+}
+
+impl AbandonOnDrop {
+    fn complete(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for AbandonOnDrop {
+    fn drop(&mut self) {
+        if let Some(state) = self.0.take() {
+            state.abandon();
+        }
+    }
+}
+
+impl WasmRuntime {
+    /// Compile a component for this runtime's engine.
+    pub fn compile_component(&self, bytes: &[u8]) -> Result<Component, Error> {
+        Ok(Component::from_binary(&self.engine, bytes)?)
+    }
+
+    /// Instantiate a component guest and spawn its entrypoint, mirroring [`WasmRuntime::run`].
+    ///
+    /// The entrypoint is a top-level component export whose parameters and results correspond
+    /// to the invocation's signature: scalars map to the matching WIT integer or float type and
+    /// buffers to `list<u8>`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run_component(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        component: Component,
+        name: &str,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+        limits: ExecutionLimits,
+    ) -> Result<(), Error> {
+        let mut linker = Linker::new(&self.engine);
+        hostcalls::add_to_linker::<_, HasSelf<InstanceRegistry>>(&mut linker, |registry| registry)?;
+
+        let operations = self
+            .operations_for(capabilities)?
+            .into_iter()
+            .map(|op| (op.module(), op))
+            .collect();
+        let mut store = self.new_store(registry)?;
+        let fuel = self.assign_process(&mut store, None, process_id, capabilities, limits)?;
+        store
+            .data_mut()
+            .insert_extension(ComponentHostcalls { operations })
+            .map_err(KernelError::from)?;
+
+        let instance = linker.instantiate_async(&mut store, &component).await?;
+        let func = instance.get_func(&mut store, name).ok_or_else(|| {
+            Error::Wasmtime(wasmtime::Error::msg(format!(
+                "entrypoint `{name}` not found"
+            )))
+        })?;
+
+        let signature = entrypoint.signature().clone();
+        let params = entrypoint
+            .materialise_values(store.data_mut())?
+            .iter()
+            .map(abi_to_val)
+            .collect::<Vec<_>>();
+        let expected_results = signature.results().len();
+        let actual_results = func.ty(&store).results().len();
+        if actual_results != expected_results {
+            return Err(Error::Kernel(KernelError::Driver(format!(
+                "entrypoint `{name}` returns {actual_results} results, expected {expected_results}"
+            ))));
+        }
+
+        let crash_reports = self.crash_reports.clone();
+        let entrypoint_name = name.to_string();
+        let (start_tx, start_rx) = tokio::sync::oneshot::channel();
+        let handle = tokio::spawn(async move {
+            if start_rx.await.is_err() {
+                return Err(wasmtime::Error::msg("process start cancelled"));
+            }
+
+            let mut results = vec![Val::Bool(false); expected_results];
+            let outcome = func.call_async(&mut store, &params, &mut results).await;
+            if let Some(usage) = store.data().extension::<ProcessUsage>() {
+                usage.record_fuel(fuel.saturating_sub(store.get_fuel()?));
+            }
+            if let Err(err) = outcome {
+                let err = with_reported_panic(store.data(), with_trap_reason(store.data(), err));
+                return Err(match crash_reports {
+                    Some(reports) => reports.report(store.data(), &entrypoint_name, None, err),
+                    None => err,
+                });
+            }
+            func.post_return_async(&mut store).await?;
+            let results = decode_results(&results, &signature)?;
+            Ok(completion_value(store.data(), results))
+        });
+
+        registry
+            .initialise(process_id, handle)
+            .map_err(|err| Error::Kernel(KernelError::from(err)))?;
+
+        start_tx.send(()).map_err(|_| {
+            Error::Kernel(KernelError::Driver("process start cancelled".to_string()))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Whether `bytes` hold a component rather than a core module.
+pub fn is_component(bytes: &[u8]) -> bool {
+    bytes.get(4..8) == Some(&COMPONENT_HEADER)
+}
+
+/// Wait for a call's result, abandoning the call if the waiting task is dropped.
+async fn wait(state: CallState) -> Result<Vec<u8>, GuestError> {
+    let guard = AbandonOnDrop(Some(Arc::clone(&state)));
+    let result = poll_fn(|cx| match state.take_result() {
+        Some(result) => Poll::Ready(result),
+        None => {
+            state.register_waker(cx.waker().clone());
+            Poll::Pending
+        }
+    })
+    .await;
+    guard.complete();
+    result
+}
+
+fn abi_to_val(value: &AbiValue) -> Val {
+    match value {
+        AbiValue::Scalar(scalar) => match *scalar {
+            AbiScalarValue::I8(v) => Val::S8(v),
+            AbiScalarValue::U8(v) => Val::U8(v),
+            AbiScalarValue::I16(v) => Val::S16(v),
+            AbiScalarValue::U16(v) => Val::U16(v),
+            AbiScalarValue::I32(v) => Val::S32(v),
+            AbiScalarValue::U32(v) => Val::U32(v),
+            AbiScalarValue::I64(v) => Val::S64(v),
+            AbiScalarValue::U64(v) => Val::U64(v),
+            AbiScalarValue::F32(v) => Val::Float32(v),
+            AbiScalarValue::F64(v) => Val::Float64(v),
+        },
+        AbiValue::Buffer(bytes) => Val::List(bytes.iter().copied().map(Val::U8).collect()),
+    }
+}
+
+fn decode_results(
+    results: &[Val],
+    signature: &AbiSignature,
+) -> Result<Vec<AbiValue>, wasmtime::Error> {
+    signature
+        .results()
+        .iter()
+        .zip(results)
+        .map(|(param, val)| match (param, val) {
+            (AbiParam::Scalar(kind), val) => val_to_scalar(*kind, val).map(AbiValue::Scalar),
+            (AbiParam::Buffer, Val::List(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Val::U8(byte) => Ok(*byte),
+                    _ => Err(wasmtime::Error::msg("buffer result must be list<u8>")),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(AbiValue::Buffer),
+            (AbiParam::Buffer, _) => Err(wasmtime::Error::msg("buffer result must be list<u8>")),
+        })
+        .collect()
+}
+
+fn val_to_scalar(kind: AbiScalarType, val: &Val) -> Result<AbiScalarValue, wasmtime::Error> {
+    match (kind, val) {
+        (AbiScalarType::I8, Val::S8(v)) => Ok(AbiScalarValue::I8(*v)),
+        (AbiScalarType::U8, Val::U8(v)) => Ok(AbiScalarValue::U8(*v)),
+        (AbiScalarType::I16, Val::S16(v)) => Ok(AbiScalarValue::I16(*v)),
+        (AbiScalarType::U16, Val::U16(v)) => Ok(AbiScalarValue::U16(*v)),
+        (AbiScalarType::I32, Val::S32(v)) => Ok(AbiScalarValue::I32(*v)),
+        (AbiScalarType::U32, Val::U32(v)) => Ok(AbiScalarValue::U32(*v)),
+        (AbiScalarType::I64, Val::S64(v)) => Ok(AbiScalarValue::I64(*v)),
+        (AbiScalarType::U64, Val::U64(v)) => Ok(AbiScalarValue::U64(*v)),
+        (AbiScalarType::F32, Val::Float32(v)) => Ok(AbiScalarValue::F32(*v)),
+        (AbiScalarType::F64, Val::Float64(v)) => Ok(AbiScalarValue::F64(*v)),
+        _ => Err(wasmtime::Error::msg(format!(
+            "entrypoint result {val:?} does not match {kind:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_binaries_are_detected() {
+        let core = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let component = [0x00, 0x61, 0x73, 0x6d, 0x0d, 0x00, 0x01, 0x00];
+
+        assert!(!is_component(&core));
+        assert!(is_component(&component));
+        assert!(!is_component(&[]));
+    }
+}
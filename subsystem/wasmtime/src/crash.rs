@@ -0,0 +1,234 @@
+//! Crash reports for guests whose entrypoint traps.
+//!
+//! A report captures the trap, the Wasm backtrace, the hostcalls the guest was waiting on and
+//! those it completed last, and its memory and fuel usage. The error the process exits with is
+//! annotated with a one-line summary pointing at the report. Reports are cut short at a fixed
+//! size, so a guest that overflows its stack cannot fill the disk with backtrace frames.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use selium_kernel::{
+    drivers::process::ProcessUsage,
+    history::HostcallHistory,
+    registry::{InstanceRegistry, ProcessIdentity},
+};
+use tracing::warn;
+use wasmtime::{Trap, WasmBacktrace};
+
+const REPORT_EXTENSION: &str = "txt";
+/// Longest report written, in bytes; deep recursion is cut short rather than filling the disk
+/// with backtrace frames.
+const MAX_REPORT_LEN: usize = 64 * 1024;
+/// Marker ending a report cut short at [`MAX_REPORT_LEN`].
+const TRUNCATED: &str = "\n[report truncated]\n";
+
+/// Directory crash reports are written to.
+#[derive(Clone, Debug)]
+pub struct CrashReports {
+    dir: PathBuf,
+}
+
+impl CrashReports {
+    /// Write reports under `dir`; the directory is created on the first crash.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory holding the reports.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a report for a guest whose `entrypoint` failed with `err`, returning `err`
+    /// annotated with a summary of the crash. `memory_bytes` is the size of the guest's linear
+    /// memory, where the guest has a single one.
+    pub(crate) fn report(
+        &self,
+        registry: &InstanceRegistry,
+        entrypoint: &str,
+        memory_bytes: Option<usize>,
+        err: wasmtime::Error,
+    ) -> wasmtime::Error {
+        let process_id = registry
+            .extension::<ProcessIdentity>()
+            .map(|identity| identity.raw());
+        let history = registry.extension::<HostcallHistory>();
+        let pending = history.as_ref().map(|history| history.pending().len());
+        // Formatting into a `String` cannot fail.
+        let report = bound(render(registry, entrypoint, memory_bytes, &err).unwrap_or_default());
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        let path = self.dir.join(format!(
+            "{timestamp}-{}.{REPORT_EXTENSION}",
+            process_id.map_or_else(|| "unknown".to_string(), |id| id.to_string())
+        ));
+        let written = fs::create_dir_all(&self.dir).and_then(|()| fs::write(&path, report));
+
+        let mut summary = format!("guest trapped in `{entrypoint}`: {}", cause(&err));
+        if let Some(pending) = pending {
+            summary.push_str(&format!("; {pending} hostcalls pending"));
+        }
+        match written {
+            Ok(()) => summary.push_str(&format!("; crash report at {}", path.display())),
+            Err(io) => {
+                warn!(path = %path.display(), err = %io, "failed to write crash report");
+            }
+        }
+        warn!(process_id, "{summary}");
+
+        err.context(summary)
+    }
+}
+
+/// Trap code if the failure was a trap, otherwise the root cause.
+fn cause(err: &wasmtime::Error) -> String {
+    match err.downcast_ref::<Trap>() {
+        Some(trap) => trap.to_string(),
+        None => err.root_cause().to_string(),
+    }
+}
+
+/// `report`, cut to at most [`MAX_REPORT_LEN`] bytes.
+fn bound(mut report: String) -> String {
+    if report.len() <= MAX_REPORT_LEN {
+        return report;
+    }
+    let mut end = MAX_REPORT_LEN - TRUNCATED.len();
+    while !report.is_char_boundary(end) {
+        end -= 1;
+    }
+    report.truncate(end);
+    report.push_str(TRUNCATED);
+    report
+}
+
+fn render(
+    registry: &InstanceRegistry,
+    entrypoint: &str,
+    memory_bytes: Option<usize>,
+    err: &wasmtime::Error,
+) -> Result<String, std::fmt::Error> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "process: {}",
+        registry
+            .extension::<ProcessIdentity>()
+            .map_or_else(|| "unknown".to_string(), |id| id.raw().to_string())
+    )?;
+    writeln!(out, "entrypoint: {entrypoint}")?;
+    // The cause comes first: the full error embeds the backtrace, which may be cut short.
+    writeln!(out, "cause: {}", cause(err))?;
+    writeln!(out, "error: {err:#}")?;
+    if let Some(usage) = registry.extension::<ProcessUsage>() {
+        writeln!(out, "fuel consumed: {}", usage.fuel_consumed())?;
+    }
+    match memory_bytes {
+        Some(bytes) => writeln!(out, "linear memory: {bytes} bytes")?,
+        None => writeln!(out, "linear memory: unavailable")?,
+    }
+
+    writeln!(out, "\nwasm backtrace:")?;
+    match err.downcast_ref::<WasmBacktrace>() {
+        Some(backtrace) => writeln!(out, "{backtrace}")?,
+        None => writeln!(out, "  unavailable")?,
+    }
+
+    let Some(history) = registry.extension::<HostcallHistory>() else {
+        writeln!(out, "\nhostcall history: not recorded")?;
+        return Ok(out);
+    };
+
+    writeln!(out, "\npending hostcalls:")?;
+    for call in history.pending() {
+        writeln!(out, "  {} (running for {:?})", call.module, call.elapsed)?;
+    }
+    writeln!(out, "\nrecent hostcalls (oldest first):")?;
+    for call in history.recent() {
+        match call.outcome {
+            Ok(len) => writeln!(
+                out,
+                "  {} took {:?}: ok, {len} bytes",
+                call.module, call.elapsed
+            )?,
+            Err(code) => writeln!(out, "  {} took {:?}: {code}", call.module, call.elapsed)?,
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_kernel::registry::Registry;
+    use wasmtime::{Engine, Instance, Module, Store};
+
+    use super::*;
+
+    #[test]
+    fn trapping_guests_get_a_bounded_report_with_the_trap_and_backtrace() {
+        // Exports `trap`, which aborts straight away, and `recurse`, which calls itself until
+        // the stack overflows.
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x03, 0x02, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x07, 0x12, 0x02, 0x04]);
+        bytes.extend_from_slice(b"trap");
+        bytes.extend_from_slice(&[0x00, 0x00, 0x07]);
+        bytes.extend_from_slice(b"recurse");
+        bytes.extend_from_slice(&[0x00, 0x01]);
+        bytes.extend_from_slice(&[0x0a, 0x0a, 0x02]);
+        bytes.extend_from_slice(&[0x03, 0x00, 0x00, 0x0b]);
+        bytes.extend_from_slice(&[0x04, 0x00, 0x10, 0x01, 0x0b]);
+
+        let engine = Engine::default();
+        let module = Module::new(&engine, bytes).expect("valid module");
+        let registry = Registry::new();
+        let mut store = Store::new(&engine, registry.instance().expect("instance registry"));
+        let instance = Instance::new(&mut store, &module, &[]).expect("instance");
+        let dir = std::env::temp_dir().join(format!("selium-crash-{}", std::process::id()));
+        let reports = CrashReports::new(&dir);
+
+        let mut crash = |name: &str, trap: Trap| {
+            let err = instance
+                .get_typed_func::<(), ()>(&mut store, name)
+                .expect("export")
+                .call(&mut store, ())
+                .expect_err("trap");
+            let err = reports.report(store.data(), name, None, err);
+            assert!(err.to_string().contains(&trap.to_string()), "{err}");
+
+            let path = fs::read_dir(&dir)
+                .expect("reports")
+                .map(|entry| entry.expect("report").path())
+                .next()
+                .expect("one report");
+            let report = fs::read_to_string(&path).expect("read report");
+            fs::remove_file(path).expect("remove report");
+            assert!(report.contains(&format!("entrypoint: {name}")));
+            assert!(report.contains(&format!("cause: {trap}")), "{report}");
+            report
+        };
+
+        let report = crash("trap", Trap::UnreachableCodeReached);
+        let backtrace = report
+            .split_once("wasm backtrace:\n")
+            .expect("backtrace section")
+            .1;
+        assert!(backtrace.trim_start().starts_with("0:"), "{report}");
+
+        let report = crash("recurse", Trap::StackOverflow);
+        assert!(report.len() <= MAX_REPORT_LEN);
+        assert!(report.ends_with(TRUNCATED));
+
+        fs::remove_dir_all(&dir).expect("remove report dir");
+    }
+}
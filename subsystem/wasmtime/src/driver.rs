@@ -1,22 +1,25 @@
 use std::sync::Arc;
 
-use selium_abi::{AbiValue, EntrypointInvocation};
+use selium_abi::{CapabilitySet, EntrypointInvocation, ErrorCode, TrapReason};
 use selium_kernel::{
-    drivers::{
-        Capability, module_store::ModuleStoreReadCapability, process::ProcessLifecycleCapability,
-    },
+    KernelError,
+    drivers::{module_store::ModuleStoreReadCapability, process::ProcessLifecycleCapability},
     guest_data::GuestError,
-    registry::{Registry, ResourceId},
+    registry::{MODULE_TAG_PREFIX, Registry, ResourceId},
 };
 use tokio::task::JoinHandle;
-use wasmtime::Module;
+use tracing::{debug, warn};
 
-use crate::{Error, WasmRuntime};
+use crate::{
+    Error, ExecutionLimits, ProcessHandle, WasmRuntime, is_component,
+    prewarm::{PoolKey, PrewarmPool},
+};
 
 #[derive(Clone)]
 pub struct WasmtimeDriver {
     runtime: Arc<WasmRuntime>,
     store: Arc<dyn ModuleStoreReadCapability + Send + Sync>,
+    prewarmed: Arc<PrewarmPool>,
 }
 
 impl WasmtimeDriver {
@@ -24,12 +27,165 @@ impl WasmtimeDriver {
         runtime: Arc<WasmRuntime>,
         store: Arc<dyn ModuleStoreReadCapability + Send + Sync>,
     ) -> Arc<Self> {
-        Arc::new(Self { runtime, store })
+        Arc::new(Self {
+            runtime,
+            store,
+            prewarmed: Arc::default(),
+        })
+    }
+
+    /// The runtime processes are started on.
+    pub fn runtime(&self) -> &Arc<WasmRuntime> {
+        &self.runtime
+    }
+
+    /// Keep `count` instances of `module_id`, linked for `capabilities`, instantiated ahead of
+    /// time. Starting the module with exactly those capabilities then skips compilation,
+    /// linking and instantiation. Pools refill in the background as instances are taken.
+    pub async fn prewarm(
+        &self,
+        registry: &Arc<Registry>,
+        module_id: &str,
+        capabilities: CapabilitySet,
+        count: usize,
+    ) -> Result<(), Error> {
+        if is_component(&self.store.read(module_id)?) {
+            return Err(Error::Kernel(KernelError::Driver(format!(
+                "component `{module_id}` cannot be pre-warmed"
+            ))));
+        }
+
+        let key = PoolKey::new(module_id, capabilities);
+        self.prewarmed.designate(&key, registry, count)?;
+        self.refill(&key).await
+    }
+
+    /// Start a process like [`ProcessLifecycleCapability::start`], under explicit limits.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn start_with_limits(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        module_id: &str,
+        name: &str,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+        limits: ExecutionLimits,
+    ) -> Result<(), Error> {
+        tag_module(registry, process_id, module_id)?;
+        let bytes = self.store.read(module_id)?;
+        if is_component(&bytes) {
+            let component = self.runtime.compile_component(&bytes)?;
+            return self
+                .runtime
+                .run_component(
+                    registry,
+                    process_id,
+                    component,
+                    name,
+                    capabilities,
+                    entrypoint,
+                    limits,
+                )
+                .await;
+        }
+
+        let key = PoolKey::new(module_id, capabilities);
+        if let Some(warm) = self.prewarmed.take(&key, registry, blake3::hash(&bytes))? {
+            debug!(module_id, process_id, "starting pre-warmed instance");
+            self.runtime.start_instance(
+                registry,
+                process_id,
+                warm,
+                name,
+                capabilities,
+                entrypoint,
+                limits,
+            )?;
+            self.spawn_refill(key)?;
+            return Ok(());
+        }
+        self.spawn_refill(key)?;
+
+        let module = self.runtime.compile(&bytes)?;
+        self.runtime
+            .run(
+                registry,
+                process_id,
+                module,
+                name,
+                capabilities,
+                entrypoint,
+                limits,
+            )
+            .await
+    }
+
+    /// Start `module_id` as a plugin: bound to `process_id` with no entrypoint running, for the
+    /// host to call its exports through the returned [`ProcessHandle`].
+    pub async fn start_plugin(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        module_id: &str,
+        capabilities: CapabilitySet,
+        limits: ExecutionLimits,
+    ) -> Result<ProcessHandle, Error> {
+        tag_module(registry, process_id, module_id)?;
+        let bytes = self.store.read(module_id)?;
+        if is_component(&bytes) {
+            return Err(Error::Kernel(KernelError::Driver(format!(
+                "component `{module_id}` cannot be started as a plugin"
+            ))));
+        }
+
+        let module = self.runtime.compile(&bytes)?;
+        self.runtime
+            .start_plugin(registry, process_id, &module, capabilities, limits)
+            .await
+    }
+
+    /// Top up the pool for `key` in the background if it is below its target.
+    fn spawn_refill(&self, key: PoolKey) -> Result<(), Error> {
+        if self.prewarmed.wanted(&key)?.is_none() {
+            return Ok(());
+        }
+
+        let driver = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = driver.refill(&key).await {
+                warn!(
+                    module_id = key.module_id(),
+                    err = err.to_string(),
+                    "failed to refill pre-warmed instances"
+                );
+            }
+        });
+        Ok(())
+    }
+
+    /// Instantiate the current version of the pool's module until the pool reaches its target.
+    async fn refill(&self, key: &PoolKey) -> Result<(), Error> {
+        if self.prewarmed.wanted(key)?.is_none() {
+            return Ok(());
+        }
+
+        let bytes = self.store.read(key.module_id())?;
+        let digest = blake3::hash(&bytes);
+        let module = self.runtime.compile(&bytes)?;
+        while let Some(registry) = self.prewarmed.wanted(key)? {
+            let warm = self
+                .runtime
+                .instantiate(&registry, &module, key.capabilities())
+                .await?;
+            self.prewarmed.put(key, digest, warm)?;
+        }
+        Ok(())
     }
 }
 
 impl ProcessLifecycleCapability for WasmtimeDriver {
-    type Process = JoinHandle<Result<Vec<AbiValue>, wasmtime::Error>>;
+    type Process = JoinHandle<Result<Vec<u8>, wasmtime::Error>>;
     type Error = Error;
 
     fn start(
@@ -38,23 +194,21 @@ impl ProcessLifecycleCapability for WasmtimeDriver {
         process_id: ResourceId,
         module_id: &str,
         name: &str,
-        capabilities: Vec<Capability>,
+        capabilities: CapabilitySet,
         entrypoint: EntrypointInvocation,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         let inner = self.clone();
 
         async move {
-            let bytes = inner.store.read(module_id)?;
-            let module = Module::from_binary(&inner.runtime.engine, &bytes)?;
             inner
-                .runtime
-                .run(
+                .start_with_limits(
                     registry,
                     process_id,
-                    module,
+                    module_id,
                     name,
-                    &capabilities,
+                    capabilities,
                     entrypoint,
+                    ExecutionLimits::default(),
                 )
                 .await
         }
@@ -64,10 +218,27 @@ impl ProcessLifecycleCapability for WasmtimeDriver {
         instance.abort();
         Ok(())
     }
+
+    async fn wait(&self, instance: Self::Process) -> Result<Vec<u8>, Self::Error> {
+        instance.await?.map_err(Error::ProcessFailed)
+    }
 }
 
 impl From<Error> for GuestError {
     fn from(value: Error) -> Self {
-        Self::Subsystem(value.to_string())
+        match &value {
+            Error::ProcessFailed(err) => match err.downcast_ref::<TrapReason>() {
+                Some(reason) => Self::Coded(ErrorCode::from(*reason), value.to_string()),
+                None => Self::Subsystem(value.to_string()),
+            },
+            _ => Self::Subsystem(value.to_string()),
+        }
     }
 }
+
+/// Tag `process_id` with the module it runs, for diagnostics to attribute it and its futures.
+fn tag_module(registry: &Registry, process_id: ResourceId, module_id: &str) -> Result<(), Error> {
+    registry
+        .tag(process_id, [format!("{MODULE_TAG_PREFIX}{module_id}")])
+        .map_err(|err| Error::Kernel(KernelError::from(err)))
+}
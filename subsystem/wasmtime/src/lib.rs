@@ -3,37 +3,128 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
+    thread,
+    time::Duration,
 };
 
 use selium_abi::EntrypointInvocation;
 use selium_abi::{
     self, AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, AbiValue, CallPlan, CallPlanError,
-    hostcalls,
+    CapabilitySet, HostInfo, TrapReason, hostcalls,
 };
 use selium_kernel::{
     KernelError,
-    drivers::{Capability, module_store::ModuleStoreError, process::EntrypointInvocationExt},
+    drivers::{
+        Capability,
+        meta::{
+            self, DiagnosticsDriver, EncodingDriver, GrantedCapabilities, HostInfoDriver,
+            HostcallsDriver, IdempotencyKeyDriver, ProcessReadiness, ReadyDriver,
+        },
+        module_store::ModuleStoreError,
+        process::{
+            self, EntrypointInvocationExt, ProcessCompleteDriver, ProcessCompletion, ProcessInbox,
+            ProcessPanicked, ProcessReceiveDriver, ProcessReportPanicDriver,
+            ProcessSelfStatsDriver, ProcessUsage,
+        },
+    },
     futures::FutureSharedState,
     guest_async::GuestAsync,
-    guest_data::{GuestError, GuestInt, GuestUint, write_poll_result},
+    guest_data::{AddressWidth, GuestAddress, GuestError, GuestUint, write_poll_result},
+    history::HostcallHistory,
     mailbox,
-    operation::LinkableOperation,
+    operation::{CallState, HostcallContext, LinkableOperation, Operation},
+    profile::GuestProfiler,
     registry::{InstanceRegistry, ProcessIdentity, Registry, ResourceId},
 };
 use thiserror::Error;
-use tracing::{debug, warn};
-use wasmtime::{Caller, Config, Engine, Func, Linker, Memory, Module, Store, Val, ValType};
+use tracing::{Instrument, debug, warn};
+use wasmtime::{
+    Caller, Config, Engine, EngineWeak, Func, Instance, InstanceAllocationStrategy, Linker, Memory,
+    Module, PoolingAllocationConfig, Store, Trap, UpdateDeadline, Val, ValType, WasmBacktrace,
+    WasmTy,
+};
 
+mod cache;
+mod component;
+mod crash;
 mod driver;
+mod modules;
+mod plugin;
+mod prewarm;
+pub use cache::ModuleCache;
+pub use component::is_component;
+pub use crash::CrashReports;
 pub use driver::WasmtimeDriver;
+use modules::CompiledModules;
+pub use modules::ModuleStats;
+pub use plugin::ProcessHandle;
+use prewarm::WarmInstance;
+
+/// Linkers keyed by the capabilities they grant and the address width they link for.
+type LinkerCache = HashMap<(CapabilitySet, AddressWidth), Arc<Linker<InstanceRegistry>>>;
 
 pub struct WasmRuntime {
     engine: Engine,
     available_caps: RwLock<HashMap<Capability, Vec<Arc<dyn LinkableOperation>>>>,
+    /// Linkers already built, shared by every instance granted the same capabilities at the
+    /// same address width. Cleared whenever a capability is extended.
+    linkers: RwLock<LinkerCache>,
     guest_async: Arc<GuestAsync>,
+    meta_hostcalls: Arc<Operation<HostcallsDriver>>,
+    meta_idempotency_key: Arc<Operation<IdempotencyKeyDriver>>,
+    meta_ready: Arc<Operation<ReadyDriver>>,
+    meta_diagnostics: Arc<Operation<DiagnosticsDriver>>,
+    meta_encoding: Arc<Operation<EncodingDriver>>,
+    meta_host_info: Arc<Operation<HostInfoDriver>>,
+    process_complete: Arc<Operation<ProcessCompleteDriver>>,
+    process_receive: Arc<Operation<ProcessReceiveDriver>>,
+    process_self_stats: Arc<Operation<ProcessSelfStatsDriver>>,
+    process_report_panic: Arc<Operation<ProcessReportPanicDriver>>,
+    modules: CompiledModules,
+    module_cache: Option<ModuleCache>,
+    crash_reports: Option<CrashReports>,
 }
 
 const PREALLOC_PAGES: u64 = 256;
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+/// Interval at which the engine epoch advances. Every guest yields to the async executor at
+/// least this often, so a guest spinning in a loop cannot monopolise a runtime thread, and a
+/// guest being profiled is sampled at this rate.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Sizing of Wasmtime's pooling instance allocator.
+///
+/// Pooling preallocates instance slots up front, trading reserved address space for much
+/// cheaper instantiation when short-lived processes are spawned at a high rate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PoolingLimits {
+    /// Maximum number of concurrently live instances.
+    pub max_instances: u32,
+    /// Maximum size of each instance's linear memory, in 64 KiB Wasm pages.
+    pub max_memory_pages: u64,
+}
+
+/// Per-process execution limits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExecutionLimits {
+    /// Total Wasm fuel the process may consume before it traps; `None` is unlimited.
+    pub fuel: Option<u64>,
+}
+
+/// Imports of the module an instance was instantiated from, as `(module, name)` pairs.
+///
+/// Attached as an instance extension to every core module instance, so that the host can
+/// report which hostcalls a running guest links.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModuleImports(Vec<(String, String)>);
+
+/// An export checked against the signature it is invoked with, and the values to call it with.
+struct PreparedCall {
+    func: Func,
+    params: Vec<Val>,
+    results: Vec<Val>,
+    signature: AbiSignature,
+}
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -45,8 +136,22 @@ pub enum Error {
     ModuleStore(#[from] ModuleStoreError),
     #[error("Wasmtime error: {0}")]
     Wasmtime(#[from] wasmtime::Error),
+    #[error("Payload encoding error: {0}")]
+    Payload(#[from] selium_abi::RkyvError),
     #[error("The lock guarding the Capability registry has been poisoned")]
     CapabilityRegistryPoisoned,
+    #[error("The lock guarding the linker cache has been poisoned")]
+    LinkerCachePoisoned,
+    #[error("The lock guarding the compiled module table has been poisoned")]
+    ModuleTablePoisoned,
+    #[error("The lock guarding the pre-warmed instance pools has been poisoned")]
+    PrewarmPoolPoisoned,
+    #[error("Failed to start the epoch ticker: {0}")]
+    EpochTicker(std::io::Error),
+    #[error("Process task did not complete: {0}")]
+    ProcessTask(#[from] tokio::task::JoinError),
+    #[error("Process failed: {0:#}")]
+    ProcessFailed(wasmtime::Error),
 }
 
 impl From<CallPlanError> for Error {
@@ -55,22 +160,134 @@ impl From<CallPlanError> for Error {
     }
 }
 
+impl ModuleImports {
+    /// Record the imports of `module`.
+    pub fn of(module: &Module) -> Self {
+        Self(
+            module
+                .imports()
+                .map(|import| (import.module().to_string(), import.name().to_string()))
+                .collect(),
+        )
+    }
+
+    /// The imports, as `(module, name)` pairs in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(module, name)| (module.as_str(), name.as_str()))
+    }
+}
+
+impl PoolingLimits {
+    fn allocation_config(&self) -> Result<PoolingAllocationConfig, Error> {
+        let max_memory_size = self
+            .max_memory_pages
+            .checked_mul(WASM_PAGE_SIZE)
+            .and_then(|bytes| usize::try_from(bytes).ok())
+            .ok_or(Error::Kernel(KernelError::MemoryCapacity))?;
+
+        let mut config = PoolingAllocationConfig::default();
+        config
+            .total_core_instances(self.max_instances)
+            .total_memories(self.max_instances)
+            .total_tables(self.max_instances)
+            .total_stacks(self.max_instances)
+            .max_memory_size(max_memory_size);
+        Ok(config)
+    }
+}
+
 impl WasmRuntime {
+    /// Create a runtime. Instances are allocated on demand unless `pooling` is given.
     pub fn new(
         available_caps: HashMap<Capability, Vec<Arc<dyn LinkableOperation>>>,
         guest_async: Arc<GuestAsync>,
+        pooling: Option<PoolingLimits>,
     ) -> Result<Self, Error> {
         let mut config = Config::new();
         config.async_support(true);
         config.memory_may_move(false);
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        config.wasm_memory64(true);
+        if let Some(limits) = pooling {
+            config.allocation_strategy(InstanceAllocationStrategy::Pooling(
+                limits.allocation_config()?,
+            ));
+        }
+
+        let engine = Engine::new(&config)?;
+        spawn_epoch_ticker(engine.weak())?;
 
         Ok(Self {
-            engine: Engine::new(&config)?,
+            engine,
             available_caps: RwLock::new(available_caps),
+            linkers: RwLock::new(HashMap::new()),
             guest_async,
+            meta_hostcalls: meta::operation(),
+            meta_idempotency_key: meta::idempotency_key_operation(),
+            meta_ready: meta::ready_operation(),
+            meta_diagnostics: meta::diagnostics_operation(),
+            meta_encoding: meta::encoding_operation(),
+            meta_host_info: meta::host_info_operation(meta::detect_host_info()),
+            process_complete: process::complete_operation(),
+            process_receive: process::receive_operation(),
+            process_self_stats: process::self_stats_operation(),
+            process_report_panic: process::report_panic_operation(),
+            modules: CompiledModules::default(),
+            module_cache: None,
+            crash_reports: None,
         })
     }
 
+    /// Reuse precompiled modules from `cache` instead of compiling every spawn from source.
+    pub fn with_module_cache(mut self, cache: ModuleCache) -> Self {
+        self.module_cache = Some(cache);
+        self
+    }
+
+    /// Write a crash report to `reports` whenever a guest entrypoint traps.
+    pub fn with_crash_reports(mut self, reports: CrashReports) -> Self {
+        self.crash_reports = Some(reports);
+        self
+    }
+
+    /// Report `info` to guests asking about the host, instead of the details detected from it,
+    /// for example to withhold its hostname.
+    pub fn with_host_info(mut self, info: HostInfo) -> Self {
+        self.meta_host_info = meta::host_info_operation(info);
+        self
+    }
+
+    /// Compile `bytes` for this runtime's engine. Processes started from the same bytes share
+    /// one compiled module; the first is loaded from the module cache if configured.
+    pub fn compile(&self, bytes: &[u8]) -> Result<Module, Error> {
+        self.modules
+            .get_or_compile(bytes, || match &self.module_cache {
+                Some(cache) => cache.load_or_compile(&self.engine, bytes),
+                None => Ok(Module::from_binary(&self.engine, bytes)?),
+            })
+    }
+
+    /// How often spawns reused an already compiled module rather than compiling their own.
+    pub fn module_stats(&self) -> ModuleStats {
+        self.modules.stats()
+    }
+
+    /// The capability operation linked under the hostcall import module `module`, if any.
+    pub fn operation(&self, module: &str) -> Result<Option<Arc<dyn LinkableOperation>>, Error> {
+        let map = self
+            .available_caps
+            .read()
+            .map_err(|_| Error::CapabilityRegistryPoisoned)?;
+        Ok(map
+            .values()
+            .flatten()
+            .find(|operation| operation.module() == module)
+            .cloned())
+    }
+
     pub fn extend_capability(
         &self,
         capability: Capability,
@@ -82,49 +299,119 @@ impl WasmRuntime {
             .map_err(|_| Error::CapabilityRegistryPoisoned)?;
         let entry = map.entry(capability).or_default();
         entry.extend(operations);
+        self.linkers
+            .write()
+            .map_err(|_| Error::LinkerCachePoisoned)?
+            .clear();
         Ok(())
     }
 
-    pub async fn run(
+    /// Operations linked for a guest granted `capabilities`: every operation of each granted
+    /// capability, stubs for the hostcalls of every other capability, and the meta hostcalls.
+    fn operations_for(
         &self,
-        registry: &Arc<Registry>,
-        process_id: ResourceId,
-        module: Module,
-        name: &str,
-        capabilities: &[Capability],
-        entrypoint: EntrypointInvocation,
-    ) -> Result<(), Error> {
-        let mut linker = Linker::new(&self.engine);
-        let operations_to_link = {
-            let map = self
-                .available_caps
-                .read()
-                .map_err(|_| Error::CapabilityRegistryPoisoned)?;
-            let mut ops = Vec::new();
-            let requested: HashSet<Capability> = capabilities.iter().copied().collect();
-            for capability in &requested {
-                let operations = map
-                    .get(capability)
-                    .ok_or(Error::CapabilityUnavailable(*capability))?;
-
-                if operations.is_empty() {
-                    return Err(Error::CapabilityUnavailable(*capability));
-                }
+        capabilities: CapabilitySet,
+    ) -> Result<Vec<Arc<dyn LinkableOperation>>, Error> {
+        let map = self
+            .available_caps
+            .read()
+            .map_err(|_| Error::CapabilityRegistryPoisoned)?;
+        self.operations_in(&map, capabilities)
+    }
 
-                ops.extend(operations.iter().cloned());
+    /// [`WasmRuntime::operations_for`], drawing on the capability operations in `map`.
+    fn operations_in(
+        &self,
+        map: &HashMap<Capability, Vec<Arc<dyn LinkableOperation>>>,
+        capabilities: CapabilitySet,
+    ) -> Result<Vec<Arc<dyn LinkableOperation>>, Error> {
+        let mut ops = Vec::new();
+        for capability in capabilities {
+            let operations = map
+                .get(&capability)
+                .ok_or(Error::CapabilityUnavailable(capability))?;
+
+            if operations.is_empty() {
+                return Err(Error::CapabilityUnavailable(capability));
             }
-            ops.extend(stub_operations_for_missing(&requested));
-            ops
-        };
 
-        for op in operations_to_link {
-            op.link(&mut linker)?;
+            ops.extend(operations.iter().cloned());
         }
+        ops.extend(stub_operations_for_missing(capabilities));
+        ops.extend(stub_uncatalogued_operations(map, capabilities));
+        ops.push(self.meta_hostcalls.as_linkable());
+        ops.push(self.meta_idempotency_key.as_linkable());
+        ops.push(self.meta_ready.as_linkable());
+        ops.push(self.meta_diagnostics.as_linkable());
+        ops.push(self.meta_encoding.as_linkable());
+        ops.push(self.meta_host_info.as_linkable());
+        ops.push(self.process_complete.as_linkable());
+        ops.push(self.process_receive.as_linkable());
+        ops.push(self.process_self_stats.as_linkable());
+        ops.push(self.process_report_panic.as_linkable());
+        Ok(ops)
+    }
 
+    /// The linker for guests granted `capabilities` whose hostcalls pass addresses of `width`,
+    /// built on first use and reused for every later instance.
+    fn linker_for(
+        &self,
+        capabilities: CapabilitySet,
+        width: AddressWidth,
+    ) -> Result<Arc<Linker<InstanceRegistry>>, Error> {
+        // Held until the linker is cached, so a capability extended meanwhile cannot leave a
+        // stale linker behind.
+        let map = self
+            .available_caps
+            .read()
+            .map_err(|_| Error::CapabilityRegistryPoisoned)?;
+        let key = (capabilities, width);
+        if let Some(linker) = self
+            .linkers
+            .read()
+            .map_err(|_| Error::LinkerCachePoisoned)?
+            .get(&key)
+        {
+            return Ok(Arc::clone(linker));
+        }
+
+        let mut linker = Linker::new(&self.engine);
+        for op in self.operations_in(&map, capabilities)? {
+            op.link(&mut linker, width)?;
+        }
         self.guest_async.link(&mut linker)?;
 
+        let linker = Arc::new(linker);
+        self.linkers
+            .write()
+            .map_err(|_| Error::LinkerCachePoisoned)?
+            .insert(key, Arc::clone(&linker));
+        Ok(linker)
+    }
+
+    /// Create a store for an instance not yet bound to a process. Fuel is unlimited and the
+    /// guest yields on every epoch tick until [`WasmRuntime::assign_process`] applies limits.
+    fn new_store(&self, registry: &Arc<Registry>) -> Result<Store<InstanceRegistry>, Error> {
         let instance_registry = registry.instance().map_err(KernelError::from)?;
         let mut store = Store::new(&self.engine, instance_registry);
+        store.limiter(|registry| registry);
+        store.set_fuel(u64::MAX)?;
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(|_| Ok(UpdateDeadline::Yield(1)));
+        Ok(store)
+    }
+
+    /// Bind a store to its process: install the instance extensions and configure fuel and
+    /// epoch preemption, sampling the size of the guest's `memory` on each epoch tick, and its
+    /// stack too while it is being profiled. Returns the fuel the process starts with.
+    fn assign_process(
+        &self,
+        store: &mut Store<InstanceRegistry>,
+        memory: Option<Memory>,
+        process_id: ResourceId,
+        capabilities: CapabilitySet,
+        limits: ExecutionLimits,
+    ) -> Result<u64, Error> {
         store
             .data_mut()
             .set_process_id(process_id)
@@ -134,11 +421,89 @@ impl WasmRuntime {
             .data_mut()
             .insert_extension(identity)
             .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(GrantedCapabilities::new(capabilities))
+            .map_err(KernelError::from)?;
+        let context =
+            HostcallContext::for_process(store.data().registry(), process_id, capabilities)
+                .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(context)
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(ProcessUsage::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(ProcessReadiness::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(ProcessCompletion::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(ProcessInbox::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(ProcessPanicked::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(HostcallHistory::default())
+            .map_err(KernelError::from)?;
+        store
+            .data_mut()
+            .insert_extension(GuestProfiler::default())
+            .map_err(KernelError::from)?;
+        let usage = store
+            .data()
+            .extension::<ProcessUsage>()
+            .ok_or(KernelError::Driver("process usage missing".to_string()))?;
+        let profiler = store
+            .data()
+            .extension::<GuestProfiler>()
+            .ok_or(KernelError::Driver("guest profiler missing".to_string()))?;
+        if let Some(memory) = memory {
+            usage.record_memory(memory.data_size(&*store));
+        }
+        let fuel = limits.fuel.unwrap_or(u64::MAX);
+        store.set_fuel(fuel)?;
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(move |store| {
+            usage.record_fuel(fuel.saturating_sub(store.get_fuel()?));
+            if let Some(memory) = memory {
+                usage.record_memory(memory.data_size(&store));
+            }
+            if profiler.is_active() {
+                profiler.record(stack_frames(&WasmBacktrace::capture(&store)));
+            }
+            Ok(UpdateDeadline::Yield(1))
+        });
+
+        Ok(fuel)
+    }
+
+    /// Link and instantiate `module` for a guest granted `capabilities`, without binding it to
+    /// a process or calling its entrypoint.
+    pub(crate) async fn instantiate(
+        &self,
+        registry: &Arc<Registry>,
+        module: &Module,
+        capabilities: CapabilitySet,
+    ) -> Result<WarmInstance, Error> {
+        let linker = self.linker_for(capabilities, AddressWidth::of(module))?;
+
+        let mut store = self.new_store(registry)?;
         // Limit linear memory growth to keep the mailbox pointers stable across the
         // instance lifetime. We preallocate and then lock the limit to the current
         // size so guest-initiated growth fails fast instead of moving the base
         // address out from under host-side wakers.
-        let instance = linker.instantiate_async(&mut store, &module).await?;
+        let instance = linker.instantiate_async(&mut store, module).await?;
 
         // Initialise waker mailbox
         let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| {
@@ -151,73 +516,116 @@ impl WasmRuntime {
             .load_mailbox(mb)
             .map_err(KernelError::from)?;
 
-        let signature = entrypoint.signature().clone();
-        let call_values = {
-            let registry = store.data_mut();
-            entrypoint.materialise_values(registry)?
-        };
-        let plan = CallPlan::new(&signature, &call_values)?;
-        materialise_plan(&memory, &mut store, &plan)?;
+        Ok(WarmInstance {
+            store,
+            instance,
+            memory,
+        })
+    }
 
-        let func = instance.get_func(&mut store, name).ok_or_else(|| {
-            Error::Wasmtime(wasmtime::Error::msg(format!(
-                "entrypoint `{name}` not found"
-            )))
-        })?;
-        let func_ty = func.ty(&store);
-        let param_types: Vec<ValType> = func_ty.params().collect();
-        let result_types: Vec<ValType> = func_ty.results().collect();
-        let expected_params = flatten_signature_types(signature.params());
-        let expected_results = flatten_signature_types(signature.results());
-
-        let params_match = param_types.len() == expected_params.len()
-            && param_types
-                .iter()
-                .zip(expected_params.iter())
-                .all(|(actual, expected)| valtype_eq(actual, expected));
-
-        if !params_match {
-            return Err(Error::Kernel(KernelError::Driver(format!(
-                "entrypoint `{name}` expects params {:?}, got {:?}",
-                expected_params, param_types
-            ))));
-        }
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        module: Module,
+        name: &str,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+        limits: ExecutionLimits,
+    ) -> Result<(), Error> {
+        let warm = self.instantiate(registry, &module, capabilities).await?;
+        self.start_instance(
+            registry,
+            process_id,
+            warm,
+            name,
+            capabilities,
+            entrypoint,
+            limits,
+        )
+    }
 
-        let results_match = result_types.len() == expected_results.len()
-            && result_types
-                .iter()
-                .zip(expected_results.iter())
-                .all(|(actual, expected)| valtype_eq(actual, expected));
-
-        if !results_match {
-            return Err(Error::Kernel(KernelError::Driver(format!(
-                "entrypoint expects results {:?}, got {:?}",
-                expected_results, result_types
-            ))));
-        }
+    /// Instantiate `module` and bind it to `process_id` without running an entrypoint, for the
+    /// host to call its exports through the returned [`ProcessHandle`].
+    pub async fn start_plugin(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        module: &Module,
+        capabilities: CapabilitySet,
+        limits: ExecutionLimits,
+    ) -> Result<ProcessHandle, Error> {
+        let WarmInstance {
+            mut store,
+            instance,
+            memory,
+        } = self.instantiate(registry, module, capabilities).await?;
+        let fuel =
+            self.assign_process(&mut store, Some(memory), process_id, capabilities, limits)?;
+        store
+            .data_mut()
+            .insert_extension(ModuleImports::of(module))
+            .map_err(KernelError::from)?;
+
+        Ok(ProcessHandle::new(
+            registry,
+            process_id,
+            store,
+            instance,
+            memory,
+            fuel,
+            self.crash_reports.clone(),
+        ))
+    }
+
+    /// Bind an instantiated guest to `process_id` and spawn its entrypoint.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn start_instance(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        warm: WarmInstance,
+        name: &str,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+        limits: ExecutionLimits,
+    ) -> Result<(), Error> {
+        let WarmInstance {
+            mut store,
+            instance,
+            memory,
+        } = warm;
+        let fuel =
+            self.assign_process(&mut store, Some(memory), process_id, capabilities, limits)?;
+        let imports = ModuleImports::of(instance.module(&store));
+        store
+            .data_mut()
+            .insert_extension(imports)
+            .map_err(KernelError::from)?;
 
-        let params = prepare_params(&param_types, plan.params())
-            .map_err(|err| Error::Kernel(KernelError::Driver(err)))?;
-        let result_template = prepare_results(&result_types)
-            .map_err(|err| Error::Kernel(KernelError::Driver(err)))?;
-        let signature_clone = signature.clone();
+        let call = prepare_call(&mut store, &instance, &memory, name, &entrypoint)?;
+        let crash_reports = self.crash_reports.clone();
+        let entrypoint_name = name.to_string();
         let (start_tx, start_rx) = tokio::sync::oneshot::channel();
-        let handle = tokio::spawn(async move {
-            // Wait for registration before invoking entrypoint. This prevents races between
-            // guests registering resources and the process_id being set on the registry.
-            if start_rx.await.is_err() {
-                return Err(wasmtime::Error::msg("process start cancelled"));
+        let handle = tokio::spawn(
+            async move {
+                // Wait for registration before invoking entrypoint. This prevents races between
+                // guests registering resources and the process_id being set on the registry.
+                if start_rx.await.is_err() {
+                    return Err(wasmtime::Error::msg("process start cancelled"));
+                }
+                invoke_entrypoint(
+                    store,
+                    memory,
+                    call,
+                    fuel,
+                    crash_reports.map(|reports| (reports, entrypoint_name)),
+                )
+                .await
             }
-            invoke_entrypoint(
-                func,
-                store,
-                memory,
-                params,
-                result_template,
-                signature_clone,
-            )
-            .await
-        });
+            .in_current_span(),
+        );
 
         registry
             .initialise(process_id, handle)
@@ -232,6 +640,69 @@ impl WasmRuntime {
     }
 }
 
+/// Write the buffers of `invocation` into guest memory and check the export `name` against its
+/// signature, ready to be called.
+fn prepare_call(
+    store: &mut Store<InstanceRegistry>,
+    instance: &Instance,
+    memory: &Memory,
+    name: &str,
+    invocation: &EntrypointInvocation,
+) -> Result<PreparedCall, Error> {
+    let signature = invocation.signature().clone();
+    let call_values = invocation.materialise_values(store.data_mut())?;
+    let plan = CallPlan::new(&signature, &call_values)?;
+    materialise_plan(memory, store, &plan)?;
+
+    let func = instance.get_func(&mut *store, name).ok_or_else(|| {
+        Error::Wasmtime(wasmtime::Error::msg(format!(
+            "entrypoint `{name}` not found"
+        )))
+    })?;
+    let func_ty = func.ty(&*store);
+    let param_types: Vec<ValType> = func_ty.params().collect();
+    let result_types: Vec<ValType> = func_ty.results().collect();
+    let expected_params = flatten_signature_types(signature.params());
+    let expected_results = flatten_signature_types(signature.results());
+
+    let params_match = param_types.len() == expected_params.len()
+        && param_types
+            .iter()
+            .zip(expected_params.iter())
+            .all(|(actual, expected)| valtype_eq(actual, expected));
+
+    if !params_match {
+        return Err(Error::Kernel(KernelError::Driver(format!(
+            "entrypoint `{name}` expects params {:?}, got {:?}",
+            expected_params, param_types
+        ))));
+    }
+
+    let results_match = result_types.len() == expected_results.len()
+        && result_types
+            .iter()
+            .zip(expected_results.iter())
+            .all(|(actual, expected)| valtype_eq(actual, expected));
+
+    if !results_match {
+        return Err(Error::Kernel(KernelError::Driver(format!(
+            "entrypoint expects results {:?}, got {:?}",
+            expected_results, result_types
+        ))));
+    }
+
+    let params = prepare_params(&param_types, plan.params())
+        .map_err(|err| Error::Kernel(KernelError::Driver(err)))?;
+    let results =
+        prepare_results(&result_types).map_err(|err| Error::Kernel(KernelError::Driver(err)))?;
+    Ok(PreparedCall {
+        func,
+        params,
+        results,
+        signature,
+    })
+}
+
 fn materialise_plan(
     memory: &Memory,
     store: &mut Store<InstanceRegistry>,
@@ -270,6 +741,38 @@ fn preallocate_memory(memory: &Memory, store: &mut Store<InstanceRegistry>) {
     debug!(pages = current, bytes, "prepared guest linear memory");
 }
 
+/// Names of the frames of `backtrace`, innermost first. Frames without a name in the module's
+/// name section are identified by module and function index.
+fn stack_frames(backtrace: &WasmBacktrace) -> Vec<String> {
+    backtrace
+        .frames()
+        .iter()
+        .map(|frame| match frame.func_name() {
+            Some(name) => name.to_string(),
+            None => format!(
+                "{}!wasm-function[{}]",
+                frame.module().name().unwrap_or("<module>"),
+                frame.func_index()
+            ),
+        })
+        .collect()
+}
+
+/// Advance the engine epoch every [`EPOCH_TICK`] until the engine is dropped.
+fn spawn_epoch_ticker(engine: EngineWeak) -> Result<(), Error> {
+    thread::Builder::new()
+        .name("selium-epoch".to_string())
+        .spawn(move || {
+            while let Some(engine) = engine.upgrade() {
+                engine.increment_epoch();
+                drop(engine);
+                thread::sleep(EPOCH_TICK);
+            }
+        })
+        .map(|_| ())
+        .map_err(Error::EpochTicker)
+}
+
 fn prepare_params(param_types: &[ValType], scalars: &[AbiScalarValue]) -> Result<Vec<Val>, String> {
     if param_types.len() != scalars.len() {
         return Err(format!(
@@ -293,13 +796,13 @@ fn prepare_results(result_types: &[ValType]) -> Result<Vec<Val>, String> {
         .collect())
 }
 
-fn stub_operations_for_missing(requested: &HashSet<Capability>) -> Vec<Arc<dyn LinkableOperation>> {
+fn stub_operations_for_missing(requested: CapabilitySet) -> Vec<Arc<dyn LinkableOperation>> {
     let hostcalls_by_capability = hostcalls::by_capability();
 
     selium_abi::Capability::ALL
         .iter()
         .copied()
-        .filter(|capability| !requested.contains(capability))
+        .filter(|capability| !requested.contains(*capability))
         .flat_map(|capability| {
             hostcalls_by_capability
                 .get(&capability)
@@ -312,6 +815,33 @@ fn stub_operations_for_missing(requested: &HashSet<Capability>) -> Vec<Arc<dyn L
         .collect()
 }
 
+/// Stubs for the hostcalls outside the catalogue, such as those added by plugins, of every
+/// capability in `map` but not in `requested`.
+fn stub_uncatalogued_operations(
+    map: &HashMap<Capability, Vec<Arc<dyn LinkableOperation>>>,
+    requested: CapabilitySet,
+) -> Vec<Arc<dyn LinkableOperation>> {
+    let mut linked: HashSet<&str> = hostcalls::ALL.iter().map(|meta| meta.name).collect();
+    linked.extend(
+        map.iter()
+            .filter(|(capability, _)| requested.contains(**capability))
+            .flat_map(|(_, operations)| operations.iter().map(|operation| operation.module())),
+    );
+
+    map.iter()
+        .filter(|(capability, _)| !requested.contains(**capability))
+        .flat_map(|(capability, operations)| {
+            operations
+                .iter()
+                .map(move |operation| (*capability, operation.module()))
+        })
+        .filter(|(_, module)| linked.insert(module))
+        .map(|(capability, module)| {
+            StubOperation::new(module, capability) as Arc<dyn LinkableOperation>
+        })
+        .collect()
+}
+
 struct StubOperation {
     module: &'static str,
     capability: Capability,
@@ -340,8 +870,8 @@ impl StubOperation {
         mut caller: Caller<'_, InstanceRegistry>,
         state_id: GuestUint,
         _task_id: GuestUint,
-        result_ptr: GuestInt,
-        result_capacity: GuestUint,
+        result_ptr: GuestAddress,
+        result_capacity: GuestAddress,
         module: &'static str,
         capability: Capability,
     ) -> Result<GuestUint, KernelError> {
@@ -361,8 +891,8 @@ impl StubOperation {
     fn drop_stub_future(
         mut caller: Caller<'_, InstanceRegistry>,
         state_id: GuestUint,
-        result_ptr: GuestInt,
-        result_capacity: GuestUint,
+        result_ptr: GuestAddress,
+        result_capacity: GuestAddress,
         module: &'static str,
         capability: Capability,
     ) -> Result<GuestUint, KernelError> {
@@ -378,18 +908,18 @@ impl StubOperation {
 
         write_poll_result(&mut caller, result_ptr, result_capacity, result)
     }
-}
 
-impl LinkableOperation for StubOperation {
-    fn link(&self, linker: &mut Linker<InstanceRegistry>) -> Result<(), KernelError> {
+    /// Link the stub's imports with guest addresses and lengths of type `A`.
+    fn link_with<A>(&self, linker: &mut Linker<InstanceRegistry>) -> Result<(), KernelError>
+    where
+        A: WasmTy + Into<GuestAddress>,
+    {
         let module = self.module;
         let capability = self.capability;
         linker.func_wrap(
             module,
             "create",
-            move |caller: Caller<'_, InstanceRegistry>,
-                  _args_ptr: GuestInt,
-                  _args_len: GuestUint| {
+            move |caller: Caller<'_, InstanceRegistry>, _args_ptr: A, _args_len: A| {
                 StubOperation::create_stub_future(caller, module, capability).map_err(Into::into)
             },
         )?;
@@ -402,14 +932,14 @@ impl LinkableOperation for StubOperation {
             move |caller: Caller<'_, InstanceRegistry>,
                   state_id: GuestUint,
                   task_id: GuestUint,
-                  result_ptr: GuestInt,
-                  result_capacity: GuestUint| {
+                  result_ptr: A,
+                  result_capacity: A| {
                 StubOperation::poll_stub_future(
                     caller,
                     state_id,
                     task_id,
-                    result_ptr,
-                    result_capacity,
+                    result_ptr.into(),
+                    result_capacity.into(),
                     module,
                     capability,
                 )
@@ -424,13 +954,13 @@ impl LinkableOperation for StubOperation {
             "drop",
             move |caller: Caller<'_, InstanceRegistry>,
                   state_id: GuestUint,
-                  result_ptr: GuestInt,
-                  result_capacity: GuestUint| {
+                  result_ptr: A,
+                  result_capacity: A| {
                 StubOperation::drop_stub_future(
                     caller,
                     state_id,
-                    result_ptr,
-                    result_capacity,
+                    result_ptr.into(),
+                    result_capacity.into(),
                     module,
                     capability,
                 )
@@ -442,16 +972,144 @@ impl LinkableOperation for StubOperation {
     }
 }
 
+impl LinkableOperation for StubOperation {
+    fn module(&self) -> &'static str {
+        self.module
+    }
+
+    fn invoke(
+        &self,
+        _registry: &mut InstanceRegistry,
+        _input: &[u8],
+    ) -> Result<CallState, KernelError> {
+        debug!(module = %self.module, capability = ?self.capability, "invoking stub capability binding");
+        let state = FutureSharedState::new();
+        state.resolve(Err(GuestError::PermissionDenied));
+        Ok(state)
+    }
+
+    fn link(
+        &self,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError> {
+        match width {
+            AddressWidth::Bits32 => self.link_with::<u32>(linker),
+            AddressWidth::Bits64 => self.link_with::<u64>(linker),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn invoke_entrypoint(
-    func: Func,
     mut store: Store<InstanceRegistry>,
     memory: Memory,
-    params: Vec<Val>,
-    mut results: Vec<Val>,
-    signature: AbiSignature,
+    call: PreparedCall,
+    fuel: u64,
+    crash_reports: Option<(CrashReports, String)>,
+) -> Result<Vec<u8>, wasmtime::Error> {
+    let crash_reports = crash_reports
+        .as_ref()
+        .map(|(reports, entrypoint)| (reports, entrypoint.as_str()));
+    let results = call_export(&mut store, &memory, call, fuel, crash_reports).await?;
+    Ok(completion_value(store.data(), results))
+}
+
+/// Call a prepared export and decode its results, recording the fuel the process has consumed
+/// out of the `fuel` it started with, and reporting a crash should the guest trap.
+async fn call_export(
+    store: &mut Store<InstanceRegistry>,
+    memory: &Memory,
+    call: PreparedCall,
+    fuel: u64,
+    crash_reports: Option<(&CrashReports, &str)>,
 ) -> Result<Vec<AbiValue>, wasmtime::Error> {
-    func.call_async(&mut store, &params, &mut results).await?;
-    decode_results(&memory, &store, &results, &signature)
+    let PreparedCall {
+        func,
+        params,
+        mut results,
+        signature,
+    } = call;
+    let outcome = func.call_async(&mut *store, &params, &mut results).await;
+    if let Some(usage) = store.data().extension::<ProcessUsage>() {
+        usage.record_fuel(fuel.saturating_sub(store.get_fuel()?));
+        usage.record_memory(memory.data_size(&*store));
+    }
+    if let Err(err) = outcome {
+        let err = with_reported_panic(store.data(), with_trap_reason(store.data(), err));
+        return Err(match crash_reports {
+            Some((reports, entrypoint)) => reports.report(
+                store.data(),
+                entrypoint,
+                Some(memory.data_size(&*store)),
+                err,
+            ),
+            None => err,
+        });
+    }
+    decode_results(memory, store, &results, &signature)
+}
+
+/// Why the guest trapped with `err`. Errors that are not Wasm traps were raised by host
+/// functions, and an `unreachable` after failing to grow linear memory is how guests abort on
+/// allocation failure.
+fn trap_reason(registry: &InstanceRegistry, err: &wasmtime::Error) -> TrapReason {
+    match err.downcast_ref::<Trap>() {
+        Some(Trap::UnreachableCodeReached) if registry.memory_exhausted() => {
+            TrapReason::OutOfMemory
+        }
+        Some(Trap::UnreachableCodeReached) => TrapReason::Unreachable,
+        Some(Trap::AllocationTooLarge) => TrapReason::OutOfMemory,
+        Some(Trap::OutOfFuel) => TrapReason::FuelExhausted,
+        Some(Trap::Interrupt) => TrapReason::EpochInterrupt,
+        Some(_) => TrapReason::Other,
+        None => TrapReason::HostcallError,
+    }
+}
+
+/// `err` with the reason the guest trapped attached as context, after recording the reason on
+/// the guest's process.
+fn with_trap_reason(registry: &InstanceRegistry, err: wasmtime::Error) -> wasmtime::Error {
+    let reason = trap_reason(registry, &err);
+    if let Some(process_id) = registry.process()
+        && let Err(record_err) = registry.registry().record_trap(process_id, reason)
+    {
+        // The process resource is gone once a parent waits on the process.
+        debug!(process_id, %reason, err = %record_err, "process trap not recorded");
+    }
+    err.context(reason)
+}
+
+/// `err` with the panic the guest reported before trapping, if any, attached as context.
+fn with_reported_panic(registry: &InstanceRegistry, err: wasmtime::Error) -> wasmtime::Error {
+    match registry
+        .extension::<ProcessPanicked>()
+        .and_then(|panicked| panicked.get())
+    {
+        Some(panic) if panic.location.is_empty() => {
+            err.context(format!("guest panicked: {}", panic.message))
+        }
+        Some(panic) => err.context(format!(
+            "guest panicked at {}: {}",
+            panic.location, panic.message
+        )),
+        None => err,
+    }
+}
+
+/// The value a finished entrypoint hands to whoever waits on its process: the value it recorded
+/// through [`hostcalls::PROCESS_COMPLETE`], or else its first buffer result.
+fn completion_value(instance: &InstanceRegistry, results: Vec<AbiValue>) -> Vec<u8> {
+    instance
+        .extension::<ProcessCompletion>()
+        .and_then(|completion| completion.take())
+        .or_else(|| {
+            results.into_iter().find_map(|value| match value {
+                AbiValue::Buffer(bytes) => Some(bytes),
+                AbiValue::Scalar(_) => None,
+            })
+        })
+        .unwrap_or_default()
 }
 
 fn decode_results(
@@ -665,3 +1323,467 @@ fn take_u32(iter: &mut std::slice::Iter<Val>, msg: &str) -> Result<u32, wasmtime
     let raw = take_i32(iter, msg)?;
     Ok(u32::from_ne_bytes(raw.to_ne_bytes()))
 }
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{ErrorCode, ProcessPanic};
+    use selium_kernel::{
+        drivers::module_store::InMemoryModuleStore, events::KernelEvent, operation::Enforcement,
+        registry::ResourceType, session::Session,
+    };
+
+    use super::*;
+
+    #[test]
+    fn pooling_limits_configure_engine() {
+        let limits = PoolingLimits {
+            max_instances: 4,
+            max_memory_pages: PREALLOC_PAGES,
+        };
+        let mut config = Config::new();
+        config.allocation_strategy(InstanceAllocationStrategy::Pooling(
+            limits.allocation_config().expect("valid limits"),
+        ));
+        Engine::new(&config).expect("pooled engine");
+
+        let oversized = PoolingLimits {
+            max_instances: 1,
+            max_memory_pages: u64::MAX,
+        };
+        assert!(oversized.allocation_config().is_err());
+    }
+
+    #[test]
+    fn address_width_follows_module_memory() {
+        // A module exporting a one-page memory, with the memory's limits flags spliced in.
+        fn module(engine: &Engine, flags: u8) -> Module {
+            let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+            bytes.extend_from_slice(&[0x05, 0x03, 0x01, flags, 0x01]);
+            bytes.extend_from_slice(&[0x07, 0x0a, 0x01, 0x06]);
+            bytes.extend_from_slice(b"memory");
+            bytes.extend_from_slice(&[0x02, 0x00]);
+            Module::new(engine, bytes).expect("valid module")
+        }
+
+        let mut config = Config::new();
+        config.wasm_memory64(true);
+        let engine = Engine::new(&config).expect("engine");
+        assert_eq!(
+            AddressWidth::of(&module(&engine, 0x00)),
+            AddressWidth::Bits32
+        );
+        assert_eq!(
+            AddressWidth::of(&module(&engine, 0x04)),
+            AddressWidth::Bits64
+        );
+    }
+
+    #[test]
+    fn traps_are_classified_and_recorded() {
+        // Exports `oom`, which fails to grow its one-page memory and then aborts, and `trap`,
+        // which aborts straight away.
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x03, 0x02, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x05, 0x04, 0x01, 0x01, 0x01, 0x01]);
+        bytes.extend_from_slice(&[0x07, 0x0e, 0x02, 0x03]);
+        bytes.extend_from_slice(b"oom");
+        bytes.extend_from_slice(&[0x00, 0x00, 0x04]);
+        bytes.extend_from_slice(b"trap");
+        bytes.extend_from_slice(&[0x00, 0x01]);
+        bytes.extend_from_slice(&[0x0a, 0x0e, 0x02]);
+        bytes.extend_from_slice(&[0x08, 0x00, 0x41, 0x01, 0x40, 0x00, 0x1a, 0x00, 0x0b]);
+        bytes.extend_from_slice(&[0x03, 0x00, 0x00, 0x0b]);
+
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).expect("engine");
+        let module = Module::new(&engine, bytes).expect("valid module");
+        let registry = Registry::new();
+        let process_id = registry
+            .add((), None, ResourceType::Process)
+            .expect("add process")
+            .into_id();
+        let mut instance_registry = registry.instance().expect("instance registry");
+        instance_registry
+            .set_process_id(process_id)
+            .expect("set process id");
+        let mut store = Store::new(&engine, instance_registry);
+        store.limiter(|registry| registry);
+        store.set_fuel(10_000).expect("set fuel");
+        let instance = Instance::new(&mut store, &module, &[]).expect("instance");
+        let call = |store: &mut Store<InstanceRegistry>, name: &str| {
+            let func = instance
+                .get_typed_func::<(), ()>(&mut *store, name)
+                .expect("export");
+            func.call(&mut *store, ()).expect_err("trap")
+        };
+
+        let err = call(&mut store, "trap");
+        assert_eq!(trap_reason(store.data(), &err), TrapReason::Unreachable);
+        let err = call(&mut store, "oom");
+        assert_eq!(trap_reason(store.data(), &err), TrapReason::OutOfMemory);
+        store.set_fuel(0).expect("set fuel");
+        let err = call(&mut store, "trap");
+        assert_eq!(trap_reason(store.data(), &err), TrapReason::FuelExhausted);
+        assert_eq!(
+            trap_reason(store.data(), &wasmtime::Error::msg("bad pointer")),
+            TrapReason::HostcallError
+        );
+
+        let err = with_trap_reason(store.data(), err);
+        assert_eq!(
+            err.downcast_ref::<TrapReason>(),
+            Some(&TrapReason::FuelExhausted)
+        );
+        assert_eq!(
+            registry.process_trap(process_id),
+            Some(TrapReason::FuelExhausted)
+        );
+        assert!(matches!(
+            GuestError::from(Error::ProcessFailed(err)),
+            GuestError::Coded(ErrorCode::ProcessFuelExhausted, _)
+        ));
+    }
+
+    /// Address of the buffer [`time_now_guest`] polls its result into.
+    const RESULT_BUF: i32 = 0x8000;
+    const RESULT_CAPACITY: i32 = 256;
+
+    /// A module exporting `memory` and `now`, which calls `selium::time::now` and polls it once,
+    /// returning the poll status and the result buffer.
+    fn time_now_guest() -> Vec<u8> {
+        use wasm_encoder::{
+            CodeSection, EntityType, ExportKind, ExportSection, Function, FunctionSection,
+            ImportSection, Instruction, MemorySection, MemoryType, TypeSection, ValType,
+        };
+
+        let mut types = TypeSection::new();
+        types.ty().function([ValType::I32; 2], [ValType::I32]);
+        types.ty().function([ValType::I32; 4], [ValType::I32]);
+        types.ty().function([], [ValType::I32; 3]);
+        let mut imports = ImportSection::new();
+        imports.import("selium::time::now", "create", EntityType::Function(0));
+        imports.import("selium::time::now", "poll", EntityType::Function(1));
+        let mut functions = FunctionSection::new();
+        functions.function(2);
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("now", ExportKind::Func, 2);
+        let mut now = Function::new([]);
+        for instruction in [
+            Instruction::I32Const(0),
+            Instruction::I32Const(0),
+            Instruction::Call(0),
+            Instruction::I32Const(0),
+            Instruction::I32Const(RESULT_BUF),
+            Instruction::I32Const(RESULT_CAPACITY),
+            Instruction::Call(1),
+            Instruction::I32Const(RESULT_BUF),
+            Instruction::I32Const(RESULT_CAPACITY),
+            Instruction::End,
+        ] {
+            now.instruction(&instruction);
+        }
+        let mut code = CodeSection::new();
+        code.function(&now);
+
+        let mut module = wasm_encoder::Module::new();
+        module
+            .section(&types)
+            .section(&imports)
+            .section(&functions)
+            .section(&memories)
+            .section(&exports)
+            .section(&code);
+        module.finish()
+    }
+
+    /// Call the `now` export of a [`time_now_guest`], returning its poll status and result.
+    async fn call_time_now(plugin: &ProcessHandle) -> (u32, Vec<u8>) {
+        let invocation = EntrypointInvocation::new(
+            AbiSignature::new(
+                Vec::new(),
+                vec![AbiParam::Scalar(AbiScalarType::U32), AbiParam::Buffer],
+            ),
+            Vec::new(),
+        )
+        .expect("invocation");
+        match plugin
+            .invoke("now", &invocation)
+            .await
+            .expect("call now")
+            .as_slice()
+        {
+            [
+                AbiValue::Scalar(AbiScalarValue::U32(status)),
+                AbiValue::Buffer(result),
+            ] => (*status, result.clone()),
+            other => panic!("unexpected results {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn plugins_started_through_the_driver_are_tagged_with_their_module() {
+        let runtime = WasmRuntime::new(
+            HashMap::new(),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let store = InMemoryModuleStore::new();
+        store.insert("echo.wasm", time_now_guest());
+        let driver = WasmtimeDriver::new(Arc::new(runtime), Arc::new(store));
+        let registry = Registry::new();
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+
+        let _plugin = driver
+            .start_plugin(
+                &registry,
+                process_id,
+                "echo.wasm",
+                CapabilitySet::default(),
+                ExecutionLimits::default(),
+            )
+            .await
+            .expect("start plugin");
+        assert_eq!(registry.tags(process_id), vec!["module:echo.wasm"]);
+    }
+
+    #[tokio::test]
+    async fn linked_hostcalls_are_refused_once_the_session_lacks_the_capability() {
+        let time = selium_kernel::drivers::time::operations(
+            selium_kernel::drivers::time::SystemTimeService,
+        );
+        let runtime = WasmRuntime::new(
+            HashMap::from([(
+                Capability::TimeRead,
+                vec![time.0.as_linkable(), time.1.as_linkable()],
+            )]),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let module = runtime.compile(&time_now_guest()).expect("compile");
+        let registry = Registry::new();
+        let start = |process_id| {
+            runtime.start_plugin(
+                &registry,
+                process_id,
+                &module,
+                Capability::TimeRead.into(),
+                ExecutionLimits::default(),
+            )
+        };
+
+        // A process calls as a session holding exactly what it was granted.
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let granted = start(process_id).await.expect("start plugin");
+        let (status, _) = call_time_now(&granted).await;
+        assert!(status <= selium_abi::DRIVER_RESULT_READY_MAX);
+
+        // One whose spawner registered a narrower session has the hostcall linked, but refused.
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let session = registry
+            .add(
+                Session::bootstrap(CapabilitySet::default(), [0; 32]),
+                Some(process_id),
+                ResourceType::Session,
+            )
+            .expect("add session")
+            .into_id();
+        let narrowed = start(process_id).await.expect("start plugin");
+        let (status, result) = call_time_now(&narrowed).await;
+        assert_eq!(
+            status,
+            selium_abi::driver_encode_error(selium_abi::DRIVER_ERROR_MESSAGE_CODE)
+        );
+        let error = selium_abi::decode_driver_error(&result).expect("driver error");
+        assert_eq!(error.code, ErrorCode::PermissionDenied.code());
+
+        drop(narrowed);
+        assert!(registry.metadata(session).is_none());
+    }
+
+    #[tokio::test]
+    async fn audited_hostcalls_run_but_report_the_denial() {
+        let time = selium_kernel::drivers::time::operations(
+            selium_kernel::drivers::time::SystemTimeService,
+        );
+        let runtime = WasmRuntime::new(
+            HashMap::from([(
+                Capability::TimeRead,
+                vec![time.0.as_linkable(), time.1.as_linkable()],
+            )]),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let module = runtime.compile(&time_now_guest()).expect("compile");
+        let registry = Registry::new();
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        registry
+            .add(
+                Session::bootstrap(CapabilitySet::default(), [0; 32]),
+                Some(process_id),
+                ResourceType::Session,
+            )
+            .expect("add session");
+        let plugin = runtime
+            .start_plugin(
+                &registry,
+                process_id,
+                &module,
+                Capability::TimeRead.into(),
+                ExecutionLimits::default(),
+            )
+            .await
+            .expect("start plugin");
+        let mut events = registry.events().subscribe();
+        let mut denial = || {
+            std::iter::from_fn(|| events.try_recv().ok()).find_map(|event| match event {
+                KernelEvent::CapabilityDenied {
+                    hostcall,
+                    capability,
+                    process_id: denied,
+                    enforced,
+                    ..
+                } => {
+                    assert_eq!(hostcall, "selium::time::now");
+                    assert_eq!(capability, Capability::TimeRead);
+                    assert_eq!(denied, Some(process_id));
+                    Some(enforced)
+                }
+                _ => None,
+            })
+        };
+
+        let (status, _) = call_time_now(&plugin).await;
+        assert!(status > selium_abi::DRIVER_RESULT_READY_MAX);
+        assert_eq!(denial(), Some(true));
+
+        time.0.set_enforcement(Enforcement::Audit);
+        let (status, _) = call_time_now(&plugin).await;
+        assert!(status <= selium_abi::DRIVER_RESULT_READY_MAX);
+        assert_eq!(denial(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn linkers_are_shared_until_a_capability_is_extended() {
+        let runtime = WasmRuntime::new(
+            HashMap::new(),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let capabilities = CapabilitySet::default();
+
+        let first = runtime
+            .linker_for(capabilities, AddressWidth::Bits32)
+            .expect("linker");
+        let again = runtime
+            .linker_for(capabilities, AddressWidth::Bits32)
+            .expect("linker");
+        assert!(Arc::ptr_eq(&first, &again));
+        let wide = runtime
+            .linker_for(capabilities, AddressWidth::Bits64)
+            .expect("linker");
+        assert!(!Arc::ptr_eq(&first, &wide));
+
+        runtime
+            .extend_capability(Capability::TimeRead, Vec::new())
+            .expect("extend");
+        let rebuilt = runtime
+            .linker_for(capabilities, AddressWidth::Bits32)
+            .expect("linker");
+        assert!(!Arc::ptr_eq(&first, &rebuilt));
+    }
+
+    #[test]
+    fn ungranted_uncatalogued_hostcalls_are_stubbed() {
+        let plugin = Operation::new(meta::ReadyDriver, "acme::ready").as_linkable();
+        let runtime = WasmRuntime::new(
+            HashMap::from([(Capability::TimeRead, vec![plugin])]),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let linked = |capabilities| {
+            runtime
+                .operations_for(capabilities)
+                .expect("operations")
+                .iter()
+                .filter(|operation| operation.module() == "acme::ready")
+                .count()
+        };
+
+        assert_eq!(linked(CapabilitySet::default()), 1);
+        assert_eq!(linked(CapabilitySet::from([Capability::TimeRead])), 1);
+    }
+
+    #[test]
+    fn completion_value_prefers_the_recorded_value() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let results = || {
+            vec![
+                AbiValue::Scalar(AbiScalarValue::I32(1)),
+                AbiValue::Buffer(vec![2]),
+            ]
+        };
+
+        assert!(completion_value(&instance, Vec::new()).is_empty());
+        assert_eq!(completion_value(&instance, results()), [2]);
+
+        instance
+            .insert_extension(ProcessCompletion::default())
+            .expect("insert completion");
+        if let Some(completion) = instance.extension::<ProcessCompletion>() {
+            completion.set(vec![3]);
+        }
+        assert_eq!(completion_value(&instance, results()), [3]);
+        assert_eq!(completion_value(&instance, results()), [2]);
+    }
+
+    #[test]
+    fn traps_carry_the_reported_panic() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let trap = || wasmtime::Error::msg("wasm trap: unreachable");
+
+        assert_eq!(
+            with_reported_panic(&instance, trap()).to_string(),
+            "wasm trap: unreachable"
+        );
+
+        instance
+            .insert_extension(ProcessPanicked::default())
+            .expect("insert panicked");
+        if let Some(panicked) = instance.extension::<ProcessPanicked>() {
+            panicked.set(ProcessPanic {
+                message: "boom".to_string(),
+                location: "src/lib.rs:3:5".to_string(),
+            });
+        }
+        assert_eq!(
+            with_reported_panic(&instance, trap()).to_string(),
+            "guest panicked at src/lib.rs:3:5: boom"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Compiled modules shared by every process started from the same Wasm bytes.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use tracing::debug;
+use wasmtime::Module;
+
+use crate::Error;
+
+/// A compiled module, or a slot awaiting its first compilation.
+type Slot = Arc<Mutex<Option<Module>>>;
+
+/// Compiled modules keyed by the BLAKE3 digest of their bytes, kept for the life of the runtime.
+///
+/// Each digest is compiled at most once: concurrent spawns of the same bytes wait for the first
+/// compilation rather than repeating it, while different modules compile in parallel.
+#[derive(Default)]
+pub(crate) struct CompiledModules {
+    modules: Mutex<HashMap<blake3::Hash, Slot>>,
+    hits: AtomicU64,
+    compiled: AtomicU64,
+}
+
+/// Counters of module compilation, as reported by [`crate::WasmRuntime::module_stats`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ModuleStats {
+    /// Spawns that reused a module already compiled for the same bytes.
+    pub hits: u64,
+    /// Modules compiled, from source or the on-disk cache, and held since.
+    pub compiled: u64,
+}
+
+impl CompiledModules {
+    /// Return the module compiled from `bytes`, calling `compile` only if none is held yet.
+    pub(crate) fn get_or_compile(
+        &self,
+        bytes: &[u8],
+        compile: impl FnOnce() -> Result<Module, Error>,
+    ) -> Result<Module, Error> {
+        let digest = blake3::hash(bytes);
+        let slot = Arc::clone(
+            self.modules
+                .lock()
+                .map_err(|_| Error::ModuleTablePoisoned)?
+                .entry(digest)
+                .or_default(),
+        );
+
+        let mut slot = slot.lock().map_err(|_| Error::ModuleTablePoisoned)?;
+        if let Some(module) = slot.as_ref() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            debug!(%digest, "reusing compiled module");
+            return Ok(module.clone());
+        }
+
+        let module = compile()?;
+        self.compiled.fetch_add(1, Ordering::Relaxed);
+        *slot = Some(module.clone());
+        Ok(module)
+    }
+
+    /// Current compilation counters.
+    pub(crate) fn stats(&self) -> ModuleStats {
+        ModuleStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            compiled: self.compiled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmtime::Engine;
+
+    use super::*;
+
+    const MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn identical_bytes_compile_once() {
+        let engine = Engine::default();
+        let modules = CompiledModules::default();
+        let compile = || Ok(Module::from_binary(&engine, MODULE)?);
+
+        modules.get_or_compile(MODULE, compile).expect("compile");
+        modules.get_or_compile(MODULE, compile).expect("reuse");
+        assert_eq!(
+            modules.stats(),
+            ModuleStats {
+                hits: 1,
+                compiled: 1
+            }
+        );
+    }
+}
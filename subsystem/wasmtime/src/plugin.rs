@@ -0,0 +1,183 @@
+//! Calling the exports of a guest from the host, to drive it as a plugin.
+//!
+//! A guest started with [`WasmtimeDriver::start_plugin`](crate::WasmtimeDriver::start_plugin) is
+//! instantiated and bound to its process, but no entrypoint is run. The host then calls its
+//! exports through the returned [`ProcessHandle`], one call at a time. Buffer arguments are
+//! written to the region after the guest's mailbox, just as they are for an entrypoint, so a
+//! guest must copy out any argument it keeps beyond the call.
+
+use std::sync::Arc;
+
+use rkyv::{
+    Archive, Deserialize,
+    api::high::{HighDeserializer, HighValidator},
+    bytecheck::CheckBytes,
+    rancor::Error as RancorError,
+};
+use selium_abi::{
+    AbiParam, AbiSignature, AbiValue, EntrypointArg, EntrypointInvocation, RkyvEncode, decode_rkyv,
+    encode_rkyv,
+};
+use selium_kernel::{
+    KernelError,
+    registry::{InstanceRegistry, Registry, ResourceId},
+};
+use tokio::sync::Mutex;
+use wasmtime::{Instance, Memory, Store};
+
+use crate::{CrashReports, Error, call_export, prepare_call};
+
+/// A guest instance bound to a process, whose exports the host calls directly.
+///
+/// The process is released once the handle is dropped.
+pub struct ProcessHandle {
+    process_id: ResourceId,
+    registry: Arc<Registry>,
+    guest: Mutex<Guest>,
+    fuel: u64,
+    crash_reports: Option<CrashReports>,
+}
+
+struct Guest {
+    store: Store<InstanceRegistry>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl ProcessHandle {
+    pub(crate) fn new(
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        store: Store<InstanceRegistry>,
+        instance: Instance,
+        memory: Memory,
+        fuel: u64,
+        crash_reports: Option<CrashReports>,
+    ) -> Self {
+        Self {
+            process_id,
+            registry: Arc::clone(registry),
+            guest: Mutex::new(Guest {
+                store,
+                instance,
+                memory,
+            }),
+            fuel,
+            crash_reports,
+        }
+    }
+
+    /// The process the guest is bound to.
+    pub fn process_id(&self) -> ResourceId {
+        self.process_id
+    }
+
+    /// Call the export `export` with `args` encoded as rkyv, and decode its rkyv result.
+    ///
+    /// The export takes the pointer and length of the encoded arguments, and returns the
+    /// pointer and length of its encoded result.
+    pub async fn call<Args, Ret>(&self, export: &str, args: &Args) -> Result<Ret, Error>
+    where
+        Args: RkyvEncode,
+        Ret: Archive + Sized,
+        for<'a> Ret::Archived: 'a
+            + Deserialize<Ret, HighDeserializer<RancorError>>
+            + CheckBytes<HighValidator<'a, RancorError>>,
+    {
+        let invocation = EntrypointInvocation::new(
+            AbiSignature::new(vec![AbiParam::Buffer], vec![AbiParam::Buffer]),
+            vec![EntrypointArg::Buffer(encode_rkyv(args)?)],
+        )?;
+        match self.invoke(export, &invocation).await?.as_slice() {
+            [AbiValue::Buffer(bytes)] => Ok(decode_rkyv(bytes)?),
+            _ => Err(Error::Kernel(KernelError::Driver(format!(
+                "export `{export}` did not return a buffer"
+            )))),
+        }
+    }
+
+    /// Call the export `export` as described by `invocation`, returning its decoded results.
+    pub async fn invoke(
+        &self,
+        export: &str,
+        invocation: &EntrypointInvocation,
+    ) -> Result<Vec<AbiValue>, Error> {
+        let mut guest = self.guest.lock().await;
+        let Guest {
+            store,
+            instance,
+            memory,
+        } = &mut *guest;
+        let call = prepare_call(store, instance, memory, export, invocation)?;
+        let crash_reports = self.crash_reports.as_ref().map(|reports| (reports, export));
+        Ok(call_export(store, memory, call, self.fuel, crash_reports).await?)
+    }
+}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        self.registry.discard(self.process_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use selium_abi::CapabilitySet;
+    use selium_kernel::{guest_async::GuestAsync, registry::ResourceType};
+
+    use super::*;
+    use crate::{ExecutionLimits, WasmRuntime};
+
+    /// A module exporting `memory` and `echo`, which returns the buffer it is given.
+    fn echo_module() -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        // One function of type (i32, i32) -> (i32, i32), and one page of memory.
+        bytes.extend_from_slice(&[0x01, 0x08, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x02, 0x7f, 0x7f]);
+        bytes.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        bytes.extend_from_slice(&[0x05, 0x03, 0x01, 0x00, 0x01]);
+        bytes.extend_from_slice(&[0x07, 0x11, 0x02, 0x06]);
+        bytes.extend_from_slice(b"memory");
+        bytes.extend_from_slice(&[0x02, 0x00, 0x04]);
+        bytes.extend_from_slice(b"echo");
+        bytes.extend_from_slice(&[0x00, 0x00]);
+        // Body: local.get 0, local.get 1.
+        bytes.extend_from_slice(&[0x0a, 0x08, 0x01, 0x06, 0x00, 0x20, 0x00, 0x20, 0x01, 0x0b]);
+        bytes
+    }
+
+    #[tokio::test]
+    async fn exports_are_called_with_rkyv_arguments() {
+        let runtime = WasmRuntime::new(
+            HashMap::new(),
+            Arc::new(GuestAsync::new(Arc::new(tokio::sync::Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let registry = Registry::new();
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let module = runtime.compile(&echo_module()).expect("compile");
+        let plugin = runtime
+            .start_plugin(
+                &registry,
+                process_id,
+                &module,
+                CapabilitySet::default(),
+                ExecutionLimits::default(),
+            )
+            .await
+            .expect("start plugin");
+
+        let echoed: Vec<u64> = plugin.call("echo", &vec![1u64, 2, 3]).await.expect("call");
+        assert_eq!(echoed, [1, 2, 3]);
+        let echoed: u64 = plugin.call("echo", &42u64).await.expect("call again");
+        assert_eq!(echoed, 42);
+        assert!(plugin.call::<u64, u64>("missing", &0).await.is_err());
+
+        drop(plugin);
+        assert!(!registry.discard(process_id));
+    }
+}
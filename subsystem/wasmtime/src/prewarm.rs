@@ -0,0 +1,200 @@
+//! Pools of instantiated but unstarted guests.
+//!
+//! Compiling, linking and instantiating a module dominates the latency of `process::start`.
+//! Modules designated for pre-warming keep a number of instances ready ahead of time, so a start
+//! only binds an instance to its process and passes the entrypoint arguments. Instances are
+//! linked for a fixed capability set, so each pool serves one module and one set of grants.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use selium_abi::CapabilitySet;
+use selium_kernel::registry::{InstanceRegistry, Registry};
+use wasmtime::{Instance, Memory, Store};
+
+use crate::Error;
+
+/// A linked and instantiated guest, with its mailbox loaded but no process assigned.
+pub(crate) struct WarmInstance {
+    pub(crate) store: Store<InstanceRegistry>,
+    pub(crate) instance: Instance,
+    pub(crate) memory: Memory,
+}
+
+/// Warm instances kept for designated module and capability pairs.
+#[derive(Default)]
+pub(crate) struct PrewarmPool {
+    pools: Mutex<HashMap<PoolKey, Pool>>,
+}
+
+/// Module and capability set a pool serves.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct PoolKey {
+    module_id: String,
+    capabilities: CapabilitySet,
+}
+
+struct Pool {
+    registry: Arc<Registry>,
+    target: usize,
+    ready: Vec<(blake3::Hash, WarmInstance)>,
+}
+
+impl PoolKey {
+    /// Identify the pool for `module_id` granted `capabilities`.
+    pub(crate) fn new(module_id: &str, capabilities: CapabilitySet) -> Self {
+        Self {
+            module_id: module_id.to_string(),
+            capabilities,
+        }
+    }
+
+    pub(crate) fn module_id(&self) -> &str {
+        &self.module_id
+    }
+
+    pub(crate) fn capabilities(&self) -> CapabilitySet {
+        self.capabilities
+    }
+}
+
+impl PrewarmPool {
+    /// Keep `target` instances ready for `key`, instantiated in `registry`. Designating a key
+    /// again replaces its target; instances from another registry are discarded.
+    pub(crate) fn designate(
+        &self,
+        key: &PoolKey,
+        registry: &Arc<Registry>,
+        target: usize,
+    ) -> Result<(), Error> {
+        let mut pools = self.pools.lock().map_err(|_| Error::PrewarmPoolPoisoned)?;
+        let pool = pools.entry(key.clone()).or_insert_with(|| Pool {
+            registry: Arc::clone(registry),
+            target,
+            ready: Vec::new(),
+        });
+        if !Arc::ptr_eq(&pool.registry, registry) {
+            pool.registry = Arc::clone(registry);
+            pool.ready.clear();
+        }
+        pool.target = target;
+        pool.ready.truncate(target);
+        Ok(())
+    }
+
+    /// Take an instance of the module whose bytes hash to `digest`. Instances of any other
+    /// version of the module are stale and discarded.
+    pub(crate) fn take(
+        &self,
+        key: &PoolKey,
+        registry: &Arc<Registry>,
+        digest: blake3::Hash,
+    ) -> Result<Option<WarmInstance>, Error> {
+        let mut pools = self.pools.lock().map_err(|_| Error::PrewarmPoolPoisoned)?;
+        let Some(pool) = pools.get_mut(key) else {
+            return Ok(None);
+        };
+        if !Arc::ptr_eq(&pool.registry, registry) {
+            return Ok(None);
+        }
+
+        pool.ready.retain(|(ready, _)| *ready == digest);
+        Ok(pool.ready.pop().map(|(_, instance)| instance))
+    }
+
+    /// Registry to instantiate into if the pool for `key` is below its target.
+    pub(crate) fn wanted(&self, key: &PoolKey) -> Result<Option<Arc<Registry>>, Error> {
+        let pools = self.pools.lock().map_err(|_| Error::PrewarmPoolPoisoned)?;
+        Ok(pools
+            .get(key)
+            .filter(|pool| pool.ready.len() < pool.target)
+            .map(|pool| Arc::clone(&pool.registry)))
+    }
+
+    /// Return a freshly instantiated module to its pool. Instances beyond the target, for example
+    /// from concurrent refills, are dropped.
+    pub(crate) fn put(
+        &self,
+        key: &PoolKey,
+        digest: blake3::Hash,
+        instance: WarmInstance,
+    ) -> Result<(), Error> {
+        let mut pools = self.pools.lock().map_err(|_| Error::PrewarmPoolPoisoned)?;
+        if let Some(pool) = pools.get_mut(key)
+            && pool.ready.len() < pool.target
+        {
+            pool.ready.push((digest, instance));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use selium_kernel::{drivers::Capability, guest_async::GuestAsync};
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::WasmRuntime;
+
+    /// A module that only exports a single page of linear memory as `memory`.
+    const MEMORY_ONLY_MODULE: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic and version
+        0x05, 0x03, 0x01, 0x00, 0x01, // memory section: one memory, min 1 page
+        0x07, 0x0a, 0x01, 0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02,
+        0x00, // export section: memory 0 as `memory`
+    ];
+
+    #[tokio::test]
+    async fn stale_and_foreign_instances_are_not_reused() {
+        let runtime = WasmRuntime::new(
+            HashMap::new(),
+            Arc::new(GuestAsync::new(Arc::new(Notify::new()))),
+            None,
+        )
+        .expect("runtime");
+        let module = runtime.compile(MEMORY_ONLY_MODULE).expect("module");
+        let registry = Registry::new();
+        let other_registry = Registry::new();
+        let key = PoolKey::new("worker.wasm", Capability::TimeRead.into());
+        let current = blake3::hash(b"current");
+        let pool = PrewarmPool::default();
+
+        pool.designate(&key, &registry, 1).expect("designate");
+        let wanted = pool.wanted(&key).expect("wanted").expect("below target");
+        let warm = runtime
+            .instantiate(&wanted, &module, CapabilitySet::EMPTY)
+            .await
+            .expect("instantiate");
+        pool.put(&key, current, warm).expect("put");
+        assert!(pool.wanted(&key).expect("wanted").is_none());
+
+        assert!(
+            pool.take(&key, &other_registry, current)
+                .expect("take")
+                .is_none()
+        );
+        assert!(
+            pool.take(&key, &registry, blake3::hash(b"stale"))
+                .expect("take")
+                .is_none()
+        );
+        assert!(pool.wanted(&key).expect("wanted").is_some());
+
+        let warm = runtime
+            .instantiate(&registry, &module, CapabilitySet::EMPTY)
+            .await
+            .expect("instantiate");
+        pool.put(&key, current, warm).expect("put");
+        let same = PoolKey::new("worker.wasm", CapabilitySet::from([Capability::TimeRead]));
+        assert!(
+            pool.take(&same, &registry, current)
+                .expect("take")
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,932 @@
+//! C bindings for guests written in languages other than Rust.
+//!
+//! [`CHeader`] renders `selium.h`: the `create`/`poll`/`drop` imports of every hostcall, the
+//! poll result and mailbox constants, and the archived layout of each payload that C can
+//! express. [`C_HELPERS`] is `selium.c`, a small library that builds and reads those payloads
+//! and blocks on a hostcall until it completes. Layouts are measured from the archived Rust
+//! types and asserted in the header, so a C compiler rejects bindings that have drifted from the
+//! host they were generated by.
+
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    mem::{align_of, offset_of, size_of},
+};
+
+use rkyv::Archived;
+
+use crate::{
+    BarrierWait, Capability, ChannelBackpressure, ChannelCreate, CompressDeflate, CompressInflate,
+    CompressionAlgorithm, CompressionLevel, CounterAdd, DEFAULT_BUFFER_BASE,
+    DRIVER_ERROR_MESSAGE_CODE, DRIVER_RESULT_CHUNK, DRIVER_RESULT_PENDING, DRIVER_RESULT_READY_MAX,
+    DriverErrorPayload, ErrorCode, FsEvent, FsEventKind, FsWatch, GaugeAdd, GaugeSet, HostInfo,
+    IdempotencyKey, IoFrame, IoRead, IoWrite, LatchRequest, LockAcquire, LockRelease, NetAccept,
+    NetAcceptReply, NetConnect, NetConnectReply, NetCreateListener, NetCreateListenerReply,
+    NetProtocol, NetTlsConfigReply, PayloadEncoding, ProcessInfo, ProcessLogLookup,
+    ProcessLogRegistration, ProcessMessage, ProcessPanic, ProcessSelfStats, ServiceHealth,
+    ServiceHealthUpdate, ServiceInstance, ServiceLookup, ServiceRegister, SessionCreate,
+    SessionEntitlement, SessionRemove, SessionResource, SingletonLookup, SingletonRegister,
+    TimeNow, TimeSleep, TrapReason, WORD_SIZE, WatchCreate, WatchSet, WatchSubscribe, WatchValue,
+    hostcalls::{
+        self, DEFAULT_MAX_PAYLOAD, HostcallMeta, META_DIAGNOSTICS, META_ENCODING, META_HOST_INFO,
+        META_HOSTCALLS, META_IDEMPOTENCY_KEY, META_READY, PROCESS_COMPLETE, PROCESS_RECEIVE,
+        PROCESS_REPORT_PANIC, PROCESS_SELF_STATS,
+    },
+    mailbox,
+};
+
+macro_rules! c_payloads {
+    ($(
+        $ty:ident => $c:literal { $( $field:ident: $kind:expr ),+ $(,)? }
+    ),+ $(,)?) => {
+        &[$(
+            CPayload {
+                rust: stringify!($ty),
+                c: $c,
+                size: size_of::<Archived<$ty>>(),
+                align: align_of::<Archived<$ty>>(),
+                fields: &[$(
+                    CField {
+                        name: stringify!($field),
+                        kind: $kind,
+                        offset: offset_of!(Archived<$ty>, $field),
+                    },
+                )+],
+            },
+        )+]
+    };
+}
+
+/// File name the header is written to; [`C_HELPERS`] includes it by this name.
+pub const HEADER_FILE: &str = "selium.h";
+/// File name the helper library is written to.
+pub const HELPERS_FILE: &str = "selium.c";
+/// Source of the C helper library declared at the end of the header.
+pub const C_HELPERS: &str = include_str!("c_bindings/selium.c");
+
+/// Import module of the host function that parks a guest until its mailbox is signalled.
+const ASYNC_MODULE: &str = "selium::async";
+
+/// Hostcalls linked into every instance, with their input and output payload types.
+const META_HOSTCALLS_PAYLOAD_TYPES: [(&str, &str, &str); 10] = [
+    (META_HOSTCALLS, "()", "Vec<String>"),
+    (META_IDEMPOTENCY_KEY, "IdempotencyKey", "()"),
+    (META_READY, "()", "()"),
+    (META_DIAGNOSTICS, "()", "InstanceDiagnostics"),
+    (META_ENCODING, "PayloadEncoding", "PayloadEncoding"),
+    (META_HOST_INFO, "()", "HostInfo"),
+    (PROCESS_COMPLETE, "Vec<u8>", "()"),
+    (PROCESS_RECEIVE, "()", "Vec<u8>"),
+    (PROCESS_SELF_STATS, "()", "ProcessSelfStats"),
+    (PROCESS_REPORT_PANIC, "ProcessPanic", "()"),
+];
+
+/// Archived payloads whose layout the header declares, with their fields.
+const PAYLOADS: &[CPayload] = c_payloads! {
+    SessionCreate => "selium_session_create" {
+        session_id: CKind::U32,
+        pubkey: CKind::Bytes32,
+    },
+    SessionEntitlement => "selium_session_entitlement" {
+        session_id: CKind::U32,
+        target_id: CKind::U32,
+        capability: CKind::Enum("selium_capability"),
+    },
+    SessionResource => "selium_session_resource" {
+        session_id: CKind::U32,
+        target_id: CKind::U32,
+        capability: CKind::Enum("selium_capability"),
+        resource_id: CKind::U64,
+    },
+    SessionRemove => "selium_session_remove" {
+        session_id: CKind::U32,
+        target_id: CKind::U32,
+    },
+    ChannelCreate => "selium_channel_create" {
+        capacity: CKind::U32,
+        backpressure: CKind::Enum("selium_channel_backpressure"),
+    },
+    IoRead => "selium_io_read" {
+        handle: CKind::U32,
+        len: CKind::U32,
+    },
+    IoWrite => "selium_io_write" {
+        handle: CKind::U32,
+        payload: CKind::Bytes,
+    },
+    IoFrame => "selium_io_frame" {
+        writer_id: CKind::U16,
+        payload: CKind::Bytes,
+    },
+    NetCreateListener => "selium_net_create_listener" {
+        protocol: CKind::Enum("selium_net_protocol"),
+        domain: CKind::String,
+        port: CKind::U16,
+        tls: CKind::OptionU64,
+    },
+    NetCreateListenerReply => "selium_net_create_listener_reply" {
+        handle: CKind::U64,
+    },
+    NetAccept => "selium_net_accept" {
+        handle: CKind::U64,
+    },
+    NetAcceptReply => "selium_net_accept_reply" {
+        reader: CKind::U64,
+        writer: CKind::U64,
+        remote_addr: CKind::String,
+    },
+    NetConnect => "selium_net_connect" {
+        protocol: CKind::Enum("selium_net_protocol"),
+        domain: CKind::String,
+        port: CKind::U16,
+        tls: CKind::OptionU64,
+    },
+    NetConnectReply => "selium_net_connect_reply" {
+        reader: CKind::U64,
+        writer: CKind::U64,
+        remote_addr: CKind::String,
+    },
+    NetTlsConfigReply => "selium_net_tls_config_reply" {
+        handle: CKind::U64,
+    },
+    ProcessLogRegistration => "selium_process_log_registration" {
+        channel: CKind::U64,
+    },
+    ProcessLogLookup => "selium_process_log_lookup" {
+        process_id: CKind::U64,
+    },
+    ProcessMessage => "selium_process_message" {
+        process_id: CKind::U64,
+        payload: CKind::Bytes,
+    },
+    ProcessInfo => "selium_process_info" {
+        fuel_consumed: CKind::OptionU64,
+        trap: CKind::OptionEnum("selium_trap_reason"),
+    },
+    ProcessPanic => "selium_process_panic" {
+        message: CKind::String,
+        location: CKind::String,
+    },
+    ProcessSelfStats => "selium_process_self_stats" {
+        memory_bytes: CKind::OptionU64,
+        fuel_consumed: CKind::OptionU64,
+        hostcalls: CKind::U64,
+        pending_futures: CKind::U32,
+    },
+    SingletonRegister => "selium_singleton_register" {
+        id: CKind::Bytes32,
+        name: CKind::String,
+        resource: CKind::U64,
+    },
+    SingletonLookup => "selium_singleton_lookup" {
+        id: CKind::Bytes32,
+    },
+    ServiceRegister => "selium_service_register" {
+        id: CKind::Bytes32,
+        name: CKind::String,
+        resource: CKind::U64,
+    },
+    ServiceHealthUpdate => "selium_service_health_update" {
+        id: CKind::Bytes32,
+        resource: CKind::U64,
+        health: CKind::Enum("selium_service_health"),
+    },
+    ServiceLookup => "selium_service_lookup" {
+        id: CKind::Bytes32,
+    },
+    ServiceInstance => "selium_service_instance" {
+        resource: CKind::U64,
+        health: CKind::Enum("selium_service_health"),
+    },
+    CompressDeflate => "selium_compress_deflate" {
+        algorithm: CKind::Enum("selium_compression_algorithm"),
+        level: CKind::Enum("selium_compression_level"),
+        data: CKind::Bytes,
+    },
+    CompressInflate => "selium_compress_inflate" {
+        algorithm: CKind::Enum("selium_compression_algorithm"),
+        data: CKind::Bytes,
+    },
+    FsWatch => "selium_fs_watch" {
+        path: CKind::String,
+        recursive: CKind::Bool,
+    },
+    FsEvent => "selium_fs_event" {
+        kind: CKind::Enum("selium_fs_event_kind"),
+        path: CKind::String,
+    },
+    TimeNow => "selium_time_now" {
+        unix_ms: CKind::U64,
+        monotonic_ms: CKind::U64,
+    },
+    TimeSleep => "selium_time_sleep" {
+        duration_ms: CKind::U64,
+    },
+    BarrierWait => "selium_barrier_wait" {
+        name: CKind::String,
+        parties: CKind::U32,
+    },
+    LatchRequest => "selium_latch_request" {
+        name: CKind::String,
+        count: CKind::U32,
+    },
+    LockAcquire => "selium_lock_acquire" {
+        name: CKind::String,
+        ttl_ms: CKind::U64,
+    },
+    LockRelease => "selium_lock_release" {
+        name: CKind::String,
+        token: CKind::U64,
+    },
+    CounterAdd => "selium_counter_add" {
+        name: CKind::String,
+        delta: CKind::U64,
+    },
+    GaugeSet => "selium_gauge_set" {
+        name: CKind::String,
+        value: CKind::I64,
+    },
+    GaugeAdd => "selium_gauge_add" {
+        name: CKind::String,
+        delta: CKind::I64,
+    },
+    WatchCreate => "selium_watch_create" {
+        value: CKind::Bytes,
+    },
+    WatchSet => "selium_watch_set" {
+        watch: CKind::U64,
+        value: CKind::Bytes,
+    },
+    WatchSubscribe => "selium_watch_subscribe" {
+        watch: CKind::U64,
+        seen: CKind::U64,
+    },
+    WatchValue => "selium_watch_value" {
+        version: CKind::U64,
+        value: CKind::Bytes,
+    },
+    IdempotencyKey => "selium_idempotency_key" {
+        key: CKind::String,
+    },
+    HostInfo => "selium_host_info" {
+        os_family: CKind::String,
+        arch: CKind::String,
+        locale: CKind::String,
+        hostname: CKind::String,
+    },
+    DriverErrorPayload => "selium_driver_error_payload" {
+        code: CKind::U16,
+        message: CKind::String,
+    },
+};
+
+/// Renders `selium.h`, the C header for the hostcall catalogue of this build.
+///
+/// The header is written for clang's `wasm32` and `wasm64` targets; its payload layouts hold
+/// for any C compiler with natural alignment.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CHeader;
+
+/// How a field of an archived payload is represented in C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CKind {
+    U16,
+    U32,
+    U64,
+    I64,
+    /// An archived `bool`, one byte holding 0 or 1.
+    Bool,
+    /// A unit-only enum, archived as its one-byte discriminant; names the C enum.
+    Enum(&'static str),
+    /// A 32-byte array, such as a key or [`crate::DependencyId`].
+    Bytes32,
+    /// An archived `Vec<u8>`.
+    Bytes,
+    /// An archived `String`.
+    String,
+    /// An archived `Option<u64>`.
+    OptionU64,
+    /// An archived `Option` of a unit-only enum; names the C enum.
+    OptionEnum(&'static str),
+}
+
+/// A field of an archived payload.
+struct CField {
+    name: &'static str,
+    kind: CKind,
+    offset: usize,
+}
+
+/// An archived payload and its layout, as measured from its Rust type.
+struct CPayload {
+    rust: &'static str,
+    c: &'static str,
+    size: usize,
+    align: usize,
+    fields: &'static [CField],
+}
+
+/// A hostcall import module and the payloads it exchanges.
+struct CHostcall<'a> {
+    module: &'static str,
+    /// Capability the hostcall requires, if any.
+    capability: Option<Capability>,
+    max_input: usize,
+    max_output: usize,
+    input: &'a str,
+    output: &'a str,
+}
+
+/// A C enum mirroring a Rust enum.
+struct CEnum {
+    name: &'static str,
+    doc: &'static str,
+    variants: Vec<(String, u16)>,
+}
+
+impl CKind {
+    /// C type of the field, and the array suffix following its name.
+    const fn declaration(self) -> (&'static str, &'static str) {
+        match self {
+            Self::U16 => ("uint16_t", ""),
+            Self::U32 => ("uint32_t", ""),
+            Self::U64 => ("uint64_t", ""),
+            Self::I64 => ("int64_t", ""),
+            Self::Bool => ("uint8_t", ""),
+            Self::Enum(_) => ("uint8_t", ""),
+            Self::Bytes32 => ("uint8_t", "[32]"),
+            Self::Bytes => ("selium_bytes_t", ""),
+            Self::String => ("selium_string_t", ""),
+            Self::OptionU64 => ("selium_option_u64_t", ""),
+            Self::OptionEnum(_) => ("selium_option_u8_t", ""),
+        }
+    }
+}
+
+impl CEnum {
+    fn new<T: Debug>(
+        name: &'static str,
+        doc: &'static str,
+        variants: impl IntoIterator<Item = T>,
+        value: impl Fn(&T) -> u16,
+    ) -> Self {
+        Self {
+            name,
+            doc,
+            variants: variants
+                .into_iter()
+                .map(|variant| {
+                    (
+                        screaming_snake_case(&format!("{variant:?}")),
+                        value(&variant),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn all() -> [Self; 10] {
+        [
+            Self::new(
+                "selium_capability",
+                "Capabilities a hostcall may require.",
+                Capability::ALL,
+                |capability| u16::from(*capability as u8),
+            ),
+            Self::new(
+                "selium_error_code",
+                "Codes classifying a failed hostcall, as reported by selium_driver_error.",
+                ErrorCode::ALL,
+                |code| code.code(),
+            ),
+            Self::new(
+                "selium_channel_backpressure",
+                "Behaviour of channel writers when the channel is full.",
+                [ChannelBackpressure::Park, ChannelBackpressure::Drop],
+                |backpressure| u16::from(*backpressure as u8),
+            ),
+            Self::new(
+                "selium_net_protocol",
+                "Network transport protocols.",
+                [NetProtocol::Quic, NetProtocol::Http, NetProtocol::Https],
+                |protocol| u16::from(*protocol as u8),
+            ),
+            Self::new(
+                "selium_payload_encoding",
+                "Encodings of hostcall payloads, negotiated through selium::meta::encoding.",
+                [PayloadEncoding::Rkyv, PayloadEncoding::Json],
+                |encoding| u16::from(*encoding as u8),
+            ),
+            Self::new(
+                "selium_service_health",
+                "Health states of a service instance in the directory.",
+                ServiceHealth::ALL,
+                |health| u16::from(*health as u8),
+            ),
+            Self::new(
+                "selium_trap_reason",
+                "Reasons a process trapped, as reported by selium::process::info.",
+                TrapReason::ALL,
+                |reason| u16::from(*reason as u8),
+            ),
+            Self::new(
+                "selium_compression_algorithm",
+                "Formats selium::compress payloads are compressed in.",
+                CompressionAlgorithm::ALL,
+                |algorithm| u16::from(*algorithm as u8),
+            ),
+            Self::new(
+                "selium_compression_level",
+                "Effort selium::compress::deflate spends shrinking a payload.",
+                CompressionLevel::ALL,
+                |level| u16::from(*level as u8),
+            ),
+            Self::new(
+                "selium_fs_event_kind",
+                "How a file watched through selium::fs::watch changed.",
+                FsEventKind::ALL,
+                |kind| u16::from(*kind as u8),
+            ),
+        ]
+    }
+}
+
+impl<'a> CHostcall<'a> {
+    fn new(meta: &HostcallMeta, input: &'a str, output: &'a str) -> Self {
+        Self {
+            module: meta.name,
+            capability: Some(meta.capability),
+            max_input: meta.max_input,
+            max_output: meta.max_output,
+            input,
+            output,
+        }
+    }
+}
+
+impl Display for CHostcall<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let module = self.module;
+        let ident = c_identifier(module);
+        match self.capability {
+            Some(capability) => writeln!(
+                f,
+                "/* {module}: requires SELIUM_CAPABILITY_{}.",
+                screaming_snake_case(&capability.to_string())
+            )?,
+            None => writeln!(f, "/* {module}: linked into every instance.")?,
+        }
+        writeln!(
+            f,
+            " * Input: {}, at most {} bytes.",
+            c_payload_type(self.input),
+            self.max_input
+        )?;
+        writeln!(
+            f,
+            " * Output: {}, at most {} bytes. */",
+            c_payload_type(self.output),
+            self.max_output
+        )?;
+        writeln!(f, "SELIUM_IMPORT(\"{module}\", \"create\")")?;
+        writeln!(
+            f,
+            "uint32_t {ident}_create(const void *args, size_t args_len);"
+        )?;
+        writeln!(f, "SELIUM_IMPORT(\"{module}\", \"poll\")")?;
+        writeln!(
+            f,
+            "uint32_t {ident}_poll(uint32_t handle, uint32_t task_id, void *result, size_t result_len);"
+        )?;
+        writeln!(f, "SELIUM_IMPORT(\"{module}\", \"drop\")")?;
+        writeln!(
+            f,
+            "uint32_t {ident}_drop(uint32_t handle, void *result, size_t result_len);"
+        )?;
+        writeln!(f)
+    }
+}
+
+impl Display for CEnum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "/* {} */", self.doc)?;
+        writeln!(f, "enum {} {{", self.name)?;
+        let prefix = self.name.to_ascii_uppercase();
+        for (variant, value) in &self.variants {
+            writeln!(f, "    {prefix}_{variant} = {value},")?;
+        }
+        writeln!(f, "}};")?;
+        writeln!(f)
+    }
+}
+
+impl Display for CPayload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = self.c;
+        writeln!(f, "/* Archived `{}`. */", self.rust)?;
+        writeln!(f, "typedef struct {name} {{")?;
+        for field in self.fields {
+            let (ty, suffix) = field.kind.declaration();
+            match field.kind {
+                CKind::Enum(c_enum) | CKind::OptionEnum(c_enum) => {
+                    writeln!(f, "    {ty} {}{suffix}; /* enum {c_enum} */", field.name)?
+                }
+                _ => writeln!(f, "    {ty} {}{suffix};", field.name)?,
+            }
+        }
+        writeln!(f, "}} {name}_t;")?;
+        writeln!(
+            f,
+            "SELIUM_ASSERT_LAYOUT(sizeof({name}_t) == {}, \"{name}_t size\");",
+            self.size
+        )?;
+        writeln!(
+            f,
+            "SELIUM_ASSERT_LAYOUT(SELIUM_ALIGNOF({name}_t) == {}, \"{name}_t alignment\");",
+            self.align
+        )?;
+        for field in self.fields {
+            writeln!(
+                f,
+                "SELIUM_ASSERT_LAYOUT(offsetof({name}_t, {field}) == {offset}, \"{name}_t.{field} offset\");",
+                field = field.name,
+                offset = field.offset,
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl Display for CHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(PROLOGUE)?;
+        writeln!(f, "/* Size in bytes of a guest machine word. */")?;
+        writeln!(f, "#define SELIUM_WORD_SIZE {WORD_SIZE}u")?;
+        writeln!(
+            f,
+            "/* Largest payload a hostcall accepts or returns unless noted otherwise. */"
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_DEFAULT_MAX_PAYLOAD {DEFAULT_MAX_PAYLOAD}u"
+        )?;
+        writeln!(f)?;
+        writeln!(f, "/* Poll result words. */")?;
+        writeln!(
+            f,
+            "#define SELIUM_DRIVER_RESULT_READY_MAX {DRIVER_RESULT_READY_MAX:#x}u"
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_DRIVER_RESULT_PENDING {DRIVER_RESULT_PENDING:#x}u"
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_DRIVER_RESULT_CHUNK {DRIVER_RESULT_CHUNK:#x}u"
+        )?;
+        writeln!(
+            f,
+            "/* Error code meaning the result buffer holds a length-prefixed driver error payload. */"
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_DRIVER_ERROR_MESSAGE_CODE {DRIVER_ERROR_MESSAGE_CODE}u"
+        )?;
+        f.write_str(POLL_MACROS)?;
+        writeln!(
+            f,
+            "/* Layout of the wake-up mailbox at the start of linear memory. */"
+        )?;
+        writeln!(f, "#define SELIUM_MAILBOX_CAPACITY {}u", mailbox::CAPACITY)?;
+        writeln!(
+            f,
+            "#define SELIUM_MAILBOX_SLOT_SIZE {}u",
+            mailbox::SLOT_SIZE
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_MAILBOX_FLAG_OFFSET {}u",
+            mailbox::FLAG_OFFSET
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_MAILBOX_HEAD_OFFSET {}u",
+            mailbox::HEAD_OFFSET
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_MAILBOX_TAIL_OFFSET {}u",
+            mailbox::TAIL_OFFSET
+        )?;
+        writeln!(
+            f,
+            "#define SELIUM_MAILBOX_RING_OFFSET {}u",
+            mailbox::RING_OFFSET
+        )?;
+        writeln!(f, "#define SELIUM_MAILBOX_BYTES {DEFAULT_BUFFER_BASE}u")?;
+        writeln!(f)?;
+
+        for c_enum in CEnum::all() {
+            write!(f, "{c_enum}")?;
+        }
+        f.write_str(ARCHIVED_PRIMITIVES)?;
+        for payload in PAYLOADS {
+            write!(f, "{payload}")?;
+        }
+
+        writeln!(f, "/* Hostcall imports. */")?;
+        writeln!(f)?;
+        for (meta, (input, output)) in hostcalls::ALL.iter().zip(hostcalls::PAYLOAD_TYPES) {
+            write!(f, "{}", CHostcall::new(meta, input, output))?;
+        }
+        for (module, input, output) in META_HOSTCALLS_PAYLOAD_TYPES {
+            let hostcall = CHostcall {
+                module,
+                capability: None,
+                max_input: DEFAULT_MAX_PAYLOAD,
+                max_output: DEFAULT_MAX_PAYLOAD,
+                input,
+                output,
+            };
+            write!(f, "{hostcall}")?;
+        }
+        writeln!(
+            f,
+            "/* Park until the host signals the wake-up mailbox. */\nSELIUM_IMPORT(\"{ASYNC_MODULE}\", \"yield_now\")\nvoid {}_yield_now(void);\n",
+            c_identifier(ASYNC_MODULE)
+        )?;
+        f.write_str(EPILOGUE)
+    }
+}
+
+/// Describe the C form of a payload named by its Rust type.
+fn c_payload_type(rust: &str) -> String {
+    match rust {
+        "()" => "none".to_string(),
+        "u32" | "GuestUint" => "uint32_t".to_string(),
+        "u64" => "uint64_t".to_string(),
+        "i64" => "int64_t".to_string(),
+        "GuestResourceId" => "uint64_t resource id".to_string(),
+        "PayloadEncoding" => "uint8_t (enum selium_payload_encoding)".to_string(),
+        "Vec<u8>" => "selium_bytes_t".to_string(),
+        "Vec<ServiceInstance>" => {
+            "selium_bytes_t whose len counts selium_service_instance_t entries".to_string()
+        }
+        _ => PAYLOADS
+            .iter()
+            .find(|payload| payload.rust == rust)
+            .map(|payload| format!("{}_t", payload.c))
+            .unwrap_or_else(|| format!("archived `{rust}`, which has no C layout")),
+    }
+}
+
+/// C identifier for a hostcall import module, such as `selium_time_now` for
+/// `selium::time::now`.
+fn c_identifier(module: &str) -> String {
+    module.replace("::", "_")
+}
+
+/// Convert a Rust variant name such as `NetQuicBind` to `NET_QUIC_BIND`.
+fn screaming_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (index, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && index > 0 {
+            out.push('_');
+        }
+        out.push(ch.to_ascii_uppercase());
+    }
+    out
+}
+
+const PROLOGUE: &str = r#"/* selium.h: hostcall bindings for C guests.
+ *
+ * Generated by `selium-runtime generate-c-bindings`; do not edit. Regenerate when the host is
+ * upgraded: the layout assertions below pin the host these bindings came from.
+ *
+ * Every hostcall is a driver with `create`, `poll` and `drop` imports. `create` takes the
+ * encoded input and returns a handle; `poll` writes the output to the result buffer once it is
+ * ready and returns a poll result word (see SELIUM_POLL_IS_READY and friends); `drop` cancels a
+ * call that is no longer wanted. selium_block_on polls a handle until the call completes.
+ *
+ * Payloads are rkyv archives. The root struct sits at the very end of the buffer, and the bytes
+ * of any string or byte vector it refers to must come before it. Build inputs with
+ * selium_payload_t and read outputs with SELIUM_PAYLOAD_ROOT. Buffers should be aligned to 8.
+ *
+ * The host keeps its wake-up mailbox in the first SELIUM_MAILBOX_BYTES of linear memory, so link
+ * with a global base beyond it (for example -Wl,--global-base=65536) and export `memory`.
+ */
+
+#ifndef SELIUM_H
+#define SELIUM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#define SELIUM_ASSERT_LAYOUT(cond, msg) static_assert(cond, msg)
+#define SELIUM_ALIGNOF(type) alignof(type)
+#else
+#define SELIUM_ASSERT_LAYOUT(cond, msg) _Static_assert(cond, msg)
+#define SELIUM_ALIGNOF(type) _Alignof(type)
+#endif
+
+#define SELIUM_IMPORT(module, name) __attribute__((import_module(module), import_name(name)))
+
+"#;
+
+const POLL_MACROS: &str = r#"
+/* The call completed and the result buffer holds `word` bytes of output. */
+#define SELIUM_POLL_IS_READY(word) ((word) <= SELIUM_DRIVER_RESULT_READY_MAX)
+/* The call has not completed; poll again once the mailbox is signalled. */
+#define SELIUM_POLL_IS_PENDING(word) ((word) == SELIUM_DRIVER_RESULT_PENDING)
+/* The result buffer is full with the next part of an output too large for it; keep the bytes
+ * and poll again straight away for the rest. */
+#define SELIUM_POLL_IS_CHUNK(word) ((word) == SELIUM_DRIVER_RESULT_CHUNK)
+/* The call failed; SELIUM_POLL_ERROR_CODE gives the error code. */
+#define SELIUM_POLL_IS_ERROR(word) \
+    ((word) > SELIUM_DRIVER_RESULT_PENDING && (word) != SELIUM_DRIVER_RESULT_CHUNK)
+#define SELIUM_POLL_ERROR_CODE(word) ((word) & SELIUM_DRIVER_RESULT_READY_MAX)
+
+"#;
+
+const ARCHIVED_PRIMITIVES: &str = r#"/* An archived byte vector. `offset` is relative to the field itself; use selium_bytes_set and
+ * selium_bytes_get rather than reading it directly. */
+typedef struct selium_bytes {
+    int32_t offset;
+    uint32_t len;
+} selium_bytes_t;
+SELIUM_ASSERT_LAYOUT(sizeof(selium_bytes_t) == 8, "selium_bytes_t size");
+
+/* An archived string: up to 8 bytes inline, longer strings out of line. Use selium_string_set
+ * and selium_string_get rather than reading it directly. */
+typedef union selium_string {
+    uint8_t inline_bytes[8];
+    struct {
+        uint32_t len;
+        int32_t offset;
+    } out_of_line;
+} selium_string_t;
+SELIUM_ASSERT_LAYOUT(sizeof(selium_string_t) == 8, "selium_string_t size");
+
+/* An archived optional 64-bit value; `value` is meaningful only when `some` is 1. */
+typedef struct selium_option_u64 {
+    uint8_t some;
+    uint64_t value;
+} selium_option_u64_t;
+SELIUM_ASSERT_LAYOUT(sizeof(selium_option_u64_t) == 16, "selium_option_u64_t size");
+
+/* An archived optional enum; `value` is meaningful only when `some` is 1. */
+typedef struct selium_option_u8 {
+    uint8_t some;
+    uint8_t value;
+} selium_option_u8_t;
+SELIUM_ASSERT_LAYOUT(sizeof(selium_option_u8_t) == 2, "selium_option_u8_t size");
+
+"#;
+
+const EPILOGUE: &str = r#"/* Helpers, implemented in selium.c. */
+
+/* Poll import of a hostcall, such as selium_time_now_poll. */
+typedef uint32_t (*selium_poll_fn)(uint32_t handle, uint32_t task_id, void *result, size_t result_len);
+
+/* A payload being built in a caller-supplied buffer. */
+typedef struct selium_payload {
+    uint8_t *buf;
+    size_t capacity;
+    size_t len;
+} selium_payload_t;
+
+/* Start building a payload in `buf`. */
+void selium_payload_init(selium_payload_t *payload, void *buf, size_t capacity);
+/* Append out-of-line bytes, such as a string or byte vector the root refers to. Returns where
+ * they were copied to, or NULL if the buffer is full. */
+void *selium_payload_push(selium_payload_t *payload, const void *data, size_t len);
+/* Reserve the zeroed root struct, which must come last. Returns NULL if the buffer is full; on
+ * success the payload to pass to `create` is `payload->buf`, `payload->len` bytes long. */
+void *selium_payload_root(selium_payload_t *payload, size_t size, size_t align);
+#define SELIUM_PAYLOAD_NEW_ROOT(payload, type) \
+    ((type *)selium_payload_root((payload), sizeof(type), SELIUM_ALIGNOF(type)))
+/* The root struct of an archived output `len` bytes long. */
+#define SELIUM_PAYLOAD_ROOT(type, buf, len) \
+    ((const type *)((const uint8_t *)(buf) + (len) - sizeof(type)))
+
+/* Point a byte vector field at `len` bytes already pushed into the same payload. */
+void selium_bytes_set(selium_bytes_t *field, const void *data, uint32_t len);
+/* The bytes of an archived byte vector. */
+const uint8_t *selium_bytes_get(const selium_bytes_t *field, uint32_t *len);
+/* Set a string field. Strings longer than 8 bytes must already have been pushed into the same
+ * payload; shorter ones are copied inline from anywhere. */
+void selium_string_set(selium_string_t *field, const char *data, uint32_t len);
+/* The bytes of an archived string, which are not NUL-terminated. */
+const char *selium_string_get(const selium_string_t *field, uint32_t *len);
+
+/* Read the driver error payload a failed call with SELIUM_DRIVER_ERROR_MESSAGE_CODE left in its
+ * result buffer. Returns 0 on success and -1 if the buffer does not hold one. */
+int selium_driver_error(
+    const void *result,
+    size_t result_len,
+    uint16_t *code,
+    const char **message,
+    uint32_t *message_len
+);
+
+/* Poll `handle` until its call is no longer pending, parking between polls, and return the
+ * final poll result word. Wake-ups queued in the mailbox meanwhile are discarded. */
+uint32_t selium_block_on(uint32_t handle, selium_poll_fn poll, void *result, size_t result_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SELIUM_H */
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode_rkyv;
+
+    /// Size and alignment of a field kind under C's natural alignment rules.
+    fn c_layout(kind: CKind) -> (usize, usize) {
+        match kind {
+            CKind::U16 => (2, 2),
+            CKind::U32 => (4, 4),
+            CKind::U64 | CKind::I64 => (8, 8),
+            CKind::Bool | CKind::Enum(_) => (1, 1),
+            CKind::Bytes32 => (32, 1),
+            CKind::Bytes | CKind::String => (8, 4),
+            CKind::OptionU64 => (16, 8),
+            CKind::OptionEnum(_) => (2, 1),
+        }
+    }
+
+    #[test]
+    fn c_structs_lay_out_like_the_archived_payloads() {
+        for payload in PAYLOADS {
+            let mut offset = 0usize;
+            let mut align = 1;
+            for field in payload.fields {
+                let (size, field_align) = c_layout(field.kind);
+                offset = offset.next_multiple_of(field_align);
+                assert_eq!(offset, field.offset, "{}.{}", payload.rust, field.name);
+                offset += size;
+                align = align.max(field_align);
+            }
+            assert_eq!(
+                offset.next_multiple_of(align),
+                payload.size,
+                "{}",
+                payload.rust
+            );
+            assert_eq!(align, payload.align, "{}", payload.rust);
+        }
+    }
+
+    #[test]
+    fn c_enums_match_archived_discriminants() {
+        assert_eq!(
+            encode_rkyv(&NetProtocol::Https).expect("encode"),
+            [NetProtocol::Https as u8]
+        );
+        assert_eq!(
+            encode_rkyv(&ChannelBackpressure::Drop).expect("encode"),
+            [ChannelBackpressure::Drop as u8]
+        );
+        assert_eq!(
+            encode_rkyv(&PayloadEncoding::Json).expect("encode"),
+            [PayloadEncoding::Json as u8]
+        );
+        assert_eq!(
+            encode_rkyv(&ServiceHealth::Unhealthy).expect("encode"),
+            [ServiceHealth::Unhealthy as u8]
+        );
+        assert_eq!(
+            encode_rkyv(&Capability::TimeRead).expect("encode"),
+            [Capability::TimeRead as u8]
+        );
+        assert_eq!(
+            encode_rkyv(&Some(TrapReason::FuelExhausted)).expect("encode"),
+            [1, TrapReason::FuelExhausted as u8]
+        );
+    }
+
+    #[test]
+    fn header_declares_every_hostcall() {
+        let header = CHeader.to_string();
+        for meta in hostcalls::ALL {
+            let ident = c_identifier(meta.name);
+            for hook in ["create", "poll", "drop"] {
+                assert!(
+                    header.contains(&format!("uint32_t {ident}_{hook}(")),
+                    "{ident}_{hook}"
+                );
+            }
+        }
+        assert!(header.contains("uint32_t selium_meta_ready_create("));
+        assert!(header.contains("SELIUM_CAPABILITY_NET_QUIC_BIND = 5,"));
+        assert!(header.contains("SELIUM_ERROR_CODE_MODULE_STORE_FILESYSTEM = 301,"));
+        assert!(header.contains(
+            "/* selium::time::sleep: requires SELIUM_CAPABILITY_TIME_READ.\n * Input: selium_time_sleep_t, at most 64 bytes."
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+/* selium.c: helpers for building and reading Selium hostcall payloads from C.
+ *
+ * Written by `selium-runtime generate-c-bindings` alongside selium.h; compile it into the
+ * guest next to the header it was generated with.
+ */
+
+#include "selium.h"
+
+#include <string.h>
+
+/* Bytes a string holds inline, before it must be stored out of line. */
+#define SELIUM_STRING_INLINE_CAPACITY 8u
+/* Tag bits set in the first byte of an out-of-line string's length. */
+#define SELIUM_STRING_OUT_OF_LINE_TAG 0x80u
+#define SELIUM_STRING_TAG_MASK 0xc0u
+
+static uint32_t *selium_mailbox_word(size_t offset) {
+    return (uint32_t *)(uintptr_t)offset;
+}
+
+/* Park until the host signals the wake-up mailbox, then discard the queued wake-ups: a caller
+ * blocked on a single hostcall polls it again whatever woke it. */
+static void selium_mailbox_wait(void) {
+    uint32_t *flag = selium_mailbox_word(SELIUM_MAILBOX_FLAG_OFFSET);
+    if (__atomic_load_n(flag, __ATOMIC_ACQUIRE) == 0) {
+        selium_async_yield_now();
+    }
+    uint32_t tail = __atomic_load_n(selium_mailbox_word(SELIUM_MAILBOX_TAIL_OFFSET), __ATOMIC_ACQUIRE);
+    __atomic_store_n(selium_mailbox_word(SELIUM_MAILBOX_HEAD_OFFSET), tail, __ATOMIC_RELEASE);
+    __atomic_store_n(flag, 0, __ATOMIC_RELAXED);
+}
+
+void selium_payload_init(selium_payload_t *payload, void *buf, size_t capacity) {
+    payload->buf = (uint8_t *)buf;
+    payload->capacity = capacity;
+    payload->len = 0;
+}
+
+void *selium_payload_push(selium_payload_t *payload, const void *data, size_t len) {
+    if (len > payload->capacity - payload->len) {
+        return NULL;
+    }
+    uint8_t *out = payload->buf + payload->len;
+    memcpy(out, data, len);
+    payload->len += len;
+    return out;
+}
+
+void *selium_payload_root(selium_payload_t *payload, size_t size, size_t align) {
+    size_t start = (payload->len + align - 1) & ~(align - 1);
+    if (start < payload->len || start > payload->capacity || size > payload->capacity - start) {
+        return NULL;
+    }
+    memset(payload->buf + payload->len, 0, start + size - payload->len);
+    payload->len = start + size;
+    return payload->buf + start;
+}
+
+void selium_bytes_set(selium_bytes_t *field, const void *data, uint32_t len) {
+    field->offset = (int32_t)((intptr_t)data - (intptr_t)field);
+    field->len = len;
+}
+
+const uint8_t *selium_bytes_get(const selium_bytes_t *field, uint32_t *len) {
+    *len = field->len;
+    return (const uint8_t *)field + field->offset;
+}
+
+void selium_string_set(selium_string_t *field, const char *data, uint32_t len) {
+    if (len <= SELIUM_STRING_INLINE_CAPACITY) {
+        memset(field->inline_bytes, 0xff, SELIUM_STRING_INLINE_CAPACITY);
+        memcpy(field->inline_bytes, data, len);
+        return;
+    }
+    field->out_of_line.len =
+        (len & 0x3fu) | SELIUM_STRING_OUT_OF_LINE_TAG | ((len & ~0x3fu) << 2);
+    field->out_of_line.offset = (int32_t)((intptr_t)data - (intptr_t)field);
+}
+
+const char *selium_string_get(const selium_string_t *field, uint32_t *len) {
+    if ((field->inline_bytes[0] & SELIUM_STRING_TAG_MASK) != SELIUM_STRING_OUT_OF_LINE_TAG) {
+        uint32_t used = 0;
+        while (used < SELIUM_STRING_INLINE_CAPACITY && field->inline_bytes[used] != 0xff) {
+            used++;
+        }
+        *len = used;
+        return (const char *)field->inline_bytes;
+    }
+    uint32_t raw = field->out_of_line.len;
+    *len = (raw & 0x3fu) | ((raw & ~0xffu) >> 2);
+    return (const char *)field + field->out_of_line.offset;
+}
+
+int selium_driver_error(
+    const void *result,
+    size_t result_len,
+    uint16_t *code,
+    const char **message,
+    uint32_t *message_len
+) {
+    uint32_t len;
+    if (result_len < sizeof(len)) {
+        return -1;
+    }
+    memcpy(&len, result, sizeof(len));
+    if (len < sizeof(selium_driver_error_payload_t) || len > result_len - sizeof(len)) {
+        return -1;
+    }
+    const selium_driver_error_payload_t *payload = SELIUM_PAYLOAD_ROOT(
+        selium_driver_error_payload_t, (const uint8_t *)result + sizeof(len), len
+    );
+    *code = payload->code;
+    *message = selium_string_get(&payload->message, message_len);
+    return 0;
+}
+
+uint32_t selium_block_on(uint32_t handle, selium_poll_fn poll, void *result, size_t result_len) {
+    for (;;) {
+        uint32_t word = poll(handle, handle, result, result_len);
+        if (!SELIUM_POLL_IS_PENDING(word)) {
+            return word;
+        }
+        selium_mailbox_wait();
+    }
+}
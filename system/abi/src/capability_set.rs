@@ -0,0 +1,200 @@
+//! Compact sets of [`Capability`] values.
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::Capability;
+
+/// A set of capabilities, stored as one bit per [`Capability`].
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct CapabilitySet(u64);
+
+/// Iterator over the capabilities of a [`CapabilitySet`], in identifier order.
+#[derive(Clone, Debug)]
+pub struct CapabilitySetIter {
+    remaining: u64,
+}
+
+impl CapabilitySet {
+    /// The set holding no capability.
+    pub const EMPTY: Self = Self(0);
+
+    /// The set holding every capability in [`Capability::ALL`].
+    pub fn all() -> Self {
+        Capability::ALL.into_iter().collect()
+    }
+
+    /// Whether the set holds `capability`.
+    pub const fn contains(self, capability: Capability) -> bool {
+        self.0 & Self::bit(capability) != 0
+    }
+
+    /// Add `capability`, returning whether it was absent.
+    pub fn insert(&mut self, capability: Capability) -> bool {
+        let absent = !self.contains(capability);
+        self.0 |= Self::bit(capability);
+        absent
+    }
+
+    /// Remove `capability`, returning whether it was present.
+    pub fn remove(&mut self, capability: Capability) -> bool {
+        let present = self.contains(capability);
+        self.0 &= !Self::bit(capability);
+        present
+    }
+
+    /// Whether every capability in `self` is also in `other`.
+    pub const fn is_subset(self, other: Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Whether every capability in `other` is also in `self`.
+    pub const fn is_superset(self, other: Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Capabilities in either set.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Capabilities in both sets.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Capabilities in `self` but not in `other`.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Whether the set holds no capability.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Number of capabilities in the set.
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterate over the capabilities in the set, in identifier order.
+    pub const fn iter(self) -> CapabilitySetIter {
+        CapabilitySetIter { remaining: self.0 }
+    }
+
+    const fn bit(capability: Capability) -> u64 {
+        1 << capability as u8
+    }
+}
+
+impl Iterator for CapabilitySetIter {
+    type Item = Capability;
+
+    fn next(&mut self) -> Option<Capability> {
+        while self.remaining != 0 {
+            let id = self.remaining.trailing_zeros();
+            self.remaining &= self.remaining - 1;
+            // Bits are only ever set from valid capabilities, but skip any that are not.
+            if let Ok(capability) = Capability::try_from(id as u8) {
+                return Some(capability);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining.count_ones() as usize))
+    }
+}
+
+impl IntoIterator for CapabilitySet {
+    type Item = Capability;
+    type IntoIter = CapabilitySetIter;
+
+    fn into_iter(self) -> CapabilitySetIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Capability> for CapabilitySet {
+    fn from_iter<I: IntoIterator<Item = Capability>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Capability> for CapabilitySet {
+    fn extend<I: IntoIterator<Item = Capability>>(&mut self, iter: I) {
+        for capability in iter {
+            self.insert(capability);
+        }
+    }
+}
+
+impl From<Capability> for CapabilitySet {
+    fn from(capability: Capability) -> Self {
+        Self(Self::bit(capability))
+    }
+}
+
+impl<const N: usize> From<[Capability; N]> for CapabilitySet {
+    fn from(capabilities: [Capability; N]) -> Self {
+        capabilities.into_iter().collect()
+    }
+}
+
+impl Debug for CapabilitySet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Display for CapabilitySet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, capability) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            Display::fmt(&capability, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_rkyv, encode_rkyv};
+
+    #[test]
+    fn sets_compare_iterate_and_display() {
+        let reader = CapabilitySet::from([Capability::ChannelReader, Capability::TimeRead]);
+        let mut both = reader;
+        assert!(both.insert(Capability::ChannelWriter));
+        assert!(!both.insert(Capability::ChannelWriter));
+
+        assert!(reader.is_subset(both));
+        assert!(both.is_superset(reader));
+        assert!(!both.is_subset(reader));
+        assert_eq!(both.difference(reader), Capability::ChannelWriter.into());
+        assert_eq!(both.len(), 3);
+        assert_eq!(
+            both.iter().collect::<Vec<_>>(),
+            [
+                Capability::ChannelReader,
+                Capability::ChannelWriter,
+                Capability::TimeRead
+            ]
+        );
+        assert_eq!(both.to_string(), "ChannelReader, ChannelWriter, TimeRead");
+        assert_eq!(CapabilitySet::all().len(), Capability::ALL.len());
+
+        let bytes = encode_rkyv(&both).expect("encode");
+        assert_eq!(decode_rkyv::<CapabilitySet>(&bytes).expect("decode"), both);
+    }
+}
@@ -0,0 +1,69 @@
+//! Compression hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Format a payload is compressed in.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum CompressionAlgorithm {
+    /// gzip (RFC 1952), for interoperating with HTTP and file tooling.
+    Gzip = 0,
+    /// Zstandard, which is faster and compresses better at comparable levels.
+    Zstd = 1,
+}
+
+/// How hard the host works to shrink a payload, mapped onto each algorithm's own levels.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum CompressionLevel {
+    /// Favour speed over size.
+    Fastest = 0,
+    /// The algorithm's default trade-off.
+    #[default]
+    Default = 1,
+    /// Favour size over speed.
+    Best = 2,
+}
+
+/// Payload used to compress data.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct CompressDeflate {
+    /// Format to compress into.
+    pub algorithm: CompressionAlgorithm,
+    /// Effort to spend compressing.
+    pub level: CompressionLevel,
+    /// Uncompressed bytes.
+    pub data: Vec<u8>,
+}
+
+/// Payload used to decompress data.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct CompressInflate {
+    /// Format `data` is compressed in.
+    pub algorithm: CompressionAlgorithm,
+    /// Compressed bytes.
+    pub data: Vec<u8>,
+}
+
+impl CompressionAlgorithm {
+    /// Every algorithm, in discriminant order.
+    pub const ALL: [CompressionAlgorithm; 2] =
+        [CompressionAlgorithm::Gzip, CompressionAlgorithm::Zstd];
+}
+
+impl CompressionLevel {
+    /// Every level, in discriminant order.
+    pub const ALL: [CompressionLevel; 3] = [
+        CompressionLevel::Fastest,
+        CompressionLevel::Default,
+        CompressionLevel::Best,
+    ];
+}
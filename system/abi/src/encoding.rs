@@ -0,0 +1,130 @@
+//! Encodings of hostcall payloads.
+//!
+//! Payloads are rkyv archives. Hosts and guests built with the `json` feature may instead agree,
+//! per instance, to exchange self-describing JSON, so that hostcall traffic can be inspected with
+//! standard tools while debugging.
+
+use rkyv::{
+    Archive, Deserialize, Serialize,
+    api::high::{HighDeserializer, HighValidator},
+    rancor::Error as RancorError,
+};
+use thiserror::Error;
+
+use crate::{RkyvEncode, RkyvError, decode_rkyv, encode_rkyv};
+
+/// Payloads that can be exchanged as JSON. Every type qualifies unless the `json` feature is
+/// enabled, in which case payloads must also implement serde's traits.
+#[cfg(feature = "json")]
+pub trait JsonPayload: serde::Serialize + serde::de::DeserializeOwned {}
+
+/// Payloads that can be exchanged as JSON. Every type qualifies unless the `json` feature is
+/// enabled, in which case payloads must also implement serde's traits.
+#[cfg(not(feature = "json"))]
+pub trait JsonPayload {}
+
+/// Encoding of an instance's hostcall payloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum PayloadEncoding {
+    /// rkyv archives, which every build supports.
+    #[default]
+    Rkyv,
+    /// Self-describing JSON, supported by builds with the `json` feature.
+    Json,
+}
+
+/// Errors returned when encoding or decoding a hostcall payload.
+#[derive(Debug, Error)]
+pub enum PayloadError {
+    /// The rkyv payload could not be encoded or decoded.
+    #[error(transparent)]
+    Rkyv(#[from] RkyvError),
+    /// The JSON payload could not be encoded or decoded.
+    #[error("json payload failed: {0}")]
+    Json(String),
+    /// This build cannot handle the encoding.
+    #[error("payload encoding {0:?} is not supported by this build")]
+    Unsupported(PayloadEncoding),
+}
+
+#[cfg(feature = "json")]
+impl<T> JsonPayload for T where T: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(not(feature = "json"))]
+impl<T: ?Sized> JsonPayload for T {}
+
+impl PayloadEncoding {
+    /// Whether this build can encode and decode payloads as `self`.
+    pub const fn is_supported(self) -> bool {
+        match self {
+            Self::Rkyv => true,
+            Self::Json => cfg!(feature = "json"),
+        }
+    }
+}
+
+/// Encode a hostcall payload as `encoding`.
+pub fn encode_payload<T>(value: &T, encoding: PayloadEncoding) -> Result<Vec<u8>, PayloadError>
+where
+    T: RkyvEncode + JsonPayload,
+{
+    match encoding {
+        PayloadEncoding::Rkyv => Ok(encode_rkyv(value)?),
+        #[cfg(feature = "json")]
+        PayloadEncoding::Json => {
+            serde_json::to_vec(value).map_err(|err| PayloadError::Json(err.to_string()))
+        }
+        #[cfg(not(feature = "json"))]
+        PayloadEncoding::Json => Err(PayloadError::Unsupported(encoding)),
+    }
+}
+
+/// Decode a hostcall payload encoded as `encoding`.
+pub fn decode_payload<T>(bytes: &[u8], encoding: PayloadEncoding) -> Result<T, PayloadError>
+where
+    T: Archive + JsonPayload + Sized,
+    for<'a> T::Archived: 'a
+        + Deserialize<T, HighDeserializer<RancorError>>
+        + rkyv::bytecheck::CheckBytes<HighValidator<'a, RancorError>>,
+{
+    match encoding {
+        PayloadEncoding::Rkyv => Ok(decode_rkyv(bytes)?),
+        #[cfg(feature = "json")]
+        PayloadEncoding::Json => {
+            serde_json::from_slice(bytes).map_err(|err| PayloadError::Json(err.to_string()))
+        }
+        #[cfg(not(feature = "json"))]
+        PayloadEncoding::Json => Err(PayloadError::Unsupported(encoding)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeSleep;
+
+    #[test]
+    fn payloads_round_trip_in_supported_encodings() {
+        let sleep = TimeSleep { duration_ms: 250 };
+        for encoding in [PayloadEncoding::Rkyv, PayloadEncoding::Json] {
+            let encoded = encode_payload(&sleep, encoding);
+            if !encoding.is_supported() {
+                assert!(matches!(encoded, Err(PayloadError::Unsupported(_))));
+                continue;
+            }
+            let decoded: TimeSleep =
+                decode_payload(&encoded.expect("encode"), encoding).expect("decode");
+            assert_eq!(decoded, sleep);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_payloads_are_self_describing() {
+        let encoded =
+            encode_payload(&TimeSleep { duration_ms: 250 }, PayloadEncoding::Json).expect("encode");
+        assert_eq!(encoded, br#"{"duration_ms":250}"#);
+    }
+}
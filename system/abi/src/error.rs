@@ -0,0 +1,174 @@
+//! Stable numeric error codes shared by host drivers and guests.
+//!
+//! Codes are part of the wire format: never renumber an existing variant, only append.
+
+use std::fmt::{Display, Formatter};
+
+use rkyv::{Archive, Deserialize, Serialize};
+use thiserror::Error;
+
+/// Canonical classification of a failed hostcall.
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ErrorCode {
+    /// The failure does not fit any other code.
+    Unknown = 0,
+
+    // Data errors
+    /// An argument was malformed or out of range.
+    InvalidArgument = 1,
+    /// Guest input contained invalid UTF-8.
+    InvalidUtf8 = 2,
+    /// A guest memory slice was out of bounds.
+    MemorySlice = 3,
+    /// The referenced resource does not exist.
+    NotFound = 4,
+    /// The caller is not entitled to perform this call.
+    PermissionDenied = 5,
+    /// A resource with the same identifier already exists.
+    AlreadyExists = 6,
+    /// An input or output payload exceeded the hostcall's size limit.
+    PayloadTooLarge = 7,
+    /// The hostcall did not complete within its execution timeout.
+    TimedOut = 8,
+
+    // System errors
+    /// The kernel failed internally.
+    Kernel = 100,
+    /// The kernel registry failed internally.
+    Registry = 101,
+    /// A capability provider failed.
+    Subsystem = 102,
+
+    // Sessions
+    /// A payload signature did not verify.
+    InvalidSignature = 110,
+    /// The session is not authorised to perform this action.
+    Unauthorised = 111,
+    /// A session was granted entitlements beyond its parent's.
+    EntitlementScope = 112,
+    /// A resource was revoked from an `Any` scope.
+    RevokeOnAny = 113,
+
+    // Processes
+    /// The awaited process executed an `unreachable` instruction, e.g. by panicking.
+    ProcessUnreachable = 120,
+    /// The awaited process ran out of linear memory.
+    ProcessOutOfMemory = 121,
+    /// The awaited process consumed all of its fuel.
+    ProcessFuelExhausted = 122,
+    /// The awaited process was interrupted at an epoch deadline.
+    ProcessInterrupted = 123,
+    /// The awaited process failed inside a hostcall.
+    ProcessHostcallFailed = 124,
+    /// The awaited process trapped for any other reason.
+    ProcessTrapped = 125,
+
+    // Module store
+    /// A module path failed validation.
+    InvalidModulePath = 300,
+    /// The module store could not read from its backing filesystem.
+    ModuleStoreFilesystem = 301,
+}
+
+/// Error payload written by the host when a hostcall fails.
+#[derive(Clone, Debug, Eq, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct DriverErrorPayload {
+    /// Numeric [`ErrorCode`] classifying the failure.
+    pub code: u16,
+    /// Human-readable description of the failure.
+    pub message: String,
+}
+
+/// Error produced when decoding an unknown numeric error code.
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("unknown error code {0}")]
+pub struct ErrorCodeDecodeError(pub u16);
+
+impl ErrorCode {
+    /// Every error code, in numeric order.
+    pub const ALL: [ErrorCode; 24] = [
+        ErrorCode::Unknown,
+        ErrorCode::InvalidArgument,
+        ErrorCode::InvalidUtf8,
+        ErrorCode::MemorySlice,
+        ErrorCode::NotFound,
+        ErrorCode::PermissionDenied,
+        ErrorCode::AlreadyExists,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::TimedOut,
+        ErrorCode::Kernel,
+        ErrorCode::Registry,
+        ErrorCode::Subsystem,
+        ErrorCode::InvalidSignature,
+        ErrorCode::Unauthorised,
+        ErrorCode::EntitlementScope,
+        ErrorCode::RevokeOnAny,
+        ErrorCode::ProcessUnreachable,
+        ErrorCode::ProcessOutOfMemory,
+        ErrorCode::ProcessFuelExhausted,
+        ErrorCode::ProcessInterrupted,
+        ErrorCode::ProcessHostcallFailed,
+        ErrorCode::ProcessTrapped,
+        ErrorCode::InvalidModulePath,
+        ErrorCode::ModuleStoreFilesystem,
+    ];
+
+    /// Numeric value of this code on the wire.
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+}
+
+impl DriverErrorPayload {
+    /// Build a payload from a code and message.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: code.code(),
+            message: message.into(),
+        }
+    }
+
+    /// Classification of the failure; codes from a newer host decode as [`ErrorCode::Unknown`].
+    pub fn error_code(&self) -> ErrorCode {
+        ErrorCode::try_from(self.code).unwrap_or(ErrorCode::Unknown)
+    }
+}
+
+impl TryFrom<u16> for ErrorCode {
+    type Error = ErrorCodeDecodeError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|code| code.code() == value)
+            .ok_or(ErrorCodeDecodeError(value))
+    }
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(value: ErrorCode) -> Self {
+        value.code()
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?} ({})", self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::try_from(code.code()), Ok(code));
+        }
+        assert_eq!(ErrorCode::try_from(9999), Err(ErrorCodeDecodeError(9999)));
+    }
+}
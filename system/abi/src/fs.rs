@@ -0,0 +1,48 @@
+//! Filesystem hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Payload used to watch a path in the caller's sandbox for changes.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct FsWatch {
+    /// Sandbox path of the file or directory to watch; it need not exist yet.
+    pub path: String,
+    /// Whether changes in subdirectories of a watched directory are reported too.
+    pub recursive: bool,
+}
+
+/// How a watched file changed.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum FsEventKind {
+    /// The file appeared.
+    Created = 0,
+    /// The file's contents or size changed.
+    Modified = 1,
+    /// The file went away.
+    Removed = 2,
+}
+
+/// A change to a watched file.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct FsEvent {
+    /// How the file changed.
+    pub kind: FsEventKind,
+    /// Sandbox path of the file that changed.
+    pub path: String,
+}
+
+impl FsEventKind {
+    /// Every kind, in discriminant order.
+    pub const ALL: [FsEventKind; 3] = [
+        FsEventKind::Created,
+        FsEventKind::Modified,
+        FsEventKind::Removed,
+    ];
+}
@@ -0,0 +1,25 @@
+//! Host command execution payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Payload used to run an allow-listed host command.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct HostExec {
+    /// Name the command is allow-listed under, not a path on the host.
+    pub command: String,
+    /// Arguments appended to those the allow-list fixes, if the command accepts any.
+    pub args: Vec<String>,
+}
+
+/// Result of a host command that ran to completion.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct HostExecOutput {
+    /// Exit status, or `None` if the command was killed by a signal.
+    pub status: Option<i32>,
+    /// Everything the command wrote to its standard output.
+    pub stdout: Vec<u8>,
+}
@@ -4,18 +4,69 @@
 //! - symbol names used in `#[link(wasm_import_module = "...")]`
 //! - capability → hostcall coverage (for stub generation)
 //! - input/output type pairing enforced at compile time
+//! - maximum encoded input/output payload sizes
+//!
+//! Hostcalls whose import module is declared as a bare `&str` constant, such as
+//! [`META_HOSTCALLS`], require no capability and are linked into every instance, so they are not
+//! part of [`ALL`].
 
 use core::marker::PhantomData;
 use std::collections::BTreeMap;
 
 use crate::{
-    Capability, ChannelCreate, GuestResourceId, GuestUint, IoFrame, IoRead, IoWrite, NetAccept,
-    NetAcceptReply, NetConnect, NetConnectReply, NetCreateListener, NetCreateListenerReply,
-    NetTlsClientConfig, NetTlsConfigReply, NetTlsServerConfig, ProcessLogLookup,
-    ProcessLogRegistration, ProcessStart, RkyvEncode, SessionCreate, SessionEntitlement,
-    SessionRemove, SessionResource, SingletonLookup, SingletonRegister, TimeNow, TimeSleep,
+    BarrierWait, Capability, CapabilitySet, ChannelCreate, CompressDeflate, CompressInflate,
+    CounterAdd, FsEvent, FsWatch, GaugeAdd, GaugeSet, GuestResourceId, GuestUint, HostExec,
+    HostExecOutput, IoFrame, IoRead, IoWrite, JsonPayload, LatchRequest, LockAcquire, LockRelease,
+    NetAccept, NetAcceptReply, NetConnect, NetConnectReply, NetCreateListener,
+    NetCreateListenerReply, NetTlsClientConfig, NetTlsConfigReply, NetTlsServerConfig, ProcessInfo,
+    ProcessLogLookup, ProcessLogRegistration, ProcessMessage, ProcessStartEnvelope, RkyvEncode,
+    ServiceHealthUpdate, ServiceInstance, ServiceLookup, ServiceRegister, SessionCreate,
+    SessionEntitlement, SessionRemove, SessionResource, SingletonLookup, SingletonRegister,
+    SqlRows, SqlStatement, TimeNow, TimeSleep, WatchCreate, WatchSet, WatchSubscribe, WatchValue,
 };
 
+/// Default cap on a hostcall's encoded input or output payload, in bytes.
+pub const DEFAULT_MAX_PAYLOAD: usize = 1024 * 1024;
+
+/// Largest encoded input of [`PROCESS_SEND`], in bytes: inboxes carry control messages, not bulk
+/// data.
+pub const MAX_PROCESS_MESSAGE: usize = 64 * 1024;
+
+/// Import module of the introspection hostcall that lists the hostcalls linked for the caller.
+pub const META_HOSTCALLS: &str = "selium::meta::hostcalls";
+
+/// Import module of the hostcall that attaches an idempotency key to the caller's next hostcall.
+pub const META_IDEMPOTENCY_KEY: &str = "selium::meta::idempotency_key";
+
+/// Import module of the hostcall a guest uses to report that it has finished initialising.
+pub const META_READY: &str = "selium::meta::ready";
+
+/// Import module of the hostcall that reports the caller's pending futures, mailbox counters and
+/// slot usage.
+pub const META_DIAGNOSTICS: &str = "selium::meta::diagnostics";
+
+/// Import module of the hostcall a guest uses to negotiate the encoding of its hostcall payloads.
+pub const META_ENCODING: &str = "selium::meta::encoding";
+
+/// Import module of the hostcall that reports the host's OS family, architecture, locale and
+/// hostname.
+pub const META_HOST_INFO: &str = "selium::meta::host_info";
+
+/// Import module of the hostcall a guest uses to record the rkyv-encoded value its entrypoint
+/// completes with, which a parent retrieves through [`PROCESS_WAIT`].
+pub const PROCESS_COMPLETE: &str = "selium::process::complete";
+
+/// Import module of the hostcall a guest uses to take the next message from its own inbox, as
+/// pushed by [`PROCESS_SEND`].
+pub const PROCESS_RECEIVE: &str = "selium::process::receive";
+
+/// Import module of the hostcall a guest uses to read its own memory size, fuel consumed,
+/// hostcalls issued and pending futures.
+pub const PROCESS_SELF_STATS: &str = "selium::process::self_stats";
+
+/// Import module of the hostcall a panicking guest uses to report the panic before it traps.
+pub const PROCESS_REPORT_PANIC: &str = "selium::process::report_panic";
+
 /// Type-erased metadata describing a hostcall.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct HostcallMeta {
@@ -23,6 +74,10 @@ pub struct HostcallMeta {
     pub name: &'static str,
     /// Capability required to invoke the hostcall.
     pub capability: Capability,
+    /// Largest encoded input payload the host will accept, in bytes.
+    pub max_input: usize,
+    /// Largest encoded output payload the host will return, in bytes.
+    pub max_output: usize,
 }
 
 /// Typed description of a hostcall linking point.
@@ -35,8 +90,8 @@ pub struct Hostcall<I, O> {
 
 impl<I, O> Hostcall<I, O>
 where
-    I: RkyvEncode + Send,
-    O: RkyvEncode + Send,
+    I: RkyvEncode + JsonPayload + Send,
+    O: RkyvEncode + JsonPayload + Send,
     for<'a> I::Archived: 'a
         + rkyv::Deserialize<I, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
@@ -44,14 +99,26 @@ where
         + rkyv::Deserialize<O, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
 {
-    /// Construct a new hostcall descriptor.
+    /// Construct a new hostcall descriptor with the default payload limits.
     pub const fn new(name: &'static str, capability: Capability) -> Self {
         Self {
-            meta: HostcallMeta { name, capability },
+            meta: HostcallMeta {
+                name,
+                capability,
+                max_input: DEFAULT_MAX_PAYLOAD,
+                max_output: DEFAULT_MAX_PAYLOAD,
+            },
             _marker: PhantomData,
         }
     }
 
+    /// Override the maximum encoded input and output payload sizes.
+    pub const fn with_limits(mut self, max_input: usize, max_output: usize) -> Self {
+        self.meta.max_input = max_input;
+        self.meta.max_output = max_output;
+        self
+    }
+
     /// Access the symbol name.
     pub const fn name(&self) -> &'static str {
         self.meta.name
@@ -62,12 +129,31 @@ where
         self.meta.capability
     }
 
+    /// Largest encoded input payload the host will accept, in bytes.
+    pub const fn max_input(&self) -> usize {
+        self.meta.max_input
+    }
+
+    /// Largest encoded output payload the host will return, in bytes.
+    pub const fn max_output(&self) -> usize {
+        self.meta.max_output
+    }
+
     /// Access the type-erased metadata.
     pub const fn meta(&self) -> HostcallMeta {
         self.meta
     }
 }
 
+macro_rules! payload_limit {
+    () => {
+        DEFAULT_MAX_PAYLOAD
+    };
+    ($limit:expr) => {
+        $limit
+    };
+}
+
 macro_rules! declare_hostcalls {
     (
         $( $ident:ident => {
@@ -75,16 +161,26 @@ macro_rules! declare_hostcalls {
             capability: $cap:path,
             input: $input:ty,
             output: $output:ty
+            $(, max_input: $max_input:expr)?
+            $(, max_output: $max_output:expr)?
         }, )+
     ) => {
         $(
             #[doc = concat!("Hostcall descriptor for `", $name, "`.")]
-            pub const $ident: Hostcall<$input, $output> = Hostcall::new($name, $cap);
+            pub const $ident: Hostcall<$input, $output> = Hostcall::new($name, $cap).with_limits(
+                payload_limit!($($max_input)?),
+                payload_limit!($($max_output)?),
+            );
         )+
 
         /// Complete catalogue of hostcalls, grouped by capability.
         pub const ALL: &[HostcallMeta] = &[
-            $(HostcallMeta { name: $name, capability: $cap },)+
+            $($ident.meta(),)+
+        ];
+
+        /// Names of the input and output payload types of each hostcall in [`ALL`], in order.
+        pub(crate) const PAYLOAD_TYPES: &[(&str, &str)] = &[
+            $((stringify!($input), stringify!($output)),)+
         ];
 
         /// Build a map of capabilities to the hostcalls they expose.
@@ -209,17 +305,157 @@ declare_hostcalls! {
         input: SingletonLookup,
         output: GuestResourceId
     },
+    SERVICE_REGISTER => {
+        name: "selium::services::directory::register",
+        capability: Capability::ServiceDirectory,
+        input: ServiceRegister,
+        output: ()
+    },
+    SERVICE_SET_HEALTH => {
+        name: "selium::services::directory::set_health",
+        capability: Capability::ServiceDirectory,
+        input: ServiceHealthUpdate,
+        output: ()
+    },
+    SERVICE_RESOLVE => {
+        name: "selium::services::directory::resolve",
+        capability: Capability::ServiceDirectory,
+        input: ServiceLookup,
+        output: GuestResourceId
+    },
+    SERVICE_INSTANCES => {
+        name: "selium::services::directory::instances",
+        capability: Capability::ServiceDirectory,
+        input: ServiceLookup,
+        output: Vec<ServiceInstance>
+    },
+    WATCH_CREATE => {
+        name: "selium::watch::create",
+        capability: Capability::Watch,
+        input: WatchCreate,
+        output: GuestResourceId
+    },
+    WATCH_SET => {
+        name: "selium::watch::set",
+        capability: Capability::Watch,
+        input: WatchSet,
+        output: u64
+    },
+    WATCH_SUBSCRIBE => {
+        name: "selium::watch::subscribe",
+        capability: Capability::Watch,
+        input: WatchSubscribe,
+        output: WatchValue
+    },
+    METRICS_COUNTER_ADD => {
+        name: "selium::metrics::counter::add",
+        capability: Capability::Metrics,
+        input: CounterAdd,
+        output: u64
+    },
+    METRICS_GAUGE_SET => {
+        name: "selium::metrics::gauge::set",
+        capability: Capability::Metrics,
+        input: GaugeSet,
+        output: ()
+    },
+    METRICS_GAUGE_ADD => {
+        name: "selium::metrics::gauge::add",
+        capability: Capability::Metrics,
+        input: GaugeAdd,
+        output: i64
+    },
+    LOCK_ACQUIRE => {
+        name: "selium::lock::acquire",
+        capability: Capability::Lock,
+        input: LockAcquire,
+        output: u64
+    },
+    LOCK_RELEASE => {
+        name: "selium::lock::release",
+        capability: Capability::Lock,
+        input: LockRelease,
+        output: ()
+    },
+    SYNC_BARRIER_WAIT => {
+        name: "selium::sync::barrier::wait",
+        capability: Capability::Sync,
+        input: BarrierWait,
+        output: u32
+    },
+    SYNC_LATCH_COUNT_DOWN => {
+        name: "selium::sync::latch::count_down",
+        capability: Capability::Sync,
+        input: LatchRequest,
+        output: u32
+    },
+    SYNC_LATCH_WAIT => {
+        name: "selium::sync::latch::wait",
+        capability: Capability::Sync,
+        input: LatchRequest,
+        output: ()
+    },
+    FUTURE_SHARE => {
+        name: "selium::future::share",
+        capability: Capability::FutureHandoff,
+        input: GuestUint,
+        output: GuestResourceId
+    },
+    FUTURE_AWAIT => {
+        name: "selium::future::await",
+        capability: Capability::FutureHandoff,
+        input: GuestResourceId,
+        output: Vec<u8>
+    },
+    COMPRESS_DEFLATE => {
+        name: "selium::compress::deflate",
+        capability: Capability::Compression,
+        input: CompressDeflate,
+        output: Vec<u8>
+    },
+    COMPRESS_INFLATE => {
+        name: "selium::compress::inflate",
+        capability: Capability::Compression,
+        input: CompressInflate,
+        output: Vec<u8>
+    },
+    HOST_EXEC => {
+        name: "selium::host::exec",
+        capability: Capability::HostExec,
+        input: HostExec,
+        output: HostExecOutput
+    },
+    FS_WATCH => {
+        name: "selium::fs::watch",
+        capability: Capability::Filesystem,
+        input: FsWatch,
+        output: Option<FsEvent>
+    },
+    SQL_QUERY => {
+        name: "selium::sql::query",
+        capability: Capability::Sql,
+        input: SqlStatement,
+        output: SqlRows
+    },
+    SQL_EXECUTE => {
+        name: "selium::sql::execute",
+        capability: Capability::Sql,
+        input: SqlStatement,
+        output: u64
+    },
     TIME_NOW => {
         name: "selium::time::now",
         capability: Capability::TimeRead,
         input: (),
-        output: TimeNow
+        output: TimeNow,
+        max_input: 64
     },
     TIME_SLEEP => {
         name: "selium::time::sleep",
         capability: Capability::TimeRead,
         input: TimeSleep,
-        output: ()
+        output: (),
+        max_input: 64
     },
     CHANNEL_STRONG_READER_CREATE => {
         name: "selium::channel::strong_reader_create",
@@ -284,7 +520,7 @@ declare_hostcalls! {
     PROCESS_START => {
         name: "selium::process::start",
         capability: Capability::ProcessLifecycle,
-        input: ProcessStart,
+        input: ProcessStartEnvelope,
         output: GuestResourceId
     },
     PROCESS_STOP => {
@@ -293,6 +529,25 @@ declare_hostcalls! {
         input: GuestResourceId,
         output: ()
     },
+    PROCESS_INFO => {
+        name: "selium::process::info",
+        capability: Capability::ProcessLifecycle,
+        input: GuestResourceId,
+        output: ProcessInfo
+    },
+    PROCESS_WAIT => {
+        name: "selium::process::wait",
+        capability: Capability::ProcessLifecycle,
+        input: GuestResourceId,
+        output: Vec<u8>
+    },
+    PROCESS_SEND => {
+        name: "selium::process::send",
+        capability: Capability::ProcessLifecycle,
+        input: ProcessMessage,
+        output: (),
+        max_input: MAX_PROCESS_MESSAGE
+    },
     NET_QUIC_BIND => {
         name: "selium::net::quic::bind",
         capability: Capability::NetQuicBind,
@@ -366,3 +621,9 @@ declare_hostcalls! {
         output: NetTlsConfigReply
     },
 }
+
+/// Iterate over the catalogue entries that are linked for an instance granted `capabilities`.
+pub fn granted(capabilities: CapabilitySet) -> impl Iterator<Item = &'static HostcallMeta> {
+    ALL.iter()
+        .filter(move |meta| capabilities.contains(meta.capability))
+}
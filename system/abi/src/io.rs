@@ -2,6 +2,7 @@ use rkyv::{Archive, Deserialize, Serialize};
 
 /// Backpressure behaviour for channel writers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 #[repr(u8)]
 pub enum ChannelBackpressure {
@@ -13,6 +14,7 @@ pub enum ChannelBackpressure {
 
 /// Request to create a new channel.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct ChannelCreate {
     /// Channel capacity in bytes.
@@ -23,6 +25,7 @@ pub struct ChannelCreate {
 
 /// Request to read data from a reader.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct IoRead {
     /// Handle of the reader.
@@ -33,6 +36,7 @@ pub struct IoRead {
 
 /// Request to write data to a writer.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct IoWrite {
     /// Handle of the writer.
@@ -43,6 +47,7 @@ pub struct IoWrite {
 
 /// Response carrying an attributed frame.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct IoFrame {
     /// Identifier of the writer that produced this frame.
@@ -13,32 +13,70 @@ use std::{
 };
 use thiserror::Error;
 
+pub mod c_bindings;
+mod capability_set;
+mod compress;
+mod encoding;
+mod error;
+mod fs;
+mod host;
 pub mod hostcalls;
 mod io;
+mod lock;
+mod meta;
+mod metrics;
 mod net;
 mod process;
+mod services;
 mod session;
 mod singleton;
+mod sql;
+mod sync;
 mod time;
 mod tls;
+mod versioned;
+mod watch;
 
 // pub use external::*;
+pub use capability_set::*;
+pub use compress::*;
+pub use encoding::*;
+pub use error::*;
+pub use fs::*;
+pub use host::*;
 pub use hostcalls::*;
 pub use io::*;
+pub use lock::*;
+pub use meta::*;
+pub use metrics::*;
 pub use net::*;
 pub use process::*;
+pub use services::*;
 pub use session::*;
 pub use singleton::*;
+pub use sql::*;
+pub use sync::*;
 pub use time::*;
 pub use tls::*;
+pub use versioned::*;
+pub use watch::*;
 
-/// Guest pointer-sized signed integer.
+/// Guest word-sized signed integer.
 pub type GuestInt = i32;
-/// Guest pointer-sized unsigned integer.
+/// Guest word-sized unsigned integer, used for handles and poll results whatever the guest's
+/// address width.
 pub type GuestUint = u32;
+/// Address in guest linear memory, as passed to hostcalls: 32 bits wide for wasm32 guests.
+#[cfg(not(target_arch = "wasm64"))]
+pub type GuestPtr = u32;
+/// Address in guest linear memory, as passed to hostcalls: 64 bits wide for memory64 guests.
+#[cfg(target_arch = "wasm64")]
+pub type GuestPtr = u64;
+/// Length of a guest buffer, as passed to hostcalls; as wide as [`GuestPtr`].
+pub type GuestSize = GuestPtr;
 /// Guest-facing resource identifiers.
 pub type GuestResourceId = u64;
-/// Guest pointer-sized atomic unsigned integer.
+/// Guest word-sized atomic unsigned integer.
 pub type GuestAtomicUint = std::sync::atomic::AtomicU32;
 
 /// Size, in bytes, of a guest machine word.
@@ -49,7 +87,10 @@ const DRIVER_RESULT_SPECIAL_FLAG: GuestUint = 1 << 31;
 pub const DRIVER_RESULT_READY_MAX: GuestUint = DRIVER_RESULT_SPECIAL_FLAG - 1;
 /// Word signalling the host is still processing the driver future.
 pub const DRIVER_RESULT_PENDING: GuestUint = DRIVER_RESULT_SPECIAL_FLAG;
-/// Error code indicating the payload buffer contains a driver error string.
+/// Word signalling the result buffer is filled with the next chunk of a result too large for
+/// it; the guest should append the chunk and poll again for the remainder.
+pub const DRIVER_RESULT_CHUNK: GuestUint = GuestUint::MAX;
+/// Error code indicating the payload buffer contains a [`DriverErrorPayload`].
 pub const DRIVER_ERROR_MESSAGE_CODE: GuestUint = 1;
 
 /// Shared constants describing the guest↔host waker mailbox layout.
@@ -101,6 +142,9 @@ pub enum DriverPollResult {
     Ready(GuestUint),
     /// Host has not completed execution; guest should poll again later.
     Pending,
+    /// Host filled the whole result buffer with part of a larger result; guest should poll
+    /// again straight away for the rest.
+    Chunk,
     /// Host reported an error; `code` identifies the error class.
     Error(GuestUint),
 }
@@ -110,6 +154,7 @@ pub enum DriverPollResult {
 #[derive(
     Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Archive, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum Capability {
     SessionLifecycle = 0,
@@ -132,11 +177,22 @@ pub enum Capability {
     SingletonRegistry = 17,
     SingletonLookup = 18,
     TimeRead = 19,
+    ServiceDirectory = 20,
+    Watch = 21,
+    Metrics = 22,
+    Lock = 23,
+    Sync = 24,
+    FutureHandoff = 25,
+    Compression = 26,
+    HostExec = 27,
+    Filesystem = 28,
+    Sql = 29,
+    HostShutdown = 30,
 }
 
 impl Capability {
     /// All capabilities understood by the Selium kernel ABI.
-    pub const ALL: [Capability; 20] = [
+    pub const ALL: [Capability; 31] = [
         Capability::SessionLifecycle,
         Capability::ChannelLifecycle,
         Capability::ChannelReader,
@@ -157,6 +213,17 @@ impl Capability {
         Capability::SingletonRegistry,
         Capability::SingletonLookup,
         Capability::TimeRead,
+        Capability::ServiceDirectory,
+        Capability::Watch,
+        Capability::Metrics,
+        Capability::Lock,
+        Capability::Sync,
+        Capability::FutureHandoff,
+        Capability::Compression,
+        Capability::HostExec,
+        Capability::Filesystem,
+        Capability::Sql,
+        Capability::HostShutdown,
     ];
 }
 
@@ -167,6 +234,7 @@ pub struct CapabilityDecodeError;
 
 /// Scalar value kinds supported by the ABI.
 #[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum AbiScalarValue {
     /// 8-bit signed integer.
@@ -193,6 +261,7 @@ pub enum AbiScalarValue {
 
 /// Scalar kinds that can be part of an ABI signature.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum AbiScalarType {
     /// 8-bit signed integer.
@@ -219,6 +288,7 @@ pub enum AbiScalarType {
 
 /// Logical parameter kinds supported by the ABI.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum AbiParam {
     /// An immediate scalar value.
@@ -229,6 +299,7 @@ pub enum AbiParam {
 
 /// Description of a guest entrypoint's parameters and results.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct AbiSignature {
     params: Vec<AbiParam>,
@@ -237,6 +308,7 @@ pub struct AbiSignature {
 
 /// Values supplied for a call.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum AbiValue {
     /// Scalar argument.
@@ -318,6 +390,17 @@ impl TryFrom<u8> for Capability {
             17 => Ok(Capability::SingletonRegistry),
             18 => Ok(Capability::SingletonLookup),
             19 => Ok(Capability::TimeRead),
+            20 => Ok(Capability::ServiceDirectory),
+            21 => Ok(Capability::Watch),
+            22 => Ok(Capability::Metrics),
+            23 => Ok(Capability::Lock),
+            24 => Ok(Capability::Sync),
+            25 => Ok(Capability::FutureHandoff),
+            26 => Ok(Capability::Compression),
+            27 => Ok(Capability::HostExec),
+            28 => Ok(Capability::Filesystem),
+            29 => Ok(Capability::Sql),
+            30 => Ok(Capability::HostShutdown),
             _ => Err(CapabilityDecodeError),
         }
     }
@@ -352,6 +435,17 @@ impl Display for Capability {
             Capability::SingletonRegistry => write!(f, "SingletonRegistry"),
             Capability::SingletonLookup => write!(f, "SingletonLookup"),
             Capability::TimeRead => write!(f, "TimeRead"),
+            Capability::ServiceDirectory => write!(f, "ServiceDirectory"),
+            Capability::Watch => write!(f, "Watch"),
+            Capability::Metrics => write!(f, "Metrics"),
+            Capability::Lock => write!(f, "Lock"),
+            Capability::Sync => write!(f, "Sync"),
+            Capability::FutureHandoff => write!(f, "FutureHandoff"),
+            Capability::Compression => write!(f, "Compression"),
+            Capability::HostExec => write!(f, "HostExec"),
+            Capability::Filesystem => write!(f, "Filesystem"),
+            Capability::Sql => write!(f, "Sql"),
+            Capability::HostShutdown => write!(f, "HostShutdown"),
         }
     }
 }
@@ -554,6 +648,7 @@ impl From<DriverPollResult> for GuestUint {
         match value {
             DriverPollResult::Ready(len) => len,
             DriverPollResult::Pending => DRIVER_RESULT_PENDING,
+            DriverPollResult::Chunk => DRIVER_RESULT_CHUNK,
             DriverPollResult::Error(code) => driver_encode_error(code),
         }
     }
@@ -588,9 +683,9 @@ where
     rkyv::from_bytes::<T, RancorError>(bytes).map_err(|err| RkyvError::Decode(err.to_string()))
 }
 
-/// Encode a human-readable driver error message for guest consumption.
-pub fn encode_driver_error_message(message: &str) -> Result<Vec<u8>, RkyvError> {
-    let encoded = encode_rkyv(&message.to_string())?;
+/// Encode a driver error code and human-readable message for guest consumption.
+pub fn encode_driver_error(code: ErrorCode, message: &str) -> Result<Vec<u8>, RkyvError> {
+    let encoded = encode_rkyv(&DriverErrorPayload::new(code, message))?;
     let len = u32::try_from(encoded.len()).map_err(|_| {
         RkyvError::Encode("driver error message length does not fit u32".to_string())
     })?;
@@ -600,8 +695,8 @@ pub fn encode_driver_error_message(message: &str) -> Result<Vec<u8>, RkyvError>
     Ok(bytes)
 }
 
-/// Decode a driver error message payload written by the kernel.
-pub fn decode_driver_error_message(bytes: &[u8]) -> Result<String, RkyvError> {
+/// Decode a driver error payload written by the kernel.
+pub fn decode_driver_error(bytes: &[u8]) -> Result<DriverErrorPayload, RkyvError> {
     let prefix = bytes
         .get(..4)
         .ok_or_else(|| RkyvError::Decode("driver error message missing length".to_string()))?;
@@ -613,7 +708,7 @@ pub fn decode_driver_error_message(bytes: &[u8]) -> Result<String, RkyvError> {
     let payload = bytes.get(4..4 + len).ok_or_else(|| {
         RkyvError::Decode("driver error message length exceeds buffer".to_string())
     })?;
-    decode_rkyv::<String>(payload)
+    decode_rkyv::<DriverErrorPayload>(payload)
 }
 
 pub fn driver_encode_ready(len: GuestUint) -> Option<GuestUint> {
@@ -636,6 +731,8 @@ pub fn driver_decode_result(word: GuestUint) -> DriverPollResult {
         DriverPollResult::Ready(word)
     } else if word == DRIVER_RESULT_SPECIAL_FLAG {
         DriverPollResult::Pending
+    } else if word == DRIVER_RESULT_CHUNK {
+        DriverPollResult::Chunk
     } else {
         DriverPollResult::Error(word & DRIVER_RESULT_READY_MAX)
     }
@@ -735,6 +832,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn poll_result_words_round_trip() {
+        for result in [
+            DriverPollResult::Ready(0),
+            DriverPollResult::Ready(DRIVER_RESULT_READY_MAX),
+            DriverPollResult::Pending,
+            DriverPollResult::Chunk,
+            DriverPollResult::Error(ErrorCode::NotFound as GuestUint),
+        ] {
+            assert_eq!(driver_decode_result(GuestUint::from(result)), result);
+        }
+    }
+
     #[test]
     fn call_plan_flattens_integer_widths() {
         let signature = AbiSignature::new(
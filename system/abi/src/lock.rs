@@ -0,0 +1,25 @@
+//! Named lock hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Payload used to acquire a named lock, waiting while another holder has it.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct LockAcquire {
+    /// Name of the lock.
+    pub name: String,
+    /// Time in milliseconds after which the lock is released if its holder has not done so.
+    pub ttl_ms: u64,
+}
+
+/// Payload used to release a named lock.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct LockRelease {
+    /// Name of the lock.
+    pub name: String,
+    /// Token returned when the lock was acquired.
+    pub token: u64,
+}
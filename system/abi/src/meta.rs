@@ -0,0 +1,100 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Idempotency key attached to the next hostcall made by the calling instance.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct IdempotencyKey {
+    /// Caller-chosen key; retries of the same call must reuse it.
+    pub key: String,
+}
+
+/// Environment of the host a guest runs on, for guests that include it in logs and reports.
+///
+/// Fields the host withholds by policy, or cannot determine, are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct HostInfo {
+    /// Operating system family, such as `unix` or `windows`.
+    pub os_family: String,
+    /// CPU architecture, such as `x86_64` or `aarch64`.
+    pub arch: String,
+    /// Locale of the host process, such as `en_GB.UTF-8`.
+    pub locale: String,
+    /// Name of the host machine.
+    pub hostname: String,
+}
+
+/// Snapshot of an instance's async state, for debugging guests stuck in `WouldBlock` loops.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct InstanceDiagnostics {
+    /// Every future handle the instance holds, in handle order.
+    pub futures: Vec<FutureDiagnostics>,
+    /// Wake-up mailbox counters, if the instance has registered a mailbox.
+    pub mailbox: Option<MailboxDiagnostics>,
+    /// Usage of the instance's resource slot table.
+    pub slots: SlotUsage,
+    /// Usage of the instance's future handle table.
+    pub future_slots: SlotUsage,
+    /// Tags of the instance's process, such as the module it was spawned from.
+    pub tags: Vec<String>,
+}
+
+/// State of one future handle held by an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct FutureDiagnostics {
+    /// Handle the guest polls the future through.
+    pub handle: u32,
+    /// How far the future has progressed.
+    pub state: FutureState,
+    /// Results produced by the host but not yet taken by the guest.
+    pub queued: u32,
+    /// Whether the guest has registered a waker that has not fired yet.
+    pub waiting: bool,
+}
+
+/// Progress of a future, as seen by the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum FutureState {
+    /// The host has not produced the final result yet.
+    Pending,
+    /// The final result is waiting to be taken by the guest.
+    Resolved,
+    /// The final result has been taken; the guest has not dropped the handle yet.
+    Complete,
+    /// The guest dropped the future before it completed.
+    Abandoned,
+}
+
+/// Counters of an instance's wake-up mailbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct MailboxDiagnostics {
+    /// Wake-ups the host has signalled since the mailbox was created, modulo 2^32.
+    pub signalled: u32,
+    /// Wake-ups signalled but not yet drained by the guest executor.
+    pub undrained: u32,
+    /// Whether the ready flag is raised.
+    pub flagged: bool,
+    /// Whether the host has closed the mailbox.
+    pub closed: bool,
+}
+
+/// Occupancy of a handle table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct SlotUsage {
+    /// Slots holding a live handle.
+    pub live: u32,
+    /// Slots allocated so far, live or free for reuse.
+    pub allocated: u32,
+}
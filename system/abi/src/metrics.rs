@@ -0,0 +1,36 @@
+//! Guest metric hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Payload used to add to a named counter, creating it at zero if needed.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct CounterAdd {
+    /// Prometheus metric name of the counter.
+    pub name: String,
+    /// Amount to add.
+    pub delta: u64,
+}
+
+/// Payload used to set a named gauge, creating it if needed.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct GaugeSet {
+    /// Prometheus metric name of the gauge.
+    pub name: String,
+    /// New value.
+    pub value: i64,
+}
+
+/// Payload used to add to a named gauge, creating it at zero if needed.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct GaugeAdd {
+    /// Prometheus metric name of the gauge.
+    pub name: String,
+    /// Amount to add; negative to subtract.
+    pub delta: i64,
+}
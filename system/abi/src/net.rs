@@ -5,6 +5,7 @@ use crate::GuestResourceId;
 /// Network transport protocols supported by the ABI.
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum NetProtocol {
     /// QUIC over UDP with TLS 1.3.
@@ -17,6 +18,7 @@ pub enum NetProtocol {
 
 /// Arguments for creating a network listener.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetCreateListener {
     /// Protocol to use for the listener.
@@ -31,6 +33,7 @@ pub struct NetCreateListener {
 
 /// Reply containing guest-visible handles for a created listener.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetCreateListenerReply {
     /// Listener handle registered in the instance registry.
@@ -39,6 +42,7 @@ pub struct NetCreateListenerReply {
 
 /// Request to accept the next inbound connection on a listener.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetAccept {
     /// Handle of the listener to accept on.
@@ -47,6 +51,7 @@ pub struct NetAccept {
 
 /// Reply containing guest-visible handles for an accepted connection.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetAcceptReply {
     /// Reader handle registered in the instance registry.
@@ -59,6 +64,7 @@ pub struct NetAcceptReply {
 
 /// Arguments for connecting to a remote endpoint.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetConnect {
     /// Protocol to use for the connection.
@@ -73,6 +79,7 @@ pub struct NetConnect {
 
 /// Reply containing guest-visible handles for a connected session.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetConnectReply {
     /// Reader handle registered in the instance registry.
@@ -1,11 +1,15 @@
+use std::fmt::{self, Display, Formatter};
+
 use rkyv::{Archive, Deserialize, Serialize};
 
 use crate::{
-    AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, CallPlanError, GuestResourceId,
+    AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, CallPlanError, Capability,
+    CapabilitySet, ErrorCode, GuestResourceId, Versioned,
 };
 
 /// Argument supplied to a process entrypoint.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub enum EntrypointArg {
     /// Immediate scalar value.
@@ -18,6 +22,7 @@ pub enum EntrypointArg {
 
 /// Invocation of a process entrypoint.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct EntrypointInvocation {
     /// ABI signature describing the entrypoint.
@@ -82,6 +87,7 @@ impl EntrypointInvocation {
 
 /// Register a process's logging channel with the host.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct ProcessLogRegistration {
     /// Shared channel handle exported by the guest.
@@ -90,14 +96,125 @@ pub struct ProcessLogRegistration {
 
 /// Request the logging channel for a running process.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct ProcessLogLookup {
     /// Handle referencing the process to inspect.
     pub process_id: GuestResourceId,
 }
 
+/// Message pushed into the inbox of a running process.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ProcessMessage {
+    /// Handle referencing the receiving process.
+    pub process_id: GuestResourceId,
+    /// Message contents.
+    pub payload: Vec<u8>,
+}
+
+/// Runtime statistics for a running process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ProcessInfo {
+    /// Wasm fuel consumed so far, if the process's runtime meters fuel.
+    pub fuel_consumed: Option<u64>,
+    /// Why the process trapped, if it has.
+    pub trap: Option<TrapReason>,
+}
+
+/// Why a process trapped, as classified by its runtime.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum TrapReason {
+    /// The guest executed an `unreachable` instruction, as Rust guests do when they panic or
+    /// abort.
+    Unreachable = 0,
+    /// The guest ran out of linear memory: growing it failed before the guest gave up.
+    OutOfMemory = 1,
+    /// The guest consumed all of the fuel it was granted.
+    FuelExhausted = 2,
+    /// The guest was interrupted at an epoch deadline.
+    EpochInterrupt = 3,
+    /// A hostcall failed in a way the guest could not recover from.
+    HostcallError = 4,
+    /// Any other trap, such as an out-of-bounds memory access or a stack overflow.
+    Other = 5,
+}
+
+impl TrapReason {
+    /// Every trap reason, in discriminant order.
+    pub const ALL: [TrapReason; 6] = [
+        TrapReason::Unreachable,
+        TrapReason::OutOfMemory,
+        TrapReason::FuelExhausted,
+        TrapReason::EpochInterrupt,
+        TrapReason::HostcallError,
+        TrapReason::Other,
+    ];
+}
+
+impl Display for TrapReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unreachable => "unreachable executed",
+            Self::OutOfMemory => "out of memory",
+            Self::FuelExhausted => "fuel exhausted",
+            Self::EpochInterrupt => "epoch interrupt",
+            Self::HostcallError => "hostcall failed",
+            Self::Other => "trapped",
+        })
+    }
+}
+
+impl From<TrapReason> for ErrorCode {
+    fn from(value: TrapReason) -> Self {
+        match value {
+            TrapReason::Unreachable => ErrorCode::ProcessUnreachable,
+            TrapReason::OutOfMemory => ErrorCode::ProcessOutOfMemory,
+            TrapReason::FuelExhausted => ErrorCode::ProcessFuelExhausted,
+            TrapReason::EpochInterrupt => ErrorCode::ProcessInterrupted,
+            TrapReason::HostcallError => ErrorCode::ProcessHostcallFailed,
+            TrapReason::Other => ErrorCode::ProcessTrapped,
+        }
+    }
+}
+
+/// A panic a guest reports before it traps, so the host can say why the process died.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ProcessPanic {
+    /// The panic message.
+    pub message: String,
+    /// Source location of the panic as `file:line:column`, or empty if unknown.
+    pub location: String,
+}
+
+/// Resource usage of the calling process, for guests that throttle themselves or report their
+/// own health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ProcessSelfStats {
+    /// Size of the process's linear memory in bytes, as last sampled by its runtime, if the
+    /// runtime samples it.
+    pub memory_bytes: Option<u64>,
+    /// Wasm fuel consumed so far, if the process's runtime meters fuel.
+    pub fuel_consumed: Option<u64>,
+    /// Hostcalls the process has issued, this one included.
+    pub hostcalls: u64,
+    /// Futures the process holds whose final result the host has yet to produce.
+    pub pending_futures: u32,
+}
+
 /// Request to start a new process instance.
 #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct ProcessStart {
     /// Module identifier that should be activated.
@@ -105,7 +222,55 @@ pub struct ProcessStart {
     /// Friendly process name.
     pub name: String,
     /// Capabilities granted to the process.
-    pub capabilities: Vec<crate::Capability>,
+    pub capabilities: CapabilitySet,
+    /// Entrypoint invocation details.
+    pub entrypoint: EntrypointInvocation,
+}
+
+/// The original shape of [`ProcessStart`], which listed capabilities individually.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ProcessStartV1 {
+    /// Module identifier that should be activated.
+    pub module_id: String,
+    /// Friendly process name.
+    pub name: String,
+    /// Capabilities granted to the process.
+    pub capabilities: Vec<Capability>,
     /// Entrypoint invocation details.
     pub entrypoint: EntrypointInvocation,
 }
+
+/// Every version of [`ProcessStart`] a guest may send.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum ProcessStartEnvelope {
+    /// The original request shape.
+    V1(Box<ProcessStartV1>),
+    /// The request with its capabilities as a [`CapabilitySet`].
+    V2(Box<ProcessStart>),
+}
+
+impl From<ProcessStart> for ProcessStartEnvelope {
+    fn from(start: ProcessStart) -> Self {
+        Self::V2(Box::new(start))
+    }
+}
+
+impl Versioned for ProcessStart {
+    type Envelope = ProcessStartEnvelope;
+
+    fn upgrade(envelope: ProcessStartEnvelope) -> Self {
+        match envelope {
+            ProcessStartEnvelope::V1(start) => Self {
+                module_id: start.module_id,
+                name: start.name,
+                capabilities: start.capabilities.into_iter().collect(),
+                entrypoint: start.entrypoint,
+            },
+            ProcessStartEnvelope::V2(start) => *start,
+        }
+    }
+}
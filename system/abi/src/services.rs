@@ -0,0 +1,80 @@
+//! Service directory hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{DependencyId, GuestResourceId};
+
+/// Health an instance of a service reports to the directory.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum ServiceHealth {
+    /// Serving normally; preferred by resolution.
+    Healthy = 0,
+    /// Serving, but only resolved to when no instance is healthy.
+    Degraded = 1,
+    /// Not serving; never resolved to.
+    Unhealthy = 2,
+}
+
+/// Payload used to add an instance to a service in the directory.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ServiceRegister {
+    /// Service identifier.
+    pub id: DependencyId,
+    /// Name the identifier was computed from; the host refuses registrations whose name does
+    /// not hash to `id`.
+    pub name: String,
+    /// Shared handle to the resource backing this instance.
+    pub resource: GuestResourceId,
+}
+
+/// Payload used to report the health of a registered instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ServiceHealthUpdate {
+    /// Service identifier.
+    pub id: DependencyId,
+    /// Shared handle the instance was registered with.
+    pub resource: GuestResourceId,
+    /// Health the instance now reports.
+    pub health: ServiceHealth,
+}
+
+/// Payload used to resolve a service to one of its instances, or to list them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ServiceLookup {
+    /// Service identifier.
+    pub id: DependencyId,
+}
+
+/// An instance of a service, as listed by the directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct ServiceInstance {
+    /// Shared handle to the resource backing the instance.
+    pub resource: GuestResourceId,
+    /// Health the instance last reported.
+    pub health: ServiceHealth,
+}
+
+impl ServiceHealth {
+    /// Every health state, from most to least preferred.
+    pub const ALL: [ServiceHealth; 3] = [
+        ServiceHealth::Healthy,
+        ServiceHealth::Degraded,
+        ServiceHealth::Unhealthy,
+    ];
+
+    /// Whether resolution may hand out an instance in this state.
+    pub const fn is_serving(self) -> bool {
+        !matches!(self, ServiceHealth::Unhealthy)
+    }
+}
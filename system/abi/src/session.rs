@@ -4,6 +4,7 @@ use crate::{Capability, GuestUint};
 
 /// Request to create a new session.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SessionCreate {
     /// Parent session handle.
@@ -14,6 +15,7 @@ pub struct SessionCreate {
 
 /// Request to add or remove entitlements from a session.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SessionEntitlement {
     /// Parent session handle.
@@ -26,6 +28,7 @@ pub struct SessionEntitlement {
 
 /// Request to attach or detach a resource from a session entitlement.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SessionResource {
     /// Parent session handle.
@@ -40,6 +43,7 @@ pub struct SessionResource {
 
 /// Request to remove a session.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SessionRemove {
     /// Parent session handle.
@@ -4,30 +4,41 @@ use rkyv::{Archive, Deserialize, Serialize};
 
 use crate::GuestResourceId;
 
-/// Stable identifier for a singleton dependency.
+/// Stable identifier for a singleton dependency: the BLAKE3 hash of its name.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
-pub struct DependencyId(pub [u8; 16]);
+pub struct DependencyId(pub [u8; 32]);
 
 impl DependencyId {
+    /// Compute the identifier of the dependency called `name`.
+    pub fn from_name(name: &str) -> Self {
+        Self(*blake3::hash(name.as_bytes()).as_bytes())
+    }
+
     /// Return the raw byte representation of the identifier.
-    pub const fn bytes(self) -> [u8; 16] {
+    pub const fn bytes(self) -> [u8; 32] {
         self.0
     }
 }
 
 /// Payload used to register a singleton dependency in the host registry.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SingletonRegister {
     /// Dependency identifier.
     pub id: DependencyId,
+    /// Name the identifier was computed from; the host refuses registrations whose name does
+    /// not hash to `id`.
+    pub name: String,
     /// Shared handle to the resource that should back this singleton.
     pub resource: GuestResourceId,
 }
 
 /// Payload used to look up a singleton dependency from the host registry.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct SingletonLookup {
     /// Dependency identifier.
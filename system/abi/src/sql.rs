@@ -0,0 +1,46 @@
+//! SQL hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::GuestResourceId;
+
+/// A value bound to a statement parameter or read from a result column.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub enum SqlValue {
+    /// SQL `NULL`.
+    Null,
+    /// Integer of any width the database stores.
+    Integer(i64),
+    /// Floating-point number.
+    Real(f64),
+    /// Text.
+    Text(String),
+    /// Binary data.
+    Blob(Vec<u8>),
+}
+
+/// Payload used to run a statement on a host-held connection.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct SqlStatement {
+    /// Shared handle of the connection, as looked up from the singleton registry.
+    pub connection: GuestResourceId,
+    /// Statement text, with placeholders in the database's own syntax.
+    pub statement: String,
+    /// Values bound to the statement's placeholders, in order.
+    pub params: Vec<SqlValue>,
+}
+
+/// Rows returned by a query.
+#[derive(Debug, Clone, Default, PartialEq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct SqlRows {
+    /// Column names, in result order.
+    pub columns: Vec<String>,
+    /// Each row's values, one per column.
+    pub rows: Vec<Vec<SqlValue>>,
+}
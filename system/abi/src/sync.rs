@@ -0,0 +1,25 @@
+//! Barrier and latch hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Payload used to wait at a named barrier until every party has arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct BarrierWait {
+    /// Name of the barrier.
+    pub name: String,
+    /// Number of parties the barrier waits for; every party must agree on it.
+    pub parties: u32,
+}
+
+/// Payload used to count down, or wait on, a named latch.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct LatchRequest {
+    /// Name of the latch.
+    pub name: String,
+    /// Count the latch opens after; every user must agree on it.
+    pub count: u32,
+}
@@ -2,6 +2,7 @@ use rkyv::{Archive, Deserialize, Serialize};
 
 /// Snapshot of the host clock values.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct TimeNow {
     /// Unix timestamp in milliseconds.
@@ -12,6 +13,7 @@ pub struct TimeNow {
 
 /// Request to sleep for a duration in milliseconds.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct TimeSleep {
     /// Duration to sleep in milliseconds.
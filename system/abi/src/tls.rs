@@ -6,6 +6,7 @@ use crate::GuestResourceId;
 
 /// TLS material supplied by a guest for server listeners.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct TlsServerBundle {
     /// PEM-encoded certificate chain presented by the server.
@@ -22,6 +23,7 @@ pub struct TlsServerBundle {
 
 /// TLS material supplied by a guest for client connections.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct TlsClientBundle {
     /// PEM-encoded CA bundle used to verify servers.
@@ -36,6 +38,7 @@ pub struct TlsClientBundle {
 
 /// Arguments for creating a server-side TLS configuration handle.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetTlsServerConfig {
     /// TLS bundle supplied for server listeners.
@@ -44,6 +47,7 @@ pub struct NetTlsServerConfig {
 
 /// Arguments for creating a client-side TLS configuration handle.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetTlsClientConfig {
     /// TLS bundle supplied for client connections.
@@ -52,6 +56,7 @@ pub struct NetTlsClientConfig {
 
 /// Reply containing a TLS configuration handle.
 #[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 #[rkyv(bytecheck())]
 pub struct NetTlsConfigReply {
     /// TLS configuration handle registered in the instance registry.
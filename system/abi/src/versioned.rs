@@ -0,0 +1,135 @@
+//! Payloads whose shape can evolve without breaking older guests.
+//!
+//! An rkyv archive has no room for fields its reader does not expect, so a payload struct can
+//! never change once guests have been built against it. Payloads that need to grow are instead
+//! exchanged through an *envelope*: an enum with one variant per version of the payload, each
+//! holding its version boxed. Boxing keeps the archived envelope the same size however many
+//! versions it gains, so an envelope written by an older guest is still readable by a newer
+//! host.
+//!
+//! To evolve such a payload:
+//! - freeze the current struct under a versioned name (as [`ProcessStartV1`]) and keep it
+//!   unchanged;
+//! - give the struct its new shape, and append a variant for it to the envelope;
+//! - fill in the new fields for older variants in [`Versioned::upgrade`], usually with defaults.
+//!
+//! Variants must only ever be appended, never reordered or removed.
+
+/// A payload exchanged through a versioned envelope.
+pub trait Versioned: Sized {
+    /// Envelope carrying any version of the payload.
+    type Envelope: From<Self>;
+
+    /// Convert whichever version arrived into the current one.
+    fn upgrade(envelope: Self::Envelope) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        AbiSignature, Capability, EntrypointInvocation, ProcessStart, ProcessStartEnvelope,
+        ProcessStartV1, decode_rkyv, encode_rkyv,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+    #[rkyv(bytecheck())]
+    struct ShapeV1 {
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+    #[rkyv(bytecheck())]
+    struct Shape {
+        name: String,
+        weight: u32,
+    }
+
+    /// The envelope as an older guest knew it.
+    #[derive(Debug, Archive, Serialize, Deserialize)]
+    #[rkyv(bytecheck())]
+    enum OldEnvelope {
+        V1(Box<ShapeV1>),
+    }
+
+    /// The envelope as a newer host knows it.
+    #[derive(Debug, Archive, Serialize, Deserialize)]
+    #[rkyv(bytecheck())]
+    enum Envelope {
+        V1(Box<ShapeV1>),
+        V2(Box<Shape>),
+    }
+
+    impl From<Shape> for Envelope {
+        fn from(shape: Shape) -> Self {
+            Self::V2(Box::new(shape))
+        }
+    }
+
+    impl Versioned for Shape {
+        type Envelope = Envelope;
+
+        fn upgrade(envelope: Envelope) -> Self {
+            match envelope {
+                Envelope::V1(shape) => Self {
+                    name: shape.name,
+                    weight: 1,
+                },
+                Envelope::V2(shape) => *shape,
+            }
+        }
+    }
+
+    #[test]
+    fn newer_envelopes_read_older_versions() {
+        let old = OldEnvelope::V1(Box::new(ShapeV1 {
+            name: "anvil".to_string(),
+        }));
+        let bytes = encode_rkyv(&old).expect("encode");
+        let decoded = decode_rkyv::<Envelope>(&bytes).expect("decode older envelope");
+        assert_eq!(
+            Shape::upgrade(decoded),
+            Shape {
+                name: "anvil".to_string(),
+                weight: 1,
+            }
+        );
+
+        let current = Shape {
+            name: "feather".to_string(),
+            weight: 0,
+        };
+        let bytes = encode_rkyv(&Envelope::from(current.clone())).expect("encode");
+        let decoded = decode_rkyv::<Envelope>(&bytes).expect("decode current envelope");
+        assert_eq!(Shape::upgrade(decoded), current);
+    }
+
+    #[test]
+    fn process_start_round_trips_through_its_envelope() {
+        let start = ProcessStart {
+            module_id: "module".to_string(),
+            name: "proc".to_string(),
+            capabilities: Capability::TimeRead.into(),
+            entrypoint: EntrypointInvocation::new(
+                AbiSignature::new(Vec::new(), Vec::new()),
+                Vec::new(),
+            )
+            .expect("invocation"),
+        };
+        let bytes = encode_rkyv(&ProcessStartEnvelope::from(start.clone())).expect("encode");
+        let envelope = decode_rkyv::<ProcessStartEnvelope>(&bytes).expect("decode");
+        assert_eq!(ProcessStart::upgrade(envelope), start);
+
+        let original = ProcessStartEnvelope::V1(Box::new(ProcessStartV1 {
+            module_id: start.module_id.clone(),
+            name: start.name.clone(),
+            capabilities: vec![Capability::TimeRead],
+            entrypoint: start.entrypoint.clone(),
+        }));
+        let bytes = encode_rkyv(&original).expect("encode");
+        let envelope = decode_rkyv::<ProcessStartEnvelope>(&bytes).expect("decode");
+        assert_eq!(ProcessStart::upgrade(envelope), start);
+    }
+}
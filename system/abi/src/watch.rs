@@ -0,0 +1,47 @@
+//! Watched value hostcall payloads.
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::GuestResourceId;
+
+/// Payload used to create a watched value.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct WatchCreate {
+    /// Value the watch starts out holding.
+    pub value: Vec<u8>,
+}
+
+/// Payload used to replace the value of a watch.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct WatchSet {
+    /// Shared handle of the watch.
+    pub watch: GuestResourceId,
+    /// New value.
+    pub value: Vec<u8>,
+}
+
+/// Payload used to wait for a watch to hold a value newer than one already seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct WatchSubscribe {
+    /// Shared handle of the watch.
+    pub watch: GuestResourceId,
+    /// Version the caller last saw; `0` has not seen any.
+    pub seen: u64,
+}
+
+/// A value held by a watch.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+#[rkyv(bytecheck())]
+pub struct WatchValue {
+    /// Version of the value, starting at `1` and increasing with every set.
+    pub version: u64,
+    /// The value.
+    pub value: Vec<u8>,
+}
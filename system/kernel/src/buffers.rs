@@ -0,0 +1,91 @@
+//! Reusable byte buffers for hostcall payloads.
+//!
+//! Every hostcall copies its input out of guest memory before decoding it. Chatty guests make
+//! many small calls, so rather than allocating a fresh buffer each time, the copy borrows an
+//! aligned buffer from a small per-thread pool and hands it back once the input is no longer
+//! needed.
+
+use std::{cell::RefCell, ops::Deref};
+
+use rkyv::util::AlignedVec;
+
+/// Most buffers each thread keeps for reuse.
+pub const MAX_POOLED_BUFFERS: usize = 32;
+/// Largest buffer capacity kept for reuse. Larger buffers are freed, so that one oversized
+/// payload does not pin its memory for the life of the thread.
+pub const MAX_POOLED_CAPACITY: usize = 64 * 1024;
+
+thread_local! {
+    static POOL: RefCell<Vec<AlignedVec>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An aligned copy of a payload, held in a buffer borrowed from the calling thread's pool and
+/// returned to the pool of whichever thread drops it.
+pub struct PooledBuffer {
+    bytes: AlignedVec,
+}
+
+impl PooledBuffer {
+    /// Copy `bytes` into a pooled buffer.
+    pub fn copy_from(bytes: &[u8]) -> Self {
+        let mut buffer = POOL
+            .with_borrow_mut(|pool| pool.pop())
+            .unwrap_or_else(|| AlignedVec::with_capacity(bytes.len()));
+        buffer.extend_from_slice(bytes);
+        Self { bytes: buffer }
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if self.bytes.capacity() > MAX_POOLED_CAPACITY {
+            return;
+        }
+
+        let mut buffer = std::mem::take(&mut self.bytes);
+        buffer.clear();
+        // The pool may already be gone while the thread shuts down; the buffer is freed then.
+        POOL.try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < MAX_POOLED_BUFFERS {
+                pool.push(buffer);
+            }
+        })
+        .unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_are_reused_once_dropped() {
+        let first = PooledBuffer::copy_from(&[1, 2, 3]);
+        let addr = first.as_ptr();
+        drop(first);
+
+        let second = PooledBuffer::copy_from(&[4, 5]);
+        assert_eq!(second.as_ptr(), addr);
+        assert_eq!(&*second, &[4, 5]);
+    }
+
+    #[test]
+    fn oversized_buffers_are_freed() {
+        let large = PooledBuffer::copy_from(&[0; MAX_POOLED_CAPACITY + 1]);
+        drop(large);
+
+        assert!(POOL.with_borrow(|pool| {
+            pool.iter()
+                .all(|buffer| buffer.capacity() <= MAX_POOLED_CAPACITY)
+        }));
+    }
+}
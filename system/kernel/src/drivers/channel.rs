@@ -5,8 +5,6 @@ use std::{
     sync::Arc,
 };
 
-use wasmtime::Caller;
-
 use crate::{
     drivers::io::{
         IoCapability, IoCreateReaderDriver, IoCreateWriterDriver, IoReadDriver, IoWriteDriver,
@@ -148,22 +146,21 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         args: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
 
         let result = (|| -> GuestResult<GuestUint> {
             let channel = inner
                 .create(args.capacity, args.backpressure)
                 .map_err(Into::into)?;
             let ptr = inner.ptr(&channel);
-            let slot = caller
-                .data_mut()
+            let slot = instance
                 .insert(channel, None, ResourceType::Channel)
                 .map_err(GuestError::from)?;
-            if let Some(resource_id) = caller.data().entry(slot) {
+            if let Some(resource_id) = instance.entry(slot) {
                 registry.record_host_ptr(resource_id, &ptr);
             }
             let handle = GuestUint::try_from(slot).map_err(|_| GuestError::InvalidArgument)?;
@@ -183,14 +180,13 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         channel_id: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
         let result = (|| -> GuestResult<()> {
             let slot = channel_id as usize;
-            let channel = caller
-                .data_mut()
+            let channel = instance
                 .remove::<Impl::Channel>(slot)
                 .ok_or(GuestError::NotFound)?;
 
@@ -211,14 +207,13 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         channel_id: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
         let result = (|| -> GuestResult<()> {
             let slot = channel_id as usize;
-            caller
-                .data()
+            instance
                 .with(slot, |chan| this.drain(chan))
                 .ok_or(GuestError::NotFound)?
                 .map_err(Into::into)?;
@@ -238,18 +233,16 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        match caller
-            .data_mut()
+        match instance
             .remove::<Impl::StrongWriter>(input as usize)
             .ok_or(GuestError::NotFound)
             .and_then(|writer| self.0.downgrade_writer(writer).map_err(Into::into))
         {
             Ok(writer) => {
-                let result = caller
-                    .data_mut()
+                let result = instance
                     .insert(writer, None, ResourceType::Writer)
                     .map_err(Into::into)
                     .and_then(|idx| {
@@ -268,12 +261,11 @@ impl Contract for ChannelExportDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         handle: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let registry = caller.data().registry_arc();
-        let result = caller
-            .data()
+        let registry = instance.registry_arc();
+        let result = instance
             .entry(handle as usize)
             .ok_or(GuestError::NotFound)
             .and_then(|rid| registry.share_handle(rid).map_err(GuestError::from));
@@ -288,16 +280,15 @@ impl Contract for ChannelAttachDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         resource_id: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
         let result = registry
             .resolve_shared(resource_id)
             .ok_or(GuestError::NotFound)
             .and_then(|rid| {
-                caller
-                    .data_mut()
+                instance
                     .insert_id(rid)
                     .map_err(GuestError::from)
                     .and_then(|slot| {
@@ -315,11 +306,10 @@ impl Contract for ChannelDetachDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         handle: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let result = caller
-            .data_mut()
+        let result = instance
             .detach_slot(handle as usize)
             .ok_or(GuestError::NotFound)
             .map(|_| ());
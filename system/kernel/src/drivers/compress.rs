@@ -0,0 +1,119 @@
+//! Hostcall drivers that compress and decompress guest payloads on the host.
+//!
+//! Guests handling large payloads hand them to a host-native codec rather than bundling their
+//! own, which would grow the module and run much slower under Wasm.
+
+use std::{future::Future, sync::Arc};
+
+use selium_abi::{CompressDeflate, CompressInflate, CompressionAlgorithm, CompressionLevel};
+
+use crate::{
+    guest_data::GuestResult,
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+
+type CompressionOps<C> = (
+    Arc<Operation<CompressDeflateDriver<C>>>,
+    Arc<Operation<CompressInflateDriver<C>>>,
+);
+
+/// Capability providing the codecs guests compress payloads with.
+pub trait CompressionCapability {
+    /// Compress `data` into `algorithm`'s format, spending `level` effort.
+    fn deflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        level: CompressionLevel,
+        data: Vec<u8>,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static;
+
+    /// Decompress `data` from `algorithm`'s format, failing once the output would exceed
+    /// `max_len` bytes.
+    fn inflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        data: Vec<u8>,
+        max_len: usize,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static;
+}
+
+/// Hostcall driver that compresses a payload.
+pub struct CompressDeflateDriver<Impl>(Impl);
+/// Hostcall driver that decompresses a payload.
+pub struct CompressInflateDriver<Impl>(Impl);
+
+impl<T> CompressionCapability for Arc<T>
+where
+    T: CompressionCapability,
+{
+    fn deflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        level: CompressionLevel,
+        data: Vec<u8>,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static {
+        self.as_ref().deflate(algorithm, level, data)
+    }
+
+    fn inflate(
+        &self,
+        algorithm: CompressionAlgorithm,
+        data: Vec<u8>,
+        max_len: usize,
+    ) -> impl Future<Output = GuestResult<Vec<u8>>> + Send + 'static {
+        self.as_ref().inflate(algorithm, data, max_len)
+    }
+}
+
+impl<Impl> Contract for CompressDeflateDriver<Impl>
+where
+    Impl: CompressionCapability + Send + 'static,
+{
+    type Input = CompressDeflate;
+    type Output = Vec<u8>;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        self.0.deflate(input.algorithm, input.level, input.data)
+    }
+}
+
+impl<Impl> Contract for CompressInflateDriver<Impl>
+where
+    Impl: CompressionCapability + Send + 'static,
+{
+    type Input = CompressInflate;
+    type Output = Vec<u8>;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        // Stop decompressing once the result could no longer be returned, so a small payload
+        // cannot expand into an unbounded allocation on the host.
+        let max_len = selium_abi::hostcall_contract!(COMPRESS_INFLATE).max_output();
+        self.0.inflate(input.algorithm, input.data, max_len)
+    }
+}
+
+/// Build hostcall operations for compression, backed by the codecs of `codecs`.
+pub fn operations<C>(codecs: C) -> CompressionOps<C>
+where
+    C: CompressionCapability + Clone + Send + 'static,
+{
+    (
+        Operation::from_hostcall(
+            CompressDeflateDriver(codecs.clone()),
+            selium_abi::hostcall_contract!(COMPRESS_DEFLATE),
+        ),
+        Operation::from_hostcall(
+            CompressInflateDriver(codecs),
+            selium_abi::hostcall_contract!(COMPRESS_INFLATE),
+        ),
+    )
+}
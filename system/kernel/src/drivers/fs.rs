@@ -0,0 +1,71 @@
+//! Hostcall drivers for the filesystem a guest sees through its sandbox.
+
+use std::sync::Arc;
+
+use futures_util::Stream;
+use selium_abi::{FsEvent, FsWatch};
+
+use crate::{
+    guest_data::GuestResult,
+    operation::{StreamContract, StreamOperation},
+    registry::{InstanceRegistry, ResourceId},
+};
+
+/// Capability resolving guest paths against each process's sandbox and reporting changes to
+/// them.
+pub trait FilesystemWatchCapability {
+    /// Stream changes to `path` in the sandbox of `process`, descending into subdirectories if
+    /// `recursive` is set. Processes without a sandbox, and paths outside it, end the stream
+    /// with an error.
+    fn watch(
+        &self,
+        process: Option<ResourceId>,
+        path: String,
+        recursive: bool,
+    ) -> impl Stream<Item = GuestResult<FsEvent>> + Send + 'static;
+}
+
+/// Hostcall driver that streams changes to a sandbox path.
+pub struct FsWatchDriver<Impl>(Impl);
+
+impl<T> FilesystemWatchCapability for Arc<T>
+where
+    T: FilesystemWatchCapability,
+{
+    fn watch(
+        &self,
+        process: Option<ResourceId>,
+        path: String,
+        recursive: bool,
+    ) -> impl Stream<Item = GuestResult<FsEvent>> + Send + 'static {
+        self.as_ref().watch(process, path, recursive)
+    }
+}
+
+impl<Impl> StreamContract for FsWatchDriver<Impl>
+where
+    Impl: FilesystemWatchCapability + Send + Sync + 'static,
+{
+    type Input = FsWatch;
+    type Item = FsEvent;
+
+    fn to_stream(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Stream<Item = GuestResult<Self::Item>> + Send + 'static {
+        self.0
+            .watch(instance.process(), input.path, input.recursive)
+    }
+}
+
+/// Build the streaming hostcall operation for watching sandbox paths through `filesystem`.
+pub fn watch_operation<C>(filesystem: C) -> Arc<StreamOperation<FsWatchDriver<C>>>
+where
+    C: FilesystemWatchCapability + Send + Sync + 'static,
+{
+    StreamOperation::from_hostcall(
+        FsWatchDriver(filesystem),
+        selium_abi::hostcall_contract!(FS_WATCH),
+    )
+}
@@ -0,0 +1,128 @@
+//! Hostcall drivers for handing a pending hostcall result from one process to another.
+//!
+//! A process shares one of its futures, much as it would share a channel, and passes the
+//! shared handle to another process, which claims the future and awaits the original
+//! hostcall's result. Until it is claimed, the future stays owned by the process that shared
+//! it. Results are handed over as the original hostcall encoded them.
+
+use std::{
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use selium_abi::{GuestResourceId, GuestUint};
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::{GuestFuture, InstanceRegistry},
+};
+
+type FutureOps = (
+    Arc<Operation<FutureShareDriver>>,
+    Arc<Operation<FutureAwaitDriver>>,
+);
+
+/// Hostcall driver that shares a pending future for another process to await.
+pub struct FutureShareDriver;
+/// Hostcall driver that claims a shared future and awaits its result.
+pub struct FutureAwaitDriver;
+
+/// A claimed future, abandoned should its awaiter go away before the result arrives.
+struct Claimed(GuestFuture);
+
+impl Drop for Claimed {
+    fn drop(&mut self) {
+        if self.0.is_pending() {
+            self.0.abandon();
+        }
+    }
+}
+
+impl Contract for FutureShareDriver {
+    type Input = GuestUint;
+    type Output = GuestResourceId;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        handle: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let result = instance
+            .share_future(handle as usize)
+            .map_err(GuestError::from)
+            .and_then(|shared| shared.ok_or(GuestError::NotFound));
+
+        ready(result)
+    }
+}
+
+impl Contract for FutureAwaitDriver {
+    type Input = GuestResourceId;
+    type Output = Vec<u8>;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        shared: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let claimed = instance.take_shared_future(shared).map(Claimed);
+
+        async move {
+            let claimed = claimed.ok_or(GuestError::NotFound)?;
+            Arc::clone(&claimed.0)
+                .next_result()
+                .await
+                .unwrap_or(Err(GuestError::NotFound))
+        }
+    }
+}
+
+/// Build hostcall operations for handing futures between processes.
+pub fn operations() -> FutureOps {
+    (
+        Operation::from_hostcall(
+            FutureShareDriver,
+            selium_abi::hostcall_contract!(FUTURE_SHARE),
+        ),
+        Operation::from_hostcall(
+            FutureAwaitDriver,
+            selium_abi::hostcall_contract!(FUTURE_AWAIT),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::FutureExt;
+
+    use super::*;
+    use crate::{futures::FutureSharedState, registry::Registry};
+
+    #[tokio::test]
+    async fn shared_futures_are_claimed_once_by_another_instance() {
+        let registry = Registry::new();
+        let mut sharer = registry.instance().expect("sharer");
+        let mut awaiter = registry.instance().expect("awaiter");
+        let state = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+        let handle = sharer
+            .insert_future(Arc::clone(&state))
+            .expect("insert future");
+
+        let shared = FutureShareDriver
+            .to_future(&mut sharer, handle as GuestUint)
+            .await
+            .expect("share");
+        assert!(sharer.future_state(handle).is_none());
+
+        let mut result = FutureAwaitDriver.to_future(&mut awaiter, shared).boxed();
+        assert!((&mut result).now_or_never().is_none());
+        state.resolve(Ok(vec![1, 2, 3]));
+        assert_eq!(result.await.expect("result"), vec![1, 2, 3]);
+
+        assert!(matches!(
+            FutureAwaitDriver.to_future(&mut awaiter, shared).await,
+            Err(GuestError::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+//! Hostcall driver that runs allow-listed commands on the host.
+//!
+//! This is an escape hatch for work no hostcall family covers, such as invoking a vendor's
+//! hardware tool. The capability backing it decides which commands exist at all; the driver
+//! logs every invocation and its outcome as an audit warning, whichever capability backs it and
+//! whether or not the runtime keeps an audit log.
+
+use std::{future::Future, sync::Arc};
+
+use selium_abi::{HostExec, HostExecOutput, PayloadEncoding};
+use tracing::warn;
+
+use crate::{
+    guest_data::GuestResult,
+    operation::{Contract, Operation},
+    registry::{InstanceRegistry, ResourceId},
+};
+
+/// Bytes of an encoded [`HostExecOutput`] reserved for everything but the standard output.
+const OUTPUT_ENVELOPE: usize = 64;
+/// Bytes a single byte of standard output may take up once encoded as JSON, e.g. `255,`.
+const JSON_BYTE_LEN: usize = 4;
+
+/// Capability running the host commands guests are allowed to invoke.
+pub trait HostExecCapability {
+    /// Run the command allow-listed as `command` with `args` appended on behalf of `process`,
+    /// refusing names that are not allow-listed and arguments the command does not accept. The
+    /// command is killed, and the call fails, if it writes more than `max_stdout` bytes to its
+    /// standard output.
+    fn exec(
+        &self,
+        process: Option<ResourceId>,
+        command: String,
+        args: Vec<String>,
+        max_stdout: usize,
+    ) -> impl Future<Output = GuestResult<HostExecOutput>> + Send + 'static;
+}
+
+/// Hostcall driver that runs a host command.
+pub struct HostExecDriver<Impl>(Impl);
+
+impl<T> HostExecCapability for Arc<T>
+where
+    T: HostExecCapability,
+{
+    fn exec(
+        &self,
+        process: Option<ResourceId>,
+        command: String,
+        args: Vec<String>,
+        max_stdout: usize,
+    ) -> impl Future<Output = GuestResult<HostExecOutput>> + Send + 'static {
+        self.as_ref().exec(process, command, args, max_stdout)
+    }
+}
+
+impl<Impl> Contract for HostExecDriver<Impl>
+where
+    Impl: HostExecCapability + Send + 'static,
+{
+    type Input = HostExec;
+    type Output = HostExecOutput;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let process = instance.process();
+        warn!(
+            ?process,
+            command = %input.command,
+            args = ?input.args,
+            "audit: host command invoked"
+        );
+        let command = input.command.clone();
+        // Stop reading once the output could no longer be returned, so a chatty command cannot
+        // grow an unbounded buffer on the host.
+        let encoding = instance
+            .extension::<PayloadEncoding>()
+            .map(|encoding| *encoding)
+            .unwrap_or_default();
+        let exec = self
+            .0
+            .exec(process, input.command, input.args, max_stdout(encoding));
+
+        async move {
+            let result = exec.await;
+            match &result {
+                Ok(output) => warn!(
+                    ?process,
+                    %command,
+                    status = ?output.status,
+                    stdout_len = output.stdout.len(),
+                    "audit: host command exited"
+                ),
+                Err(err) => warn!(?process, %command, %err, "audit: host command failed"),
+            }
+            result
+        }
+    }
+}
+
+/// Most standard output a host command may write for its result to still fit the hostcall's
+/// output once encoded with `encoding`.
+pub fn max_stdout(encoding: PayloadEncoding) -> usize {
+    let room = selium_abi::hostcall_contract!(HOST_EXEC)
+        .max_output()
+        .saturating_sub(OUTPUT_ENVELOPE);
+    match encoding {
+        PayloadEncoding::Rkyv => room,
+        PayloadEncoding::Json => room / JSON_BYTE_LEN,
+    }
+}
+
+/// Build the hostcall operation for running host commands through `commands`.
+pub fn operation<C>(commands: C) -> Arc<Operation<HostExecDriver<C>>>
+where
+    C: HostExecCapability + Send + 'static,
+{
+    Operation::from_hostcall(
+        HostExecDriver(commands),
+        selium_abi::hostcall_contract!(HOST_EXEC),
+    )
+}
@@ -1,13 +1,13 @@
 use std::{future::ready, sync::Arc};
 
+use rkyv::Archived;
 use selium_abi::hostcalls::Hostcall;
 use selium_abi::{GuestUint, IoFrame, IoRead, IoWrite};
-use wasmtime::Caller;
 
 use crate::{
-    guest_data::{GuestError, GuestResult},
+    guest_data::{ArchivedPayload, GuestError, GuestResult},
     operation::{Contract, Operation},
-    registry::{InstanceRegistry, ResourceHandle, ResourceType},
+    registry::{InstanceRegistry, ResourceHandle, ResourceId, ResourceType},
 };
 
 /// The capabilities that any subsystem implementation needs to provide
@@ -86,15 +86,12 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
-        let idx = caller
-            .data()
-            .entry(input as usize)
-            .ok_or(GuestError::NotFound);
-        let registry = caller.data().registry_arc();
+        let idx = instance.entry(input as usize).ok_or(GuestError::NotFound);
+        let registry = instance.registry_arc();
 
         let result = (|| -> GuestResult<GuestUint> {
             let idx = idx?;
@@ -105,11 +102,10 @@ where
                 .expect("Invalid resource id from InstanceRegistry")
                 .map_err(Into::into)?;
 
-            let slot = caller
-                .data_mut()
+            let slot = instance
                 .insert(reader, None, ResourceType::Reader)
                 .map_err(GuestError::from)?;
-            if let Some(resource_id) = caller.data().entry(slot) {
+            if let Some(resource_id) = instance.entry(slot) {
                 registry.record_parent(resource_id, idx);
             }
             GuestUint::try_from(slot).map_err(|_| GuestError::InvalidArgument)
@@ -128,15 +124,14 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
-        let idx = caller
-            .data()
+        let idx = instance
             .entry(input.handle as usize)
             .ok_or(GuestError::NotFound);
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
         let len = input.len as usize;
 
         async move {
@@ -160,15 +155,12 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
-        let idx = caller
-            .data()
-            .entry(input as usize)
-            .ok_or(GuestError::NotFound);
-        let registry = caller.data().registry_arc();
+        let idx = instance.entry(input as usize).ok_or(GuestError::NotFound);
+        let registry = instance.registry_arc();
 
         let result = (|| -> GuestResult<GuestUint> {
             let idx = idx?;
@@ -179,11 +171,10 @@ where
                 .expect("Invalid resource id from InstanceRegistry")
                 .map_err(Into::into)?;
 
-            let slot = caller
-                .data_mut()
+            let slot = instance
                 .insert(writer, None, ResourceType::Writer)
                 .map_err(GuestError::from)?;
-            if let Some(resource_id) = caller.data().entry(slot) {
+            if let Some(resource_id) = instance.entry(slot) {
                 registry.record_parent(resource_id, idx);
             }
             GuestUint::try_from(slot).map_err(|_| GuestError::InvalidArgument)
@@ -202,16 +193,15 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let this = self.0.clone();
         let payload = input.payload;
-        let idx = caller
-            .data()
+        let idx = instance
             .entry(input.handle as usize)
             .ok_or(GuestError::NotFound);
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
         let payload_len = payload.len();
 
         async move {
@@ -228,6 +218,40 @@ where
             Ok(count)
         }
     }
+
+    const ARCHIVED_INPUT: bool = true;
+
+    /// Writes straight from the archived payload, sparing a copy of every write.
+    fn to_future_archived(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: ArchivedPayload<Self::Input>,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+        let this = self.0.clone();
+        let idx = instance
+            .entry(input.get().handle.to_native() as usize)
+            .ok_or(GuestError::NotFound);
+        let registry = instance.registry_arc();
+
+        async move {
+            let payload_len = input.get().payload.len();
+            registry
+                .with_async(ResourceHandle::<Impl::Writer>::new(idx?), move |writer| {
+                    Box::pin(async move { this.write(writer, &input.get().payload).await })
+                })
+                .await
+                .expect("Invalid resource id from InstanceRegistry")
+                .map_err(Into::into)?;
+
+            let count =
+                GuestUint::try_from(payload_len).map_err(|_| GuestError::InvalidArgument)?;
+            Ok(count)
+        }
+    }
+
+    fn archived_resource(&self, _input: &Archived<Self::Input>) -> Option<ResourceId> {
+        None
+    }
 }
 
 pub fn create_reader_op<C>(
@@ -0,0 +1,236 @@
+//! Hostcall drivers for named locks that guests use to coordinate on shared resources.
+//!
+//! A lock is held until its holder releases it or its time-to-live runs out, so a guest that
+//! exits while holding one cannot wedge the others. Every acquisition returns a fresh token,
+//! which increases across acquisitions and can be used as a fencing token.
+
+use std::{
+    collections::HashMap,
+    future::{Future, ready},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use selium_abi::{LockAcquire, LockRelease};
+use tokio::{sync::Notify, time::Instant};
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+
+type LockOps<C> = (
+    Arc<Operation<LockAcquireDriver<C>>>,
+    Arc<Operation<LockReleaseDriver<C>>>,
+);
+
+/// Capability holding the named locks guests acquire.
+///
+/// [`LocalLocks`] keeps them in this host; an implementation backed by a shared store extends
+/// them across hosts.
+pub trait LockCapability {
+    /// Acquire the lock `name` for at most `ttl`, waiting while another holder has it, and
+    /// return the token identifying this acquisition.
+    fn acquire(
+        &self,
+        name: String,
+        ttl: Duration,
+    ) -> impl Future<Output = GuestResult<u64>> + Send + 'static;
+
+    /// Release the lock `name` if it is still held under `token`.
+    fn release(&self, name: &str, token: u64) -> GuestResult<()>;
+}
+
+/// Hostcall driver that acquires a named lock.
+pub struct LockAcquireDriver<Impl>(Impl);
+/// Hostcall driver that releases a named lock.
+pub struct LockReleaseDriver<Impl>(Impl);
+
+/// Locks held in this host's memory. Clones share the same locks.
+#[derive(Clone, Default)]
+pub struct LocalLocks {
+    inner: Arc<LocalLocksInner>,
+}
+
+#[derive(Default)]
+struct LocalLocksInner {
+    held: Mutex<HashMap<String, Held>>,
+    released: Notify,
+    next_token: AtomicU64,
+}
+
+/// A current holder of a lock.
+struct Held {
+    token: u64,
+    expires: Instant,
+}
+
+impl<T> LockCapability for Arc<T>
+where
+    T: LockCapability,
+{
+    fn acquire(
+        &self,
+        name: String,
+        ttl: Duration,
+    ) -> impl Future<Output = GuestResult<u64>> + Send + 'static {
+        self.as_ref().acquire(name, ttl)
+    }
+
+    fn release(&self, name: &str, token: u64) -> GuestResult<()> {
+        self.as_ref().release(name, token)
+    }
+}
+
+impl LockCapability for LocalLocks {
+    // Not an `async fn`: the returned future must not borrow `self`.
+    #[allow(clippy::manual_async_fn)]
+    fn acquire(
+        &self,
+        name: String,
+        ttl: Duration,
+    ) -> impl Future<Output = GuestResult<u64>> + Send + 'static {
+        let inner = Arc::clone(&self.inner);
+        async move {
+            if ttl.is_zero() {
+                return Err(GuestError::InvalidArgument);
+            }
+            loop {
+                // Registered before the lock is inspected, so a release in between still wakes
+                // this waiter.
+                let released = inner.released.notified();
+                tokio::pin!(released);
+                released.as_mut().enable();
+
+                let expires = {
+                    let mut held = inner.held.lock();
+                    let now = Instant::now();
+                    match held.get(&name) {
+                        Some(holder) if holder.expires > now => holder.expires,
+                        _ => {
+                            let token = inner.next_token.fetch_add(1, Ordering::Relaxed) + 1;
+                            held.insert(
+                                name,
+                                Held {
+                                    token,
+                                    expires: now + ttl,
+                                },
+                            );
+                            return Ok(token);
+                        }
+                    }
+                };
+
+                tokio::select! {
+                    _ = released => {}
+                    _ = tokio::time::sleep_until(expires) => {}
+                }
+            }
+        }
+    }
+
+    fn release(&self, name: &str, token: u64) -> GuestResult<()> {
+        let mut held = self.inner.held.lock();
+        match held.get(name) {
+            Some(holder) if holder.token == token => {
+                held.remove(name);
+                drop(held);
+                self.inner.released.notify_waiters();
+                Ok(())
+            }
+            // Held under another token: this acquisition expired and the lock moved on.
+            Some(_) => Err(GuestError::PermissionDenied),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<Impl> Contract for LockAcquireDriver<Impl>
+where
+    Impl: LockCapability + Send + 'static,
+{
+    type Input = LockAcquire;
+    type Output = u64;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        self.0
+            .acquire(input.name, Duration::from_millis(input.ttl_ms))
+    }
+}
+
+impl<Impl> Contract for LockReleaseDriver<Impl>
+where
+    Impl: LockCapability + Send + 'static,
+{
+    type Input = LockRelease;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        ready(self.0.release(&input.name, input.token))
+    }
+}
+
+/// Build hostcall operations for named locks, held in `locks`.
+pub fn operations<C>(locks: C) -> LockOps<C>
+where
+    C: LockCapability + Clone + Send + 'static,
+{
+    (
+        Operation::from_hostcall(
+            LockAcquireDriver(locks.clone()),
+            selium_abi::hostcall_contract!(LOCK_ACQUIRE),
+        ),
+        Operation::from_hostcall(
+            LockReleaseDriver(locks),
+            selium_abi::hostcall_contract!(LOCK_RELEASE),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn locks_pass_to_waiters_on_release_or_expiry() {
+        let locks = LocalLocks::default();
+        let ttl = Duration::from_millis(20);
+        let first = locks
+            .acquire("jobs".to_string(), Duration::from_secs(60))
+            .await
+            .expect("first");
+
+        let mut second = locks.acquire("jobs".to_string(), ttl).boxed();
+        assert!((&mut second).now_or_never().is_none());
+        locks.release("jobs", first).expect("release");
+        let second = second.await.expect("second");
+        assert!(second > first);
+
+        // The second holder never releases, so the third waits out its time-to-live.
+        let third = locks.acquire("jobs".to_string(), ttl).await.expect("third");
+        assert!(third > second);
+        assert!(matches!(
+            locks.release("jobs", second),
+            Err(GuestError::PermissionDenied)
+        ));
+        assert!(matches!(
+            locks.acquire("jobs".to_string(), Duration::ZERO).await,
+            Err(GuestError::InvalidArgument)
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+//! Hostcall drivers that let guests introspect their own linkage and the host they run on.
+
+use std::{
+    env,
+    ffi::CStr,
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    idempotency::PendingIdempotencyKey,
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+use selium_abi::{
+    Capability, CapabilitySet, HostInfo, IdempotencyKey, InstanceDiagnostics, PayloadEncoding,
+    hostcalls,
+};
+
+/// Environment variables naming the process locale, in order of precedence.
+const LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Capabilities an instance was granted when it was linked.
+///
+/// Attach this as an instance extension so that [`HostcallsDriver`] can report which hostcalls
+/// resolve to real drivers rather than trapping stubs.
+#[derive(Clone, Debug, Default)]
+pub struct GrantedCapabilities(CapabilitySet);
+
+/// Whether an instance has reported that it finished initialising.
+///
+/// Attach this as an instance extension so that the host can wait for [`ReadyDriver`] calls,
+/// for example before retiring the process a reloaded module replaces.
+#[derive(Debug, Default)]
+pub struct ProcessReadiness {
+    ready: AtomicBool,
+    notify: Notify,
+}
+
+/// Hostcall driver that lists the catalogue hostcalls linked for the calling instance.
+pub struct HostcallsDriver;
+/// Hostcall driver that attaches an idempotency key to the calling instance's next hostcall.
+pub struct IdempotencyKeyDriver;
+/// Hostcall driver that marks the calling instance as ready.
+pub struct ReadyDriver;
+/// Hostcall driver that reports the calling instance's pending futures, mailbox counters and
+/// slot usage.
+pub struct DiagnosticsDriver;
+/// Hostcall driver that switches the calling instance's later hostcalls to the payload encoding
+/// it requests, if this build supports it, and reports the encoding in effect.
+pub struct EncodingDriver;
+/// Hostcall driver that reports the environment of the host, as configured when it was built.
+pub struct HostInfoDriver(HostInfo);
+
+impl GrantedCapabilities {
+    /// Record the capabilities granted to an instance.
+    pub fn new(capabilities: impl IntoIterator<Item = Capability>) -> Self {
+        Self(capabilities.into_iter().collect())
+    }
+
+    /// Capabilities granted to the instance.
+    pub fn capabilities(&self) -> CapabilitySet {
+        self.0
+    }
+}
+
+impl ProcessReadiness {
+    /// Mark the instance ready and wake every waiter.
+    pub fn mark_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+        self.notify.notify_waiters();
+    }
+
+    /// Whether the instance has reported ready.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Wait until the instance reports ready.
+    pub async fn wait(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_ready() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Contract for HostcallsDriver {
+    type Input = ();
+    type Output = Vec<String>;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let names = instance
+            .extension::<GrantedCapabilities>()
+            .map(|granted| {
+                hostcalls::granted(granted.capabilities())
+                    .map(|meta| meta.name.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        std::future::ready(Ok(names))
+    }
+}
+
+impl Contract for IdempotencyKeyDriver {
+    type Input = IdempotencyKey;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        // The key is stored as soon as the call is created, so the guest may issue the keyed
+        // hostcall without awaiting this one first.
+        let result = PendingIdempotencyKey::set(instance, input.key).map_err(GuestError::from);
+        std::future::ready(result)
+    }
+}
+
+impl Contract for ReadyDriver {
+    type Input = ();
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        if let Some(readiness) = instance.extension::<ProcessReadiness>() {
+            readiness.mark_ready();
+        }
+        std::future::ready(Ok(()))
+    }
+}
+
+impl Contract for DiagnosticsDriver {
+    type Input = ();
+    type Output = InstanceDiagnostics;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        // Taken when the call is created, so the report does not include this call's own future.
+        let diagnostics = instance.diagnostics().ok_or(GuestError::NotFound);
+        std::future::ready(diagnostics)
+    }
+}
+
+impl Contract for EncodingDriver {
+    type Input = PayloadEncoding;
+    type Output = PayloadEncoding;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        // This call's own reply keeps the encoding in effect when it was created, so the guest
+        // can decode it before switching.
+        let result = if input.is_supported() {
+            instance
+                .insert_extension(input)
+                .map(|()| input)
+                .map_err(GuestError::from)
+        } else {
+            Ok(instance
+                .extension::<PayloadEncoding>()
+                .map(|encoding| *encoding)
+                .unwrap_or_default())
+        };
+        std::future::ready(result)
+    }
+}
+
+impl Contract for HostInfoDriver {
+    type Input = ();
+    type Output = HostInfo;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        std::future::ready(Ok(self.0.clone()))
+    }
+}
+
+/// Describe the host this process runs on. Fields that cannot be determined are left empty.
+pub fn detect_host_info() -> HostInfo {
+    HostInfo {
+        os_family: env::consts::FAMILY.to_string(),
+        arch: env::consts::ARCH.to_string(),
+        locale: LOCALE_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_default(),
+        hostname: hostname().unwrap_or_default(),
+    }
+}
+
+/// Describe the host as [`detect_host_info`] does, leaving the hostname empty if
+/// `redact_hostname` is set.
+pub fn guest_host_info(redact_hostname: bool) -> HostInfo {
+    let mut info = detect_host_info();
+    if redact_hostname {
+        info.hostname.clear();
+    }
+    info
+}
+
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: `buf` is valid for writes of its full length, and the last byte is never
+    // written, so the name is NUL-terminated even if it was truncated.
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len() - 1) };
+    if rc != 0 {
+        return None;
+    }
+    let name = CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Build the hostcall introspection operation.
+pub fn operation() -> Arc<Operation<HostcallsDriver>> {
+    Operation::new(HostcallsDriver, hostcalls::META_HOSTCALLS)
+}
+
+/// Build the operation that attaches idempotency keys to hostcalls.
+pub fn idempotency_key_operation() -> Arc<Operation<IdempotencyKeyDriver>> {
+    Operation::new(IdempotencyKeyDriver, hostcalls::META_IDEMPOTENCY_KEY)
+}
+
+/// Build the operation that reports an instance as ready.
+pub fn ready_operation() -> Arc<Operation<ReadyDriver>> {
+    Operation::new(ReadyDriver, hostcalls::META_READY)
+}
+
+/// Build the operation that reports an instance's async state.
+pub fn diagnostics_operation() -> Arc<Operation<DiagnosticsDriver>> {
+    Operation::new(DiagnosticsDriver, hostcalls::META_DIAGNOSTICS)
+}
+
+/// Build the operation that negotiates an instance's payload encoding.
+pub fn encoding_operation() -> Arc<Operation<EncodingDriver>> {
+    Operation::new(EncodingDriver, hostcalls::META_ENCODING)
+}
+
+/// Build the operation that reports `info` as the environment of the host.
+///
+/// Pass [`guest_host_info`], or [`detect_host_info`] with any fields policy withholds from
+/// guests cleared.
+pub fn host_info_operation(info: HostInfo) -> Arc<Operation<HostInfoDriver>> {
+    Operation::new(HostInfoDriver(info), hostcalls::META_HOST_INFO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::BlockingKernelClient;
+
+    #[test]
+    fn host_info_withholds_the_hostname_when_redacted() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let detected = detect_host_info();
+
+        let info = client
+            .call(&host_info_operation(guest_host_info(false)), &())
+            .expect("host info");
+        assert_eq!(info, detected);
+
+        let info = client
+            .call(&host_info_operation(guest_host_info(true)), &())
+            .expect("host info");
+        assert_eq!(
+            info,
+            HostInfo {
+                hostname: String::new(),
+                ..detected
+            }
+        );
+    }
+}
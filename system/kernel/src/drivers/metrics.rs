@@ -0,0 +1,223 @@
+//! Hostcall drivers for guest metrics: named counters and gauges kept by the kernel and
+//! reported by the host alongside its own metrics.
+
+use std::{
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use selium_abi::{CounterAdd, ErrorCode, GaugeAdd, GaugeSet};
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    metrics::{GuestMetricError, GuestMetrics},
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+
+type MetricsOps = (
+    Arc<Operation<CounterAddDriver>>,
+    Arc<Operation<GaugeSetDriver>>,
+    Arc<Operation<GaugeAddDriver>>,
+);
+
+/// Hostcall driver that adds to a counter.
+pub struct CounterAddDriver(GuestMetrics);
+/// Hostcall driver that sets a gauge.
+pub struct GaugeSetDriver(GuestMetrics);
+/// Hostcall driver that adds to a gauge.
+pub struct GaugeAddDriver(GuestMetrics);
+
+impl Contract for CounterAddDriver {
+    type Input = CounterAdd;
+    type Output = u64;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        ready(
+            self.0
+                .add_counter(&input.name, input.delta)
+                .map_err(GuestError::from),
+        )
+    }
+}
+
+impl Contract for GaugeSetDriver {
+    type Input = GaugeSet;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        ready(
+            self.0
+                .set_gauge(&input.name, input.value)
+                .map_err(GuestError::from),
+        )
+    }
+}
+
+impl Contract for GaugeAddDriver {
+    type Input = GaugeAdd;
+    type Output = i64;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        ready(
+            self.0
+                .add_gauge(&input.name, input.delta)
+                .map_err(GuestError::from),
+        )
+    }
+}
+
+impl From<GuestMetricError> for GuestError {
+    fn from(value: GuestMetricError) -> Self {
+        let code = match value {
+            GuestMetricError::InvalidName | GuestMetricError::KindMismatch(_) => {
+                ErrorCode::InvalidArgument
+            }
+            GuestMetricError::TooMany => ErrorCode::Subsystem,
+        };
+        GuestError::Coded(code, value.to_string())
+    }
+}
+
+/// Build hostcall operations updating the guest metrics in `metrics`.
+pub fn operations(metrics: GuestMetrics) -> MetricsOps {
+    (
+        Operation::from_hostcall(
+            CounterAddDriver(metrics.clone()),
+            selium_abi::hostcall_contract!(METRICS_COUNTER_ADD),
+        ),
+        Operation::from_hostcall(
+            GaugeSetDriver(metrics.clone()),
+            selium_abi::hostcall_contract!(METRICS_GAUGE_SET),
+        ),
+        Operation::from_hostcall(
+            GaugeAddDriver(metrics),
+            selium_abi::hostcall_contract!(METRICS_GAUGE_ADD),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metrics::GuestMetricsSnapshot, registry::Registry};
+
+    fn counter_add(name: &str, delta: u64) -> CounterAdd {
+        CounterAdd {
+            name: name.to_string(),
+            delta,
+        }
+    }
+
+    fn gauge_add(name: &str, delta: i64) -> GaugeAdd {
+        GaugeAdd {
+            name: name.to_string(),
+            delta,
+        }
+    }
+
+    #[tokio::test]
+    async fn counters_and_gauges_wrap_rather_than_fail() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let metrics = GuestMetrics::default();
+        let counter = CounterAddDriver(metrics.clone());
+        let set = GaugeSetDriver(metrics.clone());
+        let add = GaugeAddDriver(metrics.clone());
+
+        let total = counter
+            .to_future(&mut instance, counter_add("requests_total", u64::MAX))
+            .await
+            .expect("add counter");
+        assert_eq!(total, u64::MAX);
+        let total = counter
+            .to_future(&mut instance, counter_add("requests_total", 2))
+            .await
+            .expect("add counter");
+        assert_eq!(total, 1);
+
+        set.to_future(
+            &mut instance,
+            GaugeSet {
+                name: "in_flight".to_string(),
+                value: 3,
+            },
+        )
+        .await
+        .expect("set gauge");
+        let value = add
+            .to_future(&mut instance, gauge_add("in_flight", -5))
+            .await
+            .expect("add gauge");
+        assert_eq!(value, -2);
+
+        set.to_future(
+            &mut instance,
+            GaugeSet {
+                name: "in_flight".to_string(),
+                value: i64::MAX,
+            },
+        )
+        .await
+        .expect("set gauge");
+        let value = add
+            .to_future(&mut instance, gauge_add("in_flight", 1))
+            .await
+            .expect("add gauge");
+        assert_eq!(value, i64::MIN);
+
+        assert_eq!(
+            metrics.snapshot(),
+            GuestMetricsSnapshot {
+                counters: vec![("requests_total".to_string(), 1)],
+                gauges: vec![("in_flight".to_string(), i64::MIN)],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_metrics_start_at_zero_and_keep_their_kind() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let metrics = GuestMetrics::default();
+        let counter = CounterAddDriver(metrics.clone());
+        let add = GaugeAddDriver(metrics.clone());
+
+        assert!(metrics.snapshot().gauges.is_empty());
+        let value = add
+            .to_future(&mut instance, gauge_add("queue_depth", -1))
+            .await
+            .expect("add gauge");
+        assert_eq!(value, -1);
+
+        let err = counter
+            .to_future(&mut instance, counter_add("queue_depth", 1))
+            .await
+            .expect_err("counter over a gauge");
+        assert!(matches!(
+            err,
+            GuestError::Coded(ErrorCode::InvalidArgument, _)
+        ));
+        let err = add
+            .to_future(&mut instance, gauge_add("selium_queue_depth", 1))
+            .await
+            .expect_err("host metric name");
+        assert!(matches!(
+            err,
+            GuestError::Coded(ErrorCode::InvalidArgument, _)
+        ));
+        assert_eq!(metrics.snapshot().gauges.len(), 1);
+    }
+}
@@ -1,10 +1,21 @@
 pub use selium_abi::{Capability, CapabilityDecodeError};
 
 pub mod channel;
+pub mod compress;
+pub mod fs;
+pub mod future;
+pub mod host;
 pub mod io;
+pub mod lock;
+pub mod meta;
+pub mod metrics;
 pub mod module_store;
 pub mod net;
 pub mod process;
+pub mod services;
 pub mod session;
 pub mod singleton;
+pub mod sql;
+pub mod sync;
 pub mod time;
+pub mod watch;
@@ -1,12 +1,22 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::SystemTime};
 
+use parking_lot::RwLock;
+use selium_abi::ErrorCode;
 use thiserror::Error;
+
+use crate::guest_data::GuestError;
 // use wasmtime::Linker;
 
 // use crate::{KernelError, registry::InstanceRegistry};
 
 pub trait ModuleStoreReadCapability {
     fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError>;
+
+    /// Revision of the module stored as `module_id`, which changes whenever its contents do, or
+    /// `None` if the store cannot tell without reading the module.
+    fn revision(&self, _module_id: &str) -> Option<ModuleRevision> {
+        None
+    }
 }
 
 // @todo Should this capability be linked?
@@ -16,21 +26,120 @@ pub trait ModuleStoreReadCapability {
 //     }
 // }
 
+/// Module store holding bytes registered programmatically, so that embedders and tests can spawn
+/// modules without touching the filesystem.
+#[derive(Debug, Default)]
+pub struct InMemoryModuleStore {
+    modules: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+/// Identifies one version of a stored module, so that copies of it can be told from copies of
+/// the module it was replaced with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModuleRevision {
+    /// Digest of the module's contents, for stores that keep one.
+    Digest(Vec<u8>),
+    /// Modification time and length of the file holding the module.
+    Modified(SystemTime, u64),
+}
+
 #[derive(Error, Debug)]
 pub enum ModuleStoreError {
     #[error("Path validation failed for {0}: {1}")]
     InvalidPath(PathBuf, String),
     #[error("Error reading filesytem: {0}")]
     Filesystem(String),
+    #[error("Module signature rejected for {0}: {1}")]
+    Signature(PathBuf, String),
+    #[error("Module rejected by policy for {0}: {1}")]
+    Policy(PathBuf, String),
+    #[error("Module not found: {0}")]
+    NotFound(String),
+}
+
+impl InMemoryModuleStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `bytes` under `module_id`, returning the module it replaces, if any.
+    pub fn insert(
+        &self,
+        module_id: impl Into<String>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        self.modules.write().insert(module_id.into(), bytes.into())
+    }
+
+    /// Unregister `module_id`, returning its bytes if it was registered.
+    pub fn remove(&self, module_id: &str) -> Option<Vec<u8>> {
+        self.modules.write().remove(module_id)
+    }
+
+    /// Whether a module is registered under `module_id`.
+    pub fn contains(&self, module_id: &str) -> bool {
+        self.modules.read().contains_key(module_id)
+    }
+}
+
+impl<T> ModuleStoreReadCapability for Arc<T>
+where
+    T: ModuleStoreReadCapability + ?Sized,
+{
+    fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError> {
+        self.as_ref().read(module_id)
+    }
+
+    fn revision(&self, module_id: &str) -> Option<ModuleRevision> {
+        self.as_ref().revision(module_id)
+    }
+}
+
+impl ModuleStoreReadCapability for InMemoryModuleStore {
+    fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError> {
+        self.modules
+            .read()
+            .get(module_id)
+            .cloned()
+            .ok_or_else(|| ModuleStoreError::NotFound(module_id.to_string()))
+    }
 }
 
 // impl<T> ModuleStoreReadLinker for T where T: ModuleStoreReadCapability + 'static {}
 
-impl From<ModuleStoreError> for i32 {
-    fn from(value: ModuleStoreError) -> Self {
+impl From<&ModuleStoreError> for ErrorCode {
+    fn from(value: &ModuleStoreError) -> Self {
         match value {
-            ModuleStoreError::InvalidPath(_, _) => -300,
-            ModuleStoreError::Filesystem(_) => -301,
+            ModuleStoreError::InvalidPath(_, _) => ErrorCode::InvalidModulePath,
+            ModuleStoreError::Filesystem(_) => ErrorCode::ModuleStoreFilesystem,
+            ModuleStoreError::Signature(_, _) => ErrorCode::InvalidSignature,
+            ModuleStoreError::Policy(_, _) => ErrorCode::PermissionDenied,
+            ModuleStoreError::NotFound(_) => ErrorCode::NotFound,
         }
     }
 }
+
+impl From<ModuleStoreError> for GuestError {
+    fn from(value: ModuleStoreError) -> Self {
+        GuestError::Coded(ErrorCode::from(&value), value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_modules_are_read_by_id() {
+        let store = InMemoryModuleStore::new();
+        assert!(store.insert("echo", b"\0asm".to_vec()).is_none());
+        assert_eq!(store.read("echo").expect("registered"), b"\0asm");
+
+        let err = store.read("missing").expect_err("unregistered");
+        assert_eq!(ErrorCode::from(&err), ErrorCode::NotFound);
+
+        assert_eq!(store.remove("echo"), Some(b"\0asm".to_vec()));
+        assert!(!store.contains("echo"));
+    }
+}
@@ -1,8 +1,6 @@
 use futures_util::future::BoxFuture;
 use std::{future::Future, sync::Arc};
 
-use wasmtime::Caller;
-
 use crate::{
     drivers::io::{self, IoCapability, IoReadDriver, IoWriteDriver},
     guest_data::{GuestError, GuestResult},
@@ -132,19 +130,19 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registrar = caller.data().registrar();
-        let registry = caller.data().registry_arc();
+        let registrar = instance.registrar();
+        let registry = instance.registry_arc();
         let NetCreateListener {
             protocol,
             domain,
             port,
             tls,
         } = input;
-        let tls = resolve_tls_server_config(caller.data(), &registry, protocol, tls);
+        let tls = resolve_tls_server_config(instance, &registry, protocol, tls);
 
         async move {
             let handle = inner
@@ -175,15 +173,15 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registrar = caller.data().registrar();
-        let registry = caller.data().registry_arc();
+        let registrar = instance.registrar();
+        let registry = instance.registry_arc();
         let handle = (|| {
             let slot = usize::try_from(input.handle).map_err(|_| GuestError::InvalidArgument)?;
-            caller.data().entry(slot).ok_or(GuestError::NotFound)
+            instance.entry(slot).ok_or(GuestError::NotFound)
         })();
 
         async move {
@@ -230,19 +228,19 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registrar = caller.data().registrar();
-        let registry = caller.data().registry_arc();
+        let registrar = instance.registrar();
+        let registry = instance.registry_arc();
         let NetConnect {
             protocol,
             domain,
             port,
             tls,
         } = input;
-        let tls = resolve_tls_client_config(caller.data(), &registry, protocol, tls);
+        let tls = resolve_tls_client_config(instance, &registry, protocol, tls);
 
         async move {
             let (reader, writer, remote_addr) = inner
@@ -1,30 +1,39 @@
 use std::{
+    collections::VecDeque,
     convert::TryFrom,
     future::{Future, ready},
     marker::PhantomData,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
+use parking_lot::Mutex;
 use selium_abi::{
-    AbiParam, AbiScalarType, AbiScalarValue, AbiValue, EntrypointArg, EntrypointInvocation,
-    GuestResourceId, ProcessLogLookup, ProcessLogRegistration, ProcessStart,
+    AbiParam, AbiScalarType, AbiScalarValue, AbiValue, CapabilitySet, EntrypointArg,
+    EntrypointInvocation, GuestResourceId, ProcessInfo, ProcessLogLookup, ProcessLogRegistration,
+    ProcessMessage, ProcessPanic, ProcessSelfStats, ProcessStart, ProcessStartEnvelope, Versioned,
+    hostcalls,
 };
-use tracing::debug;
-use wasmtime::Caller;
+use tokio::sync::Notify;
+use tracing::{debug, error};
 
 use crate::{
     KernelError,
-    drivers::Capability,
+    events::KernelEvent,
     guest_data::{GuestError, GuestResult},
-    operation::{Contract, Operation},
+    operation::{Contract, HostcallContext, Operation},
     registry::{
         InstanceRegistry, ProcessIdentity, Registry, ResourceHandle, ResourceId, ResourceType,
     },
+    session::Session,
 };
 
 type ProcessLifecycleOps<C> = (
     Arc<Operation<ProcessStartDriver<C>>>,
     Arc<Operation<ProcessStopDriver<C>>>,
+    Arc<Operation<ProcessWaitDriver<C>>>,
 );
 
 type ProcessLogOps<C> = (
@@ -32,6 +41,9 @@ type ProcessLogOps<C> = (
     Arc<Operation<ProcessLogLookupDriver<C>>>,
 );
 
+/// Messages a process's inbox holds before further sends are refused.
+const INBOX_CAPACITY: usize = 64;
+
 /// Capability responsible for starting/stopping guest instances.
 pub trait ProcessLifecycleCapability {
     type Process: Send;
@@ -44,7 +56,7 @@ pub trait ProcessLifecycleCapability {
         process_id: ResourceId,
         module_id: &str,
         name: &str,
-        capabilities: Vec<Capability>,
+        capabilities: CapabilitySet,
         entrypoint: EntrypointInvocation,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 
@@ -53,16 +65,74 @@ pub trait ProcessLifecycleCapability {
         &self,
         instance: &mut Self::Process,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Wait for a process to exit, returning the rkyv-encoded value its entrypoint completed
+    /// with, or an empty buffer if it completed without one.
+    fn wait(
+        &self,
+        instance: Self::Process,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
 }
 
 /// Hostcall driver that starts new processes.
 pub struct ProcessStartDriver<Impl>(Impl);
 /// Hostcall driver that stops running processes.
 pub struct ProcessStopDriver<Impl>(Impl);
+/// Hostcall driver that waits for a process to exit and returns its completion value.
+pub struct ProcessWaitDriver<Impl>(Impl);
+/// Hostcall driver that records the calling instance's completion value.
+pub struct ProcessCompleteDriver;
 /// Hostcall driver that records the logging channel exported by a process.
 pub struct ProcessRegisterLogDriver<Impl>(PhantomData<Impl>);
 /// Hostcall driver that fetches the logging channel for a running process.
 pub struct ProcessLogLookupDriver<Impl>(PhantomData<Impl>);
+/// Hostcall driver that reports runtime statistics for a running process.
+pub struct ProcessInfoDriver;
+/// Hostcall driver that pushes a message into another process's inbox.
+pub struct ProcessSendDriver;
+/// Hostcall driver that takes the next message from the calling instance's inbox.
+pub struct ProcessReceiveDriver;
+/// Hostcall driver that reports the calling instance's own resource usage.
+pub struct ProcessSelfStatsDriver;
+/// Hostcall driver that logs a panic reported by the calling instance and records it.
+pub struct ProcessReportPanicDriver;
+
+/// Resource usage of a running process, published by its runtime.
+///
+/// Runtimes attach this as an instance extension and update it while the guest executes.
+#[derive(Debug, Default)]
+pub struct ProcessUsage {
+    fuel_consumed: AtomicU64,
+    memory_bytes: AtomicU64,
+}
+
+/// Value a process's entrypoint completed with, recorded through [`ProcessCompleteDriver`].
+///
+/// Runtimes attach this as an instance extension and take the value once the entrypoint
+/// returns, so that it can be handed to whoever waits on the process.
+#[derive(Debug, Default)]
+pub struct ProcessCompletion {
+    value: Mutex<Option<Vec<u8>>>,
+}
+
+/// Panic a process reported through [`ProcessReportPanicDriver`] before trapping.
+///
+/// Runtimes attach this as an instance extension and attach the panic to the error the guest
+/// traps with, so that it explains the trap.
+#[derive(Debug, Default)]
+pub struct ProcessPanicked {
+    panic: Mutex<Option<ProcessPanic>>,
+}
+
+/// Control messages sent to a process through [`ProcessSendDriver`], oldest first.
+///
+/// Runtimes attach this as an instance extension so that other processes can reach it through
+/// the process handle, without setting up a channel first.
+#[derive(Debug, Default)]
+pub struct ProcessInbox {
+    messages: Mutex<VecDeque<Vec<u8>>>,
+    arrived: Notify,
+}
 
 impl<T> ProcessLifecycleCapability for Arc<T>
 where
@@ -77,7 +147,7 @@ where
         process_id: ResourceId,
         module_id: &str,
         name: &str,
-        capabilities: Vec<Capability>,
+        capabilities: CapabilitySet,
         entrypoint: EntrypointInvocation,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.as_ref().start(
@@ -96,44 +166,60 @@ where
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.as_ref().stop(instance)
     }
+
+    fn wait(
+        &self,
+        instance: Self::Process,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+        self.as_ref().wait(instance)
+    }
 }
 
 impl<Impl> Contract for ProcessStartDriver<Impl>
 where
     Impl: ProcessLifecycleCapability + Clone + Send + 'static,
 {
-    type Input = ProcessStart;
+    type Input = ProcessStartEnvelope;
     type Output = GuestResourceId;
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
+        let parent_session = instance
+            .extension::<HostcallContext>()
+            .map(|context| context.session());
         let ProcessStart {
             module_id,
             name,
             capabilities,
             entrypoint,
-        } = input;
+        } = ProcessStart::upgrade(input);
 
         let preparation =
-            (|| -> GuestResult<(String, String, Vec<Capability>, EntrypointInvocation)> {
+            (|| -> GuestResult<(String, String, CapabilitySet, EntrypointInvocation)> {
                 entrypoint
                     .validate()
                     .map_err(|err| GuestError::from(KernelError::Driver(err.to_string())))?;
-                let entrypoint = resolve_entrypoint_resources(entrypoint, caller.data())?;
+                let entrypoint = resolve_entrypoint_resources(entrypoint, instance)?;
                 Ok((module_id, name, capabilities, entrypoint))
             })();
 
         async move {
             let (module_id, name, capabilities, entrypoint) = preparation?;
-            debug!(%module_id, %name, capabilities = ?capabilities, "process_start requested");
+            debug!(%module_id, %name, %capabilities, "process_start requested");
             let process_id = registry
                 .reserve(None, ResourceType::Process)
                 .map_err(GuestError::from)?;
+            if let Some(parent) = parent_session
+                && let Err(err) = spawn_session(&registry, parent, process_id, capabilities)
+            {
+                registry.discard(process_id);
+                return Err(err);
+            }
 
             match inner
                 .start(
@@ -160,6 +246,32 @@ where
     }
 }
 
+/// Register the session `process_id` calls as: one derived from the spawning `parent` session,
+/// entitled to `capabilities` only as far as `parent` is, so a guest cannot start a process
+/// more privileged than itself.
+fn spawn_session(
+    registry: &Registry,
+    parent: ResourceId,
+    process_id: ResourceId,
+    capabilities: CapabilitySet,
+) -> GuestResult<()> {
+    let session = registry
+        .with(ResourceHandle::<Session>::new(parent), |parent| {
+            parent.derive(capabilities, [0; 32])
+        })
+        .ok_or(GuestError::PermissionDenied)?
+        .map_err(|_| GuestError::PermissionDenied)?;
+    let session = registry
+        .add(session, Some(process_id), ResourceType::Session)?
+        .into_id();
+    registry.events().emit(KernelEvent::SessionCreated {
+        session_id: session,
+        parent_id: Some(parent),
+        process_id: Some(process_id),
+    });
+    Ok(())
+}
+
 impl<Impl> Contract for ProcessStopDriver<Impl>
 where
     Impl: ProcessLifecycleCapability + Clone + Send + 'static,
@@ -169,11 +281,11 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
 
         async move {
             let handle = ResourceId::try_from(input).map_err(|_| GuestError::InvalidArgument)?;
@@ -189,6 +301,63 @@ where
             Ok(())
         }
     }
+
+    fn resource(&self, input: &Self::Input) -> Option<ResourceId> {
+        ResourceId::try_from(*input).ok()
+    }
+}
+
+impl<Impl> Contract for ProcessWaitDriver<Impl>
+where
+    Impl: ProcessLifecycleCapability + Clone + Send + 'static,
+{
+    type Input = GuestResourceId;
+    type Output = Vec<u8>;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let inner = self.0.clone();
+        let registry = instance.registry_arc();
+
+        async move {
+            let handle = ResourceId::try_from(input).map_err(|_| GuestError::InvalidArgument)?;
+            if let Some(meta) = registry.metadata(handle)
+                && meta.kind != ResourceType::Process
+            {
+                return Err(GuestError::InvalidArgument);
+            }
+            // Waiting consumes the process, as stopping it does: it leaves the registry now and
+            // cannot be stopped or waited on again.
+            let process = registry
+                .remove(ResourceHandle::<Impl::Process>::new(handle))
+                .ok_or(GuestError::NotFound)?;
+            inner.wait(process).await.map_err(Into::into)
+        }
+    }
+
+    fn resource(&self, input: &Self::Input) -> Option<ResourceId> {
+        ResourceId::try_from(*input).ok()
+    }
+}
+
+impl Contract for ProcessCompleteDriver {
+    type Input = Vec<u8>;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let result = instance
+            .extension::<ProcessCompletion>()
+            .map(|completion| completion.set(input))
+            .ok_or(GuestError::PermissionDenied);
+        ready(result)
+    }
 }
 
 impl<Impl> Contract for ProcessRegisterLogDriver<Impl>
@@ -200,14 +369,13 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let identity = caller
-            .data()
+        let identity = instance
             .extension::<ProcessIdentity>()
             .map(|identity| *identity);
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
 
         ready((|| -> GuestResult<Self::Output> {
             let identity = identity.ok_or(GuestError::PermissionDenied)?;
@@ -245,10 +413,10 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
 
         ready(
             ResourceId::try_from(input.process_id)
@@ -264,6 +432,207 @@ where
     }
 }
 
+impl Contract for ProcessInfoDriver {
+    type Input = GuestResourceId;
+    type Output = ProcessInfo;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+
+        ready(
+            ResourceId::try_from(input)
+                .map_err(|_| GuestError::InvalidArgument)
+                .and_then(|id| match registry.metadata(id) {
+                    Some(meta) if meta.kind == ResourceType::Process => Ok(ProcessInfo {
+                        fuel_consumed: registry
+                            .process_extension::<ProcessUsage>(id)
+                            .map(|usage| usage.fuel_consumed()),
+                        trap: registry.process_trap(id),
+                    }),
+                    Some(_) => Err(GuestError::InvalidArgument),
+                    None => Err(GuestError::NotFound),
+                }),
+        )
+    }
+
+    fn resource(&self, input: &Self::Input) -> Option<ResourceId> {
+        ResourceId::try_from(*input).ok()
+    }
+}
+
+impl Contract for ProcessSendDriver {
+    type Input = ProcessMessage;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+
+        ready(
+            ResourceId::try_from(input.process_id)
+                .map_err(|_| GuestError::InvalidArgument)
+                .and_then(|id| match registry.metadata(id) {
+                    Some(meta) if meta.kind == ResourceType::Process => registry
+                        .process_extension::<ProcessInbox>(id)
+                        .ok_or(GuestError::NotFound),
+                    Some(_) => Err(GuestError::InvalidArgument),
+                    None => Err(GuestError::NotFound),
+                })
+                .and_then(|inbox| {
+                    if inbox.push(input.payload) {
+                        Ok(())
+                    } else {
+                        Err(GuestError::Subsystem("process inbox is full".to_string()))
+                    }
+                }),
+        )
+    }
+
+    fn resource(&self, input: &Self::Input) -> Option<ResourceId> {
+        ResourceId::try_from(input.process_id).ok()
+    }
+}
+
+impl Contract for ProcessReceiveDriver {
+    type Input = ();
+    type Output = Vec<u8>;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let inbox = instance.extension::<ProcessInbox>();
+
+        async move {
+            let inbox = inbox.ok_or(GuestError::PermissionDenied)?;
+            Ok(inbox.pop().await)
+        }
+    }
+}
+
+impl Contract for ProcessSelfStatsDriver {
+    type Input = ();
+    type Output = ProcessSelfStats;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        _input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let usage = instance.extension::<ProcessUsage>();
+        ready(Ok(ProcessSelfStats {
+            memory_bytes: usage.as_ref().and_then(|usage| usage.memory_bytes()),
+            fuel_consumed: usage.as_ref().map(|usage| usage.fuel_consumed()),
+            hostcalls: instance.hostcalls_issued(),
+            pending_futures: u32::try_from(instance.pending_futures()).unwrap_or(u32::MAX),
+        }))
+    }
+}
+
+impl Contract for ProcessReportPanicDriver {
+    type Input = ProcessPanic;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let process_id = instance.process();
+        error!(
+            process_id,
+            location = %input.location,
+            "guest panicked: {}",
+            input.message
+        );
+        if let Some(panicked) = instance.extension::<ProcessPanicked>() {
+            panicked.set(input);
+        }
+        ready(Ok(()))
+    }
+}
+
+impl ProcessUsage {
+    /// Record the total fuel consumed by the process so far.
+    pub fn record_fuel(&self, consumed: u64) {
+        self.fuel_consumed.store(consumed, Ordering::Relaxed);
+    }
+
+    /// Total fuel consumed as of the last update.
+    pub fn fuel_consumed(&self) -> u64 {
+        self.fuel_consumed.load(Ordering::Relaxed)
+    }
+
+    /// Record the current size of the process's linear memory.
+    pub fn record_memory(&self, bytes: usize) {
+        let bytes = u64::try_from(bytes).unwrap_or(u64::MAX);
+        self.memory_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Size of the process's linear memory in bytes as of the last update, or `None` if its
+    /// runtime has not recorded it.
+    pub fn memory_bytes(&self) -> Option<u64> {
+        Some(self.memory_bytes.load(Ordering::Relaxed)).filter(|bytes| *bytes > 0)
+    }
+}
+
+impl ProcessPanicked {
+    /// Record the panic, replacing any recorded earlier.
+    pub fn set(&self, panic: ProcessPanic) {
+        *self.panic.lock() = Some(panic);
+    }
+
+    /// The panic recorded, if the process has reported one.
+    pub fn get(&self) -> Option<ProcessPanic> {
+        self.panic.lock().clone()
+    }
+}
+
+impl ProcessCompletion {
+    /// Record the completion value, replacing any recorded earlier.
+    pub fn set(&self, value: Vec<u8>) {
+        *self.value.lock() = Some(value);
+    }
+
+    /// Take the recorded completion value, if any.
+    pub fn take(&self) -> Option<Vec<u8>> {
+        self.value.lock().take()
+    }
+}
+
+impl ProcessInbox {
+    /// Queue a message, waking the receiver. Returns `false`, dropping the message, if the
+    /// inbox is full.
+    pub fn push(&self, message: Vec<u8>) -> bool {
+        let mut messages = self.messages.lock();
+        if messages.len() >= INBOX_CAPACITY {
+            return false;
+        }
+        messages.push_back(message);
+        drop(messages);
+        self.arrived.notify_one();
+        true
+    }
+
+    /// Wait for the oldest queued message and take it.
+    pub async fn pop(&self) -> Vec<u8> {
+        loop {
+            if let Some(message) = self.messages.lock().pop_front() {
+                return message;
+            }
+            self.arrived.notified().await;
+        }
+    }
+}
+
 /// Helpers for working with entrypoint invocations inside the kernel.
 pub trait EntrypointInvocationExt {
     fn materialise_values(
@@ -327,12 +696,7 @@ fn resolve_entrypoint_resources(
     let signature = entrypoint.signature;
     let mut resolved = Vec::with_capacity(entrypoint.args.len());
 
-    for (index, (param, arg)) in signature
-        .params()
-        .iter()
-        .zip(entrypoint.args.into_iter())
-        .enumerate()
-    {
+    for (index, (param, arg)) in signature.params().iter().zip(entrypoint.args).enumerate() {
         let arg = match (param, arg) {
             (AbiParam::Scalar(AbiScalarType::I32), EntrypointArg::Resource(handle)) => {
                 let slot = usize::try_from(handle)
@@ -373,12 +737,52 @@ where
             selium_abi::hostcall_contract!(PROCESS_START),
         ),
         Operation::from_hostcall(
-            ProcessStopDriver(cap),
+            ProcessStopDriver(cap.clone()),
             selium_abi::hostcall_contract!(PROCESS_STOP),
         ),
+        Operation::from_hostcall(
+            ProcessWaitDriver(cap),
+            selium_abi::hostcall_contract!(PROCESS_WAIT),
+        ),
     )
 }
 
+/// Build the operation that records an instance's completion value.
+pub fn complete_operation() -> Arc<Operation<ProcessCompleteDriver>> {
+    Operation::new(ProcessCompleteDriver, hostcalls::PROCESS_COMPLETE)
+}
+
+/// Build the hostcall operation that reports process statistics.
+pub fn info_op() -> Arc<Operation<ProcessInfoDriver>> {
+    Operation::from_hostcall(
+        ProcessInfoDriver,
+        selium_abi::hostcall_contract!(PROCESS_INFO),
+    )
+}
+
+/// Build the hostcall operation that pushes messages into process inboxes.
+pub fn send_op() -> Arc<Operation<ProcessSendDriver>> {
+    Operation::from_hostcall(
+        ProcessSendDriver,
+        selium_abi::hostcall_contract!(PROCESS_SEND),
+    )
+}
+
+/// Build the operation that takes messages from the calling instance's inbox.
+pub fn receive_operation() -> Arc<Operation<ProcessReceiveDriver>> {
+    Operation::new(ProcessReceiveDriver, hostcalls::PROCESS_RECEIVE)
+}
+
+/// Build the operation that reports the calling instance's own resource usage.
+pub fn self_stats_operation() -> Arc<Operation<ProcessSelfStatsDriver>> {
+    Operation::new(ProcessSelfStatsDriver, hostcalls::PROCESS_SELF_STATS)
+}
+
+/// Build the operation that records panics reported by the calling instance.
+pub fn report_panic_operation() -> Arc<Operation<ProcessReportPanicDriver>> {
+    Operation::new(ProcessReportPanicDriver, hostcalls::PROCESS_REPORT_PANIC)
+}
+
 /// Build hostcall operations for process log channel metadata.
 pub fn log_ops<C>() -> ProcessLogOps<C>
 where
@@ -395,3 +799,151 @@ where
         ),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use futures_util::FutureExt;
+
+    use selium_abi::TrapReason;
+
+    use super::*;
+    use crate::testing::BlockingKernelClient;
+
+    #[tokio::test]
+    async fn send_delivers_to_the_process_inbox_in_order() {
+        let registry = Registry::new();
+        let mut sender = registry.instance().expect("sender registry");
+        let mut receiver = registry.instance().expect("receiver registry");
+        let process_id = registry
+            .add((), None, ResourceType::Process)
+            .expect("add process")
+            .into_id();
+        let handle = GuestResourceId::try_from(process_id).expect("process handle");
+        receiver.set_process_id(process_id).expect("set process id");
+        receiver
+            .insert_extension(ProcessInbox::default())
+            .expect("insert inbox");
+
+        let mut pending = ProcessReceiveDriver.to_future(&mut receiver, ()).boxed();
+        assert!((&mut pending).now_or_never().is_none());
+
+        for payload in [b"first".to_vec(), b"second".to_vec()] {
+            ProcessSendDriver
+                .to_future(
+                    &mut sender,
+                    ProcessMessage {
+                        process_id: handle,
+                        payload,
+                    },
+                )
+                .await
+                .expect("send");
+        }
+        assert_eq!(pending.await.expect("receive"), b"first");
+        assert_eq!(
+            ProcessReceiveDriver
+                .to_future(&mut receiver, ())
+                .await
+                .expect("receive"),
+            b"second"
+        );
+    }
+
+    #[test]
+    fn self_stats_report_the_callers_own_usage() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let operation = self_stats_operation();
+
+        let stats = client.call(&operation, &()).expect("self stats");
+        assert_eq!(
+            stats,
+            ProcessSelfStats {
+                memory_bytes: None,
+                fuel_consumed: None,
+                hostcalls: 1,
+                pending_futures: 0,
+            }
+        );
+
+        let usage = ProcessUsage::default();
+        usage.record_fuel(1_500);
+        usage.record_memory(65_536);
+        client
+            .instance()
+            .insert_extension(usage)
+            .expect("insert usage");
+        let pending = receive_operation();
+        client
+            .instance()
+            .insert_extension(ProcessInbox::default())
+            .expect("insert inbox");
+        let handle = client
+            .start(
+                pending.as_linkable().as_ref(),
+                &selium_abi::encode_rkyv(&()).expect("encode"),
+            )
+            .expect("start receive");
+
+        let stats = client.call(&operation, &()).expect("self stats");
+        assert_eq!(
+            stats,
+            ProcessSelfStats {
+                memory_bytes: Some(65_536),
+                fuel_consumed: Some(1_500),
+                hostcalls: 3,
+                pending_futures: 1,
+            }
+        );
+        assert!(client.drop_call(handle));
+    }
+
+    #[test]
+    fn info_reports_why_a_process_trapped() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let registry = Arc::clone(client.registry());
+        let process_id = registry
+            .add((), None, ResourceType::Process)
+            .expect("add process")
+            .into_id();
+        let handle = GuestResourceId::try_from(process_id).expect("process handle");
+        let operation = info_op();
+
+        let info = client.call(&operation, &handle).expect("info");
+        assert_eq!(info.trap, None);
+
+        registry
+            .record_trap(process_id, TrapReason::FuelExhausted)
+            .expect("record trap");
+        let info = client.call(&operation, &handle).expect("info");
+        assert_eq!(info.trap, Some(TrapReason::FuelExhausted));
+
+        registry.discard(process_id);
+        assert_eq!(registry.process_trap(process_id), None);
+        assert!(registry.record_trap(process_id, TrapReason::Other).is_err());
+    }
+
+    #[test]
+    fn reported_panics_are_recorded() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let operation = report_panic_operation();
+        let panic = ProcessPanic {
+            message: "index out of bounds".to_string(),
+            location: "src/lib.rs:12:5".to_string(),
+        };
+
+        client
+            .call(&operation, &panic)
+            .expect("report without extension");
+        client
+            .instance()
+            .insert_extension(ProcessPanicked::default())
+            .expect("insert extension");
+        client.call(&operation, &panic).expect("report");
+
+        let panicked = client
+            .instance()
+            .extension::<ProcessPanicked>()
+            .expect("extension");
+        assert_eq!(panicked.get(), Some(panic));
+    }
+}
@@ -0,0 +1,305 @@
+//! Hostcall drivers for the service directory, which holds many instances per service name.
+
+use std::{
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+use selium_abi::{
+    GuestResourceId, ServiceHealthUpdate, ServiceInstance, ServiceLookup, ServiceRegister,
+};
+
+type ServiceOps = (
+    Arc<Operation<ServiceRegisterDriver>>,
+    Arc<Operation<ServiceSetHealthDriver>>,
+    Arc<Operation<ServiceResolveDriver>>,
+    Arc<Operation<ServiceInstancesDriver>>,
+);
+
+/// Hostcall driver that adds an instance to a service.
+pub struct ServiceRegisterDriver;
+/// Hostcall driver that records the health an instance reports.
+pub struct ServiceSetHealthDriver;
+/// Hostcall driver that resolves a service to one of its instances.
+pub struct ServiceResolveDriver;
+/// Hostcall driver that lists the instances of a service.
+pub struct ServiceInstancesDriver;
+
+impl Contract for ServiceRegisterDriver {
+    type Input = ServiceRegister;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let ServiceRegister { id, name, resource } = input;
+
+        ready((|| -> GuestResult<Self::Output> {
+            let resource_id = registry
+                .resolve_shared(resource)
+                .ok_or(GuestError::NotFound)?;
+            registry.metadata(resource_id).ok_or(GuestError::NotFound)?;
+            if !registry.register_service(id, &name, resource_id)? {
+                return Err(GuestError::StableIdExists);
+            }
+            Ok(())
+        })())
+    }
+}
+
+impl Contract for ServiceSetHealthDriver {
+    type Input = ServiceHealthUpdate;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let caller = instance.process();
+        let ServiceHealthUpdate {
+            id,
+            resource,
+            health,
+        } = input;
+
+        ready((|| -> GuestResult<Self::Output> {
+            let resource_id = registry
+                .resolve_shared(resource)
+                .ok_or(GuestError::NotFound)?;
+            // Only the process serving an instance may speak for its health.
+            if caller.is_none() || registry.owning_process(resource_id) != caller {
+                return Err(GuestError::PermissionDenied);
+            }
+            if !registry.set_service_health(id, resource_id, health)? {
+                return Err(GuestError::NotFound);
+            }
+            Ok(())
+        })())
+    }
+}
+
+impl Contract for ServiceResolveDriver {
+    type Input = ServiceLookup;
+    type Output = GuestResourceId;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let ServiceLookup { id } = input;
+
+        ready((|| -> GuestResult<Self::Output> {
+            let resource_id = registry.resolve_service(id).ok_or(GuestError::NotFound)?;
+            registry.share_handle(resource_id).map_err(GuestError::from)
+        })())
+    }
+}
+
+impl Contract for ServiceInstancesDriver {
+    type Input = ServiceLookup;
+    type Output = Vec<ServiceInstance>;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let ServiceLookup { id } = input;
+
+        ready(
+            registry
+                .service_instances(id)
+                .into_iter()
+                .map(|(resource_id, health)| {
+                    Ok(ServiceInstance {
+                        resource: registry.share_handle(resource_id)?,
+                        health,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Build hostcall operations for the service directory.
+pub fn operations() -> ServiceOps {
+    (
+        Operation::from_hostcall(
+            ServiceRegisterDriver,
+            selium_abi::hostcall_contract!(SERVICE_REGISTER),
+        ),
+        Operation::from_hostcall(
+            ServiceSetHealthDriver,
+            selium_abi::hostcall_contract!(SERVICE_SET_HEALTH),
+        ),
+        Operation::from_hostcall(
+            ServiceResolveDriver,
+            selium_abi::hostcall_contract!(SERVICE_RESOLVE),
+        ),
+        Operation::from_hostcall(
+            ServiceInstancesDriver,
+            selium_abi::hostcall_contract!(SERVICE_INSTANCES),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{DependencyId, ServiceHealth};
+
+    use super::*;
+    use crate::registry::{Registry, ResourceId, ResourceType};
+
+    const NAME: &str = "tests.service";
+
+    /// A process serving one shared resource, and the instance it calls hostcalls through.
+    fn serving_process(registry: &Arc<Registry>) -> (InstanceRegistry, ResourceId) {
+        let process_id = registry
+            .add((), None, ResourceType::Process)
+            .expect("add process")
+            .into_id();
+        let mut instance = registry.instance().expect("instance registry");
+        instance.set_process_id(process_id).expect("set process id");
+        let resource_id = registry
+            .add((), Some(process_id), ResourceType::Other)
+            .expect("add resource")
+            .into_id();
+        (instance, resource_id)
+    }
+
+    fn register(resource: GuestResourceId) -> ServiceRegister {
+        ServiceRegister {
+            id: DependencyId::from_name(NAME),
+            name: NAME.to_string(),
+            resource,
+        }
+    }
+
+    #[tokio::test]
+    async fn instances_register_resolve_and_report_their_health() {
+        let registry = Registry::new();
+        let (mut instance, resource_id) = serving_process(&registry);
+        let resource = registry.share_handle(resource_id).expect("share resource");
+        let id = DependencyId::from_name(NAME);
+
+        ServiceRegisterDriver
+            .to_future(&mut instance, register(resource))
+            .await
+            .expect("register");
+        assert_eq!(
+            ServiceResolveDriver
+                .to_future(&mut instance, ServiceLookup { id })
+                .await
+                .expect("resolve"),
+            resource
+        );
+
+        ServiceSetHealthDriver
+            .to_future(
+                &mut instance,
+                ServiceHealthUpdate {
+                    id,
+                    resource,
+                    health: ServiceHealth::Degraded,
+                },
+            )
+            .await
+            .expect("set health");
+        assert_eq!(
+            ServiceInstancesDriver
+                .to_future(&mut instance, ServiceLookup { id })
+                .await
+                .expect("instances"),
+            [ServiceInstance {
+                resource,
+                health: ServiceHealth::Degraded,
+            }]
+        );
+
+        ServiceSetHealthDriver
+            .to_future(
+                &mut instance,
+                ServiceHealthUpdate {
+                    id,
+                    resource,
+                    health: ServiceHealth::Unhealthy,
+                },
+            )
+            .await
+            .expect("set health");
+        assert!(matches!(
+            ServiceResolveDriver
+                .to_future(&mut instance, ServiceLookup { id })
+                .await,
+            Err(GuestError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_services_and_duplicate_registrations_are_refused() {
+        let registry = Registry::new();
+        let (mut instance, resource_id) = serving_process(&registry);
+        let (mut other, _) = serving_process(&registry);
+        let resource = registry.share_handle(resource_id).expect("share resource");
+        let unknown = DependencyId::from_name("tests.unknown");
+
+        assert!(matches!(
+            ServiceResolveDriver
+                .to_future(&mut instance, ServiceLookup { id: unknown })
+                .await,
+            Err(GuestError::NotFound)
+        ));
+        assert!(
+            ServiceInstancesDriver
+                .to_future(&mut instance, ServiceLookup { id: unknown })
+                .await
+                .expect("instances")
+                .is_empty()
+        );
+
+        ServiceRegisterDriver
+            .to_future(&mut instance, register(resource))
+            .await
+            .expect("register");
+        assert!(matches!(
+            ServiceRegisterDriver
+                .to_future(&mut instance, register(resource))
+                .await,
+            Err(GuestError::StableIdExists)
+        ));
+
+        let update = |id, health| ServiceHealthUpdate {
+            id,
+            resource,
+            health,
+        };
+        assert!(matches!(
+            ServiceSetHealthDriver
+                .to_future(&mut instance, update(unknown, ServiceHealth::Healthy))
+                .await,
+            Err(GuestError::NotFound)
+        ));
+        assert!(matches!(
+            ServiceSetHealthDriver
+                .to_future(
+                    &mut other,
+                    update(DependencyId::from_name(NAME), ServiceHealth::Unhealthy)
+                )
+                .await,
+            Err(GuestError::PermissionDenied)
+        ));
+    }
+}
@@ -1,13 +1,12 @@
 use std::{convert::TryFrom, future::ready, sync::Arc};
 
-use wasmtime::Caller;
-
 use crate::{
     drivers::Capability,
+    events::KernelEvent,
     guest_data::{GuestError, GuestResult},
     operation::{Contract, Operation},
     registry::{InstanceRegistry, ResourceId, ResourceType},
-    session::Session,
+    session::{Session, SessionError},
 };
 use selium_abi::{SessionCreate, SessionEntitlement, SessionRemove, SessionResource};
 
@@ -121,7 +120,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -129,8 +128,7 @@ where
 
         let result = (|| -> GuestResult<u32> {
             let parent_slot = session_id as usize;
-            let new_session = match caller
-                .data()
+            let new_session = match instance
                 .with::<Session, _>(parent_slot, |session| inner.clone().create(session, pubkey))
             {
                 Some(Ok(session)) => session,
@@ -139,15 +137,13 @@ where
             };
 
             let slot = {
-                caller
-                    .data_mut()
+                instance
                     .insert(new_session, None, ResourceType::Session)
                     .map_err(GuestError::from)?
             };
 
             {
-                let granted = caller
-                    .data()
+                let granted = instance
                     .with::<Session, _>(parent_slot, |session| {
                         session.grant_resource(Capability::SessionLifecycle, slot)
                     })
@@ -159,6 +155,16 @@ where
             }
 
             let handle = u32::try_from(slot).map_err(|_| GuestError::InvalidArgument)?;
+            if let Some(session_id) = instance.entry(slot) {
+                instance
+                    .registry()
+                    .events()
+                    .emit(KernelEvent::SessionCreated {
+                        session_id,
+                        parent_id: instance.entry(parent_slot),
+                        process_id: instance.process(),
+                    });
+            }
             Ok(handle)
         })();
 
@@ -175,7 +181,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -189,22 +195,27 @@ where
             let session_slot = session_id as usize;
             let target_slot = target_id as usize;
 
-            let authorised = caller
-                .data()
+            let (authorised, entitled) = instance
                 .with::<Session, _>(session_slot, |parent| {
-                    parent.authorise(Capability::SessionLifecycle, target_slot)
+                    (
+                        parent.authorise(Capability::SessionLifecycle, target_slot),
+                        parent.entitled(capability),
+                    )
                 })
                 .ok_or(GuestError::NotFound)?;
 
             if !authorised {
                 return Err(GuestError::PermissionDenied);
             }
+            // Entitlements only ever propagate downwards: a session cannot grant a capability
+            // it does not hold itself.
+            if !entitled {
+                return Err(SessionError::EntitlementScope.into());
+            }
 
-            match caller
-                .data_mut()
-                .with::<Session, _>(target_slot, move |target| {
-                    inner.clone().add_entitlement(target, capability)
-                }) {
+            match instance.with::<Session, _>(target_slot, move |target| {
+                inner.clone().add_entitlement(target, capability)
+            }) {
                 Some(Ok(())) => Ok(()),
                 Some(Err(err)) => Err(err.into()),
                 None => Err(GuestError::NotFound),
@@ -224,7 +235,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -238,8 +249,7 @@ where
             let session_slot = session_id as usize;
             let target_slot = target_id as usize;
 
-            let authorised = caller
-                .data()
+            let authorised = instance
                 .with::<Session, _>(session_slot, |parent| {
                     parent.authorise(Capability::SessionLifecycle, target_slot)
                 })
@@ -249,11 +259,9 @@ where
                 return Err(GuestError::PermissionDenied);
             }
 
-            match caller
-                .data_mut()
-                .with::<Session, _>(target_slot, move |target| {
-                    inner.clone().rm_entitlement(target, capability)
-                }) {
+            match instance.with::<Session, _>(target_slot, move |target| {
+                inner.clone().rm_entitlement(target, capability)
+            }) {
                 Some(Ok(())) => Ok(()),
                 Some(Err(err)) => Err(err.into()),
                 None => Err(GuestError::NotFound),
@@ -273,7 +281,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -290,24 +298,28 @@ where
             let resource_slot =
                 ResourceId::try_from(resource_id).map_err(|_| GuestError::InvalidArgument)?;
 
-            let authorised = caller
-                .data()
+            let (authorised, reachable) = instance
                 .with::<Session, _>(session_slot, |parent| {
-                    parent.authorise(Capability::SessionLifecycle, target_slot)
+                    (
+                        parent.authorise(Capability::SessionLifecycle, target_slot),
+                        parent.authorise(capability, resource_slot),
+                    )
                 })
                 .ok_or(GuestError::NotFound)?;
 
             if !authorised {
                 return Err(GuestError::PermissionDenied);
             }
+            // Likewise, a session can only grant access to resources it can reach itself.
+            if !reachable {
+                return Err(SessionError::EntitlementScope.into());
+            }
 
-            match caller
-                .data_mut()
-                .with::<Session, _>(target_slot, move |target| {
-                    inner
-                        .clone()
-                        .add_resource(target, capability, resource_slot)
-                }) {
+            match instance.with::<Session, _>(target_slot, move |target| {
+                inner
+                    .clone()
+                    .add_resource(target, capability, resource_slot)
+            }) {
                 Some(Ok(true)) => Ok(1),
                 Some(Ok(false)) => Ok(0),
                 Some(Err(err)) => Err(err.into()),
@@ -328,7 +340,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -345,8 +357,7 @@ where
             let resource_slot =
                 ResourceId::try_from(resource_id).map_err(|_| GuestError::InvalidArgument)?;
 
-            let authorised = caller
-                .data()
+            let authorised = instance
                 .with::<Session, _>(session_slot, |parent| {
                     parent.authorise(Capability::SessionLifecycle, target_slot)
                 })
@@ -356,11 +367,9 @@ where
                 return Err(GuestError::PermissionDenied);
             }
 
-            match caller
-                .data_mut()
-                .with::<Session, _>(target_slot, move |target| {
-                    inner.clone().rm_resource(target, capability, resource_slot)
-                }) {
+            match instance.with::<Session, _>(target_slot, move |target| {
+                inner.clone().rm_resource(target, capability, resource_slot)
+            }) {
                 Some(Ok(removed)) => Ok(if removed { 1 } else { 0 }),
                 Some(Err(err)) => Err(err.into()),
                 None => Err(GuestError::NotFound),
@@ -380,7 +389,7 @@ where
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
         let inner = self.0.clone();
@@ -393,8 +402,7 @@ where
             let session_slot = session_id as usize;
             let target_slot = target_id as usize;
 
-            let authorised = caller
-                .data()
+            let authorised = instance
                 .with::<Session, _>(session_slot, |parent| {
                     parent.authorise(Capability::SessionLifecycle, target_slot)
                 })
@@ -404,16 +412,15 @@ where
                 return Err(GuestError::PermissionDenied);
             }
 
-            if let Some(Err(err)) = caller
-                .data()
-                .with::<Session, _>(target_slot, |target| inner.clone().remove(target))
+            if let Some(Err(err)) =
+                instance.with::<Session, _>(target_slot, |target| inner.clone().remove(target))
             {
                 return Err(err.into());
             }
 
-            caller.data_mut().remove::<Session>(target_slot);
+            instance.remove::<Session>(target_slot);
 
-            match caller.data().with::<Session, _>(session_slot, |session| {
+            match instance.with::<Session, _>(session_slot, |session| {
                 session.revoke_resource(Capability::SessionLifecycle, target_slot)
             }) {
                 Some(Ok(_)) => Ok(()),
@@ -457,3 +464,82 @@ where
         ),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use selium_abi::ErrorCode;
+
+    use super::*;
+    use crate::{
+        registry::Registry,
+        session::{ResourceScope, SessionLifecycleDriver},
+    };
+
+    #[tokio::test]
+    async fn sessions_only_grant_what_they_hold() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance");
+        let root = Session::bootstrap(
+            [Capability::SessionLifecycle, Capability::TimeRead].into(),
+            [0; 32],
+        );
+        let parent = root
+            .create(
+                HashMap::from([
+                    (Capability::SessionLifecycle, ResourceScope::None),
+                    (
+                        Capability::TimeRead,
+                        ResourceScope::Some(HashSet::from([7])),
+                    ),
+                ]),
+                [0; 32],
+            )
+            .expect("create parent");
+        let parent = instance
+            .insert(parent, None, ResourceType::Session)
+            .expect("insert parent") as u32;
+        let child = SessionCreateDriver(SessionLifecycleDriver)
+            .to_future(
+                &mut instance,
+                SessionCreate {
+                    session_id: parent,
+                    pubkey: [0; 32],
+                },
+            )
+            .await
+            .expect("create child");
+
+        let entitle = SessionAddEntitlementDriver(SessionLifecycleDriver);
+        let entitlement = |capability| SessionEntitlement {
+            session_id: parent,
+            target_id: child,
+            capability,
+        };
+        entitle
+            .to_future(&mut instance, entitlement(Capability::TimeRead))
+            .await
+            .expect("grant held capability");
+        let err = entitle
+            .to_future(&mut instance, entitlement(Capability::ProcessLifecycle))
+            .await
+            .expect_err("grant unheld capability");
+        assert_eq!(err.code(), ErrorCode::EntitlementScope);
+
+        let grant = SessionAddResourceDriver(SessionLifecycleDriver);
+        let resource = |resource_id| SessionResource {
+            session_id: parent,
+            target_id: child,
+            capability: Capability::TimeRead,
+            resource_id,
+        };
+        let granted = grant.to_future(&mut instance, resource(7)).await;
+        assert_eq!(granted.expect("grant reachable resource"), 1);
+        let err = grant
+            .to_future(&mut instance, resource(8))
+            .await
+            .expect_err("grant unreachable resource");
+        assert_eq!(err.code(), ErrorCode::EntitlementScope);
+    }
+}
@@ -5,8 +5,6 @@ use std::{
     sync::Arc,
 };
 
-use wasmtime::Caller;
-
 use crate::{
     guest_data::{GuestError, GuestResult},
     operation::{Contract, Operation},
@@ -30,18 +28,18 @@ impl Contract for SingletonRegisterDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let registry = caller.data().registry_arc();
-        let SingletonRegister { id, resource } = input;
+        let registry = instance.registry_arc();
+        let SingletonRegister { id, name, resource } = input;
 
         ready((|| -> GuestResult<Self::Output> {
             let resource_id = registry
                 .resolve_shared(resource)
                 .ok_or(GuestError::NotFound)?;
             registry.metadata(resource_id).ok_or(GuestError::NotFound)?;
-            let inserted = registry.register_singleton(id, resource_id)?;
+            let inserted = registry.register_singleton(id, &name, resource_id)?;
             if !inserted {
                 return Err(GuestError::StableIdExists);
             }
@@ -56,10 +54,10 @@ impl Contract for SingletonLookupDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let registry = caller.data().registry_arc();
+        let registry = instance.registry_arc();
         let SingletonLookup { id } = input;
 
         ready((|| -> GuestResult<Self::Output> {
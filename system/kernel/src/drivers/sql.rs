@@ -0,0 +1,241 @@
+//! Hostcall drivers running SQL statements on host-held connections.
+//!
+//! Connections live in the registry, registered by the host with [`register_connection`] and
+//! published as singleton dependencies. Guests look a connection up by name, then pass its
+//! shared handle with each statement; they see statement text and typed rows, never the
+//! connection string or its credentials.
+
+use std::{
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use futures_util::{FutureExt, future::BoxFuture};
+use selium_abi::{DependencyId, GuestResourceId, SqlRows, SqlStatement, SqlValue};
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::{
+        InstanceRegistry, Registry, RegistryError, ResourceHandle, ResourceId, ResourceType,
+    },
+};
+
+type SqlOps = (
+    Arc<Operation<SqlQueryDriver>>,
+    Arc<Operation<SqlExecuteDriver>>,
+);
+
+/// A database connection, or pool of connections, held by the host on guests' behalf.
+pub trait SqlConnection: Send + Sync {
+    /// Run `statement` with `params` bound to its placeholders and return the rows it yields.
+    fn query(
+        &self,
+        statement: String,
+        params: Vec<SqlValue>,
+    ) -> BoxFuture<'static, GuestResult<SqlRows>>;
+
+    /// Run `statement` with `params` bound to its placeholders and return how many rows it
+    /// changed.
+    fn execute(
+        &self,
+        statement: String,
+        params: Vec<SqlValue>,
+    ) -> BoxFuture<'static, GuestResult<u64>>;
+}
+
+/// Hostcall driver that runs a query and returns its rows.
+pub struct SqlQueryDriver;
+/// Hostcall driver that runs a statement and returns how many rows it changed.
+pub struct SqlExecuteDriver;
+
+/// Registry entry backing a connection.
+struct ConnectionState(Arc<dyn SqlConnection>);
+
+impl Contract for SqlQueryDriver {
+    type Input = SqlStatement;
+    type Output = SqlRows;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let SqlStatement {
+            connection,
+            statement,
+            params,
+        } = input;
+        match connection_for(instance.registry(), connection) {
+            Ok(connection) => connection.query(statement, params),
+            Err(err) => ready(Err(err)).boxed(),
+        }
+    }
+}
+
+impl Contract for SqlExecuteDriver {
+    type Input = SqlStatement;
+    type Output = u64;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let SqlStatement {
+            connection,
+            statement,
+            params,
+        } = input;
+        match connection_for(instance.registry(), connection) {
+            Ok(connection) => connection.execute(statement, params),
+            Err(err) => ready(Err(err)).boxed(),
+        }
+    }
+}
+
+/// Connection behind the shared handle `connection`.
+fn connection_for(
+    registry: &Registry,
+    connection: GuestResourceId,
+) -> GuestResult<Arc<dyn SqlConnection>> {
+    let resource_id = registry
+        .resolve_shared(connection)
+        .ok_or(GuestError::NotFound)?;
+    registry
+        .with(
+            ResourceHandle::<ConnectionState>::new(resource_id),
+            |state| Arc::clone(&state.0),
+        )
+        .ok_or(GuestError::NotFound)
+}
+
+/// Hold `connection` in `registry` and publish it as the singleton dependency `name`, for guests
+/// to look up and run statements on. Fails if another resource already holds `name`.
+pub fn register_connection(
+    registry: &Registry,
+    name: &str,
+    connection: Arc<dyn SqlConnection>,
+) -> Result<ResourceId, RegistryError> {
+    let resource_id = registry
+        .add(ConnectionState(connection), None, ResourceType::Other)?
+        .into_id();
+    match registry.register_singleton(DependencyId::from_name(name), name, resource_id) {
+        Ok(true) => Ok(resource_id),
+        Ok(false) => {
+            registry.discard(resource_id);
+            Err(RegistryError::DependencyTaken)
+        }
+        Err(err) => {
+            registry.discard(resource_id);
+            Err(err)
+        }
+    }
+}
+
+/// Build hostcall operations for running statements on host-held connections.
+pub fn operations() -> SqlOps {
+    (
+        Operation::from_hostcall(SqlQueryDriver, selium_abi::hostcall_contract!(SQL_QUERY)),
+        Operation::from_hostcall(
+            SqlExecuteDriver,
+            selium_abi::hostcall_contract!(SQL_EXECUTE),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Connection that records each statement it runs.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl SqlConnection for Recorder {
+        fn query(
+            &self,
+            statement: String,
+            params: Vec<SqlValue>,
+        ) -> BoxFuture<'static, GuestResult<SqlRows>> {
+            self.0.lock().expect("statements").push(statement);
+            ready(Ok(SqlRows {
+                columns: vec!["echo".to_string()],
+                rows: params.into_iter().map(|param| vec![param]).collect(),
+            }))
+            .boxed()
+        }
+
+        fn execute(
+            &self,
+            statement: String,
+            _params: Vec<SqlValue>,
+        ) -> BoxFuture<'static, GuestResult<u64>> {
+            self.0.lock().expect("statements").push(statement);
+            ready(Ok(1)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn statements_run_on_the_connection_published_under_a_name() {
+        let registry = Registry::new();
+        let recorder = Arc::new(Recorder::default());
+        let resource_id = register_connection(&registry, "sql/orders", recorder.clone())
+            .expect("register connection");
+        assert!(matches!(
+            register_connection(&registry, "sql/orders", Arc::new(Recorder::default())),
+            Err(RegistryError::DependencyTaken)
+        ));
+
+        let mut instance = registry.instance().expect("instance");
+        let found = registry
+            .singleton(DependencyId::from_name("sql/orders"))
+            .expect("published connection");
+        assert_eq!(found, resource_id);
+        let connection = registry.share_handle(found).expect("share connection");
+
+        let rows = SqlQueryDriver
+            .to_future(
+                &mut instance,
+                SqlStatement {
+                    connection,
+                    statement: "SELECT ?".to_string(),
+                    params: vec![SqlValue::Integer(7)],
+                },
+            )
+            .await
+            .expect("query");
+        assert_eq!(rows.rows, [[SqlValue::Integer(7)]]);
+        let changed = SqlExecuteDriver
+            .to_future(
+                &mut instance,
+                SqlStatement {
+                    connection,
+                    statement: "DELETE FROM orders".to_string(),
+                    params: Vec::new(),
+                },
+            )
+            .await
+            .expect("execute");
+        assert_eq!(changed, 1);
+        assert_eq!(
+            *recorder.0.lock().expect("statements"),
+            ["SELECT ?", "DELETE FROM orders"]
+        );
+
+        let err = SqlQueryDriver
+            .to_future(
+                &mut instance,
+                SqlStatement {
+                    connection: connection + 1,
+                    statement: "SELECT 1".to_string(),
+                    params: Vec::new(),
+                },
+            )
+            .await
+            .expect_err("unknown handle");
+        assert!(matches!(err, GuestError::NotFound));
+    }
+}
@@ -0,0 +1,257 @@
+//! Hostcall drivers for named barriers and latches, letting cooperating guests rendezvous
+//! with wakeups managed by the host.
+//!
+//! A barrier releases its parties together once the last of them arrives, then starts over for
+//! the next phase. A latch opens once it has been counted down to zero and stays open. Both are
+//! created by their first use, and every later use must agree on their size.
+
+use std::{
+    collections::HashMap,
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use selium_abi::{BarrierWait, LatchRequest};
+use tokio::sync::watch;
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::InstanceRegistry,
+};
+
+type SyncOps = (
+    Arc<Operation<BarrierWaitDriver>>,
+    Arc<Operation<LatchCountDownDriver>>,
+    Arc<Operation<LatchWaitDriver>>,
+);
+
+/// Hostcall driver that waits at a barrier.
+pub struct BarrierWaitDriver(SyncPrimitives);
+/// Hostcall driver that counts a latch down.
+pub struct LatchCountDownDriver(SyncPrimitives);
+/// Hostcall driver that waits for a latch to open.
+pub struct LatchWaitDriver(SyncPrimitives);
+
+/// Barriers and latches shared by every guest on this host. Clones share the same primitives.
+#[derive(Clone, Default)]
+pub struct SyncPrimitives {
+    barriers: Arc<Mutex<HashMap<String, Barrier>>>,
+    latches: Arc<Mutex<HashMap<String, Latch>>>,
+}
+
+struct Barrier {
+    parties: u32,
+    arrived: u32,
+    /// Phase the barrier is in, advanced each time every party has arrived.
+    generation: watch::Sender<u64>,
+}
+
+struct Latch {
+    count: u32,
+    remaining: watch::Sender<u32>,
+}
+
+impl SyncPrimitives {
+    /// Arrive at the barrier `name` of `parties` parties and wait for the rest. Resolves to the
+    /// order the caller arrived in, from `0`; the last to arrive gets `parties - 1`.
+    pub fn barrier_wait(
+        &self,
+        name: String,
+        parties: u32,
+    ) -> impl Future<Output = GuestResult<u32>> + Send + 'static {
+        let arrival = (|| {
+            if parties == 0 {
+                return Err(GuestError::InvalidArgument);
+            }
+            let mut barriers = self.barriers.lock();
+            let barrier = barriers.entry(name).or_insert_with(|| Barrier {
+                parties,
+                arrived: 0,
+                generation: watch::channel(0).0,
+            });
+            if barrier.parties != parties {
+                return Err(GuestError::InvalidArgument);
+            }
+            let index = barrier.arrived;
+            barrier.arrived += 1;
+            if barrier.arrived == parties {
+                barrier.arrived = 0;
+                barrier
+                    .generation
+                    .send_modify(|generation| *generation += 1);
+                return Ok((index, None));
+            }
+            let phase = *barrier.generation.borrow();
+            Ok((index, Some((barrier.generation.subscribe(), phase))))
+        })();
+
+        async move {
+            let (index, waiter) = arrival?;
+            if let Some((mut generation, phase)) = waiter {
+                generation
+                    .wait_for(|generation| *generation > phase)
+                    .await
+                    .map_err(|_| GuestError::NotFound)?;
+            }
+            Ok(index)
+        }
+    }
+
+    /// Count the latch `name` of `count` down by one, returning how many counts remain.
+    pub fn latch_count_down(&self, name: String, count: u32) -> GuestResult<u32> {
+        let mut latches = self.latches.lock();
+        let latch = Self::latch(&mut latches, name, count)?;
+        let mut remaining = 0;
+        latch.remaining.send_modify(|left| {
+            *left = left.saturating_sub(1);
+            remaining = *left;
+        });
+        Ok(remaining)
+    }
+
+    /// Wait for the latch `name` of `count` to open.
+    pub fn latch_wait(
+        &self,
+        name: String,
+        count: u32,
+    ) -> impl Future<Output = GuestResult<()>> + Send + 'static {
+        let remaining = {
+            let mut latches = self.latches.lock();
+            Self::latch(&mut latches, name, count).map(|latch| latch.remaining.subscribe())
+        };
+
+        async move {
+            remaining?
+                .wait_for(|left| *left == 0)
+                .await
+                .map(|_| ())
+                .map_err(|_| GuestError::NotFound)
+        }
+    }
+
+    /// The latch `name`, created with `count` if it does not exist yet.
+    fn latch(
+        latches: &mut HashMap<String, Latch>,
+        name: String,
+        count: u32,
+    ) -> GuestResult<&mut Latch> {
+        let latch = latches.entry(name).or_insert_with(|| Latch {
+            count,
+            remaining: watch::channel(count).0,
+        });
+        if latch.count == count {
+            Ok(latch)
+        } else {
+            Err(GuestError::InvalidArgument)
+        }
+    }
+}
+
+impl Contract for BarrierWaitDriver {
+    type Input = BarrierWait;
+    type Output = u32;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        self.0.barrier_wait(input.name, input.parties)
+    }
+}
+
+impl Contract for LatchCountDownDriver {
+    type Input = LatchRequest;
+    type Output = u32;
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        ready(self.0.latch_count_down(input.name, input.count))
+    }
+}
+
+impl Contract for LatchWaitDriver {
+    type Input = LatchRequest;
+    type Output = ();
+
+    fn to_future(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        self.0.latch_wait(input.name, input.count)
+    }
+}
+
+/// Build hostcall operations for barriers and latches, held in `primitives`.
+pub fn operations(primitives: SyncPrimitives) -> SyncOps {
+    (
+        Operation::from_hostcall(
+            BarrierWaitDriver(primitives.clone()),
+            selium_abi::hostcall_contract!(SYNC_BARRIER_WAIT),
+        ),
+        Operation::from_hostcall(
+            LatchCountDownDriver(primitives.clone()),
+            selium_abi::hostcall_contract!(SYNC_LATCH_COUNT_DOWN),
+        ),
+        Operation::from_hostcall(
+            LatchWaitDriver(primitives),
+            selium_abi::hostcall_contract!(SYNC_LATCH_WAIT),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn barriers_release_together_and_latches_stay_open() {
+        let sync = SyncPrimitives::default();
+
+        for _ in 0..2 {
+            let mut first = sync.barrier_wait("startup".to_string(), 2).boxed();
+            assert!((&mut first).now_or_never().is_none());
+            assert!(matches!(
+                sync.barrier_wait("startup".to_string(), 3).await,
+                Err(GuestError::InvalidArgument)
+            ));
+            assert_eq!(
+                sync.barrier_wait("startup".to_string(), 2)
+                    .await
+                    .expect("last"),
+                1
+            );
+            assert_eq!(first.await.expect("first"), 0);
+        }
+
+        let mut opened = sync.latch_wait("loaded".to_string(), 2).boxed();
+        assert_eq!(
+            sync.latch_count_down("loaded".to_string(), 2)
+                .expect("count down"),
+            1
+        );
+        assert!((&mut opened).now_or_never().is_none());
+        assert_eq!(
+            sync.latch_count_down("loaded".to_string(), 2)
+                .expect("count down"),
+            0
+        );
+        opened.await.expect("opened");
+        assert_eq!(
+            sync.latch_count_down("loaded".to_string(), 2)
+                .expect("count down"),
+            0
+        );
+        sync.latch_wait("loaded".to_string(), 2)
+            .await
+            .expect("still open");
+    }
+}
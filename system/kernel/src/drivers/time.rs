@@ -2,63 +2,171 @@
 
 use std::{
     future::Future,
-    sync::OnceLock,
+    sync::{Arc, OnceLock},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use wasmtime::Caller;
+use rkyv::Archived;
+use tokio::sync::watch;
 
 use crate::{
-    guest_data::GuestResult,
+    guest_data::{ArchivedPayload, GuestError, GuestResult},
     operation::{Contract, Operation},
-    registry::InstanceRegistry,
+    registry::{InstanceRegistry, ResourceId},
 };
 use selium_abi::{TimeNow, TimeSleep};
 
-type TimeOps = (
-    std::sync::Arc<Operation<TimeNowDriver>>,
-    std::sync::Arc<Operation<TimeSleepDriver>>,
+type TimeOps<C> = (
+    Arc<Operation<TimeNowDriver<C>>>,
+    Arc<Operation<TimeSleepDriver<C>>>,
 );
 
+/// Capability providing the clock guests observe.
+pub trait TimeCapability {
+    /// Current wall-clock and monotonic time.
+    fn now(&self) -> TimeNow;
+
+    /// Resolve once `duration` has elapsed on this clock.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = GuestResult<()>> + Send + 'static;
+}
+
 /// Hostcall driver that returns the current host time.
-pub struct TimeNowDriver;
+pub struct TimeNowDriver<Impl>(Impl);
 /// Hostcall driver that sleeps for the requested duration.
-pub struct TimeSleepDriver;
+pub struct TimeSleepDriver<Impl>(Impl);
+
+/// The host's own clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemTimeService;
+
+/// A clock that stands still until it is explicitly advanced, for reproducible tests.
+///
+/// Its monotonic time starts at zero, and its wall-clock time at the instant it was created
+/// with. Guests sleeping on it wake only once [`SteppedTimeService::advance`] moves the clock
+/// past their deadline. Clones share the same clock.
+#[derive(Clone, Debug)]
+pub struct SteppedTimeService {
+    unix_start_ms: u64,
+    monotonic_ms: Arc<watch::Sender<u64>>,
+}
+
+impl<T> TimeCapability for Arc<T>
+where
+    T: TimeCapability,
+{
+    fn now(&self) -> TimeNow {
+        self.as_ref().now()
+    }
+
+    fn sleep(&self, duration: Duration) -> impl Future<Output = GuestResult<()>> + Send + 'static {
+        self.as_ref().sleep(duration)
+    }
+}
+
+impl TimeCapability for SystemTimeService {
+    fn now(&self) -> TimeNow {
+        TimeNow {
+            unix_ms: unix_ms(),
+            monotonic_ms: monotonic_ms(),
+        }
+    }
+
+    // Not an `async fn`: the returned future must not borrow `self`.
+    #[allow(clippy::manual_async_fn)]
+    fn sleep(&self, duration: Duration) -> impl Future<Output = GuestResult<()>> + Send + 'static {
+        async move {
+            tokio::time::sleep(duration).await;
+            Ok(())
+        }
+    }
+}
+
+impl SteppedTimeService {
+    /// Create a clock frozen at `unix_ms` milliseconds since the Unix epoch.
+    pub fn new(unix_ms: u64) -> Self {
+        let (monotonic_ms, _) = watch::channel(0);
+        Self {
+            unix_start_ms: unix_ms,
+            monotonic_ms: Arc::new(monotonic_ms),
+        }
+    }
+
+    /// Move the clock forward by `step`, waking every sleeper whose deadline has passed, and
+    /// return the new time.
+    pub fn advance(&self, step: Duration) -> TimeNow {
+        let step = u64::try_from(step.as_millis()).unwrap_or(u64::MAX);
+        self.monotonic_ms
+            .send_modify(|now| *now = now.saturating_add(step));
+        self.now()
+    }
+}
 
-impl Contract for TimeNowDriver {
+impl TimeCapability for SteppedTimeService {
+    fn now(&self) -> TimeNow {
+        let monotonic_ms = *self.monotonic_ms.borrow();
+        TimeNow {
+            unix_ms: self.unix_start_ms.saturating_add(monotonic_ms),
+            monotonic_ms,
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> impl Future<Output = GuestResult<()>> + Send + 'static {
+        let mut now = self.monotonic_ms.subscribe();
+        let step = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        let deadline = now.borrow().saturating_add(step);
+        async move {
+            now.wait_for(|now| *now >= deadline)
+                .await
+                .map(|_| ())
+                .map_err(|_| GuestError::Subsystem("stepped clock was dropped".to_string()))
+        }
+    }
+}
+
+impl<Impl> Contract for TimeNowDriver<Impl>
+where
+    Impl: TimeCapability + Send + 'static,
+{
     type Input = ();
     type Output = TimeNow;
 
     fn to_future(
         &self,
-        _caller: &mut Caller<'_, InstanceRegistry>,
+        _instance: &mut InstanceRegistry,
         _input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        std::future::ready(Ok(now()))
+        std::future::ready(Ok(self.0.now()))
     }
 }
 
-impl Contract for TimeSleepDriver {
+impl<Impl> Contract for TimeSleepDriver<Impl>
+where
+    Impl: TimeCapability + Send + 'static,
+{
     type Input = TimeSleep;
     type Output = ();
 
     fn to_future(
         &self,
-        _caller: &mut Caller<'_, InstanceRegistry>,
+        _instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
-        let duration = Duration::from_millis(input.duration_ms);
-        async move {
-            tokio::time::sleep(duration).await;
-            Ok(())
-        }
+        self.0.sleep(Duration::from_millis(input.duration_ms))
+    }
+
+    const ARCHIVED_INPUT: bool = true;
+
+    fn to_future_archived(
+        &self,
+        _instance: &mut InstanceRegistry,
+        input: ArchivedPayload<Self::Input>,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+        self.0
+            .sleep(Duration::from_millis(input.get().duration_ms.to_native()))
     }
-}
 
-fn now() -> TimeNow {
-    TimeNow {
-        unix_ms: unix_ms(),
-        monotonic_ms: monotonic_ms(),
+    fn archived_resource(&self, _input: &Archived<Self::Input>) -> Option<ResourceId> {
+        None
     }
 }
 
@@ -74,10 +182,47 @@ fn monotonic_ms() -> u64 {
     START.get_or_init(Instant::now).elapsed().as_millis() as u64
 }
 
-/// Build hostcall operations for time access.
-pub fn operations() -> TimeOps {
+/// Build hostcall operations for time access, reading the clock from `clock`.
+pub fn operations<C>(clock: C) -> TimeOps<C>
+where
+    C: TimeCapability + Clone + Send + 'static,
+{
     (
-        Operation::from_hostcall(TimeNowDriver, selium_abi::hostcall_contract!(TIME_NOW)),
-        Operation::from_hostcall(TimeSleepDriver, selium_abi::hostcall_contract!(TIME_SLEEP)),
+        Operation::from_hostcall(
+            TimeNowDriver(clock.clone()),
+            selium_abi::hostcall_contract!(TIME_NOW),
+        ),
+        Operation::from_hostcall(
+            TimeSleepDriver(clock),
+            selium_abi::hostcall_contract!(TIME_SLEEP),
+        ),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stepped_clock_wakes_sleepers_once_advanced_past_deadline() {
+        let clock = SteppedTimeService::new(1_000);
+        let sleeper = tokio::spawn(clock.sleep(Duration::from_millis(50)));
+
+        clock.advance(Duration::from_millis(30));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+
+        let now = clock.advance(Duration::from_millis(20));
+        assert_eq!(
+            now,
+            TimeNow {
+                unix_ms: 1_050,
+                monotonic_ms: 50,
+            }
+        );
+        sleeper
+            .await
+            .expect("sleeper task")
+            .expect("sleep completes");
+    }
+}
@@ -0,0 +1,192 @@
+//! Hostcall drivers for watched values: a single value that many guests follow, each woken
+//! when it changes and shown only the latest version.
+
+use std::{
+    future::{Future, ready},
+    sync::Arc,
+};
+
+use tokio::sync::watch;
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, Operation},
+    registry::{InstanceRegistry, Registry, ResourceHandle, ResourceType},
+};
+use selium_abi::{GuestResourceId, WatchCreate, WatchSet, WatchSubscribe, WatchValue};
+
+type WatchOps = (
+    Arc<Operation<WatchCreateDriver>>,
+    Arc<Operation<WatchSetDriver>>,
+    Arc<Operation<WatchSubscribeDriver>>,
+);
+
+/// Hostcall driver that creates a watched value.
+pub struct WatchCreateDriver;
+/// Hostcall driver that replaces the value of a watch.
+pub struct WatchSetDriver;
+/// Hostcall driver that waits for a watch to change.
+pub struct WatchSubscribeDriver;
+
+/// Registry entry backing a watch, owned by the process that created it.
+struct WatchState(watch::Sender<WatchValue>);
+
+impl Contract for WatchCreateDriver {
+    type Input = WatchCreate;
+    type Output = GuestResourceId;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let owner = instance.process();
+        let WatchCreate { value } = input;
+
+        ready((|| -> GuestResult<Self::Output> {
+            let (sender, _) = watch::channel(WatchValue { version: 1, value });
+            let resource_id = registry
+                .add(WatchState(sender), owner, ResourceType::Other)?
+                .into_id();
+            registry.share_handle(resource_id).map_err(|err| {
+                registry.discard(resource_id);
+                GuestError::from(err)
+            })
+        })())
+    }
+}
+
+impl Contract for WatchSetDriver {
+    type Input = WatchSet;
+    type Output = u64;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let WatchSet { watch, value } = input;
+
+        ready(with_watch(&registry, watch, |state| {
+            let mut version = 0;
+            state.0.send_modify(|current| {
+                current.version += 1;
+                current.value = value;
+                version = current.version;
+            });
+            version
+        }))
+    }
+}
+
+impl Contract for WatchSubscribeDriver {
+    type Input = WatchSubscribe;
+    type Output = WatchValue;
+
+    fn to_future(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + 'static {
+        let registry = instance.registry_arc();
+        let WatchSubscribe { watch, seen } = input;
+        let receiver = with_watch(&registry, watch, |state| state.0.subscribe());
+
+        async move {
+            let mut receiver = receiver?;
+            // Resolves straight away if the watch already holds a newer version, and fails once
+            // the watch is removed along with the process that created it.
+            let value = receiver
+                .wait_for(|value| value.version > seen)
+                .await
+                .map_err(|_| GuestError::NotFound)?;
+            Ok(value.clone())
+        }
+    }
+}
+
+/// Run `func` against the watch behind the shared handle `watch`.
+fn with_watch<R>(
+    registry: &Registry,
+    watch: GuestResourceId,
+    func: impl FnOnce(&mut WatchState) -> R,
+) -> GuestResult<R> {
+    let resource_id = registry.resolve_shared(watch).ok_or(GuestError::NotFound)?;
+    registry
+        .with(ResourceHandle::<WatchState>::new(resource_id), func)
+        .ok_or(GuestError::NotFound)
+}
+
+/// Build hostcall operations for watched values.
+pub fn operations() -> WatchOps {
+    (
+        Operation::from_hostcall(
+            WatchCreateDriver,
+            selium_abi::hostcall_contract!(WATCH_CREATE),
+        ),
+        Operation::from_hostcall(WatchSetDriver, selium_abi::hostcall_contract!(WATCH_SET)),
+        Operation::from_hostcall(
+            WatchSubscribeDriver,
+            selium_abi::hostcall_contract!(WATCH_SUBSCRIBE),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_wake_with_the_latest_value() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance");
+        let watch = WatchCreateDriver
+            .to_future(
+                &mut instance,
+                WatchCreate {
+                    value: b"leader-a".to_vec(),
+                },
+            )
+            .await
+            .expect("create");
+
+        let current = WatchSubscribeDriver
+            .to_future(&mut instance, WatchSubscribe { watch, seen: 0 })
+            .await
+            .expect("subscribe");
+        assert_eq!(current.version, 1);
+        assert_eq!(current.value, b"leader-a");
+
+        let mut next = WatchSubscribeDriver
+            .to_future(&mut instance, WatchSubscribe { watch, seen: 1 })
+            .boxed();
+        assert!((&mut next).now_or_never().is_none());
+        for value in [b"leader-b", b"leader-c"] {
+            WatchSetDriver
+                .to_future(
+                    &mut instance,
+                    WatchSet {
+                        watch,
+                        value: value.to_vec(),
+                    },
+                )
+                .await
+                .expect("set");
+        }
+        let latest = next.await.expect("changed");
+        assert_eq!(latest.version, 3);
+        assert_eq!(latest.value, b"leader-c");
+
+        let resource_id = registry.resolve_shared(watch).expect("shared watch");
+        let mut orphaned = WatchSubscribeDriver
+            .to_future(&mut instance, WatchSubscribe { watch, seen: 3 })
+            .boxed();
+        assert!((&mut orphaned).now_or_never().is_none());
+        registry.discard(resource_id);
+        assert!(matches!(orphaned.await, Err(GuestError::NotFound)));
+    }
+}
@@ -0,0 +1,110 @@
+//! Broadcast bus of kernel lifecycle events.
+//!
+//! The [`Registry`](crate::registry::Registry) owns an [`EventBus`] on which the kernel publishes
+//! structured events as processes start and stop, sessions are created and hostcalls are denied.
+//! Runtime subsystems and embedders subscribe to it rather than scraping logs. Events are
+//! delivered to every subscriber; one that falls more than [`EVENT_CAPACITY`] events behind
+//! misses the oldest and is told how many it missed.
+
+use selium_abi::Capability;
+use tokio::sync::broadcast::{self, Receiver, Sender, error::SendError};
+use tracing::trace;
+
+use crate::registry::ResourceId;
+
+/// Number of events buffered for each subscriber before the oldest are dropped.
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// A lifecycle event published by the kernel.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum KernelEvent {
+    /// A process was bound to its running task.
+    ProcessStarted {
+        /// Registry id of the process.
+        process_id: ResourceId,
+    },
+    /// A started process was removed from the registry, whether it exited or was stopped.
+    ProcessStopped {
+        /// Registry id of the process.
+        process_id: ResourceId,
+    },
+    /// A guest created a session.
+    SessionCreated {
+        /// Registry id of the new session.
+        session_id: ResourceId,
+        /// Registry id of the session it was derived from.
+        parent_id: Option<ResourceId>,
+        /// Process whose guest created the session, if known.
+        process_id: Option<ResourceId>,
+    },
+    /// A hostcall failed its capability check because the caller lacks the capability it
+    /// requires.
+    CapabilityDenied {
+        /// Wasm import module name of the hostcall.
+        hostcall: &'static str,
+        /// Capability the hostcall requires.
+        capability: Capability,
+        /// Registry id of the calling session, if the caller has one.
+        session_id: Option<ResourceId>,
+        /// Process whose guest made the call, if known.
+        process_id: Option<ResourceId>,
+        /// Whether the call was refused; `false` if the operation only audits its checks.
+        enforced: bool,
+    },
+}
+
+/// Sending half of the kernel event bus. Clones publish to the same subscribers.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: Sender<KernelEvent>,
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Receive every event published from now on.
+    pub fn subscribe(&self) -> Receiver<KernelEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish `event` to the current subscribers, if there are any.
+    pub fn emit(&self, event: KernelEvent) {
+        if let Err(SendError(event)) = self.sender.send(event) {
+            trace!(?event, "kernel event has no subscribers");
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_reach_every_subscriber() {
+        let bus = EventBus::default();
+        bus.emit(KernelEvent::ProcessStarted { process_id: 1 });
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.emit(KernelEvent::ProcessStopped { process_id: 1 });
+
+        for receiver in [&mut first, &mut second] {
+            assert_eq!(
+                receiver.try_recv(),
+                Ok(KernelEvent::ProcessStopped { process_id: 1 })
+            );
+            assert!(receiver.try_recv().is_err());
+        }
+    }
+}
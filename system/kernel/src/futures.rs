@@ -1,24 +1,41 @@
-use std::{sync::Arc, task::Waker};
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    sync::Arc,
+    task::{Poll, Waker},
+    time::{Duration, Instant},
+};
 
 use parking_lot::Mutex;
+use selium_abi::{FutureDiagnostics, FutureState};
+use tokio::task::AbortHandle;
 
 struct FutureSharedInner<Output> {
-    result: Option<Output>,
+    results: VecDeque<Output>,
     waker: Option<Waker>,
+    complete: bool,
     dropped: bool,
+    task: Option<AbortHandle>,
 }
 
 /// Shared state backing a guest-visible future.
+///
+/// A future resolves with a single result. Streams push any number of items before resolving
+/// with their final one; the state is complete once that final item has been taken.
 pub struct FutureSharedState<Output> {
     inner: Mutex<FutureSharedInner<Output>>,
+    created: Instant,
+    hostcall: Option<&'static str>,
 }
 
 impl<Output> FutureSharedInner<Output> {
     pub fn new() -> Self {
         Self {
-            result: None,
+            results: VecDeque::new(),
             waker: None,
+            complete: false,
             dropped: false,
+            task: None,
         }
     }
 }
@@ -27,20 +44,57 @@ impl<Output> FutureSharedState<Output> {
     pub fn new() -> Arc<Self> {
         Arc::new(Self {
             inner: Mutex::new(FutureSharedInner::new()),
+            created: Instant::now(),
+            hostcall: None,
         })
     }
 
+    /// Create the state of a call to the hostcall with Wasm import module name `module`.
+    pub fn for_hostcall(module: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(FutureSharedInner::new()),
+            created: Instant::now(),
+            hostcall: Some(module),
+        })
+    }
+
+    /// Wasm import module name of the hostcall producing this state's results, if known.
+    pub fn hostcall(&self) -> Option<&'static str> {
+        self.hostcall
+    }
+
+    /// Time since this state was created.
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
     /// Store the completion result and wake any registered guest task.
     pub fn resolve(self: &Arc<Self>, result: Output) {
         let mut inner = self.inner.lock();
-        if inner.dropped {
+        if inner.dropped || inner.complete {
             return;
         }
 
-        inner.result = Some(result);
+        inner.results.push_back(result);
+        inner.complete = true;
+        if let Some(waker) = inner.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Queue an intermediate stream item and wake any registered guest task. Returns `false`
+    /// once the guest has dropped the state or it has already resolved, so producers can stop.
+    pub fn push(self: &Arc<Self>, item: Output) -> bool {
+        let mut inner = self.inner.lock();
+        if inner.dropped || inner.complete {
+            return false;
+        }
+
+        inner.results.push_back(item);
         if let Some(waker) = inner.waker.take() {
             waker.wake();
         }
+        true
     }
 
     /// Register a waker for the guest task awaiting this future.
@@ -51,25 +105,107 @@ impl<Output> FutureSharedState<Output> {
         }
 
         inner.waker = Some(waker);
-        if inner.result.is_some()
+        if !inner.results.is_empty()
             && let Some(waker) = inner.waker.take()
         {
             waker.wake();
         }
     }
 
-    /// Retrieve the completion result, if available
+    /// Retrieve the next available result, if any.
     pub fn take_result(self: &Arc<Self>) -> Option<Output> {
         let mut inner = self.inner.lock();
-        inner.result.take()
+        inner.results.pop_front()
+    }
+
+    /// Return the unread remainder of a taken result to the front of the queue, for the guest to
+    /// take on its next poll.
+    pub fn requeue(self: &Arc<Self>, remainder: Output) {
+        let mut inner = self.inner.lock();
+        if inner.dropped {
+            return;
+        }
+
+        inner.results.push_front(remainder);
+    }
+
+    /// Wait for the next result on the host, in place of a guest polling for it. Resolves to
+    /// `None` once the state is abandoned, or complete with every result taken.
+    pub async fn next_result(self: Arc<Self>) -> Option<Output> {
+        poll_fn(|cx| {
+            let mut inner = self.inner.lock();
+            if let Some(result) = inner.results.pop_front() {
+                return Poll::Ready(Some(result));
+            }
+            if inner.dropped || inner.complete {
+                return Poll::Ready(None);
+            }
+            inner.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Whether the final result has yet to be produced and the guest still holds the state.
+    pub fn is_pending(self: &Arc<Self>) -> bool {
+        let inner = self.inner.lock();
+        !inner.complete && !inner.dropped
+    }
+
+    /// Whether the final result has been produced and taken.
+    pub fn is_complete(self: &Arc<Self>) -> bool {
+        let inner = self.inner.lock();
+        inner.complete && inner.results.is_empty()
     }
 
-    /// Mark the future as dropped by the guest; subsequent completions are ignored.
+    /// Report the progress of this state, as held by the guest under `handle`.
+    pub fn diagnostics(self: &Arc<Self>, handle: u32) -> FutureDiagnostics {
+        let inner = self.inner.lock();
+        let state = match (inner.dropped, inner.complete, inner.results.is_empty()) {
+            (true, _, _) => FutureState::Abandoned,
+            (false, false, _) => FutureState::Pending,
+            (false, true, false) => FutureState::Resolved,
+            (false, true, true) => FutureState::Complete,
+        };
+        FutureDiagnostics {
+            handle,
+            state,
+            queued: u32::try_from(inner.results.len()).unwrap_or(u32::MAX),
+            waiting: inner.waker.is_some(),
+        }
+    }
+
+    /// Associate the host task producing this state's results, so that it can be aborted if the
+    /// guest drops the future first.
+    pub fn attach_task(self: &Arc<Self>, task: AbortHandle) {
+        let mut inner = self.inner.lock();
+        if inner.dropped {
+            task.abort();
+            return;
+        }
+
+        inner.task = Some(task);
+    }
+
+    /// Abort the producing host task, if any, and resolve with `result` in its place.
+    pub fn cancel(self: &Arc<Self>, result: Output) {
+        let task = self.inner.lock().task.take();
+        if let Some(task) = task {
+            task.abort();
+        }
+        self.resolve(result);
+    }
+
+    /// Mark the future as dropped by the guest; subsequent completions are ignored and the
+    /// producing host task, if any, is aborted.
     pub fn abandon(self: &Arc<Self>) {
         let mut inner = self.inner.lock();
         inner.dropped = true;
-        inner.result = None;
+        inner.results.clear();
         inner.waker = None;
+        if let Some(task) = inner.task.take() {
+            task.abort();
+        }
     }
 }
 
@@ -107,4 +243,44 @@ mod tests {
         assert!(flag.load(Ordering::SeqCst));
         assert!(state.take_result().is_some());
     }
+
+    #[test]
+    fn stream_items_drain_before_completion() {
+        let state = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+
+        assert!(state.push(Ok(vec![1])));
+        state.resolve(Ok(vec![2]));
+        assert!(!state.push(Ok(vec![3])));
+
+        assert!(matches!(state.take_result(), Some(Ok(item)) if item == [1]));
+        assert!(!state.is_complete());
+        assert!(matches!(state.take_result(), Some(Ok(item)) if item == [2]));
+        assert!(state.is_complete());
+    }
+
+    #[test]
+    fn requeued_remainder_is_taken_before_later_items() {
+        let state = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+
+        assert!(state.push(Ok(vec![1, 2])));
+        state.resolve(Ok(vec![3]));
+        assert!(matches!(state.take_result(), Some(Ok(item)) if item == [1, 2]));
+        state.requeue(Ok(vec![2]));
+
+        assert!(matches!(state.take_result(), Some(Ok(item)) if item == [2]));
+        assert!(!state.is_complete());
+        assert!(matches!(state.take_result(), Some(Ok(item)) if item == [3]));
+        assert!(state.is_complete());
+    }
+
+    #[tokio::test]
+    async fn abandon_aborts_attached_task() {
+        let state = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+        let task = tokio::spawn(std::future::pending::<()>());
+        state.attach_task(task.abort_handle());
+
+        state.abandon();
+
+        assert!(task.await.expect_err("task aborted").is_cancelled());
+    }
 }
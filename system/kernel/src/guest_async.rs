@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use tokio::{select, sync::Notify};
 use wasmtime::{Caller, Linker};
@@ -8,17 +8,30 @@ use crate::{KernelError, mailbox::GuestMailbox, registry::InstanceRegistry};
 /// Host-side support for guest async helpers.
 pub struct GuestAsync {
     shutdown: Arc<Notify>,
+    coalesce: Option<Duration>,
 }
 
 impl GuestAsync {
     /// Create a new guest async capability.
     pub fn new(notify: Arc<Notify>) -> Self {
-        Self { shutdown: notify }
+        Self {
+            shutdown: notify,
+            coalesce: None,
+        }
+    }
+
+    /// Hold a parked guest for `window` after its first wake-up, so that completions arriving
+    /// within the window are drained in one resume rather than one each. Guests that already
+    /// have wake-ups pending when they yield resume straight away.
+    pub fn with_wake_coalescing(mut self, window: Duration) -> Self {
+        self.coalesce = Some(window).filter(|window| !window.is_zero());
+        self
     }
 
     /// Link the `selium::async` host functions into the Wasmtime linker.
     pub fn link(&self, linker: &mut Linker<InstanceRegistry>) -> Result<(), KernelError> {
         let shutdown = Arc::clone(&self.shutdown);
+        let coalesce = self.coalesce;
         linker.func_wrap_async(
             "selium::async",
             "yield_now",
@@ -26,19 +39,7 @@ impl GuestAsync {
                 let mailbox_ref: &'static GuestMailbox =
                     caller.data().mailbox().expect("guest mailbox missing");
                 let shutdown = Arc::clone(&shutdown);
-                Box::new(async move {
-                    loop {
-                        if mailbox_ref.is_closed() || mailbox_ref.is_signalled() {
-                            break;
-                        }
-                        select! {
-                            _ = shutdown.notified() => {
-                                break;
-                            }
-                            _ = mailbox_ref.wait_for_signal() => {}
-                        }
-                    }
-                })
+                Box::new(async move { park(mailbox_ref, &shutdown, coalesce).await })
             },
         )?;
         Ok(())
@@ -49,3 +50,60 @@ impl GuestAsync {
         self.shutdown.notify_waiters();
     }
 }
+
+/// Wait until the guest has wake-ups to drain, the mailbox closes or the host shuts down. A
+/// guest that had to wait is then held for the `coalesce` window, if any.
+async fn park(mailbox: &GuestMailbox, shutdown: &Notify, coalesce: Option<Duration>) {
+    let mut parked = false;
+    loop {
+        if mailbox.is_closed() {
+            return;
+        }
+        if mailbox.is_signalled() {
+            break;
+        }
+        parked = true;
+        select! {
+            _ = shutdown.notified() => {
+                return;
+            }
+            _ = mailbox.wait_for_signal() => {}
+        }
+    }
+
+    if let Some(window) = coalesce.filter(|_| parked) {
+        select! {
+            _ = shutdown.notified() => {}
+            _ = tokio::time::sleep(window) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+
+    use futures_util::FutureExt;
+    use wasmtime::{Engine, Memory, MemoryType, Store};
+
+    use super::*;
+    use crate::mailbox::create_guest_mailbox;
+
+    #[tokio::test]
+    async fn wake_ups_within_the_window_resume_the_guest_once() {
+        let engine = Engine::default();
+        let mut store = Store::new(&engine, ());
+        let memory = Memory::new(&mut store, MemoryType::new(1, None)).expect("memory");
+        let mailbox = unsafe { create_guest_mailbox(&memory, &mut store) };
+        let shutdown = Notify::new();
+
+        let mut parked = pin!(park(mailbox, &shutdown, Some(Duration::from_millis(50))));
+        assert!((&mut parked).now_or_never().is_none());
+        mailbox.waker(1).wake();
+        assert!((&mut parked).now_or_never().is_none());
+        mailbox.waker(2).wake();
+        parked.await;
+
+        assert_eq!(mailbox.diagnostics().undrained, 2);
+    }
+}
@@ -1,21 +1,43 @@
-use std::str;
+use std::{marker::PhantomData, str};
 
+use rkyv::{
+    Archive, Archived, Deserialize,
+    api::high::{HighDeserializer, HighValidator},
+    bytecheck::CheckBytes,
+    rancor::Error as RancorError,
+};
 use thiserror::Error;
-use wasmtime::{AsContext, Caller};
+use wasmtime::{AsContext, Caller, Module};
 
 use crate::{
     KernelError,
+    buffers::PooledBuffer,
     drivers::Capability,
     registry::{InstanceRegistry, RegistryError},
 };
 use selium_abi::{
-    DRIVER_ERROR_MESSAGE_CODE, DRIVER_RESULT_PENDING, RkyvEncode, WORD_SIZE, decode_rkyv,
-    driver_encode_error, driver_encode_ready, encode_driver_error_message, encode_rkyv,
+    CapabilitySet, DRIVER_ERROR_MESSAGE_CODE, DRIVER_RESULT_CHUNK, DRIVER_RESULT_PENDING,
+    ErrorCode, JsonPayload, PayloadEncoding, RkyvEncode, WORD_SIZE, decode_payload,
+    driver_encode_error, driver_encode_ready, encode_driver_error, encode_rkyv,
 };
 pub use selium_abi::{GuestInt, GuestUint};
 
 pub type GuestResult<T, E = GuestError> = Result<T, E>;
 
+/// Address or length in guest linear memory, widened to 64 bits so that wasm32 and memory64
+/// guests share the same host code.
+pub type GuestAddress = u64;
+
+/// Width of the addresses a guest passes to hostcalls, which follows its linear memory.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum AddressWidth {
+    /// 32-bit addresses, as used by wasm32 modules.
+    #[default]
+    Bits32,
+    /// 64-bit addresses, as used by memory64 modules.
+    Bits64,
+}
+
 #[derive(Error, Debug)]
 pub enum GuestError {
     // Data errors
@@ -41,20 +63,103 @@ pub enum GuestError {
     Subsystem(String),
     #[error("This function would block")]
     WouldBlock,
+    #[error("hostcall timed out")]
+    TimedOut,
+    #[error("{1}")]
+    Coded(ErrorCode, String),
+}
+
+/// A guest-supplied rkyv payload, validated but left in its archived form.
+///
+/// Drivers that only read a few fields, or forward a large buffer, work on [`Self::get`]
+/// rather than deserialising an owned copy of the input. The payload owns its aligned bytes,
+/// so it can move into a driver's future.
+pub struct ArchivedPayload<T> {
+    bytes: PooledBuffer,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl AddressWidth {
+    /// Address width of `module`'s linear memory, whether exported or imported. Modules
+    /// without a memory are treated as wasm32.
+    pub fn of(module: &Module) -> Self {
+        let memory64 = module
+            .exports()
+            .filter_map(|export| export.ty().memory().map(|memory| memory.is_64()))
+            .chain(
+                module
+                    .imports()
+                    .filter_map(|import| import.ty().memory().map(|memory| memory.is_64())),
+            )
+            .any(|is_64| is_64);
+        if memory64 { Self::Bits64 } else { Self::Bits32 }
+    }
+}
+
+impl<T> ArchivedPayload<T>
+where
+    T: Archive,
+    for<'a> T::Archived: CheckBytes<HighValidator<'a, RancorError>>,
+{
+    /// Copy `bytes` into an aligned buffer and validate them as an archived `T`.
+    pub fn new(bytes: &[u8]) -> Result<Self, GuestError> {
+        let bytes = PooledBuffer::copy_from(bytes);
+        rkyv::access::<T::Archived, RancorError>(&bytes)
+            .map_err(|_| GuestError::InvalidArgument)?;
+
+        Ok(Self {
+            bytes,
+            _value: PhantomData,
+        })
+    }
+
+    /// The archived value.
+    pub fn get(&self) -> &Archived<T> {
+        // SAFETY: the bytes were validated as an archived `T` when the payload was created and
+        // are never modified afterwards.
+        unsafe { rkyv::access_unchecked::<T::Archived>(&self.bytes) }
+    }
+
+    /// Deserialise an owned copy of the value.
+    pub fn deserialize(&self) -> GuestResult<T>
+    where
+        T::Archived: Deserialize<T, HighDeserializer<RancorError>>,
+    {
+        rkyv::deserialize::<T, RancorError>(self.get()).map_err(|_| GuestError::InvalidArgument)
+    }
 }
 
 impl GuestError {
+    /// Stable numeric classification of this error, as reported to the guest.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            GuestError::InvalidArgument => ErrorCode::InvalidArgument,
+            GuestError::InvalidUtf8 => ErrorCode::InvalidUtf8,
+            GuestError::MemorySlice => ErrorCode::MemorySlice,
+            GuestError::NotFound => ErrorCode::NotFound,
+            GuestError::PermissionDenied => ErrorCode::PermissionDenied,
+            GuestError::Kernel(KernelError::PayloadTooLarge { .. }) => ErrorCode::PayloadTooLarge,
+            GuestError::Kernel(_) => ErrorCode::Kernel,
+            GuestError::Registry(_) => ErrorCode::Registry,
+            GuestError::StableIdExists => ErrorCode::AlreadyExists,
+            GuestError::Subsystem(_) => ErrorCode::Subsystem,
+            GuestError::WouldBlock => ErrorCode::Unknown,
+            GuestError::TimedOut => ErrorCode::TimedOut,
+            GuestError::Coded(code, _) => *code,
+        }
+    }
+
     fn encode_for_guest(
         self,
         caller: &mut Caller<'_, InstanceRegistry>,
-        ptr: GuestInt,
-        len: GuestUint,
+        ptr: GuestAddress,
+        len: GuestAddress,
     ) -> Result<GuestUint, KernelError> {
         if matches!(self, GuestError::WouldBlock) {
             return Ok(DRIVER_RESULT_PENDING);
         }
 
-        let bytes = encode_driver_error_message(&self.to_string())
+        let bytes = encode_driver_error(self.code(), &self.to_string())
             .map_err(|err| KernelError::Driver(err.to_string()))?;
         write_encoded(caller, ptr, len, &bytes)?;
         Ok(driver_encode_error(DRIVER_ERROR_MESSAGE_CODE))
@@ -63,8 +168,8 @@ impl GuestError {
 
 pub fn write_poll_result(
     caller: &mut Caller<'_, InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
+    ptr: GuestAddress,
+    len: GuestAddress,
     result: GuestResult<Vec<u8>>,
 ) -> Result<GuestUint, KernelError> {
     match result {
@@ -73,10 +178,22 @@ pub fn write_poll_result(
     }
 }
 
+/// Write `bytes` as one chunk of a result too large for the guest's `len`-byte buffer. The
+/// guest polls again for the rest.
+pub fn write_poll_chunk(
+    caller: &mut Caller<'_, InstanceRegistry>,
+    ptr: GuestAddress,
+    len: GuestAddress,
+    bytes: &[u8],
+) -> Result<GuestUint, KernelError> {
+    write_encoded(caller, ptr, len, bytes)?;
+    Ok(DRIVER_RESULT_CHUNK)
+}
+
 pub fn write_rkyv_value<T>(
     caller: &mut Caller<'_, InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
+    ptr: GuestAddress,
+    len: GuestAddress,
     value: T,
 ) -> Result<GuestUint, KernelError>
 where
@@ -86,43 +203,66 @@ where
     write_encoded(caller, ptr, len, &bytes)
 }
 
-pub fn read_rkyv_value<T>(
+/// Decode a guest-supplied value in the instance's payload `encoding`, refusing payloads larger
+/// than `max_len` bytes before they are copied out of guest memory.
+pub fn read_payload_value<T>(
     caller: &mut Caller<'_, InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
+    ptr: GuestAddress,
+    len: GuestAddress,
+    max_len: usize,
+    encoding: PayloadEncoding,
 ) -> Result<T, KernelError>
 where
-    T: rkyv::Archive + Sized,
+    T: rkyv::Archive + JsonPayload + Sized,
     for<'a> T::Archived: 'a
         + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
 {
-    let bytes = read_guest_bytes(caller, ptr, len)?;
-    decode_value(&bytes)
+    check_payload_len(len, max_len)?;
+    let bytes = PooledBuffer::copy_from(guest_bytes(caller, ptr, len)?);
+    decode_payload(&bytes, encoding).map_err(|err| KernelError::Driver(err.to_string()))
 }
 
-fn encode_value<T>(value: &T) -> Result<Vec<u8>, KernelError>
+/// Validate a guest-supplied rkyv payload in place of decoding it, refusing payloads larger
+/// than `max_len` bytes before they are copied out of guest memory.
+pub fn read_archived_payload<T>(
+    caller: &mut Caller<'_, InstanceRegistry>,
+    ptr: GuestAddress,
+    len: GuestAddress,
+    max_len: usize,
+) -> Result<ArchivedPayload<T>, KernelError>
 where
-    T: RkyvEncode,
+    T: Archive,
+    for<'a> T::Archived: CheckBytes<HighValidator<'a, RancorError>>,
 {
-    encode_rkyv(value).map_err(|err| KernelError::Driver(err.to_string()))
+    check_payload_len(len, max_len)?;
+    ArchivedPayload::new(guest_bytes(caller, ptr, len)?)
+        .map_err(|err| KernelError::Driver(err.to_string()))
 }
 
-fn decode_value<T>(bytes: &[u8]) -> Result<T, KernelError>
+fn check_payload_len(len: GuestAddress, max_len: usize) -> Result<(), KernelError> {
+    let payload_len = usize::try_from(len).map_err(KernelError::IntConvert)?;
+    if payload_len > max_len {
+        return Err(KernelError::PayloadTooLarge {
+            len: payload_len,
+            max: max_len,
+        });
+    }
+    Ok(())
+}
+
+fn encode_value<T>(value: &T) -> Result<Vec<u8>, KernelError>
 where
-    T: rkyv::Archive + Sized,
-    for<'a> T::Archived: 'a
-        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
-        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    T: RkyvEncode,
 {
-    decode_rkyv(bytes).map_err(|err| KernelError::Driver(err.to_string()))
+    encode_rkyv(value).map_err(|err| KernelError::Driver(err.to_string()))
 }
 
-fn read_guest_bytes(
-    caller: &mut Caller<'_, InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
-) -> Result<Vec<u8>, KernelError> {
+fn guest_bytes<'c>(
+    caller: &'c mut Caller<'_, InstanceRegistry>,
+    ptr: GuestAddress,
+    len: GuestAddress,
+) -> Result<&'c [u8], KernelError> {
     let memory = caller
         .get_export("memory")
         .and_then(|export| export.into_memory())
@@ -132,18 +272,16 @@ fn read_guest_bytes(
     let len = usize::try_from(len).map_err(KernelError::IntConvert)?;
     let end = start.checked_add(len).ok_or(KernelError::MemoryCapacity)?;
 
-    let ctx = caller.as_context();
-    let data = memory
-        .data(&ctx)
+    memory
+        .data(&*caller)
         .get(start..end)
-        .ok_or(KernelError::MemoryCapacity)?;
-    Ok(data.to_vec())
+        .ok_or(KernelError::MemoryCapacity)
 }
 
 fn write_encoded(
     caller: &mut Caller<'_, InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
+    ptr: GuestAddress,
+    len: GuestAddress,
     bytes: &[u8],
 ) -> Result<GuestUint, KernelError> {
     let memory = caller
@@ -184,14 +322,15 @@ pub fn read_i32(data: &[u8], index: usize) -> GuestResult<i32> {
 pub fn read_utf8(
     memory: &wasmtime::Memory,
     ctx: &impl AsContext<Data = InstanceRegistry>,
-    ptr: GuestInt,
-    len: GuestUint,
+    ptr: GuestAddress,
+    len: GuestAddress,
 ) -> GuestResult<String> {
     let ptr = usize::try_from(ptr).map_err(|_| GuestError::InvalidArgument)?;
     let len = usize::try_from(len).map_err(|_| GuestError::InvalidArgument)?;
+    let end = ptr.checked_add(len).ok_or(GuestError::MemorySlice)?;
     let data = memory
         .data(ctx)
-        .get(ptr..ptr + len)
+        .get(ptr..end)
         .ok_or(GuestError::MemorySlice)?;
     let s = str::from_utf8(data).map_err(|_| GuestError::InvalidUtf8)?;
     Ok(s.to_string())
@@ -200,20 +339,19 @@ pub fn read_utf8(
 pub fn read_capabilities(
     memory: &wasmtime::Memory,
     ctx: &impl AsContext<Data = InstanceRegistry>,
-    ptr: GuestInt,
-    count: GuestUint,
-) -> GuestResult<Vec<Capability>> {
+    ptr: GuestAddress,
+    count: GuestAddress,
+) -> GuestResult<CapabilitySet> {
     let ptr = usize::try_from(ptr).map_err(|_| GuestError::InvalidArgument)?;
     let count = usize::try_from(count).map_err(|_| GuestError::InvalidArgument)?;
+    let end = ptr.checked_add(count).ok_or(GuestError::MemorySlice)?;
     let data = memory
         .data(ctx)
-        .get(ptr..ptr + count)
+        .get(ptr..end)
         .ok_or(GuestError::MemorySlice)?;
-    let mut caps = Vec::with_capacity(count);
-    for byte in data {
-        caps.push(Capability::try_from(*byte).map_err(|_| GuestError::InvalidArgument)?);
-    }
-    Ok(caps)
+    data.iter()
+        .map(|byte| Capability::try_from(*byte).map_err(|_| GuestError::InvalidArgument))
+        .collect()
 }
 pub fn encode_ready_len(len: usize) -> Result<GuestUint, KernelError> {
     let guest_len = GuestUint::try_from(len).map_err(|_| KernelError::MemoryCapacity)?;
@@ -0,0 +1,165 @@
+//! Per-instance record of in-flight and recently completed hostcalls.
+//!
+//! Instances carrying a [`HostcallHistory`] extension have every hostcall recorded as it is
+//! started and completed, so that a crash report can show what the guest was waiting on and
+//! what it did last.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use selium_abi::ErrorCode;
+
+use crate::{
+    guest_data::GuestResult,
+    operation::{HostcallInfo, HostcallInterceptor},
+};
+
+/// Number of completed hostcalls remembered per instance.
+pub const RECENT_HOSTCALLS: usize = 32;
+
+/// In-flight and recently completed hostcalls of a single instance.
+#[derive(Default)]
+pub struct HostcallHistory {
+    state: Mutex<HistoryState>,
+}
+
+/// A hostcall that has started but not yet completed.
+#[derive(Clone, Debug)]
+pub struct PendingCall {
+    /// Wasm import module name of the hostcall.
+    pub module: &'static str,
+    /// How long the call has been running.
+    pub elapsed: Duration,
+}
+
+/// A hostcall that has completed.
+#[derive(Clone, Debug)]
+pub struct CompletedCall {
+    /// Wasm import module name of the hostcall.
+    pub module: &'static str,
+    /// How long the call took.
+    pub elapsed: Duration,
+    /// Length of the encoded output, or the code of the error the call failed with.
+    pub outcome: Result<usize, ErrorCode>,
+}
+
+#[derive(Default)]
+struct HistoryState {
+    next_id: u64,
+    pending: BTreeMap<u64, (&'static str, Instant)>,
+    recent: VecDeque<CompletedCall>,
+}
+
+/// Interceptor recording the completion of one call into its instance's history.
+///
+/// A call whose result is never delivered, such as one abandoned by the guest, is removed from
+/// the pending set when the recorder is dropped.
+pub(crate) struct CallRecorder {
+    history: Arc<HostcallHistory>,
+    id: u64,
+}
+
+impl HostcallHistory {
+    /// Calls that have started but not completed, oldest first.
+    pub fn pending(&self) -> Vec<PendingCall> {
+        let state = self.state.lock();
+        state
+            .pending
+            .values()
+            .map(|(module, started)| PendingCall {
+                module,
+                elapsed: started.elapsed(),
+            })
+            .collect()
+    }
+
+    /// The most recently completed calls, oldest first.
+    pub fn recent(&self) -> Vec<CompletedCall> {
+        self.state.lock().recent.iter().cloned().collect()
+    }
+
+    /// Record the start of a call to `module`, returning the interceptor that records its end.
+    pub(crate) fn begin(self: &Arc<Self>, module: &'static str) -> Arc<CallRecorder> {
+        let mut state = self.state.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.pending.insert(id, (module, Instant::now()));
+        Arc::new(CallRecorder {
+            history: Arc::clone(self),
+            id,
+        })
+    }
+}
+
+impl HostcallInterceptor for CallRecorder {
+    fn after(&self, _call: &HostcallInfo, elapsed: Duration, result: &GuestResult<Vec<u8>>) {
+        let mut state = self.history.state.lock();
+        let Some((module, _)) = state.pending.remove(&self.id) else {
+            return;
+        };
+        if state.recent.len() == RECENT_HOSTCALLS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(CompletedCall {
+            module,
+            elapsed,
+            outcome: result.as_ref().map(Vec::len).map_err(|err| err.code()),
+        });
+    }
+}
+
+impl Drop for CallRecorder {
+    fn drop(&mut self) {
+        self.history.state.lock().pending.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guest_data::GuestError;
+
+    fn info(module: &'static str) -> HostcallInfo {
+        HostcallInfo {
+            module,
+            capability: None,
+            session: None,
+            payload_len: 0,
+        }
+    }
+
+    #[test]
+    fn calls_move_from_pending_to_recent() {
+        let history = Arc::new(HostcallHistory::default());
+        let read = history.begin("selium::channel::read");
+        let abandoned = history.begin("selium::time::sleep");
+        assert_eq!(history.pending().len(), 2);
+
+        read.after(
+            &info("selium::channel::read"),
+            Duration::ZERO,
+            &Ok(vec![1, 2]),
+        );
+        drop(abandoned);
+        assert!(history.pending().is_empty());
+
+        for _ in 0..RECENT_HOSTCALLS {
+            history.begin("selium::time::now").after(
+                &info("selium::time::now"),
+                Duration::ZERO,
+                &Err(GuestError::NotFound),
+            );
+        }
+        let recent = history.recent();
+        assert_eq!(recent.len(), RECENT_HOSTCALLS);
+        assert!(
+            recent
+                .iter()
+                .all(|call| call.outcome == Err(ErrorCode::NotFound))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Result caching for hostcalls retried under an idempotency key.
+//!
+//! A guest that loses track of a call (for example because it dropped the future before polling
+//! it to completion) can retry it under the same key. The retry observes the original call's
+//! result instead of running a side-effectful driver such as `process::start` a second time.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    futures::FutureSharedState,
+    guest_data::{GuestError, GuestResult},
+    registry::{InstanceRegistry, RegistryError, ResourceId},
+};
+
+type GuestState = Arc<FutureSharedState<GuestResult<Vec<u8>>>>;
+
+/// Cache of encoded hostcall results, keyed by caller scope, hostcall and idempotency key.
+pub struct IdempotencyCache {
+    window: Duration,
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+}
+
+/// Idempotency key waiting to be attached to the instance's next hostcall.
+#[derive(Default)]
+pub struct PendingIdempotencyKey(Mutex<Option<String>>);
+
+/// Identity of a cached call.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct CacheKey {
+    scope: ResourceId,
+    module: &'static str,
+    key: String,
+}
+
+enum Entry {
+    /// The original call is still running; retries wait on its result.
+    Running(Vec<GuestState>),
+    /// The original call succeeded with this encoded output.
+    Complete { at: Instant, output: Vec<u8> },
+}
+
+/// Outcome of claiming a key for a new call.
+pub(crate) enum Claim {
+    /// No live entry exists; the caller must run the driver and [`IdempotencyCache::complete`].
+    Fresh,
+    /// The original call is still running and will resolve the given state too.
+    Joined,
+    /// The original call already succeeded with this encoded output.
+    Cached(Vec<u8>),
+}
+
+impl IdempotencyCache {
+    /// Create a cache that remembers successful results for `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long successful results are remembered.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Claim `key` for a call whose result will be delivered to `state`.
+    pub(crate) fn claim(&self, key: &CacheKey, state: &GuestState) -> Claim {
+        let mut entries = self.entries.lock();
+        let now = Instant::now();
+        entries.retain(|_, entry| match entry {
+            Entry::Running(_) => true,
+            Entry::Complete { at, .. } => now.duration_since(*at) < self.window,
+        });
+
+        match entries.get_mut(key) {
+            Some(Entry::Running(waiters)) => {
+                waiters.push(Arc::clone(state));
+                Claim::Joined
+            }
+            Some(Entry::Complete { output, .. }) => Claim::Cached(output.clone()),
+            None => {
+                entries.insert(key.clone(), Entry::Running(Vec::new()));
+                Claim::Fresh
+            }
+        }
+    }
+
+    /// Record the result of a fresh call and resolve any retries that joined it.
+    ///
+    /// Failures are not cached, so a later retry runs the driver again.
+    pub(crate) fn complete(&self, key: &CacheKey, result: &GuestResult<Vec<u8>>) {
+        let mut entries = self.entries.lock();
+        let waiters = match result {
+            Ok(output) => entries.insert(
+                key.clone(),
+                Entry::Complete {
+                    at: Instant::now(),
+                    output: output.clone(),
+                },
+            ),
+            Err(_) => entries.remove(key),
+        };
+        drop(entries);
+
+        if let Some(Entry::Running(waiters)) = waiters {
+            for waiter in waiters {
+                waiter.resolve(match result {
+                    Ok(output) => Ok(output.clone()),
+                    Err(err) => Err(GuestError::Coded(err.code(), err.to_string())),
+                });
+            }
+        }
+    }
+}
+
+impl PendingIdempotencyKey {
+    /// Attach `key` to the instance's next hostcall, replacing any key not yet consumed.
+    pub fn set(registry: &mut InstanceRegistry, key: String) -> Result<(), RegistryError> {
+        match registry.extension::<PendingIdempotencyKey>() {
+            Some(pending) => {
+                *pending.0.lock() = Some(key);
+                Ok(())
+            }
+            None => registry.insert_extension(PendingIdempotencyKey(Mutex::new(Some(key)))),
+        }
+    }
+
+    /// Consume the key attached to this call, if any.
+    pub fn take(registry: &InstanceRegistry) -> Option<String> {
+        registry
+            .extension::<PendingIdempotencyKey>()
+            .and_then(|pending| pending.0.lock().take())
+    }
+}
+
+impl CacheKey {
+    /// Identify a call by the scope it was made in, its hostcall and its key.
+    pub(crate) fn new(scope: ResourceId, module: &'static str, key: String) -> Self {
+        Self { scope, module, key }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> CacheKey {
+        CacheKey::new(1, "selium::process::start", name.to_string())
+    }
+
+    #[test]
+    fn successful_results_are_replayed() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+        let first = FutureSharedState::new();
+        let retry = FutureSharedState::new();
+        let late = FutureSharedState::new();
+
+        assert!(matches!(cache.claim(&key("a"), &first), Claim::Fresh));
+        assert!(matches!(cache.claim(&key("a"), &retry), Claim::Joined));
+        cache.complete(&key("a"), &Ok(vec![7]));
+
+        assert!(matches!(retry.take_result(), Some(Ok(bytes)) if bytes == [7]));
+        assert!(matches!(cache.claim(&key("a"), &late), Claim::Cached(bytes) if bytes == [7]));
+        assert!(matches!(cache.claim(&key("b"), &late), Claim::Fresh));
+    }
+
+    #[test]
+    fn failures_and_expired_results_run_again() {
+        let cache = IdempotencyCache::new(Duration::ZERO);
+        let state = FutureSharedState::new();
+
+        assert!(matches!(cache.claim(&key("a"), &state), Claim::Fresh));
+        cache.complete(&key("a"), &Err(GuestError::NotFound));
+        assert!(matches!(cache.claim(&key("a"), &state), Claim::Fresh));
+        cache.complete(&key("a"), &Ok(vec![1]));
+        assert!(matches!(cache.claim(&key("a"), &state), Claim::Fresh));
+    }
+}
@@ -1,22 +1,78 @@
 use std::{
-    any::{Any, TypeId},
-    collections::HashMap,
+    any::{Any, TypeId, type_name},
+    collections::{BTreeSet, HashMap},
     num::TryFromIntError,
     sync::Arc,
 };
 
+use selium_abi::hostcalls;
 use thiserror::Error;
+use tracing::debug;
 
-use crate::registry::RegistryError;
+use crate::{drivers::Capability, operation::LinkableOperation, registry::RegistryError};
 
+pub mod buffers;
 pub mod drivers;
+pub mod events;
 pub mod futures;
 pub mod guest_async;
 pub mod guest_data;
+pub mod history;
+pub mod idempotency;
 pub mod mailbox;
+pub mod metrics;
 pub mod operation;
+pub mod priority;
+pub mod profile;
 pub mod registry;
 pub mod session;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
+pub mod watchdog;
+
+/// Source of drivers and hostcall operations that registers itself with a [`KernelBuilder`],
+/// letting embedders add hostcall families without changing how the kernel is assembled.
+pub trait CapabilityProvider: Send + Sync {
+    /// Name of the provider, for diagnostics.
+    fn name(&self) -> &str;
+
+    /// Version of the provider, for diagnostics such as the runtime's startup report.
+    fn version(&self) -> Option<&str> {
+        None
+    }
+
+    /// Add this provider's drivers and operations to `builder`.
+    fn register(&self, builder: &mut KernelBuilder) -> Result<(), KernelError>;
+}
+
+/// A hostcall family shipped by a crate outside Selium.
+///
+/// Where a [`CapabilityProvider`] may register anything, a plugin declares the hostcalls it adds
+/// and the capability that grants them, and [`KernelBuilder::add_plugins`] checks its operations
+/// against that declaration. Guests not granted the capability link stubs that deny every call,
+/// as they do for built-in hostcalls.
+pub trait HostCapabilityPlugin: Send + Sync {
+    /// Name of the plugin, for diagnostics.
+    fn name(&self) -> &str;
+
+    /// Version of the plugin, for diagnostics such as the runtime's startup report.
+    fn version(&self) -> Option<&str> {
+        None
+    }
+
+    /// Capability a guest must be granted to call this plugin's hostcalls.
+    fn capability(&self) -> Capability;
+
+    /// Names of the hostcalls this plugin adds, as guests import them.
+    fn hostcalls(&self) -> &[&'static str];
+
+    /// Build an operation for each of [`hostcalls`](Self::hostcalls), adding any drivers they
+    /// share to `builder`.
+    fn operations(
+        &self,
+        builder: &mut KernelBuilder,
+    ) -> Result<Vec<Arc<dyn LinkableOperation>>, KernelError>;
+}
 
 pub struct Kernel {
     capabilities: HashMap<TypeId, Arc<dyn Any>>,
@@ -25,6 +81,10 @@ pub struct Kernel {
 #[derive(Default)]
 pub struct KernelBuilder {
     capabilities: HashMap<TypeId, Arc<dyn Any>>,
+    operations: HashMap<Capability, Vec<Arc<dyn LinkableOperation>>>,
+    required: BTreeSet<Capability>,
+    /// Capability types [`KernelBuilder::build`] insists were added, with their names.
+    asserted: Vec<(TypeId, &'static str)>,
 }
 
 #[derive(Error, Debug)]
@@ -45,6 +105,14 @@ pub enum KernelError {
     Registry(#[from] RegistryError),
     #[error("Driver error: {0}")]
     Driver(String),
+    #[error("Payload of {len} bytes exceeds the {max} byte limit")]
+    PayloadTooLarge { len: usize, max: usize },
+    #[error("No operation registered for hostcalls: {}", .0.join(", "))]
+    UncoveredHostcalls(Vec<String>),
+    #[error("Kernel has no `{0}` capability")]
+    MissingCapability(&'static str),
+    #[error("Plugin `{plugin}` rejected: {reason}")]
+    InvalidPlugin { plugin: String, reason: String },
 }
 
 impl Kernel {
@@ -52,11 +120,19 @@ impl Kernel {
         KernelBuilder::default()
     }
 
+    /// The capability of type `C`, for capabilities a kernel may be built without.
     pub fn get<C: 'static>(&self) -> Option<&C> {
         self.capabilities
             .get(&TypeId::of::<C>())
             .and_then(|cap| cap.downcast_ref::<C>())
     }
+
+    /// The capability of type `C`, failing with [`KernelError::MissingCapability`] if the kernel
+    /// was built without it.
+    pub fn require<C: 'static>(&self) -> Result<&C, KernelError> {
+        self.get::<C>()
+            .ok_or(KernelError::MissingCapability(type_name::<C>()))
+    }
 }
 
 impl KernelBuilder {
@@ -66,9 +142,321 @@ impl KernelBuilder {
         capability
     }
 
+    /// Link `operation` into every instance granted `capability`.
+    pub fn register_operation(
+        &mut self,
+        operation: Arc<dyn LinkableOperation>,
+        capability: Capability,
+    ) {
+        self.operations
+            .entry(capability)
+            .or_default()
+            .push(operation);
+    }
+
+    /// Link each of `operations` into every instance granted `capability`.
+    pub fn register_operations(
+        &mut self,
+        operations: impl IntoIterator<Item = Arc<dyn LinkableOperation>>,
+        capability: Capability,
+    ) {
+        self.operations
+            .entry(capability)
+            .or_default()
+            .extend(operations);
+    }
+
+    /// Let each of `providers` register its drivers and operations, in order.
+    pub fn add_providers<'a>(
+        &mut self,
+        providers: impl IntoIterator<Item = &'a dyn CapabilityProvider>,
+    ) -> Result<(), KernelError> {
+        for provider in providers {
+            debug!(
+                provider = provider.name(),
+                "registering capability provider"
+            );
+            provider.register(self)?;
+        }
+        Ok(())
+    }
+
+    /// Register the operations of each of `plugins`, in order, under the capability it declares.
+    ///
+    /// Fails if a plugin's operations do not implement exactly the hostcalls it declares, or if
+    /// it declares a hostcall of the catalogue or one registered already.
+    pub fn add_plugins<'a>(
+        &mut self,
+        plugins: impl IntoIterator<Item = &'a dyn HostCapabilityPlugin>,
+    ) -> Result<(), KernelError> {
+        for plugin in plugins {
+            debug!(
+                plugin = plugin.name(),
+                capability = %plugin.capability(),
+                "registering host capability plugin"
+            );
+            let invalid = |reason: String| KernelError::InvalidPlugin {
+                plugin: plugin.name().to_string(),
+                reason,
+            };
+
+            let declared: BTreeSet<&str> = plugin.hostcalls().iter().copied().collect();
+            if let Some(taken) = declared.iter().find(|name| self.provides(name)) {
+                return Err(invalid(format!("hostcall `{taken}` is already provided")));
+            }
+
+            let operations = plugin.operations(self)?;
+            let implemented: BTreeSet<&str> = operations.iter().map(|op| op.module()).collect();
+            if let Some(missing) = declared.difference(&implemented).next() {
+                return Err(invalid(format!("no operation for hostcall `{missing}`")));
+            }
+            if let Some(undeclared) = implemented.difference(&declared).next() {
+                return Err(invalid(format!(
+                    "operation for undeclared hostcall `{undeclared}`"
+                )));
+            }
+
+            self.register_operations(operations, plugin.capability());
+        }
+        Ok(())
+    }
+
+    /// Operations registered so far, by the capability that grants them.
+    pub fn operations(&self) -> &HashMap<Capability, Vec<Arc<dyn LinkableOperation>>> {
+        &self.operations
+    }
+
+    /// Make [`KernelBuilder::build`] fail unless every catalogue hostcall of each of
+    /// `capabilities` has a registered operation, catching drivers left out of assembly.
+    pub fn require_capabilities(&mut self, capabilities: impl IntoIterator<Item = Capability>) {
+        self.required.extend(capabilities);
+    }
+
+    /// Make [`KernelBuilder::build`] fail unless a capability of type `C` has been added, so that
+    /// code relying on [`Kernel::require`] cannot meet a kernel assembled without it.
+    pub fn assert_capability<C: 'static>(&mut self) {
+        self.asserted.push((TypeId::of::<C>(), type_name::<C>()));
+    }
+
     pub fn build(self) -> Result<Kernel, KernelError> {
+        if let Some((_, name)) = self
+            .asserted
+            .iter()
+            .find(|(id, _)| !self.capabilities.contains_key(id))
+        {
+            return Err(KernelError::MissingCapability(name));
+        }
+
+        let uncovered = self.uncovered_hostcalls();
+        if !uncovered.is_empty() {
+            return Err(KernelError::UncoveredHostcalls(uncovered));
+        }
+
         Ok(Kernel {
             capabilities: self.capabilities,
         })
     }
+
+    /// Whether `hostcall` is in the catalogue or has a registered operation.
+    fn provides(&self, hostcall: &str) -> bool {
+        hostcalls::ALL.iter().any(|meta| meta.name == hostcall)
+            || self
+                .operations
+                .values()
+                .flatten()
+                .any(|operation| operation.module() == hostcall)
+    }
+
+    /// Catalogue hostcalls of the required capabilities that no registered operation links, as
+    /// `name (Capability)`.
+    fn uncovered_hostcalls(&self) -> Vec<String> {
+        let catalogue = hostcalls::by_capability();
+        let mut uncovered = Vec::new();
+        for capability in &self.required {
+            let registered = self.operations.get(capability);
+            for meta in catalogue.get(capability).into_iter().flatten() {
+                let linked = registered
+                    .into_iter()
+                    .flatten()
+                    .any(|operation| operation.module() == meta.name);
+                if !linked {
+                    uncovered.push(format!("{} ({capability})", meta.name));
+                }
+            }
+        }
+        uncovered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Provider registering the singleton lookup hostcall.
+    struct LookupProvider;
+
+    impl CapabilityProvider for LookupProvider {
+        fn name(&self) -> &str {
+            "lookup"
+        }
+
+        fn register(&self, builder: &mut KernelBuilder) -> Result<(), KernelError> {
+            let (_, lookup) = drivers::singleton::operations();
+            builder.register_operation(lookup.as_linkable(), Capability::SingletonLookup);
+            Ok(())
+        }
+    }
+
+    /// Plugin declaring `declared` and implementing `implemented` with the ready driver.
+    struct ReadyPlugin {
+        declared: &'static [&'static str],
+        implemented: &'static [&'static str],
+    }
+
+    impl HostCapabilityPlugin for ReadyPlugin {
+        fn name(&self) -> &str {
+            "ready"
+        }
+
+        fn capability(&self) -> Capability {
+            Capability::TimeRead
+        }
+
+        fn hostcalls(&self) -> &[&'static str] {
+            self.declared
+        }
+
+        fn operations(
+            &self,
+            _builder: &mut KernelBuilder,
+        ) -> Result<Vec<Arc<dyn LinkableOperation>>, KernelError> {
+            Ok(self
+                .implemented
+                .iter()
+                .map(|module| {
+                    operation::Operation::new(drivers::meta::ReadyDriver, module).as_linkable()
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn plugins_register_the_hostcalls_they_declare() {
+        let plugin = ReadyPlugin {
+            declared: &["acme::ready"],
+            implemented: &["acme::ready"],
+        };
+        let mut builder = Kernel::build();
+        builder
+            .add_plugins([&plugin as &dyn HostCapabilityPlugin])
+            .expect("register plugin");
+
+        let modules: Vec<_> = builder.operations()[&Capability::TimeRead]
+            .iter()
+            .map(|operation| operation.module())
+            .collect();
+        assert_eq!(modules, ["acme::ready"]);
+
+        let Err(KernelError::InvalidPlugin { reason, .. }) =
+            builder.add_plugins([&plugin as &dyn HostCapabilityPlugin])
+        else {
+            panic!("plugin registered twice");
+        };
+        assert_eq!(reason, "hostcall `acme::ready` is already provided");
+    }
+
+    #[test]
+    fn plugins_must_implement_exactly_what_they_declare() {
+        let cases = [
+            (
+                ReadyPlugin {
+                    declared: &["selium::time::now"],
+                    implemented: &["selium::time::now"],
+                },
+                "hostcall `selium::time::now` is already provided",
+            ),
+            (
+                ReadyPlugin {
+                    declared: &["acme::ready", "acme::wait"],
+                    implemented: &["acme::ready"],
+                },
+                "no operation for hostcall `acme::wait`",
+            ),
+            (
+                ReadyPlugin {
+                    declared: &["acme::ready"],
+                    implemented: &["acme::ready", "acme::wait"],
+                },
+                "operation for undeclared hostcall `acme::wait`",
+            ),
+        ];
+
+        for (plugin, expected) in cases {
+            let mut builder = Kernel::build();
+            let Err(KernelError::InvalidPlugin { reason, .. }) =
+                builder.add_plugins([&plugin as &dyn HostCapabilityPlugin])
+            else {
+                panic!("invalid plugin registered");
+            };
+            assert_eq!(reason, expected);
+            assert!(builder.operations().is_empty());
+        }
+    }
+
+    #[test]
+    fn asserted_capabilities_must_be_added() {
+        let mut builder = Kernel::build();
+        builder.assert_capability::<drivers::meta::ReadyDriver>();
+        assert!(matches!(
+            builder.build(),
+            Err(KernelError::MissingCapability(name)) if name.ends_with("ReadyDriver")
+        ));
+
+        let mut builder = Kernel::build();
+        builder.add_capability(Arc::new(drivers::meta::ReadyDriver));
+        builder.assert_capability::<drivers::meta::ReadyDriver>();
+        let kernel = builder.build().expect("kernel");
+        assert!(kernel.require::<drivers::meta::ReadyDriver>().is_ok());
+        assert!(kernel.get::<drivers::meta::HostcallsDriver>().is_none());
+        assert!(matches!(
+            kernel.require::<drivers::meta::HostcallsDriver>(),
+            Err(KernelError::MissingCapability(name)) if name.ends_with("HostcallsDriver")
+        ));
+    }
+
+    #[test]
+    fn providers_register_operations_by_capability() {
+        let mut builder = Kernel::build();
+        builder
+            .add_providers([&LookupProvider as &dyn CapabilityProvider])
+            .expect("register provider");
+
+        let operations = builder.operations();
+        assert_eq!(operations.len(), 1);
+        let modules: Vec<_> = operations[&Capability::SingletonLookup]
+            .iter()
+            .map(|operation| operation.module())
+            .collect();
+        assert_eq!(modules, ["selium::singleton::lookup"]);
+    }
+
+    #[test]
+    fn required_capabilities_must_cover_every_hostcall() {
+        let mut builder = Kernel::build();
+        builder
+            .add_providers([&LookupProvider as &dyn CapabilityProvider])
+            .expect("register provider");
+        builder.require_capabilities([Capability::SingletonLookup, Capability::TimeRead]);
+
+        let Err(KernelError::UncoveredHostcalls(uncovered)) = builder.build() else {
+            panic!("kernel without time operations built");
+        };
+        assert_eq!(
+            uncovered,
+            [
+                "selium::time::now (TimeRead)",
+                "selium::time::sleep (TimeRead)"
+            ]
+        );
+    }
 }
@@ -32,8 +32,8 @@ use tokio::sync::Notify;
 use wasmtime::{Memory, Store};
 
 use selium_abi::{
-    GuestAtomicUint, GuestUint,
-    mailbox::{CAPACITY, FLAG_OFFSET, RING_OFFSET, TAIL_OFFSET},
+    GuestAtomicUint, GuestUint, MailboxDiagnostics,
+    mailbox::{CAPACITY, FLAG_OFFSET, HEAD_OFFSET, RING_OFFSET, TAIL_OFFSET},
 };
 
 /// Mailbox exposing guest task IDs to the host async scheduler.
@@ -130,6 +130,26 @@ impl GuestMailbox {
         unsafe { (*flag).load(Ordering::Acquire) != 0 }
     }
 
+    /// Report how many wake-ups have been signalled and how many the guest has yet to drain.
+    pub(crate) fn diagnostics(&self) -> MailboxDiagnostics {
+        let closed = self.is_closed();
+        let base = self.base.load(Ordering::Acquire);
+        let cell = |offset: usize| (base + offset) as *const GuestAtomicUint;
+        let (flag, head, tail) = unsafe {
+            (
+                (*cell(FLAG_OFFSET)).load(Ordering::Acquire),
+                (*cell(HEAD_OFFSET)).load(Ordering::Acquire),
+                (*cell(TAIL_OFFSET)).load(Ordering::Acquire),
+            )
+        };
+        MailboxDiagnostics {
+            signalled: tail,
+            undrained: tail.wrapping_sub(head),
+            flagged: flag != 0,
+            closed,
+        }
+    }
+
     /// Await the next mailbox wake-up notification from the host.
     pub(crate) async fn wait_for_signal(&self) {
         self.notify.notified().await;
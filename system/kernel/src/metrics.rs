@@ -0,0 +1,355 @@
+//! Per-hostcall call counts, error counts and latency histograms, and metrics kept for guests.
+//!
+//! A [`HostcallMetrics`] interceptor attached to an operation counts every call to it, keyed by
+//! the hostcall's Wasm import module name, and files the time from the call's creation to its
+//! resolution into a fixed set of latency buckets.
+//!
+//! [`GuestMetrics`] holds the named counters and gauges that guests update through the metrics
+//! hostcalls, so that the host can report them alongside its own.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use parking_lot::RwLock;
+use selium_abi::ErrorCode;
+use thiserror::Error;
+use tracing::trace;
+
+use crate::{
+    guest_data::GuestResult,
+    operation::{HostcallInfo, HostcallInterceptor},
+};
+
+/// Upper bounds of the latency buckets, in microseconds. Calls slower than the last bound are
+/// counted in a final overflow bucket.
+pub const LATENCY_BUCKETS_US: [u64; 12] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 50_000, 250_000, 1_000_000, 10_000_000,
+];
+
+/// Most distinct metrics guests may name; further names are refused.
+pub const MAX_GUEST_METRICS: usize = 1024;
+
+/// Counters shared by every operation the interceptor is attached to. Clones share the same
+/// counters.
+#[derive(Clone, Default)]
+pub struct HostcallMetrics {
+    hostcalls: Arc<RwLock<HashMap<&'static str, Arc<Counters>>>>,
+}
+
+/// Counters of a single hostcall at the time of a [`HostcallMetrics::snapshot`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HostcallStats {
+    /// Wasm import module name of the hostcall.
+    pub module: String,
+    /// Calls that have completed, successfully or not.
+    pub calls: u64,
+    /// Failed calls by error code, in numeric order; codes never returned are left out.
+    pub errors: Vec<(ErrorCode, u64)>,
+    /// Time from creation to resolution of every completed call.
+    pub latency: LatencyHistogram,
+}
+
+/// Distribution of hostcall latencies over [`LATENCY_BUCKETS_US`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LatencyHistogram {
+    /// Calls per bucket, not cumulative; the last entry counts calls slower than every bound.
+    pub buckets: Vec<u64>,
+    /// Sum of all recorded latencies.
+    pub total: Duration,
+}
+
+/// Named counters and gauges updated by guests. Clones share the same metrics.
+#[derive(Clone, Default)]
+pub struct GuestMetrics {
+    metrics: Arc<RwLock<HashMap<String, GuestMetric>>>,
+}
+
+/// Values of the guest metrics at the time of a [`GuestMetrics::snapshot`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GuestMetricsSnapshot {
+    /// Counters by name, ordered by name.
+    pub counters: Vec<(String, u64)>,
+    /// Gauges by name, ordered by name.
+    pub gauges: Vec<(String, i64)>,
+}
+
+/// Reasons a guest metric update is refused.
+#[derive(Clone, Copy, Debug, Error, Eq, PartialEq)]
+pub enum GuestMetricError {
+    /// The name is not a Prometheus metric name, or claims the host's `selium_` prefix.
+    #[error("metric names must match [a-zA-Z_:][a-zA-Z0-9_:]* and not start with `selium_`")]
+    InvalidName,
+    /// The name is already in use by a metric of another kind.
+    #[error("metric is already registered as a {0}")]
+    KindMismatch(&'static str),
+    /// [`MAX_GUEST_METRICS`] metrics are already registered.
+    #[error("no more than {MAX_GUEST_METRICS} guest metrics may be registered")]
+    TooMany,
+}
+
+#[derive(Clone)]
+enum GuestMetric {
+    Counter(Arc<AtomicU64>),
+    Gauge(Arc<AtomicI64>),
+}
+
+struct Counters {
+    calls: AtomicU64,
+    errors: [AtomicU64; ErrorCode::ALL.len()],
+    buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+    total_us: AtomicU64,
+}
+
+impl HostcallMetrics {
+    /// Counters of every hostcall called at least once, ordered by module name.
+    pub fn snapshot(&self) -> Vec<HostcallStats> {
+        let mut stats: Vec<_> = self
+            .hostcalls
+            .read()
+            .iter()
+            .map(|(module, counters)| counters.snapshot(module))
+            .collect();
+        stats.sort_by(|a, b| a.module.cmp(&b.module));
+        stats
+    }
+
+    fn counters(&self, module: &'static str) -> Arc<Counters> {
+        if let Some(counters) = self.hostcalls.read().get(module) {
+            return Arc::clone(counters);
+        }
+        Arc::clone(
+            self.hostcalls
+                .write()
+                .entry(module)
+                .or_insert_with(|| Arc::new(Counters::default())),
+        )
+    }
+}
+
+impl GuestMetrics {
+    /// Add `delta` to the counter `name`, creating it at zero, and return its new value.
+    pub fn add_counter(&self, name: &str, delta: u64) -> Result<u64, GuestMetricError> {
+        match self.metric(name, || GuestMetric::Counter(Arc::default()))? {
+            GuestMetric::Counter(value) => Ok(value
+                .fetch_add(delta, Ordering::Relaxed)
+                .wrapping_add(delta)),
+            GuestMetric::Gauge(_) => Err(GuestMetricError::KindMismatch("gauge")),
+        }
+    }
+
+    /// Set the gauge `name` to `value`, creating it.
+    pub fn set_gauge(&self, name: &str, value: i64) -> Result<(), GuestMetricError> {
+        match self.metric(name, || GuestMetric::Gauge(Arc::default()))? {
+            GuestMetric::Gauge(gauge) => {
+                gauge.store(value, Ordering::Relaxed);
+                Ok(())
+            }
+            GuestMetric::Counter(_) => Err(GuestMetricError::KindMismatch("counter")),
+        }
+    }
+
+    /// Add `delta` to the gauge `name`, creating it at zero, and return its new value.
+    pub fn add_gauge(&self, name: &str, delta: i64) -> Result<i64, GuestMetricError> {
+        match self.metric(name, || GuestMetric::Gauge(Arc::default()))? {
+            GuestMetric::Gauge(gauge) => Ok(gauge
+                .fetch_add(delta, Ordering::Relaxed)
+                .wrapping_add(delta)),
+            GuestMetric::Counter(_) => Err(GuestMetricError::KindMismatch("counter")),
+        }
+    }
+
+    /// Current value of every guest metric.
+    pub fn snapshot(&self) -> GuestMetricsSnapshot {
+        let mut snapshot = GuestMetricsSnapshot::default();
+        for (name, metric) in self.metrics.read().iter() {
+            match metric {
+                GuestMetric::Counter(value) => snapshot
+                    .counters
+                    .push((name.clone(), value.load(Ordering::Relaxed))),
+                GuestMetric::Gauge(value) => snapshot
+                    .gauges
+                    .push((name.clone(), value.load(Ordering::Relaxed))),
+            }
+        }
+        snapshot.counters.sort();
+        snapshot.gauges.sort();
+        snapshot
+    }
+
+    /// The metric `name`, registering the one built by `create` if there is none yet.
+    fn metric(
+        &self,
+        name: &str,
+        create: impl FnOnce() -> GuestMetric,
+    ) -> Result<GuestMetric, GuestMetricError> {
+        if let Some(metric) = self.metrics.read().get(name) {
+            return Ok(metric.clone());
+        }
+        if !is_metric_name(name) {
+            return Err(GuestMetricError::InvalidName);
+        }
+        let mut metrics = self.metrics.write();
+        if let Some(metric) = metrics.get(name) {
+            return Ok(metric.clone());
+        }
+        if metrics.len() >= MAX_GUEST_METRICS {
+            return Err(GuestMetricError::TooMany);
+        }
+        let metric = create();
+        metrics.insert(name.to_string(), metric.clone());
+        Ok(metric)
+    }
+}
+
+impl HostcallInterceptor for HostcallMetrics {
+    fn after(&self, call: &HostcallInfo, elapsed: Duration, result: &GuestResult<Vec<u8>>) {
+        let code = result.as_ref().err().map(|err| err.code());
+        self.counters(call.module).record(elapsed, code);
+        trace!(
+            hostcall = call.module,
+            elapsed_us = elapsed.as_micros(),
+            error = code.map(tracing::field::debug),
+            "hostcall resolved"
+        );
+    }
+}
+
+impl Counters {
+    fn record(&self, elapsed: Duration, code: Option<ErrorCode>) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(micros, Ordering::Relaxed);
+        if let Some(index) = code.and_then(|code| ErrorCode::ALL.iter().position(|c| *c == code)) {
+            self.errors[index].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self, module: &str) -> HostcallStats {
+        HostcallStats {
+            module: module.to_string(),
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: ErrorCode::ALL
+                .into_iter()
+                .zip(&self.errors)
+                .map(|(code, count)| (code, count.load(Ordering::Relaxed)))
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+            latency: LatencyHistogram {
+                buckets: self
+                    .buckets
+                    .iter()
+                    .map(|count| count.load(Ordering::Relaxed))
+                    .collect(),
+                total: Duration::from_micros(self.total_us.load(Ordering::Relaxed)),
+            },
+        }
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            errors: std::array::from_fn(|_| AtomicU64::new(0)),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            total_us: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Whether `name` is a Prometheus metric name outside the host's `selium_` namespace.
+fn is_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid_start = |ch: char| ch.is_ascii_alphabetic() || ch == '_' || ch == ':';
+    chars.next().is_some_and(valid_start)
+        && chars.all(|ch| valid_start(ch) || ch.is_ascii_digit())
+        && !name.starts_with("selium_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guest_data::GuestError;
+
+    fn info(module: &'static str) -> HostcallInfo {
+        HostcallInfo {
+            module,
+            capability: None,
+            session: None,
+            payload_len: 0,
+        }
+    }
+
+    #[test]
+    fn calls_are_counted_per_hostcall() {
+        let metrics = HostcallMetrics::default();
+        let sleep = info("selium::time::sleep");
+        metrics.after(&sleep, Duration::from_micros(40), &Ok(Vec::new()));
+        metrics.after(&sleep, Duration::from_secs(60), &Err(GuestError::TimedOut));
+        metrics.after(
+            &info("selium::time::now"),
+            Duration::from_micros(300),
+            &Ok(Vec::new()),
+        );
+
+        let stats = metrics.snapshot();
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stats| stats.module.as_str())
+                .collect::<Vec<_>>(),
+            ["selium::time::now", "selium::time::sleep"]
+        );
+        let sleep = &stats[1];
+        assert_eq!(sleep.calls, 2);
+        assert_eq!(sleep.errors, [(ErrorCode::TimedOut, 1)]);
+        assert_eq!(sleep.latency.buckets[0], 1);
+        assert_eq!(sleep.latency.buckets[LATENCY_BUCKETS_US.len()], 1);
+        assert_eq!(
+            sleep.latency.total,
+            Duration::from_secs(60) + Duration::from_micros(40)
+        );
+        assert_eq!(stats[0].latency.buckets[3], 1);
+    }
+
+    #[test]
+    fn guest_metrics_keep_one_kind_per_name() {
+        let metrics = GuestMetrics::default();
+        assert_eq!(metrics.add_counter("requests_total", 2), Ok(2));
+        assert_eq!(metrics.add_counter("requests_total", 3), Ok(5));
+        assert_eq!(metrics.set_gauge("in_flight", 4), Ok(()));
+        assert_eq!(metrics.add_gauge("in_flight", -5), Ok(-1));
+
+        assert_eq!(
+            metrics.set_gauge("requests_total", 1),
+            Err(GuestMetricError::KindMismatch("counter"))
+        );
+        assert_eq!(
+            metrics.add_counter("selium_hostcall_errors_total", 1),
+            Err(GuestMetricError::InvalidName)
+        );
+        assert_eq!(
+            metrics.add_counter("9lives", 1),
+            Err(GuestMetricError::InvalidName)
+        );
+        assert_eq!(
+            metrics.snapshot(),
+            GuestMetricsSnapshot {
+                counters: vec![("requests_total".to_string(), 5)],
+                gauges: vec![("in_flight".to_string(), -1)],
+            }
+        );
+    }
+}
@@ -1,48 +1,267 @@
-use std::{convert::TryFrom, sync::Arc};
+use std::{
+    convert::TryFrom,
+    future::ready,
+    pin::pin,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use selium_abi::hostcalls::Hostcall;
-use selium_abi::{RkyvEncode, encode_rkyv};
-use tracing::{debug, trace};
-use wasmtime::{Caller, Linker};
+use futures_util::{FutureExt, Stream, StreamExt, future::Either};
+use selium_abi::hostcalls::{DEFAULT_MAX_PAYLOAD, Hostcall, HostcallMeta};
+use selium_abi::{
+    CapabilitySet, JsonPayload, PayloadEncoding, RkyvEncode, decode_rkyv, encode_payload,
+};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, debug, debug_span, trace, warn};
+use wasmtime::{Caller, Linker, WasmTy};
 
 use crate::{
     KernelError,
+    drivers::Capability,
+    events::KernelEvent,
     futures::FutureSharedState,
     guest_data::{
-        GuestError, GuestInt, GuestResult, GuestUint, read_rkyv_value, write_poll_result,
+        AddressWidth, ArchivedPayload, GuestAddress, GuestError, GuestResult, GuestUint,
+        read_archived_payload, read_payload_value, write_poll_chunk, write_poll_result,
+    },
+    history::HostcallHistory,
+    idempotency::{CacheKey, Claim, IdempotencyCache, PendingIdempotencyKey},
+    priority::PriorityClass,
+    registry::{
+        InstanceRegistry, ProcessIdentity, Registry, RegistryError, ResourceHandle, ResourceId,
+        ResourceType,
     },
-    registry::InstanceRegistry,
+    session::Session,
 };
 
+/// Shared state a started hostcall delivers its encoded result to.
+pub type CallState = Arc<FutureSharedState<GuestResult<Vec<u8>>>>;
+
 /// `Contract` is used by kernel drivers to define a consistent method for guest execution.
 /// This allows [`Operation`]s to expose the driver contract to the guest without having
 /// to know its internal structure.
 pub trait Contract {
-    type Input: RkyvEncode + Send;
-    type Output: RkyvEncode + Send;
+    type Input: RkyvEncode + JsonPayload + Send;
+    type Output: RkyvEncode + JsonPayload + Send;
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static;
+
+    /// Registry resource targeted by this call, if any. When present, the calling session's
+    /// resource scope for the hostcall's capability is checked against it.
+    fn resource(&self, _input: &Self::Input) -> Option<ResourceId> {
+        None
+    }
+
+    /// Whether rkyv-encoded calls start through [`Contract::to_future_archived`], leaving the
+    /// input archived rather than deserialising it first.
+    const ARCHIVED_INPUT: bool = false;
+
+    /// Start the call from its validated, archived input. Drivers that set
+    /// [`Contract::ARCHIVED_INPUT`] override this to read fields or borrow large payloads in
+    /// place; the default deserialises the input and defers to [`Contract::to_future`].
+    fn to_future_archived(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: ArchivedPayload<Self::Input>,
+    ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static
+    where
+        for<'a> <Self::Input as rkyv::Archive>::Archived: 'a
+            + rkyv::Deserialize<Self::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+        Self::Output: 'static,
+    {
+        match input.deserialize() {
+            Ok(input) => Either::Left(self.to_future(instance, input)),
+            Err(err) => Either::Right(ready(Err(err))),
+        }
+    }
+
+    /// Archived counterpart to [`Contract::resource`], used alongside
+    /// [`Contract::to_future_archived`]. The default deserialises the input, so drivers that
+    /// set [`Contract::ARCHIVED_INPUT`] override it.
+    fn archived_resource(&self, input: &rkyv::Archived<Self::Input>) -> Option<ResourceId>
+    where
+        for<'a> <Self::Input as rkyv::Archive>::Archived: 'a
+            + rkyv::Deserialize<Self::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>,
+    {
+        rkyv::deserialize::<Self::Input, rkyv::rancor::Error>(input)
+            .ok()
+            .and_then(|input| self.resource(&input))
+    }
+}
+
+/// Streaming counterpart to [`Contract`] for hostcalls that yield a sequence of outputs, such
+/// as subscriptions. Each guest poll returns the next item encoded as `Some(item)`; a final
+/// `None` marks the end of the stream, after which the guest-visible state is released.
+pub trait StreamContract {
+    type Input: RkyvEncode + JsonPayload + Send;
+    type Item: RkyvEncode + JsonPayload + Send;
+
+    fn to_stream(
+        &self,
+        instance: &mut InstanceRegistry,
+        input: Self::Input,
+    ) -> impl Stream<Item = GuestResult<Self::Item>> + Send + 'static;
+
+    /// Registry resource targeted by this call, if any. See [`Contract::resource`].
+    fn resource(&self, _input: &Self::Input) -> Option<ResourceId> {
+        None
+    }
+}
+
+/// Middleware invoked around every hostcall made through an [`Operation`].
+///
+/// Interceptors compose cross-cutting concerns such as rate limiting, metrics and audit
+/// logging without touching individual drivers. They run in registration order.
+pub trait HostcallInterceptor: Send + Sync {
+    /// Called before the driver runs. Returning an error rejects the call and resolves the
+    /// guest future with that error; later interceptors and the driver are skipped.
+    fn before(&self, _call: &HostcallInfo) -> GuestResult<()> {
+        Ok(())
+    }
+
+    /// Called once the call has completed (or been rejected) with its elapsed time and the
+    /// encoded result that will be handed to the guest.
+    fn after(&self, _call: &HostcallInfo, _elapsed: Duration, _result: &GuestResult<Vec<u8>>) {}
+}
+
+/// Per-instance context describing who is making hostcalls.
+///
+/// Attach this as an instance extension to have every capability-bound hostcall authorised
+/// against the calling session before its driver runs. Runtimes attach one to every process
+/// they start, through [`HostcallContext::for_process`].
+#[derive(Clone, Copy, Debug)]
+pub struct HostcallContext {
+    session: ResourceId,
+}
+
+/// Description of a single hostcall invocation, as seen by [`HostcallInterceptor`]s.
+#[derive(Clone, Debug)]
+pub struct HostcallInfo {
+    /// Wasm import module name of the hostcall.
+    pub module: &'static str,
+    /// Capability the hostcall requires, if it was declared from the hostcall catalogue.
+    pub capability: Option<Capability>,
+    /// Registry id of the calling session, if the instance carries a [`HostcallContext`].
+    pub session: Option<ResourceId>,
+    /// Size in bytes of the encoded input payload.
+    pub payload_len: usize,
+}
+
+/// How an operation acts on the capability checks and interceptor rejections it evaluates
+/// before running its driver.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Enforcement {
+    /// Calls that fail a check are refused.
+    #[default]
+    Enforce,
+    /// Calls that fail a check are logged as ones that would be refused, then run anyway, so
+    /// that a stricter policy can be rolled out before it is enforced.
+    Audit,
 }
 
 /// An asynchronous system task that a guest can execute in a non-blocking fashion.
 pub struct Operation<Driver> {
     driver: Driver,
+    dispatch: Dispatch,
+    idempotency: RwLock<Option<Arc<IdempotencyCache>>>,
+}
+
+/// A streaming system task whose guest-visible state yields successive items until exhausted.
+pub struct StreamOperation<Driver> {
+    driver: Driver,
+    dispatch: Dispatch,
+}
+
+/// Linking metadata and call admission shared by [`Operation`] and [`StreamOperation`].
+struct Dispatch {
     module: &'static str,
+    capability: Option<Capability>,
+    max_input: usize,
+    max_output: usize,
+    interceptors: RwLock<Arc<[Arc<dyn HostcallInterceptor>]>>,
+    /// Execution timeout for the driver in nanoseconds; zero disables it.
+    timeout_nanos: AtomicU64,
+    /// Whether failed checks are only logged; see [`Enforcement::Audit`].
+    audit_only: AtomicBool,
+    priority: RwLock<Option<Arc<PriorityClass>>>,
 }
 
 /// Trait object for operations that can be linked into a Wasmtime linker.
 pub trait LinkableOperation: Send + Sync {
-    fn link(&self, linker: &mut Linker<InstanceRegistry>) -> Result<(), KernelError>;
+    /// Link this operation's imports for a guest whose hostcall arguments carry addresses of
+    /// the given `width`.
+    fn link(
+        &self,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError>;
+
+    /// Wasm import module name this operation links under.
+    fn module(&self) -> &'static str;
+
+    /// Append an interceptor to this operation's middleware chain. Operations that never
+    /// dispatch to a driver (such as stubs) may ignore it.
+    fn intercept(&self, _interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        Ok(())
+    }
+
+    /// Bound how long the driver future may run before the call resolves with
+    /// [`GuestError::TimedOut`]. Operations that never dispatch to a driver may ignore it.
+    fn set_timeout(&self, _timeout: Option<Duration>) {}
+
+    /// Choose whether failed checks refuse calls or are only logged. Operations that never
+    /// dispatch to a driver may ignore it.
+    fn set_enforcement(&self, _enforcement: Enforcement) {}
+
+    /// Run this operation's driver tasks within the given priority class. Operations that never
+    /// dispatch to a driver may ignore it.
+    fn set_priority(&self, _class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        Ok(())
+    }
+
+    /// Replay results of calls retried under an idempotency key from the given cache.
+    /// Operations whose calls cannot be replayed (such as streams) may ignore it.
+    fn set_idempotency(&self, _cache: Arc<IdempotencyCache>) -> Result<(), KernelError> {
+        Ok(())
+    }
+
+    /// Start a call from an already-encoded input rather than from guest linear memory, for
+    /// instantiation paths that marshal arguments themselves. The result is delivered to the
+    /// returned state. Only future-returning operations support this.
+    fn invoke(
+        &self,
+        _registry: &mut InstanceRegistry,
+        _input: &[u8],
+    ) -> Result<CallState, KernelError> {
+        Err(KernelError::Driver(format!(
+            "{} cannot be invoked directly",
+            self.module()
+        )))
+    }
 }
 
 struct OperationLinker<Driver> {
     operation: Arc<Operation<Driver>>,
 }
 
+struct StreamOperationLinker<Driver> {
+    operation: Arc<StreamOperation<Driver>>,
+}
+
+/// A call's input, as handed to its driver.
+enum CallInput<T> {
+    Decoded(T),
+    Archived(ArchivedPayload<T>),
+}
+
 impl<Driver> LinkableOperation for OperationLinker<Driver>
 where
     Driver: Contract + Send + Sync + 'static,
@@ -53,8 +272,80 @@ where
         + rkyv::Deserialize<Driver::Output, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
 {
-    fn link(&self, linker: &mut Linker<InstanceRegistry>) -> Result<(), KernelError> {
-        self.operation.link(linker)
+    fn link(
+        &self,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError> {
+        self.operation.link(linker, width)
+    }
+
+    fn module(&self) -> &'static str {
+        self.operation.dispatch.module
+    }
+
+    fn intercept(&self, interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        self.operation.intercept(interceptor)
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.operation.set_timeout(timeout);
+    }
+
+    fn set_enforcement(&self, enforcement: Enforcement) {
+        self.operation.dispatch.set_enforcement(enforcement);
+    }
+
+    fn set_priority(&self, class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        self.operation.set_priority(class)
+    }
+
+    fn set_idempotency(&self, cache: Arc<IdempotencyCache>) -> Result<(), KernelError> {
+        self.operation.set_idempotency(cache)
+    }
+
+    fn invoke(
+        &self,
+        registry: &mut InstanceRegistry,
+        input: &[u8],
+    ) -> Result<CallState, KernelError> {
+        self.operation.invoke(registry, input)
+    }
+}
+
+impl<Driver> LinkableOperation for StreamOperationLinker<Driver>
+where
+    Driver: StreamContract + Send + Sync + 'static,
+    for<'a> <Driver::Input as rkyv::Archive>::Archived: 'a
+        + rkyv::Deserialize<Driver::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+{
+    fn link(
+        &self,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError> {
+        self.operation.link(linker, width)
+    }
+
+    fn module(&self) -> &'static str {
+        self.operation.dispatch.module
+    }
+
+    fn intercept(&self, interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        self.operation.dispatch.intercept(interceptor)
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        self.operation.dispatch.set_timeout(timeout);
+    }
+
+    fn set_enforcement(&self, enforcement: Enforcement) {
+        self.operation.dispatch.set_enforcement(enforcement);
+    }
+
+    fn set_priority(&self, class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        self.operation.dispatch.set_priority(class)
     }
 }
 
@@ -69,7 +360,11 @@ where
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
 {
     pub fn new(driver: Driver, module: &'static str) -> Arc<Self> {
-        Arc::new(Self { driver, module })
+        Arc::new(Self {
+            driver,
+            dispatch: Dispatch::new(module),
+            idempotency: RwLock::new(None),
+        })
     }
 
     /// Create an operation from a canonical hostcall descriptor.
@@ -77,162 +372,797 @@ where
         driver: Driver,
         hostcall: &'static Hostcall<Driver::Input, Driver::Output>,
     ) -> Arc<Self> {
-        Self::new(driver, hostcall.name())
+        Arc::new(Self {
+            driver,
+            dispatch: Dispatch::from_meta(hostcall.meta()),
+            idempotency: RwLock::new(None),
+        })
+    }
+
+    /// Append an interceptor to the middleware chain run around every call.
+    pub fn intercept(&self, interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        self.dispatch.intercept(interceptor)
+    }
+
+    /// Bound how long the driver future may run. `None` lets calls run to completion.
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        self.dispatch.set_timeout(timeout);
+    }
+
+    /// Choose whether failed checks refuse calls or are only logged.
+    pub fn set_enforcement(&self, enforcement: Enforcement) {
+        self.dispatch.set_enforcement(enforcement);
+    }
+
+    /// Currently configured execution timeout, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.dispatch.timeout()
+    }
+
+    /// Run driver tasks within the given priority class instead of on the ambient runtime.
+    pub fn set_priority(&self, class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        self.dispatch.set_priority(class)
+    }
+
+    /// Replay the results of calls retried under an idempotency key from `cache` instead of
+    /// running the driver again.
+    pub fn set_idempotency(&self, cache: Arc<IdempotencyCache>) -> Result<(), KernelError> {
+        let mut idempotency = self
+            .idempotency
+            .write()
+            .map_err(|_| KernelError::Driver("idempotency cache poisoned".to_string()))?;
+        *idempotency = Some(cache);
+        Ok(())
+    }
+
+    /// Claim the idempotency key attached to this call, if both a key and a cache are present.
+    /// Calls are scoped to the calling session, or to the calling process without one.
+    fn claim_idempotency(
+        &self,
+        registry: &InstanceRegistry,
+        call: &HostcallInfo,
+        state: &Arc<FutureSharedState<GuestResult<Vec<u8>>>>,
+    ) -> Result<Option<(Arc<IdempotencyCache>, CacheKey, Claim)>, KernelError> {
+        let Some(key) = PendingIdempotencyKey::take(registry) else {
+            return Ok(None);
+        };
+        let cache = self
+            .idempotency
+            .read()
+            .map_err(|_| KernelError::Driver("idempotency cache poisoned".to_string()))?
+            .clone();
+        let scope = call.session.or_else(|| {
+            registry
+                .extension::<ProcessIdentity>()
+                .map(|identity| identity.raw())
+        });
+        let (Some(cache), Some(scope)) = (cache, scope) else {
+            return Ok(None);
+        };
+        let key = CacheKey::new(scope, self.dispatch.module, key);
+        let claim = cache.claim(&key, state);
+        Ok(Some((cache, key, claim)))
     }
 }
 
-impl<Driver> Operation<Driver>
+impl<Driver> StreamOperation<Driver>
 where
-    Driver: Contract + Send + Sync + 'static,
+    Driver: StreamContract,
     for<'a> <Driver::Input as rkyv::Archive>::Archived: 'a
         + rkyv::Deserialize<Driver::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
-    for<'a> <Driver::Output as rkyv::Archive>::Archived: 'a
-        + rkyv::Deserialize<Driver::Output, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+    for<'a> <Option<Driver::Item> as rkyv::Archive>::Archived: 'a
+        + rkyv::Deserialize<
+            Option<Driver::Item>,
+            rkyv::api::high::HighDeserializer<rkyv::rancor::Error>,
+        >
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
 {
-    pub fn link(
-        self: &Arc<Self>,
+    /// Create a streaming operation from a canonical hostcall descriptor. The descriptor's
+    /// output is the per-poll wire type, `Option<Item>`.
+    pub fn from_hostcall(
+        driver: Driver,
+        hostcall: &'static Hostcall<Driver::Input, Option<Driver::Item>>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            driver,
+            dispatch: Dispatch::from_meta(hostcall.meta()),
+        })
+    }
+}
+
+impl<Driver> StreamOperation<Driver> {
+    /// Create a streaming operation linked under an arbitrary module name.
+    pub fn new(driver: Driver, module: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            driver,
+            dispatch: Dispatch::new(module),
+        })
+    }
+
+    /// Append an interceptor to the middleware chain run around every call.
+    pub fn intercept(&self, interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        self.dispatch.intercept(interceptor)
+    }
+
+    /// Bound how long the stream may wait for each item. `None` waits indefinitely.
+    pub fn set_timeout(&self, timeout: Option<Duration>) {
+        self.dispatch.set_timeout(timeout);
+    }
+
+    /// Choose whether failed checks refuse calls or are only logged.
+    pub fn set_enforcement(&self, enforcement: Enforcement) {
+        self.dispatch.set_enforcement(enforcement);
+    }
+
+    /// Run the stream's pump task within the given priority class.
+    pub fn set_priority(&self, class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        self.dispatch.set_priority(class)
+    }
+}
+
+impl Dispatch {
+    fn new(module: &'static str) -> Self {
+        Self {
+            module,
+            capability: None,
+            max_input: DEFAULT_MAX_PAYLOAD,
+            max_output: DEFAULT_MAX_PAYLOAD,
+            interceptors: RwLock::new(Arc::new([])),
+            timeout_nanos: AtomicU64::new(0),
+            audit_only: AtomicBool::new(false),
+            priority: RwLock::new(None),
+        }
+    }
+
+    fn from_meta(meta: HostcallMeta) -> Self {
+        Self {
+            capability: Some(meta.capability),
+            max_input: meta.max_input,
+            max_output: meta.max_output,
+            ..Self::new(meta.name)
+        }
+    }
+
+    /// Decode the guest's input, turning an oversized payload into a rejected call rather than a
+    /// trap. `Ok(Err(handle))` carries the guest handle of the rejected call.
+    fn read_input<T>(
+        &self,
+        caller: &mut Caller<'_, InstanceRegistry>,
+        ptr: GuestAddress,
+        len: GuestAddress,
+        encoding: PayloadEncoding,
+    ) -> Result<Result<T, GuestUint>, KernelError>
+    where
+        T: rkyv::Archive + JsonPayload + Sized,
+        for<'a> T::Archived: 'a
+            + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    {
+        let read = read_payload_value::<T>(caller, ptr, len, self.max_input, encoding);
+        self.screen_input(caller, read)
+    }
+
+    /// Turn an oversized payload read from the guest into a rejected call rather than a trap.
+    /// `Ok(Err(handle))` carries the guest handle of the rejected call.
+    fn screen_input<T>(
+        &self,
+        caller: &mut Caller<'_, InstanceRegistry>,
+        read: Result<T, KernelError>,
+    ) -> Result<Result<T, GuestUint>, KernelError> {
+        match read {
+            Ok(input) => Ok(Ok(input)),
+            Err(err @ KernelError::PayloadTooLarge { len, .. }) => {
+                let state = self.reject(caller.data(), len, GuestError::from(err))?;
+                let handle = caller.data_mut().insert_future(state)?;
+                Ok(Err(GuestUint::try_from(handle)?))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolve a call whose input was refused before reaching the driver.
+    fn reject(
+        &self,
+        registry: &InstanceRegistry,
+        payload_len: usize,
+        err: GuestError,
+    ) -> Result<CallState, KernelError> {
+        warn!(hostcall = self.module, %err, "hostcall input rejected");
+        let call = self.call_info(registry, payload_len);
+        let result = Err(err);
+        for interceptor in self.interceptors_for(registry)?.iter() {
+            interceptor.after(&call, Duration::ZERO, &result);
+        }
+        let state = FutureSharedState::for_hostcall(self.module);
+        state.resolve(result);
+        Ok(state)
+    }
+
+    fn intercept(&self, interceptor: Arc<dyn HostcallInterceptor>) -> Result<(), KernelError> {
+        let mut interceptors = self
+            .interceptors
+            .write()
+            .map_err(|_| KernelError::Driver("interceptor chain poisoned".to_string()))?;
+        let mut chain = interceptors.to_vec();
+        chain.push(interceptor);
+        *interceptors = chain.into();
+        Ok(())
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        let nanos = timeout
+            .map(|timeout| u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX).max(1))
+            .unwrap_or(0);
+        self.timeout_nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        match self.timeout_nanos.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    fn set_enforcement(&self, enforcement: Enforcement) {
+        self.audit_only
+            .store(enforcement == Enforcement::Audit, Ordering::Relaxed);
+    }
+
+    fn set_priority(&self, class: Arc<PriorityClass>) -> Result<(), KernelError> {
+        let mut priority = self
+            .priority
+            .write()
+            .map_err(|_| KernelError::Driver("priority class poisoned".to_string()))?;
+        *priority = Some(class);
+        Ok(())
+    }
+
+    /// Spawn a driver task in this operation's priority class, or on the ambient runtime, within
+    /// a `hostcall` span so that everything it logs carries the hostcall's name.
+    fn spawn<F>(&self, task: F) -> Result<JoinHandle<()>, KernelError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let task = task.instrument(debug_span!("hostcall", hostcall = self.module));
+        let priority = self
+            .priority
+            .read()
+            .map_err(|_| KernelError::Driver("priority class poisoned".to_string()))?;
+        Ok(match priority.as_ref() {
+            Some(class) => class.spawn(task),
+            None => tokio::spawn(task),
+        })
+    }
+
+    fn interceptors(&self) -> Result<Arc<[Arc<dyn HostcallInterceptor>]>, KernelError> {
+        self.interceptors
+            .read()
+            .map(|chain| Arc::clone(&chain))
+            .map_err(|_| KernelError::Driver("interceptor chain poisoned".to_string()))
+    }
+
+    /// Interceptor chain for a call made by `registry`, followed by the recorder for the
+    /// instance's [`HostcallHistory`] if it keeps one.
+    fn interceptors_for(
+        &self,
+        registry: &InstanceRegistry,
+    ) -> Result<Arc<[Arc<dyn HostcallInterceptor>]>, KernelError> {
+        let chain = self.interceptors()?;
+        Ok(match registry.extension::<HostcallHistory>() {
+            Some(history) => chain
+                .iter()
+                .cloned()
+                .chain([history.begin(self.module) as Arc<dyn HostcallInterceptor>])
+                .collect(),
+            None => chain,
+        })
+    }
+
+    /// Describe a call made by `registry`'s instance, counting it against the instance's
+    /// hostcalls issued.
+    fn call_info(&self, registry: &InstanceRegistry, payload_len: usize) -> HostcallInfo {
+        registry.record_hostcall();
+        HostcallInfo {
+            module: self.module,
+            capability: self.capability,
+            session: registry
+                .extension::<HostcallContext>()
+                .map(|context| context.session()),
+            payload_len,
+        }
+    }
+
+    /// Run the capability check and interceptor `before` hooks for a call.
+    fn admit(
+        &self,
+        registry: &InstanceRegistry,
+        call: &HostcallInfo,
+        resource: Option<ResourceId>,
+        interceptors: &[Arc<dyn HostcallInterceptor>],
+    ) -> GuestResult<()> {
+        let enforced = !self.audit_only.load(Ordering::Relaxed);
+        if let Some(capability) = self.capability
+            && let Err(err) = authorise_hostcall(registry, capability, resource)
+        {
+            registry
+                .registry()
+                .events()
+                .emit(KernelEvent::CapabilityDenied {
+                    hostcall: self.module,
+                    capability,
+                    session_id: call.session,
+                    process_id: registry.process(),
+                    enforced,
+                });
+            if enforced {
+                warn!(hostcall = self.module, ?capability, "hostcall denied");
+                return Err(err);
+            }
+            warn!(
+                hostcall = self.module,
+                ?capability,
+                "hostcall would be denied"
+            );
+        }
+
+        for interceptor in interceptors {
+            match interceptor.before(call) {
+                Err(err) if enforced => return Err(err),
+                Err(err) => warn!(hostcall = self.module, %err, "hostcall would be rejected"),
+                Ok(()) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Link the `create`/`poll`/`drop` imports for this module, with `create` supplied by the
+    /// concrete operation. Guest addresses and lengths are `width` bits wide on the wire and
+    /// widened before they reach the host.
+    fn link<F>(
+        &self,
         linker: &mut Linker<InstanceRegistry>,
-    ) -> Result<(), KernelError> {
-        let this = self.clone();
+        width: AddressWidth,
+        create: F,
+    ) -> Result<(), KernelError>
+    where
+        F: Fn(
+                Caller<'_, InstanceRegistry>,
+                GuestAddress,
+                GuestAddress,
+            ) -> Result<GuestUint, KernelError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        match width {
+            AddressWidth::Bits32 => self.link_with::<u32, F>(linker, create),
+            AddressWidth::Bits64 => self.link_with::<u64, F>(linker, create),
+        }
+    }
+
+    fn link_with<A, F>(
+        &self,
+        linker: &mut Linker<InstanceRegistry>,
+        create: F,
+    ) -> Result<(), KernelError>
+    where
+        A: WasmTy + Into<GuestAddress>,
+        F: Fn(
+                Caller<'_, InstanceRegistry>,
+                GuestAddress,
+                GuestAddress,
+            ) -> Result<GuestUint, KernelError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let module = self.module;
         linker.func_wrap(
-            self.module,
+            module,
             "create",
-            move |caller: Caller<'_, InstanceRegistry>, args_ptr: GuestInt, args_len: GuestUint| {
-                this.create(caller, args_ptr, args_len).map_err(Into::into)
+            move |caller: Caller<'_, InstanceRegistry>, args_ptr: A, args_len: A| {
+                create(caller, args_ptr.into(), args_len.into()).map_err(Into::into)
             },
         )?;
 
-        let this = self.clone();
         linker.func_wrap(
-            self.module,
+            module,
             "poll",
             move |caller: Caller<'_, InstanceRegistry>,
                   state_id: GuestUint,
                   task_id: GuestUint,
-                  result_ptr: GuestInt,
-                  result_capacity: GuestUint| {
-                this.poll(caller, state_id, task_id, result_ptr, result_capacity)
-                    .map_err(Into::into)
+                  result_ptr: A,
+                  result_capacity: A| {
+                poll_state(
+                    caller,
+                    module,
+                    state_id,
+                    task_id,
+                    result_ptr.into(),
+                    result_capacity.into(),
+                )
+                .map_err(Into::into)
             },
         )?;
 
-        let this = self.clone();
         linker.func_wrap(
-            self.module,
+            module,
             "drop",
             move |caller: Caller<'_, InstanceRegistry>,
                   state_id: GuestUint,
-                  result_ptr: GuestInt,
-                  result_capacity: GuestUint| {
-                this.drop(caller, state_id, result_ptr, result_capacity)
-                    .map_err(Into::into)
+                  result_ptr: A,
+                  result_capacity: A| {
+                drop_state(
+                    caller,
+                    module,
+                    state_id,
+                    result_ptr.into(),
+                    result_capacity.into(),
+                )
+                .map_err(Into::into)
             },
         )?;
 
         Ok(())
     }
+}
+
+impl HostcallContext {
+    /// Create a context for hostcalls made on behalf of the given session resource.
+    pub fn new(session: ResourceId) -> Self {
+        Self { session }
+    }
+
+    /// Create the context for hostcalls made by `process_id`. The process calls as the session
+    /// its spawner registered for it, or else as a new session entitled to exactly
+    /// `capabilities`. Either way the session is owned by the process and released with it.
+    pub fn for_process(
+        registry: &Registry,
+        process_id: ResourceId,
+        capabilities: CapabilitySet,
+    ) -> Result<Self, RegistryError> {
+        let spawned = registry.owned_resources(process_id).into_iter().find(|id| {
+            registry
+                .metadata(*id)
+                .is_some_and(|meta| meta.kind == ResourceType::Session)
+        });
+        if let Some(session) = spawned {
+            return Ok(Self::new(session));
+        }
+
+        let session = registry
+            .add(
+                Session::bootstrap(capabilities, [0; 32]),
+                Some(process_id),
+                ResourceType::Session,
+            )?
+            .into_id();
+        registry.events().emit(KernelEvent::SessionCreated {
+            session_id: session,
+            parent_id: None,
+            process_id: Some(process_id),
+        });
+        Ok(Self::new(session))
+    }
+
+    /// Registry id of the calling session.
+    pub fn session(&self) -> ResourceId {
+        self.session
+    }
+}
+
+impl<Driver> Operation<Driver>
+where
+    Driver: Contract + Send + Sync + 'static,
+    for<'a> <Driver::Input as rkyv::Archive>::Archived: 'a
+        + rkyv::Deserialize<Driver::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    for<'a> <Driver::Output as rkyv::Archive>::Archived: 'a
+        + rkyv::Deserialize<Driver::Output, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+{
+    pub fn link(
+        self: &Arc<Self>,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError> {
+        let this = self.clone();
+        self.dispatch.link(linker, width, move |caller, ptr, len| {
+            this.create(caller, ptr, len)
+        })
+    }
 
     fn create(
         self: &Arc<Self>,
         mut caller: Caller<'_, InstanceRegistry>,
-        ptr: GuestInt,
-        len: GuestUint,
+        ptr: GuestAddress,
+        len: GuestAddress,
     ) -> Result<GuestUint, KernelError> {
-        trace!("Creating future for {}", self.module);
+        trace!("Creating future for {}", self.dispatch.module);
 
-        let input = read_rkyv_value::<Driver::Input>(&mut caller, ptr, len)?;
-        let task = self.driver.to_future(&mut caller, input);
-        let state = FutureSharedState::new();
-        let shared = Arc::clone(&state);
-        tokio::spawn(async move {
-            let result = task.await.and_then(|out| {
-                encode_rkyv(&out)
-                    .map_err(|err| GuestError::Kernel(KernelError::Driver(err.to_string())))
-            });
-            shared.resolve(result);
-        });
-
-        let handle = caller.data_mut().insert_future(Arc::clone(&state))?;
+        let encoding = payload_encoding(caller.data());
+        let payload_len = usize::try_from(len)?;
+        let state = if Driver::ARCHIVED_INPUT && encoding == PayloadEncoding::Rkyv {
+            let read = read_archived_payload::<Driver::Input>(
+                &mut caller,
+                ptr,
+                len,
+                self.dispatch.max_input,
+            );
+            match self.dispatch.screen_input(&mut caller, read)? {
+                Ok(input) => self.start(
+                    caller.data_mut(),
+                    CallInput::Archived(input),
+                    payload_len,
+                    encoding,
+                )?,
+                Err(rejected) => return Ok(rejected),
+            }
+        } else {
+            match self
+                .dispatch
+                .read_input::<Driver::Input>(&mut caller, ptr, len, encoding)?
+            {
+                Ok(input) => self.start(
+                    caller.data_mut(),
+                    CallInput::Decoded(input),
+                    payload_len,
+                    encoding,
+                )?,
+                Err(rejected) => return Ok(rejected),
+            }
+        };
+        let handle = caller.data_mut().insert_future(state)?;
 
         GuestUint::try_from(handle).map_err(KernelError::IntConvert)
     }
 
-    fn poll(
+    /// Start a call from an rkyv-encoded input, applying the same size limit, authorisation and
+    /// interceptors as a call made from guest linear memory.
+    pub fn invoke(
         self: &Arc<Self>,
-        mut caller: Caller<'_, InstanceRegistry>,
-        state_id: GuestUint,
-        task_id: GuestUint,
-        ptr: GuestInt,
-        capacity: GuestUint,
-    ) -> Result<GuestUint, KernelError> {
-        trace!("Polling future for {}", self.module);
+        registry: &mut InstanceRegistry,
+        input: &[u8],
+    ) -> Result<CallState, KernelError> {
+        if input.len() > self.dispatch.max_input {
+            let err = KernelError::PayloadTooLarge {
+                len: input.len(),
+                max: self.dispatch.max_input,
+            };
+            return self.dispatch.reject(registry, input.len(), err.into());
+        }
 
-        let state_id = usize::try_from(state_id)?;
-        let task_id = usize::try_from(task_id)?;
+        if Driver::ARCHIVED_INPUT {
+            return match ArchivedPayload::<Driver::Input>::new(input) {
+                Ok(archived) => self.start(
+                    registry,
+                    CallInput::Archived(archived),
+                    input.len(),
+                    PayloadEncoding::Rkyv,
+                ),
+                Err(err) => self.dispatch.reject(registry, input.len(), err),
+            };
+        }
 
-        if let Some(base) = mailbox_base(&mut caller) {
-            caller.data().refresh_mailbox(base);
+        match decode_rkyv::<Driver::Input>(input) {
+            Ok(decoded) => self.start(
+                registry,
+                CallInput::Decoded(decoded),
+                input.len(),
+                PayloadEncoding::Rkyv,
+            ),
+            Err(_) => self
+                .dispatch
+                .reject(registry, input.len(), GuestError::InvalidArgument),
         }
+    }
 
-        let guest_result = {
-            let registry = caller.data_mut();
-            match registry.future_state(state_id) {
-                Some(state) => {
-                    let waker = registry.waker(task_id).ok_or_else(|| {
-                        KernelError::Driver("guest mailbox unavailable".to_string())
-                    })?;
-                    state.register_waker(waker);
+    /// Authorise the call against the calling session and interceptor chain, then spawn the
+    /// driver future and return its shared state.
+    fn start(
+        self: &Arc<Self>,
+        registry: &mut InstanceRegistry,
+        input: CallInput<Driver::Input>,
+        payload_len: usize,
+        encoding: PayloadEncoding,
+    ) -> Result<CallState, KernelError> {
+        let started = Instant::now();
+        let call = self.dispatch.call_info(registry, payload_len);
+        let interceptors = self.dispatch.interceptors_for(registry)?;
+        let state = FutureSharedState::for_hostcall(self.dispatch.module);
+        let resource = match &input {
+            CallInput::Decoded(input) => self.driver.resource(input),
+            CallInput::Archived(input) => self.driver.archived_resource(input.get()),
+        };
 
-                    match state.take_result() {
-                        None => Err(GuestError::WouldBlock),
-                        Some(output) => {
-                            registry.remove_future(state_id);
-                            output
-                        }
-                    }
+        let admitted = self
+            .dispatch
+            .admit(registry, &call, resource, &interceptors);
+        let idempotency = match admitted {
+            Ok(()) => self.claim_idempotency(registry, &call, &state)?,
+            Err(_) => None,
+        };
+
+        match (admitted, idempotency) {
+            (Ok(()), Some((_, _, Claim::Cached(output)))) => {
+                trace!(
+                    hostcall = self.dispatch.module,
+                    "replaying idempotent result"
+                );
+                let result = Ok(output);
+                for interceptor in interceptors.iter() {
+                    interceptor.after(&call, started.elapsed(), &result);
                 }
-                None => Err(GuestError::NotFound),
+                state.resolve(result);
             }
-        };
+            (Ok(()), Some((_, _, Claim::Joined))) => {
+                trace!(
+                    hostcall = self.dispatch.module,
+                    "joining in-flight idempotent call"
+                );
+            }
+            (Ok(()), idempotency) => {
+                let replay = idempotency.map(|(cache, key, _)| (cache, key));
+                let detached = replay.is_some();
+                let mut task = Box::pin(match input {
+                    CallInput::Decoded(input) => {
+                        Either::Left(self.driver.to_future(registry, input))
+                    }
+                    CallInput::Archived(input) => {
+                        Either::Right(self.driver.to_future_archived(registry, input))
+                    }
+                });
+                let timeout = self.dispatch.timeout();
+                let module = self.dispatch.module;
+                let max_output = self.dispatch.max_output;
+                let finish = move |output: GuestResult<Driver::Output>, state: &CallState| {
+                    let result = output.and_then(|out| encode_output(&out, max_output, encoding));
+                    for interceptor in interceptors.iter() {
+                        interceptor.after(&call, started.elapsed(), &result);
+                    }
+                    if let Some((cache, key)) = replay {
+                        cache.complete(&key, &result);
+                    }
+                    state.resolve(result);
+                };
 
-        let written = write_poll_result(
-            &mut caller,
-            ptr,
-            capacity,
-            guest_result.inspect_err(|e| {
-                if !matches!(e, GuestError::WouldBlock) {
-                    debug!("Future failed with error: {e}");
+                // Drivers that are ready straight away resolve here, sparing a task spawn and
+                // a wake-up before the guest's first poll finds the result.
+                if let Some(output) = (&mut task).now_or_never() {
+                    finish(output, &state);
+                } else {
+                    let shared = Arc::clone(&state);
+                    let driver_task = self.dispatch.spawn(async move {
+                        let output = match timeout {
+                            Some(timeout) => tokio::time::timeout(timeout, task)
+                                .await
+                                .unwrap_or_else(|_| {
+                                    warn!(hostcall = module, ?timeout, "hostcall timed out");
+                                    Err(GuestError::TimedOut)
+                                }),
+                            None => task.await,
+                        };
+                        finish(output, &shared);
+                    })?;
+                    // Idempotent calls run to completion even if the guest drops the future, so
+                    // a retry can pick up their result.
+                    if !detached {
+                        state.attach_task(driver_task.abort_handle());
+                    }
                 }
-            }),
-        )?;
-        Ok(written as GuestUint)
+            }
+            (Err(err), _) => {
+                let result = Err(err);
+                for interceptor in interceptors.iter() {
+                    interceptor.after(&call, started.elapsed(), &result);
+                }
+                state.resolve(result);
+            }
+        }
+
+        Ok(state)
     }
+}
 
-    fn drop(
+impl<Driver> StreamOperation<Driver>
+where
+    Driver: StreamContract + Send + Sync + 'static,
+    for<'a> <Driver::Input as rkyv::Archive>::Archived: 'a
+        + rkyv::Deserialize<Driver::Input, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+{
+    pub fn link(
+        self: &Arc<Self>,
+        linker: &mut Linker<InstanceRegistry>,
+        width: AddressWidth,
+    ) -> Result<(), KernelError> {
+        let this = self.clone();
+        self.dispatch.link(linker, width, move |caller, ptr, len| {
+            this.create(caller, ptr, len)
+        })
+    }
+
+    pub fn as_linkable(self: &Arc<Self>) -> Arc<dyn LinkableOperation> {
+        Arc::new(StreamOperationLinker {
+            operation: Arc::clone(self),
+        })
+    }
+
+    fn create(
         self: &Arc<Self>,
         mut caller: Caller<'_, InstanceRegistry>,
-        state_id: GuestUint,
-        ptr: GuestInt,
-        capacity: GuestUint,
+        ptr: GuestAddress,
+        len: GuestAddress,
     ) -> Result<GuestUint, KernelError> {
-        trace!("Dropping future for {}", self.module);
+        trace!("Creating stream for {}", self.dispatch.module);
 
-        let state_id = usize::try_from(state_id)?;
+        let encoding = payload_encoding(caller.data());
+        let input =
+            match self
+                .dispatch
+                .read_input::<Driver::Input>(&mut caller, ptr, len, encoding)?
+            {
+                Ok(input) => input,
+                Err(rejected) => return Ok(rejected),
+            };
+        let payload_len = usize::try_from(len)?;
+        self.create_with_input(caller, input, payload_len, encoding)
+    }
 
-        let guest_result = {
-            let registry = caller.data_mut();
-            if let Some(state) = registry.remove_future(state_id) {
-                state.abandon();
-                Ok(Vec::new())
-            } else {
-                Err(GuestError::NotFound)
+    /// Authorise the call, then spawn a task that pumps driver items into the shared state
+    /// until the stream ends, errors, or the guest drops it.
+    fn create_with_input(
+        self: &Arc<Self>,
+        mut caller: Caller<'_, InstanceRegistry>,
+        input: Driver::Input,
+        payload_len: usize,
+        encoding: PayloadEncoding,
+    ) -> Result<GuestUint, KernelError> {
+        let started = Instant::now();
+        let call = self.dispatch.call_info(caller.data(), payload_len);
+        let interceptors = self.dispatch.interceptors_for(caller.data())?;
+        let state = FutureSharedState::for_hostcall(self.dispatch.module);
+        let resource = self.driver.resource(&input);
+        if PendingIdempotencyKey::take(caller.data()).is_some() {
+            debug!(
+                hostcall = self.dispatch.module,
+                "streams ignore idempotency keys"
+            );
+        }
+
+        match self
+            .dispatch
+            .admit(caller.data(), &call, resource, &interceptors)
+        {
+            Ok(()) => {
+                let stream = self.driver.to_stream(caller.data_mut(), input);
+                let shared = Arc::clone(&state);
+                let timeout = self.dispatch.timeout();
+                let module = self.dispatch.module;
+                let max_output = self.dispatch.max_output;
+                let driver_task = self.dispatch.spawn(async move {
+                    let result =
+                        pump_stream(stream, &shared, timeout, module, max_output, encoding).await;
+                    for interceptor in interceptors.iter() {
+                        interceptor.after(&call, started.elapsed(), &result);
+                    }
+                    shared.resolve(result);
+                })?;
+                state.attach_task(driver_task.abort_handle());
             }
-        };
+            Err(err) => {
+                let result = Err(err);
+                for interceptor in interceptors.iter() {
+                    interceptor.after(&call, started.elapsed(), &result);
+                }
+                state.resolve(result);
+            }
+        }
+
+        let handle = caller.data_mut().insert_future(Arc::clone(&state))?;
 
-        let written = write_poll_result(&mut caller, ptr, capacity, guest_result)?;
-        Ok(written as GuestUint)
+        GuestUint::try_from(handle).map_err(KernelError::IntConvert)
     }
 }
 
@@ -253,9 +1183,437 @@ where
     }
 }
 
+/// Check that the instance's calling session holds `capability`, scoped to `resource` when the
+/// call targets one. Instances without a [`HostcallContext`] rely on link-time gating only.
+pub fn authorise_hostcall(
+    registry: &InstanceRegistry,
+    capability: Capability,
+    resource: Option<ResourceId>,
+) -> GuestResult<()> {
+    let Some(context) = registry.extension::<HostcallContext>() else {
+        return Ok(());
+    };
+
+    let permitted = registry
+        .registry()
+        .with(
+            ResourceHandle::<Session>::new(context.session()),
+            |session| match resource {
+                Some(resource) => session.authorise(capability, resource),
+                None => session.entitled(capability),
+            },
+        )
+        .unwrap_or(false);
+
+    if permitted {
+        Ok(())
+    } else {
+        Err(GuestError::PermissionDenied)
+    }
+}
+
+/// Forward stream items into `state` as encoded `Some(item)` values, returning the final
+/// result: an encoded `None` at end of stream, or the error that terminated it.
+async fn pump_stream<S, Item>(
+    stream: S,
+    state: &Arc<FutureSharedState<GuestResult<Vec<u8>>>>,
+    timeout: Option<Duration>,
+    module: &'static str,
+    max_output: usize,
+    encoding: PayloadEncoding,
+) -> GuestResult<Vec<u8>>
+where
+    S: Stream<Item = GuestResult<Item>>,
+    Item: RkyvEncode + JsonPayload,
+{
+    let mut stream = pin!(stream);
+    loop {
+        let next = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, stream.next())
+                .await
+                .map_err(|_| {
+                    warn!(hostcall = module, ?timeout, "stream item timed out");
+                    GuestError::TimedOut
+                })?,
+            None => stream.next().await,
+        };
+
+        match next {
+            Some(item) => {
+                let encoded = encode_output(&Some(item?), max_output, encoding)?;
+                if !state.push(Ok(encoded)) {
+                    // The guest dropped the stream; stop pulling from the driver.
+                    return Err(GuestError::NotFound);
+                }
+            }
+            None => return encode_output(&None::<Item>, max_output, encoding),
+        }
+    }
+}
+
+/// Poll a guest-visible state, removing it once its final result has been taken.
+fn poll_state(
+    mut caller: Caller<'_, InstanceRegistry>,
+    module: &'static str,
+    state_id: GuestUint,
+    task_id: GuestUint,
+    ptr: GuestAddress,
+    capacity: GuestAddress,
+) -> Result<GuestUint, KernelError> {
+    trace!("Polling future for {module}");
+
+    let state_id = usize::try_from(state_id)?;
+    let task_id = usize::try_from(task_id)?;
+    let limit = usize::try_from(capacity)?;
+    let mut chunked = false;
+
+    if let Some(base) = mailbox_base(&mut caller) {
+        caller.data().refresh_mailbox(base);
+    }
+
+    let guest_result = {
+        let registry = caller.data_mut();
+        match registry.future_state(state_id) {
+            Some(state) => {
+                let waker = registry
+                    .waker(task_id)
+                    .ok_or_else(|| KernelError::Driver("guest mailbox unavailable".to_string()))?;
+                state.register_waker(waker);
+
+                match state.take_result() {
+                    None => Err(GuestError::WouldBlock),
+                    // Hand over results too large for the guest's buffer a buffer at a time,
+                    // keeping the remainder queued for the guest's next poll.
+                    Some(Ok(mut bytes)) if limit > 0 && bytes.len() > limit => {
+                        let remainder = bytes.split_off(limit);
+                        state.requeue(Ok(remainder));
+                        chunked = true;
+                        Ok(bytes)
+                    }
+                    Some(output) => {
+                        if state.is_complete() {
+                            registry.remove_future(state_id);
+                        }
+                        output
+                    }
+                }
+            }
+            None => Err(GuestError::NotFound),
+        }
+    };
+
+    if chunked && let Ok(bytes) = &guest_result {
+        return write_poll_chunk(&mut caller, ptr, capacity, bytes);
+    }
+
+    let written = write_poll_result(
+        &mut caller,
+        ptr,
+        capacity,
+        guest_result.inspect_err(|e| {
+            if !matches!(e, GuestError::WouldBlock) {
+                debug!("Future failed with error: {e}");
+            }
+        }),
+    )?;
+    Ok(written as GuestUint)
+}
+
+/// Release a guest-visible state at the guest's request.
+fn drop_state(
+    mut caller: Caller<'_, InstanceRegistry>,
+    module: &'static str,
+    state_id: GuestUint,
+    ptr: GuestAddress,
+    capacity: GuestAddress,
+) -> Result<GuestUint, KernelError> {
+    trace!("Dropping future for {module}");
+
+    let state_id = usize::try_from(state_id)?;
+
+    let guest_result = {
+        let registry = caller.data_mut();
+        if let Some(state) = registry.remove_future(state_id) {
+            state.abandon();
+            Ok(Vec::new())
+        } else {
+            Err(GuestError::NotFound)
+        }
+    };
+
+    let written = write_poll_result(&mut caller, ptr, capacity, guest_result)?;
+    Ok(written as GuestUint)
+}
+
+/// Encode a driver output for the guest, rejecting encodings larger than `max_len` bytes.
+fn encode_output<T>(value: &T, max_len: usize, encoding: PayloadEncoding) -> GuestResult<Vec<u8>>
+where
+    T: RkyvEncode + JsonPayload,
+{
+    let bytes = encode_payload(value, encoding)
+        .map_err(|err| GuestError::Kernel(KernelError::Driver(err.to_string())))?;
+    if bytes.len() > max_len {
+        return Err(GuestError::Kernel(KernelError::PayloadTooLarge {
+            len: bytes.len(),
+            max: max_len,
+        }));
+    }
+    Ok(bytes)
+}
+
+/// Payload encoding the instance negotiated, or rkyv if it never did.
+fn payload_encoding(registry: &InstanceRegistry) -> PayloadEncoding {
+    registry
+        .extension::<PayloadEncoding>()
+        .map(|encoding| *encoding)
+        .unwrap_or_default()
+}
+
 fn mailbox_base(caller: &mut Caller<'_, InstanceRegistry>) -> Option<usize> {
     caller
         .get_export("memory")
         .and_then(|export| export.into_memory())
         .map(|memory| memory.data_ptr(&mut *caller) as usize)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::registry::{Registry, ResourceType};
+
+    struct NoopDriver;
+
+    impl Contract for NoopDriver {
+        type Input = ();
+        type Output = ();
+
+        fn to_future(
+            &self,
+            _instance: &mut InstanceRegistry,
+            _input: Self::Input,
+        ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+            std::future::ready(Ok(()))
+        }
+    }
+
+    /// Reports the length of its input, failing should the input ever be deserialised.
+    struct ArchivedLenDriver;
+
+    impl Contract for ArchivedLenDriver {
+        type Input = Vec<u8>;
+        type Output = u64;
+
+        fn to_future(
+            &self,
+            _instance: &mut InstanceRegistry,
+            _input: Self::Input,
+        ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+            std::future::ready(Err(GuestError::InvalidArgument))
+        }
+
+        const ARCHIVED_INPUT: bool = true;
+
+        fn to_future_archived(
+            &self,
+            _instance: &mut InstanceRegistry,
+            input: ArchivedPayload<Self::Input>,
+        ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+            std::future::ready(Ok(input.get().len() as u64))
+        }
+
+        fn archived_resource(&self, _input: &rkyv::Archived<Self::Input>) -> Option<ResourceId> {
+            None
+        }
+    }
+
+    struct Budget(AtomicUsize);
+
+    impl HostcallInterceptor for Budget {
+        fn before(&self, _call: &HostcallInfo) -> GuestResult<()> {
+            self.0
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .map(|_| ())
+                .map_err(|_| GuestError::WouldBlock)
+        }
+    }
+
+    #[test]
+    fn authorise_hostcall_checks_calling_session() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        assert!(authorise_hostcall(&instance, Capability::TimeRead, None).is_ok());
+
+        let session = Session::bootstrap(Capability::TimeRead.into(), [0; 32]);
+        let session = registry
+            .add(session, None, ResourceType::Session)
+            .expect("add session");
+        instance
+            .insert_extension(HostcallContext::new(session.into_id()))
+            .expect("attach context");
+
+        assert!(authorise_hostcall(&instance, Capability::TimeRead, None).is_ok());
+        assert!(authorise_hostcall(&instance, Capability::TimeRead, Some(7)).is_ok());
+        assert!(matches!(
+            authorise_hostcall(&instance, Capability::ProcessLifecycle, None),
+            Err(GuestError::PermissionDenied)
+        ));
+    }
+
+    #[test]
+    fn interceptors_can_reject_calls() {
+        let registry = Registry::new();
+        let instance = registry.instance().expect("instance registry");
+        let operation = Operation::new(NoopDriver, "test::noop");
+        operation
+            .intercept(Arc::new(Budget(AtomicUsize::new(1))))
+            .expect("add interceptor");
+
+        let call = HostcallInfo {
+            module: "test::noop",
+            capability: None,
+            session: None,
+            payload_len: 0,
+        };
+        let chain = operation
+            .dispatch
+            .interceptors()
+            .expect("interceptor chain");
+        assert!(
+            operation
+                .dispatch
+                .admit(&instance, &call, None, &chain)
+                .is_ok()
+        );
+        assert!(matches!(
+            operation.dispatch.admit(&instance, &call, None, &chain),
+            Err(GuestError::WouldBlock)
+        ));
+    }
+
+    #[test]
+    fn audited_checks_log_instead_of_refusing() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let session = Session::bootstrap(Capability::TimeRead.into(), [0; 32]);
+        let session = registry
+            .add(session, None, ResourceType::Session)
+            .expect("add session")
+            .into_id();
+        instance
+            .insert_extension(HostcallContext::new(session))
+            .expect("attach context");
+        let mut events = registry.events().subscribe();
+
+        let dispatch = Dispatch {
+            capability: Some(Capability::ProcessLifecycle),
+            ..Dispatch::new("test::noop")
+        };
+        dispatch
+            .intercept(Arc::new(Budget(AtomicUsize::new(0))))
+            .expect("add interceptor");
+        let chain = dispatch.interceptors().expect("interceptor chain");
+        let call = dispatch.call_info(&instance, 0);
+        assert!(matches!(
+            dispatch.admit(&instance, &call, None, &chain),
+            Err(GuestError::PermissionDenied)
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(KernelEvent::CapabilityDenied { enforced: true, .. })
+        ));
+
+        dispatch.set_enforcement(Enforcement::Audit);
+        assert!(dispatch.admit(&instance, &call, None, &chain).is_ok());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(KernelEvent::CapabilityDenied {
+                enforced: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn timeout_is_configurable() {
+        let operation = Operation::new(NoopDriver, "test::noop");
+        assert_eq!(operation.timeout(), None);
+
+        operation.set_timeout(Some(Duration::from_millis(250)));
+        assert_eq!(operation.timeout(), Some(Duration::from_millis(250)));
+
+        operation.set_timeout(None);
+        assert_eq!(operation.timeout(), None);
+    }
+
+    // Not a `tokio::test`: a spawned driver task would panic without a runtime.
+    #[test]
+    fn ready_drivers_resolve_without_a_task() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let operation = Operation::new(NoopDriver, "test::noop");
+        let input = selium_abi::encode_rkyv(&()).expect("encode input");
+
+        let state = operation.invoke(&mut instance, &input).expect("invoke");
+        assert!(matches!(state.take_result(), Some(Ok(_))));
+        assert!(state.is_complete());
+    }
+
+    #[test]
+    fn archived_drivers_read_input_without_deserialising() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let operation = Operation::new(ArchivedLenDriver, "test::archived");
+        let input = selium_abi::encode_rkyv(&vec![7u8; 300]).expect("encode input");
+
+        let state = operation.invoke(&mut instance, &input).expect("invoke");
+        let output = state.take_result().expect("result").expect("output");
+        assert_eq!(decode_rkyv::<u64>(&output).expect("decode output"), 300);
+
+        let state = operation.invoke(&mut instance, &[0xff; 3]).expect("invoke");
+        assert!(matches!(
+            state.take_result(),
+            Some(Err(GuestError::InvalidArgument))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_items_are_queued_until_exhausted() {
+        let state = FutureSharedState::new();
+        let items = futures_util::stream::iter([Ok(1u32), Ok(2u32)]);
+
+        let last = pump_stream(
+            items,
+            &state,
+            None,
+            "test::stream",
+            DEFAULT_MAX_PAYLOAD,
+            PayloadEncoding::Rkyv,
+        )
+        .await;
+        state.resolve(last);
+
+        let mut decoded = Vec::new();
+        while let Some(item) = state.take_result() {
+            let bytes = item.expect("stream item");
+            decoded.push(selium_abi::decode_rkyv::<Option<u32>>(&bytes).expect("decode item"));
+        }
+        assert_eq!(decoded, [Some(1), Some(2), None]);
+        assert!(state.is_complete());
+    }
+
+    #[test]
+    fn oversized_outputs_are_rejected() {
+        assert!(encode_output(&vec![0u8; 16], DEFAULT_MAX_PAYLOAD, PayloadEncoding::Rkyv).is_ok());
+        assert!(matches!(
+            encode_output(&vec![0u8; 16], 8, PayloadEncoding::Rkyv),
+            Err(GuestError::Kernel(KernelError::PayloadTooLarge {
+                max: 8,
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+//! On-demand CPU profiling of guest instances.
+//!
+//! Instances carrying a [`GuestProfiler`] extension can be sampled while they execute: once a
+//! profile is started, the runtime records the guest's call stack at every preemption point
+//! until the profile is finished. Samples are aggregated into folded stacks, the input format of
+//! flamegraph tools such as `inferno` and `flamegraph.pl`.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+
+/// Separator between frames of a folded stack.
+const FRAME_SEPARATOR: char = ';';
+
+/// Sampling state of a single instance.
+#[derive(Default)]
+pub struct GuestProfiler {
+    active: AtomicBool,
+    samples: Mutex<FoldedStacks>,
+}
+
+/// A profile being collected. Sampling stops when this is finished or dropped.
+pub struct ActiveProfile {
+    profiler: Arc<GuestProfiler>,
+}
+
+/// Sample counts keyed by call stack, outermost frame first.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FoldedStacks {
+    stacks: BTreeMap<String, u64>,
+}
+
+impl GuestProfiler {
+    /// Start collecting samples, or return `None` if a profile is already being collected.
+    pub fn start(self: &Arc<Self>) -> Option<ActiveProfile> {
+        let mut samples = self.samples.lock();
+        if self.active.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        *samples = FoldedStacks::default();
+        Some(ActiveProfile {
+            profiler: Arc::clone(self),
+        })
+    }
+
+    /// Whether a profile is being collected.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Record one sample of the stack `frames`, listed innermost frame first as in a backtrace.
+    /// Samples taken while no profile is being collected are dropped.
+    pub fn record<I>(&self, frames: I)
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        let mut samples = self.samples.lock();
+        if self.is_active() {
+            samples.add(frames.into_iter().rev());
+        }
+    }
+}
+
+impl ActiveProfile {
+    /// Stop sampling and return the samples collected since the profile started.
+    pub fn finish(self) -> FoldedStacks {
+        std::mem::take(&mut *self.profiler.samples.lock())
+    }
+}
+
+impl Drop for ActiveProfile {
+    fn drop(&mut self) {
+        self.profiler.active.store(false, Ordering::Release);
+    }
+}
+
+impl FoldedStacks {
+    /// Count one sample of the stack `frames`, listed outermost frame first.
+    pub fn add(&mut self, frames: impl IntoIterator<Item = String>) {
+        let stack = frames
+            .into_iter()
+            .map(|frame| frame.replace([FRAME_SEPARATOR, ' ', '\n'], "_"))
+            .collect::<Vec<_>>()
+            .join(&FRAME_SEPARATOR.to_string());
+        if !stack.is_empty() {
+            *self.stacks.entry(stack).or_default() += 1;
+        }
+    }
+
+    /// Total number of samples.
+    pub fn samples(&self) -> u64 {
+        self.stacks.values().sum()
+    }
+
+    /// Whether no samples were collected.
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+}
+
+impl fmt::Display for FoldedStacks {
+    /// One line per distinct stack: the frames separated by `;`, a space, and the sample count.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stack, count) in &self.stacks {
+            writeln!(f, "{stack} {count}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn samples_fold_outermost_first() {
+        let profiler = Arc::new(GuestProfiler::default());
+        profiler.record(frames(&["inner", "main"]));
+
+        let active = profiler.start().expect("start profile");
+        assert!(profiler.start().is_none());
+        profiler.record(frames(&["spin", "main"]));
+        profiler.record(frames(&["spin", "main"]));
+        profiler.record(frames(&["hash block", "main"]));
+        let profile = active.finish();
+
+        assert!(!profiler.is_active());
+        assert_eq!(profile.samples(), 3);
+        assert_eq!(profile.to_string(), "main;hash_block 1\nmain;spin 2\n");
+    }
+
+    #[test]
+    fn dropping_a_profile_stops_sampling() {
+        let profiler = Arc::new(GuestProfiler::default());
+        drop(profiler.start().expect("start profile"));
+        assert!(!profiler.is_active());
+        assert!(profiler.start().is_some());
+    }
+}
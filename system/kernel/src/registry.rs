@@ -1,10 +1,14 @@
 use futures_util::future::BoxFuture;
+use rkyv::{Archive, Deserialize, Serialize};
 use sharded_slab::Slab;
 use std::{
     any::{Any, TypeId},
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     marker::PhantomData,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, RwLock, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
     task::Waker,
 };
 use thiserror::Error;
@@ -16,20 +20,39 @@ use tracing::{
 use crate::{
     KernelError,
     drivers::Capability,
+    events::{EventBus, KernelEvent},
     futures::FutureSharedState,
     guest_data::GuestResult,
     mailbox::GuestMailbox,
     session::{Session, SessionError},
 };
-use selium_abi::{DependencyId, GuestResourceId};
-use wasmtime::{StoreLimits, StoreLimitsBuilder};
+use selium_abi::{
+    DependencyId, GuestResourceId, InstanceDiagnostics, ServiceHealth, SlotUsage, TrapReason,
+};
+use wasmtime::{ResourceLimiter, StoreLimits, StoreLimitsBuilder};
 
 /// Stable registry identifier for stored resources.
 pub type ResourceId = usize;
-type GuestFuture = Arc<FutureSharedState<GuestResult<Vec<u8>>>>;
+pub(crate) type GuestFuture = Arc<FutureSharedState<GuestResult<Vec<u8>>>>;
+/// Callback invoked after a resource has been removed from the registry.
+pub type RemoveHook = Arc<dyn Fn(ResourceMetadata) + Send + Sync>;
+type InstanceHandleShard = Arc<Mutex<InstanceHandles>>;
+
+const RESOURCE_TYPE_COUNT: usize = ResourceType::ALL.len();
+/// Number of low bits in a handle that address the slot index.
+const HANDLE_INDEX_BITS: u32 = 20;
+/// Number of bits in a handle carrying the slot generation; keeps handles within `i32`.
+const HANDLE_GENERATION_BITS: u32 = 11;
+const HANDLE_INDEX_MASK: usize = (1 << HANDLE_INDEX_BITS) - 1;
+const HANDLE_GENERATION_MASK: usize = (1 << HANDLE_GENERATION_BITS) - 1;
+/// Bound on owner links followed when resolving the process that owns a resource.
+const MAX_OWNER_DEPTH: usize = 16;
+/// Prefix of the tag naming the module a process was spawned from.
+pub const MODULE_TAG_PREFIX: &str = "module:";
 
 /// High-level classification of a resource stored in the registry.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
 pub enum ResourceType {
     /// Guest process resource.
     Process,
@@ -62,6 +85,84 @@ pub struct ResourceMetadata {
     pub kind: ResourceType,
 }
 
+/// Point-in-time summary of registry state, intended for diagnostics dumps.
+///
+/// Snapshots are rkyv-serialisable so they can be shipped to an admin client as-is.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct RegistrySnapshot {
+    /// Number of live resources per kind, in [`ResourceType::ALL`] order.
+    pub counts: Vec<ResourceCount>,
+    /// Per-resource detail, ordered by resource id.
+    pub resources: Vec<ResourceSnapshot>,
+    /// Registered singleton dependencies and their backing resources.
+    pub singletons: Vec<SingletonSnapshot>,
+    /// Services in the directory and their instances.
+    pub services: Vec<ServiceSnapshot>,
+}
+
+/// Live resource count for a single [`ResourceType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct ResourceCount {
+    /// Resource kind being counted.
+    pub kind: ResourceType,
+    /// Number of live resources of this kind.
+    pub count: u64,
+}
+
+/// Diagnostic view of a single registry entry.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct ResourceSnapshot {
+    /// Resource identifier.
+    pub id: u64,
+    /// Resource kind classification.
+    pub kind: ResourceType,
+    /// Owner resource identifier, if recorded.
+    pub owner: Option<u64>,
+    /// Parent resource identifier, if recorded.
+    pub parent: Option<u64>,
+    /// Shared guest handle, if the resource has been shared.
+    pub shared: Option<GuestResourceId>,
+    /// Tags attached to the resource.
+    pub tags: Vec<String>,
+}
+
+/// Diagnostic view of a singleton registration.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct SingletonSnapshot {
+    /// Singleton dependency identifier, as guests compute it.
+    pub id: DependencyId,
+    /// Name the identifier was computed from.
+    pub name: String,
+    /// Resource backing the singleton.
+    pub resource: u64,
+}
+
+/// Diagnostic view of a service in the directory.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct ServiceSnapshot {
+    /// Service identifier, as guests compute it.
+    pub id: DependencyId,
+    /// Name the identifier was computed from.
+    pub name: String,
+    /// Instances of the service, in registration order.
+    pub instances: Vec<ServiceInstanceSnapshot>,
+}
+
+/// Diagnostic view of one instance of a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct ServiceInstanceSnapshot {
+    /// Resource backing the instance.
+    pub resource: u64,
+    /// Health the instance last reported.
+    pub health: ServiceHealth,
+}
+
 /// Typed handle to a resource stored in the [`Registry`].
 #[derive(Clone)]
 pub struct ResourceHandle<T>(ResourceId, PhantomData<T>);
@@ -78,19 +179,45 @@ struct InstanceState {
     mailbox: Option<&'static GuestMailbox>,
     extensions: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
     limits: StoreLimits,
+    handles: InstanceHandleShard,
 }
 
+/// Slab of guest-visible handles with per-slot generation counters.
+///
+/// Handles encode `(generation << HANDLE_INDEX_BITS) | index`, so a handle that outlives its
+/// slot no longer resolves once the slot is reused.
 #[derive(Default)]
 struct HandleTable {
-    entries: Vec<Option<ResourceId>>,
+    entries: Vec<HandleSlot>,
     free: Vec<usize>,
 }
 
+#[derive(Default)]
+struct HandleSlot {
+    generation: usize,
+    resource: Option<ResourceId>,
+}
+
+#[derive(Default)]
 struct HandleIndex {
     shared: HandleTable,
     shared_reverse: HashMap<ResourceId, usize>,
-    instances: HashMap<ResourceId, HandleTable>,
-    futures: HashMap<ResourceId, HandleTable>,
+}
+
+/// Guest handle tables private to a single instance, locked independently of every other
+/// instance so that per-hostcall slot and future churn never contends across guests.
+#[derive(Default)]
+struct InstanceHandles {
+    slots: HandleTable,
+    futures: HandleTable,
+}
+
+/// A service in the directory: every instance registered under one name.
+struct ServiceEntry {
+    name: String,
+    instances: Vec<(ResourceId, ServiceHealth)>,
+    /// Round-robin position used to spread resolutions across the instances.
+    next: usize,
 }
 
 #[derive(Default)]
@@ -103,15 +230,30 @@ struct RelationIndex {
     process_to_instance: HashMap<ResourceId, ResourceId>,
     process_log_channel: HashMap<ResourceId, ResourceId>,
     log_channel_process: HashMap<ResourceId, ResourceId>,
+    process_traps: HashMap<ResourceId, TrapReason>,
+    dependency_namespace: Option<[u8; 32]>,
     singletons: HashMap<DependencyId, ResourceId>,
+    singleton_names: HashMap<DependencyId, String>,
     singleton_ids: HashMap<ResourceId, DependencyId>,
+    services: HashMap<DependencyId, ServiceEntry>,
+    service_ids: HashMap<ResourceId, DependencyId>,
+    successors: HashMap<ResourceId, ResourceId>,
+    tags_of: HashMap<ResourceId, Vec<String>>,
+    tagged: HashMap<String, Vec<ResourceId>>,
 }
 
 /// Registry of guest resources.
+///
+/// Resource payloads live in a lock-free slab. Bookkeeping is split so that hot paths only
+/// take narrowly-scoped locks: live resource ids are sharded by [`ResourceType`], and guest
+/// slot/future tables are held per instance.
 pub struct Registry {
     resources: Slab<Resource>,
+    live: [Mutex<BTreeSet<ResourceId>>; RESOURCE_TYPE_COUNT],
     relations: Mutex<RelationIndex>,
     handles: Mutex<HandleIndex>,
+    remove_hooks: RwLock<HashMap<ResourceType, Vec<RemoveHook>>>,
+    events: EventBus,
 }
 
 /// Registry view tied to a specific guest instance.
@@ -120,6 +262,12 @@ pub struct InstanceRegistry {
     registry: Arc<Registry>,
     /// Instance state resource identifier.
     instance_id: ResourceId,
+    /// Guest handle tables owned by this instance.
+    handles: InstanceHandleShard,
+    /// Hostcalls issued by this instance.
+    hostcalls: AtomicU64,
+    /// Whether growing the instance's linear memory has failed.
+    memory_exhausted: bool,
 }
 
 /// Cloneable view for registering instance-scoped resources from async contexts.
@@ -127,6 +275,7 @@ pub struct InstanceRegistry {
 pub struct InstanceRegistrar {
     registry: Arc<Registry>,
     instance_id: ResourceId,
+    handles: InstanceHandleShard,
 }
 
 /// Errors surfaced when interacting with the registry.
@@ -144,67 +293,130 @@ pub enum RegistryError {
     /// Instance state is missing from the registry.
     #[error("instance state missing")]
     MissingInstance,
+    /// A singleton dependency identifier is not the hash of the name it was registered with,
+    /// or is already bound to another name.
+    #[error("dependency identifier does not match its name")]
+    DependencyBinding,
+    /// A singleton dependency is already registered against another resource.
+    #[error("dependency already registered")]
+    DependencyTaken,
 }
 
 /// Stable identity associated with a running process instance.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ProcessIdentity(ResourceId);
 
+impl ResourceType {
+    /// Every resource classification, in shard order.
+    pub const ALL: [ResourceType; 9] = [
+        ResourceType::Process,
+        ResourceType::Instance,
+        ResourceType::Channel,
+        ResourceType::Reader,
+        ResourceType::Writer,
+        ResourceType::Session,
+        ResourceType::Network,
+        ResourceType::Future,
+        ResourceType::Other,
+    ];
+
+    fn shard(self) -> usize {
+        self as usize
+    }
+}
+
 impl InstanceState {
-    fn new() -> Self {
+    fn new(handles: InstanceHandleShard) -> Self {
         Self {
             process_id: None,
             mailbox: None,
             extensions: HashMap::new(),
             limits: StoreLimits::default(),
+            handles,
         }
     }
 }
 
 impl HandleTable {
-    fn allocate(&mut self, resource_id: ResourceId) -> usize {
-        if let Some(slot) = self.free.pop()
-            && let Some(entry) = self.entries.get_mut(slot)
-        {
-            *entry = Some(resource_id);
-            return slot;
-        }
+    fn allocate(&mut self, resource_id: ResourceId) -> Option<usize> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = self.entries.len();
+                if index > HANDLE_INDEX_MASK {
+                    return None;
+                }
+                self.entries.push(HandleSlot::default());
+                index
+            }
+        };
 
-        self.entries.push(Some(resource_id));
-        self.entries.len() - 1
+        let slot = self.entries.get_mut(index)?;
+        slot.resource = Some(resource_id);
+        Some(Self::encode(index, slot.generation))
     }
 
     fn resolve(&self, handle: usize) -> Option<ResourceId> {
-        self.entries.get(handle).and_then(|entry| *entry)
+        let (index, generation) = Self::decode(handle)?;
+        let slot = self.entries.get(index)?;
+        if slot.generation != generation {
+            return None;
+        }
+        slot.resource
     }
 
     fn remove(&mut self, handle: usize) -> Option<ResourceId> {
-        let entry = self.entries.get_mut(handle)?;
-        let resource_id = entry.take();
-        if resource_id.is_some() {
-            self.free.push(handle);
+        let (index, generation) = Self::decode(handle)?;
+        let slot = self.entries.get_mut(index)?;
+        if slot.generation != generation {
+            return None;
         }
-        resource_id
+        let resource_id = slot.resource.take()?;
+        slot.generation = slot.generation.wrapping_add(1) & HANDLE_GENERATION_MASK;
+        self.free.push(index);
+        Some(resource_id)
     }
-}
 
-impl HandleIndex {
-    fn new() -> Self {
-        Self {
-            shared: HandleTable::default(),
-            shared_reverse: HashMap::new(),
-            instances: HashMap::new(),
-            futures: HashMap::new(),
+    fn usage(&self) -> SlotUsage {
+        let allocated = self.entries.len();
+        SlotUsage {
+            live: u32::try_from(allocated - self.free.len()).unwrap_or(u32::MAX),
+            allocated: u32::try_from(allocated).unwrap_or(u32::MAX),
         }
     }
 
+    /// Live handles and the resources they refer to, in slot order.
+    fn live(&self) -> impl Iterator<Item = (usize, ResourceId)> + '_ {
+        self.entries.iter().enumerate().filter_map(|(index, slot)| {
+            slot.resource
+                .map(|resource| (Self::encode(index, slot.generation), resource))
+        })
+    }
+
+    fn encode(index: usize, generation: usize) -> usize {
+        (generation << HANDLE_INDEX_BITS) | index
+    }
+
+    fn decode(handle: usize) -> Option<(usize, usize)> {
+        let generation = handle >> HANDLE_INDEX_BITS;
+        if generation > HANDLE_GENERATION_MASK {
+            return None;
+        }
+        Some((handle & HANDLE_INDEX_MASK, generation))
+    }
+}
+
+impl HandleIndex {
     fn share_handle(&mut self, id: ResourceId) -> Result<GuestResourceId, RegistryError> {
         if let Some(existing) = self.shared_reverse.get(&id).copied() {
             return GuestResourceId::try_from(existing)
                 .map_err(|_| RegistryError::CapacityExhausted);
         }
 
-        let handle = self.shared.allocate(id);
+        let handle = self
+            .shared
+            .allocate(id)
+            .ok_or(RegistryError::CapacityExhausted)?;
         match GuestResourceId::try_from(handle) {
             Ok(guest) => {
                 self.shared_reverse.insert(id, handle);
@@ -232,49 +444,6 @@ impl HandleIndex {
             self.shared.remove(handle);
         }
     }
-
-    fn insert_instance(&mut self, instance_id: ResourceId, resource_id: ResourceId) -> usize {
-        self.instances
-            .entry(instance_id)
-            .or_default()
-            .allocate(resource_id)
-    }
-
-    fn resolve_instance(&self, instance_id: ResourceId, handle: usize) -> Option<ResourceId> {
-        self.instances
-            .get(&instance_id)
-            .and_then(|table| table.resolve(handle))
-    }
-
-    fn remove_instance(&mut self, instance_id: ResourceId, handle: usize) -> Option<ResourceId> {
-        self.instances
-            .get_mut(&instance_id)
-            .and_then(|table| table.remove(handle))
-    }
-
-    fn insert_future(&mut self, instance_id: ResourceId, resource_id: ResourceId) -> usize {
-        self.futures
-            .entry(instance_id)
-            .or_default()
-            .allocate(resource_id)
-    }
-
-    fn resolve_future(&self, instance_id: ResourceId, handle: usize) -> Option<ResourceId> {
-        self.futures
-            .get(&instance_id)
-            .and_then(|table| table.resolve(handle))
-    }
-
-    fn remove_future(&mut self, instance_id: ResourceId, handle: usize) -> Option<ResourceId> {
-        self.futures
-            .get_mut(&instance_id)
-            .and_then(|table| table.remove(handle))
-    }
-
-    fn remove_instance_tables(&mut self, instance_id: ResourceId) {
-        self.instances.remove(&instance_id);
-        self.futures.remove(&instance_id);
-    }
 }
 
 impl RelationIndex {
@@ -347,18 +516,224 @@ impl RelationIndex {
         self.process_log_channel.get(&process_id).copied()
     }
 
-    fn register_singleton(&mut self, id: DependencyId, resource: ResourceId) -> bool {
-        if self.singletons.contains_key(&id) || self.singleton_ids.contains_key(&resource) {
-            return false;
+    fn set_trap(&mut self, process_id: ResourceId, reason: TrapReason) {
+        self.process_traps.insert(process_id, reason);
+    }
+
+    fn trap(&self, process_id: ResourceId) -> Option<TrapReason> {
+        self.process_traps.get(&process_id).copied()
+    }
+
+    /// Key `id` is stored under: the identifier itself, or a keyed hash of it when the registry
+    /// has a dependency namespace.
+    fn namespaced(&self, id: DependencyId) -> DependencyId {
+        match &self.dependency_namespace {
+            Some(key) => DependencyId(*blake3::keyed_hash(key, &id.0).as_bytes()),
+            None => id,
+        }
+    }
+
+    fn register_singleton(
+        &mut self,
+        id: DependencyId,
+        name: &str,
+        resource: ResourceId,
+    ) -> Result<bool, RegistryError> {
+        if DependencyId::from_name(name) != id {
+            return Err(RegistryError::DependencyBinding);
+        }
+        let id = self.namespaced(id);
+        if self
+            .singleton_names
+            .get(&id)
+            .is_some_and(|bound| bound != name)
+        {
+            return Err(RegistryError::DependencyBinding);
+        }
+        if self.singleton_ids.contains_key(&resource) {
+            return Ok(false);
+        }
+
+        if let Some(&existing) = self.singletons.get(&id) {
+            let succeeds = self
+                .owning_process(existing)
+                .and_then(|owner| self.successors.get(&owner))
+                .is_some_and(|successor| self.owning_process(resource) == Some(*successor));
+            if !succeeds {
+                return Ok(false);
+            }
+            self.singleton_ids.remove(&existing);
         }
 
         self.singletons.insert(id, resource);
+        self.singleton_names.insert(id, name.to_string());
         self.singleton_ids.insert(resource, id);
-        true
+        Ok(true)
     }
 
     fn singleton(&self, id: DependencyId) -> Option<ResourceId> {
-        self.singletons.get(&id).copied()
+        self.singletons.get(&self.namespaced(id)).copied()
+    }
+
+    fn set_successor(&mut self, predecessor: ResourceId, successor: ResourceId) {
+        self.successors.insert(predecessor, successor);
+    }
+
+    /// Process that owns `id`, following the owner chain.
+    fn owning_process(&self, id: ResourceId) -> Option<ResourceId> {
+        let mut current = id;
+        for _ in 0..MAX_OWNER_DEPTH {
+            if self.process_to_instance.contains_key(&current) {
+                return Some(current);
+            }
+            current = *self.owner_of.get(&current)?;
+        }
+        None
+    }
+
+    fn singleton_providers(&self) -> Vec<ResourceId> {
+        let mut providers: Vec<_> = self
+            .singletons
+            .values()
+            .filter_map(|resource| self.owning_process(*resource))
+            .collect();
+        providers.sort_unstable();
+        providers.dedup();
+        providers
+    }
+
+    fn singletons(&self) -> Vec<SingletonSnapshot> {
+        let mut singletons: Vec<_> = self
+            .singletons
+            .iter()
+            .filter_map(|(id, resource)| {
+                let name = self.singleton_names.get(id)?;
+                Some(SingletonSnapshot {
+                    id: DependencyId::from_name(name),
+                    name: name.clone(),
+                    resource: *resource as u64,
+                })
+            })
+            .collect();
+        singletons.sort_by_key(|entry| entry.resource);
+        singletons
+    }
+
+    fn register_service(
+        &mut self,
+        id: DependencyId,
+        name: &str,
+        resource: ResourceId,
+    ) -> Result<bool, RegistryError> {
+        if DependencyId::from_name(name) != id {
+            return Err(RegistryError::DependencyBinding);
+        }
+        let id = self.namespaced(id);
+        if self
+            .services
+            .get(&id)
+            .is_some_and(|entry| entry.name != name)
+        {
+            return Err(RegistryError::DependencyBinding);
+        }
+        if self.service_ids.contains_key(&resource) {
+            return Ok(false);
+        }
+
+        self.services
+            .entry(id)
+            .or_insert_with(|| ServiceEntry {
+                name: name.to_string(),
+                instances: Vec::new(),
+                next: 0,
+            })
+            .instances
+            .push((resource, ServiceHealth::Healthy));
+        self.service_ids.insert(resource, id);
+        Ok(true)
+    }
+
+    fn set_service_health(
+        &mut self,
+        id: DependencyId,
+        resource: ResourceId,
+        health: ServiceHealth,
+    ) -> bool {
+        let id = self.namespaced(id);
+        let instance = self.services.get_mut(&id).and_then(|entry| {
+            entry
+                .instances
+                .iter_mut()
+                .find(|(instance, _)| *instance == resource)
+        });
+        match instance {
+            Some((_, current)) => {
+                *current = health;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pick an instance of the service, rotating through those in the healthiest state that
+    /// is still serving.
+    fn resolve_service(&mut self, id: DependencyId) -> Option<ResourceId> {
+        let id = self.namespaced(id);
+        let entry = self.services.get_mut(&id)?;
+        let health = ServiceHealth::ALL
+            .into_iter()
+            .filter(|health| health.is_serving())
+            .find(|health| entry.instances.iter().any(|(_, h)| h == health))?;
+        let mut candidates = entry.instances.iter().filter(|(_, h)| *h == health);
+        let count = candidates.clone().count();
+        let (resource, _) = candidates.nth(entry.next % count)?;
+        entry.next = entry.next.wrapping_add(1);
+        Some(*resource)
+    }
+
+    fn service_instances(&self, id: DependencyId) -> Vec<(ResourceId, ServiceHealth)> {
+        self.services
+            .get(&self.namespaced(id))
+            .map(|entry| entry.instances.clone())
+            .unwrap_or_default()
+    }
+
+    fn services(&self) -> Vec<ServiceSnapshot> {
+        let mut services: Vec<_> = self
+            .services
+            .values()
+            .map(|entry| ServiceSnapshot {
+                id: DependencyId::from_name(&entry.name),
+                name: entry.name.clone(),
+                instances: entry
+                    .instances
+                    .iter()
+                    .map(|(resource, health)| ServiceInstanceSnapshot {
+                        resource: *resource as u64,
+                        health: *health,
+                    })
+                    .collect(),
+            })
+            .collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        services
+    }
+
+    fn add_tag(&mut self, id: ResourceId, tag: String) {
+        let tags = self.tags_of.entry(id).or_default();
+        if tags.contains(&tag) {
+            return;
+        }
+        tags.push(tag.clone());
+        Self::push_unique(self.tagged.entry(tag).or_default(), id);
+    }
+
+    fn tags(&self, id: ResourceId) -> Vec<String> {
+        self.tags_of.get(&id).cloned().unwrap_or_default()
+    }
+
+    fn tagged(&self, tag: &str) -> Vec<ResourceId> {
+        self.tagged.get(tag).cloned().unwrap_or_default()
     }
 
     fn remove_resource(&mut self, id: ResourceId) {
@@ -386,8 +761,32 @@ impl RelationIndex {
             self.process_log_channel.remove(&process);
         }
 
+        self.process_traps.remove(&id);
+
         if let Some(singleton_id) = self.singleton_ids.remove(&id) {
             self.singletons.remove(&singleton_id);
+            self.singleton_names.remove(&singleton_id);
+        }
+
+        if let Some(service_id) = self.service_ids.remove(&id)
+            && let Some(entry) = self.services.get_mut(&service_id)
+        {
+            entry.instances.retain(|(instance, _)| *instance != id);
+            if entry.instances.is_empty() {
+                self.services.remove(&service_id);
+            }
+        }
+
+        self.successors.remove(&id);
+        self.successors.retain(|_, successor| *successor != id);
+
+        for tag in self.tags_of.remove(&id).unwrap_or_default() {
+            if let Some(list) = self.tagged.get_mut(&tag) {
+                list.retain(|entry| *entry != id);
+                if list.is_empty() {
+                    self.tagged.remove(&tag);
+                }
+            }
         }
     }
 
@@ -445,10 +844,25 @@ impl Registry {
 
     /// Create a new registry.
     pub fn new() -> Arc<Self> {
-        let registry = Arc::new(Self {
-            resources: Slab::new(),
-            relations: Mutex::new(RelationIndex::default()),
-            handles: Mutex::new(HandleIndex::new()),
+        let registry = Arc::new_cyclic(|registry: &Weak<Self>| {
+            let registry = Weak::clone(registry);
+            // A process's sessions are released with it.
+            let release_sessions: RemoveHook = Arc::new(move |process| {
+                if let Some(registry) = registry.upgrade() {
+                    registry.discard_owned(process.id, ResourceType::Session);
+                }
+            });
+            Self {
+                resources: Slab::new(),
+                live: std::array::from_fn(|_| Mutex::new(BTreeSet::new())),
+                relations: Mutex::new(RelationIndex::default()),
+                handles: Mutex::new(HandleIndex::default()),
+                remove_hooks: RwLock::new(HashMap::from([(
+                    ResourceType::Process,
+                    vec![release_sessions],
+                )])),
+                events: EventBus::default(),
+            }
         });
 
         // Reserve the first ID (id=0) for system use
@@ -463,10 +877,18 @@ impl Registry {
 
     /// Create an [`InstanceRegistry`] view tied to this registry.
     pub fn instance(self: &Arc<Self>) -> Result<InstanceRegistry, RegistryError> {
-        let instance = self.add(InstanceState::new(), None, ResourceType::Instance)?;
+        let handles = InstanceHandleShard::default();
+        let instance = self.add(
+            InstanceState::new(Arc::clone(&handles)),
+            None,
+            ResourceType::Instance,
+        )?;
         Ok(InstanceRegistry {
             registry: self.clone(),
             instance_id: instance.into_id(),
+            handles,
+            hostcalls: AtomicU64::new(0),
+            memory_exhausted: false,
         })
     }
 
@@ -486,6 +908,7 @@ impl Registry {
             .resources
             .insert(r)
             .ok_or(RegistryError::CapacityExhausted)?;
+        self.track(raw, kind)?;
         if let Some(owner) = owner {
             let mut relations = self
                 .relations
@@ -497,6 +920,25 @@ impl Registry {
         Ok(ResourceHandle(raw, PhantomData))
     }
 
+    /// Add a resource labelled with free-form tags and return its typed handle.
+    ///
+    /// Tags are intended for attribution in diagnostics (e.g. the deploying team or module)
+    /// and carry no authority. Duplicate tags are ignored.
+    pub fn add_with_tags<T: Send + 'static>(
+        &self,
+        resource: T,
+        owner: Option<ResourceId>,
+        kind: ResourceType,
+        tags: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<ResourceHandle<T>, RegistryError> {
+        let handle = self.add(resource, owner, kind)?;
+        if let Err(err) = self.tag(handle.0, tags) {
+            self.discard(handle.0);
+            return Err(err);
+        }
+        Ok(handle)
+    }
+
     /// Reserve a slot for a resource and return its identifier.
     pub fn reserve(
         &self,
@@ -513,6 +955,7 @@ impl Registry {
             .resources
             .insert(r)
             .ok_or(RegistryError::CapacityExhausted)?;
+        self.track(id, kind)?;
         if let Some(owner) = owner {
             let mut relations = self
                 .relations
@@ -541,24 +984,25 @@ impl Registry {
         }
 
         *guard = Some(Box::new(resource));
+        drop(guard);
         self.record_resource_initialised::<T>(id);
+        if entry.kind == ResourceType::Process {
+            self.events
+                .emit(KernelEvent::ProcessStarted { process_id: id });
+        }
         Ok(ResourceHandle(id, PhantomData))
     }
 
     /// Remove a resource from the registry, returning ownership.
     pub fn remove<T: 'static>(&self, id: ResourceHandle<T>) -> Option<T> {
-        self.record_resource_removed(id.0);
-        let kind = self.resources.get(id.0).map(|resource| resource.kind);
-        if let Ok(mut handles) = self.handles.lock() {
-            handles.remove_shared(id.0);
-            if matches!(kind, Some(ResourceType::Instance)) {
-                handles.remove_instance_tables(id.0);
-            }
-        }
-        if let Ok(mut relations) = self.relations.lock() {
-            relations.remove_resource(id.0);
+        let metadata = self.unlink(id.0);
+        let removed = self.resources.take(id.0);
+        if let Some(resource) = &removed
+            && let Some(metadata) = metadata
+        {
+            self.resource_removed(metadata, resource);
         }
-        self.resources.take(id.0).and_then(|resource| {
+        removed.and_then(|resource| {
             let data = Arc::try_unwrap(resource.data).ok()?;
             let boxed_opt = data.into_inner().ok()?;
             let boxed = boxed_opt?;
@@ -568,18 +1012,37 @@ impl Registry {
 
     /// Discard a resource entry without attempting to downcast its payload.
     pub fn discard(&self, id: ResourceId) -> bool {
-        self.record_resource_removed(id);
-        let kind = self.resources.get(id).map(|resource| resource.kind);
-        if let Ok(mut handles) = self.handles.lock() {
-            handles.remove_shared(id);
-            if matches!(kind, Some(ResourceType::Instance)) {
-                handles.remove_instance_tables(id);
-            }
-        }
-        if let Ok(mut relations) = self.relations.lock() {
-            relations.remove_resource(id);
+        let metadata = self.unlink(id);
+        let removed = self.resources.take(id);
+        if let Some(resource) = &removed
+            && let Some(metadata) = metadata
+        {
+            self.resource_removed(metadata, resource);
         }
-        self.resources.take(id).is_some()
+        removed.is_some()
+    }
+
+    /// Register a callback that runs after any resource of `kind` is removed.
+    ///
+    /// Hooks run on the thread performing the removal, after the resource has left the
+    /// registry and without any registry locks held, so they may safely call back into the
+    /// registry. Hooks must not block.
+    pub fn on_remove(
+        &self,
+        kind: ResourceType,
+        hook: impl Fn(ResourceMetadata) + Send + Sync + 'static,
+    ) -> Result<(), RegistryError> {
+        let mut hooks = self
+            .remove_hooks
+            .write()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        hooks.entry(kind).or_default().push(Arc::new(hook));
+        Ok(())
+    }
+
+    /// Bus on which the kernel publishes lifecycle events.
+    pub fn events(&self) -> &EventBus {
+        &self.events
     }
 
     /// Borrow a resource mutably by erased handle and run a closure with it.
@@ -665,6 +1128,137 @@ impl Registry {
         })
     }
 
+    /// Enumerate metadata for every live resource, ordered by resource id.
+    ///
+    /// The returned iterator is a point-in-time copy; resources added or removed after the
+    /// call are not reflected.
+    pub fn iter_metadata(&self) -> impl Iterator<Item = ResourceMetadata> {
+        self.collect_metadata(&ResourceType::ALL, |_, _| true)
+            .into_iter()
+    }
+
+    /// Enumerate metadata for every live resource of the given kind.
+    pub fn metadata_by_type(&self, kind: ResourceType) -> Vec<ResourceMetadata> {
+        self.collect_metadata(&[kind], |_, _| true)
+    }
+
+    /// Enumerate metadata for every live resource owned by the given resource id.
+    ///
+    /// Guest-created resources are owned by their process, so passing a process id returns
+    /// everything that process has registered.
+    pub fn metadata_by_owner(&self, owner: ResourceId) -> Vec<ResourceMetadata> {
+        self.collect_metadata(&ResourceType::ALL, |_, meta| meta.owner == Some(owner))
+    }
+
+    /// Enumerate metadata for every live resource owned by the process the given session
+    /// belongs to, directly or through other resources, leaving out the process and the session
+    /// themselves.
+    ///
+    /// Every process is started with a session of its own, so this lists what the guest acting
+    /// under that session has registered. Sessions that belong to no process own nothing.
+    pub fn metadata_by_session(&self, session: ResourceId) -> Vec<ResourceMetadata> {
+        let Some(process) = self
+            .metadata(session)
+            .filter(|meta| meta.kind == ResourceType::Session)
+            .and_then(|_| self.owning_process(session))
+        else {
+            return Vec::new();
+        };
+        self.collect_metadata(&ResourceType::ALL, |relations, meta| {
+            meta.id != process
+                && meta.id != session
+                && relations.owning_process(meta.id) == Some(process)
+        })
+    }
+
+    /// Attach tags to an existing resource.
+    pub fn tag(
+        &self,
+        id: ResourceId,
+        tags: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<(), RegistryError> {
+        if self.resources.get(id).is_none() {
+            return Err(RegistryError::InvalidReservation);
+        }
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        for tag in tags {
+            relations.add_tag(id, tag.into());
+        }
+        Ok(())
+    }
+
+    /// Return the tags attached to a resource, in insertion order.
+    pub fn tags(&self, id: ResourceId) -> Vec<String> {
+        self.relations
+            .lock()
+            .map(|relations| relations.tags(id))
+            .unwrap_or_default()
+    }
+
+    /// Enumerate metadata for every live resource carrying the given tag.
+    pub fn metadata_by_tag(&self, tag: &str) -> Vec<ResourceMetadata> {
+        let ids = self
+            .relations
+            .lock()
+            .map(|relations| relations.tagged(tag))
+            .unwrap_or_default();
+        let mut collected: Vec<_> = ids.into_iter().filter_map(|id| self.metadata(id)).collect();
+        collected.sort_by_key(|meta| meta.id);
+        collected
+    }
+
+    /// Capture a point-in-time summary of every live resource, share, singleton and service.
+    ///
+    /// The snapshot is assembled from independently locked indices, so it is consistent per
+    /// entry but not across the whole registry when resources churn concurrently.
+    pub fn snapshot(&self) -> RegistrySnapshot {
+        let metadata: Vec<_> = self.iter_metadata().collect();
+        let counts = ResourceType::ALL
+            .iter()
+            .map(|kind| ResourceCount {
+                kind: *kind,
+                count: metadata.iter().filter(|meta| meta.kind == *kind).count() as u64,
+            })
+            .collect();
+        let shared: HashMap<ResourceId, GuestResourceId> = self
+            .handles
+            .lock()
+            .map(|handles| {
+                metadata
+                    .iter()
+                    .filter_map(|meta| Some((meta.id, handles.shared_handle(meta.id)?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (resources, singletons, services) = match self.relations.lock() {
+            Ok(relations) => {
+                let resources = metadata
+                    .iter()
+                    .map(|meta| ResourceSnapshot {
+                        id: meta.id as u64,
+                        kind: meta.kind,
+                        owner: meta.owner.map(|owner| owner as u64),
+                        parent: relations.parent(meta.id).map(|parent| parent as u64),
+                        shared: shared.get(&meta.id).copied(),
+                        tags: relations.tags(meta.id),
+                    })
+                    .collect();
+                (resources, relations.singletons(), relations.services())
+            }
+            Err(_) => (Vec::new(), Vec::new(), Vec::new()),
+        };
+
+        RegistrySnapshot {
+            counts,
+            resources,
+            singletons,
+            services,
+        }
+    }
+
     /// Return the recorded owner for a resource.
     pub fn owner(&self, id: ResourceId) -> Option<ResourceId> {
         self.relations.lock().ok()?.owner(id)
@@ -701,6 +1295,63 @@ impl Registry {
         self.relations.lock().ok()?.process_instance(process_id)
     }
 
+    /// Borrow extension data attached to the instance running the given process.
+    pub fn process_extension<T: Any + Send + Sync>(
+        &self,
+        process_id: ResourceId,
+    ) -> Option<Arc<T>> {
+        let instance_id = self.relations.lock().ok()?.process_instance(process_id)?;
+        self.with(
+            ResourceHandle::<InstanceState>::new(instance_id),
+            |state: &mut InstanceState| {
+                state
+                    .extensions
+                    .get(&TypeId::of::<T>())
+                    .and_then(|boxed| Arc::clone(boxed).downcast::<T>().ok())
+            },
+        )
+        .flatten()
+    }
+
+    /// Pending futures, mailbox counters and slot usage of the instance running `process_id`, or
+    /// `None` if the process has no live instance.
+    pub fn process_diagnostics(&self, process_id: ResourceId) -> Option<InstanceDiagnostics> {
+        let instance_id = self.process_instance(process_id)?;
+        self.instance_diagnostics(instance_id)
+    }
+
+    fn instance_diagnostics(&self, instance_id: ResourceId) -> Option<InstanceDiagnostics> {
+        let (process_id, mailbox, handles) = self.with(
+            ResourceHandle::<InstanceState>::new(instance_id),
+            |state: &mut InstanceState| {
+                (state.process_id, state.mailbox, Arc::clone(&state.handles))
+            },
+        )?;
+        let (slots, future_slots, futures) = {
+            let handles = handles.lock().ok()?;
+            let futures: Vec<_> = handles.futures.live().collect();
+            (handles.slots.usage(), handles.futures.usage(), futures)
+        };
+        let futures = futures
+            .into_iter()
+            .filter_map(|(handle, resource_id)| {
+                let handle = u32::try_from(handle).ok()?;
+                self.with(
+                    ResourceHandle::<GuestFuture>::new(resource_id),
+                    |state: &mut GuestFuture| state.diagnostics(handle),
+                )
+            })
+            .collect();
+
+        Some(InstanceDiagnostics {
+            futures,
+            mailbox: mailbox.map(GuestMailbox::diagnostics),
+            slots,
+            future_slots,
+            tags: process_id.map(|id| self.tags(id)).unwrap_or_default(),
+        })
+    }
+
     /// Return the registered log channel resource for the process, if present.
     pub fn log_channel(&self, process_id: ResourceId) -> Option<ResourceId> {
         self.relations.lock().ok()?.log_channel(process_id)
@@ -712,19 +1363,76 @@ impl Registry {
         self.shared_handle(channel_id)
     }
 
-    /// Register a singleton dependency identifier against the supplied resource.
+    /// Record that `process_id` trapped for `reason`, replacing any reason recorded earlier.
+    /// The record lasts as long as the process resource.
+    pub fn record_trap(
+        &self,
+        process_id: ResourceId,
+        reason: TrapReason,
+    ) -> Result<(), RegistryError> {
+        if self.resources.get(process_id).is_none() {
+            return Err(RegistryError::InvalidReservation);
+        }
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        relations.set_trap(process_id, reason);
+        Ok(())
+    }
+
+    /// Return why the process trapped, if it has.
+    pub fn process_trap(&self, process_id: ResourceId) -> Option<TrapReason> {
+        self.relations.lock().ok()?.trap(process_id)
+    }
+
+    /// Keep singleton dependencies in the namespace derived from `salt`, so that identifiers
+    /// are stored under keys specific to this deployment. Call before any singleton is
+    /// registered; registrations made earlier become unreachable.
+    pub fn set_dependency_namespace(&self, salt: &[u8]) -> Result<(), RegistryError> {
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        relations.dependency_namespace =
+            Some(blake3::derive_key("selium dependency namespace", salt));
+        Ok(())
+    }
+
+    /// Register the singleton dependency `id`, computed from `name`, against the supplied
+    /// resource.
     ///
-    /// Returns `false` if the identifier or resource is already registered.
+    /// Returns `false` if the identifier or resource is already registered, and
+    /// [`RegistryError::DependencyBinding`] if `id` is not the identifier of `name`.
     pub fn register_singleton(
         &self,
         id: DependencyId,
+        name: &str,
         resource: ResourceId,
     ) -> Result<bool, RegistryError> {
         let mut relations = self
             .relations
             .lock()
             .map_err(|_| RegistryError::LockPoisoned)?;
-        Ok(relations.register_singleton(id, resource))
+        relations.register_singleton(id, name, resource)
+    }
+
+    /// Let the `successor` process take over singleton registrations held by resources of the
+    /// `predecessor` process.
+    ///
+    /// Lookups keep resolving to the predecessor's resources until the successor registers the
+    /// same identifiers, so a replacement can start alongside the process it replaces.
+    pub fn set_successor(
+        &self,
+        predecessor: ResourceId,
+        successor: ResourceId,
+    ) -> Result<(), RegistryError> {
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        relations.set_successor(predecessor, successor);
+        Ok(())
     }
 
     /// Resolve a singleton dependency identifier to its backing resource id.
@@ -732,6 +1440,153 @@ impl Registry {
         self.relations.lock().ok()?.singleton(id)
     }
 
+    /// Processes owning the resource behind at least one registered singleton, in id order.
+    pub fn singleton_providers(&self) -> Vec<ResourceId> {
+        self.relations
+            .lock()
+            .map(|relations| relations.singleton_providers())
+            .unwrap_or_default()
+    }
+
+    /// Process that owns `id`, following the owner chain, if any.
+    pub fn owning_process(&self, id: ResourceId) -> Option<ResourceId> {
+        self.relations.lock().ok()?.owning_process(id)
+    }
+
+    /// Add the supplied resource as an instance of the service `id`, computed from `name`.
+    /// New instances start out [`ServiceHealth::Healthy`].
+    ///
+    /// Returns `false` if the resource is already an instance of a service, and
+    /// [`RegistryError::DependencyBinding`] if `id` is not the identifier of `name`.
+    pub fn register_service(
+        &self,
+        id: DependencyId,
+        name: &str,
+        resource: ResourceId,
+    ) -> Result<bool, RegistryError> {
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        relations.register_service(id, name, resource)
+    }
+
+    /// Record the health of an instance of the service `id`, returning `false` if the resource
+    /// is not one of its instances.
+    pub fn set_service_health(
+        &self,
+        id: DependencyId,
+        resource: ResourceId,
+        health: ServiceHealth,
+    ) -> Result<bool, RegistryError> {
+        let mut relations = self
+            .relations
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+        Ok(relations.set_service_health(id, resource, health))
+    }
+
+    /// Resolve the service `id` to one of its instances.
+    ///
+    /// Healthy instances are preferred, then degraded ones; unhealthy instances are never
+    /// returned. Successive calls rotate through the instances in the preferred state.
+    pub fn resolve_service(&self, id: DependencyId) -> Option<ResourceId> {
+        self.relations.lock().ok()?.resolve_service(id)
+    }
+
+    /// Instances of the service `id` and their health, in registration order.
+    pub fn service_instances(&self, id: DependencyId) -> Vec<(ResourceId, ServiceHealth)> {
+        self.relations
+            .lock()
+            .map(|relations| relations.service_instances(id))
+            .unwrap_or_default()
+    }
+
+    /// Drop handle and relation bookkeeping for a resource that is about to be removed,
+    /// returning its metadata as it stood beforehand.
+    fn unlink(&self, id: ResourceId) -> Option<ResourceMetadata> {
+        self.record_resource_removed(id);
+        let metadata = self.metadata(id);
+        if let Some(meta) = metadata
+            && let Ok(mut live) = self.live[meta.kind.shard()].lock()
+        {
+            live.remove(&id);
+        }
+        if let Ok(mut handles) = self.handles.lock() {
+            handles.remove_shared(id);
+        }
+        if let Ok(mut relations) = self.relations.lock() {
+            relations.remove_resource(id);
+        }
+        metadata
+    }
+
+    /// Announce the removal of `resource`: a process that had started is reported stopped, and
+    /// the remove hooks for its kind run.
+    fn resource_removed(&self, metadata: ResourceMetadata, resource: &Resource) {
+        let initialised = resource.data.lock().is_ok_and(|data| data.is_some());
+        if metadata.kind == ResourceType::Process && initialised {
+            self.events.emit(KernelEvent::ProcessStopped {
+                process_id: metadata.id,
+            });
+        }
+        self.run_remove_hooks(metadata);
+    }
+
+    /// Discard the resources of `kind` that `owner` owned.
+    fn discard_owned(&self, owner: ResourceId, kind: ResourceType) {
+        for id in self.owned_resources(owner) {
+            if self.metadata(id).is_some_and(|meta| meta.kind == kind) {
+                self.discard(id);
+            }
+        }
+    }
+
+    fn run_remove_hooks(&self, metadata: ResourceMetadata) {
+        let hooks = match self.remove_hooks.read() {
+            Ok(hooks) => hooks.get(&metadata.kind).cloned().unwrap_or_default(),
+            Err(_) => return,
+        };
+        for hook in hooks {
+            hook(metadata);
+        }
+    }
+
+    fn track(&self, id: ResourceId, kind: ResourceType) -> Result<(), RegistryError> {
+        self.live[kind.shard()]
+            .lock()
+            .map_err(|_| RegistryError::LockPoisoned)?
+            .insert(id);
+        Ok(())
+    }
+
+    fn collect_metadata(
+        &self,
+        kinds: &[ResourceType],
+        filter: impl Fn(&RelationIndex, &ResourceMetadata) -> bool,
+    ) -> Vec<ResourceMetadata> {
+        let Ok(relations) = self.relations.lock() else {
+            return Vec::new();
+        };
+        let mut collected = Vec::new();
+        for kind in kinds {
+            let Ok(live) = self.live[kind.shard()].lock() else {
+                continue;
+            };
+            collected.extend(
+                live.iter()
+                    .map(|id| ResourceMetadata {
+                        id: *id,
+                        owner: relations.owner(*id),
+                        kind: *kind,
+                    })
+                    .filter(|meta| filter(&relations, meta)),
+            );
+        }
+        collected.sort_by_key(|meta| meta.id);
+        collected
+    }
+
     fn record_resource_added<T: 'static>(&self, id: ResourceId) {
         if let Some(resource) = self.resources.get(id) {
             resource.span.record("resource_id", field::display(id));
@@ -865,6 +1720,7 @@ impl InstanceRegistry {
         InstanceRegistrar {
             registry: self.registry.clone(),
             instance_id: self.instance_id,
+            handles: Arc::clone(&self.handles),
         }
     }
 
@@ -894,40 +1750,40 @@ impl InstanceRegistry {
 
     fn insert_instance_handle(&self, resource_id: ResourceId) -> Result<usize, RegistryError> {
         let mut handles = self
-            .registry
             .handles
             .lock()
             .map_err(|_| RegistryError::LockPoisoned)?;
-        Ok(handles.insert_instance(self.instance_id, resource_id))
+        handles
+            .slots
+            .allocate(resource_id)
+            .ok_or(RegistryError::CapacityExhausted)
     }
 
     fn remove_instance_handle(&self, handle: usize) -> Option<ResourceId> {
-        let mut handles = self.registry.handles.lock().ok()?;
-        handles.remove_instance(self.instance_id, handle)
+        self.handles.lock().ok()?.slots.remove(handle)
     }
 
     fn resolve_instance_handle(&self, handle: usize) -> Option<ResourceId> {
-        let handles = self.registry.handles.lock().ok()?;
-        handles.resolve_instance(self.instance_id, handle)
+        self.handles.lock().ok()?.slots.resolve(handle)
     }
 
     fn insert_future_handle(&self, resource_id: ResourceId) -> Result<usize, RegistryError> {
         let mut handles = self
-            .registry
             .handles
             .lock()
             .map_err(|_| RegistryError::LockPoisoned)?;
-        Ok(handles.insert_future(self.instance_id, resource_id))
+        handles
+            .futures
+            .allocate(resource_id)
+            .ok_or(RegistryError::CapacityExhausted)
     }
 
     fn resolve_future_handle(&self, handle: usize) -> Option<ResourceId> {
-        let handles = self.registry.handles.lock().ok()?;
-        handles.resolve_future(self.instance_id, handle)
+        self.handles.lock().ok()?.futures.resolve(handle)
     }
 
     fn remove_future_handle(&self, handle: usize) -> Option<ResourceId> {
-        let mut handles = self.registry.handles.lock().ok()?;
-        handles.remove_future(self.instance_id, handle)
+        self.handles.lock().ok()?.futures.remove(handle)
     }
 
     /// Insert a resource entry and return its slot index.
@@ -1015,6 +1871,46 @@ impl InstanceRegistry {
         self.with_instance_state(|state| state.mailbox).flatten()
     }
 
+    /// Pending futures, mailbox counters and slot usage of this instance.
+    pub fn diagnostics(&self) -> Option<InstanceDiagnostics> {
+        self.registry.instance_diagnostics(self.instance_id)
+    }
+
+    /// Count a hostcall issued by this instance.
+    pub(crate) fn record_hostcall(&self) {
+        self.hostcalls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Hostcalls issued by this instance so far.
+    pub fn hostcalls_issued(&self) -> u64 {
+        self.hostcalls.load(Ordering::Relaxed)
+    }
+
+    /// Whether growing the instance's linear memory has failed, with the runtime enforcing its
+    /// limits through this registry's [`ResourceLimiter`].
+    pub fn memory_exhausted(&self) -> bool {
+        self.memory_exhausted
+    }
+
+    /// Futures this instance holds whose final result has yet to be produced.
+    pub fn pending_futures(&self) -> usize {
+        let futures: Vec<_> = match self.handles.lock() {
+            Ok(handles) => handles.futures.live().map(|(_, id)| id).collect(),
+            Err(_) => Vec::new(),
+        };
+        futures
+            .into_iter()
+            .filter(|id| {
+                self.registry
+                    .with(
+                        ResourceHandle::<GuestFuture>::new(*id),
+                        |state: &mut GuestFuture| state.is_pending(),
+                    )
+                    .unwrap_or(false)
+            })
+            .count()
+    }
+
     /// Get a reference to the global registry.
     pub fn registry(&self) -> &Registry {
         &self.registry
@@ -1036,6 +1932,11 @@ impl InstanceRegistry {
             .set_instance_process(self.instance_id, process_id)
     }
 
+    /// Process this instance is bound to, if it has been bound.
+    pub fn process(&self) -> Option<ResourceId> {
+        self.process_id().ok().flatten()
+    }
+
     fn process_id(&self) -> Result<Option<ResourceId>, RegistryError> {
         self.with_instance_state(|state| state.process_id)
             .ok_or(RegistryError::MissingInstance)
@@ -1073,7 +1974,15 @@ impl InstanceRegistry {
         state: Arc<FutureSharedState<GuestResult<Vec<u8>>>>,
     ) -> Result<usize, RegistryError> {
         let owner = self.process_id()?;
-        let entry = self.registry.add(state, owner, ResourceType::Future)?;
+        // Futures inherit the module tag of the process creating them.
+        let tags = owner
+            .map(|process_id| self.registry.tags(process_id))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tag| tag.starts_with(MODULE_TAG_PREFIX));
+        let entry = self
+            .registry
+            .add_with_tags(state, owner, ResourceType::Future, tags)?;
         let handle = self.insert_future_handle(entry.0)?;
         Ok(handle)
     }
@@ -1093,6 +2002,32 @@ impl InstanceRegistry {
         self.registry
             .remove(ResourceHandle::<GuestFuture>::new(resource_id))
     }
+
+    /// Hand the future under `handle` over for another process to await, returning the shared
+    /// handle it is claimed with, or `None` if there is no such future. The handle is released
+    /// here, so the future is no longer abandoned when this instance drops it.
+    pub fn share_future(
+        &mut self,
+        handle: usize,
+    ) -> Result<Option<GuestResourceId>, RegistryError> {
+        let Some(resource_id) = self.resolve_future_handle(handle) else {
+            return Ok(None);
+        };
+        let shared = self.registry.share_handle(resource_id)?;
+        self.remove_future_handle(handle);
+        Ok(Some(shared))
+    }
+
+    /// Claim a future handed over with [`InstanceRegistry::share_future`]. The future leaves
+    /// the registry, so it is claimed at most once.
+    pub fn take_shared_future(&self, shared: GuestResourceId) -> Option<GuestFuture> {
+        let resource_id = self.registry.resolve_shared(shared)?;
+        if self.registry.metadata(resource_id)?.kind != ResourceType::Future {
+            return None;
+        }
+        self.registry
+            .remove(ResourceHandle::<GuestFuture>::new(resource_id))
+    }
 }
 
 impl InstanceRegistrar {
@@ -1107,16 +2042,17 @@ impl InstanceRegistrar {
 
     fn insert_instance_handle(&self, resource_id: ResourceId) -> Result<usize, RegistryError> {
         let mut handles = self
-            .registry
             .handles
             .lock()
             .map_err(|_| RegistryError::LockPoisoned)?;
-        Ok(handles.insert_instance(self.instance_id, resource_id))
+        handles
+            .slots
+            .allocate(resource_id)
+            .ok_or(RegistryError::CapacityExhausted)
     }
 
     fn resolve_instance_handle(&self, handle: usize) -> Option<ResourceId> {
-        let handles = self.registry.handles.lock().ok()?;
-        handles.resolve_instance(self.instance_id, handle)
+        self.handles.lock().ok()?.slots.resolve(handle)
     }
 
     /// Insert a resource entry and return its slot index.
@@ -1147,6 +2083,34 @@ impl InstanceRegistrar {
     }
 }
 
+/// Leaves growth to the engine's own limits, recording when growing linear memory fails so
+/// that a trap following it can be attributed to memory exhaustion.
+impl ResourceLimiter for InstanceRegistry {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
+    }
+
+    fn memory_grow_failed(&mut self, error: wasmtime::Error) -> wasmtime::Result<()> {
+        debug!(instance_id = self.instance_id, %error, "guest memory growth failed");
+        self.memory_exhausted = true;
+        Ok(())
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
+    }
+}
+
 impl Drop for InstanceRegistry {
     fn drop(&mut self) {
         if let Some(mb) = self.mailbox() {
@@ -1159,6 +2123,7 @@ impl Drop for InstanceRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use selium_abi::{CapabilitySet, FutureState};
     use std::sync::Arc;
 
     #[test]
@@ -1216,6 +2181,194 @@ mod tests {
         assert_eq!(registry.owner(instance_id), Some(process_id));
     }
 
+    #[test]
+    fn process_extensions_resolve_through_instance() {
+        let registry = Registry::new();
+        let process_id = registry
+            .add((), None, ResourceType::Process)
+            .expect("insert process")
+            .into_id();
+
+        let mut instance = registry.instance().expect("instance registry");
+        instance.set_process_id(process_id).expect("set process id");
+        instance.insert_extension(7u32).expect("insert extension");
+
+        assert_eq!(
+            registry.process_extension::<u32>(process_id).as_deref(),
+            Some(&7)
+        );
+        assert!(registry.process_extension::<u64>(process_id).is_none());
+    }
+
+    #[test]
+    fn process_lifecycle_is_published() {
+        let registry = Registry::new();
+        let mut events = registry.events().subscribe();
+
+        let abandoned = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        assert!(registry.discard(abandoned));
+        let process_id = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let handle = registry
+            .initialise(process_id, 5u32)
+            .expect("initialise process");
+        assert_eq!(registry.remove(handle), Some(5));
+
+        assert_eq!(
+            events.try_recv(),
+            Ok(KernelEvent::ProcessStarted { process_id })
+        );
+        assert_eq!(
+            events.try_recv(),
+            Ok(KernelEvent::ProcessStopped { process_id })
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn successors_take_over_singletons() {
+        let registry = Registry::new();
+        let mut processes = Vec::new();
+        let mut instances = Vec::new();
+        for _ in 0..3 {
+            let process_id = registry
+                .add((), None, ResourceType::Process)
+                .expect("insert process")
+                .into_id();
+            let mut instance = registry.instance().expect("instance registry");
+            instance.set_process_id(process_id).expect("set process id");
+            processes.push(process_id);
+            instances.push(instance);
+        }
+        let resources: Vec<_> = processes
+            .iter()
+            .map(|process| {
+                registry
+                    .add((), Some(*process), ResourceType::Other)
+                    .expect("insert resource")
+                    .into_id()
+            })
+            .collect();
+        let name = "tests.singleton";
+        let id = DependencyId::from_name(name);
+
+        assert!(
+            registry
+                .register_singleton(id, name, resources[0])
+                .expect("register")
+        );
+        assert_eq!(registry.singleton_providers(), [processes[0]]);
+        registry
+            .set_successor(processes[0], processes[1])
+            .expect("set successor");
+        assert!(
+            !registry
+                .register_singleton(id, name, resources[2])
+                .expect("register")
+        );
+        assert_eq!(registry.singleton(id), Some(resources[0]));
+
+        assert!(
+            registry
+                .register_singleton(id, name, resources[1])
+                .expect("register")
+        );
+        assert_eq!(registry.singleton(id), Some(resources[1]));
+        assert_eq!(registry.singleton_providers(), [processes[1]]);
+        registry.discard(resources[0]);
+        assert_eq!(registry.singleton(id), Some(resources[1]));
+    }
+
+    #[test]
+    fn singletons_bind_identifiers_to_names_within_a_namespace() {
+        let registry = Registry::new();
+        registry
+            .set_dependency_namespace(b"staging")
+            .expect("set namespace");
+        let resource = registry
+            .add((), None, ResourceType::Other)
+            .expect("insert resource")
+            .into_id();
+        let id = DependencyId::from_name("tests.singleton");
+
+        assert!(matches!(
+            registry.register_singleton(id, "tests.spoofed", resource),
+            Err(RegistryError::DependencyBinding)
+        ));
+        assert!(
+            registry
+                .register_singleton(id, "tests.singleton", resource)
+                .expect("register")
+        );
+        assert_eq!(registry.singleton(id), Some(resource));
+        assert_eq!(registry.snapshot().singletons[0].id, id);
+    }
+
+    #[test]
+    fn services_resolve_round_robin_by_health() {
+        let registry = Registry::new();
+        let resources: Vec<_> = (0..3)
+            .map(|_| {
+                registry
+                    .add((), None, ResourceType::Other)
+                    .expect("insert resource")
+                    .into_id()
+            })
+            .collect();
+        let name = "tests.service";
+        let id = DependencyId::from_name(name);
+
+        assert!(matches!(
+            registry.register_service(id, "tests.spoofed", resources[0]),
+            Err(RegistryError::DependencyBinding)
+        ));
+        for resource in &resources {
+            assert!(
+                registry
+                    .register_service(id, name, *resource)
+                    .expect("register")
+            );
+        }
+        assert!(
+            !registry
+                .register_service(id, name, resources[0])
+                .expect("register")
+        );
+
+        let resolved: Vec<_> = (0..3)
+            .filter_map(|_| registry.resolve_service(id))
+            .collect();
+        assert_eq!(resolved, resources);
+
+        registry
+            .set_service_health(id, resources[0], ServiceHealth::Unhealthy)
+            .expect("set health");
+        registry
+            .set_service_health(id, resources[1], ServiceHealth::Degraded)
+            .expect("set health");
+        assert_eq!(registry.resolve_service(id), Some(resources[2]));
+        assert_eq!(registry.resolve_service(id), Some(resources[2]));
+
+        registry.discard(resources[2]);
+        assert_eq!(registry.resolve_service(id), Some(resources[1]));
+        assert_eq!(
+            registry.service_instances(id),
+            [
+                (resources[0], ServiceHealth::Unhealthy),
+                (resources[1], ServiceHealth::Degraded)
+            ]
+        );
+        assert_eq!(registry.snapshot().services[0].instances.len(), 2);
+
+        registry.discard(resources[1]);
+        assert_eq!(registry.resolve_service(id), None);
+        registry.discard(resources[0]);
+        assert!(registry.snapshot().services.is_empty());
+    }
+
     #[test]
     fn parent_child_relation_roundtrip() {
         let registry = Registry::new();
@@ -1279,6 +2432,76 @@ mod tests {
         assert!(instance.future_state(handle).is_none());
     }
 
+    #[test]
+    fn diagnostics_report_future_progress_and_slot_usage() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        instance
+            .insert(1u32, None, ResourceType::Other)
+            .expect("insert resource");
+        let pending = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+        let resolved = FutureSharedState::<GuestResult<Vec<u8>>>::new();
+        instance.insert_future(pending).expect("insert future");
+        let handle = instance
+            .insert_future(Arc::clone(&resolved))
+            .expect("insert future");
+        resolved.resolve(Ok(Vec::new()));
+        instance.remove_future(handle).expect("remove future");
+        let handle = instance
+            .insert_future(Arc::clone(&resolved))
+            .expect("insert future");
+
+        let diagnostics = instance.diagnostics().expect("diagnostics");
+        assert_eq!(
+            diagnostics
+                .futures
+                .iter()
+                .map(|future| (future.handle as usize, future.state, future.queued))
+                .collect::<Vec<_>>(),
+            [
+                (0, FutureState::Pending, 0),
+                (handle, FutureState::Resolved, 1)
+            ]
+        );
+        assert_eq!(
+            diagnostics.slots,
+            SlotUsage {
+                live: 1,
+                allocated: 1
+            }
+        );
+        assert_eq!(
+            diagnostics.future_slots,
+            SlotUsage {
+                live: 2,
+                allocated: 2
+            }
+        );
+        assert!(diagnostics.mailbox.is_none());
+    }
+
+    #[test]
+    fn futures_and_diagnostics_carry_the_module_tag() {
+        let registry = Registry::new();
+        let process = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        registry
+            .tag(process, ["team:ingest", "module:echo.wasm"])
+            .expect("tag process");
+        let mut instance = registry.instance().expect("instance registry");
+        instance.set_process_id(process).expect("set process");
+
+        let handle = instance
+            .insert_future(FutureSharedState::<GuestResult<Vec<u8>>>::new())
+            .expect("insert future");
+        let future = instance.resolve_future_handle(handle).expect("future id");
+        assert_eq!(registry.tags(future), vec!["module:echo.wasm"]);
+
+        let diagnostics = instance.diagnostics().expect("diagnostics");
+        assert_eq!(diagnostics.tags, vec!["team:ingest", "module:echo.wasm"]);
+    }
+
     #[test]
     fn instance_handle_reuse() {
         let registry = Registry::new();
@@ -1295,7 +2518,24 @@ mod tests {
         let slot_c = instance
             .insert(3u32, None, ResourceType::Other)
             .expect("insert resource");
-        assert_eq!(slot_c, slot_b);
+        assert_ne!(slot_c, slot_b);
+        assert_eq!(slot_c & HANDLE_INDEX_MASK, slot_b & HANDLE_INDEX_MASK);
+    }
+
+    #[test]
+    fn stale_instance_handles_do_not_alias() {
+        let registry = Registry::new();
+        let mut instance = registry.instance().expect("instance registry");
+        let stale = instance
+            .insert(1u32, None, ResourceType::Other)
+            .expect("insert resource");
+        instance.remove::<u32>(stale).expect("remove resource");
+
+        let fresh = instance
+            .insert(2u32, None, ResourceType::Other)
+            .expect("insert resource");
+        assert!(instance.remove::<u32>(stale).is_none());
+        assert_eq!(instance.remove::<u32>(fresh), Some(2));
     }
 
     #[test]
@@ -1320,4 +2560,224 @@ mod tests {
         assert_eq!(value, 42);
         assert_eq!(registry.owner(resource_id), Some(process_id));
     }
+
+    #[test]
+    fn iter_metadata_filters_by_type_and_owner() {
+        let registry = Registry::new();
+        let process = registry
+            .add((), None, ResourceType::Process)
+            .expect("insert process")
+            .into_id();
+        let channel = registry
+            .add(1u32, Some(process), ResourceType::Channel)
+            .expect("insert channel")
+            .into_id();
+        let reserved = registry
+            .reserve(None, ResourceType::Future)
+            .expect("reserve future");
+
+        let all: Vec<_> = registry.iter_metadata().map(|meta| meta.id).collect();
+        assert_eq!(all, vec![process, channel, reserved]);
+
+        let channels = registry.metadata_by_type(ResourceType::Channel);
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].id, channel);
+        assert_eq!(channels[0].owner, Some(process));
+
+        let owned = registry.metadata_by_owner(process);
+        assert_eq!(owned.len(), 1);
+        assert_eq!(owned[0].id, channel);
+
+        registry.discard(channel);
+        assert!(registry.metadata_by_type(ResourceType::Channel).is_empty());
+        assert!(registry.metadata_by_owner(process).is_empty());
+    }
+
+    #[test]
+    fn metadata_by_session_lists_what_its_process_owns() {
+        let registry = Registry::new();
+        let process = registry
+            .add((), None, ResourceType::Process)
+            .expect("insert process")
+            .into_id();
+        let mut instance = registry.instance().expect("instance");
+        instance.set_process_id(process).expect("set process id");
+        let session = registry
+            .add(
+                Session::bootstrap(CapabilitySet::default(), [0; 32]),
+                Some(process),
+                ResourceType::Session,
+            )
+            .expect("insert session")
+            .into_id();
+        let channel = registry
+            .add(1u32, Some(process), ResourceType::Channel)
+            .expect("insert channel")
+            .into_id();
+        let reader = registry
+            .add(2u32, Some(channel), ResourceType::Reader)
+            .expect("insert reader")
+            .into_id();
+        let unowned = registry
+            .add(3u32, None, ResourceType::Channel)
+            .expect("insert unowned channel")
+            .into_id();
+        let detached = registry
+            .add(
+                Session::bootstrap(CapabilitySet::default(), [0; 32]),
+                None,
+                ResourceType::Session,
+            )
+            .expect("insert detached session")
+            .into_id();
+
+        let owned: Vec<_> = registry
+            .metadata_by_session(session)
+            .into_iter()
+            .map(|meta| meta.id)
+            .collect();
+        assert_eq!(owned, vec![instance.instance_id, channel, reader]);
+        assert!(!owned.contains(&unowned));
+
+        assert!(registry.metadata_by_session(detached).is_empty());
+        assert!(registry.metadata_by_session(channel).is_empty());
+    }
+
+    #[test]
+    fn remove_hooks_run_for_matching_kind() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let registry = Registry::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&calls);
+        registry
+            .on_remove(ResourceType::Channel, move |meta| {
+                assert_eq!(meta.kind, ResourceType::Channel);
+                seen.fetch_add(1, Ordering::SeqCst);
+            })
+            .expect("register hook");
+
+        let channel = registry
+            .add(1u32, None, ResourceType::Channel)
+            .expect("insert channel");
+        let other = registry
+            .add(2u32, None, ResourceType::Other)
+            .expect("insert other")
+            .into_id();
+
+        assert_eq!(registry.remove(channel), Some(1));
+        assert!(registry.discard(other));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Removing an already-removed resource must not fire the hook again.
+        assert!(!registry.discard(other));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn instance_handle_tables_are_independent() {
+        let registry = Registry::new();
+        let mut first = registry.instance().expect("instance registry");
+        let mut second = registry.instance().expect("instance registry");
+
+        let slot_a = first
+            .insert(1u32, None, ResourceType::Other)
+            .expect("insert resource");
+        let slot_b = second
+            .insert(2u32, None, ResourceType::Other)
+            .expect("insert resource");
+        assert_eq!(slot_a, slot_b);
+
+        assert_eq!(first.with(slot_a, |value: &mut u32| *value), Some(1));
+        assert_eq!(second.with(slot_b, |value: &mut u32| *value), Some(2));
+    }
+
+    #[test]
+    fn tags_are_queryable_and_cleared_on_remove() {
+        let registry = Registry::new();
+        let tagged = registry
+            .add_with_tags(
+                1u32,
+                None,
+                ResourceType::Other,
+                ["team:ingest", "module:echo"],
+            )
+            .expect("insert tagged")
+            .into_id();
+        let untagged = registry
+            .add(2u32, None, ResourceType::Other)
+            .expect("insert untagged")
+            .into_id();
+        registry
+            .tag(untagged, ["team:ingest", "team:ingest"])
+            .expect("tag resource");
+
+        assert_eq!(registry.tags(tagged), vec!["team:ingest", "module:echo"]);
+        assert_eq!(registry.tags(untagged), vec!["team:ingest"]);
+
+        let ids: Vec<_> = registry
+            .metadata_by_tag("team:ingest")
+            .into_iter()
+            .map(|meta| meta.id)
+            .collect();
+        assert_eq!(ids, vec![tagged, untagged]);
+
+        registry.discard(tagged);
+        assert!(registry.tags(tagged).is_empty());
+        assert!(registry.metadata_by_tag("module:echo").is_empty());
+        assert_eq!(registry.metadata_by_tag("team:ingest").len(), 1);
+    }
+
+    #[test]
+    fn snapshot_summarises_registry_state() {
+        let registry = Registry::new();
+        let process = registry
+            .add((), None, ResourceType::Process)
+            .expect("insert process")
+            .into_id();
+        let channel = registry
+            .add_with_tags(1u32, Some(process), ResourceType::Channel, ["team:ingest"])
+            .expect("insert channel")
+            .into_id();
+        let shared = registry.share_handle(channel).expect("share handle");
+        let dependency = DependencyId::from_name("tests.ingest");
+        assert!(
+            registry
+                .register_singleton(dependency, "tests.ingest", channel)
+                .expect("register singleton")
+        );
+
+        let snapshot = registry.snapshot();
+        let count = |kind| {
+            snapshot
+                .counts
+                .iter()
+                .find(|entry| entry.kind == kind)
+                .map(|entry| entry.count)
+        };
+        assert_eq!(count(ResourceType::Process), Some(1));
+        assert_eq!(count(ResourceType::Channel), Some(1));
+        assert_eq!(count(ResourceType::Future), Some(0));
+
+        let entry = snapshot
+            .resources
+            .iter()
+            .find(|entry| entry.id == channel as u64)
+            .expect("channel entry");
+        assert_eq!(entry.owner, Some(process as u64));
+        assert_eq!(entry.shared, Some(shared));
+        assert_eq!(entry.tags, vec!["team:ingest"]);
+        assert_eq!(
+            snapshot.singletons,
+            vec![SingletonSnapshot {
+                id: dependency,
+                name: "tests.ingest".to_string(),
+                resource: channel as u64,
+            }]
+        );
+
+        let bytes = selium_abi::encode_rkyv(&snapshot).expect("encode snapshot");
+        let decoded: RegistrySnapshot = selium_abi::decode_rkyv(&bytes).expect("decode snapshot");
+        assert_eq!(decoded, snapshot);
+    }
 }
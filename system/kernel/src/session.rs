@@ -3,6 +3,7 @@ use std::{
     sync::Arc,
 };
 
+use selium_abi::{CapabilitySet, ErrorCode};
 use thiserror::Error;
 use tracing::{debug, warn};
 use uuid::Uuid;
@@ -31,6 +32,7 @@ pub struct Session {
 /// None = "cannot use this capability on any resources",
 /// Some = "can only use this capability on the given resources",
 /// Any = "can use this capability on any resource"
+#[derive(Clone)]
 pub enum ResourceScope {
     None,
     Some(HashSet<ResourceId>),
@@ -43,7 +45,7 @@ pub enum SessionError {
     InvalidSignature,
     #[error("session not authorised to perform this action")]
     Unauthorised,
-    #[error("attempted to create a session with entitlements beyond its own")]
+    #[error("attempted to grant entitlements beyond the granting session's own")]
     EntitlementScope,
     #[error("attempted to revoke a resource from 'Any' scope")]
     RevokeOnAny,
@@ -56,7 +58,7 @@ impl Session {
     ///
     /// Note that we don't accept any entitlement resource restrictions as they won't yet
     /// exist. Best practice is to send every capability enabled in the current kernel.
-    pub fn bootstrap(entitlements: Vec<Capability>, pubkey: [u8; 32]) -> Self {
+    pub fn bootstrap(entitlements: CapabilitySet, pubkey: [u8; 32]) -> Self {
         let entitlements =
             HashMap::from_iter(entitlements.into_iter().map(|id| (id, ResourceScope::Any)));
 
@@ -100,6 +102,27 @@ impl Session {
         })
     }
 
+    /// Create a new session, linked to this one, entitled to each of `capabilities` on the
+    /// resources this session may use it for. Fails if this session lacks any of them.
+    pub fn derive(&self, capabilities: CapabilitySet, pubkey: [u8; 32]) -> Result<Self> {
+        let entitlements = capabilities
+            .into_iter()
+            .map(|cap| {
+                self.entitlements
+                    .get(&cap)
+                    .map(|scope| (cap, scope.clone()))
+                    .ok_or(SessionError::EntitlementScope)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            parent: self.id,
+            entitlements,
+            _pubkey: pubkey,
+        })
+    }
+
     /// Authenticate a payload against this session's public key. If successful, the
     /// payload is an authentic payload for this session and can be trusted. Otherwise
     /// this payload is counterfit, meaning either that one or both of session Id and
@@ -136,6 +159,15 @@ impl Session {
         success
     }
 
+    /// Check whether this session holds an entitlement for `capability` that grants access to
+    /// at least some resources.
+    pub fn entitled(&self, capability: Capability) -> bool {
+        matches!(
+            self.entitlements.get(&capability),
+            Some(ResourceScope::Any | ResourceScope::Some(_))
+        )
+    }
+
     fn upsert_entitlement(&mut self, entitlement: Capability) {
         self.entitlements
             .insert(entitlement, ResourceScope::Some(HashSet::new()));
@@ -191,7 +223,7 @@ impl Session {
 
 impl From<SessionError> for GuestError {
     fn from(value: SessionError) -> Self {
-        GuestError::Subsystem(value.to_string())
+        GuestError::Coded(ErrorCode::from(&value), value.to_string())
     }
 }
 
@@ -216,13 +248,13 @@ impl ResourceScope {
     }
 }
 
-impl From<SessionError> for i32 {
-    fn from(value: SessionError) -> Self {
+impl From<&SessionError> for ErrorCode {
+    fn from(value: &SessionError) -> Self {
         match value {
-            SessionError::InvalidSignature => -110,
-            SessionError::Unauthorised => -111,
-            SessionError::EntitlementScope => -112,
-            SessionError::RevokeOnAny => -113,
+            SessionError::InvalidSignature => ErrorCode::InvalidSignature,
+            SessionError::Unauthorised => ErrorCode::Unauthorised,
+            SessionError::EntitlementScope => ErrorCode::EntitlementScope,
+            SessionError::RevokeOnAny => ErrorCode::RevokeOnAny,
         }
     }
 }
@@ -0,0 +1,205 @@
+//! Support for driving hostcall operations from host-side tests.
+//!
+//! [`BlockingKernelClient`] calls an operation the way a guest would: the call is created and
+//! its future inserted into the instance's future table, polled until it resolves, and dropped
+//! if it is abandoned. Each step runs on a current-thread runtime owned by the client, so tests
+//! need neither a guest mailbox nor an async test harness. Enable the `test-support` feature to
+//! use it from other crates.
+
+use std::{future::Future, sync::Arc};
+
+use rkyv::{
+    Archive, Deserialize,
+    api::high::{HighDeserializer, HighValidator},
+    bytecheck::CheckBytes,
+    rancor::Error as RancorError,
+};
+use selium_abi::{RkyvEncode, decode_rkyv, encode_rkyv};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    KernelError,
+    guest_data::{GuestError, GuestResult},
+    operation::{Contract, LinkableOperation, Operation},
+    registry::{InstanceRegistry, Registry},
+};
+
+/// Calls hostcall operations to completion from synchronous host code.
+pub struct BlockingKernelClient {
+    runtime: Runtime,
+    registry: Arc<Registry>,
+    instance: InstanceRegistry,
+}
+
+impl BlockingKernelClient {
+    /// Create a client with a registry of its own.
+    pub fn new() -> Result<Self, KernelError> {
+        Self::with_registry(&Registry::new())
+    }
+
+    /// Create a client calling as a fresh instance of `registry`.
+    pub fn with_registry(registry: &Arc<Registry>) -> Result<Self, KernelError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| KernelError::Driver(format!("failed to build test runtime: {err}")))?;
+        Ok(Self {
+            runtime,
+            registry: Arc::clone(registry),
+            instance: registry.instance()?,
+        })
+    }
+
+    /// The registry the client's instance belongs to.
+    pub fn registry(&self) -> &Arc<Registry> {
+        &self.registry
+    }
+
+    /// The instance calls are made as, e.g. to attach a session or bind a process before
+    /// calling.
+    pub fn instance(&mut self) -> &mut InstanceRegistry {
+        &mut self.instance
+    }
+
+    /// Run `future` to completion on the client's runtime.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Call `operation` with `input`, wait for it to resolve and decode its output.
+    pub fn call<Driver>(
+        &mut self,
+        operation: &Arc<Operation<Driver>>,
+        input: &Driver::Input,
+    ) -> GuestResult<Driver::Output>
+    where
+        Driver: Contract + Send + Sync + 'static,
+        Driver::Input: RkyvEncode,
+        for<'a> <Driver::Input as Archive>::Archived: 'a
+            + Deserialize<Driver::Input, HighDeserializer<RancorError>>
+            + CheckBytes<HighValidator<'a, RancorError>>,
+        for<'a> <Driver::Output as Archive>::Archived: 'a
+            + Deserialize<Driver::Output, HighDeserializer<RancorError>>
+            + CheckBytes<HighValidator<'a, RancorError>>,
+    {
+        let input = encode_rkyv(input).map_err(|_| GuestError::InvalidArgument)?;
+        let handle = {
+            let _entered = self.runtime.enter();
+            let state = operation.invoke(&mut self.instance, &input)?;
+            self.instance.insert_future(state)?
+        };
+        let output = self.wait(handle)?;
+        decode_rkyv(&output).map_err(|err| GuestError::Subsystem(err.to_string()))
+    }
+
+    /// Call `operation` with rkyv-encoded `input` and wait for its encoded output.
+    pub fn call_raw(
+        &mut self,
+        operation: &dyn LinkableOperation,
+        input: &[u8],
+    ) -> GuestResult<Vec<u8>> {
+        let handle = self.start(operation, input)?;
+        self.wait(handle)
+    }
+
+    /// Start a call to `operation` with rkyv-encoded `input`, returning the handle of its
+    /// future in the instance's future table.
+    pub fn start(
+        &mut self,
+        operation: &dyn LinkableOperation,
+        input: &[u8],
+    ) -> Result<usize, KernelError> {
+        let _entered = self.runtime.enter();
+        let state = operation.invoke(&mut self.instance, input)?;
+        Ok(self.instance.insert_future(state)?)
+    }
+
+    /// Poll the future under `handle` until its result arrives, then remove it from the table.
+    ///
+    /// Fails with [`GuestError::NotFound`] if there is no such future, or if it was abandoned
+    /// before resolving.
+    pub fn wait(&mut self, handle: usize) -> GuestResult<Vec<u8>> {
+        let state = self
+            .instance
+            .future_state(handle)
+            .ok_or(GuestError::NotFound)?;
+        let result = self.runtime.block_on(Arc::clone(&state).next_result());
+        if result.is_none() || state.is_complete() {
+            self.instance.remove_future(handle);
+        }
+        result.unwrap_or(Err(GuestError::NotFound))
+    }
+
+    /// Drop the future under `handle` without waiting for it, aborting its driver task.
+    /// Returns whether there was such a future.
+    pub fn drop_call(&mut self, handle: usize) -> bool {
+        match self.instance.remove_future(handle) {
+            Some(state) => {
+                state.abandon();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Resolves with its input after sleeping for that many milliseconds.
+    struct SleepDriver;
+
+    impl Contract for SleepDriver {
+        type Input = u64;
+        type Output = u64;
+
+        #[allow(clippy::manual_async_fn)]
+        fn to_future(
+            &self,
+            _instance: &mut InstanceRegistry,
+            input: Self::Input,
+        ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
+            async move {
+                tokio::time::sleep(Duration::from_millis(input)).await;
+                Ok(input)
+            }
+        }
+    }
+
+    #[test]
+    fn calls_are_driven_to_completion() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let operation = Operation::new(SleepDriver, "test::sleep");
+
+        assert_eq!(client.call(&operation, &0).expect("ready call"), 0);
+        assert_eq!(client.call(&operation, &5).expect("pending call"), 5);
+
+        let linkable = operation.as_linkable();
+        let input = encode_rkyv(&1u64).expect("encode input");
+        let output = client
+            .call_raw(linkable.as_ref(), &input)
+            .expect("raw call");
+        assert_eq!(decode_rkyv::<u64>(&output).expect("decode output"), 1);
+        assert!(matches!(
+            client.call_raw(linkable.as_ref(), &[0xff; 3]),
+            Err(GuestError::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn dropped_calls_leave_the_future_table() {
+        let mut client = BlockingKernelClient::new().expect("client");
+        let operation = Operation::new(SleepDriver, "test::sleep");
+        let input = encode_rkyv(&60_000u64).expect("encode input");
+
+        let handle = client
+            .start(operation.as_linkable().as_ref(), &input)
+            .expect("start");
+        assert!(client.drop_call(handle));
+        assert!(!client.drop_call(handle));
+        assert!(matches!(client.wait(handle), Err(GuestError::NotFound)));
+    }
+}
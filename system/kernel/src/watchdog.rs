@@ -0,0 +1,179 @@
+//! Watchdog turning silent guest hangs into alerts.
+//!
+//! A [`Watchdog`] flags two kinds of trouble. Attached to operations as a
+//! [`HostcallInterceptor`], it warns about every hostcall that takes longer than its latency
+//! budget. Scanned periodically with [`Watchdog::scan`], it reports each guest future whose
+//! result is still outstanding after a threshold, and can abort the host task behind it so that
+//! the guest wakes with [`GuestError::TimedOut`] instead of waiting forever.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+use tracing::warn;
+
+use crate::{
+    guest_data::{GuestError, GuestResult},
+    operation::{HostcallInfo, HostcallInterceptor},
+    registry::{GuestFuture, Registry, ResourceHandle, ResourceId, ResourceType},
+};
+
+/// Thresholds a [`Watchdog`] applies.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WatchdogConfig {
+    /// How long a guest future may stay unresolved before it is reported; `None` disables the
+    /// check.
+    pub stuck_after: Option<Duration>,
+    /// How long a hostcall may take before it is reported; `None` disables the check.
+    pub hostcall_budget: Option<Duration>,
+    /// Whether the host tasks behind stuck futures are aborted once reported.
+    pub abort: bool,
+}
+
+/// Flags stuck guest futures and slow hostcalls. Clones share the futures already reported.
+#[derive(Clone, Debug, Default)]
+pub struct Watchdog {
+    config: WatchdogConfig,
+    reported: Arc<Mutex<HashSet<ResourceId>>>,
+}
+
+/// A guest future found unresolved past [`WatchdogConfig::stuck_after`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StuckFuture {
+    /// Registry id of the future.
+    pub future_id: ResourceId,
+    /// Process whose guest holds the future, if known.
+    pub process_id: Option<ResourceId>,
+    /// Hostcall that created the future, if known.
+    pub hostcall: Option<&'static str>,
+    /// How long the future has been unresolved.
+    pub age: Duration,
+    /// Whether the future's host task was aborted and the future resolved with
+    /// [`GuestError::TimedOut`].
+    pub aborted: bool,
+}
+
+impl Watchdog {
+    /// Create a watchdog applying `config`.
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config,
+            reported: Arc::default(),
+        }
+    }
+
+    /// Thresholds this watchdog applies.
+    pub fn config(&self) -> WatchdogConfig {
+        self.config
+    }
+
+    /// Check every guest future in `registry`, returning those newly found stuck. Each stuck
+    /// future is reported once, and aborted if the watchdog is configured to.
+    pub fn scan(&self, registry: &Registry) -> Vec<StuckFuture> {
+        let Some(stuck_after) = self.config.stuck_after else {
+            return Vec::new();
+        };
+
+        let futures = registry.metadata_by_type(ResourceType::Future);
+        let mut reported = self.reported.lock();
+        let live: HashSet<_> = futures.iter().map(|meta| meta.id).collect();
+        reported.retain(|id| live.contains(id));
+
+        let mut stuck = Vec::new();
+        for meta in futures {
+            if reported.contains(&meta.id) {
+                continue;
+            }
+            let Some(state) = registry.with(
+                ResourceHandle::<GuestFuture>::new(meta.id),
+                |state: &mut GuestFuture| Arc::clone(state),
+            ) else {
+                continue;
+            };
+            let age = state.age();
+            if age < stuck_after || !state.is_pending() {
+                continue;
+            }
+
+            if self.config.abort {
+                state.cancel(Err(GuestError::TimedOut));
+            }
+            reported.insert(meta.id);
+            stuck.push(StuckFuture {
+                future_id: meta.id,
+                process_id: meta.owner,
+                hostcall: state.hostcall(),
+                age,
+                aborted: self.config.abort,
+            });
+        }
+        stuck
+    }
+}
+
+impl HostcallInterceptor for Watchdog {
+    fn after(&self, call: &HostcallInfo, elapsed: Duration, _result: &GuestResult<Vec<u8>>) {
+        if let Some(budget) = self.config.hostcall_budget
+            && elapsed > budget
+        {
+            warn!(
+                hostcall = call.module,
+                session = call.session,
+                ?elapsed,
+                ?budget,
+                "hostcall exceeded its latency budget"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::futures::FutureSharedState;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn stuck_futures_are_reported_once_and_aborted() {
+        let registry = Registry::new();
+        let process = registry
+            .reserve(None, ResourceType::Process)
+            .expect("reserve process");
+        let stuck = FutureSharedState::<GuestResult<Vec<u8>>>::for_hostcall("selium::time::sleep");
+        let task = tokio::spawn(std::future::pending::<()>());
+        stuck.attach_task(task.abort_handle());
+        let resolved = FutureSharedState::<GuestResult<Vec<u8>>>::for_hostcall("selium::time::now");
+        resolved.resolve(Ok(Vec::new()));
+        let stuck_id = registry
+            .add(Arc::clone(&stuck), Some(process), ResourceType::Future)
+            .expect("add stuck future")
+            .into_id();
+        registry
+            .add(resolved, Some(process), ResourceType::Future)
+            .expect("add resolved future");
+
+        let patient = Watchdog::new(WatchdogConfig {
+            stuck_after: Some(Duration::from_secs(60)),
+            ..WatchdogConfig::default()
+        });
+        assert!(patient.scan(&registry).is_empty());
+
+        let watchdog = Watchdog::new(WatchdogConfig {
+            stuck_after: Some(Duration::ZERO),
+            hostcall_budget: None,
+            abort: true,
+        });
+        let found = watchdog.scan(&registry);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].future_id, stuck_id);
+        assert_eq!(found[0].process_id, Some(process));
+        assert_eq!(found[0].hostcall, Some("selium::time::sleep"));
+        assert!(found[0].aborted);
+        assert!(matches!(
+            stuck.take_result(),
+            Some(Err(GuestError::TimedOut))
+        ));
+        assert!(task.await.expect_err("task aborted").is_cancelled());
+
+        assert!(watchdog.scan(&registry).is_empty());
+    }
+}
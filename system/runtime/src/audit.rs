@@ -0,0 +1,413 @@
+//! Append-only audit log of the hostcalls guests make.
+//!
+//! Every hostcall is recorded as one JSON object per line: when it was created, the calling
+//! session, the capability and hostcall, the outcome and the size of the input payload. A call
+//! is recorded as `started` once it is admitted, so calls that never resolve still leave a
+//! trace, and again with its outcome once it resolves. The log is a file opened for appending,
+//! or a Unix domain socket that a collector is listening on. Records are queued and written by a
+//! dedicated thread, so a slow sink never stalls a guest. Should the queue fill up, further
+//! records are dropped, and the number dropped is written to the log once it drains.
+//!
+//! Host commands run through `selium::host::exec` are recorded in the same way, with the process
+//! that ran them, the allow-listed name and arguments, and the exit status or error, so the log
+//! shows what ran on the host and not merely that the hostcall was made.
+//!
+//! Denied calls are also logged as warnings, so they surface at the default log level whether or
+//! not anyone reads the audit log.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use selium_abi::{ErrorCode, HostExecOutput, hostcalls};
+use selium_kernel::{
+    guest_data::GuestResult,
+    operation::{HostcallInfo, HostcallInterceptor},
+    registry::ResourceId,
+};
+use serde::Serialize;
+use tracing::{debug, error, warn};
+
+/// Error codes classified as denials rather than failures.
+const DENIAL_CODES: [ErrorCode; 3] = [
+    ErrorCode::PermissionDenied,
+    ErrorCode::Unauthorised,
+    ErrorCode::EntitlementScope,
+];
+
+/// Records queued for the writer before further records are dropped.
+const QUEUE_CAPACITY: usize = 4096;
+
+/// Interceptor queueing a record of every call for the audit log writer.
+pub struct AuditLog {
+    records: SyncSender<AuditLine>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// A record queued for the writer.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum AuditLine {
+    Hostcall(AuditRecord),
+    HostCommand(HostCommandRecord),
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+struct AuditRecord {
+    /// When the call was created, in milliseconds since the Unix epoch.
+    timestamp_ms: u64,
+    session: Option<ResourceId>,
+    capability: Option<String>,
+    hostcall: &'static str,
+    outcome: Outcome,
+    /// Name of the error code a denied or failed call returned.
+    error: Option<String>,
+    payload_len: usize,
+}
+
+/// One line of the audit log recording a host command.
+#[derive(Debug, Serialize)]
+struct HostCommandRecord {
+    /// When the command was started or finished, in milliseconds since the Unix epoch.
+    timestamp_ms: u64,
+    process: Option<ResourceId>,
+    hostcall: &'static str,
+    /// Name the command is allow-listed under.
+    command: String,
+    args: Vec<String>,
+    outcome: Outcome,
+    /// Exit status of a command that ran, or `None` if it was killed by a signal.
+    status: Option<i32>,
+    stdout_len: Option<usize>,
+    /// Name of the error code a denied or failed command returned.
+    error: Option<String>,
+}
+
+/// Line written in place of records dropped while the queue was full.
+#[derive(Debug, Serialize)]
+struct DroppedRecords {
+    timestamp_ms: u64,
+    dropped: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Started,
+    Ok,
+    Denied,
+    Error,
+}
+
+impl AuditLog {
+    /// Append records to the file at `path`, creating it if needed, or stream them to the Unix
+    /// domain socket at `path` if there is one.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::spawn(open_sink(path)?, path, QUEUE_CAPACITY)
+    }
+
+    /// Queue up to `capacity` records for a thread writing them to `sink`, which was opened at
+    /// `path`.
+    fn spawn(sink: Box<dyn Write + Send>, path: &Path, capacity: usize) -> Result<Self> {
+        let (records, queue) = mpsc::sync_channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let writer_dropped = Arc::clone(&dropped);
+        let path = path.to_path_buf();
+        thread::Builder::new()
+            .name("selium-audit".to_string())
+            .spawn(move || write_records(sink, queue, &writer_dropped, &path))
+            .context("spawn audit log writer")?;
+        Ok(Self { records, dropped })
+    }
+
+    fn record(
+        &self,
+        call: &HostcallInfo,
+        elapsed: Duration,
+        outcome: Outcome,
+        code: Option<ErrorCode>,
+    ) {
+        let record = AuditRecord {
+            timestamp_ms: unix_millis(SystemTime::now().checked_sub(elapsed)),
+            session: call.session,
+            capability: call.capability.map(|capability| capability.to_string()),
+            hostcall: call.module,
+            outcome,
+            error: code.map(|code| format!("{code:?}")),
+            payload_len: call.payload_len,
+        };
+
+        if outcome == Outcome::Denied {
+            warn!(
+                hostcall = record.hostcall,
+                session = record.session,
+                capability = record.capability,
+                error = record.error,
+                "audit: hostcall denied"
+            );
+        }
+        self.queue(AuditLine::Hostcall(record));
+    }
+
+    /// Record that `process` asked to run the host command allow-listed as `command` with
+    /// `args`, and how it went once `result` is known; `None` records that it started.
+    pub(crate) fn record_host_command(
+        &self,
+        process: Option<ResourceId>,
+        command: &str,
+        args: &[String],
+        result: Option<&GuestResult<HostExecOutput>>,
+    ) {
+        let (outcome, output, code) = match result {
+            None => (Outcome::Started, None, None),
+            Some(Ok(output)) => (Outcome::Ok, Some(output), None),
+            Some(Err(err)) => (Outcome::of(Some(err.code())), None, Some(err.code())),
+        };
+        self.queue(AuditLine::HostCommand(HostCommandRecord {
+            timestamp_ms: unix_millis(Some(SystemTime::now())),
+            process,
+            hostcall: hostcalls::HOST_EXEC.name(),
+            command: command.to_string(),
+            args: args.to_vec(),
+            outcome,
+            status: output.and_then(|output| output.status),
+            stdout_len: output.map(|output| output.stdout.len()),
+            error: code.map(|code| format!("{code:?}")),
+        }));
+    }
+
+    /// Hand `line` to the writer, counting it as dropped if the queue is full.
+    fn queue(&self, line: AuditLine) {
+        match self.records.try_send(line) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                debug!("audit log writer has stopped");
+            }
+        }
+    }
+}
+
+impl Outcome {
+    /// Outcome of a call that returned the error `code`, if any.
+    fn of(code: Option<ErrorCode>) -> Self {
+        match code {
+            None => Self::Ok,
+            Some(code) if DENIAL_CODES.contains(&code) => Self::Denied,
+            Some(_) => Self::Error,
+        }
+    }
+}
+
+impl HostcallInterceptor for AuditLog {
+    fn before(&self, call: &HostcallInfo) -> GuestResult<()> {
+        self.record(call, Duration::ZERO, Outcome::Started, None);
+        Ok(())
+    }
+
+    fn after(&self, call: &HostcallInfo, elapsed: Duration, result: &GuestResult<Vec<u8>>) {
+        let code = result.as_ref().err().map(|err| err.code());
+        self.record(call, elapsed, Outcome::of(code), code);
+    }
+}
+
+/// Connect to the socket at `path`, or open the file there for appending.
+fn open_sink(path: &Path) -> Result<Box<dyn Write + Send>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::{fs::FileTypeExt, net::UnixStream};
+
+        if fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            let stream = UnixStream::connect(path)
+                .with_context(|| format!("connect to audit socket {}", path.display()))?;
+            return Ok(Box::new(stream));
+        }
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open audit log {}", path.display()))?;
+    Ok(Box::new(file))
+}
+
+/// Write queued records until every [`AuditLog`] is dropped, flushing whenever the queue runs
+/// dry and noting how many records were `dropped` since the last time it did. Once the sink
+/// fails, further records are discarded.
+fn write_records(
+    sink: Box<dyn Write + Send>,
+    queue: Receiver<AuditLine>,
+    dropped: &AtomicU64,
+    path: &Path,
+) {
+    let mut sink = BufWriter::new(sink);
+    let mut reported = 0;
+    while let Ok(record) = queue.recv() {
+        let written = std::iter::once(record)
+            .chain(queue.try_iter())
+            .try_for_each(|record| write_record(&mut sink, &record))
+            .and_then(|()| {
+                let total = dropped.load(Ordering::Relaxed);
+                if total == reported {
+                    return Ok(());
+                }
+                warn!(dropped = total - reported, "audit log queue overflowed");
+                let gap = DroppedRecords {
+                    timestamp_ms: unix_millis(Some(SystemTime::now())),
+                    dropped: total - reported,
+                };
+                reported = total;
+                write_record(&mut sink, &gap)
+            })
+            .and_then(|()| sink.flush());
+        if let Err(err) = written {
+            error!(path = %path.display(), %err, "audit log write failed; discarding further records");
+            queue.iter().for_each(drop);
+            return;
+        }
+    }
+}
+
+fn write_record(sink: &mut impl Write, record: &impl Serialize) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *sink, record)?;
+    sink.write_all(b"\n")
+}
+
+/// Milliseconds from the Unix epoch to `time`, or zero if it is unknown or earlier.
+fn unix_millis(time: Option<SystemTime>) -> u64 {
+    let since_epoch = time
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::Capability;
+    use selium_kernel::guest_data::GuestError;
+
+    use super::*;
+
+    #[test]
+    fn records_are_appended_as_json_lines() {
+        let path = std::env::temp_dir().join(format!("selium-audit-{}.log", std::process::id()));
+        let audit = AuditLog::open(&path).expect("open audit log");
+        let call = HostcallInfo {
+            module: "selium::time::now",
+            capability: Some(Capability::TimeRead),
+            session: Some(3),
+            payload_len: 8,
+        };
+        audit.before(&call).expect("admit");
+        audit.after(&call, Duration::ZERO, &Ok(Vec::new()));
+        audit.after(&call, Duration::ZERO, &Err(GuestError::PermissionDenied));
+        audit.after(&call, Duration::ZERO, &Err(GuestError::TimedOut));
+        drop(audit);
+
+        let mut outcomes = Vec::new();
+        for _ in 0..100 {
+            let contents = fs::read_to_string(&path).expect("read audit log");
+            outcomes = contents
+                .lines()
+                .map(|line| {
+                    let record: serde_json::Value = serde_json::from_str(line).expect("json");
+                    assert_eq!(record["hostcall"], "selium::time::now");
+                    assert_eq!(record["capability"], "TimeRead");
+                    assert_eq!(record["session"], 3);
+                    assert_eq!(record["payload_len"], 8);
+                    record["outcome"].as_str().expect("outcome").to_string()
+                })
+                .collect();
+            if outcomes.len() == 4 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(outcomes, ["started", "ok", "denied", "error"]);
+
+        fs::remove_file(&path).expect("remove audit log");
+    }
+
+    /// Sink that holds up its first write until released, collecting what is written.
+    struct StalledSink {
+        stalled: Option<(mpsc::Sender<()>, mpsc::Receiver<()>)>,
+        written: Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl Write for StalledSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if let Some((entered, release)) = self.stalled.take() {
+                entered.send(()).expect("signal write");
+                release.recv().expect("await release");
+            }
+            self.written
+                .lock()
+                .expect("sink lock")
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn records_beyond_the_queue_are_counted_and_reported() {
+        let (entered, writing) = mpsc::channel();
+        let (release, released) = mpsc::channel();
+        let written = Arc::default();
+        let sink = StalledSink {
+            stalled: Some((entered, released)),
+            written: Arc::clone(&written),
+        };
+        let audit = AuditLog::spawn(Box::new(sink), Path::new("stalled"), 2).expect("spawn");
+        let call = HostcallInfo {
+            module: "selium::time::now",
+            capability: Some(Capability::TimeRead),
+            session: None,
+            payload_len: 0,
+        };
+
+        audit.before(&call).expect("admit");
+        writing.recv().expect("writer busy");
+        for _ in 0..5 {
+            audit.before(&call).expect("admit");
+        }
+        assert_eq!(audit.dropped.load(Ordering::Relaxed), 3);
+        release.send(()).expect("release writer");
+        drop(audit);
+
+        let mut lines = Vec::new();
+        for _ in 0..100 {
+            let contents =
+                String::from_utf8(written.lock().expect("sink lock").clone()).expect("utf-8");
+            lines = contents
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("json"))
+                .collect();
+            if lines.len() == 4 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(lines.len(), 4);
+        assert!(lines[..3].iter().all(|line| line["outcome"] == "started"));
+        assert_eq!(lines[3]["dropped"], 3);
+    }
+}
@@ -0,0 +1,292 @@
+//! The `bench` subcommand, measuring hostcall latency and throughput.
+//!
+//! A kernel is assembled exactly as the host assembles it, in a scratch work directory with
+//! freshly generated certificates, and each case calls one hostcall through the operation a
+//! guest would reach: admission, interceptors, idempotency and driver task spawning included.
+//! Calls are started from host code with rkyv-encoded input, so the figures leave out guest
+//! execution and the copy out of linear memory, and are comparable between builds of the host.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use selium_abi::{
+    ChannelBackpressure, ChannelCreate, CounterAdd, TimeSleep, WatchCreate, encode_rkyv,
+    hostcalls::{CHANNEL_CREATE, METRICS_COUNTER_ADD, TIME_NOW, TIME_SLEEP, WATCH_CREATE},
+};
+use selium_kernel::{
+    operation::{CallState, LinkableOperation},
+    registry::{InstanceRegistry, Registry},
+};
+use selium_wasmtime::WasmtimeDriver;
+
+use crate::{
+    certs,
+    kernel::{self, KernelOptions},
+};
+
+/// Headings of the report table.
+const HEADINGS: [&str; 7] = [
+    "FAMILY", "HOSTCALL", "CALLS", "P50", "P99", "MEAN", "CALLS/S",
+];
+
+/// How many calls each case makes.
+#[derive(Clone, Copy, Debug)]
+pub struct BenchOptions {
+    /// Calls timed one after another for the latency figures, and again for throughput.
+    pub iterations: usize,
+    /// Calls kept in flight at once while measuring throughput.
+    pub concurrency: usize,
+}
+
+/// A hostcall to measure and the input it is called with.
+struct Case {
+    family: &'static str,
+    hostcall: &'static str,
+    input: Vec<u8>,
+}
+
+/// Figures measured for one [`Case`].
+#[derive(Debug, PartialEq)]
+struct CaseReport {
+    family: &'static str,
+    hostcall: &'static str,
+    calls: usize,
+    p50: Duration,
+    p99: Duration,
+    mean: Duration,
+    throughput: f64,
+}
+
+/// Scratch work directory, removed once the benchmark is done.
+struct ScratchDir(PathBuf);
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.0) {
+            tracing::warn!(path = %self.0.display(), %err, "failed to remove bench work directory");
+        }
+    }
+}
+
+/// Measure every case and print a report of the results.
+pub async fn run(options: BenchOptions) -> Result<()> {
+    let reports = measure_all(options).await?;
+    println!("{}", render(&reports));
+    Ok(())
+}
+
+async fn measure_all(options: BenchOptions) -> Result<Vec<CaseReport>> {
+    if options.iterations == 0 || options.concurrency == 0 {
+        return Err(anyhow!("iterations and concurrency must be at least one"));
+    }
+
+    let work_dir =
+        ScratchDir(std::env::temp_dir().join(format!("selium-bench-{}", std::process::id())));
+    certs::generate_certificates(
+        &work_dir.0.join(kernel::CERTS_SUBDIR),
+        "Selium Bench CA",
+        "localhost",
+        "client.localhost",
+    )?;
+    let (kernel, _shutdown) =
+        kernel::build(&work_dir.0, &KernelOptions::default()).context("build bench kernel")?;
+    let runtime = kernel.require::<WasmtimeDriver>()?.runtime();
+
+    let registry = Registry::new();
+    let mut instance = registry.instance()?;
+    let mut reports = Vec::new();
+    for case in cases()? {
+        let operation = runtime
+            .operation(case.hostcall)?
+            .with_context(|| format!("hostcall `{}` is not registered", case.hostcall))?;
+        reports.push(measure(&case, operation.as_ref(), &mut instance, options).await?);
+    }
+    Ok(reports)
+}
+
+fn cases() -> Result<Vec<Case>> {
+    Ok(vec![
+        Case {
+            family: "time",
+            hostcall: TIME_NOW.name(),
+            input: encode_rkyv(&())?,
+        },
+        Case {
+            family: "time",
+            hostcall: TIME_SLEEP.name(),
+            input: encode_rkyv(&TimeSleep { duration_ms: 0 })?,
+        },
+        Case {
+            family: "channel",
+            hostcall: CHANNEL_CREATE.name(),
+            input: encode_rkyv(&ChannelCreate {
+                capacity: 4096,
+                backpressure: ChannelBackpressure::Park,
+            })?,
+        },
+        Case {
+            family: "metrics",
+            hostcall: METRICS_COUNTER_ADD.name(),
+            input: encode_rkyv(&CounterAdd {
+                name: "bench_calls_total".to_string(),
+                delta: 1,
+            })?,
+        },
+        Case {
+            family: "watch",
+            hostcall: WATCH_CREATE.name(),
+            input: encode_rkyv(&WatchCreate { value: vec![0; 64] })?,
+        },
+    ])
+}
+
+/// Time `case` one call at a time for its latency, then `concurrency` calls at a time for its
+/// throughput.
+async fn measure(
+    case: &Case,
+    operation: &dyn LinkableOperation,
+    instance: &mut InstanceRegistry,
+    options: BenchOptions,
+) -> Result<CaseReport> {
+    let mut latencies = Vec::with_capacity(options.iterations);
+    for _ in 0..options.iterations {
+        let started = Instant::now();
+        call(case, operation, instance).await?;
+        latencies.push(started.elapsed());
+    }
+    latencies.sort_unstable();
+
+    let started = Instant::now();
+    let mut remaining = options.iterations;
+    while remaining > 0 {
+        let batch = remaining.min(options.concurrency);
+        let mut calls = Vec::with_capacity(batch);
+        for _ in 0..batch {
+            calls.push(operation.invoke(instance, &case.input)?);
+        }
+        for state in calls {
+            wait(case, state).await?;
+        }
+        remaining -= batch;
+    }
+    let elapsed = started.elapsed();
+
+    Ok(CaseReport {
+        family: case.family,
+        hostcall: case.hostcall,
+        calls: options.iterations,
+        p50: percentile(&latencies, 50),
+        p99: percentile(&latencies, 99),
+        mean: latencies.iter().sum::<Duration>() / latencies.len() as u32,
+        throughput: options.iterations as f64 / elapsed.as_secs_f64(),
+    })
+}
+
+async fn call(
+    case: &Case,
+    operation: &dyn LinkableOperation,
+    instance: &mut InstanceRegistry,
+) -> Result<()> {
+    let state = operation.invoke(instance, &case.input)?;
+    wait(case, state).await
+}
+
+async fn wait(case: &Case, state: CallState) -> Result<()> {
+    match state.next_result().await {
+        Some(Ok(_)) => Ok(()),
+        Some(Err(err)) => Err(anyhow!("`{}` failed: {err}", case.hostcall)),
+        None => Err(anyhow!("`{}` was abandoned", case.hostcall)),
+    }
+}
+
+/// The latency below which `percent` of the sorted `latencies` fall.
+fn percentile(latencies: &[Duration], percent: usize) -> Duration {
+    let index = (latencies.len() * percent / 100).min(latencies.len().saturating_sub(1));
+    latencies.get(index).copied().unwrap_or_default()
+}
+
+fn render(reports: &[CaseReport]) -> String {
+    let rows: Vec<[String; 7]> = reports
+        .iter()
+        .map(|report| {
+            [
+                report.family.to_string(),
+                report.hostcall.to_string(),
+                report.calls.to_string(),
+                format_latency(report.p50),
+                format_latency(report.p99),
+                format_latency(report.mean),
+                format!("{:.0}", report.throughput),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADINGS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let headings = HEADINGS.map(str::to_string);
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for row in std::iter::once(&headings).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// A latency in microseconds with one decimal place, e.g. `12.5us`.
+fn format_latency(latency: Duration) -> String {
+    format!("{:.1}us", latency.as_secs_f64() * 1e6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn every_case_completes() {
+        let reports = measure_all(BenchOptions {
+            iterations: 8,
+            concurrency: 3,
+        })
+        .await
+        .expect("bench");
+
+        assert_eq!(reports.len(), cases().expect("cases").len());
+        assert!(reports.iter().all(|report| report.calls == 8));
+        assert!(reports.iter().all(|report| report.p50 <= report.p99));
+    }
+
+    #[test]
+    fn reports_render_as_a_table() {
+        let rendered = render(&[CaseReport {
+            family: "time",
+            hostcall: "selium::time::now",
+            calls: 100,
+            p50: Duration::from_micros(12),
+            p99: Duration::from_nanos(40_300),
+            mean: Duration::from_micros(15),
+            throughput: 81_234.4,
+        }]);
+
+        assert_eq!(
+            rendered.lines().collect::<Vec<_>>(),
+            [
+                "FAMILY  HOSTCALL           CALLS  P50     P99     MEAN    CALLS/S",
+                "time    selium::time::now  100    12.0us  40.3us  15.0us  81234",
+            ]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! The `generate-c-bindings` subcommand, writing the C header and helper library that let C,
+//! Zig or TinyGo guests call hostcalls without reimplementing the payload encoding.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use selium_abi::c_bindings::{C_HELPERS, CHeader, HEADER_FILE, HELPERS_FILE};
+
+/// Write `selium.h` and `selium.c` for this host's hostcall catalogue to `output_dir`.
+pub fn generate_c_bindings(output_dir: &Path) -> Result<()> {
+    fs::create_dir_all(output_dir).context("create bindings output directory")?;
+
+    let header_path = output_dir.join(HEADER_FILE);
+    fs::write(&header_path, CHeader.to_string())
+        .with_context(|| format!("write C header {}", header_path.display()))?;
+    let helpers_path = output_dir.join(HELPERS_FILE);
+    fs::write(&helpers_path, C_HELPERS)
+        .with_context(|| format!("write C helpers {}", helpers_path.display()))?;
+
+    println!("Wrote C bindings to {}", output_dir.display());
+
+    Ok(())
+}
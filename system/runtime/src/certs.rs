@@ -39,8 +39,6 @@ pub fn generate_certificates(
     let client = generate_leaf(client_name, LeafUsage::Client, &ca)?;
     write_pair(output_dir, "client", &client.cert_pem, &client.key_pem)?;
 
-    println!("Wrote certificates to {}", output_dir.display());
-
     Ok(())
 }
 
@@ -0,0 +1,836 @@
+//! Command-line interface of the `selium-runtime` binary.
+
+use std::{
+    env,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use selium_abi::CapabilitySet;
+use selium_kernel::{
+    drivers::Capability, operation::Enforcement, session::Session, watchdog::WatchdogConfig,
+};
+use selium_wasmtime::PoolingLimits;
+use tokio::{signal, sync::Notify};
+use tracing::{info, warn};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, fmt::time::SystemTime, layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+#[cfg(feature = "otlp")]
+use crate::telemetry::Telemetry;
+use crate::{
+    Runtime, RuntimeBuilder, bench, bindings, certs,
+    config::{self, Deployment, ModulePolicy, Tenant},
+    identity::{Identities, Identity},
+    kernel::{self, KernelOptions},
+    logging::{ModuleFilter, ModuleOutputLayer},
+    modules,
+    reload::ReloadOptions,
+    signing, validate,
+};
+#[cfg(unix)]
+use crate::{control, diagnose, metrics, profile, status};
+
+/// How often module files are checked for changes when hot reload is enabled.
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How often module files are checked for changes in watch mode.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-friendly text logs suitable for local development.
+    Text,
+    /// JSON logs for ingestion into systems such as Loki or OTLP collectors.
+    Json,
+}
+
+/// Clock guests read through the time hostcalls.
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq, Eq)]
+enum ClockKind {
+    /// The host's own clock.
+    System,
+    /// A clock that stands still until advanced with the `advance-clock` command, for
+    /// reproducible tests.
+    Stepped,
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Selium host runtime")]
+struct ServerOptions {
+    /// Log output format (text or JSON) for tracing events.
+    #[arg(long, env = "SELIUM_LOG_FORMAT", default_value = "text")]
+    log_format: LogFormat,
+    /// Also export traces and metrics to the OpenTelemetry collector at this URL over OTLP/HTTP,
+    /// e.g. `http://localhost:4318`.
+    #[cfg(feature = "otlp")]
+    #[arg(long, env = "SELIUM_OTLP_ENDPOINT", value_name = "URL")]
+    otlp_endpoint: Option<String>,
+    #[command(subcommand)]
+    command: Option<ServerCommand>,
+    /// Base directory where certificates and WASM modules are stored.
+    #[arg(short, long, env = "SELIUM_WORK_DIR", default_value_os = ".")]
+    work_dir: PathBuf,
+    /// Module specification to start (repeatable). Format: `path=...;capabilities=...;args=...`
+    #[arg(long, value_name = "SPEC")]
+    module: Option<Vec<String>>,
+    /// Deployment file listing modules to start, in addition to any `--module`. Defaults to
+    /// `selium.toml` in the work directory, if present.
+    #[arg(long, env = "SELIUM_CONFIG", value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Keep instances of a module instantiated ahead of `process::start` (repeatable). Format:
+    /// `module=...;capabilities=...;count=N`. Pre-warmed instances hold pooling allocator slots.
+    #[arg(long, value_name = "SPEC")]
+    prewarm: Vec<String>,
+    /// Execution timeout for a hostcall (repeatable). Format: `<hostcall>=<milliseconds>`
+    #[arg(long, value_name = "HOSTCALL=MS", value_parser = kernel::parse_hostcall_timeout)]
+    hostcall_timeout: Vec<(String, Duration)>,
+    /// How long results of hostcalls made under an idempotency key are replayed to retries, in
+    /// milliseconds. Zero disables result caching.
+    #[arg(long, env = "SELIUM_IDEMPOTENCY_WINDOW_MS", default_value_t = 30_000)]
+    idempotency_window_ms: u64,
+    /// How long a guest parked on hostcalls waits after the first completes for others to
+    /// complete too, so that it resumes once for all of them, in microseconds. Zero resumes
+    /// it on every completion.
+    #[arg(long, env = "SELIUM_WAKE_COALESCE_US", default_value_t = 0)]
+    wake_coalesce_us: u64,
+    /// Preallocate slots for this many concurrent instances using the pooling allocator,
+    /// instead of allocating each instance on demand.
+    #[arg(long, env = "SELIUM_POOLING_MAX_INSTANCES")]
+    pooling_max_instances: Option<u32>,
+    /// Linear memory limit per pooled instance, in 64 KiB Wasm pages.
+    #[arg(
+        long,
+        env = "SELIUM_POOLING_MAX_MEMORY_PAGES",
+        default_value_t = 1024,
+        requires = "pooling_max_instances"
+    )]
+    pooling_max_memory_pages: u64,
+    /// Raw ed25519 public key whose module signatures are trusted (repeatable). When given,
+    /// unsigned or tampered modules are refused.
+    #[arg(long, value_name = "PATH")]
+    trusted_key: Vec<PathBuf>,
+    /// Restart modules whose files change, handing their singletons over to the replacement.
+    #[arg(long, env = "SELIUM_HOT_RELOAD")]
+    hot_reload: bool,
+    /// Restart modules as soon as the contents of their files change, including modules that
+    /// have exited, for a quick local development loop.
+    #[arg(long, env = "SELIUM_WATCH", conflicts_with = "hot_reload")]
+    watch: bool,
+    /// How long a reloaded module may take to report ready before the process it replaces is
+    /// stopped anyway, in milliseconds.
+    #[arg(long, env = "SELIUM_RELOAD_READY_TIMEOUT_MS", default_value_t = 10_000)]
+    reload_ready_timeout_ms: u64,
+    /// How long each module may take to exit on shutdown before it is stopped forcibly, in
+    /// milliseconds. Modules stop one at a time, singleton providers last.
+    #[arg(long, env = "SELIUM_SHUTDOWN_TIMEOUT_MS", default_value_t = 5_000)]
+    shutdown_timeout_ms: u64,
+    /// Record every hostcall guests make, as JSON lines appended to this file, or streamed to
+    /// the Unix domain socket at this path if one is listening there.
+    #[arg(long, env = "SELIUM_AUDIT_LOG", value_name = "PATH")]
+    audit_log: Option<PathBuf>,
+    /// Warn about guest futures still unresolved after this many milliseconds, naming the
+    /// module and the hostcall that created them.
+    #[arg(long, env = "SELIUM_WATCHDOG_STUCK_MS", value_name = "MS")]
+    watchdog_stuck_ms: Option<u64>,
+    /// Warn about hostcalls that take longer than this many milliseconds to complete.
+    #[arg(long, env = "SELIUM_WATCHDOG_HOSTCALL_BUDGET_MS", value_name = "MS")]
+    watchdog_hostcall_budget_ms: Option<u64>,
+    /// Abort the host task behind each future the watchdog reports as stuck, waking the guest
+    /// with a timeout error.
+    #[arg(long, env = "SELIUM_WATCHDOG_ABORT", requires = "watchdog_stuck_ms")]
+    watchdog_abort: bool,
+    /// Evaluate hostcall capability and quota checks without enforcing them: calls that fail a
+    /// check are logged as ones that would be denied, then run anyway.
+    #[arg(long, env = "SELIUM_AUDIT_ONLY")]
+    audit_only: bool,
+    /// Withhold the host's name from guests asking about the host they run on.
+    #[arg(long, env = "SELIUM_REDACT_HOSTNAME")]
+    redact_hostname: bool,
+    /// Salt singleton dependency identifiers with this deployment-specific namespace, so that
+    /// the keys they are registered under differ between deployments.
+    #[arg(long, env = "SELIUM_DEPENDENCY_NAMESPACE", value_name = "SALT")]
+    dependency_namespace: Option<String>,
+    /// Write the startup report, listing the capability providers and how each module started
+    /// at startup was linked, to this file as JSON. The report is always logged.
+    #[arg(long, env = "SELIUM_STARTUP_REPORT", value_name = "PATH")]
+    startup_report: Option<PathBuf>,
+    /// Clock guests observe.
+    #[arg(long, env = "SELIUM_CLOCK", value_enum, default_value = "system")]
+    clock: ClockKind,
+    /// Wall-clock time a stepped clock starts at, in milliseconds since the Unix epoch.
+    #[arg(long, env = "SELIUM_CLOCK_START_MS", default_value_t = 0)]
+    clock_start_ms: u64,
+    /// Unix domain socket accepting commands to list, start, stop and reload modules, or to shut
+    /// the runtime down. Combined with no modules, the runtime runs as a daemon managed
+    /// entirely through this socket.
+    #[arg(
+        long,
+        env = "SELIUM_CONTROL_SOCKET",
+        value_name = "PATH",
+        global = true
+    )]
+    control_socket: Option<PathBuf>,
+    /// Also serve control requests over TLS on this address, to clients whose certificate was
+    /// issued by the CA in the work directory's `certs` and is pinned by an `[[identity]]` of
+    /// the deployment file.
+    #[arg(long, env = "SELIUM_CONTROL_LISTEN", value_name = "ADDR")]
+    control_listen: Option<SocketAddr>,
+    /// Send operator commands to the control listener at this address instead of the control
+    /// socket, authenticating with the client certificate in the work directory's `certs`.
+    #[arg(
+        long,
+        env = "SELIUM_CONTROL_REMOTE",
+        value_name = "HOST:PORT",
+        global = true
+    )]
+    control_remote: Option<String>,
+    /// Address operator commands to the kernel of this tenant instead of the host's own.
+    #[arg(long, env = "SELIUM_TENANT", value_name = "ID", global = true)]
+    tenant: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ServerCommand {
+    /// Generate a local CA plus server and client certificate pairs.
+    GenerateCerts(GenerateCertsArgs),
+    /// Write a detached ed25519 signature for a module.
+    SignModule(SignModuleArgs),
+    /// Write a C header and helper library for guests written in C, Zig or TinyGo.
+    GenerateCBindings(GenerateCBindingsArgs),
+    /// Check a module's imports against the hostcall catalogue and the capabilities it will be
+    /// granted.
+    Validate(ValidateArgs),
+    /// Measure the latency and throughput of hostcalls from each family on a freshly built
+    /// kernel, and print a report.
+    Bench(BenchArgs),
+    /// List the modules supervised by a running host, through its control socket.
+    #[cfg(unix)]
+    #[command(visible_alias = "status")]
+    Ps(PsArgs),
+    /// Start a new module, or a stopped one, on a running host.
+    #[cfg(unix)]
+    Start(StartArgs),
+    /// Stop a module on a running host; it stays stopped until started again.
+    #[cfg(unix)]
+    Stop(TargetArgs),
+    /// Stop a module on a running host and start it again with a fresh process.
+    #[cfg(unix)]
+    Restart(TargetArgs),
+    /// Show the pending futures, wake-up mailbox counters and slot usage of a module on a
+    /// running host, for debugging guests whose tasks never wake.
+    #[cfg(unix)]
+    Diagnose(TargetArgs),
+    /// Sample the call stacks of a module on a running host and write them under `profiles` in
+    /// its work directory, in the folded stacks format read by flamegraph tools.
+    #[cfg(unix)]
+    Profile(ProfileArgs),
+    /// Move the stepped clock of a running host forward.
+    #[cfg(unix)]
+    AdvanceClock(AdvanceClockArgs),
+    /// Print the hostcall call counts, error counts and latency histograms of a running host, in
+    /// the Prometheus text format.
+    #[cfg(unix)]
+    Metrics,
+}
+
+#[derive(Args, Debug)]
+struct GenerateCertsArgs {
+    /// Directory to write certificate and key files to.
+    #[arg(long, default_value = "certs")]
+    output_dir: PathBuf,
+    /// Common Name to embed in the generated CA.
+    #[arg(long, default_value = "Selium Local CA")]
+    ca_common_name: String,
+    /// DNS name to embed in the server certificate.
+    #[arg(long, default_value = "localhost")]
+    server_name: String,
+    /// DNS name to embed in the client certificate.
+    #[arg(long, default_value = "client.localhost")]
+    client_name: String,
+}
+
+#[cfg(unix)]
+#[derive(Args, Debug)]
+struct PsArgs {
+    /// Output format.
+    #[arg(long, value_enum, default_value = "table")]
+    format: status::StatusFormat,
+}
+
+#[cfg(unix)]
+#[derive(Args, Debug)]
+struct StartArgs {
+    /// Label or process id of a supervised module that is not running.
+    #[arg(required_unless_present = "spec", conflicts_with = "spec")]
+    target: Option<String>,
+    /// Specification of a new module, in the `--module` format.
+    #[arg(long, value_name = "SPEC")]
+    spec: Option<String>,
+}
+
+#[cfg(unix)]
+#[derive(Args, Debug)]
+struct TargetArgs {
+    /// Module label, as given in its specification, or process id.
+    target: String,
+}
+
+#[cfg(unix)]
+#[derive(Args, Debug)]
+struct ProfileArgs {
+    /// Module label, as given in its specification, or process id.
+    target: String,
+    /// How long to sample the module for.
+    #[arg(long, default_value_t = 10)]
+    seconds: u32,
+}
+
+#[cfg(unix)]
+#[derive(Args, Debug)]
+struct AdvanceClockArgs {
+    /// How far to advance the clock, in milliseconds.
+    millis: u64,
+}
+
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Calls made to each hostcall, both one at a time and concurrently.
+    #[arg(long, default_value_t = 10_000)]
+    iterations: usize,
+    /// Calls kept in flight at once while measuring throughput.
+    #[arg(long, default_value_t = 64)]
+    concurrency: usize,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Capabilities the module will be granted, comma-separated.
+    #[arg(long, value_delimiter = ',')]
+    capabilities: Vec<String>,
+    /// Module to validate.
+    module: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct GenerateCBindingsArgs {
+    /// Directory to write `selium.h` and `selium.c` to.
+    #[arg(long, default_value = "include")]
+    output_dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct SignModuleArgs {
+    /// PKCS#8 ed25519 signing key.
+    #[arg(long)]
+    key: PathBuf,
+    /// Generate a new signing key at `--key`, plus its raw public key alongside with a `.pub`
+    /// extension, before signing.
+    #[arg(long)]
+    generate_key: bool,
+    /// Module to sign; the signature is written next to it with a `.sig` suffix.
+    module: PathBuf,
+}
+
+/// A started `[[tenant]]` runtime.
+struct TenantRuntime {
+    id: String,
+    work_dir: PathBuf,
+    runtime: Runtime,
+}
+
+/// Settings of the command-line host that an embedded [`Runtime`] leaves to its application.
+struct ServeOptions<'a> {
+    work_dir: &'a Path,
+    control_socket: Option<&'a Path>,
+    control_listen: Option<SocketAddr>,
+    identities: Vec<Identity>,
+    shutdown_timeout: Duration,
+    startup_report: Option<&'a Path>,
+}
+
+/// Serve the control socket and listener of a started runtime and its tenants until asked to
+/// shut down.
+async fn serve(
+    runtime: Runtime,
+    tenants: Vec<TenantRuntime>,
+    options: ServeOptions<'_>,
+) -> Result<()> {
+    info!("starting host bridge");
+
+    // This would normally be done by the Orchestrator, however during bootstrap we
+    // have a chicken-and-egg problem, so we construct the session manually.
+    let entitlements = CapabilitySet::from([
+        Capability::SessionLifecycle,
+        Capability::ChannelLifecycle,
+        Capability::ChannelReader,
+        Capability::ChannelWriter,
+        Capability::ProcessLifecycle,
+        Capability::NetQuicBind,
+        Capability::NetQuicAccept,
+        Capability::NetQuicConnect,
+        Capability::NetQuicRead,
+        Capability::NetQuicWrite,
+        Capability::TimeRead,
+        Capability::HostShutdown,
+    ]);
+    let root_session = Session::bootstrap(entitlements, [0; 32]);
+    // @todo Store session in Registry, then pass FuncParam::Resource(id) to host bridge
+
+    #[cfg(unix)]
+    spawn_snapshot_dumper(Arc::clone(runtime.registry()))?;
+
+    if let Some(path) = options.startup_report {
+        runtime.startup_report().write(path)?;
+    }
+
+    let stop_requested = Arc::new(Notify::new());
+    #[cfg(unix)]
+    let service = control::ControlService::new(
+        control::ControlTarget::new(&runtime, options.work_dir),
+        tenants
+            .iter()
+            .map(|tenant| {
+                (
+                    tenant.id.clone(),
+                    control::ControlTarget::new(&tenant.runtime, &tenant.work_dir),
+                )
+            })
+            .collect(),
+        Arc::clone(&stop_requested),
+    );
+    #[cfg(unix)]
+    let _control = options
+        .control_socket
+        .map(|path| control::ControlSocket::bind(path, &service))
+        .transpose()?;
+    #[cfg(unix)]
+    if let Some(addr) = options.control_listen {
+        control::listen(
+            addr,
+            &options.work_dir.join(kernel::CERTS_SUBDIR),
+            &service,
+            Identities::new(options.identities),
+            root_session,
+            Arc::clone(runtime.registry()),
+        )
+        .await?;
+    }
+    #[cfg(not(unix))]
+    if options.control_socket.is_some() || options.control_listen.is_some() {
+        anyhow::bail!("the control socket requires Unix domain sockets");
+    }
+
+    wait_for_shutdown(&stop_requested).await?;
+    info!("shutting down");
+    runtime.shutdown(options.shutdown_timeout).await;
+    for tenant in tenants {
+        info!(tenant = %tenant.id, "shutting down tenant");
+        tenant.runtime.shutdown(options.shutdown_timeout).await;
+    }
+
+    Ok(())
+}
+
+/// Wait for ctrl-c, `SIGTERM` or a shutdown request from the control socket.
+async fn wait_for_shutdown(requested: &Notify) -> Result<()> {
+    #[cfg(unix)]
+    let mut terminate = {
+        use tokio::signal::unix::{SignalKind, signal};
+        signal(SignalKind::terminate()).context("install SIGTERM handler")?
+    };
+    #[cfg(unix)]
+    let terminated = terminate.recv();
+    #[cfg(not(unix))]
+    let terminated = std::future::pending::<Option<()>>();
+
+    tokio::select! {
+        result = signal::ctrl_c() => result?,
+        _ = terminated => {}
+        () = requested.notified() => {}
+    }
+    Ok(())
+}
+
+/// Log a registry snapshot whenever the process receives `SIGUSR1`.
+#[cfg(unix)]
+fn spawn_snapshot_dumper(registry: Arc<selium_kernel::registry::Registry>) -> Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut signals =
+        signal(SignalKind::user_defined1()).context("install SIGUSR1 snapshot handler")?;
+    tokio::spawn(async move {
+        while signals.recv().await.is_some() {
+            let snapshot = registry.snapshot();
+            info!(?snapshot, "registry snapshot");
+        }
+    });
+
+    Ok(())
+}
+
+/// Connect to the control listener or socket of a running host, for the operator subcommands.
+#[cfg(unix)]
+async fn control_client(args: &ServerOptions) -> Result<control::ControlClient> {
+    if let Some(addr) = &args.control_remote {
+        let certs_dir = args.work_dir.join(kernel::CERTS_SUBDIR);
+        let client = control::ControlClient::connect_tls(addr, &certs_dir).await?;
+        return Ok(for_tenant(client, args));
+    }
+    let path = args
+        .control_socket
+        .as_deref()
+        .context("this command needs --control-socket or --control-remote")?;
+    let client = control::ControlClient::connect(path).await?;
+    Ok(for_tenant(client, args))
+}
+
+/// Address `client`'s requests to the tenant named by `--tenant`, if any.
+#[cfg(unix)]
+fn for_tenant(client: control::ControlClient, args: &ServerOptions) -> control::ControlClient {
+    match &args.tenant {
+        Some(id) => client.tenant(id),
+        None => client,
+    }
+}
+
+/// The deployment file's policy, and its modules followed by those given with `--module`.
+fn deployment(
+    work_dir: &Path,
+    config: Option<&Path>,
+    cli: Option<&[String]>,
+) -> Result<Deployment> {
+    let default_config = work_dir.join(config::DEFAULT_CONFIG_FILE);
+    let config = config.or_else(|| default_config.is_file().then_some(default_config.as_path()));
+
+    let mut deployment = match config {
+        Some(path) => config::load(path, work_dir)?,
+        None => Deployment::default(),
+    };
+    if let Some(cli) = cli {
+        deployment
+            .modules
+            .extend(modules::parse_cli_specs(cli, work_dir)?);
+    }
+    Ok(deployment)
+}
+
+fn initialise_tracing(
+    format: LogFormat,
+    otlp: Option<Box<dyn Layer<Registry> + Send + Sync>>,
+) -> Result<()> {
+    let env_filter = || {
+        EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(env::var("RUST_LOG").unwrap_or_else(|_| "info".into())))
+    };
+
+    let host = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_target(false)
+            .with_timer(SystemTime)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_target(false)
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    };
+    let otlp = match otlp {
+        Some(layer) => Some(layer.with_filter(ModuleFilter::host(env_filter()?))),
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(otlp)
+        .with(host.with_filter(ModuleFilter::host(env_filter()?)))
+        .with(ModuleOutputLayer.with_filter(ModuleFilter::routed(env_filter()?)))
+        .init();
+
+    Ok(())
+}
+
+/// Parse the command line and run the subcommand it names, or else the host.
+pub async fn run() -> Result<()> {
+    // Parse CLI options
+    let args = ServerOptions::parse();
+
+    // Initialise logging, exporting to a collector if one is given
+    #[cfg(feature = "otlp")]
+    let telemetry = args
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| Telemetry::install(endpoint, &args.work_dir))
+        .transpose()?;
+    #[cfg(feature = "otlp")]
+    initialise_tracing(args.log_format, telemetry.as_ref().map(Telemetry::layer))?;
+    #[cfg(not(feature = "otlp"))]
+    initialise_tracing(args.log_format, None)?;
+
+    match &args.command {
+        Some(ServerCommand::GenerateCerts(cert_args)) => {
+            certs::generate_certificates(
+                &cert_args.output_dir,
+                &cert_args.ca_common_name,
+                &cert_args.server_name,
+                &cert_args.client_name,
+            )?;
+            println!("Wrote certificates to {}", cert_args.output_dir.display());
+            return Ok(());
+        }
+        Some(ServerCommand::SignModule(sign_args)) => {
+            signing::sign_module(&sign_args.key, &sign_args.module, sign_args.generate_key)?;
+            return Ok(());
+        }
+        Some(ServerCommand::GenerateCBindings(bindings_args)) => {
+            bindings::generate_c_bindings(&bindings_args.output_dir)?;
+            return Ok(());
+        }
+        Some(ServerCommand::Validate(validate_args)) => {
+            validate::run(&validate_args.module, &validate_args.capabilities)?;
+            return Ok(());
+        }
+        Some(ServerCommand::Bench(bench_args)) => {
+            bench::run(bench::BenchOptions {
+                iterations: bench_args.iterations,
+                concurrency: bench_args.concurrency,
+            })
+            .await?;
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Ps(ps_args)) => {
+            let client = control_client(&args).await?;
+            status::print(client, ps_args.format).await?;
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Start(start_args)) => {
+            let mut client = control_client(&args).await?;
+            let command = match (&start_args.spec, &start_args.target) {
+                (Some(spec), _) => control::StartCommand::Spec(spec),
+                (None, Some(target)) => control::StartCommand::Target(target),
+                (None, None) => anyhow::bail!("give a module label, process id or --spec"),
+            };
+            let process_id = client.start(command).await?;
+            println!("started process {process_id}");
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Stop(stop_args)) => {
+            let mut client = control_client(&args).await?;
+            client.stop(&stop_args.target).await?;
+            println!("stopped {}", stop_args.target);
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Restart(restart_args)) => {
+            let mut client = control_client(&args).await?;
+            let process_id = client.restart(&restart_args.target).await?;
+            println!("restarted {} as process {process_id}", restart_args.target);
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Diagnose(diagnose_args)) => {
+            let client = control_client(&args).await?;
+            diagnose::print(client, &diagnose_args.target).await?;
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Profile(profile_args)) => {
+            let client = control_client(&args).await?;
+            profile::print(client, &profile_args.target, profile_args.seconds).await?;
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::AdvanceClock(advance_args)) => {
+            let mut client = control_client(&args).await?;
+            let now = client
+                .advance_clock(Duration::from_millis(advance_args.millis))
+                .await?;
+            println!(
+                "clock now reads {} ms since the Unix epoch ({} ms monotonic)",
+                now.unix_ms, now.monotonic_ms
+            );
+            return Ok(());
+        }
+        #[cfg(unix)]
+        Some(ServerCommand::Metrics) => {
+            let client = control_client(&args).await?;
+            metrics::print(client).await?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    if args.tenant.is_some() {
+        bail!("--tenant only applies to operator commands; list tenants in the deployment file");
+    }
+    let Deployment {
+        modules,
+        policy,
+        identities,
+        tenants,
+        host_commands,
+    } = deployment(
+        &args.work_dir,
+        args.config.as_deref(),
+        args.module.as_deref(),
+    )?;
+    let mut options = kernel_options(&args, policy, args.audit_log.clone())?;
+    options.host_commands = host_commands;
+    let mut builder = runtime_builder(&args, &args.work_dir, modules).kernel_options(options);
+    for spec in &args.prewarm {
+        builder = builder.prewarm(spec);
+    }
+    let runtime = builder.start().await?;
+    let tenants = start_tenants(&args, tenants).await?;
+    #[cfg(feature = "otlp")]
+    if let Some(telemetry) = &telemetry {
+        telemetry.observe(runtime.kernel(), None);
+        for tenant in &tenants {
+            telemetry.observe(tenant.runtime.kernel(), Some(&tenant.id));
+        }
+    }
+
+    serve(
+        runtime,
+        tenants,
+        ServeOptions {
+            work_dir: &args.work_dir,
+            control_socket: args.control_socket.as_deref(),
+            control_listen: args.control_listen,
+            identities,
+            shutdown_timeout: Duration::from_millis(args.shutdown_timeout_ms),
+            startup_report: args.startup_report.as_deref(),
+        },
+    )
+    .await
+}
+
+/// Start the runtime of each tenant, from the modules and policy of its own deployment file.
+/// Tenants share the host's options, except for their audit log and quotas, and may run no host
+/// commands.
+async fn start_tenants(args: &ServerOptions, tenants: Vec<Tenant>) -> Result<Vec<TenantRuntime>> {
+    let mut started = Vec::with_capacity(tenants.len());
+    for tenant in tenants {
+        let Deployment {
+            modules,
+            policy,
+            identities,
+            tenants: nested,
+            host_commands,
+        } = deployment(&tenant.work_dir, tenant.config.as_deref(), None)
+            .with_context(|| format!("tenant `{}`", tenant.id))?;
+        if !nested.is_empty() {
+            bail!("tenant `{}` lists tenants of its own", tenant.id);
+        }
+        if !identities.is_empty() {
+            warn!(
+                tenant = %tenant.id,
+                "ignoring [[identity]] entries of a tenant; only the host's control listener accepts them"
+            );
+        }
+        if !host_commands.is_empty() {
+            warn!(
+                tenant = %tenant.id,
+                "ignoring [[host_command]] entries of a tenant; only the host's deployment file may allow-list host commands"
+            );
+        }
+
+        let mut options = kernel_options(args, policy, tenant.audit_log)?;
+        if let Some(pooling) = tenant.pooling {
+            options.pooling = Some(pooling);
+        }
+        info!(tenant = %tenant.id, work_dir = %tenant.work_dir.display(), "starting tenant");
+        let runtime = runtime_builder(args, &tenant.work_dir, modules)
+            .kernel_options(options)
+            .start()
+            .await
+            .with_context(|| format!("start tenant `{}`", tenant.id))?;
+        started.push(TenantRuntime {
+            id: tenant.id,
+            work_dir: tenant.work_dir,
+            runtime,
+        });
+    }
+    Ok(started)
+}
+
+/// Kernel options given on the command line, restricted by `policy` and recording hostcalls to
+/// `audit_log`.
+fn kernel_options(
+    args: &ServerOptions,
+    policy: ModulePolicy,
+    audit_log: Option<PathBuf>,
+) -> Result<KernelOptions> {
+    let trusted_keys: Vec<_> = args
+        .trusted_key
+        .iter()
+        .chain(&policy.allowed_signers)
+        .cloned()
+        .collect();
+    Ok(KernelOptions {
+        hostcall_timeouts: args.hostcall_timeout.clone(),
+        idempotency_window: Some(Duration::from_millis(args.idempotency_window_ms))
+            .filter(|window| !window.is_zero()),
+        wake_coalescing: Some(Duration::from_micros(args.wake_coalesce_us))
+            .filter(|window| !window.is_zero()),
+        pooling: args
+            .pooling_max_instances
+            .map(|max_instances| PoolingLimits {
+                max_instances,
+                max_memory_pages: args.pooling_max_memory_pages,
+            }),
+        trust_root: match trusted_keys.as_slice() {
+            [] => None,
+            keys => Some(signing::load_trust_root(keys)?),
+        },
+        allowed_digests: policy.allowed_digests,
+        stepped_clock: (args.clock == ClockKind::Stepped).then_some(args.clock_start_ms),
+        providers: Vec::new(),
+        plugins: Vec::new(),
+        audit_log,
+        watchdog: WatchdogConfig {
+            stuck_after: args.watchdog_stuck_ms.map(Duration::from_millis),
+            hostcall_budget: args.watchdog_hostcall_budget_ms.map(Duration::from_millis),
+            abort: args.watchdog_abort,
+        },
+        enforcement: if args.audit_only {
+            Enforcement::Audit
+        } else {
+            Enforcement::Enforce
+        },
+        redact_hostname: args.redact_hostname,
+        host_commands: Vec::new(),
+    })
+}
+
+/// A builder for a runtime in `work_dir` starting `modules`, reloading them and namespacing
+/// dependencies as the command line asks.
+fn runtime_builder(
+    args: &ServerOptions,
+    work_dir: &Path,
+    modules: Vec<modules::ModuleSpec>,
+) -> RuntimeBuilder {
+    let mut builder = RuntimeBuilder::new(work_dir).modules(modules);
+    if args.hot_reload || args.watch {
+        builder = builder.reload(ReloadOptions {
+            poll_interval: if args.watch {
+                WATCH_POLL_INTERVAL
+            } else {
+                RELOAD_POLL_INTERVAL
+            },
+            ready_timeout: Duration::from_millis(args.reload_ready_timeout_ms),
+            compare_contents: args.watch,
+        });
+    }
+    if let Some(namespace) = &args.dependency_namespace {
+        builder = builder.dependency_namespace(namespace);
+    }
+    builder
+}
@@ -0,0 +1,796 @@
+//! Deployment files describing the modules a runtime starts.
+//!
+//! A deployment file is TOML with one `[[module]]` table per module:
+//!
+//! ```toml
+//! [[module]]
+//! path = "modules/echo.wasm"
+//! capabilities = ["channel-lifecycle", "channel-reader", "channel-writer"]
+//! args = ["utf8:hello", "u32:3"]
+//! restart = "on-failure"
+//! log_level = "debug"
+//! log_output = "file:logs/echo.log"
+//! sandbox = "rw"
+//! mounts = ["assets:ro"]
+//!
+//! [module.limits]
+//! fuel = 1_000_000
+//! ```
+//!
+//! Keys mirror those of a `--module` specification, with lists given as arrays. Unknown keys are
+//! rejected, and every error names the file and the entry it was found in.
+//!
+//! An optional `[policy]` table restricts which modules may be started at all, whether listed
+//! here, given on the command line, added through the control socket or started by a guest with
+//! `process::start`:
+//!
+//! ```toml
+//! [policy]
+//! allowed_digests = ["3f4a…"] # BLAKE3 digests of the module files, in hex
+//! allowed_signers = ["keys/release.pub"] # raw ed25519 public keys, as for `--trusted-key`
+//! ```
+//!
+//! A module must match every restriction given; refused modules are logged as audit warnings.
+//!
+//! Each `[[identity]]` entry pins a client certificate, issued by the runtime's CA, that may
+//! connect to the control listener, and the capabilities its holder is entitled to (see
+//! the `identity` module). An identity listing `tenants` may only address those tenants;
+//! one without may only address the host. Stopping the host takes `host-shutdown`:
+//!
+//! ```toml
+//! [[identity]]
+//! name = "deploy-bot"
+//! certificate = "certs/client.crt"
+//! entitlements = ["process-lifecycle"]
+//! tenants = ["acme"]
+//! ```
+//!
+//! Each `[[tenant]]` entry runs a further kernel in the same process, isolated from the host's
+//! own and from every other tenant's: it has its own registry, drivers and work directory, and
+//! starts the modules listed in that directory's deployment file. Control requests address a
+//! tenant by its `id`. The `[tenant.limits]` table sizes the tenant's pooling allocator, capping
+//! how many instances it may run at once and how much memory each may use; a tenant without
+//! limits shares the host's settings.
+//!
+//! ```toml
+//! [[tenant]]
+//! id = "acme"
+//! work_dir = "tenants/acme" # relative to the host's work directory
+//! config = "deploy.toml" # relative to the tenant's; defaults to its `selium.toml`
+//! audit_log = "audit.jsonl" # relative to the tenant's; the host's audit log is not shared
+//!
+//! [tenant.limits]
+//! max_instances = 16
+//! max_memory_pages = 256
+//! ```
+//!
+//! Each `[[host_command]]` entry allow-lists a host program that modules holding the `host-exec`
+//! capability may run through `host::exec`, under a name of its own. Guests name the entry, never
+//! a path; the program runs with an empty environment and the entry's arguments, followed by the
+//! guest's only if `allow_args` is set. Every invocation is logged as an audit warning.
+//!
+//! ```toml
+//! [[host_command]]
+//! name = "flash"
+//! program = "/usr/bin/flashrom" # must be absolute
+//! args = ["--programmer", "internal"]
+//! allow_args = true
+//! ```
+//!
+//! A module may also ship a manifest next to its Wasm file, named after it with a
+//! `.selium.toml` extension (`modules/echo.selium.toml` for `modules/echo.wasm`). It takes the
+//! same keys as a `[[module]]` entry except `path`, and supplies defaults for any key a
+//! specification leaves out, so a specification can be as short as `path=modules/echo.wasm`.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+
+use selium_wasmtime::PoolingLimits;
+
+use crate::{
+    identity::Identity,
+    modules::{self, ModuleSpec, RestartPolicy},
+};
+
+/// Default name of the deployment file in the work directory.
+pub const DEFAULT_CONFIG_FILE: &str = "selium.toml";
+/// Extension of a module manifest, replacing the `.wasm` extension of the module it describes.
+const MANIFEST_EXTENSION: &str = "selium.toml";
+/// Linear memory limit per instance of a tenant that caps its instances but not their memory.
+const DEFAULT_TENANT_MEMORY_PAGES: u64 = 1024;
+
+/// Top level of a deployment file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeploymentConfig {
+    #[serde(default, rename = "module")]
+    modules: Vec<ModuleConfig>,
+    policy: Option<PolicyConfig>,
+    #[serde(default, rename = "identity")]
+    identities: Vec<IdentityConfig>,
+    #[serde(default, rename = "tenant")]
+    tenants: Vec<TenantConfig>,
+    #[serde(default, rename = "host_command")]
+    host_commands: Vec<HostCommandConfig>,
+}
+
+/// A deployment file: the modules to start and the policy every module must satisfy.
+#[derive(Default)]
+pub struct Deployment {
+    /// Modules to start, in order.
+    pub modules: Vec<ModuleSpec>,
+    /// Restrictions on the modules that may be started.
+    pub policy: ModulePolicy,
+    /// Client certificates the control listener accepts.
+    pub identities: Vec<Identity>,
+    /// Further kernels to run in the same process.
+    pub tenants: Vec<Tenant>,
+    /// Host programs guests may run.
+    pub host_commands: Vec<HostCommand>,
+}
+
+/// A kernel run alongside the host's own, with its own work directory, modules and quotas.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Tenant {
+    /// Id control requests address the tenant by.
+    pub id: String,
+    /// Work directory holding the tenant's certificates, modules and deployment file.
+    pub work_dir: PathBuf,
+    /// Deployment file listing the tenant's modules; `None` reads `selium.toml` from its work
+    /// directory, if present.
+    pub config: Option<PathBuf>,
+    /// File or Unix domain socket the tenant's hostcalls are recorded to.
+    pub audit_log: Option<PathBuf>,
+    /// Pooling allocator sizing for the tenant's kernel; `None` uses the host's.
+    pub pooling: Option<PoolingLimits>,
+}
+
+/// A host program that guests holding the `HostExec` capability may run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HostCommand {
+    /// Name guests run the program by.
+    pub name: String,
+    /// Absolute path of the program.
+    pub program: PathBuf,
+    /// Arguments every invocation starts with.
+    pub args: Vec<String>,
+    /// Whether guests may append arguments of their own.
+    pub allow_args: bool,
+}
+
+/// Restrictions on the modules that may be started.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ModulePolicy {
+    /// Digests of the only modules that may be started; `None` allows any module.
+    pub allowed_digests: Option<Vec<blake3::Hash>>,
+    /// Public keys one of which must have signed each module; empty leaves modules unchecked.
+    pub allowed_signers: Vec<PathBuf>,
+}
+
+/// A `[[module]]` entry of a deployment file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleConfig {
+    /// Module path, relative to the work directory.
+    pub path: String,
+    /// Exported function to call; defaults to `start`.
+    pub entrypoint: Option<String>,
+    /// Log URI passed ahead of the entrypoint arguments.
+    pub log_uri: Option<String>,
+    /// Capabilities granted to the module.
+    pub capabilities: Option<Vec<String>>,
+    /// Entrypoint parameter kinds; inferred from typed `args` when omitted.
+    pub params: Option<Vec<String>>,
+    /// Entrypoint arguments, optionally prefixed with `TYPE:`.
+    pub args: Option<Vec<String>>,
+    /// When the module is restarted after it exits.
+    pub restart: Option<RestartPolicy>,
+    /// Most verbose level logged for the module.
+    pub log_level: Option<String>,
+    /// Where the module's logs are written: `host`, `stderr`, `file:PATH` or `json:PATH`.
+    pub log_output: Option<String>,
+    /// Access to the module's sandbox directory: `rw` or `ro`.
+    pub sandbox: Option<String>,
+    /// Directories mounted into the module's sandbox, as `PATH[:ro|:rw]`.
+    pub mounts: Option<Vec<String>>,
+    /// Execution limits for the module's process.
+    #[serde(default)]
+    pub limits: LimitsConfig,
+}
+
+/// A module manifest, supplying defaults for every specification of the module it sits next to.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleManifest {
+    /// Exported function to call.
+    pub entrypoint: Option<String>,
+    /// Log URI passed ahead of the entrypoint arguments.
+    pub log_uri: Option<String>,
+    /// Capabilities granted to the module.
+    pub capabilities: Option<Vec<String>>,
+    /// Entrypoint parameter kinds.
+    pub params: Option<Vec<String>>,
+    /// Entrypoint arguments, optionally prefixed with `TYPE:`.
+    pub args: Option<Vec<String>>,
+    /// When the module is restarted after it exits.
+    pub restart: Option<RestartPolicy>,
+    /// Most verbose level logged for the module.
+    pub log_level: Option<String>,
+    /// Where the module's logs are written: `host`, `stderr`, `file:PATH` or `json:PATH`.
+    pub log_output: Option<String>,
+    /// Access to the module's sandbox directory: `rw` or `ro`.
+    pub sandbox: Option<String>,
+    /// Directories mounted into the module's sandbox, as `PATH[:ro|:rw]`.
+    pub mounts: Option<Vec<String>>,
+    /// Execution limits for the module's process.
+    #[serde(default)]
+    pub limits: LimitsConfig,
+}
+
+/// The `[policy]` table of a deployment file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyConfig {
+    /// BLAKE3 digests of the only modules that may be started, in hex.
+    allowed_digests: Option<Vec<String>>,
+    /// Raw ed25519 public keys, relative to the work directory, trusted to sign modules.
+    #[serde(default)]
+    allowed_signers: Vec<PathBuf>,
+}
+
+/// An `[[identity]]` entry of a deployment file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IdentityConfig {
+    /// Name the identity is logged under.
+    name: String,
+    /// Client certificate, relative to the work directory.
+    certificate: PathBuf,
+    /// Capabilities the certificate's holder is entitled to.
+    #[serde(default)]
+    entitlements: Vec<String>,
+    /// Tenants the certificate's holder may address instead of the host.
+    #[serde(default)]
+    tenants: Vec<String>,
+}
+
+/// A `[[tenant]]` entry of a deployment file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantConfig {
+    /// Id control requests address the tenant by.
+    id: String,
+    /// Work directory, relative to the host's.
+    work_dir: PathBuf,
+    /// Deployment file, relative to the tenant's work directory.
+    config: Option<PathBuf>,
+    /// Audit log, relative to the tenant's work directory.
+    audit_log: Option<PathBuf>,
+    /// Quotas of the tenant's kernel.
+    #[serde(default)]
+    limits: TenantLimitsConfig,
+}
+
+/// A `[[host_command]]` entry of a deployment file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HostCommandConfig {
+    /// Name guests run the program by.
+    name: String,
+    /// Absolute path of the program.
+    program: PathBuf,
+    /// Arguments every invocation starts with.
+    #[serde(default)]
+    args: Vec<String>,
+    /// Whether guests may append arguments of their own.
+    #[serde(default)]
+    allow_args: bool,
+}
+
+/// The `[tenant.limits]` table of a deployment file entry.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantLimitsConfig {
+    /// Instances the tenant may run at once.
+    max_instances: Option<u32>,
+    /// Linear memory limit per instance, in 64 KiB Wasm pages.
+    max_memory_pages: Option<u64>,
+}
+
+/// The `[module.limits]` table of a deployment file entry.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Wasm fuel budget; unlimited when omitted.
+    pub fuel: Option<u64>,
+}
+
+impl ModulePolicy {
+    fn from_config(config: PolicyConfig, work_dir: &Path) -> Result<Self> {
+        let allowed_digests = config
+            .allowed_digests
+            .map(|digests| {
+                digests
+                    .iter()
+                    .map(|digest| {
+                        blake3::Hash::from_hex(digest.trim())
+                            .map_err(|err| anyhow!("invalid digest `{digest}`: {err}"))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?;
+        let allowed_signers = config
+            .allowed_signers
+            .iter()
+            .map(|path| work_dir.join(path))
+            .collect();
+        Ok(Self {
+            allowed_digests,
+            allowed_signers,
+        })
+    }
+}
+
+/// Read the deployment file at `path` and validate each module entry against `work_dir`.
+pub fn load(path: &Path, work_dir: &Path) -> Result<Deployment> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("read deployment file {}", path.display()))?;
+    parse(&raw, work_dir).with_context(|| format!("invalid deployment file {}", path.display()))
+}
+
+/// Read the manifest of the module at `module_path`, if it has one.
+pub fn load_manifest(module_path: &Path) -> Result<Option<ModuleManifest>> {
+    let path = module_path.with_extension(MANIFEST_EXTENSION);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| format!("read module manifest {}", path.display()));
+        }
+    };
+    parse_manifest(&raw)
+        .map(Some)
+        .with_context(|| format!("invalid module manifest {}", path.display()))
+}
+
+fn parse_manifest(raw: &str) -> Result<ModuleManifest> {
+    toml::from_str(raw).map_err(|err| anyhow!("{err}"))
+}
+
+fn parse(raw: &str, work_dir: &Path) -> Result<Deployment> {
+    let config: DeploymentConfig = toml::from_str(raw).map_err(|err| anyhow!("{err}"))?;
+    if config.modules.is_empty()
+        && config.policy.is_none()
+        && config.identities.is_empty()
+        && config.tenants.is_empty()
+        && config.host_commands.is_empty()
+    {
+        bail!("no [[module]] entries");
+    }
+
+    let modules = config
+        .modules
+        .iter()
+        .enumerate()
+        .map(|(index, module)| {
+            modules::spec_from_config(module, work_dir)
+                .with_context(|| format!("module {} (`{}`)", index + 1, module.path))
+        })
+        .collect::<Result<_>>()?;
+    let policy = config
+        .policy
+        .map(|policy| ModulePolicy::from_config(policy, work_dir))
+        .transpose()
+        .context("[policy]")?
+        .unwrap_or_default();
+    let mut tenants: Vec<Tenant> = Vec::with_capacity(config.tenants.len());
+    for (index, tenant) in config.tenants.iter().enumerate() {
+        let tenant = tenant_from_config(tenant, work_dir)
+            .with_context(|| format!("tenant {} (`{}`)", index + 1, tenant.id))?;
+        if let Some(other) = tenants
+            .iter()
+            .find(|other| other.id == tenant.id || other.work_dir == tenant.work_dir)
+        {
+            bail!(
+                "tenant {} (`{}`) shares its id or work directory with tenant `{}`",
+                index + 1,
+                tenant.id,
+                other.id
+            );
+        }
+        tenants.push(tenant);
+    }
+    let identities = config
+        .identities
+        .iter()
+        .enumerate()
+        .map(|(index, identity)| {
+            identity_from_config(identity, work_dir, &tenants)
+                .with_context(|| format!("identity {} (`{}`)", index + 1, identity.name))
+        })
+        .collect::<Result<_>>()?;
+    let mut host_commands: Vec<HostCommand> = Vec::with_capacity(config.host_commands.len());
+    for (index, command) in config.host_commands.iter().enumerate() {
+        let command = host_command_from_config(command)
+            .with_context(|| format!("host command {} (`{}`)", index + 1, command.name))?;
+        if host_commands.iter().any(|other| other.name == command.name) {
+            bail!(
+                "host command {} (`{}`) shares its name with an earlier entry",
+                index + 1,
+                command.name
+            );
+        }
+        host_commands.push(command);
+    }
+    Ok(Deployment {
+        modules,
+        policy,
+        identities,
+        tenants,
+        host_commands,
+    })
+}
+
+fn identity_from_config(
+    config: &IdentityConfig,
+    work_dir: &Path,
+    tenants: &[Tenant],
+) -> Result<Identity> {
+    let entitlements = config
+        .entitlements
+        .iter()
+        .map(|capability| modules::parse_capability(capability.trim()))
+        .collect::<Result<_>>()?;
+    if let Some(unknown) = config
+        .tenants
+        .iter()
+        .find(|id| !tenants.iter().any(|tenant| &tenant.id == *id))
+    {
+        bail!("unknown tenant `{unknown}`");
+    }
+    Ok(Identity::load(
+        &config.name,
+        &work_dir.join(&config.certificate),
+        entitlements,
+    )?
+    .with_tenants(&config.tenants))
+}
+
+fn tenant_from_config(config: &TenantConfig, work_dir: &Path) -> Result<Tenant> {
+    if config.id.is_empty()
+        || !config
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("tenant ids may only contain ASCII letters, digits, `-` and `_`");
+    }
+    let tenant_dir = work_dir.join(&config.work_dir);
+    if tenant_dir == work_dir {
+        bail!("a tenant cannot share the host's work directory");
+    }
+    let pooling = match (config.limits.max_instances, config.limits.max_memory_pages) {
+        (Some(max_instances), max_memory_pages) => Some(PoolingLimits {
+            max_instances,
+            max_memory_pages: max_memory_pages.unwrap_or(DEFAULT_TENANT_MEMORY_PAGES),
+        }),
+        (None, Some(_)) => bail!("`max_memory_pages` requires `max_instances`"),
+        (None, None) => None,
+    };
+    Ok(Tenant {
+        id: config.id.clone(),
+        config: config.config.as_ref().map(|path| tenant_dir.join(path)),
+        audit_log: config.audit_log.as_ref().map(|path| tenant_dir.join(path)),
+        work_dir: tenant_dir,
+        pooling,
+    })
+}
+
+fn host_command_from_config(config: &HostCommandConfig) -> Result<HostCommand> {
+    if config.name.trim().is_empty() {
+        bail!("host commands need a name");
+    }
+    if !config.program.is_absolute() {
+        bail!(
+            "program `{}` must be an absolute path",
+            config.program.display()
+        );
+    }
+    Ok(HostCommand {
+        name: config.name.clone(),
+        program: config.program.clone(),
+        args: config.args.clone(),
+        allow_args: config.allow_args,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use selium_abi::Capability;
+    use selium_filesystem_store::{MountAccess, SandboxPolicy};
+    use tracing::level_filters::LevelFilter;
+
+    use super::*;
+    use crate::logging::{LogOutput, ModuleLogSettings};
+
+    #[test]
+    fn modules_are_read_in_order() {
+        let raw = r#"
+            [[module]]
+            path = "modules/echo.wasm"
+            capabilities = ["channel-lifecycle", "channel-reader"]
+            args = ["utf8:hello, world", "u32:3"]
+            restart = "on-failure"
+            log_level = "debug"
+            log_output = "json:logs/echo.jsonl"
+            sandbox = "ro"
+            mounts = ["assets", "cache:rw"]
+
+            [module.limits]
+            fuel = 1000
+
+            [[module]]
+            path = "modules/idle.wasm"
+            capabilities = ["time-read"]
+        "#;
+
+        let specs = parse(raw, Path::new("work"))
+            .expect("valid deployment")
+            .modules;
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].label(), "modules/echo.wasm");
+        assert_eq!(specs[0].restart(), RestartPolicy::OnFailure);
+        assert_eq!(
+            *specs[0].log(),
+            ModuleLogSettings {
+                level: Some(LevelFilter::DEBUG),
+                output: LogOutput::Json(PathBuf::from("work/logs/echo.jsonl")),
+            }
+        );
+        assert_eq!(specs[0].sandbox().root, MountAccess::ReadOnly);
+        let mounts: Vec<_> = specs[0]
+            .sandbox()
+            .mounts
+            .iter()
+            .map(|mount| (mount.name(), mount.source(), mount.access()))
+            .collect();
+        assert_eq!(
+            mounts,
+            [
+                ("assets", Path::new("work/assets"), MountAccess::ReadOnly),
+                ("cache", Path::new("work/cache"), MountAccess::ReadWrite),
+            ]
+        );
+        assert!(specs[1].log().is_default());
+        assert_eq!(*specs[1].sandbox(), SandboxPolicy::default());
+        assert_eq!(specs[1].path(), Path::new("work/modules/idle.wasm"));
+        assert_eq!(specs[1].restart(), RestartPolicy::Never);
+    }
+
+    #[test]
+    fn policies_resolve_digests_and_signers() {
+        let digest = blake3::hash(b"module");
+        let raw = format!(
+            "[policy]\nallowed_digests = [\"{}\"]\nallowed_signers = [\"keys/release.pub\"]\n",
+            digest.to_hex()
+        );
+        let deployment = parse(&raw, Path::new("work")).expect("policy-only deployment");
+        assert!(deployment.modules.is_empty());
+        assert_eq!(
+            deployment.policy,
+            ModulePolicy {
+                allowed_digests: Some(vec![digest]),
+                allowed_signers: vec![PathBuf::from("work/keys/release.pub")],
+            }
+        );
+
+        let Err(err) = parse("[policy]\nallowed_digests = [\"beef\"]\n", Path::new(".")) else {
+            panic!("short digest accepted");
+        };
+        let message = format!("{err:#}");
+        assert!(message.contains("invalid digest `beef`"), "{message}");
+        assert!(parse("", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn tenants_resolve_against_the_host_work_dir() {
+        let raw = r#"
+            [[tenant]]
+            id = "acme"
+            work_dir = "tenants/acme"
+            config = "deploy.toml"
+            audit_log = "audit.jsonl"
+
+            [tenant.limits]
+            max_instances = 16
+
+            [[tenant]]
+            id = "globex"
+            work_dir = "tenants/globex"
+        "#;
+        let tenants = parse(raw, Path::new("work"))
+            .expect("tenant-only deployment")
+            .tenants;
+        assert_eq!(
+            tenants,
+            [
+                Tenant {
+                    id: "acme".to_string(),
+                    work_dir: PathBuf::from("work/tenants/acme"),
+                    config: Some(PathBuf::from("work/tenants/acme/deploy.toml")),
+                    audit_log: Some(PathBuf::from("work/tenants/acme/audit.jsonl")),
+                    pooling: Some(PoolingLimits {
+                        max_instances: 16,
+                        max_memory_pages: DEFAULT_TENANT_MEMORY_PAGES,
+                    }),
+                },
+                Tenant {
+                    id: "globex".to_string(),
+                    work_dir: PathBuf::from("work/tenants/globex"),
+                    config: None,
+                    audit_log: None,
+                    pooling: None,
+                },
+            ]
+        );
+
+        for (raw, expected) in [
+            (
+                "[[tenant]]\nid = \"a\"\nwork_dir = \"x\"\n[[tenant]]\nid = \"a\"\nwork_dir = \"y\"\n",
+                "shares its id or work directory with tenant `a`",
+            ),
+            (
+                "[[tenant]]\nid = \"a b\"\nwork_dir = \"x\"\n",
+                "tenant ids may only contain",
+            ),
+            (
+                "[[tenant]]\nid = \"a\"\nwork_dir = \"\"\n",
+                "cannot share the host's work directory",
+            ),
+            (
+                "[[tenant]]\nid = \"a\"\nwork_dir = \"x\"\n[tenant.limits]\nmax_memory_pages = 8\n",
+                "requires `max_instances`",
+            ),
+        ] {
+            let Err(err) = parse(raw, Path::new("work")) else {
+                panic!("invalid tenant accepted: {raw}");
+            };
+            let message = format!("{err:#}");
+            assert!(message.contains(expected), "{message}");
+        }
+    }
+
+    #[test]
+    fn host_commands_must_be_absolute_and_uniquely_named() {
+        let raw = r#"
+            [[host_command]]
+            name = "flash"
+            program = "/usr/bin/flashrom"
+            args = ["--programmer", "internal"]
+            allow_args = true
+
+            [[host_command]]
+            name = "uptime"
+            program = "/usr/bin/uptime"
+        "#;
+        let commands = parse(raw, Path::new("work"))
+            .expect("host-command-only deployment")
+            .host_commands;
+        assert_eq!(
+            commands,
+            [
+                HostCommand {
+                    name: "flash".to_string(),
+                    program: PathBuf::from("/usr/bin/flashrom"),
+                    args: vec!["--programmer".to_string(), "internal".to_string()],
+                    allow_args: true,
+                },
+                HostCommand {
+                    name: "uptime".to_string(),
+                    program: PathBuf::from("/usr/bin/uptime"),
+                    args: Vec::new(),
+                    allow_args: false,
+                },
+            ]
+        );
+
+        for (raw, expected) in [
+            (
+                "[[host_command]]\nname = \"a\"\nprogram = \"bin/a\"\n",
+                "host command 1 (`a`): program `bin/a` must be an absolute path",
+            ),
+            (
+                "[[host_command]]\nname = \"a\"\nprogram = \"/bin/a\"\n[[host_command]]\nname = \"a\"\nprogram = \"/bin/b\"\n",
+                "host command 2 (`a`) shares its name",
+            ),
+        ] {
+            let Err(err) = parse(raw, Path::new("work")) else {
+                panic!("invalid host command accepted: {raw}");
+            };
+            let message = format!("{err:#}");
+            assert!(message.contains(expected), "{message}");
+        }
+    }
+
+    #[test]
+    fn errors_name_the_offending_entry() {
+        let raw = r#"
+            [[module]]
+            path = "modules/echo.wasm"
+            capabilities = ["time-read"]
+
+            [[module]]
+            path = "modules/bad.wasm"
+            capabilities = ["teleport"]
+        "#;
+        let Err(err) = parse(raw, Path::new(".")) else {
+            panic!("unknown capability accepted");
+        };
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("module 2 (`modules/bad.wasm`)"),
+            "{message}"
+        );
+        assert!(
+            message.contains("unknown capability `teleport`"),
+            "{message}"
+        );
+
+        let Err(err) = parse(
+            "[[module]]\npath = \"a.wasm\"\ncapabilites = []\n",
+            Path::new("."),
+        ) else {
+            panic!("misspelt key accepted");
+        };
+        let message = format!("{err:#}");
+        assert!(message.contains("line 3"), "{message}");
+        assert!(message.contains("capabilites"), "{message}");
+    }
+
+    #[test]
+    fn manifests_fill_keys_a_specification_leaves_out() {
+        let work_dir = std::env::temp_dir().join(format!("selium-manifest-{}", std::process::id()));
+        let manifest = work_dir.join("modules/echo.selium.toml");
+        fs::create_dir_all(work_dir.join("modules")).expect("create modules dir");
+        fs::write(
+            &manifest,
+            "capabilities = [\"time-read\"]\nrestart = \"always\"\n",
+        )
+        .expect("write manifest");
+
+        let specs = parse(
+            "[[module]]\npath = \"modules/echo.wasm\"\nrestart = \"never\"\n",
+            &work_dir,
+        )
+        .expect("manifest supplies capabilities")
+        .modules;
+        assert_eq!(specs[0].capabilities(), Capability::TimeRead.into());
+        assert_eq!(specs[0].restart(), RestartPolicy::Never);
+
+        let spec = modules::parse_cli_spec(
+            "path=modules/echo.wasm;capabilities=channel-reader",
+            &work_dir,
+        )
+        .expect("valid specification");
+        assert_eq!(spec.capabilities(), Capability::ChannelReader.into());
+        assert_eq!(spec.restart(), RestartPolicy::Always);
+
+        fs::write(&manifest, "capabilites = []\n").expect("write manifest");
+        let Err(err) = modules::parse_cli_spec("path=modules/echo.wasm", &work_dir) else {
+            panic!("misspelt manifest key accepted");
+        };
+        let message = format!("{err:#}");
+        assert!(message.contains("echo.selium.toml"), "{message}");
+        assert!(message.contains("capabilites"), "{message}");
+
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+}
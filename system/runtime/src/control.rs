@@ -0,0 +1,1585 @@
+//! Admin control socket for managing modules on a running host.
+//!
+//! The runtime listens on a Unix domain socket for `selium.control` requests, so operators can
+//! list, start, stop and reload modules, inspect a module's pending futures, profile it, read
+//! hostcall metrics, or shut the host down, without restarting it. A host running on a stepped
+//! clock also accepts requests to advance it. Every message in either direction is a
+//! size-prefixed Flatbuffer: a little-endian `u32` length followed by the buffer. A connection
+//! may carry any number of requests, each answered in turn.
+//!
+//! The socket file is only accessible to the user running the host, from the moment it appears.
+//!
+//! The same requests may also be served over TCP with mutual TLS, so that hosts can be managed
+//! from elsewhere. Clients must present a certificate issued by the runtime's CA and pinned by an
+//! [`Identity`](crate::identity::Identity); each connection is served under its own session, and
+//! requests that change what runs on the host are refused unless that session is entitled to
+//! the capability they need.
+//!
+//! A host running tenants serves them through the same socket and listener: a request naming a
+//! tenant is served against that tenant's kernel, and one naming none against the host's own.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    net::SocketAddr,
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::UnixStream as StdUnixStream,
+    },
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use flatbuffers::{FlatBufferBuilder, UnionWIPOffset, WIPOffset};
+use rustls::{
+    ClientConfig, RootCertStore, ServerConfig, crypto::ring::default_provider,
+    pki_types::ServerName, server::WebPkiClientVerifier, version::TLS13,
+};
+use selium_abi::{
+    ErrorCode, FutureDiagnostics, FutureState, InstanceDiagnostics, MailboxDiagnostics, SlotUsage,
+    TimeNow,
+};
+use selium_filesystem_store::{CacheCounters, CacheMetrics};
+use selium_kernel::{
+    drivers::{Capability, time::SteppedTimeService},
+    metrics::{
+        GuestMetrics, GuestMetricsSnapshot, HostcallMetrics, HostcallStats, LATENCY_BUCKETS_US,
+        LatencyHistogram,
+    },
+    registry::{Registry, ResourceId},
+    session::Session,
+};
+use selium_userland::fbs::selium::control as control_fb;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UnixListener, UnixStream},
+    sync::Notify,
+    time::sleep,
+};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::{debug, info, warn};
+
+use crate::{
+    Runtime,
+    identity::{Identities, Principal},
+    kernel,
+    logging::LogOutput,
+    modules::{self, ModuleSpec},
+    profile,
+    supervisor::{ModuleState, ModuleStatus, Supervisor},
+};
+
+/// Largest message accepted on the control socket.
+const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+/// Length of the size prefix ahead of each message.
+const PREFIX_LEN: usize = size_of::<u32>();
+/// Pause after a failed accept, so that exhausted descriptors do not spin the accept loop.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+/// Access mode of the socket file.
+const SOCKET_MODE: u32 = 0o600;
+/// Access mode of the directory the socket is bound in before it is moved into place.
+const STAGING_MODE: u32 = 0o700;
+/// Longest profile a control request may ask for.
+const MAX_PROFILE_SECONDS: u32 = 600;
+
+/// A bound control socket. The socket file is removed when this is dropped.
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+/// Control requests served against a running host, shared by the control socket and listener.
+#[derive(Clone)]
+pub struct ControlService {
+    handler: Arc<Handler>,
+}
+
+/// The modules, clock and metrics of one kernel, which control requests are served against.
+pub struct ControlTarget {
+    supervisor: Supervisor,
+    work_dir: PathBuf,
+    clock: Option<SteppedTimeService>,
+    metrics: Option<HostcallMetrics>,
+    guest_metrics: Option<GuestMetrics>,
+    module_cache: Option<CacheCounters>,
+}
+
+/// Connection to the control socket or listener of a running host.
+pub struct ControlClient {
+    stream: Box<dyn ControlStream>,
+    tenant: Option<String>,
+}
+
+/// A byte stream carrying control messages.
+trait ControlStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+/// What a start request starts.
+#[derive(Clone, Copy, Debug)]
+pub enum StartCommand<'a> {
+    /// A new module, from a `--module` style specification.
+    Spec(&'a str),
+    /// A supervised module that is not running, by label or process id.
+    Target(&'a str),
+}
+
+/// Hostcall and guest metrics as reported by a running host.
+#[derive(Debug)]
+pub struct MetricsReport {
+    /// Upper bounds of the latency buckets, in microseconds.
+    pub latency_bounds_us: Vec<u64>,
+    /// Counters of every hostcall called at least once, ordered by module name.
+    pub hostcalls: Vec<HostcallStats>,
+    /// Counters and gauges named by guests.
+    pub guest: GuestMetricsSnapshot,
+    /// How reads through the host's module cache were served, if it caches modules.
+    pub module_cache: Option<CacheMetrics>,
+}
+
+/// A profile written by a running host.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ProfileReport {
+    /// Process that was profiled.
+    pub process_id: ResourceId,
+    /// Path of the folded stacks file on the host.
+    pub path: PathBuf,
+    /// Number of stack samples collected.
+    pub samples: u64,
+}
+
+/// Serves requests from control socket connections.
+struct Handler {
+    host: ControlTarget,
+    tenants: BTreeMap<String, ControlTarget>,
+    shutdown: Arc<Notify>,
+}
+
+/// Outcome of a control request.
+enum Reply {
+    Modules(Vec<ModuleStatus>),
+    Started(ResourceId),
+    Done,
+    Failure(String),
+    Clock(TimeNow),
+    Metrics(
+        Vec<HostcallStats>,
+        GuestMetricsSnapshot,
+        Option<CacheMetrics>,
+    ),
+    Diagnostics(ResourceId, InstanceDiagnostics),
+    Profile(ProfileReport),
+}
+
+impl<S> ControlStream for S where S: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl ControlService {
+    /// Serve requests against `host`, or against the entry of `tenants` a request names. A
+    /// shutdown request notifies `shutdown`.
+    pub fn new(
+        host: ControlTarget,
+        tenants: BTreeMap<String, ControlTarget>,
+        shutdown: Arc<Notify>,
+    ) -> Self {
+        Self {
+            handler: Arc::new(Handler {
+                host,
+                tenants,
+                shutdown,
+            }),
+        }
+    }
+}
+
+impl ControlTarget {
+    /// Serve requests against the modules `runtime` supervises. Module paths in start requests
+    /// are resolved against `work_dir`, and requests to advance the clock are refused unless
+    /// the runtime's kernel runs on a stepped clock. Metrics requests are answered from the
+    /// kernel's hostcall metrics, if it collects them, and its guest metrics.
+    pub fn new(runtime: &Runtime, work_dir: &Path) -> Self {
+        let kernel = runtime.kernel();
+        Self {
+            supervisor: runtime.supervisor().clone(),
+            work_dir: work_dir.to_path_buf(),
+            clock: kernel.get::<SteppedTimeService>().cloned(),
+            metrics: kernel.get::<HostcallMetrics>().cloned(),
+            guest_metrics: kernel.get::<GuestMetrics>().cloned(),
+            module_cache: kernel.get::<CacheCounters>().cloned(),
+        }
+    }
+}
+
+impl ControlSocket {
+    /// Listen on `path` and serve requests with `service`.
+    ///
+    /// A socket file left behind by a runtime that is no longer running is replaced; one that
+    /// still accepts connections is not.
+    pub fn bind(path: &Path, service: &ControlService) -> Result<Self> {
+        remove_stale(path)?;
+        let listener = bind_private(path)?;
+        let socket = Self {
+            path: path.to_path_buf(),
+        };
+        info!(path = %path.display(), "control socket listening");
+
+        let handler = Arc::clone(&service.handler);
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let handler = Arc::clone(&handler);
+                        tokio::spawn(async move {
+                            if let Err(err) = handler.serve(stream, None).await {
+                                debug!(err = format!("{err:#}"), "control connection closed");
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        warn!(err = %err, "control socket accept failed");
+                        sleep(ACCEPT_BACKOFF).await;
+                    }
+                }
+            }
+        });
+
+        Ok(socket)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path)
+            && err.kind() != ErrorKind::NotFound
+        {
+            warn!(path = %self.path.display(), err = %err, "failed to remove control socket");
+        }
+    }
+}
+
+impl ControlClient {
+    /// Connect to the control socket at `path`.
+    pub async fn connect(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path)
+            .await
+            .with_context(|| format!("connect to control socket {}", path.display()))?;
+        Ok(Self {
+            stream: Box::new(stream),
+            tenant: None,
+        })
+    }
+
+    /// Connect to the control listener at `addr`, given as `HOST:PORT` with the host name the
+    /// listener's certificate was issued for. The client presents `client.crt` from `certs_dir`
+    /// and trusts listeners whose certificate was issued by `ca.crt`.
+    pub async fn connect_tls(addr: &str, certs_dir: &Path) -> Result<Self> {
+        let (host, _) = addr
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("control listener address `{addr}` is not HOST:PORT"))?;
+        let server_name = ServerName::try_from(host.trim_matches(['[', ']']).to_string())
+            .with_context(|| format!("invalid control listener host `{host}`"))?;
+        let stream = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("connect to control listener {addr}"))?;
+        let stream = TlsConnector::from(client_tls(certs_dir)?)
+            .connect(server_name, stream)
+            .await
+            .with_context(|| format!("TLS handshake with control listener {addr}"))?;
+        Ok(Self {
+            stream: Box::new(stream),
+            tenant: None,
+        })
+    }
+
+    /// Address every further request to the kernel of the tenant `id`, rather than the host's.
+    pub fn tenant(mut self, id: impl Into<String>) -> Self {
+        self.tenant = Some(id.into());
+        self
+    }
+
+    /// Status of every module supervised by the host.
+    pub async fn list(&mut self) -> Result<Vec<ModuleStatus>> {
+        let response = self
+            .call(|builder| {
+                let list =
+                    control_fb::ListModules::create(builder, &control_fb::ListModulesArgs {});
+                (
+                    control_fb::ControlCommand::ListModules,
+                    list.as_union_value(),
+                )
+            })
+            .await?;
+        let response = decode_response(&response)?;
+        let modules = response
+            .reply_as_module_list()
+            .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?
+            .modules()
+            .unwrap_or_default();
+        modules.iter().map(decode_status).collect()
+    }
+
+    /// Start a module from a `--module` style specification, or start the stopped module
+    /// identified by `target`, returning its process id.
+    pub async fn start(&mut self, command: StartCommand<'_>) -> Result<ResourceId> {
+        let response = self
+            .call(|builder| {
+                let (spec, target) = match command {
+                    StartCommand::Spec(spec) => (Some(builder.create_string(spec)), None),
+                    StartCommand::Target(target) => (None, Some(builder.create_string(target))),
+                };
+                let start = control_fb::StartModule::create(
+                    builder,
+                    &control_fb::StartModuleArgs { spec, target },
+                );
+                (
+                    control_fb::ControlCommand::StartModule,
+                    start.as_union_value(),
+                )
+            })
+            .await?;
+        started(&response)
+    }
+
+    /// Stop the module identified by its label or process id.
+    pub async fn stop(&mut self, target: &str) -> Result<()> {
+        self.call(|builder| {
+            let target = Some(builder.create_string(target));
+            let stop =
+                control_fb::StopModule::create(builder, &control_fb::StopModuleArgs { target });
+            (
+                control_fb::ControlCommand::StopModule,
+                stop.as_union_value(),
+            )
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Restart the module identified by its label or process id, returning the new process id.
+    pub async fn restart(&mut self, target: &str) -> Result<ResourceId> {
+        let response = self
+            .call(|builder| {
+                let target = Some(builder.create_string(target));
+                let restart = control_fb::RestartModule::create(
+                    builder,
+                    &control_fb::RestartModuleArgs { target },
+                );
+                (
+                    control_fb::ControlCommand::RestartModule,
+                    restart.as_union_value(),
+                )
+            })
+            .await?;
+        started(&response)
+    }
+
+    /// Advance the host's stepped clock by `step`, returning the time it now reads.
+    pub async fn advance_clock(&mut self, step: Duration) -> Result<TimeNow> {
+        let millis = u64::try_from(step.as_millis()).context("clock step out of range")?;
+        let response = self
+            .call(|builder| {
+                let advance = control_fb::AdvanceClock::create(
+                    builder,
+                    &control_fb::AdvanceClockArgs { millis },
+                );
+                (
+                    control_fb::ControlCommand::AdvanceClock,
+                    advance.as_union_value(),
+                )
+            })
+            .await?;
+        let response = decode_response(&response)?;
+        let time = response
+            .reply_as_clock_time()
+            .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?;
+        Ok(TimeNow {
+            unix_ms: time.unix_ms(),
+            monotonic_ms: time.monotonic_ms(),
+        })
+    }
+
+    /// Pending futures, mailbox counters and slot usage of the running module identified by its
+    /// label or process id, with its process id.
+    pub async fn diagnose(&mut self, target: &str) -> Result<(ResourceId, InstanceDiagnostics)> {
+        let response = self
+            .call(|builder| {
+                let target = Some(builder.create_string(target));
+                let diagnose =
+                    control_fb::Diagnose::create(builder, &control_fb::DiagnoseArgs { target });
+                (
+                    control_fb::ControlCommand::Diagnose,
+                    diagnose.as_union_value(),
+                )
+            })
+            .await?;
+        let response = decode_response(&response)?;
+        let diagnostics = response
+            .reply_as_instance_diagnostics()
+            .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?;
+        decode_diagnostics(diagnostics)
+    }
+
+    /// Sample the stack of the running module identified by its label or process id for
+    /// `seconds`, returning where the host wrote the profile. The call returns once the profile
+    /// is complete.
+    pub async fn profile(&mut self, target: &str, seconds: u32) -> Result<ProfileReport> {
+        let response = self
+            .call(|builder| {
+                let target = Some(builder.create_string(target));
+                let profile = control_fb::Profile::create(
+                    builder,
+                    &control_fb::ProfileArgs { target, seconds },
+                );
+                (
+                    control_fb::ControlCommand::Profile,
+                    profile.as_union_value(),
+                )
+            })
+            .await?;
+        let response = decode_response(&response)?;
+        let written = response
+            .reply_as_profile_written()
+            .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?;
+        decode_profile(written)
+    }
+
+    /// Call counts, error counts and latency histograms of the host's hostcalls, and the
+    /// metrics its guests keep.
+    pub async fn metrics(&mut self) -> Result<MetricsReport> {
+        let response = self
+            .call(|builder| {
+                let metrics = control_fb::Metrics::create(builder, &control_fb::MetricsArgs {});
+                (
+                    control_fb::ControlCommand::Metrics,
+                    metrics.as_union_value(),
+                )
+            })
+            .await?;
+        let response = decode_response(&response)?;
+        let report = response
+            .reply_as_metrics_report()
+            .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?;
+        Ok(MetricsReport {
+            latency_bounds_us: report
+                .latency_bounds_us()
+                .unwrap_or_default()
+                .iter()
+                .collect(),
+            hostcalls: report
+                .hostcalls()
+                .unwrap_or_default()
+                .iter()
+                .map(decode_hostcall_stats)
+                .collect(),
+            guest: GuestMetricsSnapshot {
+                counters: report
+                    .guest_counters()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|counter| {
+                        (
+                            counter.name().unwrap_or_default().to_string(),
+                            counter.value(),
+                        )
+                    })
+                    .collect(),
+                gauges: report
+                    .guest_gauges()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|gauge| (gauge.name().unwrap_or_default().to_string(), gauge.value()))
+                    .collect(),
+            },
+            module_cache: report.module_cache().map(|cache| CacheMetrics {
+                hits: cache.hits(),
+                misses: cache.misses(),
+                integrity_failures: cache.integrity_failures(),
+            }),
+        })
+    }
+
+    /// Send the command built by `command` and return the size-prefixed response, or the
+    /// host's error if the command failed.
+    async fn call<F>(&mut self, command: F) -> Result<Vec<u8>>
+    where
+        F: for<'bldr> FnOnce(
+            &mut FlatBufferBuilder<'bldr>,
+        ) -> (control_fb::ControlCommand, WIPOffset<UnionWIPOffset>),
+    {
+        let mut builder = FlatBufferBuilder::new();
+        let (command_type, command) = command(&mut builder);
+        let tenant = self
+            .tenant
+            .as_deref()
+            .map(|tenant| builder.create_string(tenant));
+        let request = control_fb::ControlRequest::create(
+            &mut builder,
+            &control_fb::ControlRequestArgs {
+                command_type,
+                command: Some(command),
+                tenant,
+            },
+        );
+        control_fb::finish_size_prefixed_control_request_buffer(&mut builder, request);
+        self.stream
+            .write_all(builder.finished_data())
+            .await
+            .context("write control request")?;
+
+        let response = read_message(&mut self.stream)
+            .await?
+            .ok_or_else(|| anyhow!("control socket closed before replying"))?;
+        if let Some(failure) = decode_response(&response)?.reply_as_failure() {
+            bail!("{}", failure.message().unwrap_or("request failed"));
+        }
+        Ok(response)
+    }
+}
+
+impl Handler {
+    /// Serve requests from `stream` until it closes, authorising them against `principal` if
+    /// the client connected remotely.
+    async fn serve(
+        &self,
+        mut stream: impl AsyncRead + AsyncWrite + Unpin,
+        principal: Option<&Principal>,
+    ) -> Result<()> {
+        while let Some(request) = read_message(&mut stream).await? {
+            let reply = match self.dispatch(&request, principal).await {
+                Ok(reply) => reply,
+                Err(err) => Reply::Failure(format!("{err:#}")),
+            };
+            stream
+                .write_all(&encode_reply(&reply))
+                .await
+                .context("write control response")?;
+        }
+        Ok(())
+    }
+
+    /// Complete the TLS handshake on `stream`, map the client's certificate to its identity
+    /// and serve the connection under a session for that identity.
+    async fn serve_remote(
+        &self,
+        acceptor: TlsAcceptor,
+        stream: TcpStream,
+        identities: &Identities,
+        root: &Session,
+        registry: &Arc<Registry>,
+    ) -> Result<()> {
+        let stream = acceptor.accept(stream).await.context("TLS handshake")?;
+        let certificate = stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|chain| chain.first())
+            .ok_or_else(|| anyhow!("client presented no certificate"))?;
+        let Some(identity) = identities.resolve(certificate.as_ref()) else {
+            warn!(
+                fingerprint = %blake3::hash(certificate.as_ref()),
+                "audit: control client refused; certificate is not pinned by any identity"
+            );
+            bail!("unknown client certificate");
+        };
+        let principal = Principal::open(registry, root, identity)?;
+        info!(
+            identity = principal.name(),
+            session = principal.session(),
+            entitlements = ?identity.entitlements(),
+            "control client connected"
+        );
+        self.serve(stream, Some(&principal)).await
+    }
+
+    /// The kernel of the tenant `id`, or the host's own if `id` is `None`.
+    fn target(&self, id: Option<&str>) -> Result<&ControlTarget> {
+        match id {
+            None => Ok(&self.host),
+            Some(id) => self
+                .tenants
+                .get(id)
+                .ok_or_else(|| anyhow!("unknown tenant `{id}`")),
+        }
+    }
+
+    async fn dispatch(&self, request: &[u8], principal: Option<&Principal>) -> Result<Reply> {
+        let request = control_fb::size_prefixed_root_as_control_request(request)
+            .map_err(|err| anyhow!("decode control request: {err}"))?;
+        if let Some(principal) = principal {
+            authorise(principal, &request)?;
+        }
+        let tenant = self.target(request.tenant())?;
+        match request.command_type() {
+            control_fb::ControlCommand::ListModules => {
+                Ok(Reply::Modules(tenant.supervisor.list().await))
+            }
+            control_fb::ControlCommand::StartModule => {
+                let start = request
+                    .command_as_start_module()
+                    .ok_or_else(|| anyhow!("malformed start request"))?;
+                let process_id = match (start.spec(), start.target()) {
+                    (Some(raw), None) => {
+                        let spec = modules::parse_cli_spec(raw, &tenant.work_dir)?;
+                        if let Some(principal) = principal {
+                            admit(principal, &spec)?;
+                        }
+                        tenant.supervisor.add(spec).await?
+                    }
+                    (None, Some(target)) => tenant.supervisor.start(target).await?,
+                    _ => bail!("start request needs exactly one of a specification or a target"),
+                };
+                Ok(Reply::Started(process_id))
+            }
+            control_fb::ControlCommand::StopModule => {
+                let target = request
+                    .command_as_stop_module()
+                    .and_then(|stop| stop.target())
+                    .ok_or_else(|| anyhow!("stop request has no target"))?;
+                tenant.supervisor.stop(target).await?;
+                Ok(Reply::Done)
+            }
+            control_fb::ControlCommand::ReloadModule => {
+                let target = request
+                    .command_as_reload_module()
+                    .and_then(|reload| reload.target())
+                    .ok_or_else(|| anyhow!("reload request has no target"))?;
+                Ok(Reply::Started(tenant.supervisor.reload(target).await?))
+            }
+            control_fb::ControlCommand::RestartModule => {
+                let target = request
+                    .command_as_restart_module()
+                    .and_then(|restart| restart.target())
+                    .ok_or_else(|| anyhow!("restart request has no target"))?;
+                Ok(Reply::Started(tenant.supervisor.restart(target).await?))
+            }
+            control_fb::ControlCommand::Shutdown => {
+                if let Some(id) = request.tenant() {
+                    bail!("shutdown stops the whole host, not tenant `{id}`; send it without one");
+                }
+                info!("shutdown requested over the control socket");
+                self.shutdown.notify_one();
+                Ok(Reply::Done)
+            }
+            control_fb::ControlCommand::AdvanceClock => {
+                let millis = request
+                    .command_as_advance_clock()
+                    .ok_or_else(|| anyhow!("malformed advance clock request"))?
+                    .millis();
+                let clock = tenant
+                    .clock
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("the host is not running on a stepped clock"))?;
+                let now = clock.advance(Duration::from_millis(millis));
+                info!(
+                    step_ms = millis,
+                    monotonic_ms = now.monotonic_ms,
+                    "advanced stepped clock"
+                );
+                Ok(Reply::Clock(now))
+            }
+            control_fb::ControlCommand::Diagnose => {
+                let target = request
+                    .command_as_diagnose()
+                    .and_then(|diagnose| diagnose.target())
+                    .ok_or_else(|| anyhow!("diagnose request has no target"))?;
+                let (process_id, diagnostics) = tenant.supervisor.diagnostics(target).await?;
+                Ok(Reply::Diagnostics(process_id, diagnostics))
+            }
+            control_fb::ControlCommand::Profile => {
+                let profile_request = request
+                    .command_as_profile()
+                    .ok_or_else(|| anyhow!("malformed profile request"))?;
+                let target = profile_request
+                    .target()
+                    .ok_or_else(|| anyhow!("profile request has no target"))?;
+                let seconds = profile_request.seconds();
+                if !(1..=MAX_PROFILE_SECONDS).contains(&seconds) {
+                    bail!("profile duration must be between 1 and {MAX_PROFILE_SECONDS} seconds");
+                }
+                let (process_id, stacks) = tenant
+                    .supervisor
+                    .profile(target, Duration::from_secs(seconds.into()))
+                    .await?;
+                let path = profile::write(&tenant.work_dir, process_id, &stacks)?;
+                info!(path = %path.display(), samples = stacks.samples(), "wrote profile");
+                Ok(Reply::Profile(ProfileReport {
+                    process_id,
+                    path,
+                    samples: stacks.samples(),
+                }))
+            }
+            control_fb::ControlCommand::Metrics => {
+                let metrics = tenant
+                    .metrics
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("the host does not collect hostcall metrics"))?;
+                let guest = tenant
+                    .guest_metrics
+                    .as_ref()
+                    .map(GuestMetrics::snapshot)
+                    .unwrap_or_default();
+                let module_cache = tenant.module_cache.as_ref().map(CacheCounters::snapshot);
+                Ok(Reply::Metrics(metrics.snapshot(), guest, module_cache))
+            }
+            other => bail!("unsupported control command {other:?}"),
+        }
+    }
+}
+
+/// Listen on `addr` for TLS connections and serve their requests with `service`. The
+/// listener presents `server.crt` from `certs_dir` and accepts clients whose certificate
+/// was issued by `ca.crt` and is pinned by one of `identities`. Each client is served under
+/// a session derived from `root` and registered in `registry` while it stays connected.
+pub async fn listen(
+    addr: SocketAddr,
+    certs_dir: &Path,
+    service: &ControlService,
+    identities: Identities,
+    root: Session,
+    registry: Arc<Registry>,
+) -> Result<()> {
+    let acceptor = TlsAcceptor::from(server_tls(certs_dir)?);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind control listener {addr}"))?;
+    let local_addr = listener
+        .local_addr()
+        .context("read control listener address")?;
+    if identities.is_empty() {
+        warn!(%local_addr, "control listener has no [[identity]] entries; every client will be refused");
+    }
+    info!(%local_addr, "control listener listening");
+
+    let handler = Arc::clone(&service.handler);
+    let access = Arc::new((identities, root));
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    let handler = Arc::clone(&handler);
+                    let acceptor = acceptor.clone();
+                    let access = Arc::clone(&access);
+                    let registry = Arc::clone(&registry);
+                    tokio::spawn(async move {
+                        let (identities, root) = &*access;
+                        if let Err(err) = handler
+                            .serve_remote(acceptor, stream, identities, root, &registry)
+                            .await
+                        {
+                            debug!(%peer, err = format!("{err:#}"), "control connection closed");
+                        }
+                    });
+                }
+                Err(err) => {
+                    warn!(err = %err, "control listener accept failed");
+                    sleep(ACCEPT_BACKOFF).await;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Listen on `path` through a socket file only the current user may connect to. The socket is
+/// bound inside a private directory beside `path`, restricted and only then moved into place,
+/// so it is never reachable with looser permissions, whatever the umask.
+fn bind_private(path: &Path) -> Result<UnixListener> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("control socket {} has no file name", path.display()))?;
+    let staging = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::DirBuilder::new()
+        .mode(STAGING_MODE)
+        .create(&staging)
+        .with_context(|| format!("create control socket directory {}", staging.display()))?;
+
+    let staged = staging.join("socket");
+    let bound = UnixListener::bind(&staged)
+        .with_context(|| format!("bind control socket {}", path.display()))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(SOCKET_MODE))
+                .with_context(|| format!("restrict access to control socket {}", path.display()))?;
+            fs::rename(&staged, path)
+                .with_context(|| format!("move control socket into {}", path.display()))?;
+            Ok(listener)
+        });
+    if let Err(err) = fs::remove_dir_all(&staging) {
+        debug!(path = %staging.display(), %err, "failed to remove control socket directory");
+    }
+    bound
+}
+
+/// Remove a socket file left at `path` by a runtime that is no longer listening on it.
+fn remove_stale(path: &Path) -> Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("inspect control socket {}", path.display()));
+        }
+    };
+    if !metadata.file_type().is_socket() {
+        bail!("{} exists and is not a socket", path.display());
+    }
+    if StdUnixStream::connect(path).is_ok() {
+        bail!(
+            "control socket {} is in use by another runtime",
+            path.display()
+        );
+    }
+
+    debug!(path = %path.display(), "removing stale control socket");
+    fs::remove_file(path).with_context(|| format!("remove stale control socket {}", path.display()))
+}
+
+/// Refuse `request` unless `principal` may address its tenant and holds the capability its
+/// command requires.
+fn authorise(principal: &Principal, request: &control_fb::ControlRequest<'_>) -> Result<()> {
+    let command = request.command_type();
+    if !principal.may_address(request.tenant()) {
+        warn!(
+            identity = principal.name(),
+            ?command,
+            tenant = request.tenant(),
+            "audit: control request denied"
+        );
+        match request.tenant() {
+            Some(id) => bail!(
+                "identity `{}` may not address tenant `{id}`",
+                principal.name()
+            ),
+            None => bail!("identity `{}` may not address the host", principal.name()),
+        }
+    }
+    if let Some(capability) = required_capability(command)
+        && !principal.entitled(capability)
+    {
+        warn!(
+            identity = principal.name(),
+            ?command,
+            ?capability,
+            "audit: control request denied"
+        );
+        bail!(
+            "identity `{}` is not entitled to {capability:?}",
+            principal.name()
+        );
+    }
+    Ok(())
+}
+
+/// Refuse a specification `principal` submitted if it grants capabilities the principal is not
+/// entitled to, or reaches the host's filesystem outside the module's own sandbox: log files and
+/// mounts are left to operators on the host.
+fn admit(principal: &Principal, spec: &ModuleSpec) -> Result<()> {
+    let withheld = principal.withheld(spec.capabilities());
+    if !withheld.is_empty() {
+        warn!(
+            identity = principal.name(),
+            module = spec.label(),
+            ?withheld,
+            "audit: control request denied"
+        );
+        bail!(
+            "identity `{}` cannot grant {withheld:?} it is not entitled to",
+            principal.name()
+        );
+    }
+    let logs_to_file = matches!(spec.log().output, LogOutput::File(_) | LogOutput::Json(_));
+    if logs_to_file || !spec.sandbox().mounts.is_empty() {
+        warn!(
+            identity = principal.name(),
+            module = spec.label(),
+            logs_to_file,
+            mounts = spec.sandbox().mounts.len(),
+            "audit: control request denied"
+        );
+        bail!(
+            "identity `{}` may not set log files or mounts",
+            principal.name()
+        );
+    }
+    Ok(())
+}
+
+/// Capability a remote client's session must hold to send `command`. Requests that only read
+/// the host's state need none.
+fn required_capability(command: control_fb::ControlCommand) -> Option<Capability> {
+    match command {
+        control_fb::ControlCommand::ListModules
+        | control_fb::ControlCommand::Diagnose
+        | control_fb::ControlCommand::Metrics => None,
+        control_fb::ControlCommand::Shutdown => Some(Capability::HostShutdown),
+        _ => Some(Capability::ProcessLifecycle),
+    }
+}
+
+/// TLS configuration of the control listener: `server.crt` and `server.key` from `certs_dir`,
+/// requiring a client certificate issued by `ca.crt`.
+fn server_tls(certs_dir: &Path) -> Result<Arc<ServerConfig>> {
+    let provider = Arc::new(default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(
+        Arc::new(load_roots(&certs_dir.join("ca.crt"))?),
+        Arc::clone(&provider),
+    )
+    .build()
+    .context("build client certificate verifier")?;
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&TLS13])
+        .context("select TLS versions")?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            kernel::load_certificate_chain(&certs_dir.join("server.crt"))?,
+            kernel::load_private_key(&certs_dir.join("server.key"))?,
+        )
+        .context("load control listener certificate")?;
+    Ok(Arc::new(config))
+}
+
+/// TLS configuration of a control client: `client.crt` and `client.key` from `certs_dir`,
+/// trusting listeners whose certificate was issued by `ca.crt`.
+fn client_tls(certs_dir: &Path) -> Result<Arc<ClientConfig>> {
+    let config = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+        .with_protocol_versions(&[&TLS13])
+        .context("select TLS versions")?
+        .with_root_certificates(load_roots(&certs_dir.join("ca.crt"))?)
+        .with_client_auth_cert(
+            kernel::load_certificate_chain(&certs_dir.join("client.crt"))?,
+            kernel::load_private_key(&certs_dir.join("client.key"))?,
+        )
+        .context("load control client certificate")?;
+    Ok(Arc::new(config))
+}
+
+fn load_roots(path: &Path) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for certificate in kernel::load_certificate_chain(path)? {
+        roots
+            .add(certificate)
+            .with_context(|| format!("trust CA certificate {}", path.display()))?;
+    }
+    Ok(roots)
+}
+
+/// Read one size-prefixed message, including its prefix, or `None` if the peer closed the
+/// connection. Flatbuffer alignment is relative to the prefix, so it is kept for decoding.
+async fn read_message(stream: &mut (impl AsyncRead + Unpin)) -> Result<Option<Vec<u8>>> {
+    let len = match stream.read_u32_le().await {
+        Ok(len) => len,
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err).context("read control message length"),
+    };
+    if len as usize > MAX_MESSAGE_LEN {
+        bail!("control message of {len} bytes exceeds the {MAX_MESSAGE_LEN} byte limit");
+    }
+
+    let mut message = vec![0; PREFIX_LEN + len as usize];
+    message[..PREFIX_LEN].copy_from_slice(&len.to_le_bytes());
+    stream
+        .read_exact(&mut message[PREFIX_LEN..])
+        .await
+        .context("read control message")?;
+    Ok(Some(message))
+}
+
+/// Process id carried by a `Started` reply.
+fn started(response: &[u8]) -> Result<ResourceId> {
+    let response = decode_response(response)?;
+    let started = response
+        .reply_as_started()
+        .ok_or_else(|| anyhow!("unexpected reply {:?}", response.reply_type()))?;
+    ResourceId::try_from(started.process_id()).context("process id out of range")
+}
+
+fn decode_response(response: &[u8]) -> Result<control_fb::ControlResponse<'_>> {
+    flatbuffers::size_prefixed_root::<control_fb::ControlResponse>(response)
+        .map_err(|err| anyhow!("decode control response: {err}"))
+}
+
+fn decode_status(status: control_fb::ModuleStatus<'_>) -> Result<ModuleStatus> {
+    let capabilities = status
+        .capabilities()
+        .unwrap_or_default()
+        .iter()
+        .map(modules::parse_capability)
+        .collect::<Result<_>>()?;
+    Ok(ModuleStatus {
+        label: status.label().unwrap_or_default().to_string(),
+        process_id: ResourceId::try_from(status.process_id()).context("process id out of range")?,
+        state: match status.state() {
+            control_fb::ModuleState::Running => ModuleState::Running,
+            control_fb::ModuleState::RestartPending => ModuleState::RestartPending,
+            control_fb::ModuleState::Exited => ModuleState::Exited,
+            control_fb::ModuleState::Stopped => ModuleState::Stopped,
+            other => bail!("unknown module state {other:?}"),
+        },
+        capabilities,
+        uptime: Duration::from_millis(status.uptime_ms()),
+        restarts: status.restarts(),
+        fuel_consumed: status.fuel_consumed(),
+    })
+}
+
+/// Profile location carried by a `ProfileWritten` reply.
+fn decode_profile(written: control_fb::ProfileWritten<'_>) -> Result<ProfileReport> {
+    Ok(ProfileReport {
+        process_id: ResourceId::try_from(written.process_id())
+            .context("process id out of range")?,
+        path: PathBuf::from(
+            written
+                .path()
+                .ok_or_else(|| anyhow!("profile reply has no path"))?,
+        ),
+        samples: written.samples(),
+    })
+}
+
+/// Process id and diagnostics carried by an `InstanceDiagnostics` reply.
+fn decode_diagnostics(
+    diagnostics: control_fb::InstanceDiagnostics<'_>,
+) -> Result<(ResourceId, InstanceDiagnostics)> {
+    let futures = diagnostics
+        .futures()
+        .unwrap_or_default()
+        .iter()
+        .map(|future| {
+            Ok(FutureDiagnostics {
+                handle: future.handle(),
+                state: match future.state() {
+                    control_fb::FutureState::Pending => FutureState::Pending,
+                    control_fb::FutureState::Resolved => FutureState::Resolved,
+                    control_fb::FutureState::Complete => FutureState::Complete,
+                    control_fb::FutureState::Abandoned => FutureState::Abandoned,
+                    other => bail!("unknown future state {other:?}"),
+                },
+                queued: future.queued(),
+                waiting: future.waiting(),
+            })
+        })
+        .collect::<Result<_>>()?;
+    let slot_usage = |usage: Option<control_fb::SlotUsage<'_>>| {
+        usage.map_or_else(SlotUsage::default, |usage| SlotUsage {
+            live: usage.live(),
+            allocated: usage.allocated(),
+        })
+    };
+    Ok((
+        ResourceId::try_from(diagnostics.process_id()).context("process id out of range")?,
+        InstanceDiagnostics {
+            futures,
+            mailbox: diagnostics.mailbox().map(|mailbox| MailboxDiagnostics {
+                signalled: mailbox.signalled(),
+                undrained: mailbox.undrained(),
+                flagged: mailbox.flagged(),
+                closed: mailbox.closed(),
+            }),
+            slots: slot_usage(diagnostics.slots()),
+            future_slots: slot_usage(diagnostics.future_slots()),
+            tags: diagnostics
+                .tags()
+                .unwrap_or_default()
+                .iter()
+                .map(str::to_string)
+                .collect(),
+        },
+    ))
+}
+
+/// Hostcall counters carried by a `MetricsReport` reply. Error codes unknown to this build are
+/// counted as [`ErrorCode::Unknown`].
+fn decode_hostcall_stats(stats: control_fb::HostcallStats<'_>) -> HostcallStats {
+    let mut errors: Vec<(ErrorCode, u64)> = Vec::new();
+    for error in stats.errors().unwrap_or_default() {
+        let code = ErrorCode::try_from(error.code()).unwrap_or(ErrorCode::Unknown);
+        match errors.iter_mut().find(|(known, _)| *known == code) {
+            Some((_, count)) => *count += error.count(),
+            None => errors.push((code, error.count())),
+        }
+    }
+    HostcallStats {
+        module: stats.module().unwrap_or_default().to_string(),
+        calls: stats.calls(),
+        errors,
+        latency: LatencyHistogram {
+            buckets: stats.latency_buckets().unwrap_or_default().iter().collect(),
+            total: Duration::from_micros(stats.latency_total_us()),
+        },
+    }
+}
+
+/// Encode `reply` as a size-prefixed `ControlResponse`.
+fn encode_reply(reply: &Reply) -> Vec<u8> {
+    let mut builder = FlatBufferBuilder::new();
+    let (reply_type, body) = match reply {
+        Reply::Modules(modules) => {
+            let modules: Vec<_> = modules
+                .iter()
+                .map(|status| encode_status(&mut builder, status))
+                .collect();
+            let modules = builder.create_vector(&modules);
+            let list = control_fb::ModuleList::create(
+                &mut builder,
+                &control_fb::ModuleListArgs {
+                    modules: Some(modules),
+                },
+            );
+            (control_fb::ControlReply::ModuleList, list.as_union_value())
+        }
+        Reply::Started(process_id) => {
+            let started = control_fb::Started::create(
+                &mut builder,
+                &control_fb::StartedArgs {
+                    process_id: *process_id as u64,
+                },
+            );
+            (control_fb::ControlReply::Started, started.as_union_value())
+        }
+        Reply::Done => {
+            let done = control_fb::Done::create(&mut builder, &control_fb::DoneArgs {});
+            (control_fb::ControlReply::Done, done.as_union_value())
+        }
+        Reply::Failure(message) => {
+            let message = builder.create_string(message);
+            let failure = control_fb::Failure::create(
+                &mut builder,
+                &control_fb::FailureArgs {
+                    message: Some(message),
+                },
+            );
+            (control_fb::ControlReply::Failure, failure.as_union_value())
+        }
+        Reply::Clock(now) => {
+            let time = control_fb::ClockTime::create(
+                &mut builder,
+                &control_fb::ClockTimeArgs {
+                    unix_ms: now.unix_ms,
+                    monotonic_ms: now.monotonic_ms,
+                },
+            );
+            (control_fb::ControlReply::ClockTime, time.as_union_value())
+        }
+        Reply::Diagnostics(process_id, diagnostics) => {
+            let reply = encode_diagnostics(&mut builder, *process_id, diagnostics);
+            (
+                control_fb::ControlReply::InstanceDiagnostics,
+                reply.as_union_value(),
+            )
+        }
+        Reply::Profile(report) => {
+            let path = builder.create_string(&report.path.to_string_lossy());
+            let written = control_fb::ProfileWritten::create(
+                &mut builder,
+                &control_fb::ProfileWrittenArgs {
+                    process_id: report.process_id as u64,
+                    path: Some(path),
+                    samples: report.samples,
+                },
+            );
+            (
+                control_fb::ControlReply::ProfileWritten,
+                written.as_union_value(),
+            )
+        }
+        Reply::Metrics(hostcalls, guest, module_cache) => {
+            let hostcalls: Vec<_> = hostcalls
+                .iter()
+                .map(|stats| encode_hostcall_stats(&mut builder, stats))
+                .collect();
+            let hostcalls = builder.create_vector(&hostcalls);
+            let latency_bounds_us = builder.create_vector(&LATENCY_BUCKETS_US);
+            let guest_counters: Vec<_> = guest
+                .counters
+                .iter()
+                .map(|(name, value)| {
+                    let name = builder.create_string(name);
+                    control_fb::GuestCounter::create(
+                        &mut builder,
+                        &control_fb::GuestCounterArgs {
+                            name: Some(name),
+                            value: *value,
+                        },
+                    )
+                })
+                .collect();
+            let guest_counters = builder.create_vector(&guest_counters);
+            let guest_gauges: Vec<_> = guest
+                .gauges
+                .iter()
+                .map(|(name, value)| {
+                    let name = builder.create_string(name);
+                    control_fb::GuestGauge::create(
+                        &mut builder,
+                        &control_fb::GuestGaugeArgs {
+                            name: Some(name),
+                            value: *value,
+                        },
+                    )
+                })
+                .collect();
+            let guest_gauges = builder.create_vector(&guest_gauges);
+            let module_cache = module_cache.map(|cache| {
+                control_fb::ModuleCacheStats::create(
+                    &mut builder,
+                    &control_fb::ModuleCacheStatsArgs {
+                        hits: cache.hits,
+                        misses: cache.misses,
+                        integrity_failures: cache.integrity_failures,
+                    },
+                )
+            });
+            let report = control_fb::MetricsReport::create(
+                &mut builder,
+                &control_fb::MetricsReportArgs {
+                    latency_bounds_us: Some(latency_bounds_us),
+                    hostcalls: Some(hostcalls),
+                    guest_counters: Some(guest_counters),
+                    guest_gauges: Some(guest_gauges),
+                    module_cache,
+                },
+            );
+            (
+                control_fb::ControlReply::MetricsReport,
+                report.as_union_value(),
+            )
+        }
+    };
+
+    let response = control_fb::ControlResponse::create(
+        &mut builder,
+        &control_fb::ControlResponseArgs {
+            reply_type,
+            reply: Some(body),
+        },
+    );
+    builder.finish_size_prefixed(response, Some(control_fb::CONTROL_REQUEST_IDENTIFIER));
+    builder.finished_data().to_vec()
+}
+
+fn encode_status<'bldr>(
+    builder: &mut FlatBufferBuilder<'bldr>,
+    status: &ModuleStatus,
+) -> WIPOffset<control_fb::ModuleStatus<'bldr>> {
+    let label = builder.create_string(&status.label);
+    let capabilities: Vec<_> = status
+        .capabilities
+        .iter()
+        .map(|capability| builder.create_string(&capability.to_string()))
+        .collect();
+    let capabilities = builder.create_vector(&capabilities);
+    control_fb::ModuleStatus::create(
+        builder,
+        &control_fb::ModuleStatusArgs {
+            label: Some(label),
+            process_id: status.process_id as u64,
+            state: match status.state {
+                ModuleState::Running => control_fb::ModuleState::Running,
+                ModuleState::RestartPending => control_fb::ModuleState::RestartPending,
+                ModuleState::Exited => control_fb::ModuleState::Exited,
+                ModuleState::Stopped => control_fb::ModuleState::Stopped,
+            },
+            capabilities: Some(capabilities),
+            uptime_ms: u64::try_from(status.uptime.as_millis()).unwrap_or(u64::MAX),
+            restarts: status.restarts,
+            fuel_consumed: status.fuel_consumed,
+        },
+    )
+}
+
+fn encode_diagnostics<'bldr>(
+    builder: &mut FlatBufferBuilder<'bldr>,
+    process_id: ResourceId,
+    diagnostics: &InstanceDiagnostics,
+) -> WIPOffset<control_fb::InstanceDiagnostics<'bldr>> {
+    let futures: Vec<_> = diagnostics
+        .futures
+        .iter()
+        .map(|future| {
+            control_fb::FutureDiagnostics::create(
+                builder,
+                &control_fb::FutureDiagnosticsArgs {
+                    handle: future.handle,
+                    state: match future.state {
+                        FutureState::Pending => control_fb::FutureState::Pending,
+                        FutureState::Resolved => control_fb::FutureState::Resolved,
+                        FutureState::Complete => control_fb::FutureState::Complete,
+                        FutureState::Abandoned => control_fb::FutureState::Abandoned,
+                    },
+                    queued: future.queued,
+                    waiting: future.waiting,
+                },
+            )
+        })
+        .collect();
+    let futures = builder.create_vector(&futures);
+    let mailbox = diagnostics.mailbox.map(|mailbox| {
+        control_fb::MailboxDiagnostics::create(
+            builder,
+            &control_fb::MailboxDiagnosticsArgs {
+                signalled: mailbox.signalled,
+                undrained: mailbox.undrained,
+                flagged: mailbox.flagged,
+                closed: mailbox.closed,
+            },
+        )
+    });
+    let mut slot_usage = |usage: SlotUsage| {
+        control_fb::SlotUsage::create(
+            builder,
+            &control_fb::SlotUsageArgs {
+                live: usage.live,
+                allocated: usage.allocated,
+            },
+        )
+    };
+    let slots = slot_usage(diagnostics.slots);
+    let future_slots = slot_usage(diagnostics.future_slots);
+    let tags: Vec<_> = diagnostics
+        .tags
+        .iter()
+        .map(|tag| builder.create_string(tag))
+        .collect();
+    let tags = builder.create_vector(&tags);
+    control_fb::InstanceDiagnostics::create(
+        builder,
+        &control_fb::InstanceDiagnosticsArgs {
+            process_id: process_id as u64,
+            futures: Some(futures),
+            mailbox,
+            slots: Some(slots),
+            future_slots: Some(future_slots),
+            tags: Some(tags),
+        },
+    )
+}
+
+fn encode_hostcall_stats<'bldr>(
+    builder: &mut FlatBufferBuilder<'bldr>,
+    stats: &HostcallStats,
+) -> WIPOffset<control_fb::HostcallStats<'bldr>> {
+    let module = builder.create_string(&stats.module);
+    let errors: Vec<_> = stats
+        .errors
+        .iter()
+        .map(|(code, count)| {
+            control_fb::ErrorCount::create(
+                builder,
+                &control_fb::ErrorCountArgs {
+                    code: code.code(),
+                    count: *count,
+                },
+            )
+        })
+        .collect();
+    let errors = builder.create_vector(&errors);
+    let latency_buckets = builder.create_vector(&stats.latency.buckets);
+    control_fb::HostcallStats::create(
+        builder,
+        &control_fb::HostcallStatsArgs {
+            module: Some(module),
+            calls: stats.calls,
+            errors: Some(errors),
+            latency_buckets: Some(latency_buckets),
+            latency_total_us: u64::try_from(stats.latency.total.as_micros()).unwrap_or(u64::MAX),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::Capability;
+
+    use super::*;
+    use crate::identity::Identity;
+
+    /// A size-prefixed request to stop `web`, or to shut down if `shutdown`, addressed to
+    /// `tenant`.
+    fn request(shutdown: bool, tenant: Option<&str>) -> Vec<u8> {
+        let mut builder = FlatBufferBuilder::new();
+        let (command_type, command) = if shutdown {
+            let shutdown = control_fb::Shutdown::create(&mut builder, &control_fb::ShutdownArgs {});
+            (
+                control_fb::ControlCommand::Shutdown,
+                shutdown.as_union_value(),
+            )
+        } else {
+            let target = Some(builder.create_string("web"));
+            let stop = control_fb::StopModule::create(
+                &mut builder,
+                &control_fb::StopModuleArgs { target },
+            );
+            (
+                control_fb::ControlCommand::StopModule,
+                stop.as_union_value(),
+            )
+        };
+        let tenant = tenant.map(|tenant| builder.create_string(tenant));
+        let request = control_fb::ControlRequest::create(
+            &mut builder,
+            &control_fb::ControlRequestArgs {
+                command_type,
+                command: Some(command),
+                tenant,
+            },
+        );
+        control_fb::finish_size_prefixed_control_request_buffer(&mut builder, request);
+        builder.finished_data().to_vec()
+    }
+
+    fn authorised(principal: &Principal, request: &[u8]) -> bool {
+        let request = control_fb::size_prefixed_root_as_control_request(request).expect("request");
+        authorise(principal, &request).is_ok()
+    }
+
+    #[test]
+    fn remote_requests_are_bounded_by_the_identity() {
+        let registry = Registry::new();
+        let root = Session::bootstrap(
+            [Capability::ProcessLifecycle, Capability::HostShutdown].into(),
+            [0; 32],
+        );
+        let operator = Identity::new("operator", b"operator", Capability::ProcessLifecycle.into());
+        let operator = Principal::open(&registry, &root, &operator).expect("open session");
+        assert!(authorised(&operator, &request(false, None)));
+        assert!(!authorised(&operator, &request(false, Some("acme"))));
+        assert!(!authorised(&operator, &request(true, None)));
+
+        let tenant = Identity::new("acme", b"acme", Capability::ProcessLifecycle.into())
+            .with_tenants(["acme"]);
+        let tenant = Principal::open(&registry, &root, &tenant).expect("open session");
+        assert!(authorised(&tenant, &request(false, Some("acme"))));
+        assert!(!authorised(&tenant, &request(false, Some("globex"))));
+        assert!(!authorised(&tenant, &request(false, None)));
+
+        let admin = Identity::new(
+            "admin",
+            b"admin",
+            [Capability::ProcessLifecycle, Capability::HostShutdown].into(),
+        );
+        let admin = Principal::open(&registry, &root, &admin).expect("open session");
+        assert!(authorised(&admin, &request(true, None)));
+    }
+
+    #[test]
+    fn remote_specs_may_not_reach_the_host_filesystem() {
+        let registry = Registry::new();
+        let root = Session::bootstrap(Capability::ProcessLifecycle.into(), [0; 32]);
+        let operator = Identity::new("operator", b"operator", Capability::ProcessLifecycle.into());
+        let operator = Principal::open(&registry, &root, &operator).expect("open session");
+        let work_dir = std::env::temp_dir().join(format!("selium-admit-{}", std::process::id()));
+        let spec = |capability: &str, extra: &str| {
+            let raw = format!("path=modules/echo.wasm;capabilities={capability};{extra}");
+            modules::parse_cli_spec(&raw, &work_dir).expect("valid specification")
+        };
+
+        assert!(admit(&operator, &spec("process-lifecycle", "")).is_ok());
+        assert!(admit(&operator, &spec("process-lifecycle", "log_output=stderr")).is_ok());
+        assert!(admit(&operator, &spec("channel-reader", "")).is_err());
+        for extra in [
+            "log_output=file:logs/echo.log",
+            "log_output=json:logs/echo.json",
+            "mounts=/etc:rw",
+            "mounts=data",
+        ] {
+            let spec = spec("process-lifecycle", extra);
+            assert!(admit(&operator, &spec).is_err(), "{extra} admitted");
+        }
+    }
+
+    #[tokio::test]
+    async fn sockets_appear_restricted_to_the_owner() {
+        let dir = std::env::temp_dir().join(format!("selium-control-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("control.sock");
+
+        let _listener = bind_private(&path).expect("bind");
+        let metadata = fs::metadata(&path).expect("socket metadata");
+        assert!(metadata.file_type().is_socket());
+        assert_eq!(metadata.permissions().mode() & 0o777, SOCKET_MODE);
+        assert_eq!(fs::read_dir(&dir).expect("read dir").count(), 1);
+        UnixStream::connect(&path).await.expect("connect");
+
+        fs::remove_dir_all(&dir).expect("remove dir");
+    }
+
+    #[tokio::test]
+    async fn replies_are_framed_flatbuffers() {
+        let reply = Reply::Modules(vec![ModuleStatus {
+            label: "modules/echo.wasm".to_string(),
+            process_id: 7,
+            state: ModuleState::RestartPending,
+            capabilities: Capability::TimeRead.into(),
+            uptime: Duration::from_millis(1500),
+            restarts: 2,
+            fuel_consumed: 42,
+        }]);
+        let encoded = encode_reply(&reply);
+
+        let mut stream = encoded.as_slice();
+        let message = read_message(&mut stream)
+            .await
+            .expect("read")
+            .expect("message");
+        assert!(read_message(&mut stream).await.expect("read").is_none());
+
+        let response = flatbuffers::size_prefixed_root::<control_fb::ControlResponse>(&message)
+            .expect("response");
+        let modules = response
+            .reply_as_module_list()
+            .and_then(|list| list.modules())
+            .expect("module list");
+        let status = modules.get(0);
+        assert_eq!(status.label(), Some("modules/echo.wasm"));
+        assert_eq!(status.process_id(), 7);
+        assert_eq!(status.state(), control_fb::ModuleState::RestartPending);
+        assert_eq!(status.uptime_ms(), 1500);
+        assert_eq!(status.restarts(), 2);
+        assert_eq!(status.fuel_consumed(), 42);
+        assert_eq!(
+            status.capabilities().map(|caps| caps.get(0)),
+            Some("TimeRead")
+        );
+
+        let oversized = ((MAX_MESSAGE_LEN + 1) as u32).to_le_bytes();
+        assert!(read_message(&mut oversized.as_slice()).await.is_err());
+    }
+
+    #[test]
+    fn diagnostics_round_trip() {
+        let diagnostics = InstanceDiagnostics {
+            futures: vec![FutureDiagnostics {
+                handle: 1 << 20,
+                state: FutureState::Resolved,
+                queued: 1,
+                waiting: false,
+            }],
+            mailbox: Some(MailboxDiagnostics {
+                signalled: 12,
+                undrained: 1,
+                flagged: true,
+                closed: false,
+            }),
+            slots: SlotUsage {
+                live: 3,
+                allocated: 4,
+            },
+            future_slots: SlotUsage {
+                live: 1,
+                allocated: 2,
+            },
+            tags: vec!["module:echo.wasm".to_string()],
+        };
+        let encoded = encode_reply(&Reply::Diagnostics(5, diagnostics.clone()));
+
+        let response = decode_response(&encoded).expect("response");
+        let decoded = response
+            .reply_as_instance_diagnostics()
+            .map(decode_diagnostics)
+            .expect("diagnostics reply")
+            .expect("decode diagnostics");
+        assert_eq!(decoded, (5, diagnostics));
+    }
+}
@@ -0,0 +1,118 @@
+//! The `diagnose` subcommand, reporting the async state of a module on a running host, for
+//! debugging guests whose tasks never wake.
+
+use anyhow::Result;
+use selium_abi::{FutureState, InstanceDiagnostics};
+use selium_kernel::registry::ResourceId;
+
+use crate::control::ControlClient;
+
+/// Print the pending futures, mailbox counters and slot usage of `target`.
+pub async fn print(mut client: ControlClient, target: &str) -> Result<()> {
+    let (process_id, diagnostics) = client.diagnose(target).await?;
+    println!("{}", render(process_id, &diagnostics));
+    Ok(())
+}
+
+fn render(process_id: ResourceId, diagnostics: &InstanceDiagnostics) -> String {
+    let mut lines = vec![format!("process {process_id}")];
+    if !diagnostics.tags.is_empty() {
+        lines.push(format!("tags: {}", diagnostics.tags.join(", ")));
+    }
+    lines.push(match diagnostics.mailbox {
+        Some(mailbox) => format!(
+            "mailbox: {} signalled, {} undrained, flag {}{}",
+            mailbox.signalled,
+            mailbox.undrained,
+            if mailbox.flagged { "raised" } else { "clear" },
+            if mailbox.closed { ", closed" } else { "" },
+        ),
+        None => "mailbox: not registered".to_string(),
+    });
+    lines.push(format!(
+        "slots: {} live of {} allocated",
+        diagnostics.slots.live, diagnostics.slots.allocated
+    ));
+    lines.push(format!(
+        "futures: {} live of {} allocated",
+        diagnostics.future_slots.live, diagnostics.future_slots.allocated
+    ));
+    for future in &diagnostics.futures {
+        lines.push(format!(
+            "  {:>8}  {:<9}  queued {}{}",
+            future.handle,
+            state_label(future.state),
+            future.queued,
+            if future.waiting {
+                ", waker registered"
+            } else {
+                ""
+            },
+        ));
+    }
+    lines.join("\n")
+}
+
+fn state_label(state: FutureState) -> &'static str {
+    match state {
+        FutureState::Pending => "pending",
+        FutureState::Resolved => "resolved",
+        FutureState::Complete => "complete",
+        FutureState::Abandoned => "abandoned",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{FutureDiagnostics, MailboxDiagnostics, SlotUsage};
+
+    use super::*;
+
+    #[test]
+    fn pending_futures_are_listed() {
+        let diagnostics = InstanceDiagnostics {
+            futures: vec![
+                FutureDiagnostics {
+                    handle: 0,
+                    state: FutureState::Pending,
+                    queued: 0,
+                    waiting: true,
+                },
+                FutureDiagnostics {
+                    handle: 1_048_577,
+                    state: FutureState::Resolved,
+                    queued: 1,
+                    waiting: false,
+                },
+            ],
+            mailbox: Some(MailboxDiagnostics {
+                signalled: 40,
+                undrained: 1,
+                flagged: true,
+                closed: false,
+            }),
+            slots: SlotUsage {
+                live: 3,
+                allocated: 3,
+            },
+            future_slots: SlotUsage {
+                live: 2,
+                allocated: 2,
+            },
+            tags: vec!["module:echo.wasm".to_string()],
+        };
+
+        assert_eq!(
+            render(4, &diagnostics).lines().collect::<Vec<_>>(),
+            [
+                "process 4",
+                "tags: module:echo.wasm",
+                "mailbox: 40 signalled, 1 undrained, flag raised",
+                "slots: 3 live of 3 allocated",
+                "futures: 2 live of 2 allocated",
+                "         0  pending    queued 0, waker registered",
+                "   1048577  resolved   queued 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! The allow-list of host commands behind `selium::host::exec`.
+//!
+//! Guests name an entry of the deployment file's `[[host_command]]` list rather than a program,
+//! so a module holding `HostExec` can only run what the operator listed, with the arguments the
+//! entry fixes. Programs run with an empty environment, no standard input and their standard
+//! error discarded, and are killed if the hostcall is dropped or times out, or if they write more
+//! to their standard output than the hostcall can return. If the runtime keeps an audit log,
+//! every command is recorded there as it starts and again once it finishes or is refused.
+
+use std::{collections::HashMap, future::Future, process::Stdio, sync::Arc};
+
+use selium_abi::{ErrorCode, HostExecOutput};
+use selium_kernel::{
+    drivers::host::HostExecCapability,
+    guest_data::{GuestError, GuestResult},
+    registry::ResourceId,
+};
+use tokio::{io::AsyncReadExt, process::Command};
+
+use crate::{audit::AuditLog, config::HostCommand};
+
+/// Host commands guests may run, keyed by the name they run them by.
+#[derive(Clone, Default)]
+pub(crate) struct AllowedCommands {
+    commands: Arc<HashMap<String, HostCommand>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl AllowedCommands {
+    /// Allow `commands`, recording each run to `audit` if given.
+    pub(crate) fn new(commands: &[HostCommand], audit: Option<Arc<AuditLog>>) -> Self {
+        let commands = commands
+            .iter()
+            .map(|command| (command.name.clone(), command.clone()))
+            .collect();
+        Self {
+            commands: Arc::new(commands),
+            audit,
+        }
+    }
+}
+
+impl HostExecCapability for AllowedCommands {
+    fn exec(
+        &self,
+        process: Option<ResourceId>,
+        command: String,
+        args: Vec<String>,
+        max_stdout: usize,
+    ) -> impl Future<Output = GuestResult<HostExecOutput>> + Send + 'static {
+        let entry = self.commands.get(&command).cloned();
+        let audit = self.audit.clone();
+
+        async move {
+            if let Some(audit) = &audit {
+                audit.record_host_command(process, &command, &args, None);
+            }
+            let result = run(entry, &command, &args, max_stdout).await;
+            if let Some(audit) = &audit {
+                audit.record_host_command(process, &command, &args, Some(&result));
+            }
+            result
+        }
+    }
+}
+
+/// Run the allow-list `entry` for `command` with `args` appended, reading at most `max_stdout`
+/// bytes of its standard output.
+async fn run(
+    entry: Option<HostCommand>,
+    command: &str,
+    args: &[String],
+    max_stdout: usize,
+) -> GuestResult<HostExecOutput> {
+    let entry = entry.ok_or_else(|| {
+        GuestError::Coded(
+            ErrorCode::PermissionDenied,
+            format!("host command `{command}` is not allow-listed"),
+        )
+    })?;
+    if !args.is_empty() && !entry.allow_args {
+        return Err(GuestError::Coded(
+            ErrorCode::PermissionDenied,
+            format!("host command `{command}` does not accept arguments"),
+        ));
+    }
+
+    let failed = |err: std::io::Error| {
+        GuestError::Subsystem(format!("run {}: {err}", entry.program.display()))
+    };
+    let mut child = Command::new(&entry.program)
+        .args(&entry.args)
+        .args(args)
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(failed)?;
+
+    // Read one byte past the limit to tell a full buffer from an overflowing one.
+    let mut stdout = Vec::new();
+    child
+        .stdout
+        .take()
+        .ok_or_else(|| GuestError::Subsystem(format!("host command `{command}` has no stdout")))?
+        .take(
+            u64::try_from(max_stdout)
+                .unwrap_or(u64::MAX)
+                .saturating_add(1),
+        )
+        .read_to_end(&mut stdout)
+        .await
+        .map_err(failed)?;
+    if stdout.len() > max_stdout {
+        child.kill().await.map_err(failed)?;
+        return Err(GuestError::Coded(
+            ErrorCode::PayloadTooLarge,
+            format!("host command `{command}` wrote more than {max_stdout} bytes"),
+        ));
+    }
+
+    let status = child.wait().await.map_err(failed)?;
+    Ok(HostExecOutput {
+        status: status.code(),
+        stdout,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, time::Duration};
+
+    use selium_abi::{HostExec, PayloadEncoding};
+    use selium_kernel::{drivers, testing::BlockingKernelClient};
+
+    use super::*;
+
+    fn allowed(allow_args: bool) -> AllowedCommands {
+        AllowedCommands::new(
+            &[HostCommand {
+                name: "greet".to_string(),
+                program: PathBuf::from("/bin/sh"),
+                args: vec!["-c".to_string(), "echo \"hello $0\"; exit 3".to_string()],
+                allow_args,
+            }],
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn allow_listed_commands_report_status_and_stdout() {
+        let output = allowed(true)
+            .exec(None, "greet".to_string(), vec!["selium".to_string()], 64)
+            .await
+            .expect("run allow-listed command");
+        assert_eq!(output.status, Some(3));
+        assert_eq!(output.stdout, b"hello selium\n");
+    }
+
+    #[tokio::test]
+    async fn unlisted_commands_and_unexpected_arguments_are_denied() {
+        let err = allowed(true)
+            .exec(None, "/bin/sh".to_string(), Vec::new(), 64)
+            .await
+            .expect_err("unlisted command");
+        assert_eq!(err.code(), ErrorCode::PermissionDenied);
+
+        let err = allowed(false)
+            .exec(None, "greet".to_string(), vec!["selium".to_string()], 64)
+            .await
+            .expect_err("arguments to a fixed command");
+        assert_eq!(err.code(), ErrorCode::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn commands_writing_past_the_limit_are_killed() {
+        let commands = AllowedCommands::new(
+            &[HostCommand {
+                name: "flood".to_string(),
+                program: PathBuf::from("/bin/sh"),
+                args: vec![
+                    "-c".to_string(),
+                    "echo noise >&2; while :; do echo flood; done".to_string(),
+                ],
+                allow_args: false,
+            }],
+            None,
+        );
+
+        let err = commands
+            .exec(None, "flood".to_string(), Vec::new(), 1024)
+            .await
+            .expect_err("unbounded output");
+        assert_eq!(err.code(), ErrorCode::PayloadTooLarge);
+    }
+
+    #[test]
+    fn output_at_the_limit_is_returned_through_the_hostcall() {
+        let commands = AllowedCommands::new(
+            &[HostCommand {
+                name: "zeros".to_string(),
+                program: PathBuf::from("/bin/sh"),
+                args: vec!["-c".to_string(), "head -c \"$0\" /dev/zero".to_string()],
+                allow_args: true,
+            }],
+            None,
+        );
+        let operation = drivers::host::operation(commands);
+        let mut client = BlockingKernelClient::new().expect("client");
+        let limit = drivers::host::max_stdout(PayloadEncoding::Rkyv);
+        let zeros = |len: usize| HostExec {
+            command: "zeros".to_string(),
+            args: vec![len.to_string()],
+        };
+
+        let output = client
+            .call(&operation, &zeros(limit))
+            .expect("output at the limit");
+        assert_eq!(output.status, Some(0));
+        assert_eq!(output.stdout.len(), limit);
+
+        let err = client
+            .call(&operation, &zeros(limit + 1))
+            .expect_err("output past the limit");
+        assert_eq!(err.code(), ErrorCode::PayloadTooLarge);
+    }
+
+    #[tokio::test]
+    async fn every_run_is_recorded_in_the_audit_log() {
+        let path =
+            std::env::temp_dir().join(format!("selium-host-exec-audit-{}.log", std::process::id()));
+        let audit = Arc::new(AuditLog::open(&path).expect("open audit log"));
+        let commands = AllowedCommands {
+            audit: Some(audit),
+            ..allowed(false)
+        };
+        commands
+            .exec(Some(7), "greet".to_string(), Vec::new(), 64)
+            .await
+            .expect("run allow-listed command");
+        commands
+            .exec(Some(7), "rm".to_string(), vec!["-rf".to_string()], 64)
+            .await
+            .expect_err("unlisted command");
+        drop(commands);
+
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            let contents = fs::read_to_string(&path).expect("read audit log");
+            records = contents
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("json"))
+                .collect();
+            if records.len() == 4 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let summary: Vec<_> = records
+            .iter()
+            .map(|record| {
+                assert_eq!(record["process"], 7);
+                assert_eq!(record["hostcall"], "selium::host::exec");
+                (
+                    record["command"].as_str().expect("command").to_string(),
+                    record["outcome"].as_str().expect("outcome").to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("greet".to_string(), "started".to_string()),
+                ("greet".to_string(), "ok".to_string()),
+                ("rm".to_string(), "started".to_string()),
+                ("rm".to_string(), "denied".to_string()),
+            ]
+        );
+        assert_eq!(records[1]["status"], 3);
+        assert_eq!(records[1]["stdout_len"], "hello /bin/sh\n".len());
+        assert_eq!(records[2]["args"], serde_json::json!(["-rf"]));
+
+        fs::remove_file(&path).expect("remove audit log");
+    }
+}
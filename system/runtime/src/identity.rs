@@ -0,0 +1,238 @@
+//! Identities of remote control clients.
+//!
+//! The control listener only accepts TLS connections from clients presenting a certificate
+//! issued by the runtime's CA. Each `[[identity]]` entry of the deployment file pins one client
+//! certificate and names the capabilities its holder is entitled to. A connection made with a
+//! pinned certificate is served under a Selium session derived from the runtime's root session
+//! with exactly those entitlements, so a remote caller can do no more than its profile allows,
+//! nor more than the runtime itself may. The session stays in the registry for as long as the
+//! connection is open.
+//!
+//! An identity addresses either the host or the tenants its entry lists, never both: one with
+//! no `tenants` may only act on the host, and one with `tenants` only on those.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use anyhow::{Context, Result, anyhow};
+use selium_abi::CapabilitySet;
+use selium_kernel::{
+    drivers::Capability,
+    events::KernelEvent,
+    registry::{Registry, ResourceHandle, ResourceId, ResourceType},
+    session::{ResourceScope, Session},
+};
+
+use crate::kernel;
+
+/// A client certificate and the capabilities its holder is entitled to.
+#[derive(Clone, Debug)]
+pub struct Identity {
+    name: String,
+    fingerprint: blake3::Hash,
+    entitlements: CapabilitySet,
+    tenants: Vec<String>,
+}
+
+/// Identities recognised by the control listener, keyed by certificate fingerprint.
+#[derive(Debug, Default)]
+pub struct Identities {
+    by_fingerprint: HashMap<blake3::Hash, Identity>,
+}
+
+/// A connected client, holding the session its requests are authorised against. The session is
+/// removed from the registry when the principal is dropped.
+pub struct Principal {
+    name: String,
+    session: ResourceId,
+    tenants: Vec<String>,
+    registry: Arc<Registry>,
+}
+
+impl Identity {
+    /// Pin the first certificate in the PEM or DER file at `certificate` to `name`.
+    pub fn load(name: &str, certificate: &Path, entitlements: CapabilitySet) -> Result<Self> {
+        let chain = kernel::load_certificate_chain(certificate)
+            .with_context(|| format!("load client certificate {}", certificate.display()))?;
+        let leaf = chain
+            .first()
+            .ok_or_else(|| anyhow!("{} holds no certificate", certificate.display()))?;
+        Ok(Self::new(name, leaf.as_ref(), entitlements))
+    }
+
+    /// Pin the DER-encoded `certificate` to `name`.
+    pub fn new(name: &str, certificate: &[u8], entitlements: CapabilitySet) -> Self {
+        Self {
+            name: name.to_string(),
+            fingerprint: blake3::hash(certificate),
+            entitlements,
+            tenants: Vec::new(),
+        }
+    }
+
+    /// Scope the identity to the tenants `tenants` instead of the host.
+    pub fn with_tenants(mut self, tenants: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tenants = tenants.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Capabilities the identity is entitled to.
+    pub fn entitlements(&self) -> CapabilitySet {
+        self.entitlements
+    }
+
+    /// Tenants the identity may address; empty if it may only address the host.
+    pub fn tenants(&self) -> &[String] {
+        &self.tenants
+    }
+}
+
+impl Identities {
+    /// Recognise each of `identities`. A certificate pinned twice keeps its last identity.
+    pub fn new(identities: impl IntoIterator<Item = Identity>) -> Self {
+        Self {
+            by_fingerprint: identities
+                .into_iter()
+                .map(|identity| (identity.fingerprint, identity))
+                .collect(),
+        }
+    }
+
+    /// Whether no identity is recognised.
+    pub fn is_empty(&self) -> bool {
+        self.by_fingerprint.is_empty()
+    }
+
+    /// Identity pinned to the DER-encoded `certificate`, if any.
+    pub fn resolve(&self, certificate: &[u8]) -> Option<&Identity> {
+        self.by_fingerprint.get(&blake3::hash(certificate))
+    }
+}
+
+impl Principal {
+    /// Register a session for `identity`, derived from `root`, in `registry`. Fails if the
+    /// identity is entitled to a capability `root` does not hold.
+    pub fn open(registry: &Arc<Registry>, root: &Session, identity: &Identity) -> Result<Self> {
+        let entitlements = identity
+            .entitlements
+            .iter()
+            .map(|capability| (capability, ResourceScope::Any))
+            .collect();
+        let session = root
+            .create(entitlements, [0; 32])
+            .with_context(|| format!("create session for identity `{}`", identity.name))?;
+        let session = registry
+            .add(session, None, ResourceType::Session)
+            .with_context(|| format!("register session for identity `{}`", identity.name))?
+            .into_id();
+        registry.events().emit(KernelEvent::SessionCreated {
+            session_id: session,
+            parent_id: None,
+            process_id: None,
+        });
+
+        Ok(Self {
+            name: identity.name.clone(),
+            session,
+            tenants: identity.tenants.clone(),
+            registry: Arc::clone(registry),
+        })
+    }
+
+    /// Name of the identity the client connected as.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Registry id of the client's session.
+    pub fn session(&self) -> ResourceId {
+        self.session
+    }
+
+    /// Whether the client's session is entitled to `capability`.
+    pub fn entitled(&self, capability: Capability) -> bool {
+        self.registry
+            .with(
+                ResourceHandle::<Session>::new(self.session),
+                |session: &mut Session| session.entitled(capability),
+            )
+            .unwrap_or(false)
+    }
+
+    /// Those of `capabilities` the client's session is not entitled to.
+    pub fn withheld(&self, capabilities: CapabilitySet) -> CapabilitySet {
+        capabilities
+            .iter()
+            .filter(|capability| !self.entitled(*capability))
+            .collect()
+    }
+
+    /// Whether the client may address the tenant `tenant`, or the host if it is `None`.
+    pub fn may_address(&self, tenant: Option<&str>) -> bool {
+        match tenant {
+            None => self.tenants.is_empty(),
+            Some(id) => self.tenants.iter().any(|allowed| allowed == id),
+        }
+    }
+}
+
+impl Drop for Principal {
+    fn drop(&mut self) {
+        self.registry.discard(self.session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinned_certificates_open_sessions_limited_to_their_profile() {
+        let registry = Registry::new();
+        let root = Session::bootstrap(
+            [Capability::ProcessLifecycle, Capability::TimeRead].into(),
+            [0; 32],
+        );
+        let identities = Identities::new([
+            Identity::new("operator", b"operator", Capability::ProcessLifecycle.into()),
+            Identity::new("greedy", b"greedy", Capability::NetQuicBind.into()),
+        ]);
+        assert!(identities.resolve(b"stranger").is_none());
+
+        let operator = identities.resolve(b"operator").expect("pinned");
+        let principal = Principal::open(&registry, &root, operator).expect("open session");
+        assert_eq!(principal.name(), "operator");
+        assert!(principal.entitled(Capability::ProcessLifecycle));
+        assert!(!principal.entitled(Capability::TimeRead));
+        let session = principal.session();
+        drop(principal);
+        assert!(registry.metadata(session).is_none());
+
+        let greedy = identities.resolve(b"greedy").expect("pinned");
+        assert!(Principal::open(&registry, &root, greedy).is_err());
+    }
+
+    #[test]
+    fn principals_are_bounded_by_their_entitlements_and_tenants() {
+        let registry = Registry::new();
+        let root = Session::bootstrap(
+            [Capability::ProcessLifecycle, Capability::TimeRead].into(),
+            [0; 32],
+        );
+        let host = Identity::new("host", b"host", Capability::ProcessLifecycle.into());
+        let tenant = Identity::new("tenant", b"tenant", Capability::ProcessLifecycle.into())
+            .with_tenants(["alpha"]);
+
+        let host = Principal::open(&registry, &root, &host).expect("open session");
+        assert!(host.may_address(None));
+        assert!(!host.may_address(Some("alpha")));
+        assert_eq!(
+            host.withheld([Capability::ProcessLifecycle, Capability::TimeRead].into()),
+            Capability::TimeRead.into()
+        );
+
+        let tenant = Principal::open(&registry, &root, &tenant).expect("open session");
+        assert!(!tenant.may_address(None));
+        assert!(tenant.may_address(Some("alpha")));
+        assert!(!tenant.may_address(Some("beta")));
+    }
+}
@@ -1,133 +1,270 @@
+//! Assembly of the runtime kernel from the built-in hostcall drivers.
+
 use std::{
-    collections::HashMap,
-    fs,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Context, Result, anyhow};
+use ring::rand::{SecureRandom, SystemRandom};
 use rustls::{
     crypto::ring::sign::any_supported_type,
     pki_types::{CertificateDer, PrivateKeyDer},
     sign,
 };
 use rustls_pki_types::{PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, pem::SliceIter};
-use selium_abi::{Capability, NetProtocol};
-use selium_filesystem_store::{FilesystemStore, FilesystemStoreReadDriver};
+use selium_abi::{Capability, NetProtocol, hostcalls};
+use selium_compression::NativeCompression;
+use selium_filesystem_store::{
+    ContentCache, FilesystemStore, FilesystemStoreReadDriver, Sandboxes, TrustRoot,
+};
 use selium_kernel::{
-    Kernel, drivers, guest_async::GuestAsync, operation::LinkableOperation,
+    CapabilityProvider, HostCapabilityPlugin, Kernel,
+    drivers::{
+        self,
+        lock::LocalLocks,
+        sync::SyncPrimitives,
+        time::{SteppedTimeService, SystemTimeService},
+    },
+    guest_async::GuestAsync,
+    idempotency::IdempotencyCache,
+    metrics::{GuestMetrics, HostcallMetrics},
+    operation::{Enforcement, LinkableOperation},
+    priority::PriorityClass,
     session::SessionLifecycleDriver,
+    watchdog::{Watchdog, WatchdogConfig},
 };
 use selium_messaging::{ChannelDriver, ChannelStrongIoDriver, ChannelWeakIoDriver};
 use selium_net_hyper::HyperDriver;
 use selium_net_quinn::QuinnDriver;
-use selium_wasmtime::{WasmRuntime, WasmtimeDriver};
+use selium_wasmtime::{CrashReports, ModuleCache, PoolingLimits, WasmRuntime, WasmtimeDriver};
 use tokio::sync::Notify;
+use tracing::warn;
 
-use crate::tls;
+use crate::{audit::AuditLog, config::HostCommand, host_exec::AllowedCommands, tls};
 
 /// Where certificates are stored
-const CERTS_SUBDIR: &str = "certs";
+pub const CERTS_SUBDIR: &str = "certs";
 /// Where WASM modules are stored
 const MODULES_SUBDIR: &str = "modules";
+/// Where each module's sandbox directory is kept
+const SANDBOXES_SUBDIR: &str = "sandboxes";
+/// Where precompiled WASM modules are cached
+const CACHE_SUBDIR: &str = "cache";
+/// Where the bytes of modules read from the module store are cached, within [`CACHE_SUBDIR`]
+const CONTENT_CACHE_SUBDIR: &str = "content";
+/// Secret key authenticating the entries of the module cache, kept outside the cache itself
+const CACHE_KEY_FILE: &str = "cache.key";
+/// Where crash reports for trapped guests are written
+const CRASH_SUBDIR: &str = "crashes";
+/// Maximum number of cheap, high-volume hostcall tasks (timers) running at once
+const BULK_HOSTCALL_CONCURRENCY: usize = 1024;
+/// Capabilities whose hostcalls run in the throttled bulk priority class
+const BULK_CAPABILITIES: &[Capability] = &[Capability::TimeRead];
+
+/// Tunables applied while assembling the runtime kernel.
+#[derive(Default)]
+pub struct KernelOptions {
+    /// Per-hostcall execution timeouts, keyed by hostcall name.
+    pub hostcall_timeouts: Vec<(String, Duration)>,
+    /// How long idempotent hostcall results are replayed; `None` disables result caching.
+    pub idempotency_window: Option<Duration>,
+    /// How long a parked guest waits after its first wake-up for further completions to
+    /// resume with; `None` resumes it on every wake-up.
+    pub wake_coalescing: Option<Duration>,
+    /// Pooling allocator sizing; `None` allocates instances on demand.
+    pub pooling: Option<PoolingLimits>,
+    /// Keys module signatures are verified against; `None` accepts unsigned modules.
+    pub trust_root: Option<TrustRoot>,
+    /// Digests of the only modules that may be started; `None` allows any module.
+    pub allowed_digests: Option<Vec<blake3::Hash>>,
+    /// Start of a stepped clock that only moves when advanced, in milliseconds since the Unix
+    /// epoch; `None` gives guests the host clock.
+    pub stepped_clock: Option<u64>,
+    /// Providers registering further hostcall families after the built-in ones.
+    pub providers: Vec<Arc<dyn CapabilityProvider>>,
+    /// Plugins adding the hostcall families they declare, after the providers.
+    pub plugins: Vec<Arc<dyn HostCapabilityPlugin>>,
+    /// File or Unix domain socket every hostcall is recorded to; `None` keeps no audit log.
+    pub audit_log: Option<PathBuf>,
+    /// Thresholds for flagging stuck futures and slow hostcalls; the default flags nothing.
+    pub watchdog: WatchdogConfig,
+    /// Whether hostcalls failing their capability or quota checks are refused or only logged.
+    pub enforcement: Enforcement,
+    /// Whether the host's name is withheld from guests asking about the host.
+    pub redact_hostname: bool,
+    /// Host programs guests holding `HostExec` may run; empty allows none.
+    pub host_commands: Vec<HostCommand>,
+}
 
-pub fn build(work_dir: impl AsRef<Path>) -> Result<(Kernel, Arc<Notify>)> {
+/// Assemble the runtime kernel: the Wasmtime driver, every built-in hostcall driver and the
+/// providers and plugins in `options`, serving modules and certificates from `work_dir`. The returned
+/// [`Notify`] wakes parked guests to shut down once notified.
+pub fn build(work_dir: impl AsRef<Path>, options: &KernelOptions) -> Result<(Kernel, Arc<Notify>)> {
     let certs_dir: PathBuf = work_dir.as_ref().join(CERTS_SUBDIR);
     let modules_dir: PathBuf = work_dir.as_ref().join(MODULES_SUBDIR);
 
     let mut builder = Kernel::build();
-    let mut capability_ops: HashMap<Capability, Vec<Arc<dyn LinkableOperation>>> = HashMap::new();
+    let audit = match &options.audit_log {
+        Some(path) => Some(Arc::new(AuditLog::open(path)?)),
+        None => None,
+    };
 
     // Session Lifecycle
     let drv = builder.add_capability(SessionLifecycleDriver::new());
     let session = drivers::session::operations(drv);
-    capability_ops
-        .entry(Capability::SessionLifecycle)
-        .or_default()
-        .extend([
+    builder.register_operations(
+        [
             session.0.as_linkable(),
             session.1.as_linkable(),
             session.2.as_linkable(),
             session.3.as_linkable(),
             session.4.as_linkable(),
             session.5.as_linkable(),
-        ]);
+        ],
+        Capability::SessionLifecycle,
+    );
 
     // Channel Lifecycle
     let chan_drv = builder.add_capability(ChannelDriver::new());
     let channel = drivers::channel::lifecycle_ops(chan_drv.clone());
     let handoff = drivers::channel::handoff_ops();
-    capability_ops
-        .entry(Capability::ChannelLifecycle)
-        .or_default()
-        .extend([
+    builder.register_operations(
+        [
             channel.0.as_linkable(),
             channel.1.as_linkable(),
             channel.2.as_linkable(),
             handoff.0.as_linkable(),
             handoff.1.as_linkable(),
             handoff.2.as_linkable(),
-        ]);
+        ],
+        Capability::ChannelLifecycle,
+    );
 
     // Channel Reader
     let chan_strong_drv = builder.add_capability(ChannelStrongIoDriver::new());
     let chan_weak_drv = builder.add_capability(ChannelWeakIoDriver::new());
     let reader = drivers::channel::read_ops(chan_strong_drv.clone(), chan_weak_drv.clone());
-    capability_ops
-        .entry(Capability::ChannelReader)
-        .or_default()
-        .extend([
+    builder.register_operations(
+        [
             reader.0.as_linkable(),
             reader.1.as_linkable(),
             reader.2.as_linkable(),
             reader.3.as_linkable(),
-        ]);
+        ],
+        Capability::ChannelReader,
+    );
 
     // Channel Writer
     let writer = drivers::channel::write_ops(chan_strong_drv, chan_weak_drv);
     let writer_downgrade = drivers::channel::writer_downgrade_op(chan_drv.clone());
-    capability_ops
-        .entry(Capability::ChannelWriter)
-        .or_default()
-        .extend([
+    builder.register_operations(
+        [
             writer.0.as_linkable(),
             writer.1.as_linkable(),
             writer.2.as_linkable(),
             writer.3.as_linkable(),
             writer_downgrade.as_linkable(),
-        ]);
+        ],
+        Capability::ChannelWriter,
+    );
 
     let process_logs = drivers::process::log_ops::<WasmtimeDriver>();
-    capability_ops
-        .entry(Capability::ChannelLifecycle)
-        .or_default()
-        .push(process_logs.0.as_linkable());
+    builder.register_operation(process_logs.0.as_linkable(), Capability::ChannelLifecycle);
 
     let singleton_ops = drivers::singleton::operations();
-    capability_ops
-        .entry(Capability::SingletonRegistry)
-        .or_default()
-        .push(singleton_ops.0.as_linkable());
-    capability_ops
-        .entry(Capability::SingletonLookup)
-        .or_default()
-        .push(singleton_ops.1.as_linkable());
-
-    let time_ops = drivers::time::operations();
-    capability_ops
-        .entry(Capability::TimeRead)
-        .or_default()
-        .extend([time_ops.0.as_linkable(), time_ops.1.as_linkable()]);
+    builder.register_operation(singleton_ops.0.as_linkable(), Capability::SingletonRegistry);
+    builder.register_operation(singleton_ops.1.as_linkable(), Capability::SingletonLookup);
+
+    let service_ops = drivers::services::operations();
+    builder.register_operations(
+        [
+            service_ops.0.as_linkable(),
+            service_ops.1.as_linkable(),
+            service_ops.2.as_linkable(),
+            service_ops.3.as_linkable(),
+        ],
+        Capability::ServiceDirectory,
+    );
+
+    let watch_ops = drivers::watch::operations();
+    builder.register_operations(
+        [
+            watch_ops.0.as_linkable(),
+            watch_ops.1.as_linkable(),
+            watch_ops.2.as_linkable(),
+        ],
+        Capability::Watch,
+    );
+
+    let guest_metrics = builder.add_capability(Arc::new(GuestMetrics::default()));
+    let metric_ops = drivers::metrics::operations(GuestMetrics::clone(&guest_metrics));
+    builder.register_operations(
+        [
+            metric_ops.0.as_linkable(),
+            metric_ops.1.as_linkable(),
+            metric_ops.2.as_linkable(),
+        ],
+        Capability::Metrics,
+    );
+
+    let lock_ops = drivers::lock::operations(LocalLocks::default());
+    builder.register_operations(
+        [lock_ops.0.as_linkable(), lock_ops.1.as_linkable()],
+        Capability::Lock,
+    );
+
+    let sync_ops = drivers::sync::operations(SyncPrimitives::default());
+    builder.register_operations(
+        [
+            sync_ops.0.as_linkable(),
+            sync_ops.1.as_linkable(),
+            sync_ops.2.as_linkable(),
+        ],
+        Capability::Sync,
+    );
+
+    let future_ops = drivers::future::operations();
+    builder.register_operations(
+        [future_ops.0.as_linkable(), future_ops.1.as_linkable()],
+        Capability::FutureHandoff,
+    );
+
+    let compress_ops = drivers::compress::operations(NativeCompression);
+    builder.register_operations(
+        [compress_ops.0.as_linkable(), compress_ops.1.as_linkable()],
+        Capability::Compression,
+    );
+
+    let host_exec =
+        drivers::host::operation(AllowedCommands::new(&options.host_commands, audit.clone()));
+    builder.register_operations([host_exec.as_linkable()], Capability::HostExec);
+
+    let sql_ops = drivers::sql::operations();
+    builder.register_operations(
+        [sql_ops.0.as_linkable(), sql_ops.1.as_linkable()],
+        Capability::Sql,
+    );
+
+    let time_ops = match options.stepped_clock {
+        Some(start_ms) => {
+            let clock = builder.add_capability(Arc::new(SteppedTimeService::new(start_ms)));
+            let ops = drivers::time::operations(clock);
+            [ops.0.as_linkable(), ops.1.as_linkable()]
+        }
+        None => {
+            let ops = drivers::time::operations(SystemTimeService);
+            [ops.0.as_linkable(), ops.1.as_linkable()]
+        }
+    };
+    builder.register_operations(time_ops, Capability::TimeRead);
 
     let tls_ops = tls::operations();
-    capability_ops
-        .entry(Capability::NetTlsServerConfig)
-        .or_default()
-        .push(tls_ops.0.as_linkable());
-    capability_ops
-        .entry(Capability::NetTlsClientConfig)
-        .or_default()
-        .push(tls_ops.1.as_linkable());
+    builder.register_operation(tls_ops.0.as_linkable(), Capability::NetTlsServerConfig);
+    builder.register_operation(tls_ops.1.as_linkable(), Capability::NetTlsClientConfig);
 
     // Network
     let cert_path = certs_dir.join("server.crt");
@@ -137,73 +274,220 @@ pub fn build(work_dir: impl AsRef<Path>) -> Result<(Kernel, Arc<Notify>)> {
             .context("load QUIC listener certificate and key")?,
     );
     let drv = builder.add_capability(QuinnDriver::new(Arc::clone(&server_certified_key)));
-    capability_ops
-        .entry(Capability::NetQuicBind)
-        .or_default()
-        .push(drivers::net::listener_op(drv.clone(), NetProtocol::Quic).as_linkable());
-    capability_ops
-        .entry(Capability::NetQuicAccept)
-        .or_default()
-        .push(drivers::net::accept_op(drv.clone(), NetProtocol::Quic).as_linkable());
-    capability_ops
-        .entry(Capability::NetQuicConnect)
-        .or_default()
-        .push(drivers::net::connect_op(drv.clone(), NetProtocol::Quic).as_linkable());
-    capability_ops
-        .entry(Capability::NetQuicRead)
-        .or_default()
-        .push(drivers::net::read_op(drv.clone(), NetProtocol::Quic).as_linkable());
-    capability_ops
-        .entry(Capability::NetQuicWrite)
-        .or_default()
-        .push(drivers::net::write_op(drv, NetProtocol::Quic).as_linkable());
+    builder.register_operation(
+        drivers::net::listener_op(drv.clone(), NetProtocol::Quic).as_linkable(),
+        Capability::NetQuicBind,
+    );
+    builder.register_operation(
+        drivers::net::accept_op(drv.clone(), NetProtocol::Quic).as_linkable(),
+        Capability::NetQuicAccept,
+    );
+    builder.register_operation(
+        drivers::net::connect_op(drv.clone(), NetProtocol::Quic).as_linkable(),
+        Capability::NetQuicConnect,
+    );
+    builder.register_operation(
+        drivers::net::read_op(drv.clone(), NetProtocol::Quic).as_linkable(),
+        Capability::NetQuicRead,
+    );
+    builder.register_operation(
+        drivers::net::write_op(drv, NetProtocol::Quic).as_linkable(),
+        Capability::NetQuicWrite,
+    );
     let http_drv = builder.add_capability(HyperDriver::new(Arc::clone(&server_certified_key))?);
-    capability_ops
-        .entry(Capability::NetHttpBind)
-        .or_default()
-        .push(drivers::net::listener_op(http_drv.clone(), NetProtocol::Http).as_linkable());
-    capability_ops
-        .entry(Capability::NetHttpAccept)
-        .or_default()
-        .push(drivers::net::accept_op(http_drv.clone(), NetProtocol::Http).as_linkable());
-    capability_ops
-        .entry(Capability::NetHttpConnect)
-        .or_default()
-        .push(drivers::net::connect_op(http_drv.clone(), NetProtocol::Http).as_linkable());
-    capability_ops
-        .entry(Capability::NetHttpRead)
-        .or_default()
-        .push(drivers::net::read_op(http_drv.clone(), NetProtocol::Http).as_linkable());
-    capability_ops
-        .entry(Capability::NetHttpWrite)
-        .or_default()
-        .push(drivers::net::write_op(http_drv, NetProtocol::Http).as_linkable());
+    builder.register_operation(
+        drivers::net::listener_op(http_drv.clone(), NetProtocol::Http).as_linkable(),
+        Capability::NetHttpBind,
+    );
+    builder.register_operation(
+        drivers::net::accept_op(http_drv.clone(), NetProtocol::Http).as_linkable(),
+        Capability::NetHttpAccept,
+    );
+    builder.register_operation(
+        drivers::net::connect_op(http_drv.clone(), NetProtocol::Http).as_linkable(),
+        Capability::NetHttpConnect,
+    );
+    builder.register_operation(
+        drivers::net::read_op(http_drv.clone(), NetProtocol::Http).as_linkable(),
+        Capability::NetHttpRead,
+    );
+    builder.register_operation(
+        drivers::net::write_op(http_drv, NetProtocol::Http).as_linkable(),
+        Capability::NetHttpWrite,
+    );
 
     // Module Filesystem Store
-    let fs_store = FilesystemStore::new(&modules_dir);
+    let mut fs_store = FilesystemStore::new(&modules_dir);
+    if let Some(trust_root) = &options.trust_root {
+        fs_store = fs_store.with_trust_root(trust_root.clone());
+    }
+    if let Some(digests) = &options.allowed_digests {
+        fs_store = fs_store.with_allowed_digests(digests.iter().copied());
+    }
     let shutdown = Arc::new(Notify::new());
-    let guest_async_cap = builder.add_capability(Arc::new(GuestAsync::new(Arc::clone(&shutdown))));
-    let fs_store_drv = builder.add_capability(FilesystemStoreReadDriver::new(fs_store));
-    let wasm_runtime = Arc::new(WasmRuntime::new(
-        capability_ops.clone(),
-        Arc::clone(&guest_async_cap),
-    )?);
+    let mut guest_async = GuestAsync::new(Arc::clone(&shutdown));
+    if let Some(window) = options.wake_coalescing {
+        guest_async = guest_async.with_wake_coalescing(window);
+    }
+    let guest_async_cap = builder.add_capability(Arc::new(guest_async));
+    let module_store = ContentCache::new(
+        FilesystemStoreReadDriver::new(fs_store),
+        work_dir
+            .as_ref()
+            .join(CACHE_SUBDIR)
+            .join(CONTENT_CACHE_SUBDIR),
+    );
+    builder.add_capability(Arc::new(module_store.counters().clone()));
+    let fs_store_drv = builder.add_capability(Arc::new(module_store));
+    let sandboxes = builder.add_capability(Arc::new(Sandboxes::new(
+        work_dir.as_ref().join(SANDBOXES_SUBDIR),
+    )));
+    let fs_watch = drivers::fs::watch_operation(sandboxes);
+    builder.register_operations([fs_watch.as_linkable()], Capability::Filesystem);
+    builder.add_providers(options.providers.iter().map(AsRef::as_ref))?;
+    builder.add_plugins(options.plugins.iter().map(AsRef::as_ref))?;
+    let capability_ops = builder.operations().clone();
+    let host_info = drivers::meta::guest_host_info(options.redact_hostname);
+    let wasm_runtime = Arc::new(
+        WasmRuntime::new(
+            capability_ops.clone(),
+            Arc::clone(&guest_async_cap),
+            options.pooling,
+        )?
+        .with_module_cache(ModuleCache::new(
+            work_dir.as_ref().join(CACHE_SUBDIR),
+            load_cache_key(&work_dir.as_ref().join(CACHE_KEY_FILE))?,
+        ))
+        .with_crash_reports(CrashReports::new(work_dir.as_ref().join(CRASH_SUBDIR)))
+        .with_host_info(host_info),
+    );
     let drv = builder.add_capability(WasmtimeDriver::new(Arc::clone(&wasm_runtime), fs_store_drv));
     let process = drivers::process::lifecycle_ops(drv.clone());
+    let process_ops = vec![
+        process.0.as_linkable(),
+        process.1.as_linkable(),
+        process.2.as_linkable(),
+        drivers::process::info_op().as_linkable(),
+        drivers::process::send_op().as_linkable(),
+        process_logs.1.as_linkable(),
+    ];
+    apply_hostcall_timeouts(
+        capability_ops.values().flatten().chain(&process_ops),
+        &options.hostcall_timeouts,
+    );
+    if options.enforcement == Enforcement::Audit {
+        warn!("hostcall checks are audited only; calls that fail them will still run");
+        for operation in capability_ops.values().flatten().chain(&process_ops) {
+            operation.set_enforcement(Enforcement::Audit);
+        }
+    }
+    let bulk = Arc::new(PriorityClass::new("bulk").with_concurrency(BULK_HOSTCALL_CONCURRENCY));
+    for capability in BULK_CAPABILITIES {
+        for operation in capability_ops.get(capability).into_iter().flatten() {
+            operation.set_priority(Arc::clone(&bulk))?;
+        }
+    }
+    if let Some(window) = options.idempotency_window {
+        let cache = Arc::new(IdempotencyCache::new(window));
+        for operation in capability_ops.values().flatten().chain(&process_ops) {
+            operation.set_idempotency(Arc::clone(&cache))?;
+        }
+    }
+    let metrics = builder.add_capability(Arc::new(HostcallMetrics::default()));
+    for operation in capability_ops.values().flatten().chain(&process_ops) {
+        operation.intercept(metrics.clone())?;
+    }
+    if let Some(audit) = &audit {
+        for operation in capability_ops.values().flatten().chain(&process_ops) {
+            operation.intercept(audit.clone())?;
+        }
+    }
+    if options.watchdog != WatchdogConfig::default() {
+        let watchdog = builder.add_capability(Arc::new(Watchdog::new(options.watchdog)));
+        if options.watchdog.hostcall_budget.is_some() {
+            for operation in capability_ops.values().flatten().chain(&process_ops) {
+                operation.intercept(watchdog.clone())?;
+            }
+        }
+    }
+    builder.register_operations(process_ops.iter().cloned(), Capability::ProcessLifecycle);
     wasm_runtime
-        .extend_capability(
-            Capability::ProcessLifecycle,
-            vec![
-                process.0.as_linkable(),
-                process.1.as_linkable(),
-                process_logs.1.as_linkable(),
-            ],
-        )
+        .extend_capability(Capability::ProcessLifecycle, process_ops)
         .map_err(anyhow::Error::from)?;
 
+    builder.require_capabilities(Capability::ALL);
+    // Modules are spawned and supervised through these.
+    builder.assert_capability::<WasmtimeDriver>();
+    builder.assert_capability::<Sandboxes>();
+
     Ok((builder.build()?, shutdown))
 }
 
+/// Parse a `<hostcall>=<milliseconds>` timeout override, rejecting unknown hostcalls.
+pub fn parse_hostcall_timeout(raw: &str) -> Result<(String, Duration)> {
+    let (name, millis) = raw
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected <hostcall>=<milliseconds>"))?;
+    let name = name.trim();
+    if !hostcalls::ALL.iter().any(|meta| meta.name == name) {
+        return Err(anyhow!("unknown hostcall `{name}`"));
+    }
+    let millis: u64 = millis
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid timeout for `{name}`"))?;
+
+    Ok((name.to_string(), Duration::from_millis(millis)))
+}
+
+fn apply_hostcall_timeouts<'a>(
+    operations: impl IntoIterator<Item = &'a Arc<dyn LinkableOperation>>,
+    timeouts: &[(String, Duration)],
+) {
+    if timeouts.is_empty() {
+        return;
+    }
+
+    for operation in operations {
+        if let Some((_, timeout)) = timeouts
+            .iter()
+            .rev()
+            .find(|(name, _)| name == operation.module())
+        {
+            operation.set_timeout(Some(*timeout));
+        }
+    }
+}
+
+/// Read the module cache key at `path`, generating one readable only by its owner if there is
+/// none yet.
+fn load_cache_key(path: &Path) -> Result<[u8; blake3::KEY_LEN]> {
+    let mut key = [0; blake3::KEY_LEN];
+    match fs::read(path) {
+        Ok(bytes) => {
+            key = bytes
+                .try_into()
+                .map_err(|_| anyhow!("cache key {path:?} must be {} bytes", blake3::KEY_LEN))?;
+            return Ok(key);
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(err).with_context(|| format!("read cache key {path:?}")),
+    }
+
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| anyhow!("generate cache key"))?;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(&key))
+        .with_context(|| format!("write cache key {path:?}"))?;
+    Ok(key)
+}
+
 fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<sign::CertifiedKey> {
     let certificates = load_certificate_chain(cert_path)
         .with_context(|| format!("load certificate {cert_path:?}"))?;
@@ -215,7 +499,7 @@ fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<sign::Certifi
     Ok(certified_key)
 }
 
-fn load_certificate_chain(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+pub(crate) fn load_certificate_chain(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
     let bytes = fs::read(path).with_context(|| format!("read certificate file {path:?}"))?;
     let parsed = SliceIter::new(&bytes)
         .collect::<Result<Vec<_>, _>>()
@@ -227,7 +511,7 @@ fn load_certificate_chain(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
     Ok(vec![CertificateDer::from(bytes)])
 }
 
-fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+pub(crate) fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
     let bytes = fs::read(path).with_context(|| format!("read private key {path:?}"))?;
     let pkcs8_keys = SliceIter::new(&bytes)
         .collect::<Result<Vec<PrivatePkcs8KeyDer>, _>>()
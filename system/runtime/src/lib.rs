@@ -0,0 +1,66 @@
+//! The Selium host runtime, as a library for applications that embed Selium rather than run the
+//! `selium-runtime` binary.
+//!
+//! [`RuntimeBuilder`] assembles the same kernel the binary does, with the Wasmtime driver, the
+//! built-in hostcall drivers and any further [`CapabilityProvider`]s and
+//! [`HostCapabilityPlugin`]s, then starts and supervises modules on it. Lower-level building blocks are available from [`kernel`] and [`modules`].
+//!
+//! # Examples
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use selium_runtime::{RuntimeBuilder, modules};
+//!
+//! # async fn embed() -> anyhow::Result<()> {
+//! let work_dir = std::path::Path::new("/var/lib/selium");
+//! let runtime = RuntimeBuilder::new(work_dir)
+//!     .module(modules::parse_cli_spec(
+//!         "path=echo.wasm;capabilities=ChannelLifecycle,ChannelReader,ChannelWriter",
+//!         work_dir,
+//!     )?)
+//!     .start()
+//!     .await?;
+//!
+//! // ... serve the application ...
+//!
+//! runtime.shutdown(Duration::from_secs(5)).await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`CapabilityProvider`]: selium_kernel::CapabilityProvider
+//! [`HostCapabilityPlugin`]: selium_kernel::HostCapabilityPlugin
+
+pub use runtime::{Runtime, RuntimeBuilder, RuntimeHandle};
+
+mod audit;
+mod bench;
+mod bindings;
+mod certs;
+pub mod cli;
+pub mod config;
+#[cfg(unix)]
+mod control;
+#[cfg(unix)]
+mod diagnose;
+mod host_exec;
+mod identity;
+pub mod kernel;
+pub mod lifecycle;
+mod logging;
+#[cfg(unix)]
+mod metrics;
+pub mod modules;
+#[cfg(unix)]
+mod profile;
+pub mod reload;
+mod runtime;
+mod signing;
+pub mod startup;
+#[cfg(unix)]
+mod status;
+pub mod supervisor;
+#[cfg(feature = "otlp")]
+mod telemetry;
+mod tls;
+mod validate;
@@ -0,0 +1,150 @@
+//! Callbacks fired as supervised processes start, exit and restart.
+//!
+//! Embedders register callbacks with
+//! [`RuntimeBuilder::on_lifecycle`](crate::RuntimeBuilder::on_lifecycle) to schedule work or bill
+//! tenants as processes come and go, instead of polling the registry. Callbacks run on a task of
+//! their own, one event at a time and in the order the events happened, so a slow callback
+//! delays the callbacks after it but never the supervisor.
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::future::BoxFuture;
+use selium_abi::{CapabilitySet, TrapReason};
+use selium_kernel::registry::ResourceId;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tracing::warn;
+
+/// An async callback fired for every lifecycle event of a supervised process.
+pub type LifecycleCallback =
+    Arc<dyn Fn(LifecycleEvent, ProcessInfo) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// What happened to a supervised process.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum LifecycleEvent {
+    /// The process started running a module for the first time, or again after being stopped.
+    Started,
+    /// The process's entrypoint returned successfully.
+    Exited,
+    /// The process trapped, or its entrypoint returned an error.
+    Trapped {
+        /// Why the process failed.
+        error: String,
+        /// How the process trapped, as classified by its runtime; `None` if it failed without
+        /// running, or its task did not complete.
+        reason: Option<TrapReason>,
+    },
+    /// The process replaced `previous`, after it exited, was restarted by an operator or was
+    /// reloaded.
+    Restarted {
+        /// Process the module ran in before.
+        previous: ResourceId,
+    },
+    /// The process was stopped by an operator or for shutdown.
+    Stopped,
+}
+
+/// Metadata of the process a lifecycle event concerns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessInfo {
+    /// Module path as given in its specification.
+    pub label: String,
+    /// Registry id of the process.
+    pub process_id: ResourceId,
+    /// Capabilities granted to the process.
+    pub capabilities: CapabilitySet,
+    /// How long the process had been running when the event happened.
+    pub uptime: Duration,
+    /// Fuel the process had consumed when the event happened; zero where the runtime does not
+    /// report it.
+    pub fuel_consumed: u64,
+}
+
+/// Sending half of the task that runs the registered callbacks. Without callbacks, events are
+/// dropped as they are fired.
+#[derive(Clone, Default)]
+pub(crate) struct Lifecycle {
+    sender: Option<UnboundedSender<(LifecycleEvent, ProcessInfo)>>,
+}
+
+impl Lifecycle {
+    /// Start the task running `callbacks`, if there are any.
+    pub(crate) fn new(callbacks: Vec<LifecycleCallback>) -> Self {
+        if callbacks.is_empty() {
+            return Self::default();
+        }
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(LifecycleEvent, ProcessInfo)>();
+        tokio::spawn(async move {
+            while let Some((event, process)) = receiver.recv().await {
+                for callback in &callbacks {
+                    callback(event.clone(), process.clone()).await;
+                }
+            }
+        });
+        Self {
+            sender: Some(sender),
+        }
+    }
+
+    /// Queue `event` for the callbacks.
+    pub(crate) fn fire(&self, event: LifecycleEvent, process: ProcessInfo) {
+        if let Some(sender) = &self.sender
+            && sender.send((event, process)).is_err()
+        {
+            warn!("lifecycle callbacks have stopped; dropping event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+
+    fn process(process_id: ResourceId) -> ProcessInfo {
+        ProcessInfo {
+            label: "echo.wasm".to_string(),
+            process_id,
+            capabilities: CapabilitySet::default(),
+            uptime: Duration::ZERO,
+            fuel_consumed: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn callbacks_see_events_in_order() {
+        let (seen, mut received) = unbounded_channel();
+        let callback: LifecycleCallback = Arc::new(move |event, process: ProcessInfo| {
+            let seen = seen.clone();
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                seen.send((event, process.process_id))
+                    .expect("test receiver");
+            })
+        });
+        let lifecycle = Lifecycle::new(vec![Arc::clone(&callback), callback]);
+
+        lifecycle.fire(LifecycleEvent::Started, process(1));
+        lifecycle.fire(LifecycleEvent::Exited, process(1));
+        lifecycle.fire(LifecycleEvent::Restarted { previous: 1 }, process(2));
+
+        let mut events = Vec::new();
+        for _ in 0..6 {
+            events.push(received.recv().await.expect("event"));
+        }
+        assert_eq!(
+            events,
+            [
+                (LifecycleEvent::Started, 1),
+                (LifecycleEvent::Started, 1),
+                (LifecycleEvent::Exited, 1),
+                (LifecycleEvent::Exited, 1),
+                (LifecycleEvent::Restarted { previous: 1 }, 2),
+                (LifecycleEvent::Restarted { previous: 1 }, 2),
+            ]
+        );
+
+        Lifecycle::default().fire(LifecycleEvent::Stopped, process(3));
+    }
+}
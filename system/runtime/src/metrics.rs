@@ -0,0 +1,161 @@
+//! The `metrics` subcommand, reporting hostcall metrics of a running host in the Prometheus
+//! text exposition format, so that they can be scraped through a textfile collector.
+//!
+//! The host's module cache counters follow, if it caches modules, and then the counters and
+//! gauges kept by guests, under the names the guests gave them.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use crate::control::{ControlClient, MetricsReport};
+
+/// Print the hostcall and guest metrics of the host `client` is connected to.
+pub async fn print(mut client: ControlClient) -> Result<()> {
+    let report = client.metrics().await?;
+    print!("{}", render(&report)?);
+    Ok(())
+}
+
+fn render(report: &MetricsReport) -> Result<String> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "# HELP selium_hostcall_errors_total Hostcalls that failed, by error code."
+    )?;
+    writeln!(out, "# TYPE selium_hostcall_errors_total counter")?;
+    for stats in &report.hostcalls {
+        for (code, count) in &stats.errors {
+            writeln!(
+                out,
+                "selium_hostcall_errors_total{{hostcall=\"{}\",code=\"{code:?}\"}} {count}",
+                stats.module
+            )?;
+        }
+    }
+
+    writeln!(
+        out,
+        "# HELP selium_hostcall_duration_seconds Time from hostcall creation to resolution."
+    )?;
+    writeln!(out, "# TYPE selium_hostcall_duration_seconds histogram")?;
+    for stats in &report.hostcalls {
+        let hostcall = &stats.module;
+        let mut cumulative = 0;
+        for (bound, count) in report.latency_bounds_us.iter().zip(&stats.latency.buckets) {
+            cumulative += count;
+            writeln!(
+                out,
+                "selium_hostcall_duration_seconds_bucket{{hostcall=\"{hostcall}\",le=\"{}\"}} {cumulative}",
+                *bound as f64 / 1e6
+            )?;
+        }
+        writeln!(
+            out,
+            "selium_hostcall_duration_seconds_bucket{{hostcall=\"{hostcall}\",le=\"+Inf\"}} {}",
+            stats.calls
+        )?;
+        writeln!(
+            out,
+            "selium_hostcall_duration_seconds_sum{{hostcall=\"{hostcall}\"}} {}",
+            stats.latency.total.as_secs_f64()
+        )?;
+        writeln!(
+            out,
+            "selium_hostcall_duration_seconds_count{{hostcall=\"{hostcall}\"}} {}",
+            stats.calls
+        )?;
+    }
+
+    if let Some(cache) = &report.module_cache {
+        for (name, help, value) in [
+            (
+                "selium_module_cache_hits_total",
+                "Module reads served from the module cache.",
+                cache.hits,
+            ),
+            (
+                "selium_module_cache_misses_total",
+                "Module reads fetched from the module store.",
+                cache.misses,
+            ),
+            (
+                "selium_module_cache_integrity_failures_total",
+                "Cached modules discarded as missing or altered.",
+                cache.integrity_failures,
+            ),
+        ] {
+            writeln!(out, "# HELP {name} {help}")?;
+            writeln!(out, "# TYPE {name} counter")?;
+            writeln!(out, "{name} {value}")?;
+        }
+    }
+
+    for (name, value) in &report.guest.counters {
+        writeln!(out, "# TYPE {name} counter")?;
+        writeln!(out, "{name} {value}")?;
+    }
+    for (name, value) in &report.guest.gauges {
+        writeln!(out, "# TYPE {name} gauge")?;
+        writeln!(out, "{name} {value}")?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use selium_abi::ErrorCode;
+    use selium_filesystem_store::CacheMetrics;
+    use selium_kernel::metrics::{GuestMetricsSnapshot, HostcallStats, LatencyHistogram};
+
+    use super::*;
+
+    #[test]
+    fn histograms_are_cumulative() {
+        let report = MetricsReport {
+            latency_bounds_us: vec![100, 1_000],
+            hostcalls: vec![HostcallStats {
+                module: "selium::time::sleep".to_string(),
+                calls: 3,
+                errors: vec![(ErrorCode::TimedOut, 1)],
+                latency: LatencyHistogram {
+                    buckets: vec![1, 1, 1],
+                    total: Duration::from_millis(1_500),
+                },
+            }],
+            guest: GuestMetricsSnapshot {
+                counters: vec![("jobs_total".to_string(), 4)],
+                gauges: vec![("queue_depth".to_string(), -2)],
+            },
+            module_cache: Some(CacheMetrics {
+                hits: 5,
+                misses: 2,
+                integrity_failures: 0,
+            }),
+        };
+
+        let rendered = render(&report).expect("render");
+        let samples: Vec<_> = rendered
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        assert_eq!(
+            samples,
+            [
+                r#"selium_hostcall_errors_total{hostcall="selium::time::sleep",code="TimedOut"} 1"#,
+                r#"selium_hostcall_duration_seconds_bucket{hostcall="selium::time::sleep",le="0.0001"} 1"#,
+                r#"selium_hostcall_duration_seconds_bucket{hostcall="selium::time::sleep",le="0.001"} 2"#,
+                r#"selium_hostcall_duration_seconds_bucket{hostcall="selium::time::sleep",le="+Inf"} 3"#,
+                r#"selium_hostcall_duration_seconds_sum{hostcall="selium::time::sleep"} 1.5"#,
+                r#"selium_hostcall_duration_seconds_count{hostcall="selium::time::sleep"} 3"#,
+                "selium_module_cache_hits_total 5",
+                "selium_module_cache_misses_total 2",
+                "selium_module_cache_integrity_failures_total 0",
+                "jobs_total 4",
+                "queue_depth -2",
+            ]
+        );
+    }
+}
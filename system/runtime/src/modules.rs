@@ -1,3 +1,5 @@
+//! Module specifications, and starting the processes that run them.
+
 use std::{
     io::ErrorKind,
     path::{Component, Path, PathBuf},
@@ -7,19 +9,27 @@ use std::{
 
 use anyhow::{Context, Result, anyhow, bail};
 use selium_abi::{
-    AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, Capability, EntrypointArg,
-    EntrypointInvocation, GuestResourceId,
+    AbiParam, AbiScalarType, AbiScalarValue, AbiSignature, Capability, CapabilitySet,
+    EntrypointArg, EntrypointInvocation, GuestResourceId,
 };
+use selium_filesystem_store::{Mount, MountAccess, SandboxPolicy, Sandboxes};
 use selium_kernel::{
     Kernel, KernelError,
-    drivers::process::ProcessLifecycleCapability,
     registry::{Registry, ResourceHandle, ResourceId, ResourceType},
 };
 use selium_messaging::Channel;
 use selium_userland::fbs::selium::logging::{self as log_fb, LogLevel};
-use selium_wasmtime::{Error as WasmtimeError, WasmtimeDriver};
+use selium_wasmtime::{Error as WasmtimeError, ExecutionLimits, WasmtimeDriver};
+use serde::Deserialize;
 use tokio::time::sleep;
-use tracing::{Level, Span, info, instrument, warn};
+use tracing::{
+    Instrument, Level, Span, info, info_span, instrument, level_filters::LevelFilter, warn,
+};
+
+use crate::{
+    config::{self, ModuleConfig, ModuleManifest},
+    logging::{self, LogOutput, MODULE_SPAN, ModuleLogSettings},
+};
 
 const LOG_FRAME_CAPACITY: usize = 512 * 1024;
 const LOG_CHANNEL_WAIT: Duration = Duration::from_secs(5);
@@ -33,13 +43,47 @@ struct ModuleArgs {
     args: Vec<EntrypointArg>,
 }
 
-struct ModuleSpec {
+/// A parsed `--module` specification.
+#[derive(Clone)]
+pub struct ModuleSpec {
     module_label: String,
     module_path: PathBuf,
     entrypoint: String,
-    capabilities: Vec<Capability>,
+    capabilities: CapabilitySet,
     params: Vec<AbiParam>,
     args: Vec<EntrypointArg>,
+    limits: ExecutionLimits,
+    restart: RestartPolicy,
+    log: ModuleLogSettings,
+    sandbox: SandboxPolicy,
+}
+
+/// When a module's process is restarted after it exits.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Leave the module stopped.
+    #[default]
+    Never,
+    /// Restart the module if its entrypoint fails or traps.
+    OnFailure,
+    /// Restart the module whenever it exits.
+    Always,
+}
+
+/// A module started from a specification, and the process currently running it.
+pub struct SpawnedModule {
+    /// Specification the module was started from.
+    pub spec: ModuleSpec,
+    /// Process running the module.
+    pub process_id: ResourceId,
+}
+
+/// A parsed `--prewarm` specification.
+struct PrewarmSpec {
+    module_id: String,
+    capabilities: CapabilitySet,
+    count: usize,
 }
 
 #[derive(Default)]
@@ -47,9 +91,15 @@ struct ModuleSpecBuilder {
     path: Option<String>,
     entrypoint: Option<String>,
     log_uri: Option<String>,
-    capabilities: Option<Vec<Capability>>,
+    capabilities: Option<CapabilitySet>,
     params: Option<Vec<ParamKind>>,
     args: Option<Vec<Argument>>,
+    fuel: Option<u64>,
+    restart: Option<RestartPolicy>,
+    log_level: Option<LevelFilter>,
+    log_output: Option<LogOutput>,
+    sandbox: Option<MountAccess>,
+    mounts: Option<Vec<Mount>>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -75,6 +125,58 @@ enum Argument {
     Untyped(String),
 }
 
+impl ModuleSpec {
+    /// Module path as given in the specification.
+    pub fn label(&self) -> &str {
+        &self.module_label
+    }
+
+    /// Location of the module file.
+    pub fn path(&self) -> &Path {
+        &self.module_path
+    }
+
+    /// Capabilities granted to the module.
+    pub fn capabilities(&self) -> CapabilitySet {
+        self.capabilities
+    }
+
+    /// When the module is restarted after it exits.
+    pub fn restart(&self) -> RestartPolicy {
+        self.restart
+    }
+
+    /// Log level and output of the module.
+    pub fn log(&self) -> &ModuleLogSettings {
+        &self.log
+    }
+
+    /// Access to the module's sandbox directory and the host directories mounted into it.
+    pub fn sandbox(&self) -> &SandboxPolicy {
+        &self.sandbox
+    }
+}
+
+impl RestartPolicy {
+    fn from_label(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "never" | "no" => Some(Self::Never),
+            "on-failure" | "on_failure" | "onfailure" => Some(Self::OnFailure),
+            "always" => Some(Self::Always),
+            _ => None,
+        }
+    }
+
+    /// Whether a process that exited, successfully or not, should be restarted.
+    pub fn should_restart(self, succeeded: bool) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure => !succeeded,
+            Self::Always => true,
+        }
+    }
+}
+
 impl ModuleSpecBuilder {
     fn is_empty(&self) -> bool {
         self.path.is_none()
@@ -83,6 +185,12 @@ impl ModuleSpecBuilder {
             && self.capabilities.is_none()
             && self.params.is_none()
             && self.args.is_none()
+            && self.fuel.is_none()
+            && self.restart.is_none()
+            && self.log_level.is_none()
+            && self.log_output.is_none()
+            && self.sandbox.is_none()
+            && self.mounts.is_none()
     }
 }
 
@@ -150,40 +258,168 @@ macro_rules! emit_guest_log_event {
     };
 }
 
-/// Read module specifications from CLI strings and start each module with log forwarding.
+/// Parse module specifications from CLI strings.
 ///
 /// Input format per module: a `;`-delimited list of `key=value` entries. Required keys are
 /// `path` and `capabilities`. Optional keys are `entrypoint` (defaults to `start`), `log_uri`,
-/// `params`, and `args`. The runtime always injects the log URI buffer ahead of any user
-/// params; `log_uri` overrides the default empty value. The `args` value is a comma-separated
-/// list of values that may be prefixed with `TYPE:` to infer parameter kinds. When `params`
-/// is omitted, every arg must be typed. The `path` must be relative to `work_dir`.
+/// `params`, `args`, `fuel` (the Wasm fuel budget; unlimited when omitted), `restart`
+/// (`never`, `on-failure` or `always`; defaults to `never`), `log_level` and `log_output` (see
+/// the `logging` module; `log_output` takes `host`, `stderr`, `file:PATH` or `json:PATH`,
+/// with `PATH` relative to `work_dir`), `sandbox` (`rw` or `ro`, the module's access to its own
+/// sandbox directory; defaults to `rw`) and `mounts` (a comma-separated list of `PATH[:ro|:rw]`
+/// directories, relative to `work_dir` unless absolute, mounted into the sandbox under their
+/// final component and read-only unless marked `rw`).
+/// The runtime always injects the log
+/// URI buffer ahead of any user params; `log_uri` overrides the default empty value. The `args`
+/// value is a comma-separated list of values that may be prefixed with `TYPE:` to infer
+/// parameter kinds. When `params` is omitted, every arg must be typed. The `path` must be
+/// relative to `work_dir`. Keys left out are taken from the module's manifest, if it has one (see
+/// [`config::load_manifest`]); when the manifest grants capabilities, `path` is the only
+/// required key.
 ///
 /// Supported argument types: `i8`, `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64`, `f32`,
 /// `f64`, `buffer`, `utf8`, `resource`. Buffer values support a `hex:` prefix to pass raw
 /// bytes.
-pub async fn spawn_from_cli(
+pub fn parse_cli_specs(specs: &[String], work_dir: impl AsRef<Path>) -> Result<Vec<ModuleSpec>> {
+    parse_module_specs(specs, work_dir.as_ref())
+}
+
+/// Parse a single module specification in the format accepted by [`parse_cli_specs`].
+pub fn parse_cli_spec(raw: &str, work_dir: &Path) -> Result<ModuleSpec> {
+    parse_module_spec(raw, work_dir)
+}
+
+/// Build a module specification from a deployment file entry, with the same validation as a
+/// CLI specification.
+pub fn spec_from_config(module: &ModuleConfig, work_dir: &Path) -> Result<ModuleSpec> {
+    let builder = ModuleSpecBuilder {
+        path: Some(module.path.clone()),
+        entrypoint: module.entrypoint.clone(),
+        log_uri: module.log_uri.clone(),
+        capabilities: module
+            .capabilities
+            .as_deref()
+            .map(capability_list)
+            .transpose()?,
+        params: module.params.as_deref().map(param_list).transpose()?,
+        args: module.args.as_deref().map(argument_list),
+        fuel: module.limits.fuel,
+        restart: module.restart,
+        log_level: module
+            .log_level
+            .as_deref()
+            .map(logging::parse_level)
+            .transpose()?,
+        log_output: module
+            .log_output
+            .as_deref()
+            .map(|raw| LogOutput::parse(raw, work_dir))
+            .transpose()?,
+        sandbox: module.sandbox.as_deref().map(parse_access).transpose()?,
+        mounts: module
+            .mounts
+            .as_deref()
+            .map(|mounts| mount_list(mounts, work_dir))
+            .transpose()?,
+    };
+    build_module_spec(builder, work_dir)
+}
+
+/// Start each module with log forwarding.
+pub async fn spawn_all(
     kernel: &Kernel,
     registry: &Arc<Registry>,
-    work_dir: impl AsRef<Path>,
-    specs: &[String],
-) -> Result<Vec<ResourceId>> {
-    let specs = parse_module_specs(specs, work_dir.as_ref())?;
-    let runtime = kernel.get::<WasmtimeDriver>().ok_or_else(|| {
-        WasmtimeError::Kernel(KernelError::Driver(
-            "missing Wasmtime driver in kernel".to_string(),
-        ))
-    })?;
+    specs: Vec<ModuleSpec>,
+) -> Result<Vec<SpawnedModule>> {
+    let runtime = kernel.require::<WasmtimeDriver>()?;
+
+    let sandboxes = kernel.require::<Sandboxes>()?;
 
     let mut processes = Vec::with_capacity(specs.len());
     for spec in specs {
-        let process_id = spawn_module(runtime, registry, spec).await?;
-        processes.push(process_id);
+        let process_id = spawn_module(runtime, registry, sandboxes, &spec, None).await?;
+        processes.push(SpawnedModule { spec, process_id });
     }
 
     Ok(processes)
 }
 
+/// Keep pre-instantiated instances of modules ready so that starting them skips compilation and
+/// instantiation.
+///
+/// Input format per module: a `;`-delimited list of `key=value` entries with the keys `module`
+/// (the module id as passed to `process::start`), `capabilities` and `count`. Only starts that
+/// grant exactly these capabilities are served from the pool.
+pub async fn prewarm_from_cli(
+    kernel: &Kernel,
+    registry: &Arc<Registry>,
+    specs: &[String],
+) -> Result<()> {
+    let runtime = kernel.require::<WasmtimeDriver>()?;
+
+    for (index, raw) in specs.iter().enumerate() {
+        let spec = parse_prewarm_spec(raw)
+            .with_context(|| format!("parse prewarm specification {}", index + 1))?;
+        runtime
+            .prewarm(registry, &spec.module_id, spec.capabilities, spec.count)
+            .await
+            .with_context(|| format!("prewarm module {}", spec.module_id))?;
+        info!(
+            module = spec.module_id,
+            count = spec.count,
+            "pre-warmed module instances"
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_prewarm_spec(raw: &str) -> Result<PrewarmSpec> {
+    let mut module_id = None;
+    let mut capabilities = None;
+    let mut count = None;
+
+    for (index, entry) in raw.split(';').enumerate() {
+        let line_no = index + 1;
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("entry {line_no}: expected key=value"))?;
+        let value = value.trim();
+        match key.trim() {
+            "module" if module_id.is_none() => module_id = Some(value.to_string()),
+            "capabilities" if capabilities.is_none() => {
+                capabilities = Some(parse_capabilities(value)?)
+            }
+            "count" if count.is_none() => {
+                count = Some(
+                    value
+                        .parse()
+                        .with_context(|| format!("entry {line_no}: invalid count `{value}`"))?,
+                )
+            }
+            key @ ("module" | "capabilities" | "count") => {
+                return Err(anyhow!("entry {line_no}: duplicate {key}"));
+            }
+            key => return Err(anyhow!("entry {line_no}: unknown key `{key}`")),
+        }
+    }
+
+    let module_id = module_id
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| anyhow!("prewarm specification missing module"))?;
+    Ok(PrewarmSpec {
+        module_id,
+        capabilities: capabilities
+            .ok_or_else(|| anyhow!("prewarm specification missing capabilities"))?,
+        count: count.ok_or_else(|| anyhow!("prewarm specification missing count"))?,
+    })
+}
+
 fn parse_module_specs(specs: &[String], work_dir: &Path) -> Result<Vec<ModuleSpec>> {
     if specs.is_empty() {
         return Err(anyhow!("no module specifications provided"));
@@ -254,6 +490,48 @@ fn parse_module_spec(raw: &str, work_dir: &Path) -> Result<ModuleSpec> {
                 }
                 builder.args = Some(parse_args(value)?);
             }
+            "fuel" => {
+                if builder.fuel.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate fuel"));
+                }
+                let fuel = value
+                    .parse()
+                    .with_context(|| format!("entry {line_no}: invalid fuel `{value}`"))?;
+                builder.fuel = Some(fuel);
+            }
+            "restart" => {
+                if builder.restart.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate restart"));
+                }
+                let restart = RestartPolicy::from_label(value)
+                    .ok_or_else(|| anyhow!("entry {line_no}: unknown restart policy `{value}`"))?;
+                builder.restart = Some(restart);
+            }
+            "log_level" | "log-level" => {
+                if builder.log_level.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate log_level"));
+                }
+                builder.log_level = Some(logging::parse_level(value)?);
+            }
+            "log_output" | "log-output" => {
+                if builder.log_output.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate log_output"));
+                }
+                builder.log_output = Some(LogOutput::parse(value, work_dir)?);
+            }
+            "sandbox" => {
+                if builder.sandbox.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate sandbox"));
+                }
+                builder.sandbox = Some(parse_access(value)?);
+            }
+            "mounts" | "mount" => {
+                if builder.mounts.is_some() {
+                    return Err(anyhow!("entry {line_no}: duplicate mounts"));
+                }
+                let mounts: Vec<_> = value.split(',').map(|item| item.to_string()).collect();
+                builder.mounts = Some(mount_list(&mounts, work_dir)?);
+            }
             _ => return Err(anyhow!("entry {line_no}: unknown key `{key}`")),
         }
     }
@@ -265,10 +543,19 @@ fn parse_module_spec(raw: &str, work_dir: &Path) -> Result<ModuleSpec> {
     build_module_spec(builder, work_dir)
 }
 
-fn build_module_spec(builder: ModuleSpecBuilder, work_dir: &Path) -> Result<ModuleSpec> {
+fn build_module_spec(mut builder: ModuleSpecBuilder, work_dir: &Path) -> Result<ModuleSpec> {
     let path = builder
         .path
+        .take()
         .ok_or_else(|| anyhow!("module specification missing path"))?;
+    if path.trim().is_empty() {
+        return Err(anyhow!("module path must not be empty"));
+    }
+    let module_path = work_dir.join(parse_relative_path(&path, "module path")?);
+    if let Some(manifest) = config::load_manifest(&module_path)? {
+        apply_manifest(&mut builder, manifest, work_dir)?;
+    }
+
     let entrypoint = builder
         .entrypoint
         .unwrap_or_else(|| DEFAULT_ENTRYPOINT.to_string());
@@ -276,12 +563,22 @@ fn build_module_spec(builder: ModuleSpecBuilder, work_dir: &Path) -> Result<Modu
     let capabilities = builder.capabilities.unwrap_or_default();
     let args = builder.args.unwrap_or_default();
     let params = builder.params.unwrap_or_default();
+    let limits = ExecutionLimits { fuel: builder.fuel };
+    let restart = builder.restart.unwrap_or_default();
+    let log = ModuleLogSettings {
+        level: builder.log_level,
+        output: builder.log_output.unwrap_or_default(),
+    };
+    let sandbox = SandboxPolicy {
+        root: builder.sandbox.unwrap_or_default(),
+        mounts: builder.mounts.unwrap_or_default(),
+    };
+    if let Some(name) = sandbox.duplicate_mount() {
+        return Err(anyhow!("more than one mount is named `{name}`"));
+    }
     let (params, values) = resolve_arguments(params, args)?;
     let ModuleArgs { params, args } = inject_log_uri(build_module_args(params, values)?, log_uri)?;
 
-    if path.trim().is_empty() {
-        return Err(anyhow!("module path must not be empty"));
-    }
     if entrypoint.trim().is_empty() {
         return Err(anyhow!("entrypoint must not be empty"));
     }
@@ -289,8 +586,6 @@ fn build_module_spec(builder: ModuleSpecBuilder, work_dir: &Path) -> Result<Modu
         return Err(anyhow!("capabilities list must not be empty"));
     }
 
-    let module_path = work_dir.join(parse_relative_path(&path)?);
-
     Ok(ModuleSpec {
         module_label: path,
         module_path,
@@ -298,13 +593,77 @@ fn build_module_spec(builder: ModuleSpecBuilder, work_dir: &Path) -> Result<Modu
         capabilities,
         params,
         args,
+        limits,
+        restart,
+        log,
+        sandbox,
     })
 }
 
-fn parse_relative_path(raw: &str) -> Result<PathBuf> {
+/// Fill each key `builder` leaves unset from the module's manifest.
+fn apply_manifest(
+    builder: &mut ModuleSpecBuilder,
+    manifest: ModuleManifest,
+    work_dir: &Path,
+) -> Result<()> {
+    if builder.entrypoint.is_none() {
+        builder.entrypoint = manifest.entrypoint;
+    }
+    if builder.log_uri.is_none() {
+        builder.log_uri = manifest.log_uri;
+    }
+    if builder.capabilities.is_none() {
+        builder.capabilities = manifest
+            .capabilities
+            .as_deref()
+            .map(capability_list)
+            .transpose()?;
+    }
+    if builder.params.is_none() {
+        builder.params = manifest.params.as_deref().map(param_list).transpose()?;
+    }
+    if builder.args.is_none() {
+        builder.args = manifest.args.as_deref().map(argument_list);
+    }
+    if builder.fuel.is_none() {
+        builder.fuel = manifest.limits.fuel;
+    }
+    if builder.restart.is_none() {
+        builder.restart = manifest.restart;
+    }
+    if builder.log_level.is_none() {
+        builder.log_level = manifest
+            .log_level
+            .as_deref()
+            .map(logging::parse_level)
+            .transpose()?;
+    }
+    if builder.log_output.is_none() {
+        builder.log_output = manifest
+            .log_output
+            .as_deref()
+            .map(|raw| LogOutput::parse(raw, work_dir))
+            .transpose()?;
+    }
+    if builder.sandbox.is_none() {
+        builder.sandbox = manifest.sandbox.as_deref().map(parse_access).transpose()?;
+    }
+    if builder.mounts.is_none() {
+        builder.mounts = manifest
+            .mounts
+            .as_deref()
+            .map(|mounts| mount_list(mounts, work_dir))
+            .transpose()?;
+    }
+    Ok(())
+}
+
+/// Parse `raw` as a path that stays within the directory it is joined onto: relative, and free
+/// of `..` segments. `what` names the path in errors.
+pub(crate) fn parse_relative_path(raw: &str, what: &str) -> Result<PathBuf> {
     let path = Path::new(raw);
     if path.is_absolute() {
-        return Err(anyhow!("module path must be relative"));
+        return Err(anyhow!("{what} must be relative"));
     }
 
     if path.components().any(|component| {
@@ -313,74 +672,106 @@ fn parse_relative_path(raw: &str) -> Result<PathBuf> {
             Component::Prefix(_) | Component::RootDir | Component::ParentDir
         )
     }) {
-        return Err(anyhow!("module path must not contain parent segments"));
+        return Err(anyhow!("{what} must not contain parent segments"));
     }
 
     Ok(path.to_path_buf())
 }
 
-fn parse_capabilities(raw: &str) -> Result<Vec<Capability>> {
+fn parse_capabilities(raw: &str) -> Result<CapabilitySet> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Err(anyhow!("capabilities list must not be empty"));
     }
 
-    let mut caps = Vec::new();
+    let mut caps = CapabilitySet::EMPTY;
     for item in trimmed.split(',') {
         let item = item.trim();
         if item.is_empty() {
             return Err(anyhow!("capability entry must not be empty"));
         }
-        let capability = match item.to_ascii_lowercase().as_str() {
-            "sessionlifecycle" | "session_lifecycle" | "session-lifecycle" => {
-                Capability::SessionLifecycle
-            }
-            "channellifecycle" | "channel_lifecycle" | "channel-lifecycle" => {
-                Capability::ChannelLifecycle
-            }
-            "channelreader" | "channel_reader" | "channel-reader" => Capability::ChannelReader,
-            "channelwriter" | "channel_writer" | "channel-writer" => Capability::ChannelWriter,
-            "processlifecycle" | "process_lifecycle" | "process-lifecycle" => {
-                Capability::ProcessLifecycle
-            }
-            "netquicbind" | "net_quic_bind" | "net-quic-bind" => Capability::NetQuicBind,
-            "netquicaccept" | "net_quic_accept" | "net-quic-accept" => Capability::NetQuicAccept,
-            "netquicconnect" | "net_quic_connect" | "net-quic-connect" => {
-                Capability::NetQuicConnect
-            }
-            "netquicread" | "net_quic_read" | "net-quic-read" => Capability::NetQuicRead,
-            "netquicwrite" | "net_quic_write" | "net-quic-write" => Capability::NetQuicWrite,
-            "nethttpbind" | "net_http_bind" | "net-http-bind" => Capability::NetHttpBind,
-            "nethttpaccept" | "net_http_accept" | "net-http-accept" => Capability::NetHttpAccept,
-            "nethttpconnect" | "net_http_connect" | "net-http-connect" => {
-                Capability::NetHttpConnect
-            }
-            "nethttpread" | "net_http_read" | "net-http-read" => Capability::NetHttpRead,
-            "nethttpwrite" | "net_http_write" | "net-http-write" => Capability::NetHttpWrite,
-            "nettlsserverconfig" | "net_tls_server_config" | "net-tls-server-config" => {
-                Capability::NetTlsServerConfig
-            }
-            "nettlsclientconfig" | "net_tls_client_config" | "net-tls-client-config" => {
-                Capability::NetTlsClientConfig
-            }
-            "singletonregistry" | "singleton_registry" | "singleton-registry" => {
-                Capability::SingletonRegistry
-            }
-            "singletonlookup" | "singleton_lookup" | "singleton-lookup" => {
-                Capability::SingletonLookup
-            }
-            "timeread" | "time_read" | "time-read" => Capability::TimeRead,
-            _ => return Err(anyhow!("unknown capability `{item}`")),
-        };
-
-        if !caps.contains(&capability) {
-            caps.push(capability);
-        }
+        caps.insert(parse_capability(item)?);
     }
 
     Ok(caps)
 }
 
+fn capability_list(items: &[String]) -> Result<CapabilitySet> {
+    items
+        .iter()
+        .map(|item| parse_capability(item.trim()))
+        .collect()
+}
+
+fn parse_access(raw: &str) -> Result<MountAccess> {
+    raw.trim().parse().map_err(|err: String| anyhow!(err))
+}
+
+/// Parse `PATH[:ro|:rw]` mounts, resolving relative paths against `work_dir`.
+fn mount_list(items: &[String], work_dir: &Path) -> Result<Vec<Mount>> {
+    items
+        .iter()
+        .map(|item| {
+            let mount: Mount = item.trim().parse().map_err(|err: String| anyhow!(err))?;
+            Mount::new(work_dir.join(mount.source()), mount.access()).map_err(|err| anyhow!(err))
+        })
+        .collect()
+}
+
+/// Parse a capability name, ignoring case and accepting `-` or `_` between words.
+pub(crate) fn parse_capability(item: &str) -> Result<Capability> {
+    let capability = match item.to_ascii_lowercase().as_str() {
+        "sessionlifecycle" | "session_lifecycle" | "session-lifecycle" => {
+            Capability::SessionLifecycle
+        }
+        "channellifecycle" | "channel_lifecycle" | "channel-lifecycle" => {
+            Capability::ChannelLifecycle
+        }
+        "channelreader" | "channel_reader" | "channel-reader" => Capability::ChannelReader,
+        "channelwriter" | "channel_writer" | "channel-writer" => Capability::ChannelWriter,
+        "processlifecycle" | "process_lifecycle" | "process-lifecycle" => {
+            Capability::ProcessLifecycle
+        }
+        "netquicbind" | "net_quic_bind" | "net-quic-bind" => Capability::NetQuicBind,
+        "netquicaccept" | "net_quic_accept" | "net-quic-accept" => Capability::NetQuicAccept,
+        "netquicconnect" | "net_quic_connect" | "net-quic-connect" => Capability::NetQuicConnect,
+        "netquicread" | "net_quic_read" | "net-quic-read" => Capability::NetQuicRead,
+        "netquicwrite" | "net_quic_write" | "net-quic-write" => Capability::NetQuicWrite,
+        "nethttpbind" | "net_http_bind" | "net-http-bind" => Capability::NetHttpBind,
+        "nethttpaccept" | "net_http_accept" | "net-http-accept" => Capability::NetHttpAccept,
+        "nethttpconnect" | "net_http_connect" | "net-http-connect" => Capability::NetHttpConnect,
+        "nethttpread" | "net_http_read" | "net-http-read" => Capability::NetHttpRead,
+        "nethttpwrite" | "net_http_write" | "net-http-write" => Capability::NetHttpWrite,
+        "nettlsserverconfig" | "net_tls_server_config" | "net-tls-server-config" => {
+            Capability::NetTlsServerConfig
+        }
+        "nettlsclientconfig" | "net_tls_client_config" | "net-tls-client-config" => {
+            Capability::NetTlsClientConfig
+        }
+        "singletonregistry" | "singleton_registry" | "singleton-registry" => {
+            Capability::SingletonRegistry
+        }
+        "singletonlookup" | "singleton_lookup" | "singleton-lookup" => Capability::SingletonLookup,
+        "timeread" | "time_read" | "time-read" => Capability::TimeRead,
+        "servicedirectory" | "service_directory" | "service-directory" => {
+            Capability::ServiceDirectory
+        }
+        "watch" => Capability::Watch,
+        "metrics" => Capability::Metrics,
+        "lock" => Capability::Lock,
+        "sync" => Capability::Sync,
+        "futurehandoff" | "future_handoff" | "future-handoff" => Capability::FutureHandoff,
+        "compression" => Capability::Compression,
+        "hostexec" | "host_exec" | "host-exec" => Capability::HostExec,
+        "filesystem" => Capability::Filesystem,
+        "sql" => Capability::Sql,
+        "hostshutdown" | "host_shutdown" | "host-shutdown" => Capability::HostShutdown,
+        _ => return Err(anyhow!("unknown capability `{item}`")),
+    };
+
+    Ok(capability)
+}
+
 fn parse_params(raw: &str) -> Result<Vec<ParamKind>> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -401,6 +792,16 @@ fn parse_params(raw: &str) -> Result<Vec<ParamKind>> {
     Ok(params)
 }
 
+fn param_list(labels: &[String]) -> Result<Vec<ParamKind>> {
+    labels
+        .iter()
+        .map(|label| {
+            ParamKind::from_label(label.trim())
+                .ok_or_else(|| anyhow!("unknown param kind `{label}`"))
+        })
+        .collect()
+}
+
 fn parse_args(raw: &str) -> Result<Vec<Argument>> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -420,6 +821,10 @@ fn parse_args(raw: &str) -> Result<Vec<Argument>> {
     Ok(args)
 }
 
+fn argument_list(items: &[String]) -> Vec<Argument> {
+    items.iter().map(|item| parse_argument(item)).collect()
+}
+
 fn parse_argument(raw: &str) -> Argument {
     if let Some((label, value)) = raw.split_once(':')
         && let Some(kind) = ParamKind::from_label(label)
@@ -469,7 +874,7 @@ fn resolve_arguments(
     }
 
     let mut values = Vec::with_capacity(args.len());
-    for (index, (expected, arg)) in params.iter().zip(args.into_iter()).enumerate() {
+    for (index, (expected, arg)) in params.iter().zip(args).enumerate() {
         match arg {
             Argument::Typed { kind, value } => {
                 if *expected != kind {
@@ -632,15 +1037,25 @@ fn hex_digit(byte: u8) -> Result<u8> {
     }
 }
 
-async fn spawn_module(
+/// Start a process for `spec`. A `predecessor` process hands its singleton registrations over
+/// to the new process as it registers them.
+pub async fn spawn_module(
     runtime: &WasmtimeDriver,
     registry: &Arc<Registry>,
-    spec: ModuleSpec,
+    sandboxes: &Sandboxes,
+    spec: &ModuleSpec,
+    predecessor: Option<ResourceId>,
 ) -> Result<ResourceId> {
     let process_id = registry
         .reserve(None, ResourceType::Process)
         .map_err(KernelError::from)
         .context("reserve process id")?;
+    if let Some(predecessor) = predecessor {
+        registry
+            .set_successor(predecessor, process_id)
+            .map_err(KernelError::from)
+            .context("hand over singletons")?;
+    }
 
     let ModuleSpec {
         module_label,
@@ -649,9 +1064,13 @@ async fn spawn_module(
         capabilities,
         params,
         args,
-    } = spec;
+        limits,
+        ..
+    } = spec.clone();
 
-    info!(module = module_label, "spawning module");
+    logging::configure(&module_label, spec.log())?;
+    let span = info_span!(MODULE_SPAN, module = module_label);
+    span.in_scope(|| info!(module = module_label, "spawning module"));
 
     let entrypoint_invocation =
         EntrypointInvocation::new(AbiSignature::new(params, Vec::new()), args)
@@ -663,15 +1082,22 @@ async fn spawn_module(
         )))
     })?;
 
+    if let Err(err) = sandboxes.assign(process_id, &module_label, spec.sandbox().clone()) {
+        registry.discard(process_id);
+        return Err(err).with_context(|| format!("create sandbox for {module_label}"));
+    }
+
     if let Err(err) = runtime
-        .start(
+        .start_with_limits(
             registry,
             process_id,
             module_id,
             &entrypoint,
             capabilities,
             entrypoint_invocation,
+            limits,
         )
+        .instrument(span.clone())
         .await
     {
         registry.discard(process_id);
@@ -692,6 +1118,7 @@ async fn spawn_module(
                 );
             }
         }
+        .instrument(span)
     });
 
     Ok(process_id)
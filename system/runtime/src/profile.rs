@@ -0,0 +1,73 @@
+//! The `profile` subcommand, sampling a module's call stacks on a running host, and the folded
+//! stacks files the host writes for it.
+//!
+//! Profiles are written under `profiles` in the host's work directory, one file per request,
+//! in the folded stacks format read by flamegraph tools such as `inferno-flamegraph` and
+//! `flamegraph.pl`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use selium_kernel::{profile::FoldedStacks, registry::ResourceId};
+
+use crate::control::ControlClient;
+
+/// Directory under the work directory holding profiles.
+const PROFILE_DIR: &str = "profiles";
+const PROFILE_EXTENSION: &str = "folded";
+
+/// Profile `target` for `seconds` and print where the host wrote the profile.
+pub async fn print(mut client: ControlClient, target: &str, seconds: u32) -> Result<()> {
+    let report = client.profile(target, seconds).await?;
+    if report.samples == 0 {
+        println!(
+            "process {} did not execute while it was profiled; wrote an empty profile to {}",
+            report.process_id,
+            report.path.display()
+        );
+    } else {
+        println!(
+            "wrote {} samples of process {} to {}",
+            report.samples,
+            report.process_id,
+            report.path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Write `stacks` sampled from `process_id` to a new file under `work_dir`, returning its path.
+pub fn write(work_dir: &Path, process_id: ResourceId, stacks: &FoldedStacks) -> Result<PathBuf> {
+    let dir = work_dir.join(PROFILE_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("{timestamp}-{process_id}.{PROFILE_EXTENSION}"));
+    fs::write(&path, stacks.to_string()).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_are_written_under_the_work_dir() {
+        let work_dir = std::env::temp_dir().join(format!("selium-profile-{}", std::process::id()));
+        let mut stacks = FoldedStacks::default();
+        stacks.add(["main".to_string(), "spin".to_string()]);
+
+        let path = write(&work_dir, 7, &stacks).expect("write profile");
+        assert_eq!(path.parent(), Some(work_dir.join(PROFILE_DIR).as_path()));
+        assert!(path.to_string_lossy().ends_with("-7.folded"));
+        assert_eq!(fs::read_to_string(&path).expect("read"), "main;spin 1\n");
+
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+}
@@ -0,0 +1,245 @@
+//! Hot reload of modules whose files change on disk.
+//!
+//! When a module file changes, a replacement process is started from the same specification,
+//! so it is granted the same capabilities. The replacement takes over the singleton
+//! registrations of the process it replaces. The old process is stopped once the new one
+//! reports ready, or once the readiness timeout elapses for guests that never report.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use selium_filesystem_store::Sandboxes;
+use selium_kernel::{
+    drivers::{meta::ProcessReadiness, process::ProcessLifecycleCapability},
+    registry::{Registry, ResourceHandle, ResourceId},
+};
+use selium_wasmtime::WasmtimeDriver;
+use tokio::time::timeout;
+use tracing::{debug, info};
+
+use crate::modules::{self, SpawnedModule};
+
+pub(crate) type ProcessHandle = <WasmtimeDriver as ProcessLifecycleCapability>::Process;
+
+/// Tunables for hot module reload.
+#[derive(Clone, Copy, Debug)]
+pub struct ReloadOptions {
+    /// How often module files are checked for changes.
+    pub poll_interval: Duration,
+    /// How long a replacement may take to report ready before it is assumed ready.
+    pub ready_timeout: Duration,
+    /// Only reload a module whose file contents changed, rather than any whose modification
+    /// time changed.
+    pub compare_contents: bool,
+}
+
+/// Start a replacement for `module` and retire the process it replaces, returning the
+/// replacement's process id. The old process keeps running if the replacement fails.
+pub(crate) async fn reload(
+    runtime: &WasmtimeDriver,
+    registry: &Arc<Registry>,
+    sandboxes: &Sandboxes,
+    module: &SpawnedModule,
+    ready_timeout: Duration,
+) -> Result<ResourceId> {
+    let previous = module.process_id;
+    info!(
+        module = module.spec.label(),
+        "module changed; starting replacement"
+    );
+    let process_id =
+        modules::spawn_module(runtime, registry, sandboxes, &module.spec, Some(previous)).await?;
+
+    if let Some(readiness) = registry.process_extension::<ProcessReadiness>(process_id)
+        && timeout(ready_timeout, readiness.wait()).await.is_err()
+    {
+        debug!(
+            module = module.spec.label(),
+            ?ready_timeout,
+            "replacement did not report ready; assuming ready"
+        );
+    }
+
+    let running = registry
+        .with(ResourceHandle::<ProcessHandle>::new(process_id), |handle| {
+            !handle.is_finished()
+        })
+        .unwrap_or(false);
+    if !running {
+        stop(runtime, registry, process_id).await?;
+        return Err(anyhow!("replacement exited before taking over"));
+    }
+
+    stop(runtime, registry, previous)
+        .await
+        .context("stop replaced process")?;
+    Ok(process_id)
+}
+
+/// Stop `process_id` and release its resources, if it is still registered.
+pub(crate) async fn stop(
+    runtime: &WasmtimeDriver,
+    registry: &Registry,
+    process_id: ResourceId,
+) -> Result<()> {
+    let Some(mut process) = registry.remove(ResourceHandle::<ProcessHandle>::new(process_id))
+    else {
+        // The process already exited and was reaped.
+        return Ok(());
+    };
+
+    runtime.stop(&mut process).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+    use selium_filesystem_store::{CacheCounters, TrustRoot};
+
+    use super::*;
+    use crate::{
+        kernel::KernelOptions,
+        modules::{self, ModuleSpec},
+        runtime::{
+            Runtime, RuntimeBuilder,
+            tests::{idle_module, work_dir},
+        },
+        supervisor::ModuleState,
+    };
+
+    /// A module like `idle_module` whose entrypoint spins until it is stopped, padded with
+    /// `nops` no-ops so that variants differ in content.
+    fn busy_module(nops: u8) -> Vec<u8> {
+        let mut bytes = idle_module();
+        // Replace the code section with a body of no locals, `nops` no-ops and
+        // `loop br 0 end end`.
+        bytes.truncate(bytes.len() - 6);
+        let mut body = vec![0x00];
+        body.extend(std::iter::repeat_n(0x01, nops.into()));
+        body.extend_from_slice(&[0x03, 0x40, 0x0c, 0x00, 0x0b, 0x0b]);
+        bytes.extend_from_slice(&[0x0a, body.len() as u8 + 2, 0x01, body.len() as u8]);
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+
+    fn busy_spec() -> ModuleSpec {
+        modules::parse_cli_spec("path=busy.wasm;capabilities=time-read", Path::new(""))
+            .expect("valid spec")
+    }
+
+    /// A runtime supervising `busy.wasm`, written to a work directory named after `test` and
+    /// signed with `key` if given.
+    async fn busy_runtime(
+        test: &str,
+        options: KernelOptions,
+        key: Option<&Ed25519KeyPair>,
+    ) -> (Runtime, PathBuf) {
+        let work_dir = work_dir(test);
+        let module = busy_module(0);
+        fs::write(work_dir.join("modules/busy.wasm"), &module).expect("write module");
+        if let Some(key) = key {
+            fs::write(
+                work_dir.join("modules/busy.wasm.sig"),
+                key.sign(&module).as_ref(),
+            )
+            .expect("write signature");
+        }
+        let runtime = RuntimeBuilder::new(&work_dir)
+            .kernel_options(options)
+            .reload(ReloadOptions {
+                poll_interval: Duration::from_secs(3600),
+                ready_timeout: Duration::from_millis(100),
+                compare_contents: true,
+            })
+            .start()
+            .await
+            .expect("start runtime");
+        runtime
+            .supervisor()
+            .add(busy_spec())
+            .await
+            .expect("start module");
+        (runtime, work_dir)
+    }
+
+    async fn running_process(runtime: &Runtime) -> ResourceId {
+        let statuses = runtime.supervisor().list().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, ModuleState::Running);
+        statuses[0].process_id
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reloads_replace_the_running_process() {
+        let (runtime, work_dir) = busy_runtime("reload", KernelOptions::default(), None).await;
+        let previous = running_process(&runtime).await;
+
+        fs::write(work_dir.join("modules/busy.wasm"), busy_module(1)).expect("change module");
+        let process_id = runtime
+            .supervisor()
+            .reload("busy.wasm")
+            .await
+            .expect("reload");
+        assert_ne!(process_id, previous);
+        assert_eq!(running_process(&runtime).await, process_id);
+        assert!(runtime.registry().metadata(previous).is_none());
+        let module_cache = runtime
+            .kernel()
+            .get::<CacheCounters>()
+            .expect("module cache")
+            .snapshot();
+        assert_eq!((module_cache.hits, module_cache.misses), (0, 2));
+
+        runtime.shutdown(Duration::from_secs(1)).await;
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_replacements_leave_the_old_process_running() {
+        let (runtime, work_dir) =
+            busy_runtime("reload-rollback", KernelOptions::default(), None).await;
+        let previous = running_process(&runtime).await;
+
+        fs::write(work_dir.join("modules/busy.wasm"), b"not wasm").expect("change module");
+        assert!(runtime.supervisor().reload("busy.wasm").await.is_err());
+        assert_eq!(running_process(&runtime).await, previous);
+        assert!(runtime.registry().metadata(previous).is_some());
+
+        runtime.shutdown(Duration::from_secs(1)).await;
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unsigned_replacements_are_refused() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("generate key");
+        let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).expect("parse key");
+        let public: [u8; 32] = key.public_key().as_ref().try_into().expect("public key");
+        let options = KernelOptions {
+            trust_root: Some(TrustRoot::new([public])),
+            ..KernelOptions::default()
+        };
+        let (runtime, work_dir) = busy_runtime("reload-unsigned", options, Some(&key)).await;
+        let previous = running_process(&runtime).await;
+
+        fs::write(work_dir.join("modules/busy.wasm"), busy_module(1)).expect("change module");
+        let err = runtime
+            .supervisor()
+            .reload("busy.wasm")
+            .await
+            .expect_err("unsigned module");
+        assert!(format!("{err:#}").contains("signature"), "{err:#}");
+        assert_eq!(running_process(&runtime).await, previous);
+
+        runtime.shutdown(Duration::from_secs(1)).await;
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+}
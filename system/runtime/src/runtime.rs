@@ -0,0 +1,384 @@
+//! Embedding the runtime in another application.
+//!
+//! [`RuntimeBuilder`] assembles the kernel, starts the modules it is given and supervises them,
+//! as the `selium-runtime` binary does, and hands back a [`Runtime`] for the application to
+//! start further modules on and eventually shut down. A [`RuntimeHandle`] taken from it lets
+//! any task of the application start and stop modules and follow what the kernel does.
+//!
+//! Runtimes share nothing but the process they run in, so several may run side by side, each
+//! with its own kernel, registry, work directory and modules. The binary runs the tenants of
+//! its deployment file this way.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use selium_filesystem_store::Sandboxes;
+use selium_kernel::{
+    CapabilityProvider, HostCapabilityPlugin, Kernel,
+    drivers::{self, sql::SqlConnection},
+    events::KernelEvent,
+    registry::{Registry, ResourceId},
+};
+use tokio::sync::{Notify, broadcast::Receiver};
+use tracing::info;
+
+use crate::{
+    kernel::{self, KernelOptions},
+    lifecycle::{LifecycleCallback, LifecycleEvent, ProcessInfo},
+    modules::{self, ModuleSpec},
+    reload::ReloadOptions,
+    startup::StartupReport,
+    supervisor::Supervisor,
+};
+
+/// Builder for a [`Runtime`] running from a work directory.
+///
+/// The work directory holds the `certs` the network drivers serve with, and the `modules`
+/// guests are started from.
+pub struct RuntimeBuilder {
+    work_dir: PathBuf,
+    options: KernelOptions,
+    modules: Vec<ModuleSpec>,
+    prewarm: Vec<String>,
+    reload: Option<ReloadOptions>,
+    dependency_namespace: Option<Vec<u8>>,
+    lifecycle: Vec<LifecycleCallback>,
+    sql_connections: Vec<(String, Arc<dyn SqlConnection>)>,
+}
+
+/// Cheap, clonable handle for starting and stopping the modules of a [`Runtime`] from any task.
+#[derive(Clone)]
+pub struct RuntimeHandle {
+    registry: Arc<Registry>,
+    supervisor: Supervisor,
+}
+
+/// A running kernel and the modules it supervises.
+pub struct Runtime {
+    kernel: Kernel,
+    registry: Arc<Registry>,
+    supervisor: Supervisor,
+    startup_report: StartupReport,
+    shutdown: Arc<Notify>,
+}
+
+impl RuntimeBuilder {
+    /// Start building a runtime that keeps its state in `work_dir`.
+    pub fn new(work_dir: impl AsRef<Path>) -> Self {
+        Self {
+            work_dir: work_dir.as_ref().to_path_buf(),
+            options: KernelOptions::default(),
+            modules: Vec::new(),
+            prewarm: Vec::new(),
+            reload: None,
+            dependency_namespace: None,
+            lifecycle: Vec::new(),
+            sql_connections: Vec::new(),
+        }
+    }
+
+    /// Assemble the kernel with `options`, replacing any options, providers and plugins given so
+    /// far.
+    pub fn kernel_options(mut self, options: KernelOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Register a provider's hostcall families after the built-in ones.
+    pub fn provider(mut self, provider: Arc<dyn CapabilityProvider>) -> Self {
+        self.options.providers.push(provider);
+        self
+    }
+
+    /// Register a plugin's hostcall family, after those of the providers.
+    pub fn plugin(mut self, plugin: Arc<dyn HostCapabilityPlugin>) -> Self {
+        self.options.plugins.push(plugin);
+        self
+    }
+
+    /// Start `spec` once the kernel is built, and supervise it.
+    pub fn module(mut self, spec: ModuleSpec) -> Self {
+        self.modules.push(spec);
+        self
+    }
+
+    /// Start each of `specs` once the kernel is built, and supervise them.
+    pub fn modules(mut self, specs: impl IntoIterator<Item = ModuleSpec>) -> Self {
+        self.modules.extend(specs);
+        self
+    }
+
+    /// Keep instances of a module instantiated ahead of `process::start`, as described by
+    /// `spec` in the `--prewarm` format: `module=...;capabilities=...;count=N`.
+    pub fn prewarm(mut self, spec: impl Into<String>) -> Self {
+        self.prewarm.push(spec.into());
+        self
+    }
+
+    /// Restart supervised modules whose files change.
+    pub fn reload(mut self, options: ReloadOptions) -> Self {
+        self.reload = Some(options);
+        self
+    }
+
+    /// Salt singleton dependency identifiers with a deployment-specific `namespace`.
+    pub fn dependency_namespace(mut self, namespace: impl AsRef<[u8]>) -> Self {
+        self.dependency_namespace = Some(namespace.as_ref().to_vec());
+        self
+    }
+
+    /// Hold `connection` on guests' behalf, published as the singleton dependency `name` for
+    /// modules granted `Sql` to run statements on. Guests never see how it was opened.
+    pub fn sql_connection(
+        mut self,
+        name: impl Into<String>,
+        connection: Arc<dyn SqlConnection>,
+    ) -> Self {
+        self.sql_connections.push((name.into(), connection));
+        self
+    }
+
+    /// Call `callback` whenever a supervised process starts, exits, traps, restarts or is
+    /// stopped, with the metadata of the process. Callbacks are awaited one event at a time, in
+    /// the order the events happened and the callbacks were registered.
+    pub fn on_lifecycle<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(LifecycleEvent, ProcessInfo) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle.push(Arc::new(move |event, process| {
+            Box::pin(callback(event, process))
+        }));
+        self
+    }
+
+    /// Build the kernel, start every module and begin supervising them.
+    pub async fn start(self) -> Result<Runtime> {
+        let (kernel, shutdown) =
+            kernel::build(&self.work_dir, &self.options).context("build runtime kernel")?;
+        let registry = Registry::new();
+        kernel
+            .require::<Sandboxes>()?
+            .release_removed(&registry)
+            .context("release sandboxes of removed processes")?;
+        if let Some(namespace) = &self.dependency_namespace {
+            registry
+                .set_dependency_namespace(namespace)
+                .context("set dependency namespace")?;
+        }
+        for (name, connection) in self.sql_connections {
+            drivers::sql::register_connection(&registry, &name, connection)
+                .with_context(|| format!("register SQL connection `{name}`"))?;
+        }
+        info!("kernel initialised; starting modules");
+
+        modules::prewarm_from_cli(&kernel, &registry, &self.prewarm).await?;
+
+        let supervisor = Supervisor::new(&kernel, &registry, self.reload)?
+            .with_lifecycle_callbacks(self.lifecycle);
+        let spawned = modules::spawn_all(&kernel, &registry, self.modules).await?;
+        let startup_report = StartupReport::collect(&registry, &self.options, &spawned);
+        startup_report.log();
+        supervisor.spawn(spawned);
+
+        Ok(Runtime {
+            kernel,
+            registry,
+            supervisor,
+            startup_report,
+            shutdown,
+        })
+    }
+}
+
+impl Runtime {
+    /// The kernel guests are linked against, for looking up its drivers.
+    pub fn kernel(&self) -> &Kernel {
+        &self.kernel
+    }
+
+    /// The registry holding every guest resource.
+    pub fn registry(&self) -> &Arc<Registry> {
+        &self.registry
+    }
+
+    /// Handle to the supervised modules, to list, stop or reload them.
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    /// What was loaded when the runtime started.
+    pub fn startup_report(&self) -> &StartupReport {
+        &self.startup_report
+    }
+
+    /// Start and supervise another module, returning its process id.
+    pub async fn spawn(&self, spec: ModuleSpec) -> Result<ResourceId> {
+        self.supervisor.add(spec).await
+    }
+
+    /// A handle for starting and stopping modules from other tasks.
+    pub fn handle(&self) -> RuntimeHandle {
+        RuntimeHandle {
+            registry: Arc::clone(&self.registry),
+            supervisor: self.supervisor.clone(),
+        }
+    }
+
+    /// Stop every module, giving each up to `timeout` to exit, then stop the kernel's drivers.
+    pub async fn shutdown(self, timeout: Duration) {
+        self.supervisor.shutdown(timeout).await;
+        self.shutdown.notify_waiters();
+    }
+}
+
+impl RuntimeHandle {
+    /// Start and supervise a module, as the `start --spec` command does, returning its process
+    /// id.
+    pub async fn spawn_module(&self, spec: ModuleSpec) -> Result<ResourceId> {
+        self.supervisor.add(spec).await
+    }
+
+    /// Stop the process `process_id`, as the `stop` command does. A supervised module stays
+    /// stopped until started again.
+    pub async fn stop_process(&self, process_id: ResourceId) -> Result<()> {
+        self.supervisor.stop_process(process_id).await
+    }
+
+    /// Receive every kernel event published from now on: processes starting and stopping,
+    /// sessions being created and hostcalls being denied.
+    pub fn subscribe_events(&self) -> Receiver<KernelEvent> {
+        self.registry.events().subscribe()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs;
+
+    use selium_abi::Capability;
+    use tokio::time::timeout;
+
+    use super::*;
+    use crate::certs;
+
+    /// A module exporting `memory` and a `start` entrypoint, taking the log URI buffer, that
+    /// returns at once.
+    pub(crate) fn idle_module() -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        // One function of type (i32, i32) -> (), and two pages of memory.
+        bytes.extend_from_slice(&[0x01, 0x06, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        bytes.extend_from_slice(&[0x05, 0x03, 0x01, 0x00, 0x02]);
+        bytes.extend_from_slice(&[0x07, 0x12, 0x02, 0x06]);
+        bytes.extend_from_slice(b"memory");
+        bytes.extend_from_slice(&[0x02, 0x00, 0x05]);
+        bytes.extend_from_slice(b"start");
+        bytes.extend_from_slice(&[0x00, 0x00]);
+        bytes.extend_from_slice(&[0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b]);
+        bytes
+    }
+
+    /// A scratch work directory named after `test`, with certificates and `idle.wasm`.
+    pub(crate) fn work_dir(test: &str) -> PathBuf {
+        let work_dir =
+            std::env::temp_dir().join(format!("selium-runtime-{test}-{}", std::process::id()));
+        certs::generate_certificates(
+            &work_dir.join(kernel::CERTS_SUBDIR),
+            "Selium Test CA",
+            "localhost",
+            "client.localhost",
+        )
+        .expect("generate certificates");
+        fs::create_dir_all(work_dir.join("modules")).expect("create modules dir");
+        fs::write(work_dir.join("modules/idle.wasm"), idle_module()).expect("write module");
+        work_dir
+    }
+
+    /// A specification of `idle.wasm`. Module paths are resolved against the module store in
+    /// the work directory.
+    fn idle_spec() -> ModuleSpec {
+        modules::parse_cli_spec("path=idle.wasm;capabilities=time-read", Path::new(""))
+            .expect("valid spec")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn handles_spawn_and_stop_modules_from_other_tasks() {
+        let work_dir = work_dir("handle");
+        let runtime = RuntimeBuilder::new(&work_dir)
+            .start()
+            .await
+            .expect("start runtime");
+
+        let handle = runtime.handle();
+        let mut events = handle.subscribe_events();
+        let spec = idle_spec();
+        let process_id = tokio::spawn(async move {
+            let process_id = handle.spawn_module(spec).await.expect("spawn module");
+            assert!(handle.stop_process(process_id + 1000).await.is_err());
+            handle.stop_process(process_id).await.expect("stop module");
+            process_id
+        })
+        .await
+        .expect("handle task");
+
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event in time")
+            .expect("event");
+        assert!(matches!(
+            event,
+            KernelEvent::SessionCreated { process_id: Some(id), .. } if id == process_id
+        ));
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event in time")
+            .expect("event");
+        assert_eq!(event, KernelEvent::ProcessStarted { process_id });
+        let statuses = runtime.supervisor().list().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, crate::supervisor::ModuleState::Stopped);
+
+        runtime.shutdown(Duration::from_secs(1)).await;
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lifecycle_callbacks_follow_a_process() {
+        let work_dir = work_dir("lifecycle");
+        let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let runtime = RuntimeBuilder::new(&work_dir)
+            .module(idle_spec())
+            .on_lifecycle(move |event, process| {
+                let sender = sender.clone();
+                async move {
+                    sender.send((event, process)).expect("test receiver");
+                }
+            })
+            .start()
+            .await
+            .expect("start runtime");
+
+        let mut next = async || {
+            timeout(Duration::from_secs(5), received.recv())
+                .await
+                .expect("event in time")
+                .expect("event")
+        };
+        let (event, started) = next().await;
+        assert_eq!(event, LifecycleEvent::Started);
+        assert_eq!(started.label, "idle.wasm");
+        assert_eq!(started.capabilities, Capability::TimeRead.into());
+        let (event, exited) = next().await;
+        assert_eq!(event, LifecycleEvent::Exited);
+        assert_eq!(exited.process_id, started.process_id);
+
+        runtime.shutdown(Duration::from_secs(1)).await;
+        fs::remove_dir_all(&work_dir).expect("remove work dir");
+    }
+}
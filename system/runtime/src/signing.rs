@@ -0,0 +1,81 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use selium_filesystem_store::{PUBLIC_KEY_LEN, TrustRoot, signature_path};
+
+/// Extension of the raw public key written alongside a generated signing key.
+const PUBLIC_KEY_EXTENSION: &str = "pub";
+
+/// Sign `module` with the PKCS#8 ed25519 key at `key_path`, writing a detached signature next to
+/// it. With `generate`, a new key pair is created at `key_path` first.
+pub fn sign_module(key_path: &Path, module: &Path, generate: bool) -> Result<()> {
+    if generate {
+        generate_key(key_path)?;
+    }
+
+    let pkcs8 =
+        fs::read(key_path).with_context(|| format!("read signing key {}", key_path.display()))?;
+    let key = Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|err| anyhow!("parse signing key {}: {err}", key_path.display()))?;
+    let bytes = fs::read(module).with_context(|| format!("read module {}", module.display()))?;
+
+    let sig_path = signature_path(module);
+    fs::write(&sig_path, key.sign(&bytes))
+        .with_context(|| format!("write signature {}", sig_path.display()))?;
+    println!("Wrote signature to {}", sig_path.display());
+
+    Ok(())
+}
+
+/// Load the raw ed25519 public keys at `paths` into a trust root.
+pub fn load_trust_root(paths: &[PathBuf]) -> Result<TrustRoot> {
+    let keys = paths
+        .iter()
+        .map(|path| {
+            let bytes =
+                fs::read(path).with_context(|| format!("read trusted key {}", path.display()))?;
+            <[u8; PUBLIC_KEY_LEN]>::try_from(bytes.as_slice()).map_err(|_| {
+                anyhow!(
+                    "trusted key {} must be a raw {PUBLIC_KEY_LEN} byte ed25519 public key",
+                    path.display()
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(TrustRoot::new(keys))
+}
+
+fn generate_key(key_path: &Path) -> Result<()> {
+    if key_path.exists() {
+        return Err(anyhow!(
+            "refusing to overwrite existing signing key {}",
+            key_path.display()
+        ));
+    }
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| anyhow!("generate signing key"))?;
+    let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+        .map_err(|err| anyhow!("parse generated signing key: {err}"))?;
+    let public_path = key_path.with_extension(PUBLIC_KEY_EXTENSION);
+
+    fs::write(key_path, pkcs8.as_ref())
+        .with_context(|| format!("write signing key {}", key_path.display()))?;
+    fs::write(&public_path, key.public_key().as_ref())
+        .with_context(|| format!("write public key {}", public_path.display()))?;
+    println!(
+        "Wrote signing key to {} and public key to {}",
+        key_path.display(),
+        public_path.display()
+    );
+
+    Ok(())
+}
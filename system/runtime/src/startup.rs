@@ -0,0 +1,211 @@
+//! Report of what the runtime loaded at startup.
+//!
+//! Once the modules given at startup are running, the runtime logs the version of every
+//! capability provider and, for each module, the capabilities it was granted, the hostcalls it
+//! links to a driver and those it imports without the capability they require. The report can
+//! also be written as JSON, so that a misconfiguration shows up as soon as the host starts
+//! rather than at a guest's first failing hostcall.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use selium_kernel::registry::{Registry, ResourceId};
+use selium_wasmtime::ModuleImports;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{kernel::KernelOptions, modules::SpawnedModule, validate};
+
+/// Name reported for the drivers built into the runtime.
+const BUILTIN_PROVIDER: &str = "builtin";
+
+/// What the runtime loaded at startup.
+#[derive(Debug, Serialize)]
+pub struct StartupReport {
+    runtime_version: &'static str,
+    providers: Vec<ProviderReport>,
+    modules: Vec<ModuleReport>,
+}
+
+/// A source of hostcall drivers.
+#[derive(Debug, Serialize)]
+struct ProviderReport {
+    name: String,
+    version: Option<String>,
+}
+
+/// How a module started at startup was linked.
+#[derive(Debug, Serialize)]
+struct ModuleReport {
+    label: String,
+    process_id: ResourceId,
+    capabilities: Vec<String>,
+    /// Hostcalls linked to a driver; `None` if the module's imports could not be read because
+    /// its process had already exited or it is a component.
+    linked: Option<Vec<&'static str>>,
+    /// Hostcalls imported without the capability they require, which deny every call.
+    denied: Vec<DeniedHostcall>,
+    /// Granted capabilities none of the imported hostcalls require.
+    unused_capabilities: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DeniedHostcall {
+    hostcall: &'static str,
+    capability: String,
+}
+
+impl StartupReport {
+    /// Describe `modules`, just started against `registry`, and the drivers built into the
+    /// runtime along with those registered by the providers and plugins of `options`.
+    pub fn collect(
+        registry: &Registry,
+        options: &KernelOptions,
+        modules: &[SpawnedModule],
+    ) -> Self {
+        let runtime_version = env!("CARGO_PKG_VERSION");
+        let providers = std::iter::once(ProviderReport {
+            name: BUILTIN_PROVIDER.to_string(),
+            version: Some(runtime_version.to_string()),
+        })
+        .chain(options.providers.iter().map(|provider| ProviderReport {
+            name: provider.name().to_string(),
+            version: provider.version().map(str::to_string),
+        }))
+        .chain(options.plugins.iter().map(|plugin| ProviderReport {
+            name: plugin.name().to_string(),
+            version: plugin.version().map(str::to_string),
+        }))
+        .collect();
+        let modules = modules
+            .iter()
+            .map(|module| {
+                let imports = registry.process_extension::<ModuleImports>(module.process_id);
+                ModuleReport::new(module, imports.as_deref())
+            })
+            .collect();
+
+        Self {
+            runtime_version,
+            providers,
+            modules,
+        }
+    }
+
+    /// Log the report, warning about denied hostcalls and unused capabilities.
+    pub fn log(&self) {
+        for provider in &self.providers {
+            info!(
+                provider = provider.name,
+                version = provider.version.as_deref().unwrap_or("unknown"),
+                "capability provider loaded"
+            );
+        }
+        for module in &self.modules {
+            info!(
+                module = module.label,
+                process_id = module.process_id,
+                capabilities = ?module.capabilities,
+                linked = ?module.linked,
+                "module linked"
+            );
+            for denied in &module.denied {
+                warn!(
+                    module = module.label,
+                    hostcall = denied.hostcall,
+                    capability = denied.capability,
+                    "module imports a hostcall it is not granted; calls will be denied"
+                );
+            }
+            if !module.unused_capabilities.is_empty() {
+                warn!(
+                    module = module.label,
+                    capabilities = ?module.unused_capabilities,
+                    "module is granted capabilities no imported hostcall needs"
+                );
+            }
+        }
+    }
+
+    /// Write the report to `path` as JSON.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self).context("encode startup report")?;
+        fs::write(path, json).with_context(|| format!("write startup report {}", path.display()))
+    }
+}
+
+impl ModuleReport {
+    fn new(module: &SpawnedModule, imports: Option<&ModuleImports>) -> Self {
+        let granted = module.spec.capabilities();
+        let capabilities = granted
+            .iter()
+            .map(|capability| capability.to_string())
+            .collect();
+        let Some(imports) = imports else {
+            return Self {
+                label: module.spec.label().to_string(),
+                process_id: module.process_id,
+                capabilities,
+                linked: None,
+                denied: Vec::new(),
+                unused_capabilities: Vec::new(),
+            };
+        };
+
+        let resolved = validate::check(imports.iter(), granted);
+        Self {
+            label: module.spec.label().to_string(),
+            process_id: module.process_id,
+            capabilities,
+            linked: Some(resolved.linked),
+            denied: resolved
+                .ungranted
+                .into_iter()
+                .map(|(hostcall, capability)| DeniedHostcall {
+                    hostcall,
+                    capability: capability.to_string(),
+                })
+                .collect(),
+            unused_capabilities: resolved
+                .unused
+                .iter()
+                .map(|capability| capability.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules;
+
+    use super::*;
+
+    #[test]
+    fn modules_report_their_linking() {
+        let spec = modules::parse_cli_spec("path=echo.wasm;capabilities=TimeRead", Path::new("."))
+            .expect("parse spec");
+        let module = SpawnedModule {
+            spec,
+            process_id: 3,
+        };
+
+        let unread = ModuleReport::new(&module, None);
+        assert_eq!(unread.capabilities, ["TimeRead"]);
+        assert!(unread.linked.is_none());
+
+        let linked = ModuleReport::new(&module, Some(&ModuleImports::default()));
+        assert_eq!(linked.linked, Some(Vec::new()));
+        assert_eq!(linked.unused_capabilities, ["TimeRead"]);
+
+        let report = StartupReport::collect(
+            &Registry::new(),
+            &KernelOptions::default(),
+            std::slice::from_ref(&module),
+        );
+        let json = serde_json::to_value(&report).expect("encode report");
+        assert_eq!(json["providers"][0]["name"], BUILTIN_PROVIDER);
+        assert_eq!(json["modules"][0]["process_id"], 3);
+        assert!(json["modules"][0]["linked"].is_null());
+    }
+}
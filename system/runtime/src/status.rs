@@ -0,0 +1,180 @@
+//! The `ps` subcommand, reporting the modules supervised by a running host.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    control::ControlClient,
+    supervisor::{ModuleState, ModuleStatus},
+};
+
+/// Column headings of the table output.
+const HEADINGS: [&str; 7] = [
+    "PID",
+    "MODULE",
+    "STATE",
+    "UPTIME",
+    "RESTARTS",
+    "FUEL",
+    "CAPABILITIES",
+];
+
+/// Output format of the `ps` subcommand.
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// Aligned columns for reading in a terminal.
+    Table,
+    /// One JSON array of module objects, for scripts.
+    Json,
+}
+
+/// A module as reported in JSON.
+#[derive(Serialize)]
+struct ProcessRow<'a> {
+    module: &'a str,
+    process_id: usize,
+    state: &'static str,
+    capabilities: Vec<String>,
+    uptime_ms: u128,
+    restarts: u32,
+    fuel_consumed: u64,
+}
+
+/// Print the modules supervised by the host `client` is connected to.
+pub async fn print(mut client: ControlClient, format: StatusFormat) -> Result<()> {
+    let modules = client.list().await?;
+    let output = match format {
+        StatusFormat::Table => render_table(&modules),
+        StatusFormat::Json => render_json(&modules)?,
+    };
+    println!("{output}");
+    Ok(())
+}
+
+fn render_json(modules: &[ModuleStatus]) -> Result<String> {
+    let rows: Vec<_> = modules
+        .iter()
+        .map(|status| ProcessRow {
+            module: &status.label,
+            process_id: status.process_id,
+            state: state_label(status.state),
+            capabilities: status
+                .capabilities
+                .iter()
+                .map(|capability| capability.to_string())
+                .collect(),
+            uptime_ms: status.uptime.as_millis(),
+            restarts: status.restarts,
+            fuel_consumed: status.fuel_consumed,
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).context("encode module status")
+}
+
+fn render_table(modules: &[ModuleStatus]) -> String {
+    let rows: Vec<[String; 7]> = modules
+        .iter()
+        .map(|status| {
+            [
+                status.process_id.to_string(),
+                status.label.clone(),
+                state_label(status.state).to_string(),
+                format_uptime(status.uptime),
+                status.restarts.to_string(),
+                status.fuel_consumed.to_string(),
+                status
+                    .capabilities
+                    .iter()
+                    .map(|capability| capability.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADINGS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let headings = HEADINGS.map(str::to_string);
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for row in std::iter::once(&headings).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+fn state_label(state: ModuleState) -> &'static str {
+    match state {
+        ModuleState::Running => "running",
+        ModuleState::RestartPending => "restart-pending",
+        ModuleState::Exited => "exited",
+        ModuleState::Stopped => "stopped",
+    }
+}
+
+/// Uptime in its two most significant units, e.g. `3m04s`; `-` when not running.
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    match secs {
+        0 if uptime.is_zero() => "-".to_string(),
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..86_400 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{:02}h", secs / 86_400, secs % 86_400 / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{Capability, CapabilitySet};
+
+    use super::*;
+
+    #[test]
+    fn table_columns_align() {
+        let modules = [
+            ModuleStatus {
+                label: "modules/echo.wasm".to_string(),
+                process_id: 3,
+                state: ModuleState::Running,
+                capabilities: [Capability::ChannelReader, Capability::TimeRead].into(),
+                uptime: Duration::from_secs(184),
+                restarts: 0,
+                fuel_consumed: 1200,
+            },
+            ModuleStatus {
+                label: "idle.wasm".to_string(),
+                process_id: 12,
+                state: ModuleState::Exited,
+                capabilities: CapabilitySet::EMPTY,
+                uptime: Duration::ZERO,
+                restarts: 4,
+                fuel_consumed: 0,
+            },
+        ];
+
+        let table = render_table(&modules);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "PID  MODULE             STATE    UPTIME  RESTARTS  FUEL  CAPABILITIES",
+                "3    modules/echo.wasm  running  3m04s   0         1200  ChannelReader,TimeRead",
+                "12   idle.wasm          exited   -       4         0",
+            ]
+        );
+    }
+}
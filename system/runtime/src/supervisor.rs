@@ -0,0 +1,763 @@
+//! Supervision of the modules the runtime starts.
+//!
+//! The supervisor reaps module processes as they exit, logs how they exited and restarts them
+//! according to their [`RestartPolicy`](crate::modules::RestartPolicy). Consecutive restarts
+//! back off exponentially, so a module that fails as soon as it starts does not spin. With hot
+//! reload or watch mode enabled, the supervisor also replaces modules whose files change. When the
+//! kernel has a [`Watchdog`], the supervisor scans for stuck guest futures and reports each one
+//! against the module holding it. Lifecycle callbacks registered with the supervisor are fired
+//! as its processes start, exit, trap, restart and stop.
+//!
+//! The [`Supervisor`] handle is shared with the control socket, which starts, stops and reloads
+//! modules while the runtime is running.
+
+use std::{
+    cmp::Reverse,
+    fs,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Result, anyhow, bail};
+use selium_abi::{CapabilitySet, InstanceDiagnostics, TrapReason};
+use selium_filesystem_store::Sandboxes;
+use selium_kernel::{
+    Kernel,
+    drivers::process::ProcessUsage,
+    profile::{FoldedStacks, GuestProfiler},
+    registry::{Registry, ResourceHandle, ResourceId},
+    watchdog::{StuckFuture, Watchdog},
+};
+use selium_wasmtime::WasmtimeDriver;
+use tokio::{sync::Mutex, time::sleep};
+use tracing::{info, warn};
+
+use crate::{
+    lifecycle::{Lifecycle, LifecycleCallback, LifecycleEvent, ProcessInfo},
+    modules::{self, ModuleSpec, SpawnedModule},
+    reload::{self, ProcessHandle, ReloadOptions},
+};
+
+/// How often module processes are checked for exit.
+const SUPERVISE_INTERVAL: Duration = Duration::from_millis(250);
+/// Delay before the first restart of a module.
+const RESTART_BACKOFF_MIN: Duration = Duration::from_millis(100);
+/// Longest delay between restarts. A module that stays up this long resets its backoff.
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// How often a module is checked for exit while the runtime shuts down.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// How long a module reloaded on request may take to report ready when hot reload is disabled.
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shared handle to the table of supervised modules.
+#[derive(Clone)]
+pub struct Supervisor {
+    runtime: WasmtimeDriver,
+    registry: Arc<Registry>,
+    sandboxes: Sandboxes,
+    reload: Option<ReloadOptions>,
+    watchdog: Option<Watchdog>,
+    lifecycle: Lifecycle,
+    modules: Arc<Mutex<Vec<Supervised>>>,
+}
+
+/// Point-in-time view of a supervised module.
+#[derive(Clone, Debug)]
+pub struct ModuleStatus {
+    /// Module path as given in its specification.
+    pub label: String,
+    /// Process most recently running the module.
+    pub process_id: ResourceId,
+    /// Whether the module is running, waiting to restart or stopped.
+    pub state: ModuleState,
+    /// Capabilities granted to the module.
+    pub capabilities: CapabilitySet,
+    /// How long the current process has been running; zero unless the module is running.
+    pub uptime: Duration,
+    /// Restarts since the module last stayed up for the maximum restart backoff.
+    pub restarts: u32,
+    /// Fuel consumed by the current process; zero where the runtime does not report it.
+    pub fuel_consumed: u64,
+}
+
+/// Supervision state of a module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModuleState {
+    /// The module's process is running.
+    Running,
+    /// The module exited and will be restarted once its backoff elapses.
+    RestartPending,
+    /// The module exited and will not be restarted.
+    Exited,
+    /// The module was stopped by an operator and stays stopped until started again.
+    Stopped,
+}
+
+struct Supervised {
+    module: SpawnedModule,
+    modified: Option<SystemTime>,
+    digest: Option<blake3::Hash>,
+    /// Restarts since the module last stayed up for [`RESTART_BACKOFF_MAX`].
+    restarts: u32,
+    started: Instant,
+    state: State,
+    lifecycle: Lifecycle,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Running,
+    RestartAt(Instant),
+    Exited,
+    Stopped,
+}
+
+impl Supervised {
+    fn new(module: SpawnedModule, lifecycle: Lifecycle) -> Self {
+        Self {
+            modified: modified_at(module.spec.path()),
+            digest: digest_of(module.spec.path()),
+            module,
+            restarts: 0,
+            started: Instant::now(),
+            state: State::Running,
+            lifecycle,
+        }
+    }
+
+    /// Metadata of the module's current process, for lifecycle callbacks.
+    fn process_info(&self, registry: &Registry) -> ProcessInfo {
+        let process_id = self.module.process_id;
+        ProcessInfo {
+            label: self.module.spec.label().to_string(),
+            process_id,
+            capabilities: self.module.spec.capabilities(),
+            uptime: self.started.elapsed(),
+            fuel_consumed: registry
+                .process_extension::<ProcessUsage>(process_id)
+                .map_or(0, |usage| usage.fuel_consumed()),
+        }
+    }
+
+    /// Fire `event` for the module's current process.
+    fn fire(&self, event: LifecycleEvent, registry: &Registry) {
+        self.lifecycle.fire(event, self.process_info(registry));
+    }
+
+    fn status(&self, registry: &Registry) -> ModuleStatus {
+        let process_id = self.module.process_id;
+        let (state, uptime) = match self.state {
+            State::Running => (ModuleState::Running, self.started.elapsed()),
+            State::RestartAt(_) => (ModuleState::RestartPending, Duration::ZERO),
+            State::Exited => (ModuleState::Exited, Duration::ZERO),
+            State::Stopped => (ModuleState::Stopped, Duration::ZERO),
+        };
+        ModuleStatus {
+            label: self.module.spec.label().to_string(),
+            process_id,
+            state,
+            capabilities: self.module.spec.capabilities(),
+            uptime,
+            restarts: self.restarts,
+            fuel_consumed: registry
+                .process_extension::<ProcessUsage>(process_id)
+                .map_or(0, |usage| usage.fuel_consumed()),
+        }
+    }
+
+    /// Replace the module's process if its file changed. A module that is not running is
+    /// started afresh.
+    async fn check_reload(
+        &mut self,
+        runtime: &WasmtimeDriver,
+        registry: &Arc<Registry>,
+        sandboxes: &Sandboxes,
+        options: ReloadOptions,
+    ) {
+        if matches!(self.state, State::Stopped) {
+            return;
+        }
+        let modified = modified_at(self.module.spec.path());
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        if options.compare_contents {
+            let digest = digest_of(self.module.spec.path());
+            if digest.is_none() || digest == self.digest {
+                return;
+            }
+            self.digest = digest;
+        }
+
+        if !matches!(self.state, State::Running) {
+            info!(
+                module = self.module.spec.label(),
+                "module changed; starting it again"
+            );
+            self.restarts = 0;
+            self.restart(runtime, registry, sandboxes).await;
+            return;
+        }
+
+        let label = self.module.spec.label().to_string();
+        match reload::reload(
+            runtime,
+            registry,
+            sandboxes,
+            &self.module,
+            options.ready_timeout,
+        )
+        .await
+        {
+            Ok(process_id) => {
+                info!(module = %label, process_id, "module reloaded");
+                let previous = self.module.process_id;
+                self.module.process_id = process_id;
+                self.started = Instant::now();
+                self.state = State::Running;
+                self.fire(LifecycleEvent::Restarted { previous }, registry);
+            }
+            Err(err) => {
+                warn!(module = %label, err = format!("{err:#}"), "module reload failed");
+            }
+        }
+    }
+
+    /// Reap the module's process if it exited, and restart it when its policy and backoff
+    /// allow.
+    async fn check_exit(
+        &mut self,
+        runtime: &WasmtimeDriver,
+        registry: &Arc<Registry>,
+        sandboxes: &Sandboxes,
+    ) {
+        match self.state {
+            State::Running => self.reap(registry).await,
+            State::RestartAt(at) if Instant::now() >= at => {
+                self.restart(runtime, registry, sandboxes).await
+            }
+            State::RestartAt(_) | State::Exited | State::Stopped => {}
+        }
+    }
+
+    async fn reap(&mut self, registry: &Arc<Registry>) {
+        let label = self.module.spec.label();
+        let process_id = self.module.process_id;
+        let handle = ResourceHandle::<ProcessHandle>::new(process_id);
+        match registry.with(handle, |process| process.is_finished()) {
+            Some(false) => return,
+            Some(true) => {}
+            None => {
+                // Stopped by someone other than the supervisor.
+                self.state = State::Exited;
+                return;
+            }
+        }
+        let info = self.process_info(registry);
+        let Some(process) = registry.remove(ResourceHandle::<ProcessHandle>::new(process_id))
+        else {
+            self.state = State::Exited;
+            return;
+        };
+
+        let (succeeded, event) = match process.await {
+            Ok(Ok(_)) => {
+                info!(module = label, process_id, "module exited");
+                (true, LifecycleEvent::Exited)
+            }
+            Ok(Err(err)) => {
+                let error = format!("{err:#}");
+                let reason = err.downcast_ref::<TrapReason>().copied();
+                warn!(
+                    module = label,
+                    process_id,
+                    reason = reason.map(tracing::field::display),
+                    err = error,
+                    "module failed"
+                );
+                (false, LifecycleEvent::Trapped { error, reason })
+            }
+            Err(err) => {
+                warn!(module = label, process_id, err = %err, "module task did not complete");
+                (
+                    false,
+                    LifecycleEvent::Trapped {
+                        error: err.to_string(),
+                        reason: None,
+                    },
+                )
+            }
+        };
+        self.lifecycle.fire(event, info);
+
+        if !self.module.spec.restart().should_restart(succeeded) {
+            self.state = State::Exited;
+            return;
+        }
+        if self.started.elapsed() >= RESTART_BACKOFF_MAX {
+            self.restarts = 0;
+        }
+        self.schedule_restart();
+    }
+
+    async fn restart(
+        &mut self,
+        runtime: &WasmtimeDriver,
+        registry: &Arc<Registry>,
+        sandboxes: &Sandboxes,
+    ) {
+        let label = self.module.spec.label().to_string();
+        let previous = self.module.process_id;
+        match self.spawn(runtime, registry, sandboxes).await {
+            Ok(process_id) => {
+                info!(module = %label, process_id, restarts = self.restarts, "module restarted");
+                self.fire(LifecycleEvent::Restarted { previous }, registry);
+            }
+            Err(err) => {
+                warn!(module = %label, err = format!("{err:#}"), "module restart failed");
+                self.schedule_restart();
+            }
+        }
+    }
+
+    /// Wait up to `deadline` for the module's process to exit, then stop it.
+    async fn stop_within(
+        &mut self,
+        runtime: &WasmtimeDriver,
+        registry: &Registry,
+        deadline: Duration,
+    ) {
+        let label = self.module.spec.label();
+        let process_id = self.module.process_id;
+        let give_up = Instant::now() + deadline;
+        let info = self.process_info(registry);
+        let finished = loop {
+            let handle = ResourceHandle::<ProcessHandle>::new(process_id);
+            match registry.with(handle, |process| process.is_finished()) {
+                Some(false) if Instant::now() < give_up => sleep(SHUTDOWN_POLL_INTERVAL).await,
+                Some(finished) => break finished,
+                None => return,
+            }
+        };
+
+        if finished {
+            info!(module = label, process_id, "module exited for shutdown");
+        } else {
+            warn!(
+                module = label,
+                process_id,
+                ?deadline,
+                "module did not exit in time; stopping it"
+            );
+        }
+        if let Err(err) = reload::stop(runtime, registry, process_id).await {
+            warn!(
+                module = label,
+                process_id,
+                err = format!("{err:#}"),
+                "failed to stop module"
+            );
+        }
+        self.lifecycle.fire(LifecycleEvent::Stopped, info);
+    }
+
+    /// Start a new process for the module, without handing over from the previous one.
+    async fn spawn(
+        &mut self,
+        runtime: &WasmtimeDriver,
+        registry: &Arc<Registry>,
+        sandboxes: &Sandboxes,
+    ) -> Result<ResourceId> {
+        let process_id =
+            modules::spawn_module(runtime, registry, sandboxes, &self.module.spec, None).await?;
+        self.module.process_id = process_id;
+        self.started = Instant::now();
+        self.state = State::Running;
+        Ok(process_id)
+    }
+
+    fn schedule_restart(&mut self) {
+        let delay = RESTART_BACKOFF_MIN
+            .saturating_mul(2u32.saturating_pow(self.restarts))
+            .min(RESTART_BACKOFF_MAX);
+        self.restarts = self.restarts.saturating_add(1);
+        info!(
+            module = self.module.spec.label(),
+            ?delay,
+            "scheduling module restart"
+        );
+        self.state = State::RestartAt(Instant::now() + delay);
+    }
+}
+
+impl Supervisor {
+    /// Create a supervisor for modules run by `kernel`, replacing modules whose files change
+    /// when `reload` is given.
+    pub fn new(
+        kernel: &Kernel,
+        registry: &Arc<Registry>,
+        reload: Option<ReloadOptions>,
+    ) -> Result<Self> {
+        let runtime = kernel.require::<WasmtimeDriver>()?.clone();
+        let sandboxes = kernel.require::<Sandboxes>()?.clone();
+        Ok(Self {
+            runtime,
+            registry: Arc::clone(registry),
+            sandboxes,
+            reload,
+            watchdog: kernel.get::<Watchdog>().cloned(),
+            lifecycle: Lifecycle::default(),
+            modules: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// Fire `callbacks` for every lifecycle event of the supervised processes, replacing any
+    /// given earlier. Register them before supervising any module.
+    pub fn with_lifecycle_callbacks(mut self, callbacks: Vec<LifecycleCallback>) -> Self {
+        self.lifecycle = Lifecycle::new(callbacks);
+        self
+    }
+
+    /// Supervise modules until the runtime shuts down.
+    pub fn spawn(&self, modules: Vec<SpawnedModule>) {
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            {
+                let mut supervised = supervisor.modules.lock().await;
+                for module in modules {
+                    let entry = Supervised::new(module, supervisor.lifecycle.clone());
+                    entry.fire(LifecycleEvent::Started, &supervisor.registry);
+                    supervised.push(entry);
+                }
+            }
+            supervisor.run().await;
+        });
+    }
+
+    /// Status of every supervised module, in the order they were started.
+    pub async fn list(&self) -> Vec<ModuleStatus> {
+        self.modules
+            .lock()
+            .await
+            .iter()
+            .map(|entry| entry.status(&self.registry))
+            .collect()
+    }
+
+    /// Start and supervise a new module, returning its process id.
+    pub async fn add(&self, spec: ModuleSpec) -> Result<ResourceId> {
+        let process_id =
+            modules::spawn_module(&self.runtime, &self.registry, &self.sandboxes, &spec, None)
+                .await?;
+        let entry = Supervised::new(SpawnedModule { spec, process_id }, self.lifecycle.clone());
+        entry.fire(LifecycleEvent::Started, &self.registry);
+        self.modules.lock().await.push(entry);
+        Ok(process_id)
+    }
+
+    /// Start the stopped or exited module identified by `target`, returning its process id.
+    pub async fn start(&self, target: &str) -> Result<ResourceId> {
+        let mut modules = self.modules.lock().await;
+        let index = find(&modules, target)?;
+        let entry = &mut modules[index];
+        if matches!(entry.state, State::Running) {
+            bail!("module `{}` is already running", entry.module.spec.label());
+        }
+        entry.restarts = 0;
+        let process_id = entry
+            .spawn(&self.runtime, &self.registry, &self.sandboxes)
+            .await?;
+        info!(
+            module = entry.module.spec.label(),
+            process_id, "module started"
+        );
+        entry.fire(LifecycleEvent::Started, &self.registry);
+        Ok(process_id)
+    }
+
+    /// Stop the module identified by `target`. It is not restarted until started again.
+    pub async fn stop(&self, target: &str) -> Result<ResourceId> {
+        let mut modules = self.modules.lock().await;
+        let index = find(&modules, target)?;
+        self.stop_entry(&mut modules[index]).await
+    }
+
+    /// Stop the process `process_id`. A supervised module running in it is not restarted until
+    /// started again; any other process, such as one a guest started, is simply stopped.
+    pub async fn stop_process(&self, process_id: ResourceId) -> Result<()> {
+        let mut modules = self.modules.lock().await;
+        if let Some(entry) = modules
+            .iter_mut()
+            .find(|entry| entry.module.process_id == process_id)
+        {
+            self.stop_entry(entry).await?;
+            return Ok(());
+        }
+        if self
+            .registry
+            .with(ResourceHandle::<ProcessHandle>::new(process_id), |_| ())
+            .is_none()
+        {
+            bail!("no running process {process_id}");
+        }
+        reload::stop(&self.runtime, &self.registry, process_id).await?;
+        info!(process_id, "process stopped");
+        Ok(())
+    }
+
+    async fn stop_entry(&self, entry: &mut Supervised) -> Result<ResourceId> {
+        let process_id = entry.module.process_id;
+        match entry.state {
+            State::Running => {
+                let info = entry.process_info(&self.registry);
+                reload::stop(&self.runtime, &self.registry, process_id).await?;
+                entry.lifecycle.fire(LifecycleEvent::Stopped, info);
+            }
+            State::RestartAt(_) | State::Exited => {}
+            State::Stopped => bail!("module `{}` is already stopped", entry.module.spec.label()),
+        }
+        entry.state = State::Stopped;
+        info!(
+            module = entry.module.spec.label(),
+            process_id, "module stopped"
+        );
+        Ok(process_id)
+    }
+
+    /// Stop the module identified by `target`, if it is running, and start it again with a
+    /// fresh process, returning the new process id.
+    pub async fn restart(&self, target: &str) -> Result<ResourceId> {
+        let mut modules = self.modules.lock().await;
+        let index = find(&modules, target)?;
+        let entry = &mut modules[index];
+        if matches!(entry.state, State::Running) {
+            reload::stop(&self.runtime, &self.registry, entry.module.process_id).await?;
+            entry.state = State::Exited;
+        }
+        entry.restarts = 0;
+        let previous = entry.module.process_id;
+        let process_id = entry
+            .spawn(&self.runtime, &self.registry, &self.sandboxes)
+            .await?;
+        info!(
+            module = entry.module.spec.label(),
+            process_id, "module restarted"
+        );
+        entry.fire(LifecycleEvent::Restarted { previous }, &self.registry);
+        Ok(process_id)
+    }
+
+    /// Replace the process of the module identified by `target`, returning the process id of
+    /// the replacement.
+    pub async fn reload(&self, target: &str) -> Result<ResourceId> {
+        let ready_timeout = self
+            .reload
+            .map_or(DEFAULT_READY_TIMEOUT, |options| options.ready_timeout);
+        let mut modules = self.modules.lock().await;
+        let index = find(&modules, target)?;
+        let entry = &mut modules[index];
+        if !matches!(entry.state, State::Running) {
+            bail!("module `{}` is not running", entry.module.spec.label());
+        }
+        let process_id = reload::reload(
+            &self.runtime,
+            &self.registry,
+            &self.sandboxes,
+            &entry.module,
+            ready_timeout,
+        )
+        .await?;
+        info!(
+            module = entry.module.spec.label(),
+            process_id, "module reloaded"
+        );
+        let previous = entry.module.process_id;
+        entry.module.process_id = process_id;
+        entry.modified = modified_at(entry.module.spec.path());
+        entry.digest = digest_of(entry.module.spec.path());
+        entry.started = Instant::now();
+        entry.fire(LifecycleEvent::Restarted { previous }, &self.registry);
+        Ok(process_id)
+    }
+
+    /// Pending futures, mailbox counters and slot usage of the running module identified by
+    /// `target`, with its process id.
+    pub async fn diagnostics(&self, target: &str) -> Result<(ResourceId, InstanceDiagnostics)> {
+        let modules = self.modules.lock().await;
+        let entry = &modules[find(&modules, target)?];
+        let label = entry.module.spec.label();
+        if !matches!(entry.state, State::Running) {
+            bail!("module `{label}` is not running");
+        }
+        let process_id = entry.module.process_id;
+        let diagnostics = self
+            .registry
+            .process_diagnostics(process_id)
+            .ok_or_else(|| anyhow!("module `{label}` has no live instance"))?;
+        Ok((process_id, diagnostics))
+    }
+
+    /// Sample the stack of the running module identified by `target` for `duration`, returning
+    /// its process id and the folded stacks collected. Only one profile of a process may be
+    /// collected at a time.
+    pub async fn profile(
+        &self,
+        target: &str,
+        duration: Duration,
+    ) -> Result<(ResourceId, FoldedStacks)> {
+        let (label, process_id) = {
+            let modules = self.modules.lock().await;
+            let entry = &modules[find(&modules, target)?];
+            let label = entry.module.spec.label().to_string();
+            if !matches!(entry.state, State::Running) {
+                bail!("module `{label}` is not running");
+            }
+            (label, entry.module.process_id)
+        };
+        let profiler = self
+            .registry
+            .process_extension::<GuestProfiler>(process_id)
+            .ok_or_else(|| anyhow!("module `{label}` has no live instance"))?;
+        let profile = profiler
+            .start()
+            .ok_or_else(|| anyhow!("module `{label}` is already being profiled"))?;
+        info!(module = label, process_id, ?duration, "profiling module");
+        sleep(duration).await;
+        Ok((process_id, profile.finish()))
+    }
+
+    /// Stop every running module, giving each up to `deadline` to exit before it is stopped
+    /// forcibly. Modules that no others depend on stop first and singleton providers last, each
+    /// group in reverse start order. Stopped modules are not restarted.
+    pub async fn shutdown(&self, deadline: Duration) {
+        let mut modules = self.modules.lock().await;
+        let providers = self.registry.singleton_providers();
+        let mut order: Vec<_> = modules
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry.state, State::Running))
+            .map(|(index, entry)| {
+                let provider = providers.contains(&entry.module.process_id);
+                (provider, Reverse(index))
+            })
+            .collect();
+        order.sort_unstable();
+
+        for (_, Reverse(index)) in order {
+            modules[index]
+                .stop_within(&self.runtime, &self.registry, deadline)
+                .await;
+        }
+        for entry in modules.iter_mut() {
+            entry.state = State::Stopped;
+        }
+    }
+
+    async fn run(self) {
+        let mut last_reload_check = Instant::now();
+        loop {
+            sleep(SUPERVISE_INTERVAL).await;
+            let reload = self
+                .reload
+                .filter(|options| last_reload_check.elapsed() >= options.poll_interval);
+            if reload.is_some() {
+                last_reload_check = Instant::now();
+            }
+
+            let mut modules = self.modules.lock().await;
+            for entry in modules.iter_mut() {
+                if let Some(options) = reload {
+                    entry
+                        .check_reload(&self.runtime, &self.registry, &self.sandboxes, options)
+                        .await;
+                }
+                entry
+                    .check_exit(&self.runtime, &self.registry, &self.sandboxes)
+                    .await;
+            }
+            if let Some(watchdog) = &self.watchdog {
+                report_stuck(&modules, watchdog.scan(&self.registry));
+            }
+        }
+    }
+}
+
+/// Log each of `stuck`, naming the supervised module holding it if there is one.
+fn report_stuck(modules: &[Supervised], stuck: Vec<StuckFuture>) {
+    for future in stuck {
+        let module = modules
+            .iter()
+            .find(|entry| Some(entry.module.process_id) == future.process_id)
+            .map(|entry| entry.module.spec.label());
+        warn!(
+            module,
+            process_id = future.process_id,
+            hostcall = future.hostcall,
+            future_id = future.future_id,
+            age = ?future.age,
+            aborted = future.aborted,
+            "guest future unresolved past the watchdog threshold"
+        );
+    }
+}
+
+/// Index of the module whose process id or label is `target`.
+fn find(modules: &[Supervised], target: &str) -> Result<usize> {
+    if let Ok(process_id) = target.parse::<ResourceId>()
+        && let Some(index) = modules
+            .iter()
+            .position(|entry| entry.module.process_id == process_id)
+    {
+        return Ok(index);
+    }
+
+    let mut matches = modules
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.module.spec.label() == target)
+        .map(|(index, _)| index);
+    match (matches.next(), matches.next()) {
+        (Some(index), None) => Ok(index),
+        (Some(_), Some(_)) => bail!("several modules match `{target}`; use a process id"),
+        (None, _) => bail!("no supervised module matches `{target}`"),
+    }
+}
+
+fn digest_of(path: &Path) -> Option<blake3::Hash> {
+    fs::read(path).ok().map(|bytes| blake3::hash(&bytes))
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supervised(spec: &str, process_id: ResourceId) -> Supervised {
+        let spec = modules::parse_cli_spec(spec, Path::new(".")).expect("valid spec");
+        Supervised::new(SpawnedModule { spec, process_id }, Lifecycle::default())
+    }
+
+    #[test]
+    fn targets_resolve_by_process_id_or_label() {
+        let modules = [
+            supervised("path=echo.wasm;capabilities=time-read", 3),
+            supervised("path=idle.wasm;capabilities=time-read", 4),
+            supervised("path=idle.wasm;capabilities=time-read", 5),
+        ];
+
+        assert_eq!(find(&modules, "echo.wasm").expect("label"), 0);
+        assert_eq!(find(&modules, "5").expect("process id"), 2);
+        let Err(err) = find(&modules, "idle.wasm") else {
+            panic!("ambiguous label resolved");
+        };
+        assert!(err.to_string().contains("use a process id"), "{err}");
+        assert!(find(&modules, "9").is_err());
+    }
+}
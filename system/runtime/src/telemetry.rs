@@ -0,0 +1,315 @@
+//! Export of the host's traces and metrics to an OpenTelemetry collector over OTLP/HTTP, enabled
+//! with `--otlp-endpoint`.
+//!
+//! Spans and events the host records through `tracing` are exported as traces, next to the fmt
+//! output. The hostcall, module cache and guest metrics that the `metrics` subcommand reports are
+//! read from each kernel whenever the periodic reader collects, and exported as metrics. Both
+//! carry the host's name and work directory as resource attributes, so that a collector can tell
+//! hosts apart.
+//!
+//! Hostcall latencies are exported as a running total of seconds alongside the call count, as
+//! asynchronous instruments cannot report histograms; the `metrics` subcommand still reports the
+//! full distribution.
+
+use std::path::{self, Path};
+
+use anyhow::{Context, Result};
+use opentelemetry::{
+    KeyValue,
+    metrics::{Meter, MeterProvider},
+    trace::TracerProvider,
+};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, metrics::SdkMeterProvider, trace::SdkTracerProvider};
+use selium_filesystem_store::CacheCounters;
+use selium_kernel::{
+    Kernel,
+    drivers::meta,
+    metrics::{GuestMetrics, HostcallMetrics},
+};
+use tracing::{level_filters::LevelFilter, warn};
+use tracing_subscriber::{Layer, Registry, filter::Targets};
+
+/// Name of the exporting service, and of its tracer and meter.
+const SERVICE_NAME: &str = "selium-runtime";
+/// Resource attribute naming the host, as the OpenTelemetry semantic conventions spell it.
+const HOST_NAME: &str = "host.name";
+/// Resource attribute holding the host's absolute work directory.
+const WORK_DIR: &str = "selium.work_dir";
+/// Path the collector accepts traces on, below the endpoint.
+const TRACES_PATH: &str = "/v1/traces";
+/// Path the collector accepts metrics on, below the endpoint.
+const METRICS_PATH: &str = "/v1/metrics";
+/// Crates the exporters send requests through. Their own spans and events are kept out of the
+/// export, or every batch would trace the request that sends it.
+const EXPORT_TARGETS: [&str; 5] = ["h2", "hyper", "hyper_util", "opentelemetry", "reqwest"];
+
+/// Trace and metric pipelines exporting to a collector. Dropping it flushes and stops both.
+pub(crate) struct Telemetry {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    meter: Meter,
+}
+
+impl Telemetry {
+    /// Start exporting to the collector at `endpoint`, such as `http://localhost:4318`, as the
+    /// host working in `work_dir`.
+    pub(crate) fn install(endpoint: &str, work_dir: &Path) -> Result<Self> {
+        let endpoint = endpoint.trim_end_matches('/');
+        let resource = resource(work_dir)?;
+
+        let spans = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}{TRACES_PATH}"))
+            .build()
+            .context("build OTLP span exporter")?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(spans)
+            .with_resource(resource.clone())
+            .build();
+
+        let metrics = MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}{METRICS_PATH}"))
+            .build()
+            .context("build OTLP metric exporter")?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metrics)
+            .with_resource(resource)
+            .build();
+        let meter = meter_provider.meter(SERVICE_NAME);
+
+        Ok(Self {
+            tracer_provider,
+            meter_provider,
+            meter,
+        })
+    }
+
+    /// A layer exporting the spans and events it sees as traces.
+    pub(crate) fn layer(&self) -> Box<dyn Layer<Registry> + Send + Sync> {
+        let targets = Targets::new()
+            .with_default(LevelFilter::TRACE)
+            .with_targets(EXPORT_TARGETS.map(|target| (target, LevelFilter::OFF)));
+        tracing_opentelemetry::layer()
+            .with_tracer(self.tracer_provider.tracer(SERVICE_NAME))
+            .with_filter(targets)
+            .boxed()
+    }
+
+    /// Export the metrics of `kernel`, labelled with `tenant` if it is a tenant's.
+    pub(crate) fn observe(&self, kernel: &Kernel, tenant: Option<&str>) {
+        let labels: Vec<_> = tenant
+            .map(|tenant| KeyValue::new("tenant", tenant.to_string()))
+            .into_iter()
+            .collect();
+        let labelled = move |extra: &[KeyValue]| {
+            let mut attributes = labels.clone();
+            attributes.extend_from_slice(extra);
+            attributes
+        };
+
+        if let Some(metrics) = kernel.get::<HostcallMetrics>().cloned() {
+            let calls = metrics.clone();
+            let labels = labelled.clone();
+            self.meter
+                .u64_observable_counter("selium.hostcall.calls")
+                .with_description("Hostcalls that completed, successfully or not.")
+                .with_callback(move |observer| {
+                    for stats in calls.snapshot() {
+                        observer.observe(stats.calls, &labels(&[hostcall(&stats.module)]));
+                    }
+                })
+                .build();
+
+            let errors = metrics.clone();
+            let labels = labelled.clone();
+            self.meter
+                .u64_observable_counter("selium.hostcall.errors")
+                .with_description("Hostcalls that failed, by error code.")
+                .with_callback(move |observer| {
+                    for stats in errors.snapshot() {
+                        for (code, count) in &stats.errors {
+                            let code = KeyValue::new("code", format!("{code:?}"));
+                            observer.observe(*count, &labels(&[hostcall(&stats.module), code]));
+                        }
+                    }
+                })
+                .build();
+
+            let labels = labelled.clone();
+            self.meter
+                .f64_observable_counter("selium.hostcall.duration")
+                .with_description("Total time from hostcall creation to resolution.")
+                .with_unit("s")
+                .with_callback(move |observer| {
+                    for stats in metrics.snapshot() {
+                        observer.observe(
+                            stats.latency.total.as_secs_f64(),
+                            &labels(&[hostcall(&stats.module)]),
+                        );
+                    }
+                })
+                .build();
+        }
+
+        if let Some(cache) = kernel.get::<CacheCounters>().cloned() {
+            let labels = labelled.clone();
+            self.meter
+                .u64_observable_counter("selium.module_cache.reads")
+                .with_description(
+                    "Module reads, by whether the module cache served them, and cached modules \
+                     discarded as missing or altered.",
+                )
+                .with_callback(move |observer| {
+                    let snapshot = cache.snapshot();
+                    for (outcome, value) in [
+                        ("hit", snapshot.hits),
+                        ("miss", snapshot.misses),
+                        ("integrity_failure", snapshot.integrity_failures),
+                    ] {
+                        observer.observe(value, &labels(&[KeyValue::new("outcome", outcome)]));
+                    }
+                })
+                .build();
+        }
+
+        if let Some(guest) = kernel.get::<GuestMetrics>().cloned() {
+            let counters = guest.clone();
+            let labels = labelled.clone();
+            self.meter
+                .u64_observable_counter("selium.guest.counter")
+                .with_description("Counters kept by guests, by name.")
+                .with_callback(move |observer| {
+                    for (name, value) in counters.snapshot().counters {
+                        observer.observe(value, &labels(&[KeyValue::new("name", name)]));
+                    }
+                })
+                .build();
+
+            self.meter
+                .i64_observable_gauge("selium.guest.gauge")
+                .with_description("Gauges kept by guests, by name.")
+                .with_callback(move |observer| {
+                    for (name, value) in guest.snapshot().gauges {
+                        observer.observe(value, &labelled(&[KeyValue::new("name", name)]));
+                    }
+                })
+                .build();
+        }
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(err) = self.tracer_provider.shutdown() {
+            warn!(error = %err, "failed to flush traces to the OTLP collector");
+        }
+        if let Err(err) = self.meter_provider.shutdown() {
+            warn!(error = %err, "failed to flush metrics to the OTLP collector");
+        }
+    }
+}
+
+/// Attributes identifying the host working in `work_dir` to the collector.
+fn resource(work_dir: &Path) -> Result<Resource> {
+    let work_dir = path::absolute(work_dir)
+        .with_context(|| format!("resolve work directory {}", work_dir.display()))?;
+    Ok(Resource::builder()
+        .with_service_name(SERVICE_NAME)
+        .with_attributes([
+            KeyValue::new(HOST_NAME, meta::detect_host_info().hostname),
+            KeyValue::new(WORK_DIR, work_dir.display().to_string()),
+        ])
+        .build())
+}
+
+fn hostcall(module: &str) -> KeyValue {
+    KeyValue::new("hostcall", module.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use crate::{
+        kernel::{self, KernelOptions},
+        runtime::tests::work_dir,
+    };
+
+    use super::*;
+
+    /// Accept one request on `listener`, acknowledge it, and return its request line and body.
+    fn receive(listener: &TcpListener) -> (String, Vec<u8>) {
+        let (stream, _) = listener.accept().expect("accept exporter");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("read request line");
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).expect("read header");
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                length = value.trim().parse().expect("content length");
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).expect("read body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .expect("respond");
+        (request_line, body)
+    }
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[test]
+    fn metrics_are_exported_with_the_host_and_work_dir() {
+        let work_dir = work_dir("telemetry");
+        let (kernel, _shutdown) =
+            kernel::build(&work_dir, &KernelOptions::default()).expect("build kernel");
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind collector");
+        let endpoint = format!("http://{}", listener.local_addr().expect("address"));
+        let collector = thread::spawn(move || receive(&listener));
+
+        let telemetry = Telemetry::install(&endpoint, &work_dir).expect("install telemetry");
+        telemetry.observe(&kernel, Some("acme"));
+        telemetry
+            .meter_provider
+            .force_flush()
+            .expect("flush metrics");
+
+        let (request_line, body) = collector.join().expect("collector");
+        assert!(
+            request_line.starts_with("POST /v1/metrics "),
+            "{request_line}"
+        );
+        assert!(contains(&body, "selium.module_cache.reads"));
+        assert!(contains(&body, "acme"));
+        assert!(contains(&body, HOST_NAME));
+        assert!(contains(
+            &body,
+            &path::absolute(&work_dir)
+                .expect("absolute")
+                .display()
+                .to_string()
+        ));
+        let _ = std::fs::remove_dir_all(&work_dir);
+    }
+}
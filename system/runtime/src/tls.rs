@@ -10,7 +10,6 @@ use selium_kernel::{
     operation::{Contract, Operation},
     registry::{InstanceRegistry, ResourceType},
 };
-use wasmtime::Caller;
 
 const TLS_BUNDLE_MAX_BYTES: usize = 1024 * 1024;
 
@@ -40,10 +39,10 @@ impl Contract for TlsServerConfigCreateDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
-        let registrar = caller.data().registrar();
+        let registrar = instance.registrar();
         let NetTlsServerConfig { bundle } = input;
 
         async move {
@@ -67,10 +66,10 @@ impl Contract for TlsClientConfigCreateDriver {
 
     fn to_future(
         &self,
-        caller: &mut Caller<'_, InstanceRegistry>,
+        instance: &mut InstanceRegistry,
         input: Self::Input,
     ) -> impl Future<Output = GuestResult<Self::Output>> + Send + 'static {
-        let registrar = caller.data().registrar();
+        let registrar = instance.registrar();
         let NetTlsClientConfig { bundle } = input;
 
         async move {
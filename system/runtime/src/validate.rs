@@ -0,0 +1,188 @@
+//! The `validate` subcommand, checking a module's imports before it is deployed.
+//!
+//! Imports outside the hostcall catalogue make instantiation fail, and catalogue hostcalls whose
+//! capability is not granted are linked to stubs that deny every call. Both are reported, along
+//! with granted capabilities the module never uses.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use selium_abi::{
+    Capability, CapabilitySet,
+    hostcalls::{
+        self, META_DIAGNOSTICS, META_ENCODING, META_HOST_INFO, META_HOSTCALLS,
+        META_IDEMPOTENCY_KEY, META_READY, PROCESS_COMPLETE, PROCESS_RECEIVE, PROCESS_REPORT_PANIC,
+        PROCESS_SELF_STATS,
+    },
+};
+use selium_wasmtime::is_component;
+use wasmtime::{Engine, Module};
+
+use crate::modules;
+
+/// Functions linked for every hostcall import module.
+const HOSTCALL_FUNCTIONS: [&str; 3] = ["create", "poll", "drop"];
+/// Import module of the host functions backing guest async tasks.
+const ASYNC_MODULE: &str = "selium::async";
+/// Functions linked for [`ASYNC_MODULE`].
+const ASYNC_FUNCTIONS: [&str; 1] = ["yield_now"];
+/// Hostcalls linked for every guest, whatever it is granted.
+const META_MODULES: [&str; 10] = [
+    META_HOSTCALLS,
+    META_IDEMPOTENCY_KEY,
+    META_READY,
+    META_DIAGNOSTICS,
+    META_ENCODING,
+    META_HOST_INFO,
+    PROCESS_COMPLETE,
+    PROCESS_RECEIVE,
+    PROCESS_SELF_STATS,
+    PROCESS_REPORT_PANIC,
+];
+
+/// How a module's imports resolve against the hostcalls the host provides.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Report {
+    /// Hostcalls imported that link to a driver, in import order.
+    pub(crate) linked: Vec<&'static str>,
+    /// Imports the host does not provide, as `module::name`.
+    pub(crate) unknown: Vec<String>,
+    /// Hostcalls imported without the capability they require.
+    pub(crate) ungranted: Vec<(&'static str, Capability)>,
+    /// Granted capabilities none of the imported hostcalls require.
+    pub(crate) unused: CapabilitySet,
+}
+
+impl Report {
+    fn is_deployable(&self) -> bool {
+        self.unknown.is_empty() && self.ungranted.is_empty()
+    }
+}
+
+/// Check the imports of the module at `path` against the hostcall catalogue and the
+/// `capabilities` it will be granted, printing any problems found.
+pub fn run(path: &Path, capabilities: &[String]) -> Result<()> {
+    let capabilities = capabilities
+        .iter()
+        .map(|item| modules::parse_capability(item.trim()))
+        .collect::<Result<CapabilitySet>>()?;
+    let bytes = fs::read(path).with_context(|| format!("read module {}", path.display()))?;
+    if is_component(&bytes) {
+        bail!(
+            "{} is a component; only core modules can be validated",
+            path.display()
+        );
+    }
+    let module = Module::from_binary(&Engine::default(), &bytes)
+        .with_context(|| format!("compile module {}", path.display()))?;
+
+    let report = check(
+        module
+            .imports()
+            .map(|import| (import.module(), import.name())),
+        capabilities,
+    );
+    for import in &report.unknown {
+        println!("error: import `{import}` is not provided by the host; instantiation will fail");
+    }
+    for (hostcall, capability) in &report.ungranted {
+        println!(
+            "error: `{hostcall}` requires capability {capability}, which is not granted; calls will be denied"
+        );
+    }
+    for capability in report.unused {
+        println!("warning: capability {capability} is granted but no imported hostcall needs it");
+    }
+
+    if !report.is_deployable() {
+        bail!("{} failed validation", path.display());
+    }
+    println!("{}: ok", path.display());
+    Ok(())
+}
+
+/// Resolve `imports` against the hostcall catalogue and the `capabilities` a module is granted.
+pub(crate) fn check<'a>(
+    imports: impl Iterator<Item = (&'a str, &'a str)>,
+    capabilities: CapabilitySet,
+) -> Report {
+    let mut report = Report::default();
+    let mut used = CapabilitySet::EMPTY;
+
+    for (module, name) in imports {
+        let hostcall = hostcalls::ALL.iter().find(|meta| meta.name == module);
+        let meta_module = META_MODULES.iter().find(|meta| **meta == module);
+        let known = if hostcall.is_some() || meta_module.is_some() {
+            HOSTCALL_FUNCTIONS.contains(&name)
+        } else {
+            module == ASYNC_MODULE && ASYNC_FUNCTIONS.contains(&name)
+        };
+        if !known {
+            report.unknown.push(format!("{module}::{name}"));
+            continue;
+        }
+
+        if let Some(meta_module) = meta_module
+            && !report.linked.contains(meta_module)
+        {
+            report.linked.push(meta_module);
+        }
+        let Some(meta) = hostcall else {
+            continue;
+        };
+        if !capabilities.contains(meta.capability) {
+            if !report
+                .ungranted
+                .iter()
+                .any(|(hostcall, _)| *hostcall == meta.name)
+            {
+                report.ungranted.push((meta.name, meta.capability));
+            }
+            continue;
+        }
+        if !report.linked.contains(&meta.name) {
+            report.linked.push(meta.name);
+        }
+        used.insert(meta.capability);
+    }
+
+    report.unused = capabilities.difference(used);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_are_checked_against_catalogue_and_grants() {
+        let imports = [
+            ("selium::time::now", "create"),
+            ("selium::time::now", "poll"),
+            ("selium::channel::strong_read", "create"),
+            ("selium::channel::strong_read", "drop"),
+            ("selium::meta::ready", "create"),
+            ("selium::async", "yield_now"),
+            ("selium::time::now", "cancel"),
+            ("env", "abort"),
+        ];
+        let report = check(
+            imports.into_iter(),
+            [Capability::TimeRead, Capability::NetQuicBind].into(),
+        );
+
+        assert_eq!(
+            report,
+            Report {
+                linked: vec!["selium::time::now", "selium::meta::ready"],
+                unknown: vec![
+                    "selium::time::now::cancel".to_string(),
+                    "env::abort".to_string()
+                ],
+                ungranted: vec![("selium::channel::strong_read", Capability::ChannelReader)],
+                unused: Capability::NetQuicBind.into(),
+            }
+        );
+        assert!(!report.is_deployable());
+    }
+}
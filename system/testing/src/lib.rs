@@ -0,0 +1,201 @@
+//! Fake capability providers for testing kernel drivers and the crates built on them.
+//!
+//! Each fake implements one of the kernel's capability traits with simple, predictable
+//! behaviour, and records every call it receives in a [`Recorder`]. Tests read the calls back
+//! to assert on them, and inject failures for calls matching a predicate, either once or for
+//! every such call. Injected failures are [`FakeError`]s, which reach guests as the error code
+//! they were created with.
+//!
+//! Fakes are shared through an [`Arc`](std::sync::Arc), keeping a clone in the test while the
+//! kernel's operations hold the other. Use them with
+//! [`BlockingKernelClient`](selium_kernel::testing::BlockingKernelClient) to call the operations
+//! they back from synchronous tests.
+
+use std::fmt;
+
+use parking_lot::Mutex;
+use selium_abi::ErrorCode;
+use selium_kernel::{
+    drivers::module_store::ModuleStoreError, guest_data::GuestError, session::SessionError,
+};
+use thiserror::Error;
+
+pub mod module_store;
+pub mod process;
+pub mod session;
+pub mod time;
+
+pub use module_store::{FakeModuleStore, ModuleStoreCall};
+pub use process::{FakeProcess, FakeProcessLifecycle, ProcessCall};
+pub use session::{FakeSessionLifecycle, SessionCall};
+pub use time::{FakeTime, TimeCall};
+
+type Matcher<Call> = Box<dyn Fn(&Call) -> bool + Send + Sync>;
+
+/// A failure injected into a fake.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("{message}")]
+pub struct FakeError {
+    code: ErrorCode,
+    message: String,
+}
+
+/// Calls received by a fake, and the failures it has been told to inject.
+pub struct Recorder<Call> {
+    calls: Mutex<Vec<Call>>,
+    failures: Mutex<Vec<Failure<Call>>>,
+}
+
+struct Failure<Call> {
+    matches: Matcher<Call>,
+    error: FakeError,
+    once: bool,
+}
+
+impl FakeError {
+    /// A failure reported to guests with `code`.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// The code guests see for this failure.
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl<Call: Clone> Recorder<Call> {
+    /// Every call received so far, oldest first.
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().clone()
+    }
+
+    /// Forget the calls received so far.
+    pub fn clear(&self) {
+        self.calls.lock().clear();
+    }
+
+    /// Fail the next call for which `matches` holds with `error`.
+    pub fn fail_next(
+        &self,
+        matches: impl Fn(&Call) -> bool + Send + Sync + 'static,
+        error: FakeError,
+    ) {
+        self.inject(Box::new(matches), error, true);
+    }
+
+    /// Fail every call for which `matches` holds with `error`, until [`Self::clear_failures`].
+    pub fn fail_always(
+        &self,
+        matches: impl Fn(&Call) -> bool + Send + Sync + 'static,
+        error: FakeError,
+    ) {
+        self.inject(Box::new(matches), error, false);
+    }
+
+    /// Stop injecting failures.
+    pub fn clear_failures(&self) {
+        self.failures.lock().clear();
+    }
+
+    /// Record `call` for an infallible method, which failures are never injected into.
+    pub(crate) fn log(&self, call: Call) {
+        self.calls.lock().push(call);
+    }
+
+    /// Record `call`, returning the failure injected for it, if any. Failures are matched in
+    /// the order they were injected.
+    pub(crate) fn record(&self, call: Call) -> Result<(), FakeError> {
+        let failure = {
+            let mut failures = self.failures.lock();
+            let position = failures.iter().position(|failure| (failure.matches)(&call));
+            position.map(|index| {
+                if failures[index].once {
+                    failures.remove(index).error
+                } else {
+                    failures[index].error.clone()
+                }
+            })
+        };
+        self.calls.lock().push(call);
+        failure.map_or(Ok(()), Err)
+    }
+
+    fn inject(&self, matches: Matcher<Call>, error: FakeError, once: bool) {
+        self.failures.lock().push(Failure {
+            matches,
+            error,
+            once,
+        });
+    }
+}
+
+impl<Call: fmt::Debug> fmt::Debug for Recorder<Call> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("calls", &*self.calls.lock())
+            .field("failures", &self.failures.lock().len())
+            .finish()
+    }
+}
+
+impl<Call> Default for Recorder<Call> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(Vec::new()),
+            failures: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl From<FakeError> for GuestError {
+    fn from(value: FakeError) -> Self {
+        GuestError::Coded(value.code, value.message)
+    }
+}
+
+impl From<SessionError> for FakeError {
+    fn from(value: SessionError) -> Self {
+        Self::new(ErrorCode::from(&value), value.to_string())
+    }
+}
+
+impl From<FakeError> for ModuleStoreError {
+    fn from(value: FakeError) -> Self {
+        match value.code {
+            ErrorCode::NotFound => ModuleStoreError::NotFound(value.message),
+            _ => ModuleStoreError::Filesystem(value.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_injected_for_matching_calls() {
+        let recorder = Recorder::<u32>::default();
+        let denied = FakeError::new(ErrorCode::PermissionDenied, "denied");
+        recorder.fail_next(|call| *call == 2, denied.clone());
+        recorder.fail_always(
+            |call| *call > 2,
+            FakeError::new(ErrorCode::Kernel, "broken"),
+        );
+
+        assert_eq!(recorder.record(1), Ok(()));
+        assert_eq!(recorder.record(2), Err(denied));
+        assert_eq!(recorder.record(2), Ok(()));
+        assert!(recorder.record(3).is_err());
+        assert!(recorder.record(4).is_err());
+        assert_eq!(recorder.calls(), [1, 2, 2, 3, 4]);
+
+        recorder.clear_failures();
+        recorder.clear();
+        assert_eq!(recorder.record(3), Ok(()));
+        assert_eq!(recorder.calls(), [3]);
+    }
+}
@@ -0,0 +1,73 @@
+//! A fake module store.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use parking_lot::Mutex;
+use selium_kernel::drivers::module_store::{
+    ModuleRevision, ModuleStoreError, ModuleStoreReadCapability,
+};
+
+use crate::Recorder;
+
+/// A call received by [`FakeModuleStore`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModuleStoreCall {
+    Read(String),
+}
+
+/// A module store serving the modules inserted into it.
+///
+/// Injected failures with [`ErrorCode::NotFound`](selium_abi::ErrorCode::NotFound) surface as
+/// [`ModuleStoreError::NotFound`], and any other as [`ModuleStoreError::Filesystem`]. A module's
+/// revision is a hash of the bytes served for it, so replacing a module changes its revision.
+#[derive(Debug, Default)]
+pub struct FakeModuleStore {
+    modules: Mutex<HashMap<String, Vec<u8>>>,
+    recorder: Recorder<ModuleStoreCall>,
+}
+
+impl FakeModuleStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `bytes` for `module_id`.
+    pub fn with_module(self, module_id: impl Into<String>, bytes: impl Into<Vec<u8>>) -> Self {
+        self.insert(module_id, bytes);
+        self
+    }
+
+    /// Serve `bytes` for `module_id`, replacing any module served for it before.
+    pub fn insert(&self, module_id: impl Into<String>, bytes: impl Into<Vec<u8>>) {
+        self.modules.lock().insert(module_id.into(), bytes.into());
+    }
+
+    /// Calls received, and failures to inject.
+    pub fn recorder(&self) -> &Recorder<ModuleStoreCall> {
+        &self.recorder
+    }
+}
+
+impl ModuleStoreReadCapability for FakeModuleStore {
+    fn read(&self, module_id: &str) -> Result<Vec<u8>, ModuleStoreError> {
+        self.recorder
+            .record(ModuleStoreCall::Read(module_id.to_string()))?;
+        self.modules
+            .lock()
+            .get(module_id)
+            .cloned()
+            .ok_or_else(|| ModuleStoreError::NotFound(module_id.to_string()))
+    }
+
+    fn revision(&self, module_id: &str) -> Option<ModuleRevision> {
+        let mut hasher = DefaultHasher::new();
+        self.modules.lock().get(module_id)?.hash(&mut hasher);
+        Some(ModuleRevision::Digest(
+            hasher.finish().to_le_bytes().to_vec(),
+        ))
+    }
+}
@@ -0,0 +1,244 @@
+//! A fake process lifecycle.
+
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use parking_lot::Mutex;
+use selium_abi::{CapabilitySet, EntrypointInvocation, ErrorCode};
+use selium_kernel::{
+    drivers::process::ProcessLifecycleCapability,
+    registry::{Registry, ResourceId},
+};
+
+use crate::{FakeError, Recorder};
+
+/// A call received by [`FakeProcessLifecycle`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProcessCall {
+    Start {
+        process_id: ResourceId,
+        module_id: String,
+        name: String,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+    },
+    Stop(ResourceId),
+    Wait(ResourceId),
+}
+
+/// A process started by [`FakeProcessLifecycle`], which runs nothing.
+#[derive(Debug)]
+pub struct FakeProcess {
+    process_id: ResourceId,
+    output: Vec<u8>,
+}
+
+/// A process lifecycle that starts no guests.
+///
+/// Each started process is registered under the reserved process id, so the kernel's drivers can
+/// stop and wait on it as usual. Waiting returns the output set for the process's module with
+/// [`FakeProcessLifecycle::set_output`], or an empty buffer.
+#[derive(Debug, Default)]
+pub struct FakeProcessLifecycle {
+    outputs: Mutex<HashMap<String, Vec<u8>>>,
+    recorder: Recorder<ProcessCall>,
+}
+
+impl FakeProcess {
+    /// The id the process was started under.
+    pub fn process_id(&self) -> ResourceId {
+        self.process_id
+    }
+}
+
+impl FakeProcessLifecycle {
+    /// A lifecycle whose processes complete without output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Complete processes started from `module_id` with `output`, an rkyv-encoded value.
+    pub fn set_output(&self, module_id: impl Into<String>, output: impl Into<Vec<u8>>) {
+        self.outputs.lock().insert(module_id.into(), output.into());
+    }
+
+    /// Calls received, and failures to inject.
+    pub fn recorder(&self) -> &Recorder<ProcessCall> {
+        &self.recorder
+    }
+}
+
+impl ProcessLifecycleCapability for FakeProcessLifecycle {
+    type Process = FakeProcess;
+    type Error = FakeError;
+
+    fn start(
+        &self,
+        registry: &Arc<Registry>,
+        process_id: ResourceId,
+        module_id: &str,
+        name: &str,
+        capabilities: CapabilitySet,
+        entrypoint: EntrypointInvocation,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let result = self
+            .recorder
+            .record(ProcessCall::Start {
+                process_id,
+                module_id: module_id.to_string(),
+                name: name.to_string(),
+                capabilities,
+                entrypoint,
+            })
+            .and_then(|()| {
+                let process = FakeProcess {
+                    process_id,
+                    output: self
+                        .outputs
+                        .lock()
+                        .get(module_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                };
+                registry
+                    .initialise(process_id, process)
+                    .map(|_| ())
+                    .map_err(|err| FakeError::new(ErrorCode::Registry, err.to_string()))
+            });
+        async move { result }
+    }
+
+    fn stop(
+        &self,
+        instance: &mut Self::Process,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let result = self.recorder.record(ProcessCall::Stop(instance.process_id));
+        async move { result }
+    }
+
+    fn wait(
+        &self,
+        instance: Self::Process,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+        let result = self
+            .recorder
+            .record(ProcessCall::Wait(instance.process_id))
+            .map(|()| instance.output);
+        async move { result }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use selium_abi::{
+        AbiSignature, GuestResourceId, ProcessStart, ProcessStartEnvelope, encode_rkyv,
+    };
+    use selium_kernel::{
+        drivers::{Capability, process::lifecycle_ops},
+        guest_data::GuestError,
+        operation::HostcallContext,
+        registry::{ResourceHandle, ResourceType},
+        session::Session,
+        testing::BlockingKernelClient,
+    };
+
+    use super::*;
+
+    fn start(module_id: &str) -> ProcessStartEnvelope {
+        start_with(module_id, CapabilitySet::default())
+    }
+
+    fn start_with(module_id: &str, capabilities: CapabilitySet) -> ProcessStartEnvelope {
+        ProcessStart {
+            module_id: module_id.to_string(),
+            name: "fake".to_string(),
+            capabilities,
+            entrypoint: EntrypointInvocation::new(AbiSignature::new(vec![], vec![]), vec![])
+                .expect("entrypoint"),
+        }
+        .into()
+    }
+
+    #[test]
+    fn processes_are_started_and_waited_on_through_the_kernel() {
+        let lifecycle = Arc::new(FakeProcessLifecycle::new());
+        lifecycle.set_output("echo.wasm", encode_rkyv(&7u64).expect("encode output"));
+        let (start_op, _stop_op, wait_op) = lifecycle_ops(Arc::clone(&lifecycle));
+        let mut client = BlockingKernelClient::new().expect("client");
+
+        let process: GuestResourceId = client.call(&start_op, &start("echo.wasm")).expect("start");
+        let output = client.call(&wait_op, &process).expect("wait");
+        assert_eq!(output, encode_rkyv(&7u64).expect("encode output"));
+
+        lifecycle.recorder().fail_next(
+            |call| matches!(call, ProcessCall::Start { .. }),
+            FakeError::new(ErrorCode::PermissionDenied, "not today"),
+        );
+        assert!(matches!(
+            client.call(&start_op, &start("echo.wasm")),
+            Err(GuestError::Coded(ErrorCode::PermissionDenied, _))
+        ));
+
+        let calls = lifecycle.recorder().calls();
+        assert_eq!(calls.len(), 3);
+        assert!(matches!(
+            &calls[0],
+            ProcessCall::Start { module_id, .. } if module_id == "echo.wasm"
+        ));
+        assert!(matches!(calls[1], ProcessCall::Wait(_)));
+    }
+
+    #[test]
+    fn spawned_processes_are_no_more_entitled_than_their_spawner() {
+        let lifecycle = Arc::new(FakeProcessLifecycle::new());
+        let (start_op, _stop_op, _wait_op) = lifecycle_ops(Arc::clone(&lifecycle));
+        let mut client = BlockingKernelClient::new().expect("client");
+        let registry = Arc::clone(client.registry());
+        let spawner = registry
+            .add(
+                Session::bootstrap(
+                    [Capability::ProcessLifecycle, Capability::TimeRead].into(),
+                    [0; 32],
+                ),
+                None,
+                ResourceType::Session,
+            )
+            .expect("add session")
+            .into_id();
+        client
+            .instance()
+            .insert_extension(HostcallContext::new(spawner))
+            .expect("attach context");
+
+        let process: GuestResourceId = client
+            .call(
+                &start_op,
+                &start_with("echo.wasm", Capability::TimeRead.into()),
+            )
+            .expect("start");
+        let process = ResourceId::try_from(process).expect("process id");
+        let context = HostcallContext::for_process(&registry, process, CapabilitySet::default())
+            .expect("process context");
+        let entitled = |capability| {
+            registry
+                .with(
+                    ResourceHandle::<Session>::new(context.session()),
+                    |session: &mut Session| session.entitled(capability),
+                )
+                .expect("process session")
+        };
+        assert!(entitled(Capability::TimeRead));
+        assert!(!entitled(Capability::ProcessLifecycle));
+
+        assert!(matches!(
+            client.call(
+                &start_op,
+                &start_with("echo.wasm", Capability::NetQuicBind.into()),
+            ),
+            Err(GuestError::PermissionDenied)
+        ));
+        assert_eq!(lifecycle.recorder().calls().len(), 1);
+
+        registry.discard(process);
+        assert!(registry.metadata(context.session()).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+//! A fake session lifecycle.
+
+use selium_kernel::{
+    drivers::{Capability, session::SessionLifecycleCapability},
+    registry::ResourceId,
+    session::{Session, SessionLifecycleDriver},
+};
+
+use crate::{FakeError, Recorder};
+
+/// A call received by [`FakeSessionLifecycle`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SessionCall {
+    Create { pubkey: [u8; 32] },
+    AddEntitlement(Capability),
+    RemoveEntitlement(Capability),
+    AddResource(Capability, ResourceId),
+    RemoveResource(Capability, ResourceId),
+    Remove,
+}
+
+/// A session lifecycle that manages sessions as the kernel's own does, unless told to fail.
+#[derive(Debug, Default)]
+pub struct FakeSessionLifecycle {
+    recorder: Recorder<SessionCall>,
+}
+
+impl FakeSessionLifecycle {
+    /// A lifecycle that fails only where the kernel's own would.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls received, and failures to inject.
+    pub fn recorder(&self) -> &Recorder<SessionCall> {
+        &self.recorder
+    }
+}
+
+impl SessionLifecycleCapability for FakeSessionLifecycle {
+    type Error = FakeError;
+
+    fn create(&self, parent: &Session, pubkey: [u8; 32]) -> Result<Session, Self::Error> {
+        self.recorder.record(SessionCall::Create { pubkey })?;
+        Ok(SessionLifecycleDriver.create(parent, pubkey)?)
+    }
+
+    fn add_entitlement(
+        &self,
+        target: &mut Session,
+        entitlement: Capability,
+    ) -> Result<(), Self::Error> {
+        self.recorder
+            .record(SessionCall::AddEntitlement(entitlement))?;
+        Ok(SessionLifecycleDriver.add_entitlement(target, entitlement)?)
+    }
+
+    fn rm_entitlement(
+        &self,
+        target: &mut Session,
+        entitlement: Capability,
+    ) -> Result<(), Self::Error> {
+        self.recorder
+            .record(SessionCall::RemoveEntitlement(entitlement))?;
+        Ok(SessionLifecycleDriver.rm_entitlement(target, entitlement)?)
+    }
+
+    fn add_resource(
+        &self,
+        target: &mut Session,
+        entitlement: Capability,
+        resource: ResourceId,
+    ) -> Result<bool, Self::Error> {
+        self.recorder
+            .record(SessionCall::AddResource(entitlement, resource))?;
+        Ok(SessionLifecycleDriver.add_resource(target, entitlement, resource)?)
+    }
+
+    fn rm_resource(
+        &self,
+        target: &mut Session,
+        entitlement: Capability,
+        resource: ResourceId,
+    ) -> Result<bool, Self::Error> {
+        self.recorder
+            .record(SessionCall::RemoveResource(entitlement, resource))?;
+        Ok(SessionLifecycleDriver.rm_resource(target, entitlement, resource)?)
+    }
+
+    fn remove(&self, target: &Session) -> Result<(), Self::Error> {
+        self.recorder.record(SessionCall::Remove)?;
+        Ok(SessionLifecycleDriver.remove(target)?)
+    }
+}
@@ -0,0 +1,66 @@
+//! A fake clock.
+
+use std::{
+    future::{Future, ready},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use selium_abi::TimeNow;
+use selium_kernel::{drivers::time::TimeCapability, guest_data::GuestResult};
+
+use crate::Recorder;
+
+/// A call received by [`FakeTime`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeCall {
+    Now,
+    Sleep(Duration),
+}
+
+/// A clock whose sleeps return straight away, moving the clock forward by the time slept.
+///
+/// Only sleeps can be failed; reading the clock cannot fail.
+#[derive(Debug)]
+pub struct FakeTime {
+    now: Mutex<TimeNow>,
+    recorder: Recorder<TimeCall>,
+}
+
+impl FakeTime {
+    /// A clock reading `now`.
+    pub fn new(now: TimeNow) -> Self {
+        Self {
+            now: Mutex::new(now),
+            recorder: Recorder::default(),
+        }
+    }
+
+    /// Move the clock forward by `step`.
+    pub fn advance(&self, step: Duration) {
+        let step = u64::try_from(step.as_millis()).unwrap_or(u64::MAX);
+        let mut now = self.now.lock();
+        now.unix_ms = now.unix_ms.saturating_add(step);
+        now.monotonic_ms = now.monotonic_ms.saturating_add(step);
+    }
+
+    /// Calls received, and failures to inject.
+    pub fn recorder(&self) -> &Recorder<TimeCall> {
+        &self.recorder
+    }
+}
+
+impl TimeCapability for FakeTime {
+    fn now(&self) -> TimeNow {
+        self.recorder.log(TimeCall::Now);
+        *self.now.lock()
+    }
+
+    fn sleep(&self, duration: Duration) -> impl Future<Output = GuestResult<()>> + Send + 'static {
+        let result = self.recorder.record(TimeCall::Sleep(duration));
+        if result.is_ok() {
+            self.advance(duration);
+        }
+        ready(result.map_err(Into::into))
+    }
+}
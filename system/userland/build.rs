@@ -3,8 +3,9 @@ use std::{ffi::OsStr, fs, path::Path};
 use flatbuffers_build::BuilderOptions;
 use flatc_fork::flatc;
 
-const SCHEMAS: [&str; 3] = [
+const SCHEMAS: [&str; 4] = [
     "schemas/result.fbs",
+    "schemas/control.fbs",
     "schemas/logging.fbs",
     "schemas/net.fbs",
 ];
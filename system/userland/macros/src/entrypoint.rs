@@ -10,6 +10,8 @@ use syn::{
 enum RetKind {
     Unit,
     Result,
+    Value,
+    ResultValue,
 }
 
 struct ParamSpec {
@@ -155,6 +157,18 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
         quote! { #user_ident(#(#arg_idents),*) }
     };
 
+    let complete = quote! {
+        if let Err(err) = selium_userland::block_on(
+            selium_userland::process::complete(&__selium_value),
+        ) {
+            panic!(
+                "entrypoint {} failed to record its completion value: {}",
+                stringify!(#orig_ident),
+                err,
+            );
+        }
+    };
+
     let run_user = match ret_kind {
         RetKind::Unit => quote! {
             #call_user;
@@ -164,6 +178,17 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
                 panic!("entrypoint {} failed: {:?}", stringify!(#orig_ident), err);
             }
         },
+        RetKind::Value => quote! {
+            let __selium_value = #call_user;
+            #complete
+        },
+        RetKind::ResultValue => quote! {
+            let __selium_value = match #call_user {
+                Ok(value) => value,
+                Err(err) => panic!("entrypoint {} failed: {:?}", stringify!(#orig_ident), err),
+            };
+            #complete
+        },
     };
 
     let user_fn = quote! {
@@ -322,6 +347,7 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
     let entrypoint = quote! {
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #orig_ident(#(#entrypoint_inputs),*) {
+            selium_userland::process::install_panic_hook();
             #log_uri_binding
             #install_log_uri_registrar
             if let Err(err) = #init_logging {
@@ -346,9 +372,11 @@ fn classify_return(ret: &ReturnType) -> Result<RetKind, Error> {
         ReturnType::Type(_, ty) => match ty.as_ref() {
             Type::Tuple(tuple) if tuple.elems.is_empty() => Ok(RetKind::Unit),
             Type::Path(path) if is_result_unit(path) => Ok(RetKind::Result),
+            Type::Path(path) if is_result(path) => Ok(RetKind::ResultValue),
+            Type::Path(_) | Type::Tuple(_) | Type::Array(_) => Ok(RetKind::Value),
             other => Err(Error::new_spanned(
                 other,
-                "#[entrypoint] functions must return () or Result<(), E>",
+                "#[entrypoint] functions must return (), an owned completion value, or a Result of either",
             )),
         },
     }
@@ -516,6 +544,13 @@ fn is_str_type(ty: &Type) -> bool {
     )
 }
 
+fn is_result(path: &syn::TypePath) -> bool {
+    path.path
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "Result")
+}
+
 fn is_result_unit(path: &syn::TypePath) -> bool {
     if let Some(seg) = path.path.segments.last()
         && seg.ident == "Result"
@@ -2,6 +2,7 @@ use proc_macro::TokenStream;
 
 mod dependency_id;
 mod entrypoint;
+mod rpc;
 mod schema;
 
 /// Compute a singleton dependency identifier from a string literal.
@@ -16,6 +17,12 @@ pub fn schema(attr: TokenStream, item: TokenStream) -> TokenStream {
     schema::expand(attr, item)
 }
 
+/// Trait-level annotation generating a typed client stub and server dispatch for an RPC service.
+#[proc_macro_attribute]
+pub fn rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    rpc::expand(attr, item)
+}
+
 #[proc_macro_attribute]
 pub fn entrypoint(attr: TokenStream, item: TokenStream) -> TokenStream {
     entrypoint::expand(attr, item)
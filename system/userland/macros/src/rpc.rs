@@ -0,0 +1,247 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    Error, FnArg, Ident, ItemTrait, LitByteStr, LitStr, Pat, ReturnType, TraitItem, TraitItemFn,
+    Type, parse::Parser, parse_macro_input, parse_quote,
+};
+
+struct MethodSpec {
+    attrs: Vec<syn::Attribute>,
+    ident: Ident,
+    args: Vec<Ident>,
+    tys: Vec<Type>,
+    output: Type,
+}
+
+pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let service = parse_macro_input!(item as ItemTrait);
+    let name = match parse_name(attr.into()) {
+        Ok(name) => name.unwrap_or_else(|| service.ident.to_string()),
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let methods = match validate_methods(&service) {
+        Ok(methods) => methods,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let vis = &service.vis;
+    let attrs = &service.attrs;
+    let trait_ident = &service.ident;
+    let client_ident = format_ident!("{}Client", trait_ident);
+    let server_ident = format_ident!("{}Server", trait_ident);
+    let name_lit = LitStr::new(&name, Span::call_site());
+    let hash = blake3::hash(name.as_bytes());
+    let hash_lit = LitByteStr::new(hash.as_bytes(), Span::call_site());
+
+    let trait_methods = methods.iter().map(|method| {
+        let MethodSpec {
+            attrs,
+            ident,
+            args,
+            tys,
+            output,
+        } = method;
+        quote! {
+            #(#attrs)*
+            fn #ident(&self, #(#args: #tys),*) -> impl ::core::future::Future<Output = #output>;
+        }
+    });
+
+    let client_methods = methods.iter().map(|method| {
+        let MethodSpec {
+            attrs,
+            ident,
+            args,
+            tys,
+            output,
+        } = method;
+        let method_lit = LitStr::new(&ident.to_string(), Span::call_site());
+        quote! {
+            #(#attrs)*
+            pub async fn #ident(
+                &self,
+                #(#args: #tys),*
+            ) -> Result<#output, selium_userland::rpc::RpcError> {
+                self.0.call(#method_lit, &(#(#args,)*)).await
+            }
+        }
+    });
+
+    let dispatch_arms = methods.iter().map(|method| {
+        let MethodSpec {
+            ident, args, tys, ..
+        } = method;
+        let method_lit = LitStr::new(&ident.to_string(), Span::call_site());
+        quote! {
+            #method_lit => {
+                let (#(#args,)*): (#(#tys,)*) = selium_userland::rpc::decode(__selium_args)?;
+                selium_userland::rpc::encode(&self.0.#ident(#(#args),*).await)
+            }
+        }
+    });
+
+    let client_doc = format!("Client for the [`{trait_ident}`] RPC service.");
+    let server_doc =
+        format!("Dispatches RPC calls to a [`{trait_ident}`] implementation, for serving.");
+
+    quote! {
+        #(#attrs)*
+        #vis trait #trait_ident {
+            #(#trait_methods)*
+        }
+
+        #[doc = #client_doc]
+        #[derive(Clone)]
+        #vis struct #client_ident(selium_userland::rpc::RpcClient);
+
+        #[doc = #server_doc]
+        #vis struct #server_ident<S>(pub S);
+
+        impl #client_ident {
+            /// Wrap a client connected to the service.
+            pub fn new(client: selium_userland::rpc::RpcClient) -> Self {
+                Self(client)
+            }
+
+            #(#client_methods)*
+        }
+
+        impl selium_userland::Dependency for #client_ident {
+            type Handle = selium_userland::io::SharedChannel;
+            type Error = selium_userland::rpc::RpcError;
+
+            const DESCRIPTOR: selium_userland::DependencyDescriptor =
+                selium_userland::DependencyDescriptor::new(
+                    #name_lit,
+                    selium_userland::DependencyId(*#hash_lit),
+                );
+
+            async fn from_handle(handle: Self::Handle) -> Result<Self, Self::Error> {
+                selium_userland::rpc::RpcClient::connect(handle).await.map(Self)
+            }
+        }
+
+        impl<S: #trait_ident> selium_userland::rpc::RpcDispatch for #server_ident<S> {
+            const DESCRIPTOR: selium_userland::DependencyDescriptor =
+                <#client_ident as selium_userland::Dependency>::DESCRIPTOR;
+
+            async fn dispatch(
+                &self,
+                __selium_method: &str,
+                __selium_args: &[u8],
+            ) -> Result<Vec<u8>, selium_userland::rpc::RpcError> {
+                match __selium_method {
+                    #(#dispatch_arms)*
+                    _ => Err(selium_userland::rpc::RpcError::UnknownMethod(
+                        __selium_method.to_string(),
+                    )),
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Parse the optional `name = "..."` argument naming the service's singleton.
+fn parse_name(attr: proc_macro2::TokenStream) -> Result<Option<String>, Error> {
+    let mut name = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            let value: LitStr = meta.value()?.parse()?;
+            name = Some(value.value());
+            Ok(())
+        } else {
+            Err(meta.error("unknown key in #[rpc]"))
+        }
+    });
+    parser.parse2(attr)?;
+    Ok(name)
+}
+
+fn validate_methods(service: &ItemTrait) -> Result<Vec<MethodSpec>, Error> {
+    if !service.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &service.generics,
+            "#[rpc] does not support generic traits",
+        ));
+    }
+
+    service
+        .items
+        .iter()
+        .map(|item| match item {
+            TraitItem::Fn(method) => validate_method(method),
+            other => Err(Error::new_spanned(
+                other,
+                "#[rpc] traits may only contain methods",
+            )),
+        })
+        .collect()
+}
+
+fn validate_method(method: &TraitItemFn) -> Result<MethodSpec, Error> {
+    let sig = &method.sig;
+    if sig.asyncness.is_none() {
+        return Err(Error::new_spanned(sig, "#[rpc] methods must be async"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &sig.generics,
+            "#[rpc] methods do not support generics",
+        ));
+    }
+    if method.default.is_some() {
+        return Err(Error::new_spanned(
+            &method.default,
+            "#[rpc] methods cannot have default bodies",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        _ => {
+            return Err(Error::new_spanned(
+                sig,
+                "#[rpc] methods must take &self as their first argument",
+            ));
+        }
+    }
+
+    let mut args = Vec::new();
+    let mut tys = Vec::new();
+    for input in inputs {
+        let FnArg::Typed(pat_type) = input else {
+            return Err(Error::new_spanned(input, "unexpected receiver"));
+        };
+        let Pat::Ident(pat) = pat_type.pat.as_ref() else {
+            return Err(Error::new_spanned(
+                &pat_type.pat,
+                "#[rpc] arguments must use identifier patterns",
+            ));
+        };
+        if matches!(pat_type.ty.as_ref(), Type::Reference(_)) {
+            return Err(Error::new_spanned(
+                &pat_type.ty,
+                "#[rpc] arguments must be owned values",
+            ));
+        }
+        args.push(pat.ident.clone());
+        tys.push(pat_type.ty.as_ref().clone());
+    }
+
+    let output = match &sig.output {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ty) => ty.as_ref().clone(),
+    };
+
+    Ok(MethodSpec {
+        attrs: method.attrs.clone(),
+        ident: sig.ident.clone(),
+        args,
+        tys,
+        output,
+    })
+}
@@ -3,8 +3,8 @@
 use selium_userland_macros::entrypoint;
 
 #[entrypoint]
-fn guest() -> u32 {
-    1
+fn guest() -> &'static str {
+    "done"
 }
 
 fn main() {}
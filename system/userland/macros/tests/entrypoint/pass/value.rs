@@ -0,0 +1,18 @@
+#![allow(unused)]
+
+use selium_userland_macros::entrypoint;
+
+#[entrypoint]
+fn total(values: Vec<u64>) -> u64 {
+    values.iter().sum()
+}
+
+#[entrypoint]
+async fn checked_total(values: Vec<u64>) -> Result<u64, String> {
+    values
+        .iter()
+        .try_fold(0u64, |sum, value| sum.checked_add(*value))
+        .ok_or_else(|| "overflow".to_string())
+}
+
+fn main() {}
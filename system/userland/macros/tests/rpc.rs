@@ -0,0 +1,8 @@
+use trybuild::TestCases;
+
+#[test]
+fn rpc_macro_shape() {
+    let t = TestCases::new();
+    t.pass("tests/rpc/pass/*.rs");
+    t.compile_fail("tests/rpc/fail/*.rs");
+}
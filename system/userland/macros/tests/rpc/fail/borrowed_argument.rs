@@ -0,0 +1,8 @@
+use selium_userland_macros::rpc;
+
+#[rpc]
+trait Greeter {
+    async fn greet(&self, name: &str) -> String;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use selium_userland_macros::rpc;
+
+#[rpc]
+trait Greeter {
+    fn greet(&self, name: String) -> String;
+}
+
+fn main() {}
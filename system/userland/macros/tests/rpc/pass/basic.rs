@@ -0,0 +1,26 @@
+#![allow(unused)]
+
+use selium_userland::rpc::RpcDispatch;
+use selium_userland_macros::rpc;
+
+#[rpc(name = "selium.tests.greeter")]
+pub trait Greeter {
+    async fn greet(&self, name: String) -> String;
+    async fn reset(&self);
+}
+
+struct Polite;
+
+impl Greeter for Polite {
+    async fn greet(&self, name: String) -> String {
+        format!("Hello, {name}")
+    }
+
+    async fn reset(&self) {}
+}
+
+fn assert_dispatch<S: RpcDispatch>(_: &S) {}
+
+fn main() {
+    assert_dispatch(&GreeterServer(Polite));
+}
@@ -0,0 +1,68 @@
+//! Compression run by the host's native codecs.
+//!
+//! Compressing on the host keeps guests handling large payloads from bundling a Wasm codec,
+//! which grows the module and runs far slower than the host's.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{
+//!     compress::{self, CompressionAlgorithm, CompressionLevel},
+//!     io::DriverError,
+//! };
+//!
+//! async fn round_trip(report: &[u8]) -> Result<Vec<u8>, DriverError> {
+//!     let packed =
+//!         compress::deflate(CompressionAlgorithm::Zstd, CompressionLevel::Default, report).await?;
+//!     compress::inflate(CompressionAlgorithm::Zstd, &packed).await
+//! }
+//! ```
+
+use selium_abi::{CompressDeflate, CompressInflate, DEFAULT_MAX_PAYLOAD};
+pub use selium_abi::{CompressionAlgorithm, CompressionLevel};
+
+use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+
+/// Compress `data` into `algorithm`'s format, spending `level` effort.
+pub async fn deflate(
+    algorithm: CompressionAlgorithm,
+    level: CompressionLevel,
+    data: &[u8],
+) -> Result<Vec<u8>, DriverError> {
+    let args = encode_args(&CompressDeflate {
+        algorithm,
+        level,
+        data: data.to_vec(),
+    })?;
+    DriverFuture::<compress_deflate::Module, RkyvDecoder<Vec<u8>>>::new(
+        &args,
+        data.len().min(DEFAULT_MAX_PAYLOAD),
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+/// Decompress `data` from `algorithm`'s format. The host refuses payloads that inflate past
+/// its hostcall output limit.
+pub async fn inflate(algorithm: CompressionAlgorithm, data: &[u8]) -> Result<Vec<u8>, DriverError> {
+    let args = encode_args(&CompressInflate {
+        algorithm,
+        data: data.to_vec(),
+    })?;
+    DriverFuture::<compress_inflate::Module, RkyvDecoder<Vec<u8>>>::new(
+        &args,
+        data.len().saturating_mul(4).min(DEFAULT_MAX_PAYLOAD),
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+driver_module!(
+    compress_deflate,
+    COMPRESS_DEFLATE,
+    "selium::compress::deflate"
+);
+driver_module!(
+    compress_inflate,
+    COMPRESS_INFLATE,
+    "selium::compress::inflate"
+);
@@ -2,10 +2,10 @@
 
 use core::future::Future;
 
-use crate::{DependencyId, FromHandle, driver::DriverError, singleton};
+use crate::{DependencyId, FromHandle, driver::DriverError, services, singleton};
 use selium_abi::GuestResourceId;
 
-/// Descriptor that identifies a singleton dependency.
+/// Descriptor that identifies a singleton dependency or a service in the directory.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DependencyDescriptor {
     /// Human-readable dependency name.
@@ -58,6 +58,19 @@ impl Context {
         T::from_handle(handle).await
     }
 
+    /// Resolve a service from the directory to one of its instances, by type.
+    ///
+    /// Each call may return a different instance; see [`services`] for how one is chosen.
+    pub async fn service<T>(&self) -> Result<T, T::Error>
+    where
+        T: Dependency,
+        T::Error: From<DriverError>,
+    {
+        let raw = services::resolve(T::DESCRIPTOR.id).await?;
+        let handle = unsafe { T::Handle::from_handle(raw) };
+        T::from_handle(handle).await
+    }
+
     /// Look up a singleton dependency and trap on failure.
     pub async fn require<T>(&self) -> T
     where
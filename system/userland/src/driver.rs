@@ -2,7 +2,8 @@
 //!
 //! Selium guest APIs are implemented in terms of *drivers* (hostcalls exposed to guests). This
 //! module provides [`DriverFuture`], which wraps each driver's `create/poll/drop` hooks into an
-//! ergonomic `async` future, plus helpers for serialising driver arguments.
+//! ergonomic `async` future, [`DriverStream`] for drivers yielding a sequence of items, plus
+//! helpers for serialising driver arguments.
 //!
 //! # Examples
 //! ```
@@ -17,15 +18,17 @@
 
 use core::{marker::PhantomData, slice};
 use std::{
+    cell::{Cell, RefCell},
     future::Future,
-    io,
+    io, mem,
     pin::Pin,
     task::{Context, Poll},
 };
 
+use futures::Stream;
 use selium_abi::{
-    DRIVER_ERROR_MESSAGE_CODE, DriverPollResult, GuestInt, GuestUint, RkyvEncode,
-    decode_driver_error_message, decode_rkyv, driver_decode_result, encode_rkyv,
+    DRIVER_ERROR_MESSAGE_CODE, DriverPollResult, ErrorCode, GuestSize, GuestUint, JsonPayload,
+    PayloadEncoding, RkyvEncode, decode_payload, driver_decode_result, encode_payload,
 };
 use thiserror::Error;
 
@@ -38,9 +41,23 @@ pub const RKYV_VEC_OVERHEAD: usize = 16;
 /// The host may return human-readable error strings; this value keeps common error responses from
 /// reallocating.
 pub const MIN_RESULT_CAPACITY: usize = 256;
+/// Maximum buffer capacity reserved for driver replies.
+///
+/// Longer replies are handed over in chunks of this size and reassembled, so large reads need
+/// not reserve their full length up front.
+pub const MAX_RESULT_CAPACITY: usize = 64 * 1024;
+/// Most reply buffers kept for reuse by later driver calls.
+const MAX_POOLED_RESULTS: usize = 16;
+
+thread_local! {
+    static PAYLOAD_ENCODING: Cell<PayloadEncoding> = const { Cell::new(PayloadEncoding::Rkyv) };
+    static RESULT_BUFFERS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
 
 /// Guest pointer type used by Selium driver hooks.
-pub type DriverInt = GuestInt;
+pub type DriverInt = selium_abi::GuestPtr;
+/// Guest buffer length type used by Selium driver hooks.
+pub type DriverSize = GuestSize;
 /// Guest integer type used by Selium driver hooks.
 pub type DriverUint = GuestUint;
 
@@ -54,7 +71,7 @@ pub trait DriverModule {
     /// # Safety
     /// `args_ptr..args_ptr+args_len` must describe a readable byte range in the guest's linear
     /// memory for the duration of this call.
-    unsafe fn create(args_ptr: DriverInt, args_len: DriverUint) -> DriverUint;
+    unsafe fn create(args_ptr: DriverInt, args_len: DriverSize) -> DriverUint;
     /// Poll an existing driver handle.
     ///
     /// # Safety
@@ -65,14 +82,14 @@ pub trait DriverModule {
         handle: DriverUint,
         task_id: DriverUint,
         result_ptr: DriverInt,
-        result_len: DriverUint,
+        result_len: DriverSize,
     ) -> DriverUint;
     /// Drop a driver handle, optionally writing a final result payload.
     ///
     /// # Safety
     /// `result_ptr..result_ptr+result_len` must describe a writable byte range in the guest's
     /// linear memory for the duration of this call.
-    unsafe fn drop(handle: DriverUint, result_ptr: DriverInt, result_len: DriverUint)
+    unsafe fn drop(handle: DriverUint, result_ptr: DriverInt, result_len: DriverSize)
     -> DriverUint;
 }
 
@@ -82,7 +99,8 @@ pub trait DriverDecoder: Unpin {
     fn decode(&mut self, bytes: &[u8]) -> Result<Self::Output, DriverError>;
 }
 
-/// Decoder that deserialises an rkyv payload into the requested type.
+/// Decoder that deserialises a payload, in the instance's negotiated encoding, into the
+/// requested type.
 pub struct RkyvDecoder<T> {
     _marker: PhantomData<T>,
 }
@@ -104,7 +122,7 @@ impl<T> Default for RkyvDecoder<T> {
 
 impl<T> DriverDecoder for RkyvDecoder<T>
 where
-    T: rkyv::Archive + Sized + Unpin,
+    T: rkyv::Archive + JsonPayload + Sized + Unpin,
     for<'a> T::Archived: 'a
         + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
         + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
@@ -112,14 +130,23 @@ where
     type Output = T;
 
     fn decode(&mut self, bytes: &[u8]) -> Result<Self::Output, DriverError> {
-        decode_rkyv_value(bytes)
+        decode_payload(bytes, payload_encoding())
+            .map_err(|err| DriverError::Driver(err.to_string()))
     }
 }
 
 /// Generic error returned by host driver invocations.
 #[derive(Debug, Error)]
 pub enum DriverError {
-    /// The driver returned a structured error string.
+    /// The host reported a failure with a stable error code.
+    #[error("host error ({code}): {message}")]
+    Host {
+        /// Classification of the failure.
+        code: ErrorCode,
+        /// Human-readable description supplied by the host.
+        message: String,
+    },
+    /// Encoding or decoding a driver payload failed in the guest.
     #[error("driver error: {0}")]
     Driver(String),
     /// The kernel returned a numeric error code.
@@ -130,9 +157,22 @@ pub enum DriverError {
     InvalidArgument,
 }
 
+impl DriverError {
+    /// Stable classification of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DriverError::Host { code, .. } => *code,
+            DriverError::Driver(_) => ErrorCode::Unknown,
+            DriverError::Kernel(_) => ErrorCode::Kernel,
+            DriverError::InvalidArgument => ErrorCode::InvalidArgument,
+        }
+    }
+}
+
 impl From<DriverError> for io::Error {
     fn from(value: DriverError) -> Self {
         match value {
+            DriverError::Host { code, message } => io::Error::new(io_error_kind(code), message),
             DriverError::Driver(msg) => io::Error::other(msg),
             DriverError::Kernel(code) => {
                 io::Error::from_raw_os_error(i32::try_from(-(code as i64)).unwrap_or(-1))
@@ -144,19 +184,20 @@ impl From<DriverError> for io::Error {
     }
 }
 
-/// Encode a driver argument value using Selium's rkyv configuration.
-pub fn encode_args<T: RkyvEncode>(value: &T) -> Result<Vec<u8>, DriverError> {
-    encode_rkyv(value).map_err(|err| DriverError::Driver(err.to_string()))
+/// Encode a driver argument value in the instance's negotiated payload encoding.
+pub fn encode_args<T: RkyvEncode + JsonPayload>(value: &T) -> Result<Vec<u8>, DriverError> {
+    encode_payload(value, payload_encoding()).map_err(|err| DriverError::Driver(err.to_string()))
 }
 
-fn decode_rkyv_value<T>(bytes: &[u8]) -> Result<T, DriverError>
-where
-    T: rkyv::Archive + Sized,
-    for<'a> T::Archived: 'a
-        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
-        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
-{
-    decode_rkyv(bytes).map_err(|err| DriverError::Driver(err.to_string()))
+/// Payload encoding of this instance's hostcalls; rkyv until the host agrees to another.
+pub(crate) fn payload_encoding() -> PayloadEncoding {
+    PAYLOAD_ENCODING.with(Cell::get)
+}
+
+/// Switch the encoding of this instance's later hostcalls, once the host has agreed to it.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn set_payload_encoding(encoding: PayloadEncoding) {
+    PAYLOAD_ENCODING.with(|current| current.set(encoding));
 }
 
 struct GuestPtr {
@@ -195,8 +236,8 @@ impl Drop for GuestPtr {
     fn drop(&mut self) {}
 }
 
-fn guest_len(len: usize) -> Result<DriverUint, DriverError> {
-    DriverUint::try_from(len).map_err(|_| DriverError::InvalidArgument)
+fn guest_len(len: usize) -> Result<DriverSize, DriverError> {
+    DriverSize::try_from(len).map_err(|_| DriverError::InvalidArgument)
 }
 
 fn host_len(value: DriverUint) -> Result<usize, DriverError> {
@@ -214,10 +255,26 @@ where
 {
     handle: Option<DriverUint>,
     result: Vec<u8>,
+    chunks: Vec<u8>,
     decoder: D,
+    /// Whether the handle outlives each ready result, as a [`DriverStream`]'s does.
+    stream: bool,
     _marker: PhantomData<M>,
 }
 
+/// Guest-side stream over a streaming driver, yielding each item the host queues until the host
+/// ends the stream.
+///
+/// Each item arrives as an encoded `Some(item)`; the final `None` ends the stream and releases
+/// the kernel handle. Dropping the stream early cancels it on the host.
+pub struct DriverStream<M, D>
+where
+    M: DriverModule,
+    D: DriverDecoder,
+{
+    inner: DriverFuture<M, D>,
+}
+
 impl<M, D> DriverFuture<M, D>
 where
     M: DriverModule,
@@ -225,22 +282,44 @@ where
 {
     /// Create a new future by calling the driver's `create` hook with the supplied arguments.
     ///
-    /// `capacity` describes the expected maximum reply size and is clamped to
-    /// [`MIN_RESULT_CAPACITY`].
+    /// `capacity` describes the expected maximum reply size and is clamped between
+    /// [`MIN_RESULT_CAPACITY`] and [`MAX_RESULT_CAPACITY`].
     pub fn new(args: &[u8], capacity: usize, decoder: D) -> Result<Self, DriverError> {
         let len = guest_len(args.len())?;
         let ptr = GuestPtr::new(args.as_ptr())?;
         let handle = unsafe { M::create(ptr.raw(), len) };
 
-        let cap = capacity.max(MIN_RESULT_CAPACITY);
+        let cap = capacity.clamp(MIN_RESULT_CAPACITY, MAX_RESULT_CAPACITY);
         Ok(Self {
             handle: Some(handle),
-            result: vec![0; cap],
+            result: take_result_buffer(cap),
+            chunks: Vec::new(),
             decoder,
+            stream: false,
             _marker: core::marker::PhantomData,
         })
     }
 
+    /// Give up the kernel handle without dropping it, for a caller taking over its lifetime.
+    pub(crate) fn into_handle(mut self) -> Option<DriverUint> {
+        // The host may write into the reply buffer until the handle is passed on, so the
+        // buffer is not recycled for another call.
+        drop(mem::take(&mut self.result));
+        self.handle.take()
+    }
+
+    /// Drop the kernel handle, if the call still holds one.
+    fn release(&mut self) {
+        if let Some(handle) = self.handle.take()
+            && let (Ok(len), Ok(ptr)) = (
+                guest_len(self.result.len()),
+                GuestPtr::new(self.result.as_mut_ptr()),
+            )
+        {
+            let _ = unsafe { M::drop(handle, ptr.raw(), len) };
+        }
+    }
+
     fn poll_inner(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -259,21 +338,25 @@ where
             Ok(ptr) => ptr,
             Err(err) => return Poll::Ready(Err(err)),
         };
-        let rc = unsafe { M::poll(handle, task_id, ptr.raw(), capacity) };
+        let mut rc = unsafe { M::poll(handle, task_id, ptr.raw(), capacity) };
+        while driver_decode_result(rc) == DriverPollResult::Chunk {
+            let this = &mut *self;
+            this.chunks.extend_from_slice(&this.result);
+            rc = unsafe { M::poll(handle, task_id, ptr.raw(), capacity) };
+        }
 
         match driver_decode_result(rc) {
-            DriverPollResult::Pending => Poll::Pending,
+            DriverPollResult::Pending | DriverPollResult::Chunk => Poll::Pending,
             DriverPollResult::Error(code) => {
                 self.handle = None;
                 if code == DRIVER_ERROR_MESSAGE_CODE {
-                    let msg = decode_driver_error(&self.result);
-                    Poll::Ready(Err(DriverError::Driver(msg)))
+                    Poll::Ready(Err(decode_driver_error(&self.result)))
                 } else {
                     Poll::Ready(Err(DriverError::Kernel(code)))
                 }
             }
             DriverPollResult::Ready(value) => {
-                if value > capacity {
+                if DriverSize::from(value) > capacity {
                     self.handle = None;
                     return Poll::Ready(Err(DriverError::Kernel(value)));
                 }
@@ -290,11 +373,21 @@ where
                     return Poll::Ready(Err(DriverError::InvalidArgument));
                 }
 
-                self.handle = None;
+                if !self.stream {
+                    self.handle = None;
+                }
                 let ptr = self.result.as_ptr();
                 let output = {
                     let bytes = unsafe { slice::from_raw_parts(ptr, used) };
-                    let decoded = self.decoder.decode(bytes);
+                    let this = &mut *self;
+                    let decoded = if this.chunks.is_empty() {
+                        this.decoder.decode(bytes)
+                    } else {
+                        this.chunks.extend_from_slice(bytes);
+                        let decoded = this.decoder.decode(&this.chunks);
+                        this.chunks.clear();
+                        decoded
+                    };
                     if let Err(DriverError::Driver(ref msg)) = decoded {
                         tracing::warn!(
                             "driver decode failed (module={}, used={}): {msg}",
@@ -328,14 +421,8 @@ where
     D: DriverDecoder,
 {
     fn drop(&mut self) {
-        if let Some(handle) = self.handle.take()
-            && let (Ok(len), Ok(ptr)) = (
-                guest_len(self.result.len()),
-                GuestPtr::new(self.result.as_mut_ptr()),
-            )
-        {
-            let _ = unsafe { M::drop(handle, ptr.raw(), len) };
-        }
+        self.release();
+        recycle_result_buffer(mem::take(&mut self.result));
     }
 }
 
@@ -346,8 +433,102 @@ where
 {
 }
 
-fn decode_driver_error(buf: &[u8]) -> String {
-    decode_driver_error_message(buf).unwrap_or_else(|_| "driver error".to_string())
+impl<M, D> DriverStream<M, D>
+where
+    M: DriverModule,
+    D: DriverDecoder,
+{
+    /// Create a new stream by calling the driver's `create` hook with the supplied arguments.
+    ///
+    /// `capacity` describes the expected maximum size of one item, clamped as for
+    /// [`DriverFuture::new`].
+    pub fn new(args: &[u8], capacity: usize, decoder: D) -> Result<Self, DriverError> {
+        let mut inner = DriverFuture::new(args, capacity, decoder)?;
+        inner.stream = true;
+        Ok(Self { inner })
+    }
+}
+
+impl<M, D, T> Stream for DriverStream<M, D>
+where
+    M: DriverModule,
+    D: DriverDecoder<Output = Option<T>>,
+{
+    type Item = Result<T, DriverError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.inner.handle.is_none() {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.inner).poll_inner(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(Some(item))) => Poll::Ready(Some(Ok(item))),
+            Poll::Ready(Ok(None)) => {
+                // The host released the stream along with its final result.
+                self.inner.handle = None;
+                Poll::Ready(None)
+            }
+            Poll::Ready(Err(err)) => {
+                // Errors end the stream; one the guest hit decoding an item leaves the host
+                // still streaming, so cancel it there too.
+                self.inner.release();
+                Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
+/// Borrow a reply buffer of `capacity` bytes, reusing one from an earlier call if possible.
+fn take_result_buffer(capacity: usize) -> Vec<u8> {
+    let mut buffer = RESULT_BUFFERS
+        .with_borrow_mut(|pool| pool.pop())
+        .unwrap_or_default();
+    buffer.resize(capacity, 0);
+    buffer
+}
+
+/// Return a reply buffer the host no longer writes into, for a later call to reuse.
+fn recycle_result_buffer(buffer: Vec<u8>) {
+    if buffer.capacity() == 0 {
+        return;
+    }
+    // The pool may already be gone while the thread shuts down; the buffer is freed then.
+    RESULT_BUFFERS
+        .try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < MAX_POOLED_RESULTS {
+                pool.push(buffer);
+            }
+        })
+        .unwrap_or_default();
+}
+
+fn decode_driver_error(buf: &[u8]) -> DriverError {
+    match selium_abi::decode_driver_error(buf) {
+        Ok(payload) => DriverError::Host {
+            code: payload.error_code(),
+            message: payload.message,
+        },
+        Err(_) => DriverError::Host {
+            code: ErrorCode::Unknown,
+            message: "driver error".to_string(),
+        },
+    }
+}
+
+fn io_error_kind(code: ErrorCode) -> io::ErrorKind {
+    match code {
+        ErrorCode::InvalidArgument | ErrorCode::InvalidUtf8 | ErrorCode::MemorySlice => {
+            io::ErrorKind::InvalidInput
+        }
+        ErrorCode::NotFound => io::ErrorKind::NotFound,
+        ErrorCode::PermissionDenied | ErrorCode::Unauthorised | ErrorCode::EntitlementScope => {
+            io::ErrorKind::PermissionDenied
+        }
+        ErrorCode::AlreadyExists => io::ErrorKind::AlreadyExists,
+        ErrorCode::TimedOut => io::ErrorKind::TimedOut,
+        _ => io::ErrorKind::Other,
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -431,8 +612,8 @@ pub(crate) mod test_driver {
     };
 
     use selium_abi::{
-        DRIVER_RESULT_PENDING, GuestInt, GuestUint, IoFrame, IoRead, IoWrite, decode_rkyv,
-        driver_encode_error, driver_encode_ready, encode_rkyv,
+        DRIVER_RESULT_PENDING, GuestPtr, GuestResourceId, GuestSize, GuestUint, IoFrame, IoRead,
+        IoWrite, decode_rkyv, driver_encode_error, driver_encode_ready, encode_rkyv,
     };
 
     use super::{DriverError, RkyvEncode, host_compat};
@@ -502,7 +683,7 @@ pub(crate) mod test_driver {
         STATE.get_or_init(|| Mutex::new(State::new()))
     }
 
-    fn decode_args(ptr: GuestInt, len: GuestUint) -> Result<&'static [u8], DriverError> {
+    fn decode_args(ptr: GuestPtr, len: GuestSize) -> Result<&'static [u8], DriverError> {
         let len = usize::try_from(len).map_err(|_| DriverError::InvalidArgument)?;
         let ptr = unsafe { host_compat::ptr_from_guest(ptr) };
         if ptr.is_null() {
@@ -527,7 +708,7 @@ pub(crate) mod test_driver {
         START.get_or_init(Instant::now).elapsed().as_millis() as u64
     }
 
-    pub fn create(module: &str, args_ptr: GuestInt, args_len: GuestUint) -> GuestUint {
+    pub fn create(module: &str, args_ptr: GuestPtr, args_len: GuestSize) -> GuestUint {
         let mut guard = match state().lock() {
             Ok(guard) => guard,
             Err(_) => return 0,
@@ -648,6 +829,34 @@ pub(crate) mod test_driver {
                 };
                 guard.insert_op(Operation::Read(read))
             }
+            selium_abi::hostcall_name!(CHANNEL_SHARE) => {
+                let args = match decode_args(args_ptr, args_len) {
+                    Ok(buf) => buf,
+                    Err(_) => return 0,
+                };
+                let channel: ChannelHandle = match decode_rkyv(args) {
+                    Ok(value) => value,
+                    Err(_) => return 0,
+                };
+                match encode(&GuestResourceId::from(channel)) {
+                    Ok(bytes) => guard.insert_op(Operation::Return(bytes)),
+                    Err(_) => 0,
+                }
+            }
+            selium_abi::hostcall_name!(CHANNEL_ATTACH) => {
+                let args = match decode_args(args_ptr, args_len) {
+                    Ok(buf) => buf,
+                    Err(_) => return 0,
+                };
+                let shared: GuestResourceId = match decode_rkyv(args) {
+                    Ok(value) => value,
+                    Err(_) => return 0,
+                };
+                match ChannelHandle::try_from(shared).map(|channel| encode(&channel)) {
+                    Ok(Ok(bytes)) => guard.insert_op(Operation::Return(bytes)),
+                    _ => 0,
+                }
+            }
             selium_abi::hostcall_name!(TIME_NOW) => {
                 let now = selium_abi::TimeNow {
                     unix_ms: unix_ms(),
@@ -666,8 +875,8 @@ pub(crate) mod test_driver {
         _module: &str,
         handle: GuestUint,
         task_id: GuestUint,
-        result_ptr: GuestInt,
-        result_len: GuestUint,
+        result_ptr: GuestPtr,
+        result_len: GuestSize,
     ) -> GuestUint {
         let mut guard = match state().lock() {
             Ok(guard) => guard,
@@ -759,8 +968,8 @@ pub(crate) mod test_driver {
     pub fn drop(
         _module: &str,
         handle: GuestUint,
-        _result_ptr: GuestInt,
-        _result_len: GuestUint,
+        _result_ptr: GuestPtr,
+        _result_len: GuestSize,
     ) -> GuestUint {
         if let Ok(mut guard) = state().lock() {
             guard.operations.remove(&handle);
@@ -771,8 +980,10 @@ pub(crate) mod test_driver {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::task::noop_waker;
-    use selium_abi::{DRIVER_RESULT_PENDING, driver_encode_error, driver_encode_ready};
+    use futures::{StreamExt, task::noop_waker};
+    use selium_abi::{
+        DRIVER_RESULT_CHUNK, DRIVER_RESULT_PENDING, driver_encode_error, driver_encode_ready,
+    };
     use std::{
         pin::Pin,
         sync::atomic::{AtomicU32, Ordering},
@@ -809,7 +1020,7 @@ mod tests {
     struct ReadyModule;
 
     impl DriverModule for ReadyModule {
-        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverUint) -> DriverUint {
+        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverSize) -> DriverUint {
             1
         }
 
@@ -817,7 +1028,7 @@ mod tests {
             _handle: DriverUint,
             _task_id: DriverUint,
             result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
             let payload = b"ok";
             unsafe {
@@ -834,7 +1045,7 @@ mod tests {
         unsafe fn drop(
             _handle: DriverUint,
             _result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
             0
         }
@@ -857,10 +1068,74 @@ mod tests {
         assert_eq!(out, "ok");
     }
 
+    #[test]
+    fn reply_buffers_are_reused_across_calls() {
+        let mut fut = DriverFuture::<ReadyModule, StrDecoder>::new(&[], 512, StrDecoder).unwrap();
+        let addr = fut.result.as_ptr();
+        assert_eq!(run_ready(&mut fut).unwrap(), "ok");
+        drop(fut);
+
+        let fut = DriverFuture::<ReadyModule, StrDecoder>::new(&[], 300, StrDecoder).unwrap();
+        assert_eq!(fut.result.as_ptr(), addr);
+        assert_eq!(fut.result.len(), 300);
+    }
+
+    struct ChunkedModule;
+
+    static CHUNKS_SENT: AtomicU32 = AtomicU32::new(0);
+
+    impl DriverModule for ChunkedModule {
+        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverSize) -> DriverUint {
+            4
+        }
+
+        unsafe fn poll(
+            _handle: DriverUint,
+            _task_id: DriverUint,
+            result_ptr: DriverInt,
+            result_len: DriverSize,
+        ) -> DriverUint {
+            let payload = if CHUNKS_SENT.fetch_add(1, Ordering::SeqCst) < 2 {
+                vec![b'a'; usize::try_from(result_len).unwrap()]
+            } else {
+                b"ok".to_vec()
+            };
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    payload.as_ptr(),
+                    test_ptr_mut(result_ptr),
+                    payload.len(),
+                );
+            }
+            if payload.len() == usize::try_from(result_len).unwrap() {
+                return DRIVER_RESULT_CHUNK;
+            }
+            let len = DriverUint::try_from(payload.len()).unwrap();
+            driver_encode_ready(len).expect("payload length fits")
+        }
+
+        unsafe fn drop(
+            _handle: DriverUint,
+            _result_ptr: DriverInt,
+            _result_len: DriverSize,
+        ) -> DriverUint {
+            0
+        }
+    }
+
+    #[test]
+    fn driver_future_reassembles_chunked_replies() {
+        let fut = DriverFuture::<ChunkedModule, StrDecoder>::new(&[], 4, StrDecoder).unwrap();
+        let out = run_ready(fut).unwrap();
+        assert_eq!(out.len(), 2 * MIN_RESULT_CAPACITY + 2);
+        assert!(out.ends_with("aok"));
+        assert_eq!(CHUNKS_SENT.load(Ordering::SeqCst), 3);
+    }
+
     struct DriverErrorModule;
 
     impl DriverModule for DriverErrorModule {
-        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverUint) -> DriverUint {
+        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverSize) -> DriverUint {
             2
         }
 
@@ -868,9 +1143,10 @@ mod tests {
             _handle: DriverUint,
             _task_id: DriverUint,
             result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
-            let encoded = selium_abi::encode_driver_error_message("boom").expect("encode");
+            let encoded =
+                selium_abi::encode_driver_error(ErrorCode::NotFound, "boom").expect("encode");
             unsafe {
                 core::ptr::copy_nonoverlapping(
                     encoded.as_ptr(),
@@ -884,7 +1160,7 @@ mod tests {
         unsafe fn drop(
             _handle: DriverUint,
             _result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
             0
         }
@@ -906,7 +1182,10 @@ mod tests {
             DriverFuture::<DriverErrorModule, UnitDecoder>::new(&[], 32, UnitDecoder).unwrap();
         let err = run_ready(fut).unwrap_err();
         match err {
-            DriverError::Driver(msg) => assert_eq!(msg, "boom"),
+            DriverError::Host { code, message } => {
+                assert_eq!(code, ErrorCode::NotFound);
+                assert_eq!(message, "boom");
+            }
             other => panic!("unexpected error: {other:?}"),
         }
     }
@@ -916,7 +1195,7 @@ mod tests {
     static DROPS: AtomicU32 = AtomicU32::new(0);
 
     impl DriverModule for PendingModule {
-        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverUint) -> DriverUint {
+        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverSize) -> DriverUint {
             3
         }
 
@@ -924,7 +1203,7 @@ mod tests {
             _handle: DriverUint,
             _task_id: DriverUint,
             _result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
             DRIVER_RESULT_PENDING
         }
@@ -932,13 +1211,78 @@ mod tests {
         unsafe fn drop(
             _handle: DriverUint,
             _result_ptr: DriverInt,
-            _result_len: DriverUint,
+            _result_len: DriverSize,
         ) -> DriverUint {
             DROPS.fetch_add(1, Ordering::SeqCst);
             0
         }
     }
 
+    struct StreamModule;
+
+    static ITEMS_SENT: AtomicU32 = AtomicU32::new(0);
+    static STREAM_DROPS: AtomicU32 = AtomicU32::new(0);
+
+    impl DriverModule for StreamModule {
+        unsafe fn create(_args_ptr: DriverInt, _args_len: DriverSize) -> DriverUint {
+            5
+        }
+
+        unsafe fn poll(
+            _handle: DriverUint,
+            _task_id: DriverUint,
+            result_ptr: DriverInt,
+            _result_len: DriverSize,
+        ) -> DriverUint {
+            let payload: &[u8] = match ITEMS_SENT.fetch_add(1, Ordering::SeqCst) {
+                0 => b"a",
+                1 => b"b",
+                _ => b"",
+            };
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    payload.as_ptr(),
+                    test_ptr_mut(result_ptr),
+                    payload.len(),
+                );
+            }
+            let len = DriverUint::try_from(payload.len()).unwrap();
+            driver_encode_ready(len).expect("payload length fits")
+        }
+
+        unsafe fn drop(
+            _handle: DriverUint,
+            _result_ptr: DriverInt,
+            _result_len: DriverSize,
+        ) -> DriverUint {
+            STREAM_DROPS.fetch_add(1, Ordering::SeqCst);
+            0
+        }
+    }
+
+    /// Decodes an empty reply as the end of the stream.
+    struct ItemDecoder;
+
+    impl DriverDecoder for ItemDecoder {
+        type Output = Option<String>;
+
+        fn decode(&mut self, bytes: &[u8]) -> Result<Self::Output, DriverError> {
+            Ok((!bytes.is_empty()).then(|| std::str::from_utf8(bytes).unwrap().to_string()))
+        }
+    }
+
+    #[test]
+    fn driver_stream_yields_items_until_the_host_ends_it() {
+        let mut stream =
+            DriverStream::<StreamModule, ItemDecoder>::new(&[], 4, ItemDecoder).unwrap();
+        let items: Vec<String> = run_ready(stream.by_ref().map(Result::unwrap).collect());
+        assert_eq!(items, ["a", "b"]);
+        assert!(run_ready(stream.next()).is_none());
+        drop(stream);
+        // The host released the handle with the end of the stream.
+        assert_eq!(STREAM_DROPS.load(Ordering::SeqCst), 0);
+    }
+
     #[test]
     fn driver_future_drops_pending_handle() {
         let mut fut = DriverFuture::<PendingModule, UnitDecoder>::new(&[], 4, UnitDecoder).unwrap();
@@ -2,6 +2,75 @@
 // Combined module tree for Selium Flatbuffers namespaces.
 pub mod selium {
   use super::*;
+  pub mod control {
+    use super::*;
+    mod advance_clock_generated;
+    pub use self::advance_clock_generated::*;
+    mod clock_time_generated;
+    pub use self::clock_time_generated::*;
+    mod control_command_generated;
+    pub use self::control_command_generated::*;
+    mod control_reply_generated;
+    pub use self::control_reply_generated::*;
+    mod control_request_generated;
+    pub use self::control_request_generated::*;
+    mod control_response_generated;
+    pub use self::control_response_generated::*;
+    mod diagnose_generated;
+    pub use self::diagnose_generated::*;
+    mod done_generated;
+    pub use self::done_generated::*;
+    mod error_count_generated;
+    pub use self::error_count_generated::*;
+    mod failure_generated;
+    pub use self::failure_generated::*;
+    mod future_diagnostics_generated;
+    pub use self::future_diagnostics_generated::*;
+    mod future_state_generated;
+    pub use self::future_state_generated::*;
+    mod guest_counter_generated;
+    pub use self::guest_counter_generated::*;
+    mod guest_gauge_generated;
+    pub use self::guest_gauge_generated::*;
+    mod hostcall_stats_generated;
+    pub use self::hostcall_stats_generated::*;
+    mod instance_diagnostics_generated;
+    pub use self::instance_diagnostics_generated::*;
+    mod list_modules_generated;
+    pub use self::list_modules_generated::*;
+    mod mailbox_diagnostics_generated;
+    pub use self::mailbox_diagnostics_generated::*;
+    mod metrics_generated;
+    pub use self::metrics_generated::*;
+    mod metrics_report_generated;
+    pub use self::metrics_report_generated::*;
+    mod module_cache_stats_generated;
+    pub use self::module_cache_stats_generated::*;
+    mod module_list_generated;
+    pub use self::module_list_generated::*;
+    mod module_state_generated;
+    pub use self::module_state_generated::*;
+    mod module_status_generated;
+    pub use self::module_status_generated::*;
+    mod profile_generated;
+    pub use self::profile_generated::*;
+    mod profile_written_generated;
+    pub use self::profile_written_generated::*;
+    mod reload_module_generated;
+    pub use self::reload_module_generated::*;
+    mod restart_module_generated;
+    pub use self::restart_module_generated::*;
+    mod shutdown_generated;
+    pub use self::shutdown_generated::*;
+    mod slot_usage_generated;
+    pub use self::slot_usage_generated::*;
+    mod start_module_generated;
+    pub use self::start_module_generated::*;
+    mod started_generated;
+    pub use self::started_generated::*;
+    mod stop_module_generated;
+    pub use self::stop_module_generated::*;
+  }
   pub mod logging {
     use super::*;
     mod field_generated;
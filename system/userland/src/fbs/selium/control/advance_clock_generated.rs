@@ -0,0 +1,100 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum AdvanceClockOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct AdvanceClock<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for AdvanceClock<'a> {
+  type Inner = AdvanceClock<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> AdvanceClock<'a> {
+  pub const VT_MILLIS: ::flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    AdvanceClock { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args AdvanceClockArgs
+  ) -> ::flatbuffers::WIPOffset<AdvanceClock<'bldr>> {
+    let mut builder = AdvanceClockBuilder::new(_fbb);
+    builder.add_millis(args.millis);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn millis(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(AdvanceClock::VT_MILLIS, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for AdvanceClock<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("millis", Self::VT_MILLIS, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AdvanceClockArgs {
+    pub millis: u64,
+}
+impl<'a> Default for AdvanceClockArgs {
+  #[inline]
+  fn default() -> Self {
+    AdvanceClockArgs {
+      millis: 0,
+    }
+  }
+}
+
+pub struct AdvanceClockBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> AdvanceClockBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_millis(&mut self, millis: u64) {
+    self.fbb_.push_slot::<u64>(AdvanceClock::VT_MILLIS, millis, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> AdvanceClockBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    AdvanceClockBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<AdvanceClock<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for AdvanceClock<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("AdvanceClock");
+      ds.field("millis", &self.millis());
+      ds.finish()
+  }
+}
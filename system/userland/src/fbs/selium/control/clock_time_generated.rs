@@ -0,0 +1,117 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ClockTimeOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ClockTime<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ClockTime<'a> {
+  type Inner = ClockTime<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ClockTime<'a> {
+  pub const VT_UNIX_MS: ::flatbuffers::VOffsetT = 4;
+  pub const VT_MONOTONIC_MS: ::flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ClockTime { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ClockTimeArgs
+  ) -> ::flatbuffers::WIPOffset<ClockTime<'bldr>> {
+    let mut builder = ClockTimeBuilder::new(_fbb);
+    builder.add_monotonic_ms(args.monotonic_ms);
+    builder.add_unix_ms(args.unix_ms);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn unix_ms(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ClockTime::VT_UNIX_MS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn monotonic_ms(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ClockTime::VT_MONOTONIC_MS, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for ClockTime<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("unix_ms", Self::VT_UNIX_MS, false)?
+     .visit_field::<u64>("monotonic_ms", Self::VT_MONOTONIC_MS, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ClockTimeArgs {
+    pub unix_ms: u64,
+    pub monotonic_ms: u64,
+}
+impl<'a> Default for ClockTimeArgs {
+  #[inline]
+  fn default() -> Self {
+    ClockTimeArgs {
+      unix_ms: 0,
+      monotonic_ms: 0,
+    }
+  }
+}
+
+pub struct ClockTimeBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ClockTimeBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_unix_ms(&mut self, unix_ms: u64) {
+    self.fbb_.push_slot::<u64>(ClockTime::VT_UNIX_MS, unix_ms, 0);
+  }
+  #[inline]
+  pub fn add_monotonic_ms(&mut self, monotonic_ms: u64) {
+    self.fbb_.push_slot::<u64>(ClockTime::VT_MONOTONIC_MS, monotonic_ms, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ClockTimeBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ClockTimeBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ClockTime<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ClockTime<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ClockTime");
+      ds.field("unix_ms", &self.unix_ms());
+      ds.field("monotonic_ms", &self.monotonic_ms());
+      ds.finish()
+  }
+}
@@ -0,0 +1,126 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MIN_CONTROL_COMMAND: u8 = 0;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MAX_CONTROL_COMMAND: u8 = 10;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+#[allow(non_camel_case_types)]
+pub const ENUM_VALUES_CONTROL_COMMAND: [ControlCommand; 11] = [
+  ControlCommand::NONE,
+  ControlCommand::ListModules,
+  ControlCommand::StartModule,
+  ControlCommand::StopModule,
+  ControlCommand::ReloadModule,
+  ControlCommand::Shutdown,
+  ControlCommand::RestartModule,
+  ControlCommand::AdvanceClock,
+  ControlCommand::Metrics,
+  ControlCommand::Diagnose,
+  ControlCommand::Profile,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct ControlCommand(pub u8);
+#[allow(non_upper_case_globals)]
+impl ControlCommand {
+  pub const NONE: Self = Self(0);
+  pub const ListModules: Self = Self(1);
+  pub const StartModule: Self = Self(2);
+  pub const StopModule: Self = Self(3);
+  pub const ReloadModule: Self = Self(4);
+  pub const Shutdown: Self = Self(5);
+  pub const RestartModule: Self = Self(6);
+  pub const AdvanceClock: Self = Self(7);
+  pub const Metrics: Self = Self(8);
+  pub const Diagnose: Self = Self(9);
+  pub const Profile: Self = Self(10);
+
+  pub const ENUM_MIN: u8 = 0;
+  pub const ENUM_MAX: u8 = 10;
+  pub const ENUM_VALUES: &'static [Self] = &[
+    Self::NONE,
+    Self::ListModules,
+    Self::StartModule,
+    Self::StopModule,
+    Self::ReloadModule,
+    Self::Shutdown,
+    Self::RestartModule,
+    Self::AdvanceClock,
+    Self::Metrics,
+    Self::Diagnose,
+    Self::Profile,
+  ];
+  /// Returns the variant's name or "" if unknown.
+  pub fn variant_name(self) -> Option<&'static str> {
+    match self {
+      Self::NONE => Some("NONE"),
+      Self::ListModules => Some("ListModules"),
+      Self::StartModule => Some("StartModule"),
+      Self::StopModule => Some("StopModule"),
+      Self::ReloadModule => Some("ReloadModule"),
+      Self::Shutdown => Some("Shutdown"),
+      Self::RestartModule => Some("RestartModule"),
+      Self::AdvanceClock => Some("AdvanceClock"),
+      Self::Metrics => Some("Metrics"),
+      Self::Diagnose => Some("Diagnose"),
+      Self::Profile => Some("Profile"),
+      _ => None,
+    }
+  }
+}
+impl ::core::fmt::Debug for ControlCommand {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+    if let Some(name) = self.variant_name() {
+      f.write_str(name)
+    } else {
+      f.write_fmt(format_args!("<UNKNOWN {:?}>", self.0))
+    }
+  }
+}
+impl<'a> ::flatbuffers::Follow<'a> for ControlCommand {
+  type Inner = Self;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    let b = unsafe { ::flatbuffers::read_scalar_at::<u8>(buf, loc) };
+    Self(b)
+  }
+}
+
+impl ::flatbuffers::Push for ControlCommand {
+    type Output = ControlCommand;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        unsafe { ::flatbuffers::emplace_scalar::<u8>(dst, self.0) };
+    }
+}
+
+impl ::flatbuffers::EndianScalar for ControlCommand {
+  type Scalar = u8;
+  #[inline]
+  fn to_little_endian(self) -> u8 {
+    self.0.to_le()
+  }
+  #[inline]
+  #[allow(clippy::wrong_self_convention)]
+  fn from_little_endian(v: u8) -> Self {
+    let b = u8::from_le(v);
+    Self(b)
+  }
+}
+
+impl<'a> ::flatbuffers::Verifiable for ControlCommand {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    u8::run_verifier(v, pos)
+  }
+}
+
+impl ::flatbuffers::SimpleToVerifyInSlice for ControlCommand {}
+pub struct ControlCommandUnionTableOffset {}
+
@@ -0,0 +1,118 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MIN_CONTROL_REPLY: u8 = 0;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+pub const ENUM_MAX_CONTROL_REPLY: u8 = 8;
+#[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
+#[allow(non_camel_case_types)]
+pub const ENUM_VALUES_CONTROL_REPLY: [ControlReply; 9] = [
+  ControlReply::NONE,
+  ControlReply::ModuleList,
+  ControlReply::Started,
+  ControlReply::Done,
+  ControlReply::Failure,
+  ControlReply::ClockTime,
+  ControlReply::MetricsReport,
+  ControlReply::InstanceDiagnostics,
+  ControlReply::ProfileWritten,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct ControlReply(pub u8);
+#[allow(non_upper_case_globals)]
+impl ControlReply {
+  pub const NONE: Self = Self(0);
+  pub const ModuleList: Self = Self(1);
+  pub const Started: Self = Self(2);
+  pub const Done: Self = Self(3);
+  pub const Failure: Self = Self(4);
+  pub const ClockTime: Self = Self(5);
+  pub const MetricsReport: Self = Self(6);
+  pub const InstanceDiagnostics: Self = Self(7);
+  pub const ProfileWritten: Self = Self(8);
+
+  pub const ENUM_MIN: u8 = 0;
+  pub const ENUM_MAX: u8 = 8;
+  pub const ENUM_VALUES: &'static [Self] = &[
+    Self::NONE,
+    Self::ModuleList,
+    Self::Started,
+    Self::Done,
+    Self::Failure,
+    Self::ClockTime,
+    Self::MetricsReport,
+    Self::InstanceDiagnostics,
+    Self::ProfileWritten,
+  ];
+  /// Returns the variant's name or "" if unknown.
+  pub fn variant_name(self) -> Option<&'static str> {
+    match self {
+      Self::NONE => Some("NONE"),
+      Self::ModuleList => Some("ModuleList"),
+      Self::Started => Some("Started"),
+      Self::Done => Some("Done"),
+      Self::Failure => Some("Failure"),
+      Self::ClockTime => Some("ClockTime"),
+      Self::MetricsReport => Some("MetricsReport"),
+      Self::InstanceDiagnostics => Some("InstanceDiagnostics"),
+      Self::ProfileWritten => Some("ProfileWritten"),
+      _ => None,
+    }
+  }
+}
+impl ::core::fmt::Debug for ControlReply {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+    if let Some(name) = self.variant_name() {
+      f.write_str(name)
+    } else {
+      f.write_fmt(format_args!("<UNKNOWN {:?}>", self.0))
+    }
+  }
+}
+impl<'a> ::flatbuffers::Follow<'a> for ControlReply {
+  type Inner = Self;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    let b = unsafe { ::flatbuffers::read_scalar_at::<u8>(buf, loc) };
+    Self(b)
+  }
+}
+
+impl ::flatbuffers::Push for ControlReply {
+    type Output = ControlReply;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        unsafe { ::flatbuffers::emplace_scalar::<u8>(dst, self.0) };
+    }
+}
+
+impl ::flatbuffers::EndianScalar for ControlReply {
+  type Scalar = u8;
+  #[inline]
+  fn to_little_endian(self) -> u8 {
+    self.0.to_le()
+  }
+  #[inline]
+  #[allow(clippy::wrong_self_convention)]
+  fn from_little_endian(v: u8) -> Self {
+    let b = u8::from_le(v);
+    Self(b)
+  }
+}
+
+impl<'a> ::flatbuffers::Verifiable for ControlReply {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    u8::run_verifier(v, pos)
+  }
+}
+
+impl ::flatbuffers::SimpleToVerifyInSlice for ControlReply {}
+pub struct ControlReplyUnionTableOffset {}
+
@@ -0,0 +1,455 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ControlRequestOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ControlRequest<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ControlRequest<'a> {
+  type Inner = ControlRequest<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ControlRequest<'a> {
+  pub const VT_COMMAND_TYPE: ::flatbuffers::VOffsetT = 4;
+  pub const VT_COMMAND: ::flatbuffers::VOffsetT = 6;
+  pub const VT_TENANT: ::flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ControlRequest { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ControlRequestArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<ControlRequest<'bldr>> {
+    let mut builder = ControlRequestBuilder::new(_fbb);
+    if let Some(x) = args.tenant { builder.add_tenant(x); }
+    if let Some(x) = args.command { builder.add_command(x); }
+    builder.add_command_type(args.command_type);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn command_type(&self) -> ControlCommand {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<ControlCommand>(ControlRequest::VT_COMMAND_TYPE, Some(ControlCommand::NONE)).unwrap()}
+  }
+  #[inline]
+  pub fn command(&self) -> Option<::flatbuffers::Table<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Table<'a>>>(ControlRequest::VT_COMMAND, None)}
+  }
+  #[inline]
+  pub fn tenant(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(ControlRequest::VT_TENANT, None)}
+  }
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_list_modules(&self) -> Option<ListModules<'a>> {
+    if self.command_type() == ControlCommand::ListModules {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { ListModules::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_start_module(&self) -> Option<StartModule<'a>> {
+    if self.command_type() == ControlCommand::StartModule {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { StartModule::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_stop_module(&self) -> Option<StopModule<'a>> {
+    if self.command_type() == ControlCommand::StopModule {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { StopModule::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_reload_module(&self) -> Option<ReloadModule<'a>> {
+    if self.command_type() == ControlCommand::ReloadModule {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { ReloadModule::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_shutdown(&self) -> Option<Shutdown<'a>> {
+    if self.command_type() == ControlCommand::Shutdown {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Shutdown::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_restart_module(&self) -> Option<RestartModule<'a>> {
+    if self.command_type() == ControlCommand::RestartModule {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { RestartModule::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_advance_clock(&self) -> Option<AdvanceClock<'a>> {
+    if self.command_type() == ControlCommand::AdvanceClock {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { AdvanceClock::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_metrics(&self) -> Option<Metrics<'a>> {
+    if self.command_type() == ControlCommand::Metrics {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Metrics::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_diagnose(&self) -> Option<Diagnose<'a>> {
+    if self.command_type() == ControlCommand::Diagnose {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Diagnose::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn command_as_profile(&self) -> Option<Profile<'a>> {
+    if self.command_type() == ControlCommand::Profile {
+      self.command().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Profile::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+}
+
+impl ::flatbuffers::Verifiable for ControlRequest<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_union::<ControlCommand, _>("command_type", Self::VT_COMMAND_TYPE, "command", Self::VT_COMMAND, false, |key, v, pos| {
+        match key {
+          ControlCommand::ListModules => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<ListModules>>("ControlCommand::ListModules", pos),
+          ControlCommand::StartModule => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<StartModule>>("ControlCommand::StartModule", pos),
+          ControlCommand::StopModule => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<StopModule>>("ControlCommand::StopModule", pos),
+          ControlCommand::ReloadModule => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<ReloadModule>>("ControlCommand::ReloadModule", pos),
+          ControlCommand::Shutdown => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Shutdown>>("ControlCommand::Shutdown", pos),
+          ControlCommand::RestartModule => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<RestartModule>>("ControlCommand::RestartModule", pos),
+          ControlCommand::AdvanceClock => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<AdvanceClock>>("ControlCommand::AdvanceClock", pos),
+          ControlCommand::Metrics => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Metrics>>("ControlCommand::Metrics", pos),
+          ControlCommand::Diagnose => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Diagnose>>("ControlCommand::Diagnose", pos),
+          ControlCommand::Profile => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Profile>>("ControlCommand::Profile", pos),
+          _ => Ok(()),
+        }
+     })?
+     .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("tenant", Self::VT_TENANT, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ControlRequestArgs<'a> {
+    pub command_type: ControlCommand,
+    pub command: Option<::flatbuffers::WIPOffset<::flatbuffers::UnionWIPOffset>>,
+    pub tenant: Option<::flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for ControlRequestArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ControlRequestArgs {
+      command_type: ControlCommand::NONE,
+      command: None,
+      tenant: None,
+    }
+  }
+}
+
+pub struct ControlRequestBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ControlRequestBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_command_type(&mut self, command_type: ControlCommand) {
+    self.fbb_.push_slot::<ControlCommand>(ControlRequest::VT_COMMAND_TYPE, command_type, ControlCommand::NONE);
+  }
+  #[inline]
+  pub fn add_command(&mut self, command: ::flatbuffers::WIPOffset<::flatbuffers::UnionWIPOffset>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ControlRequest::VT_COMMAND, command);
+  }
+  #[inline]
+  pub fn add_tenant(&mut self, tenant: ::flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ControlRequest::VT_TENANT, tenant);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ControlRequestBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ControlRequestBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ControlRequest<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ControlRequest<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ControlRequest");
+      ds.field("command_type", &self.command_type());
+      match self.command_type() {
+        ControlCommand::ListModules => {
+          if let Some(x) = self.command_as_list_modules() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::StartModule => {
+          if let Some(x) = self.command_as_start_module() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::StopModule => {
+          if let Some(x) = self.command_as_stop_module() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::ReloadModule => {
+          if let Some(x) = self.command_as_reload_module() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::Shutdown => {
+          if let Some(x) = self.command_as_shutdown() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::RestartModule => {
+          if let Some(x) = self.command_as_restart_module() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::AdvanceClock => {
+          if let Some(x) = self.command_as_advance_clock() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::Metrics => {
+          if let Some(x) = self.command_as_metrics() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::Diagnose => {
+          if let Some(x) = self.command_as_diagnose() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlCommand::Profile => {
+          if let Some(x) = self.command_as_profile() {
+            ds.field("command", &x)
+          } else {
+            ds.field("command", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        _ => {
+          let x: Option<()> = None;
+          ds.field("command", &x)
+        },
+      };
+      ds.field("tenant", &self.tenant());
+      ds.finish()
+  }
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a `ControlRequest`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_control_request_unchecked`.
+pub fn root_as_control_request(buf: &[u8]) -> Result<ControlRequest<'_>, ::flatbuffers::InvalidFlatbuffer> {
+  ::flatbuffers::root::<ControlRequest>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `ControlRequest` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_control_request_unchecked`.
+pub fn size_prefixed_root_as_control_request(buf: &[u8]) -> Result<ControlRequest<'_>, ::flatbuffers::InvalidFlatbuffer> {
+  ::flatbuffers::size_prefixed_root::<ControlRequest>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `ControlRequest` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_control_request_unchecked`.
+pub fn root_as_control_request_with_opts<'b, 'o>(
+  opts: &'o ::flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<ControlRequest<'b>, ::flatbuffers::InvalidFlatbuffer> {
+  ::flatbuffers::root_with_opts::<ControlRequest<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `ControlRequest` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_control_request_unchecked`.
+pub fn size_prefixed_root_as_control_request_with_opts<'b, 'o>(
+  opts: &'o ::flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<ControlRequest<'b>, ::flatbuffers::InvalidFlatbuffer> {
+  ::flatbuffers::size_prefixed_root_with_opts::<ControlRequest<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a ControlRequest and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `ControlRequest`.
+pub unsafe fn root_as_control_request_unchecked(buf: &[u8]) -> ControlRequest<'_> {
+  unsafe { ::flatbuffers::root_unchecked::<ControlRequest>(buf) }
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed ControlRequest and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `ControlRequest`.
+pub unsafe fn size_prefixed_root_as_control_request_unchecked(buf: &[u8]) -> ControlRequest<'_> {
+  unsafe { ::flatbuffers::size_prefixed_root_unchecked::<ControlRequest>(buf) }
+}
+pub const CONTROL_REQUEST_IDENTIFIER: &str = "SCTL";
+
+#[inline]
+pub fn control_request_buffer_has_identifier(buf: &[u8]) -> bool {
+  ::flatbuffers::buffer_has_identifier(buf, CONTROL_REQUEST_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn control_request_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+  ::flatbuffers::buffer_has_identifier(buf, CONTROL_REQUEST_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_control_request_buffer<'a, 'b, A: ::flatbuffers::Allocator + 'a>(
+    fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+    root: ::flatbuffers::WIPOffset<ControlRequest<'a>>) {
+  fbb.finish(root, Some(CONTROL_REQUEST_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_control_request_buffer<'a, 'b, A: ::flatbuffers::Allocator + 'a>(fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>, root: ::flatbuffers::WIPOffset<ControlRequest<'a>>) {
+  fbb.finish_size_prefixed(root, Some(CONTROL_REQUEST_IDENTIFIER));
+}
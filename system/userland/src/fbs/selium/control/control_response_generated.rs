@@ -0,0 +1,309 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ControlResponseOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ControlResponse<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ControlResponse<'a> {
+  type Inner = ControlResponse<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ControlResponse<'a> {
+  pub const VT_REPLY_TYPE: ::flatbuffers::VOffsetT = 4;
+  pub const VT_REPLY: ::flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ControlResponse { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ControlResponseArgs
+  ) -> ::flatbuffers::WIPOffset<ControlResponse<'bldr>> {
+    let mut builder = ControlResponseBuilder::new(_fbb);
+    if let Some(x) = args.reply { builder.add_reply(x); }
+    builder.add_reply_type(args.reply_type);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn reply_type(&self) -> ControlReply {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<ControlReply>(ControlResponse::VT_REPLY_TYPE, Some(ControlReply::NONE)).unwrap()}
+  }
+  #[inline]
+  pub fn reply(&self) -> Option<::flatbuffers::Table<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Table<'a>>>(ControlResponse::VT_REPLY, None)}
+  }
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_module_list(&self) -> Option<ModuleList<'a>> {
+    if self.reply_type() == ControlReply::ModuleList {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { ModuleList::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_started(&self) -> Option<Started<'a>> {
+    if self.reply_type() == ControlReply::Started {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Started::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_done(&self) -> Option<Done<'a>> {
+    if self.reply_type() == ControlReply::Done {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Done::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_failure(&self) -> Option<Failure<'a>> {
+    if self.reply_type() == ControlReply::Failure {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { Failure::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_clock_time(&self) -> Option<ClockTime<'a>> {
+    if self.reply_type() == ControlReply::ClockTime {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { ClockTime::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_metrics_report(&self) -> Option<MetricsReport<'a>> {
+    if self.reply_type() == ControlReply::MetricsReport {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { MetricsReport::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_instance_diagnostics(&self) -> Option<InstanceDiagnostics<'a>> {
+    if self.reply_type() == ControlReply::InstanceDiagnostics {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { InstanceDiagnostics::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn reply_as_profile_written(&self) -> Option<ProfileWritten<'a>> {
+    if self.reply_type() == ControlReply::ProfileWritten {
+      self.reply().map(|t| {
+       // Safety:
+       // Created from a valid Table for this object
+       // Which contains a valid union in this slot
+       unsafe { ProfileWritten::init_from_table(t) }
+     })
+    } else {
+      None
+    }
+  }
+
+}
+
+impl ::flatbuffers::Verifiable for ControlResponse<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_union::<ControlReply, _>("reply_type", Self::VT_REPLY_TYPE, "reply", Self::VT_REPLY, false, |key, v, pos| {
+        match key {
+          ControlReply::ModuleList => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<ModuleList>>("ControlReply::ModuleList", pos),
+          ControlReply::Started => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Started>>("ControlReply::Started", pos),
+          ControlReply::Done => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Done>>("ControlReply::Done", pos),
+          ControlReply::Failure => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<Failure>>("ControlReply::Failure", pos),
+          ControlReply::ClockTime => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<ClockTime>>("ControlReply::ClockTime", pos),
+          ControlReply::MetricsReport => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<MetricsReport>>("ControlReply::MetricsReport", pos),
+          ControlReply::InstanceDiagnostics => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<InstanceDiagnostics>>("ControlReply::InstanceDiagnostics", pos),
+          ControlReply::ProfileWritten => v.verify_union_variant::<::flatbuffers::ForwardsUOffset<ProfileWritten>>("ControlReply::ProfileWritten", pos),
+          _ => Ok(()),
+        }
+     })?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ControlResponseArgs {
+    pub reply_type: ControlReply,
+    pub reply: Option<::flatbuffers::WIPOffset<::flatbuffers::UnionWIPOffset>>,
+}
+impl<'a> Default for ControlResponseArgs {
+  #[inline]
+  fn default() -> Self {
+    ControlResponseArgs {
+      reply_type: ControlReply::NONE,
+      reply: None,
+    }
+  }
+}
+
+pub struct ControlResponseBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ControlResponseBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_reply_type(&mut self, reply_type: ControlReply) {
+    self.fbb_.push_slot::<ControlReply>(ControlResponse::VT_REPLY_TYPE, reply_type, ControlReply::NONE);
+  }
+  #[inline]
+  pub fn add_reply(&mut self, reply: ::flatbuffers::WIPOffset<::flatbuffers::UnionWIPOffset>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ControlResponse::VT_REPLY, reply);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ControlResponseBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ControlResponseBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ControlResponse<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ControlResponse<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ControlResponse");
+      ds.field("reply_type", &self.reply_type());
+      match self.reply_type() {
+        ControlReply::ModuleList => {
+          if let Some(x) = self.reply_as_module_list() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::Started => {
+          if let Some(x) = self.reply_as_started() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::Done => {
+          if let Some(x) = self.reply_as_done() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::Failure => {
+          if let Some(x) = self.reply_as_failure() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::ClockTime => {
+          if let Some(x) = self.reply_as_clock_time() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::MetricsReport => {
+          if let Some(x) = self.reply_as_metrics_report() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::InstanceDiagnostics => {
+          if let Some(x) = self.reply_as_instance_diagnostics() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ControlReply::ProfileWritten => {
+          if let Some(x) = self.reply_as_profile_written() {
+            ds.field("reply", &x)
+          } else {
+            ds.field("reply", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        _ => {
+          let x: Option<()> = None;
+          ds.field("reply", &x)
+        },
+      };
+      ds.finish()
+  }
+}
@@ -0,0 +1,151 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum FutureDiagnosticsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct FutureDiagnostics<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for FutureDiagnostics<'a> {
+  type Inner = FutureDiagnostics<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> FutureDiagnostics<'a> {
+  pub const VT_HANDLE: ::flatbuffers::VOffsetT = 4;
+  pub const VT_STATE: ::flatbuffers::VOffsetT = 6;
+  pub const VT_QUEUED: ::flatbuffers::VOffsetT = 8;
+  pub const VT_WAITING: ::flatbuffers::VOffsetT = 10;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    FutureDiagnostics { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args FutureDiagnosticsArgs
+  ) -> ::flatbuffers::WIPOffset<FutureDiagnostics<'bldr>> {
+    let mut builder = FutureDiagnosticsBuilder::new(_fbb);
+    builder.add_queued(args.queued);
+    builder.add_handle(args.handle);
+    builder.add_waiting(args.waiting);
+    builder.add_state(args.state);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn handle(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(FutureDiagnostics::VT_HANDLE, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn state(&self) -> FutureState {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<FutureState>(FutureDiagnostics::VT_STATE, Some(FutureState::Pending)).unwrap()}
+  }
+  #[inline]
+  pub fn queued(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(FutureDiagnostics::VT_QUEUED, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn waiting(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(FutureDiagnostics::VT_WAITING, Some(false)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for FutureDiagnostics<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u32>("handle", Self::VT_HANDLE, false)?
+     .visit_field::<FutureState>("state", Self::VT_STATE, false)?
+     .visit_field::<u32>("queued", Self::VT_QUEUED, false)?
+     .visit_field::<bool>("waiting", Self::VT_WAITING, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct FutureDiagnosticsArgs {
+    pub handle: u32,
+    pub state: FutureState,
+    pub queued: u32,
+    pub waiting: bool,
+}
+impl<'a> Default for FutureDiagnosticsArgs {
+  #[inline]
+  fn default() -> Self {
+    FutureDiagnosticsArgs {
+      handle: 0,
+      state: FutureState::Pending,
+      queued: 0,
+      waiting: false,
+    }
+  }
+}
+
+pub struct FutureDiagnosticsBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> FutureDiagnosticsBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_handle(&mut self, handle: u32) {
+    self.fbb_.push_slot::<u32>(FutureDiagnostics::VT_HANDLE, handle, 0);
+  }
+  #[inline]
+  pub fn add_state(&mut self, state: FutureState) {
+    self.fbb_.push_slot::<FutureState>(FutureDiagnostics::VT_STATE, state, FutureState::Pending);
+  }
+  #[inline]
+  pub fn add_queued(&mut self, queued: u32) {
+    self.fbb_.push_slot::<u32>(FutureDiagnostics::VT_QUEUED, queued, 0);
+  }
+  #[inline]
+  pub fn add_waiting(&mut self, waiting: bool) {
+    self.fbb_.push_slot::<bool>(FutureDiagnostics::VT_WAITING, waiting, false);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> FutureDiagnosticsBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    FutureDiagnosticsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<FutureDiagnostics<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for FutureDiagnostics<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("FutureDiagnostics");
+      ds.field("handle", &self.handle());
+      ds.field("state", &self.state());
+      ds.field("queued", &self.queued());
+      ds.field("waiting", &self.waiting());
+      ds.finish()
+  }
+}
@@ -0,0 +1,168 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum HostcallStatsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct HostcallStats<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for HostcallStats<'a> {
+  type Inner = HostcallStats<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> HostcallStats<'a> {
+  pub const VT_MODULE: ::flatbuffers::VOffsetT = 4;
+  pub const VT_CALLS: ::flatbuffers::VOffsetT = 6;
+  pub const VT_ERRORS: ::flatbuffers::VOffsetT = 8;
+  pub const VT_LATENCY_BUCKETS: ::flatbuffers::VOffsetT = 10;
+  pub const VT_LATENCY_TOTAL_US: ::flatbuffers::VOffsetT = 12;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    HostcallStats { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args HostcallStatsArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<HostcallStats<'bldr>> {
+    let mut builder = HostcallStatsBuilder::new(_fbb);
+    builder.add_latency_total_us(args.latency_total_us);
+    builder.add_calls(args.calls);
+    if let Some(x) = args.latency_buckets { builder.add_latency_buckets(x); }
+    if let Some(x) = args.errors { builder.add_errors(x); }
+    if let Some(x) = args.module { builder.add_module(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn module(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(HostcallStats::VT_MODULE, None)}
+  }
+  #[inline]
+  pub fn calls(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(HostcallStats::VT_CALLS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn errors(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ErrorCount<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ErrorCount>>>>(HostcallStats::VT_ERRORS, None)}
+  }
+  #[inline]
+  pub fn latency_buckets(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(HostcallStats::VT_LATENCY_BUCKETS, None)}
+  }
+  #[inline]
+  pub fn latency_total_us(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(HostcallStats::VT_LATENCY_TOTAL_US, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for HostcallStats<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("module", Self::VT_MODULE, false)?
+     .visit_field::<u64>("calls", Self::VT_CALLS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<ErrorCount>>>>("errors", Self::VT_ERRORS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>("latency_buckets", Self::VT_LATENCY_BUCKETS, false)?
+     .visit_field::<u64>("latency_total_us", Self::VT_LATENCY_TOTAL_US, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct HostcallStatsArgs<'a> {
+    pub module: Option<::flatbuffers::WIPOffset<&'a str>>,
+    pub calls: u64,
+    pub errors: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ErrorCount<'a>>>>>,
+    pub latency_buckets: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+    pub latency_total_us: u64,
+}
+impl<'a> Default for HostcallStatsArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    HostcallStatsArgs {
+      module: None,
+      calls: 0,
+      errors: None,
+      latency_buckets: None,
+      latency_total_us: 0,
+    }
+  }
+}
+
+pub struct HostcallStatsBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> HostcallStatsBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_module(&mut self, module: ::flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(HostcallStats::VT_MODULE, module);
+  }
+  #[inline]
+  pub fn add_calls(&mut self, calls: u64) {
+    self.fbb_.push_slot::<u64>(HostcallStats::VT_CALLS, calls, 0);
+  }
+  #[inline]
+  pub fn add_errors(&mut self, errors: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<ErrorCount<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(HostcallStats::VT_ERRORS, errors);
+  }
+  #[inline]
+  pub fn add_latency_buckets(&mut self, latency_buckets: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , u64>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(HostcallStats::VT_LATENCY_BUCKETS, latency_buckets);
+  }
+  #[inline]
+  pub fn add_latency_total_us(&mut self, latency_total_us: u64) {
+    self.fbb_.push_slot::<u64>(HostcallStats::VT_LATENCY_TOTAL_US, latency_total_us, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> HostcallStatsBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    HostcallStatsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<HostcallStats<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for HostcallStats<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("HostcallStats");
+      ds.field("module", &self.module());
+      ds.field("calls", &self.calls());
+      ds.field("errors", &self.errors());
+      ds.field("latency_buckets", &self.latency_buckets());
+      ds.field("latency_total_us", &self.latency_total_us());
+      ds.finish()
+  }
+}
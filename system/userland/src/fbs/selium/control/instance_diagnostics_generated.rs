@@ -0,0 +1,185 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum InstanceDiagnosticsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct InstanceDiagnostics<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for InstanceDiagnostics<'a> {
+  type Inner = InstanceDiagnostics<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> InstanceDiagnostics<'a> {
+  pub const VT_PROCESS_ID: ::flatbuffers::VOffsetT = 4;
+  pub const VT_FUTURES: ::flatbuffers::VOffsetT = 6;
+  pub const VT_MAILBOX: ::flatbuffers::VOffsetT = 8;
+  pub const VT_SLOTS: ::flatbuffers::VOffsetT = 10;
+  pub const VT_FUTURE_SLOTS: ::flatbuffers::VOffsetT = 12;
+  pub const VT_TAGS: ::flatbuffers::VOffsetT = 14;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    InstanceDiagnostics { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args InstanceDiagnosticsArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<InstanceDiagnostics<'bldr>> {
+    let mut builder = InstanceDiagnosticsBuilder::new(_fbb);
+    builder.add_process_id(args.process_id);
+    if let Some(x) = args.tags { builder.add_tags(x); }
+    if let Some(x) = args.future_slots { builder.add_future_slots(x); }
+    if let Some(x) = args.slots { builder.add_slots(x); }
+    if let Some(x) = args.mailbox { builder.add_mailbox(x); }
+    if let Some(x) = args.futures { builder.add_futures(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn process_id(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(InstanceDiagnostics::VT_PROCESS_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn futures(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<FutureDiagnostics<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<FutureDiagnostics>>>>(InstanceDiagnostics::VT_FUTURES, None)}
+  }
+  #[inline]
+  pub fn mailbox(&self) -> Option<MailboxDiagnostics<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<MailboxDiagnostics>>(InstanceDiagnostics::VT_MAILBOX, None)}
+  }
+  #[inline]
+  pub fn slots(&self) -> Option<SlotUsage<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<SlotUsage>>(InstanceDiagnostics::VT_SLOTS, None)}
+  }
+  #[inline]
+  pub fn future_slots(&self) -> Option<SlotUsage<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<SlotUsage>>(InstanceDiagnostics::VT_FUTURE_SLOTS, None)}
+  }
+  #[inline]
+  pub fn tags(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>>>(InstanceDiagnostics::VT_TAGS, None)}
+  }
+}
+
+impl ::flatbuffers::Verifiable for InstanceDiagnostics<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("process_id", Self::VT_PROCESS_ID, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<FutureDiagnostics>>>>("futures", Self::VT_FUTURES, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<MailboxDiagnostics>>("mailbox", Self::VT_MAILBOX, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<SlotUsage>>("slots", Self::VT_SLOTS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<SlotUsage>>("future_slots", Self::VT_FUTURE_SLOTS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>>>("tags", Self::VT_TAGS, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct InstanceDiagnosticsArgs<'a> {
+    pub process_id: u64,
+    pub futures: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<FutureDiagnostics<'a>>>>>,
+    pub mailbox: Option<::flatbuffers::WIPOffset<MailboxDiagnostics<'a>>>,
+    pub slots: Option<::flatbuffers::WIPOffset<SlotUsage<'a>>>,
+    pub future_slots: Option<::flatbuffers::WIPOffset<SlotUsage<'a>>>,
+    pub tags: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>>>,
+}
+impl<'a> Default for InstanceDiagnosticsArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    InstanceDiagnosticsArgs {
+      process_id: 0,
+      futures: None,
+      mailbox: None,
+      slots: None,
+      future_slots: None,
+      tags: None,
+    }
+  }
+}
+
+pub struct InstanceDiagnosticsBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> InstanceDiagnosticsBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_process_id(&mut self, process_id: u64) {
+    self.fbb_.push_slot::<u64>(InstanceDiagnostics::VT_PROCESS_ID, process_id, 0);
+  }
+  #[inline]
+  pub fn add_futures(&mut self, futures: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<FutureDiagnostics<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(InstanceDiagnostics::VT_FUTURES, futures);
+  }
+  #[inline]
+  pub fn add_mailbox(&mut self, mailbox: ::flatbuffers::WIPOffset<MailboxDiagnostics<'b >>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<MailboxDiagnostics>>(InstanceDiagnostics::VT_MAILBOX, mailbox);
+  }
+  #[inline]
+  pub fn add_slots(&mut self, slots: ::flatbuffers::WIPOffset<SlotUsage<'b >>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<SlotUsage>>(InstanceDiagnostics::VT_SLOTS, slots);
+  }
+  #[inline]
+  pub fn add_future_slots(&mut self, future_slots: ::flatbuffers::WIPOffset<SlotUsage<'b >>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<SlotUsage>>(InstanceDiagnostics::VT_FUTURE_SLOTS, future_slots);
+  }
+  #[inline]
+  pub fn add_tags(&mut self, tags: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<&'b  str>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(InstanceDiagnostics::VT_TAGS, tags);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> InstanceDiagnosticsBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    InstanceDiagnosticsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<InstanceDiagnostics<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for InstanceDiagnostics<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("InstanceDiagnostics");
+      ds.field("process_id", &self.process_id());
+      ds.field("futures", &self.futures());
+      ds.field("mailbox", &self.mailbox());
+      ds.field("slots", &self.slots());
+      ds.field("future_slots", &self.future_slots());
+      ds.field("tags", &self.tags());
+      ds.finish()
+  }
+}
@@ -0,0 +1,151 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum MailboxDiagnosticsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct MailboxDiagnostics<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for MailboxDiagnostics<'a> {
+  type Inner = MailboxDiagnostics<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> MailboxDiagnostics<'a> {
+  pub const VT_SIGNALLED: ::flatbuffers::VOffsetT = 4;
+  pub const VT_UNDRAINED: ::flatbuffers::VOffsetT = 6;
+  pub const VT_FLAGGED: ::flatbuffers::VOffsetT = 8;
+  pub const VT_CLOSED: ::flatbuffers::VOffsetT = 10;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    MailboxDiagnostics { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args MailboxDiagnosticsArgs
+  ) -> ::flatbuffers::WIPOffset<MailboxDiagnostics<'bldr>> {
+    let mut builder = MailboxDiagnosticsBuilder::new(_fbb);
+    builder.add_undrained(args.undrained);
+    builder.add_signalled(args.signalled);
+    builder.add_closed(args.closed);
+    builder.add_flagged(args.flagged);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn signalled(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(MailboxDiagnostics::VT_SIGNALLED, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn undrained(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(MailboxDiagnostics::VT_UNDRAINED, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn flagged(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(MailboxDiagnostics::VT_FLAGGED, Some(false)).unwrap()}
+  }
+  #[inline]
+  pub fn closed(&self) -> bool {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<bool>(MailboxDiagnostics::VT_CLOSED, Some(false)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for MailboxDiagnostics<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u32>("signalled", Self::VT_SIGNALLED, false)?
+     .visit_field::<u32>("undrained", Self::VT_UNDRAINED, false)?
+     .visit_field::<bool>("flagged", Self::VT_FLAGGED, false)?
+     .visit_field::<bool>("closed", Self::VT_CLOSED, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct MailboxDiagnosticsArgs {
+    pub signalled: u32,
+    pub undrained: u32,
+    pub flagged: bool,
+    pub closed: bool,
+}
+impl<'a> Default for MailboxDiagnosticsArgs {
+  #[inline]
+  fn default() -> Self {
+    MailboxDiagnosticsArgs {
+      signalled: 0,
+      undrained: 0,
+      flagged: false,
+      closed: false,
+    }
+  }
+}
+
+pub struct MailboxDiagnosticsBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> MailboxDiagnosticsBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_signalled(&mut self, signalled: u32) {
+    self.fbb_.push_slot::<u32>(MailboxDiagnostics::VT_SIGNALLED, signalled, 0);
+  }
+  #[inline]
+  pub fn add_undrained(&mut self, undrained: u32) {
+    self.fbb_.push_slot::<u32>(MailboxDiagnostics::VT_UNDRAINED, undrained, 0);
+  }
+  #[inline]
+  pub fn add_flagged(&mut self, flagged: bool) {
+    self.fbb_.push_slot::<bool>(MailboxDiagnostics::VT_FLAGGED, flagged, false);
+  }
+  #[inline]
+  pub fn add_closed(&mut self, closed: bool) {
+    self.fbb_.push_slot::<bool>(MailboxDiagnostics::VT_CLOSED, closed, false);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> MailboxDiagnosticsBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    MailboxDiagnosticsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<MailboxDiagnostics<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for MailboxDiagnostics<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("MailboxDiagnostics");
+      ds.field("signalled", &self.signalled());
+      ds.field("undrained", &self.undrained());
+      ds.field("flagged", &self.flagged());
+      ds.field("closed", &self.closed());
+      ds.finish()
+  }
+}
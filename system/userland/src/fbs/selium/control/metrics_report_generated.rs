@@ -0,0 +1,168 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum MetricsReportOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct MetricsReport<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for MetricsReport<'a> {
+  type Inner = MetricsReport<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> MetricsReport<'a> {
+  pub const VT_LATENCY_BOUNDS_US: ::flatbuffers::VOffsetT = 4;
+  pub const VT_HOSTCALLS: ::flatbuffers::VOffsetT = 6;
+  pub const VT_GUEST_COUNTERS: ::flatbuffers::VOffsetT = 8;
+  pub const VT_GUEST_GAUGES: ::flatbuffers::VOffsetT = 10;
+  pub const VT_MODULE_CACHE: ::flatbuffers::VOffsetT = 12;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    MetricsReport { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args MetricsReportArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<MetricsReport<'bldr>> {
+    let mut builder = MetricsReportBuilder::new(_fbb);
+    if let Some(x) = args.module_cache { builder.add_module_cache(x); }
+    if let Some(x) = args.guest_gauges { builder.add_guest_gauges(x); }
+    if let Some(x) = args.guest_counters { builder.add_guest_counters(x); }
+    if let Some(x) = args.hostcalls { builder.add_hostcalls(x); }
+    if let Some(x) = args.latency_bounds_us { builder.add_latency_bounds_us(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn latency_bounds_us(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(MetricsReport::VT_LATENCY_BOUNDS_US, None)}
+  }
+  #[inline]
+  pub fn hostcalls(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<HostcallStats<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<HostcallStats>>>>(MetricsReport::VT_HOSTCALLS, None)}
+  }
+  #[inline]
+  pub fn guest_counters(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestCounter<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestCounter>>>>(MetricsReport::VT_GUEST_COUNTERS, None)}
+  }
+  #[inline]
+  pub fn guest_gauges(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestGauge<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestGauge>>>>(MetricsReport::VT_GUEST_GAUGES, None)}
+  }
+  #[inline]
+  pub fn module_cache(&self) -> Option<ModuleCacheStats<'a>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<ModuleCacheStats>>(MetricsReport::VT_MODULE_CACHE, None)}
+  }
+}
+
+impl ::flatbuffers::Verifiable for MetricsReport<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>("latency_bounds_us", Self::VT_LATENCY_BOUNDS_US, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<HostcallStats>>>>("hostcalls", Self::VT_HOSTCALLS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<GuestCounter>>>>("guest_counters", Self::VT_GUEST_COUNTERS, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<GuestGauge>>>>("guest_gauges", Self::VT_GUEST_GAUGES, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<ModuleCacheStats>>("module_cache", Self::VT_MODULE_CACHE, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct MetricsReportArgs<'a> {
+    pub latency_bounds_us: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+    pub hostcalls: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<HostcallStats<'a>>>>>,
+    pub guest_counters: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestCounter<'a>>>>>,
+    pub guest_gauges: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<GuestGauge<'a>>>>>,
+    pub module_cache: Option<::flatbuffers::WIPOffset<ModuleCacheStats<'a>>>,
+}
+impl<'a> Default for MetricsReportArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    MetricsReportArgs {
+      latency_bounds_us: None,
+      hostcalls: None,
+      guest_counters: None,
+      guest_gauges: None,
+      module_cache: None,
+    }
+  }
+}
+
+pub struct MetricsReportBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> MetricsReportBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_latency_bounds_us(&mut self, latency_bounds_us: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , u64>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(MetricsReport::VT_LATENCY_BOUNDS_US, latency_bounds_us);
+  }
+  #[inline]
+  pub fn add_hostcalls(&mut self, hostcalls: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<HostcallStats<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(MetricsReport::VT_HOSTCALLS, hostcalls);
+  }
+  #[inline]
+  pub fn add_guest_counters(&mut self, guest_counters: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<GuestCounter<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(MetricsReport::VT_GUEST_COUNTERS, guest_counters);
+  }
+  #[inline]
+  pub fn add_guest_gauges(&mut self, guest_gauges: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<GuestGauge<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(MetricsReport::VT_GUEST_GAUGES, guest_gauges);
+  }
+  #[inline]
+  pub fn add_module_cache(&mut self, module_cache: ::flatbuffers::WIPOffset<ModuleCacheStats<'b >>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<ModuleCacheStats>>(MetricsReport::VT_MODULE_CACHE, module_cache);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> MetricsReportBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    MetricsReportBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<MetricsReport<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for MetricsReport<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("MetricsReport");
+      ds.field("latency_bounds_us", &self.latency_bounds_us());
+      ds.field("hostcalls", &self.hostcalls());
+      ds.field("guest_counters", &self.guest_counters());
+      ds.field("guest_gauges", &self.guest_gauges());
+      ds.field("module_cache", &self.module_cache());
+      ds.finish()
+  }
+}
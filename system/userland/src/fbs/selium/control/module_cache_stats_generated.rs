@@ -0,0 +1,134 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ModuleCacheStatsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ModuleCacheStats<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ModuleCacheStats<'a> {
+  type Inner = ModuleCacheStats<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ModuleCacheStats<'a> {
+  pub const VT_HITS: ::flatbuffers::VOffsetT = 4;
+  pub const VT_MISSES: ::flatbuffers::VOffsetT = 6;
+  pub const VT_INTEGRITY_FAILURES: ::flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ModuleCacheStats { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ModuleCacheStatsArgs
+  ) -> ::flatbuffers::WIPOffset<ModuleCacheStats<'bldr>> {
+    let mut builder = ModuleCacheStatsBuilder::new(_fbb);
+    builder.add_integrity_failures(args.integrity_failures);
+    builder.add_misses(args.misses);
+    builder.add_hits(args.hits);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn hits(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleCacheStats::VT_HITS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn misses(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleCacheStats::VT_MISSES, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn integrity_failures(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleCacheStats::VT_INTEGRITY_FAILURES, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for ModuleCacheStats<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("hits", Self::VT_HITS, false)?
+     .visit_field::<u64>("misses", Self::VT_MISSES, false)?
+     .visit_field::<u64>("integrity_failures", Self::VT_INTEGRITY_FAILURES, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ModuleCacheStatsArgs {
+    pub hits: u64,
+    pub misses: u64,
+    pub integrity_failures: u64,
+}
+impl<'a> Default for ModuleCacheStatsArgs {
+  #[inline]
+  fn default() -> Self {
+    ModuleCacheStatsArgs {
+      hits: 0,
+      misses: 0,
+      integrity_failures: 0,
+    }
+  }
+}
+
+pub struct ModuleCacheStatsBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ModuleCacheStatsBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_hits(&mut self, hits: u64) {
+    self.fbb_.push_slot::<u64>(ModuleCacheStats::VT_HITS, hits, 0);
+  }
+  #[inline]
+  pub fn add_misses(&mut self, misses: u64) {
+    self.fbb_.push_slot::<u64>(ModuleCacheStats::VT_MISSES, misses, 0);
+  }
+  #[inline]
+  pub fn add_integrity_failures(&mut self, integrity_failures: u64) {
+    self.fbb_.push_slot::<u64>(ModuleCacheStats::VT_INTEGRITY_FAILURES, integrity_failures, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ModuleCacheStatsBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ModuleCacheStatsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ModuleCacheStats<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ModuleCacheStats<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ModuleCacheStats");
+      ds.field("hits", &self.hits());
+      ds.field("misses", &self.misses());
+      ds.field("integrity_failures", &self.integrity_failures());
+      ds.finish()
+  }
+}
@@ -0,0 +1,100 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ModuleListOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ModuleList<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ModuleList<'a> {
+  type Inner = ModuleList<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ModuleList<'a> {
+  pub const VT_MODULES: ::flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ModuleList { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ModuleListArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<ModuleList<'bldr>> {
+    let mut builder = ModuleListBuilder::new(_fbb);
+    if let Some(x) = args.modules { builder.add_modules(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn modules(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ModuleStatus<'a>>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ModuleStatus>>>>(ModuleList::VT_MODULES, None)}
+  }
+}
+
+impl ::flatbuffers::Verifiable for ModuleList<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<ModuleStatus>>>>("modules", Self::VT_MODULES, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ModuleListArgs<'a> {
+    pub modules: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ModuleStatus<'a>>>>>,
+}
+impl<'a> Default for ModuleListArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ModuleListArgs {
+      modules: None,
+    }
+  }
+}
+
+pub struct ModuleListBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ModuleListBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_modules(&mut self, modules: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<ModuleStatus<'b >>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ModuleList::VT_MODULES, modules);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ModuleListBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ModuleListBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ModuleList<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ModuleList<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ModuleList");
+      ds.field("modules", &self.modules());
+      ds.finish()
+  }
+}
@@ -0,0 +1,202 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ModuleStatusOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ModuleStatus<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ModuleStatus<'a> {
+  type Inner = ModuleStatus<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ModuleStatus<'a> {
+  pub const VT_LABEL: ::flatbuffers::VOffsetT = 4;
+  pub const VT_PROCESS_ID: ::flatbuffers::VOffsetT = 6;
+  pub const VT_STATE: ::flatbuffers::VOffsetT = 8;
+  pub const VT_CAPABILITIES: ::flatbuffers::VOffsetT = 10;
+  pub const VT_UPTIME_MS: ::flatbuffers::VOffsetT = 12;
+  pub const VT_RESTARTS: ::flatbuffers::VOffsetT = 14;
+  pub const VT_FUEL_CONSUMED: ::flatbuffers::VOffsetT = 16;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ModuleStatus { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ModuleStatusArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<ModuleStatus<'bldr>> {
+    let mut builder = ModuleStatusBuilder::new(_fbb);
+    builder.add_fuel_consumed(args.fuel_consumed);
+    builder.add_uptime_ms(args.uptime_ms);
+    builder.add_process_id(args.process_id);
+    builder.add_restarts(args.restarts);
+    if let Some(x) = args.capabilities { builder.add_capabilities(x); }
+    if let Some(x) = args.label { builder.add_label(x); }
+    builder.add_state(args.state);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn label(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(ModuleStatus::VT_LABEL, None)}
+  }
+  #[inline]
+  pub fn process_id(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleStatus::VT_PROCESS_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn state(&self) -> ModuleState {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<ModuleState>(ModuleStatus::VT_STATE, Some(ModuleState::Running)).unwrap()}
+  }
+  #[inline]
+  pub fn capabilities(&self) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>>>(ModuleStatus::VT_CAPABILITIES, None)}
+  }
+  #[inline]
+  pub fn uptime_ms(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleStatus::VT_UPTIME_MS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn restarts(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(ModuleStatus::VT_RESTARTS, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn fuel_consumed(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ModuleStatus::VT_FUEL_CONSUMED, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for ModuleStatus<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("label", Self::VT_LABEL, false)?
+     .visit_field::<u64>("process_id", Self::VT_PROCESS_ID, false)?
+     .visit_field::<ModuleState>("state", Self::VT_STATE, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>>>("capabilities", Self::VT_CAPABILITIES, false)?
+     .visit_field::<u64>("uptime_ms", Self::VT_UPTIME_MS, false)?
+     .visit_field::<u32>("restarts", Self::VT_RESTARTS, false)?
+     .visit_field::<u64>("fuel_consumed", Self::VT_FUEL_CONSUMED, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ModuleStatusArgs<'a> {
+    pub label: Option<::flatbuffers::WIPOffset<&'a str>>,
+    pub process_id: u64,
+    pub state: ModuleState,
+    pub capabilities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>>>,
+    pub uptime_ms: u64,
+    pub restarts: u32,
+    pub fuel_consumed: u64,
+}
+impl<'a> Default for ModuleStatusArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ModuleStatusArgs {
+      label: None,
+      process_id: 0,
+      state: ModuleState::Running,
+      capabilities: None,
+      uptime_ms: 0,
+      restarts: 0,
+      fuel_consumed: 0,
+    }
+  }
+}
+
+pub struct ModuleStatusBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ModuleStatusBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_label(&mut self, label: ::flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ModuleStatus::VT_LABEL, label);
+  }
+  #[inline]
+  pub fn add_process_id(&mut self, process_id: u64) {
+    self.fbb_.push_slot::<u64>(ModuleStatus::VT_PROCESS_ID, process_id, 0);
+  }
+  #[inline]
+  pub fn add_state(&mut self, state: ModuleState) {
+    self.fbb_.push_slot::<ModuleState>(ModuleStatus::VT_STATE, state, ModuleState::Running);
+  }
+  #[inline]
+  pub fn add_capabilities(&mut self, capabilities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b , ::flatbuffers::ForwardsUOffset<&'b  str>>>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ModuleStatus::VT_CAPABILITIES, capabilities);
+  }
+  #[inline]
+  pub fn add_uptime_ms(&mut self, uptime_ms: u64) {
+    self.fbb_.push_slot::<u64>(ModuleStatus::VT_UPTIME_MS, uptime_ms, 0);
+  }
+  #[inline]
+  pub fn add_restarts(&mut self, restarts: u32) {
+    self.fbb_.push_slot::<u32>(ModuleStatus::VT_RESTARTS, restarts, 0);
+  }
+  #[inline]
+  pub fn add_fuel_consumed(&mut self, fuel_consumed: u64) {
+    self.fbb_.push_slot::<u64>(ModuleStatus::VT_FUEL_CONSUMED, fuel_consumed, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ModuleStatusBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ModuleStatusBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ModuleStatus<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ModuleStatus<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ModuleStatus");
+      ds.field("label", &self.label());
+      ds.field("process_id", &self.process_id());
+      ds.field("state", &self.state());
+      ds.field("capabilities", &self.capabilities());
+      ds.field("uptime_ms", &self.uptime_ms());
+      ds.field("restarts", &self.restarts());
+      ds.field("fuel_consumed", &self.fuel_consumed());
+      ds.finish()
+  }
+}
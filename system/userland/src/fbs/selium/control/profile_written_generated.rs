@@ -0,0 +1,134 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum ProfileWrittenOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ProfileWritten<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for ProfileWritten<'a> {
+  type Inner = ProfileWritten<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> ProfileWritten<'a> {
+  pub const VT_PROCESS_ID: ::flatbuffers::VOffsetT = 4;
+  pub const VT_PATH: ::flatbuffers::VOffsetT = 6;
+  pub const VT_SAMPLES: ::flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    ProfileWritten { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args ProfileWrittenArgs<'args>
+  ) -> ::flatbuffers::WIPOffset<ProfileWritten<'bldr>> {
+    let mut builder = ProfileWrittenBuilder::new(_fbb);
+    builder.add_samples(args.samples);
+    builder.add_process_id(args.process_id);
+    if let Some(x) = args.path { builder.add_path(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn process_id(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ProfileWritten::VT_PROCESS_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn path(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(ProfileWritten::VT_PATH, None)}
+  }
+  #[inline]
+  pub fn samples(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(ProfileWritten::VT_SAMPLES, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for ProfileWritten<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("process_id", Self::VT_PROCESS_ID, false)?
+     .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("path", Self::VT_PATH, false)?
+     .visit_field::<u64>("samples", Self::VT_SAMPLES, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ProfileWrittenArgs<'a> {
+    pub process_id: u64,
+    pub path: Option<::flatbuffers::WIPOffset<&'a str>>,
+    pub samples: u64,
+}
+impl<'a> Default for ProfileWrittenArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    ProfileWrittenArgs {
+      process_id: 0,
+      path: None,
+      samples: 0,
+    }
+  }
+}
+
+pub struct ProfileWrittenBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ProfileWrittenBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_process_id(&mut self, process_id: u64) {
+    self.fbb_.push_slot::<u64>(ProfileWritten::VT_PROCESS_ID, process_id, 0);
+  }
+  #[inline]
+  pub fn add_path(&mut self, path: ::flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(ProfileWritten::VT_PATH, path);
+  }
+  #[inline]
+  pub fn add_samples(&mut self, samples: u64) {
+    self.fbb_.push_slot::<u64>(ProfileWritten::VT_SAMPLES, samples, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> ProfileWrittenBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    ProfileWrittenBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<ProfileWritten<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for ProfileWritten<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("ProfileWritten");
+      ds.field("process_id", &self.process_id());
+      ds.field("path", &self.path());
+      ds.field("samples", &self.samples());
+      ds.finish()
+  }
+}
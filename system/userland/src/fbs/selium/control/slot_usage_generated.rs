@@ -0,0 +1,117 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum SlotUsageOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct SlotUsage<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for SlotUsage<'a> {
+  type Inner = SlotUsage<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> SlotUsage<'a> {
+  pub const VT_LIVE: ::flatbuffers::VOffsetT = 4;
+  pub const VT_ALLOCATED: ::flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    SlotUsage { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args SlotUsageArgs
+  ) -> ::flatbuffers::WIPOffset<SlotUsage<'bldr>> {
+    let mut builder = SlotUsageBuilder::new(_fbb);
+    builder.add_allocated(args.allocated);
+    builder.add_live(args.live);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn live(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(SlotUsage::VT_LIVE, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn allocated(&self) -> u32 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u32>(SlotUsage::VT_ALLOCATED, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for SlotUsage<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u32>("live", Self::VT_LIVE, false)?
+     .visit_field::<u32>("allocated", Self::VT_ALLOCATED, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct SlotUsageArgs {
+    pub live: u32,
+    pub allocated: u32,
+}
+impl<'a> Default for SlotUsageArgs {
+  #[inline]
+  fn default() -> Self {
+    SlotUsageArgs {
+      live: 0,
+      allocated: 0,
+    }
+  }
+}
+
+pub struct SlotUsageBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> SlotUsageBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_live(&mut self, live: u32) {
+    self.fbb_.push_slot::<u32>(SlotUsage::VT_LIVE, live, 0);
+  }
+  #[inline]
+  pub fn add_allocated(&mut self, allocated: u32) {
+    self.fbb_.push_slot::<u32>(SlotUsage::VT_ALLOCATED, allocated, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> SlotUsageBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    SlotUsageBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<SlotUsage<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for SlotUsage<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("SlotUsage");
+      ds.field("live", &self.live());
+      ds.field("allocated", &self.allocated());
+      ds.finish()
+  }
+}
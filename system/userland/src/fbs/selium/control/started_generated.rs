@@ -0,0 +1,100 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+// @generated
+extern crate alloc;
+use super::*;
+pub enum StartedOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Started<'a> {
+  pub _tab: ::flatbuffers::Table<'a>,
+}
+
+impl<'a> ::flatbuffers::Follow<'a> for Started<'a> {
+  type Inner = Started<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: unsafe { ::flatbuffers::Table::new(buf, loc) } }
+  }
+}
+
+impl<'a> Started<'a> {
+  pub const VT_PROCESS_ID: ::flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+    Started { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: ::flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args StartedArgs
+  ) -> ::flatbuffers::WIPOffset<Started<'bldr>> {
+    let mut builder = StartedBuilder::new(_fbb);
+    builder.add_process_id(args.process_id);
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn process_id(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(Started::VT_PROCESS_ID, Some(0)).unwrap()}
+  }
+}
+
+impl ::flatbuffers::Verifiable for Started<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut ::flatbuffers::Verifier, pos: usize
+  ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+    v.visit_table(pos)?
+     .visit_field::<u64>("process_id", Self::VT_PROCESS_ID, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct StartedArgs {
+    pub process_id: u64,
+}
+impl<'a> Default for StartedArgs {
+  #[inline]
+  fn default() -> Self {
+    StartedArgs {
+      process_id: 0,
+    }
+  }
+}
+
+pub struct StartedBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> StartedBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_process_id(&mut self, process_id: u64) {
+    self.fbb_.push_slot::<u64>(Started::VT_PROCESS_ID, process_id, 0);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>) -> StartedBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    StartedBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> ::flatbuffers::WIPOffset<Started<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    ::flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl ::core::fmt::Debug for Started<'_> {
+  fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+    let mut ds = f.debug_struct("Started");
+      ds.field("process_id", &self.process_id());
+      ds.finish()
+  }
+}
@@ -0,0 +1,73 @@
+//! The filesystem a guest sees through its sandbox.
+//!
+//! Paths are relative to the process's sandbox directory; a path whose first component names a
+//! mount resolves inside that mount.
+//!
+//! # Examples
+//! ```no_run
+//! use futures::StreamExt;
+//! use selium_userland::{fs, io::DriverError};
+//!
+//! async fn on_dropped_orders() -> Result<(), DriverError> {
+//!     let mut changes = fs::watch("inbox")?;
+//!     while let Some(event) = changes.next().await {
+//!         let event = event?;
+//!         if event.kind == fs::FsEventKind::Created {
+//!             // process `event.path`
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use selium_abi::FsWatch;
+pub use selium_abi::{FsEvent, FsEventKind};
+
+use crate::driver::{DriverError, DriverStream, RkyvDecoder, encode_args};
+
+/// Expected size of one encoded change event.
+const EVENT_CAPACITY: usize = 512;
+
+/// Stream of the changes to a watched path, in the order the host noticed them. It ends only
+/// with an error; dropping it stops the watch.
+pub struct Watch {
+    inner: DriverStream<fs_watch::Module, RkyvDecoder<Option<FsEvent>>>,
+}
+
+/// Watch the file or directory at `path` for files being created, modified or removed. The path
+/// need not exist yet. Changes in subdirectories of a watched directory are not reported; see
+/// [`watch_recursive`].
+pub fn watch(path: &str) -> Result<Watch, DriverError> {
+    start_watch(path, false)
+}
+
+/// Watch the directory at `path` and every directory beneath it.
+pub fn watch_recursive(path: &str) -> Result<Watch, DriverError> {
+    start_watch(path, true)
+}
+
+fn start_watch(path: &str, recursive: bool) -> Result<Watch, DriverError> {
+    let args = encode_args(&FsWatch {
+        path: path.to_string(),
+        recursive,
+    })?;
+    Ok(Watch {
+        inner: DriverStream::new(&args, EVENT_CAPACITY, RkyvDecoder::new())?,
+    })
+}
+
+impl Stream for Watch {
+    type Item = Result<FsEvent, DriverError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+driver_module!(fs_watch, FS_WATCH, "selium::fs::watch");
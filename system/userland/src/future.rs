@@ -0,0 +1,94 @@
+//! Handing pending hostcall results from one process to another.
+//!
+//! An orchestrator can start a long-running hostcall, [`share`] the pending future and pass
+//! the [`SharedFuture`] to a worker, for example over a channel or in a control message. The
+//! worker then [claims](SharedFuture::claim) the result, while the orchestrator moves on.
+//! Until it is claimed, the future stays owned by the process that shared it, and each shared
+//! future can be claimed once.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{
+//!     future::{self, SharedFuture},
+//!     io::{DriverError, DriverFuture, DriverModule, RkyvDecoder},
+//! };
+//!
+//! async fn hand_off<M: DriverModule>(
+//!     pending: DriverFuture<M, RkyvDecoder<Vec<u8>>>,
+//! ) -> Result<u64, DriverError> {
+//!     let shared = future::share(pending).await?;
+//!     Ok(shared.raw())
+//! }
+//!
+//! async fn take_over(raw: u64) -> Result<Vec<u8>, DriverError> {
+//!     // SAFETY: `raw` was produced by `SharedFuture::raw` in the process that shared it.
+//!     let shared = unsafe { SharedFuture::from_raw(raw) };
+//!     shared.claim::<Vec<u8>>().await
+//! }
+//! ```
+
+use selium_abi::{GuestResourceId, JsonPayload};
+
+use crate::driver::{
+    DriverDecoder, DriverError, DriverFuture, DriverModule, RkyvDecoder, encode_args,
+};
+
+/// A pending hostcall result shared for another process to claim.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SharedFuture(GuestResourceId);
+
+impl SharedFuture {
+    /// Access the underlying registry handle, to pass to the claiming process.
+    pub fn raw(&self) -> GuestResourceId {
+        self.0
+    }
+
+    /// Construct a shared future from a raw registry identifier.
+    ///
+    /// # Safety
+    /// The handle must have been minted by [`share`]. Forged or stale handles are rejected by
+    /// the host kernel.
+    pub unsafe fn from_raw(handle: GuestResourceId) -> Self {
+        Self(handle)
+    }
+
+    /// Claim the shared future and wait for its result, decoded as the output of the hostcall
+    /// that produced it. Both processes must use the same payload encoding.
+    pub async fn claim<T>(self) -> Result<T, DriverError>
+    where
+        T: rkyv::Archive + JsonPayload + Sized + Unpin,
+        for<'a> T::Archived: 'a
+            + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    {
+        let args = encode_args(&self.0)?;
+        let bytes = DriverFuture::<future_await::Module, RkyvDecoder<Vec<u8>>>::new(
+            &args,
+            256,
+            RkyvDecoder::new(),
+        )?
+        .await?;
+        RkyvDecoder::<T>::new().decode(&bytes)
+    }
+}
+
+/// Share a pending driver future for another process to claim. The future is handed over
+/// rather than cancelled, so its hostcall carries on in the background.
+pub async fn share<M, D>(future: DriverFuture<M, D>) -> Result<SharedFuture, DriverError>
+where
+    M: DriverModule,
+    D: DriverDecoder,
+{
+    let handle = future.into_handle().ok_or(DriverError::InvalidArgument)?;
+    let args = encode_args(&handle)?;
+    let shared = DriverFuture::<future_share::Module, RkyvDecoder<GuestResourceId>>::new(
+        &args,
+        8,
+        RkyvDecoder::new(),
+    )?
+    .await?;
+    Ok(SharedFuture(shared))
+}
+
+driver_module!(future_share, FUTURE_SHARE, "selium::future::share");
+driver_module!(future_await, FUTURE_AWAIT, "selium::future::await");
@@ -0,0 +1,38 @@
+//! Commands run on the host, for work no other hostcall covers.
+//!
+//! Only commands the operator allow-listed in the runtime's deployment file can be run, by the
+//! name they were listed under, and every invocation is recorded in the host's audit trail.
+//! Prefer a dedicated hostcall wherever one exists.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{host, io::DriverError};
+//!
+//! async fn firmware_version() -> Result<Option<String>, DriverError> {
+//!     let output = host::exec("flash", &["--version"]).await?;
+//!     Ok((output.status == Some(0)).then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+//! }
+//! ```
+
+pub use selium_abi::HostExecOutput;
+use selium_abi::{DEFAULT_MAX_PAYLOAD, HostExec};
+
+use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+
+/// Run the host command allow-listed as `command`, appending `args` to the arguments the
+/// allow-list fixes, and wait for it to exit. Commands that are not allow-listed, or that do
+/// not accept arguments, are refused with a permission error.
+pub async fn exec(command: &str, args: &[&str]) -> Result<HostExecOutput, DriverError> {
+    let args = encode_args(&HostExec {
+        command: command.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+    })?;
+    DriverFuture::<host_exec::Module, RkyvDecoder<HostExecOutput>>::new(
+        &args,
+        DEFAULT_MAX_PAYLOAD,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+driver_module!(host_exec, HOST_EXEC, "selium::host::exec");
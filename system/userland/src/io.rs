@@ -1,5 +1,8 @@
 //! High-level guest IO helpers for creating channels and moving raw frames.
 //!
+//! [`channel`] layers typed messages over the same channels: a [`Sender`] rkyv-encodes each
+//! value into a frame and a [`Receiver`] decodes the frames back.
+//!
 //! # Examples
 //! ```no_run
 //! use futures::{SinkExt, StreamExt};
@@ -24,17 +27,21 @@
 use core::{
     convert::TryFrom,
     fmt,
+    marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
 };
 
 use futures::{Sink, Stream};
-use selium_abi::{ChannelCreate, GuestResourceId, GuestUint, IoFrame, IoRead, IoWrite};
+use selium_abi::{
+    ChannelCreate, GuestResourceId, GuestUint, IoFrame, IoRead, IoWrite, RkyvEncode, decode_rkyv,
+    encode_rkyv,
+};
 
 use crate::FromHandle;
 pub use crate::driver::{
-    DriverError, DriverFuture, DriverModule, MIN_RESULT_CAPACITY, RKYV_VEC_OVERHEAD, RkyvDecoder,
-    encode_args,
+    DriverError, DriverFuture, DriverModule, DriverStream, MAX_RESULT_CAPACITY,
+    MIN_RESULT_CAPACITY, RKYV_VEC_OVERHEAD, RkyvDecoder, encode_args,
 };
 /// Backpressure behaviour for channel writers.
 pub use selium_abi::ChannelBackpressure;
@@ -97,6 +104,18 @@ pub struct Writer {
     inflight: Option<WriterInflight>,
 }
 
+/// Typed sending half of a channel, encoding each value as one frame.
+pub struct Sender<T> {
+    writer: Writer,
+    _marker: PhantomData<fn(T)>,
+}
+
+/// Typed receiving half of a channel, yielding the values decoded from each frame.
+pub struct Receiver<T> {
+    reader: Reader,
+    _marker: PhantomData<fn() -> T>,
+}
+
 impl Channel {
     /// Create a new channel with the requested capacity (in bytes).
     ///
@@ -565,6 +584,98 @@ impl FromHandle for Writer {
     }
 }
 
+impl<T> Sender<T> {
+    /// Send values of type `T` through an existing writer, such as one for a shared channel.
+    pub fn new(writer: Writer) -> Self {
+        Self {
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the underlying frame writer.
+    pub fn into_inner(self) -> Writer {
+        self.writer
+    }
+}
+
+impl<T> Sink<T> for Sender<T>
+where
+    T: RkyvEncode,
+{
+    type Error = DriverError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.writer).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let payload = encode_rkyv(&item).map_err(|err| DriverError::Driver(err.to_string()))?;
+        // An empty frame marks the end of a channel, so it cannot carry a value.
+        if payload.is_empty() {
+            return Err(DriverError::InvalidArgument);
+        }
+        Pin::new(&mut self.writer).start_send(payload)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.writer).poll_close(cx)
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receive values of type `T` from an existing reader, such as one for a shared channel.
+    ///
+    /// The reader's chunk size must fit the largest encoded value.
+    pub fn new(reader: Reader) -> Self {
+        Self {
+            reader,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the underlying frame reader.
+    pub fn into_inner(self) -> Reader {
+        self.reader
+    }
+}
+
+impl<T> Stream for Receiver<T>
+where
+    T: rkyv::Archive + Sized,
+    for<'a> T::Archived: 'a
+        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+{
+    type Item = Result<T, DriverError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.reader).poll_next(cx).map(|frame| {
+            frame.map(|frame| {
+                frame.and_then(|frame| {
+                    decode_rkyv(&frame.payload).map_err(|err| DriverError::Driver(err.to_string()))
+                })
+            })
+        })
+    }
+}
+
+/// Create a channel of `capacity` bytes carrying values of type `T`, returning its sending and
+/// receiving halves.
+///
+/// Values are rkyv-encoded, one per frame, so no single value may encode to more than
+/// `capacity` bytes. Senders wait while the channel is full.
+pub async fn channel<T>(capacity: GuestUint) -> Result<(Sender<T>, Receiver<T>), DriverError> {
+    let channel = Channel::create(capacity).await?;
+    let writer = channel.publish().await?;
+    let reader = channel.subscribe(capacity).await?;
+    Ok((Sender::new(writer), Receiver::new(reader)))
+}
+
 fn guest_handle(handle: GuestResourceId) -> Result<GuestUint, DriverError> {
     GuestUint::try_from(handle).map_err(|_| DriverError::InvalidArgument)
 }
@@ -623,8 +734,9 @@ driver_module!(channel_share, CHANNEL_SHARE, "selium::channel::share");
 
 #[cfg(test)]
 mod tests {
+    use futures::{SinkExt, StreamExt};
+
     use super::*;
-    use selium_abi::decode_rkyv;
 
     #[test]
     fn channel_read_round_trips() {
@@ -637,6 +749,22 @@ mod tests {
         assert_eq!(decoded, read);
     }
 
+    #[test]
+    fn typed_channels_round_trip_values() {
+        crate::block_on(async {
+            let (mut sender, mut receiver) = channel::<(u32, String)>(1024).await.expect("channel");
+            sender.send((7, "seven".to_string())).await.expect("send");
+            let received = receiver.next().await.expect("value").expect("decode");
+            assert_eq!(received, (7, "seven".to_string()));
+
+            let (mut units, _) = channel::<()>(1024).await.expect("channel");
+            assert!(matches!(
+                units.send(()).await,
+                Err(DriverError::InvalidArgument)
+            ));
+        });
+    }
+
     #[test]
     fn encode_write_args_serializes_payload() {
         let args = IoWrite {
@@ -25,8 +25,15 @@ extern crate self as selium_userland;
 
 macro_rules! driver_module {
     ($mod_name:ident, $import:ident, $import_module:literal) => {
+        driver_module!(@module $mod_name, selium_abi::hostcall_name!($import), $import_module);
+    };
+    // Hostcalls outside the capability catalogue are keyed by their import module alone.
+    ($mod_name:ident, $import_module:literal) => {
+        driver_module!(@module $mod_name, $import_module, $import_module);
+    };
+    (@module $mod_name:ident, $name:expr, $import_module:literal) => {
         mod $mod_name {
-            use selium_abi::{GuestInt, GuestUint};
+            use selium_abi::{GuestPtr, GuestSize, GuestUint};
 
             use crate::driver::DriverModule;
 
@@ -36,25 +43,25 @@ macro_rules! driver_module {
             #[cfg(target_arch = "wasm32")]
             #[link(wasm_import_module = $import_module)]
             unsafe extern "C" {
-                pub fn create(args_ptr: GuestInt, args_len: GuestUint) -> GuestUint;
+                pub fn create(args_ptr: GuestPtr, args_len: GuestSize) -> GuestUint;
                 pub fn poll(
                     handle: GuestUint,
                     task_id: GuestUint,
-                    result_ptr: GuestInt,
-                    result_len: GuestUint,
+                    result_ptr: GuestPtr,
+                    result_len: GuestSize,
                 ) -> GuestUint;
                 pub fn drop(
                     handle: GuestUint,
-                    result_ptr: GuestInt,
-                    result_len: GuestUint,
+                    result_ptr: GuestPtr,
+                    result_len: GuestSize,
                 ) -> GuestUint;
             }
 
             #[allow(dead_code)]
             #[cfg(all(not(target_arch = "wasm32"), test))]
-            unsafe fn create(args_ptr: GuestInt, args_len: GuestUint) -> GuestUint {
+            unsafe fn create(args_ptr: GuestPtr, args_len: GuestSize) -> GuestUint {
                 crate::driver::test_driver::create(
-                    selium_abi::hostcall_name!($import),
+                    $name,
                     args_ptr,
                     args_len,
                 )
@@ -62,7 +69,7 @@ macro_rules! driver_module {
 
             #[allow(dead_code)]
             #[cfg(all(not(target_arch = "wasm32"), not(test)))]
-            unsafe fn create(_args_ptr: GuestInt, _args_len: GuestUint) -> GuestUint {
+            unsafe fn create(_args_ptr: GuestPtr, _args_len: GuestSize) -> GuestUint {
                 selium_abi::driver_encode_error(2)
             }
 
@@ -71,11 +78,11 @@ macro_rules! driver_module {
             unsafe fn poll(
                 handle: GuestUint,
                 task_id: GuestUint,
-                result_ptr: GuestInt,
-                result_len: GuestUint,
+                result_ptr: GuestPtr,
+                result_len: GuestSize,
             ) -> GuestUint {
                 crate::driver::test_driver::poll(
-                    selium_abi::hostcall_name!($import),
+                    $name,
                     handle,
                     task_id,
                     result_ptr,
@@ -88,8 +95,8 @@ macro_rules! driver_module {
             unsafe fn poll(
                 _handle: GuestUint,
                 _task_id: GuestUint,
-                _result_ptr: GuestInt,
-                _result_len: GuestUint,
+                _result_ptr: GuestPtr,
+                _result_len: GuestSize,
             ) -> GuestUint {
                 selium_abi::driver_encode_error(2)
             }
@@ -98,11 +105,11 @@ macro_rules! driver_module {
             #[cfg(all(not(target_arch = "wasm32"), test))]
             unsafe fn drop(
                 handle: GuestUint,
-                result_ptr: GuestInt,
-                result_len: GuestUint,
+                result_ptr: GuestPtr,
+                result_len: GuestSize,
             ) -> GuestUint {
                 crate::driver::test_driver::drop(
-                    selium_abi::hostcall_name!($import),
+                    $name,
                     handle,
                     result_ptr,
                     result_len,
@@ -113,30 +120,30 @@ macro_rules! driver_module {
             #[cfg(all(not(target_arch = "wasm32"), not(test)))]
             unsafe fn drop(
                 _handle: GuestUint,
-                _result_ptr: GuestInt,
-                _result_len: GuestUint,
+                _result_ptr: GuestPtr,
+                _result_len: GuestSize,
             ) -> GuestUint {
                 0
             }
 
             impl DriverModule for Module {
-                unsafe fn create(args_ptr: GuestInt, args_len: GuestUint) -> GuestUint {
+                unsafe fn create(args_ptr: GuestPtr, args_len: GuestSize) -> GuestUint {
                     unsafe { create(args_ptr, args_len) }
                 }
 
                 unsafe fn poll(
                     handle: GuestUint,
                     task_id: GuestUint,
-                    result_ptr: GuestInt,
-                    result_len: GuestUint,
+                    result_ptr: GuestPtr,
+                    result_len: GuestSize,
                 ) -> GuestUint {
                     unsafe { poll(handle, task_id, result_ptr, result_len) }
                 }
 
                 unsafe fn drop(
                     handle: GuestUint,
-                    result_ptr: GuestInt,
-                    result_len: GuestUint,
+                    result_ptr: GuestPtr,
+                    result_len: GuestSize,
                 ) -> GuestUint {
                     unsafe { drop(handle, result_ptr, result_len) }
                 }
@@ -147,6 +154,7 @@ macro_rules! driver_module {
 
 pub mod abi;
 mod r#async;
+pub mod compress;
 pub mod context;
 mod driver;
 pub mod encoding;
@@ -166,12 +174,23 @@ pub mod encoding;
 #[allow(warnings)]
 #[rustfmt::skip]
 pub mod fbs;
+pub mod fs;
+pub mod future;
+pub mod host;
 pub mod io;
+pub mod lock;
 pub mod logging;
+pub mod meta;
+pub mod metrics;
 pub mod net;
 pub mod process;
+pub mod rpc;
+pub mod services;
 pub mod singleton;
+pub mod sql;
+pub mod sync;
 pub mod time;
+pub mod watch;
 
 /// Re-export of the `rkyv` crate used for internal Selium serialisation.
 pub use rkyv;
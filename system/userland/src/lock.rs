@@ -0,0 +1,72 @@
+//! Named locks held by the host, for guests coordinating on shared resources.
+//!
+//! A lock is held until it is released or its time-to-live runs out, so a guest that exits
+//! while holding one only delays the others. Each acquisition carries a token that increases
+//! across acquisitions; passing it along with writes lets the shared resource reject a holder
+//! whose lock has already expired.
+//!
+//! # Examples
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use selium_userland::{io::DriverError, lock};
+//!
+//! async fn compact() -> Result<(), DriverError> {
+//!     let guard = lock::acquire("compaction", Duration::from_secs(30)).await?;
+//!     // ... work on the shared resource, fenced by `guard.token()` ...
+//!     lock::release(guard).await
+//! }
+//! ```
+
+use std::time::Duration;
+
+use selium_abi::{LockAcquire, LockRelease};
+
+use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+
+/// A held lock. Dropping it without [`release`] leaves the lock held until its time-to-live
+/// runs out.
+#[derive(Debug, Eq, PartialEq)]
+#[must_use = "the lock stays held until it is released or expires"]
+pub struct LockGuard {
+    name: String,
+    token: u64,
+}
+
+impl LockGuard {
+    /// Name of the lock.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Token identifying this acquisition, for fencing writes to the shared resource.
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+}
+
+/// Acquire the lock `name`, waiting while another guest holds it. The host releases the lock
+/// once `ttl` has passed, should it not be released before.
+pub async fn acquire(name: impl Into<String>, ttl: Duration) -> Result<LockGuard, DriverError> {
+    let name = name.into();
+    let args = encode_args(&LockAcquire {
+        name: name.clone(),
+        ttl_ms: u64::try_from(ttl.as_millis()).map_err(|_| DriverError::InvalidArgument)?,
+    })?;
+    let token =
+        DriverFuture::<lock_acquire::Module, RkyvDecoder<u64>>::new(&args, 8, RkyvDecoder::new())?
+            .await?;
+    Ok(LockGuard { name, token })
+}
+
+/// Release a held lock. Fails if the lock expired and was since acquired by another guest.
+pub async fn release(guard: LockGuard) -> Result<(), DriverError> {
+    let args = encode_args(&LockRelease {
+        name: guard.name,
+        token: guard.token,
+    })?;
+    DriverFuture::<lock_release::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?.await
+}
+
+driver_module!(lock_acquire, LOCK_ACQUIRE, "selium::lock::acquire");
+driver_module!(lock_release, LOCK_RELEASE, "selium::lock::release");
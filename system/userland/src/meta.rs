@@ -0,0 +1,171 @@
+//! Guest-side introspection of the hostcalls linked for this instance.
+//!
+//! Hostcalls whose capability was not granted are linked as trapping stubs, so guests with
+//! optional features should check for them here before calling.
+
+use std::future::Future;
+
+/// Environment of the host, as reported by [`host_info`].
+pub use selium_abi::HostInfo;
+#[cfg(target_arch = "wasm32")]
+use selium_abi::IdempotencyKey;
+use selium_abi::{InstanceDiagnostics, PayloadEncoding};
+
+use crate::driver::DriverError;
+#[cfg(target_arch = "wasm32")]
+use crate::driver::{DriverFuture, RkyvDecoder, encode_args, set_payload_encoding};
+
+#[cfg(target_arch = "wasm32")]
+const HOSTCALLS_CAPACITY: usize = 8 * 1024;
+#[cfg(target_arch = "wasm32")]
+const DIAGNOSTICS_CAPACITY: usize = 64 * 1024;
+#[cfg(target_arch = "wasm32")]
+const HOST_INFO_CAPACITY: usize = 1024;
+
+/// List the import module names of every hostcall linked for this instance.
+#[cfg(target_arch = "wasm32")]
+pub async fn hostcalls() -> Result<Vec<String>, DriverError> {
+    let args = encode_args(&())?;
+    DriverFuture::<meta_hostcalls::Module, RkyvDecoder<Vec<String>>>::new(
+        &args,
+        HOSTCALLS_CAPACITY,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+/// List the hostcalls linked for this instance; none are linked when running natively.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn hostcalls() -> Result<Vec<String>, DriverError> {
+    Ok(Vec::new())
+}
+
+/// Report whether the hostcall with the given import module name is linked for this instance.
+pub async fn supports(name: &str) -> Result<bool, DriverError> {
+    Ok(hostcalls().await?.iter().any(|linked| linked == name))
+}
+
+/// Run `call` under an idempotency key.
+///
+/// The key is attached to the first hostcall `call` makes. If that hostcall is retried under the
+/// same key, for example after the guest lost the original future, the host replays the original
+/// result instead of executing the call again.
+#[cfg(target_arch = "wasm32")]
+pub async fn idempotent<F, T>(key: impl Into<String>, call: F) -> Result<T, DriverError>
+where
+    F: Future<Output = Result<T, DriverError>>,
+{
+    let args = encode_args(&IdempotencyKey { key: key.into() })?;
+    // The host records the key when this call is created; it only needs to outlive `call`'s
+    // first hostcall, which is created on the first poll below.
+    let _key = DriverFuture::<meta_idempotency_key::Module, RkyvDecoder<()>>::new(
+        &args,
+        0,
+        RkyvDecoder::new(),
+    )?;
+    call.await
+}
+
+/// Run `call`; idempotency keys have no effect when running natively.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn idempotent<F, T>(_key: impl Into<String>, call: F) -> Result<T, DriverError>
+where
+    F: Future<Output = Result<T, DriverError>>,
+{
+    call.await
+}
+
+/// Report that this instance has finished initialising.
+///
+/// The host waits for this before retiring the instance that a reloaded module replaces. Guests
+/// that never call it are assumed ready once the host's readiness timeout elapses.
+#[cfg(target_arch = "wasm32")]
+pub async fn ready() -> Result<(), DriverError> {
+    let args = encode_args(&())?;
+    DriverFuture::<meta_ready::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?.await
+}
+
+/// Report readiness; a no-op when running natively.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn ready() -> Result<(), DriverError> {
+    Ok(())
+}
+
+/// Report this instance's pending futures, wake-up mailbox counters and slot usage, as seen by
+/// the host, for debugging tasks that never wake.
+#[cfg(target_arch = "wasm32")]
+pub async fn diagnostics() -> Result<InstanceDiagnostics, DriverError> {
+    let args = encode_args(&())?;
+    DriverFuture::<meta_diagnostics::Module, RkyvDecoder<InstanceDiagnostics>>::new(
+        &args,
+        DIAGNOSTICS_CAPACITY,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+/// Report diagnostics; there is no host state to report when running natively.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn diagnostics() -> Result<InstanceDiagnostics, DriverError> {
+    Ok(InstanceDiagnostics::default())
+}
+
+/// Report the OS family, architecture, locale and hostname of the host this instance runs on,
+/// for including in logs and reports. Fields the host withholds are empty.
+#[cfg(target_arch = "wasm32")]
+pub async fn host_info() -> Result<HostInfo, DriverError> {
+    let args = encode_args(&())?;
+    DriverFuture::<meta_host_info::Module, RkyvDecoder<HostInfo>>::new(
+        &args,
+        HOST_INFO_CAPACITY,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+/// Report the OS family and architecture this native build targets; the locale and hostname
+/// are left empty.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn host_info() -> Result<HostInfo, DriverError> {
+    Ok(HostInfo {
+        os_family: std::env::consts::FAMILY.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        ..HostInfo::default()
+    })
+}
+
+/// Ask the host to exchange this instance's later hostcall payloads as `requested`, returning
+/// the encoding in effect afterwards.
+///
+/// Self-describing JSON payloads make hostcall traffic readable with standard tools, but are
+/// only granted when both this guest and the host are built with the `json` feature. Any
+/// hostcall futures created beforehand must complete before the switch is requested.
+#[cfg(target_arch = "wasm32")]
+pub async fn negotiate_encoding(
+    requested: PayloadEncoding,
+) -> Result<PayloadEncoding, DriverError> {
+    let args = encode_args(&requested)?;
+    let agreed = DriverFuture::<meta_encoding::Module, RkyvDecoder<PayloadEncoding>>::new(
+        &args,
+        0,
+        RkyvDecoder::new(),
+    )?
+    .await?;
+    set_payload_encoding(agreed);
+    Ok(agreed)
+}
+
+/// Negotiate a payload encoding; payloads are always rkyv when running natively.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn negotiate_encoding(
+    _requested: PayloadEncoding,
+) -> Result<PayloadEncoding, DriverError> {
+    Ok(PayloadEncoding::Rkyv)
+}
+
+driver_module!(meta_hostcalls, "selium::meta::hostcalls");
+driver_module!(meta_idempotency_key, "selium::meta::idempotency_key");
+driver_module!(meta_ready, "selium::meta::ready");
+driver_module!(meta_diagnostics, "selium::meta::diagnostics");
+driver_module!(meta_encoding, "selium::meta::encoding");
+driver_module!(meta_host_info, "selium::meta::host_info");
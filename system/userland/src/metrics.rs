@@ -0,0 +1,122 @@
+//! Application metrics kept by the host on the guest's behalf.
+//!
+//! Counters and gauges are named with Prometheus metric names and live in the kernel, so every
+//! guest granted the metrics capability updates the same metric for a given name, and the host
+//! reports them alongside its own metrics. Names starting with `selium_` are reserved for the
+//! host.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{io::DriverError, metrics};
+//!
+//! async fn handle_request() -> Result<(), DriverError> {
+//!     let in_flight = metrics::gauge("http_requests_in_flight");
+//!     in_flight.add(1).await?;
+//!     metrics::counter("http_requests_total").increment().await?;
+//!     in_flight.add(-1).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use selium_abi::{CounterAdd, GaugeAdd, GaugeSet};
+
+use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+
+/// A monotonically increasing metric, such as a count of handled requests.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Counter {
+    name: String,
+}
+
+/// A metric that rises and falls, such as a queue depth.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gauge {
+    name: String,
+}
+
+impl Counter {
+    /// Name of the counter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add one to the counter and return its new value.
+    pub async fn increment(&self) -> Result<u64, DriverError> {
+        self.add(1).await
+    }
+
+    /// Add `delta` to the counter and return its new value.
+    pub async fn add(&self, delta: u64) -> Result<u64, DriverError> {
+        let args = encode_args(&CounterAdd {
+            name: self.name.clone(),
+            delta,
+        })?;
+        DriverFuture::<metrics_counter_add::Module, RkyvDecoder<u64>>::new(
+            &args,
+            8,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+}
+
+impl Gauge {
+    /// Name of the gauge.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set the gauge to `value`.
+    pub async fn set(&self, value: i64) -> Result<(), DriverError> {
+        let args = encode_args(&GaugeSet {
+            name: self.name.clone(),
+            value,
+        })?;
+        DriverFuture::<metrics_gauge_set::Module, RkyvDecoder<()>>::new(
+            &args,
+            0,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+
+    /// Add `delta`, which may be negative, to the gauge and return its new value.
+    pub async fn add(&self, delta: i64) -> Result<i64, DriverError> {
+        let args = encode_args(&GaugeAdd {
+            name: self.name.clone(),
+            delta,
+        })?;
+        DriverFuture::<metrics_gauge_add::Module, RkyvDecoder<i64>>::new(
+            &args,
+            8,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+}
+
+/// Handle to the counter `name`. The host creates it, at zero, on first use.
+pub fn counter(name: impl Into<String>) -> Counter {
+    Counter { name: name.into() }
+}
+
+/// Handle to the gauge `name`. The host creates it, at zero, on first use.
+pub fn gauge(name: impl Into<String>) -> Gauge {
+    Gauge { name: name.into() }
+}
+
+driver_module!(
+    metrics_counter_add,
+    METRICS_COUNTER_ADD,
+    "selium::metrics::counter::add"
+);
+driver_module!(
+    metrics_gauge_set,
+    METRICS_GAUGE_SET,
+    "selium::metrics::gauge::set"
+);
+driver_module!(
+    metrics_gauge_add,
+    METRICS_GAUGE_ADD,
+    "selium::metrics::gauge::add"
+);
@@ -1,10 +1,13 @@
 //! Guest-facing helpers for spawning and stopping Selium processes.
 //!
+//! A parent can also push control messages to a process it spawned with [`send`]; the child
+//! reads them from its [`inbox`], with no channel or singleton to set up first.
+//!
 //! # Examples
 //! ```no_run
 //! use selium_userland::{
 //!     abi::{AbiParam, AbiScalarType, AbiSignature},
-//!     process::{Capability, ProcessBuilder, ProcessError},
+//!     process::{Capability, ProcessBuilder, ProcessError, send},
 //! };
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -20,33 +23,58 @@
 //!         let builder = builder.log_uri("sel://logs/echoer");
 //!         let handle = builder.start().await?;
 //!
+//!         send(&handle, b"reload").await?;
 //!         handle.stop().await?;
+//!
+//!         // A batch worker instead runs to completion and hands back a value.
+//!         let worker = ProcessBuilder::new("selium.examples.batch", "sum")
+//!             .arg_rkyv(&vec![1u64, 2, 3])?
+//!             .start()
+//!             .await?;
+//!         let total: u64 = worker.wait().await?;
 //!         Ok::<_, ProcessError>(())
 //!     })?;
 //!     Ok(())
 //! }
 //! ```
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{panic, sync::Once};
+
+use futures::Stream;
 use selium_abi::AbiParam;
 use selium_abi::GuestResourceId;
 use selium_abi::{
     AbiScalarValue, AbiSignature, EntrypointArg, EntrypointInvocation, ProcessLogLookup,
-    ProcessLogRegistration, ProcessStart, RkyvEncode,
+    ProcessLogRegistration, ProcessMessage, ProcessPanic, ProcessStart, ProcessStartEnvelope,
+    RkyvEncode, decode_rkyv, encode_rkyv,
 };
 
 use crate::driver::{self, DriverFuture, RkyvDecoder, encode_args};
 use crate::io::SharedChannel;
 
-pub use selium_abi::Capability;
+/// Runtime statistics reported for a process.
+pub use selium_abi::ProcessInfo;
+/// Resource usage the current process reads for itself.
+pub use selium_abi::ProcessSelfStats;
+pub use selium_abi::{Capability, CapabilitySet};
 
 /// Error returned by process lifecycle helpers.
 pub type ProcessError = driver::DriverError;
 
+/// Initial buffer for a completion value; larger values are delivered in chunks.
+const COMPLETION_CAPACITY: usize = 4 * 1024;
+/// Initial buffer for an inbox message; larger messages are delivered in chunks.
+const MESSAGE_CAPACITY: usize = 1024;
+
 /// Builder for configuring and launching a Selium process.
 #[derive(Clone, Debug, PartialEq)]
 pub struct ProcessBuilder {
     module_id: String,
     entrypoint: String,
-    capabilities: Vec<Capability>,
+    capabilities: CapabilitySet,
     signature: AbiSignature,
     args: Vec<EntrypointArg>,
     log_uri: Option<String>,
@@ -58,7 +86,7 @@ impl ProcessBuilder {
         Self {
             module_id: module_id.into(),
             entrypoint: name.into(),
-            capabilities: vec![Capability::ChannelLifecycle, Capability::ChannelWriter],
+            capabilities: [Capability::ChannelLifecycle, Capability::ChannelWriter].into(),
             signature: AbiSignature::new(Vec::new(), Vec::new()),
             args: Vec::new(),
             log_uri: None,
@@ -67,9 +95,7 @@ impl ProcessBuilder {
 
     /// Add a capability that the launched process should receive.
     pub fn capability(mut self, capability: Capability) -> Self {
-        if !self.capabilities.contains(&capability) {
-            self.capabilities.push(capability);
-        }
+        self.capabilities.insert(capability);
         self
     }
 
@@ -120,9 +146,11 @@ impl ProcessBuilder {
         self.arg_buffer(value.into().into_bytes())
     }
 
-    /// Append an rkyv-encoded argument.
+    /// Append an rkyv-encoded argument. Entrypoint arguments are rkyv whatever the payload
+    /// encoding of either instance's hostcalls.
     pub fn arg_rkyv<T: RkyvEncode>(mut self, value: &T) -> Result<Self, ProcessError> {
-        let bytes = encode_args(value)?;
+        let bytes =
+            encode_rkyv(value).map_err(|err| driver::DriverError::Driver(err.to_string()))?;
         self.args.push(EntrypointArg::Buffer(bytes));
         Ok(self)
     }
@@ -149,6 +177,12 @@ impl ProcessBuilder {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ProcessHandle(GuestResourceId);
 
+/// Stream of the control messages other processes [`send`] to the current process, oldest
+/// first. It never ends: polling waits for the next message.
+pub struct Inbox {
+    inflight: Option<DriverFuture<process_receive::Module, RkyvDecoder<Vec<u8>>>>,
+}
+
 impl ProcessHandle {
     /// Access the underlying registry handle.
     pub fn raw(&self) -> GuestResourceId {
@@ -172,6 +206,39 @@ impl ProcessHandle {
             .map(|_| ())
     }
 
+    /// Wait for the process to exit and decode the value its entrypoint completed with.
+    ///
+    /// Like [`stop`](Self::stop), this consumes the process: once waited on, it can no longer
+    /// be stopped or inspected. A process that completed without a value can be waited on as
+    /// `()`.
+    pub async fn wait<T>(self) -> Result<T, ProcessError>
+    where
+        T: rkyv::Archive,
+        for<'a> T::Archived: 'a
+            + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    {
+        let args = encode_args(&self.0)?;
+        let bytes = DriverFuture::<process_wait::Module, RkyvDecoder<Vec<u8>>>::new(
+            &args,
+            COMPLETION_CAPACITY,
+            RkyvDecoder::new(),
+        )?
+        .await?;
+        decode_rkyv(&bytes).map_err(|err| driver::DriverError::Driver(err.to_string()))
+    }
+
+    /// Fetch runtime statistics, such as consumed fuel, for this process.
+    pub async fn info(&self) -> Result<ProcessInfo, ProcessError> {
+        let args = encode_args(&self.0)?;
+        DriverFuture::<process_info::Module, RkyvDecoder<ProcessInfo>>::new(
+            &args,
+            16,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+
     /// Fetch the shared logging channel registered by this process.
     pub async fn log_channel(&self) -> Result<SharedChannel, ProcessError> {
         let args = encode_args(&ProcessLogLookup { process_id: self.0 })?;
@@ -188,6 +255,38 @@ impl ProcessHandle {
     }
 }
 
+impl Stream for Inbox {
+    type Item = Result<Vec<u8>, ProcessError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+
+        if this.inflight.is_none() {
+            let args = match encode_args(&()) {
+                Ok(args) => args,
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            };
+            match DriverFuture::new(&args, MESSAGE_CAPACITY, RkyvDecoder::new()) {
+                Ok(fut) => this.inflight = Some(fut),
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+
+        let fut = match this.inflight.as_mut() {
+            Some(fut) => fut,
+            None => return Poll::Ready(Some(Err(ProcessError::InvalidArgument))),
+        };
+
+        match Pin::new(fut).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(res) => {
+                this.inflight = None;
+                Poll::Ready(Some(res))
+            }
+        }
+    }
+}
+
 /// Register the supplied shared channel as the logging stream for the current process.
 pub async fn register_log_channel(reference: SharedChannel) -> Result<(), ProcessError> {
     let args = encode_args(&ProcessLogRegistration {
@@ -202,6 +301,87 @@ pub async fn register_log_channel(reference: SharedChannel) -> Result<(), Proces
     .map(|_| ())
 }
 
+/// Record the value the current process's entrypoint completes with, for the parent to
+/// retrieve with [`ProcessHandle::wait`].
+///
+/// Entrypoints declared with `#[entrypoint]` that return a value record it automatically. The
+/// value is rkyv-encoded whatever the payload encoding of either instance's hostcalls; recording
+/// another replaces it.
+pub async fn complete<T: RkyvEncode>(value: &T) -> Result<(), ProcessError> {
+    let value = encode_rkyv(value).map_err(|err| driver::DriverError::Driver(err.to_string()))?;
+    let args = encode_args(&value)?;
+    DriverFuture::<process_complete::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
+        .await
+        .map(|_| ())
+}
+
+/// Fetch the current process's own resource usage: its linear memory size, fuel consumed,
+/// hostcalls issued and pending futures.
+///
+/// Unlike [`ProcessHandle::info`], this needs no capability, so any guest can throttle itself
+/// or report its health with it.
+pub async fn self_stats() -> Result<ProcessSelfStats, ProcessError> {
+    let args = encode_args(&())?;
+    DriverFuture::<process_self_stats::Module, RkyvDecoder<ProcessSelfStats>>::new(
+        &args,
+        64,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+/// Push a control message into the inbox of the referenced process.
+///
+/// Messages are capped at 64 KiB, and the host refuses further messages while the receiver has
+/// 64 unread ones queued.
+pub async fn send(handle: &ProcessHandle, message: &[u8]) -> Result<(), ProcessError> {
+    let args = encode_args(&ProcessMessage {
+        process_id: handle.0,
+        payload: message.to_vec(),
+    })?;
+    DriverFuture::<process_send::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
+        .await
+        .map(|_| ())
+}
+
+/// Report panics to the host before the guest traps, so host logs and crash reports say why the
+/// process died. The previously installed hook still runs afterwards.
+///
+/// `#[entrypoint]` installs this hook before running the entrypoint; installing it again has no
+/// effect.
+pub fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            report_panic(&ProcessPanic {
+                message: info.payload_as_str().unwrap_or("Box<dyn Any>").to_string(),
+                location: info.location().map(ToString::to_string).unwrap_or_default(),
+            });
+            previous(info);
+        }));
+    });
+}
+
+/// Open the current process's inbox, to read the messages sent to it with [`send`].
+pub fn inbox() -> Inbox {
+    Inbox { inflight: None }
+}
+
+/// Send `panic` to the host without waiting for a reply: the host records it as the call is
+/// made, and a panicking guest may be unable to drive its executor.
+fn report_panic(panic: &ProcessPanic) {
+    if let Ok(args) = encode_args(panic)
+        && let Ok(call) = DriverFuture::<process_report_panic::Module, RkyvDecoder<()>>::new(
+            &args,
+            0,
+            RkyvDecoder::new(),
+        )
+    {
+        drop(call);
+    }
+}
+
 async fn start_process(builder: ProcessBuilder) -> Result<ProcessHandle, ProcessError> {
     let args = encode_start_args(builder)?;
     let handle = DriverFuture::<process_start::Module, RkyvDecoder<GuestResourceId>>::new(
@@ -215,7 +395,7 @@ async fn start_process(builder: ProcessBuilder) -> Result<ProcessHandle, Process
 
 fn encode_start_args(builder: ProcessBuilder) -> Result<Vec<u8>, ProcessError> {
     let payload = build_start_payload(builder)?;
-    encode_args(&payload)
+    encode_args(&ProcessStartEnvelope::from(payload))
 }
 
 fn build_start_payload(builder: ProcessBuilder) -> Result<ProcessStart, ProcessError> {
@@ -265,6 +445,13 @@ fn inject_log_uri(
 
 driver_module!(process_start, PROCESS_START, "selium::process::start");
 driver_module!(process_stop, PROCESS_STOP, "selium::process::stop");
+driver_module!(process_info, PROCESS_INFO, "selium::process::info");
+driver_module!(process_wait, PROCESS_WAIT, "selium::process::wait");
+driver_module!(process_send, PROCESS_SEND, "selium::process::send");
+driver_module!(process_complete, "selium::process::complete");
+driver_module!(process_receive, "selium::process::receive");
+driver_module!(process_self_stats, "selium::process::self_stats");
+driver_module!(process_report_panic, "selium::process::report_panic");
 driver_module!(
     process_register_log,
     PROCESS_REGISTER_LOG,
@@ -279,8 +466,8 @@ driver_module!(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use selium_abi::decode_rkyv;
     use selium_abi::{AbiParam, AbiScalarType};
+    use selium_abi::{Versioned, decode_rkyv};
 
     #[test]
     fn encode_start_args_serialises_signature_and_arguments() {
@@ -296,16 +483,16 @@ mod tests {
             .arg_i32(42)
             .arg_buffer([1, 2, 3]);
         let bytes = encode_start_args(builder).expect("encode");
-        let start = decode_rkyv::<ProcessStart>(&bytes).expect("decode");
+        let start = ProcessStart::upgrade(decode_rkyv(&bytes).expect("decode"));
         assert_eq!(start.module_id, "module");
         assert_eq!(start.name, "proc");
         assert_eq!(
             start.capabilities,
-            vec![
+            CapabilitySet::from([
                 Capability::ChannelLifecycle,
+                Capability::ChannelReader,
                 Capability::ChannelWriter,
-                Capability::ChannelReader
-            ]
+            ])
         );
         assert_eq!(start.entrypoint.signature.params()[0], AbiParam::Buffer);
         assert_eq!(
@@ -330,7 +517,7 @@ mod tests {
             .signature(signature)
             .arg_resource(7u64);
         let bytes = encode_start_args(builder).expect("encode");
-        let start = decode_rkyv::<ProcessStart>(&bytes).expect("decode");
+        let start = ProcessStart::upgrade(decode_rkyv(&bytes).expect("decode"));
         assert_eq!(start.entrypoint.args[0], EntrypointArg::Buffer(Vec::new()));
         assert_eq!(start.entrypoint.args[1..], [EntrypointArg::Resource(7)]);
     }
@@ -342,7 +529,7 @@ mod tests {
             .signature(signature.clone())
             .arg_resource(7u64);
         let bytes = encode_start_args(builder).expect("encode");
-        let start = decode_rkyv::<ProcessStart>(&bytes).expect("decode");
+        let start = ProcessStart::upgrade(decode_rkyv(&bytes).expect("decode"));
         assert_eq!(start.entrypoint.signature.params()[0], AbiParam::Buffer);
         assert_eq!(
             start.entrypoint.signature.params()[1..],
@@ -358,7 +545,7 @@ mod tests {
         let signature = AbiSignature::new(Vec::new(), Vec::new());
         let builder = ProcessBuilder::new("module", "proc").signature(signature);
         let bytes = encode_start_args(builder).expect("encode");
-        let start = decode_rkyv::<ProcessStart>(&bytes).expect("decode");
+        let start = ProcessStart::upgrade(decode_rkyv(&bytes).expect("decode"));
         assert_eq!(start.entrypoint.signature.params()[0], AbiParam::Buffer);
         assert_eq!(start.entrypoint.args[0], EntrypointArg::Buffer(Vec::new()));
     }
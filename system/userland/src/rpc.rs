@@ -0,0 +1,402 @@
+//! Typed calls between guests.
+//!
+//! A server listens on a request channel that it registers as a singleton, so clients can
+//! discover it through [`Context::singleton`](crate::Context::singleton). Each client creates
+//! its own response channel and sends every call to the server with a shared handle to it. The
+//! channels are ring buffers in host memory, and a frame arriving on one rings the receiving
+//! guest's wake-up mailbox, so neither side polls while it waits.
+//!
+//! Rather than using [`RpcClient`] and [`RpcServer`] directly, annotate a trait with `#[rpc]`
+//! to generate a typed client stub and the server dispatch for it. Arguments and results are
+//! rkyv-encoded.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{Context, entrypoint, rpc::{self, RpcError}};
+//!
+//! #[selium_userland::rpc(name = "selium.examples.calculator")]
+//! pub trait Calculator {
+//!     async fn add(&self, a: u64, b: u64) -> u64;
+//! }
+//!
+//! struct Adder;
+//!
+//! impl Calculator for Adder {
+//!     async fn add(&self, a: u64, b: u64) -> u64 {
+//!         a + b
+//!     }
+//! }
+//!
+//! #[entrypoint]
+//! async fn server() -> Result<(), RpcError> {
+//!     rpc::serve(CalculatorServer(Adder)).await
+//! }
+//!
+//! #[entrypoint]
+//! async fn client() -> Result<(), RpcError> {
+//!     let calculator = Context::current().singleton::<CalculatorClient>().await?;
+//!     assert_eq!(calculator.add(2, 3).await?, 5);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    rc::Rc,
+};
+
+use futures::{SinkExt, StreamExt};
+use rkyv::{Archive, Deserialize, Serialize};
+use selium_abi::{GuestResourceId, GuestUint, RkyvEncode, decode_rkyv, encode_rkyv};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::{
+    DependencyDescriptor,
+    driver::DriverError,
+    io::{Channel, Reader, SharedChannel, Writer},
+    services, singleton,
+};
+
+/// Capacity, in bytes, of the request and response channels created by this module.
+pub const DEFAULT_CAPACITY: GuestUint = 64 * 1024;
+
+/// A call sent from a client to a server.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct RpcRequest {
+    /// Identifier the client matches the response against.
+    pub call_id: u64,
+    /// Shared handle of the client's response channel.
+    pub reply_to: GuestResourceId,
+    /// Name of the method to call.
+    pub method: String,
+    /// The method's rkyv-encoded arguments, as a tuple.
+    pub args: Vec<u8>,
+}
+
+/// A server's answer to an [`RpcRequest`].
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub struct RpcResponse {
+    /// Identifier of the call being answered.
+    pub call_id: u64,
+    /// Result of the call.
+    pub outcome: RpcOutcome,
+}
+
+/// Result of a call, as reported by the server.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[rkyv(bytecheck())]
+pub enum RpcOutcome {
+    /// The method returned this rkyv-encoded value.
+    Returned(Vec<u8>),
+    /// The server does not implement the method.
+    UnknownMethod,
+    /// The server could not handle the call.
+    Failed(String),
+}
+
+/// Error returned by RPC clients and servers.
+#[derive(Debug, Error)]
+pub enum RpcError {
+    /// A channel or singleton hostcall failed.
+    #[error(transparent)]
+    Driver(#[from] DriverError),
+    /// Encoding an argument, result or message failed.
+    #[error("failed to encode RPC payload: {0}")]
+    Encode(String),
+    /// Decoding an argument, result or message failed.
+    #[error("failed to decode RPC payload: {0}")]
+    Decode(String),
+    /// The server does not implement the called method.
+    #[error("server does not implement `{0}`")]
+    UnknownMethod(String),
+    /// The server could not handle the call.
+    #[error("server failed to handle the call: {0}")]
+    Failed(String),
+    /// The response channel closed before the call was answered.
+    #[error("response channel closed before the call was answered")]
+    Closed,
+}
+
+/// A service that dispatches calls to its methods. Generated by `#[rpc]`.
+pub trait RpcDispatch {
+    /// Singleton the service's request channel is registered under.
+    const DESCRIPTOR: DependencyDescriptor;
+
+    /// Call `method` with its rkyv-encoded `args`, returning the rkyv-encoded result.
+    fn dispatch(
+        &self,
+        method: &str,
+        args: &[u8],
+    ) -> impl Future<Output = Result<Vec<u8>, RpcError>>;
+}
+
+/// Client side of a connection to an RPC server.
+///
+/// Clones share the connection, and calls made through them may be in flight at once.
+#[derive(Clone)]
+pub struct RpcClient {
+    inner: Rc<ClientInner>,
+}
+
+/// Server side of an RPC service, answering the calls that arrive on its request channel.
+pub struct RpcServer {
+    requests: Channel,
+    replies: HashMap<GuestResourceId, Writer>,
+}
+
+struct ClientInner {
+    requests: Mutex<Writer>,
+    responses: Mutex<Reader>,
+    reply_to: GuestResourceId,
+    next_call: Cell<u64>,
+    /// Responses read while waiting for another call's, kept until their caller collects them.
+    arrived: RefCell<HashMap<u64, RpcOutcome>>,
+}
+
+impl RpcClient {
+    /// Connect to the server listening on the shared request channel `server`.
+    pub async fn connect(server: SharedChannel) -> Result<Self, RpcError> {
+        let responses = Channel::create(DEFAULT_CAPACITY).await?;
+        let reply_to = responses.share().await?;
+        let requests = Channel::attach_shared(server).await?;
+        Ok(Self {
+            inner: Rc::new(ClientInner {
+                requests: Mutex::new(requests.publish().await?),
+                responses: Mutex::new(responses.subscribe(DEFAULT_CAPACITY).await?),
+                reply_to: reply_to.raw(),
+                next_call: Cell::new(0),
+                arrived: RefCell::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// Call `method` with `args`, a tuple of its arguments, and decode what it returns.
+    pub async fn call<A, R>(&self, method: &str, args: &A) -> Result<R, RpcError>
+    where
+        A: RkyvEncode,
+        R: Archive,
+        for<'a> R::Archived: 'a
+            + rkyv::Deserialize<R, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    {
+        let call_id = self.inner.next_call.get();
+        self.inner.next_call.set(call_id.wrapping_add(1));
+        let request = RpcRequest {
+            call_id,
+            reply_to: self.inner.reply_to,
+            method: method.to_string(),
+            args: encode(args)?,
+        };
+        self.inner
+            .requests
+            .lock()
+            .await
+            .send(encode(&request)?)
+            .await?;
+
+        match self.response(call_id).await? {
+            RpcOutcome::Returned(bytes) => decode(&bytes),
+            RpcOutcome::UnknownMethod => Err(RpcError::UnknownMethod(method.to_string())),
+            RpcOutcome::Failed(message) => Err(RpcError::Failed(message)),
+        }
+    }
+
+    /// Wait for the response to `call_id`. Whichever caller holds the response reader reads on
+    /// behalf of the others, setting aside responses that are not its own.
+    async fn response(&self, call_id: u64) -> Result<RpcOutcome, RpcError> {
+        loop {
+            if let Some(outcome) = self.inner.arrived.borrow_mut().remove(&call_id) {
+                return Ok(outcome);
+            }
+            let mut responses = self.inner.responses.lock().await;
+            // Another caller may have read this call's response while we waited for the reader.
+            if let Some(outcome) = self.inner.arrived.borrow_mut().remove(&call_id) {
+                return Ok(outcome);
+            }
+            let frame = responses.next().await.ok_or(RpcError::Closed)??;
+            let response: RpcResponse = decode(&frame.payload)?;
+            if response.call_id == call_id {
+                return Ok(response.outcome);
+            }
+            self.inner
+                .arrived
+                .borrow_mut()
+                .insert(response.call_id, response.outcome);
+        }
+    }
+}
+
+impl RpcServer {
+    /// Create a server listening on a new request channel.
+    pub async fn create() -> Result<Self, RpcError> {
+        Ok(Self::new(Channel::create(DEFAULT_CAPACITY).await?))
+    }
+
+    /// Create a server listening on `requests`.
+    pub fn new(requests: Channel) -> Self {
+        Self {
+            requests,
+            replies: HashMap::new(),
+        }
+    }
+
+    /// Register the request channel as the singleton `descriptor`, so that clients can find it.
+    pub async fn register(&self, descriptor: DependencyDescriptor) -> Result<(), RpcError> {
+        let shared = self.requests.share().await?;
+        singleton::register(descriptor, shared.raw()).await?;
+        Ok(())
+    }
+
+    /// Register the request channel as an instance of the service `descriptor` in the
+    /// directory, alongside any other servers offering it. Clients reach one of them through
+    /// [`Context::service`](crate::Context::service).
+    pub async fn register_instance(
+        &self,
+        descriptor: DependencyDescriptor,
+    ) -> Result<(), RpcError> {
+        let shared = self.requests.share().await?;
+        services::register(descriptor, shared.raw()).await?;
+        Ok(())
+    }
+
+    /// Answer calls with `service` until the request channel closes.
+    ///
+    /// Calls are handled one at a time, in the order they arrive. A response that cannot be
+    /// delivered is logged and dropped, as its client has most likely gone away.
+    pub async fn run<S: RpcDispatch>(&mut self, service: &S) -> Result<(), RpcError> {
+        let mut requests = self.requests.subscribe(DEFAULT_CAPACITY).await?;
+        while let Some(frame) = requests.next().await {
+            let request: RpcRequest = decode(&frame?.payload)?;
+            let outcome = match service.dispatch(&request.method, &request.args).await {
+                Ok(bytes) => RpcOutcome::Returned(bytes),
+                Err(RpcError::UnknownMethod(_)) => RpcOutcome::UnknownMethod,
+                Err(err) => RpcOutcome::Failed(err.to_string()),
+            };
+            let response = RpcResponse {
+                call_id: request.call_id,
+                outcome,
+            };
+            if let Err(err) = self.reply(request.reply_to, &response).await {
+                warn!(
+                    reply_to = request.reply_to,
+                    method = %request.method,
+                    error = %err,
+                    "failed to deliver RPC response"
+                );
+                self.replies.remove(&request.reply_to);
+            }
+        }
+        Ok(())
+    }
+
+    async fn reply(
+        &mut self,
+        reply_to: GuestResourceId,
+        response: &RpcResponse,
+    ) -> Result<(), RpcError> {
+        let payload = encode(response)?;
+        let writer = match self.replies.get_mut(&reply_to) {
+            Some(writer) => writer,
+            None => {
+                // Safe because the handle was shared with the server by the host kernel on the
+                // client's behalf; the host rejects it otherwise.
+                let shared = unsafe { SharedChannel::from_raw(reply_to) };
+                let writer = Channel::attach_shared(shared).await?.publish().await?;
+                self.replies.entry(reply_to).or_insert(writer)
+            }
+        };
+        writer.send(payload).await?;
+        Ok(())
+    }
+}
+
+/// Register `service` under its descriptor and answer its calls until the request channel
+/// closes.
+pub async fn serve<S: RpcDispatch>(service: S) -> Result<(), RpcError> {
+    let mut server = RpcServer::create().await?;
+    server.register(S::DESCRIPTOR).await?;
+    server.run(&service).await
+}
+
+/// Encode an RPC argument tuple, result or message.
+pub fn encode<T: RkyvEncode>(value: &T) -> Result<Vec<u8>, RpcError> {
+    encode_rkyv(value).map_err(|err| RpcError::Encode(err.to_string()))
+}
+
+/// Decode an RPC argument tuple, result or message.
+pub fn decode<T>(bytes: &[u8]) -> Result<T, RpcError>
+where
+    T: Archive,
+    for<'a> T::Archived: 'a
+        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+        + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+{
+    decode_rkyv(bytes).map_err(|err| RpcError::Decode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[crate::rpc(name = "tests.rpc.calculator")]
+    trait Calculator {
+        async fn add(&self, a: u64, b: u64) -> u64;
+        async fn divide(&self, a: u64, b: u64) -> Result<u64, String>;
+    }
+
+    struct Service;
+
+    impl Calculator for Service {
+        async fn add(&self, a: u64, b: u64) -> u64 {
+            a + b
+        }
+
+        async fn divide(&self, a: u64, b: u64) -> Result<u64, String> {
+            a.checked_div(b)
+                .ok_or_else(|| "division by zero".to_string())
+        }
+    }
+
+    #[test]
+    fn calls_round_trip_through_generated_stubs() {
+        crate::block_on(async {
+            let requests = Channel::create(DEFAULT_CAPACITY)
+                .await
+                .expect("create request channel");
+            let shared = requests.share().await.expect("share request channel");
+            let mut server = RpcServer::new(requests);
+            let _server = crate::spawn(async move { server.run(&CalculatorServer(Service)).await });
+
+            let client = CalculatorClient::new(RpcClient::connect(shared).await.expect("connect"));
+            let (sum, quotient) =
+                futures::future::join(client.add(2, 3), client.divide(7, 0)).await;
+            assert_eq!(sum.expect("add"), 5);
+            assert_eq!(
+                quotient.expect("divide"),
+                Err("division by zero".to_string())
+            );
+
+            let unknown = client.0.call::<_, ()>("subtract", &(2u64, 3u64)).await;
+            assert!(
+                matches!(unknown, Err(RpcError::UnknownMethod(method)) if method == "subtract")
+            );
+        });
+    }
+
+    #[test]
+    fn descriptors_hash_the_service_name() {
+        let descriptor = <CalculatorClient as crate::Dependency>::DESCRIPTOR;
+        assert_eq!(descriptor.name, "tests.rpc.calculator");
+        assert_eq!(descriptor.id, crate::dependency_id!("tests.rpc.calculator"));
+        assert_eq!(
+            <CalculatorServer<Service> as RpcDispatch>::DESCRIPTOR.id,
+            descriptor.id
+        );
+    }
+}
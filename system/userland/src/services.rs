@@ -0,0 +1,96 @@
+//! Guest helpers for the service directory.
+//!
+//! Unlike a singleton, a service may have many instances, each backed by its own shared
+//! resource. Instances report their health, and resolving a service hands out its instances in
+//! turn, preferring healthy ones to degraded ones and never returning unhealthy ones.
+
+use selium_abi::{
+    DependencyId, GuestResourceId, ServiceHealthUpdate, ServiceLookup, ServiceRegister,
+};
+
+use crate::{
+    DependencyDescriptor,
+    driver::{DriverError, DriverFuture, RkyvDecoder, encode_args},
+};
+
+pub use selium_abi::{ServiceHealth, ServiceInstance};
+
+/// Capacity of the buffer an instance listing is read into; longer listings arrive in chunks.
+const INSTANCES_CAPACITY: usize = 4 * 1024;
+
+/// Add a shared resource handle as an instance of the supplied service. The host refuses the
+/// registration unless the descriptor's identifier is the hash of its name. New instances start
+/// out [`ServiceHealth::Healthy`].
+pub async fn register(
+    service: DependencyDescriptor,
+    resource: GuestResourceId,
+) -> Result<(), DriverError> {
+    let args = encode_args(&ServiceRegister {
+        id: service.id,
+        name: service.name.to_string(),
+        resource,
+    })?;
+    DriverFuture::<service_register::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
+        .await?;
+    Ok(())
+}
+
+/// Report the health of an instance this guest registered.
+pub async fn set_health(
+    id: DependencyId,
+    resource: GuestResourceId,
+    health: ServiceHealth,
+) -> Result<(), DriverError> {
+    let args = encode_args(&ServiceHealthUpdate {
+        id,
+        resource,
+        health,
+    })?;
+    DriverFuture::<service_set_health::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
+        .await?;
+    Ok(())
+}
+
+/// Resolve the service to the shared resource handle of one of its serving instances.
+pub async fn resolve(id: DependencyId) -> Result<GuestResourceId, DriverError> {
+    let args = encode_args(&ServiceLookup { id })?;
+    let handle = DriverFuture::<service_resolve::Module, RkyvDecoder<GuestResourceId>>::new(
+        &args,
+        8,
+        RkyvDecoder::new(),
+    )?
+    .await?;
+    Ok(handle)
+}
+
+/// List every instance of the service and the health it last reported.
+pub async fn instances(id: DependencyId) -> Result<Vec<ServiceInstance>, DriverError> {
+    let args = encode_args(&ServiceLookup { id })?;
+    DriverFuture::<service_instances::Module, RkyvDecoder<Vec<ServiceInstance>>>::new(
+        &args,
+        INSTANCES_CAPACITY,
+        RkyvDecoder::new(),
+    )?
+    .await
+}
+
+driver_module!(
+    service_register,
+    SERVICE_REGISTER,
+    "selium::services::directory::register"
+);
+driver_module!(
+    service_set_health,
+    SERVICE_SET_HEALTH,
+    "selium::services::directory::set_health"
+);
+driver_module!(
+    service_resolve,
+    SERVICE_RESOLVE,
+    "selium::services::directory::resolve"
+);
+driver_module!(
+    service_instances,
+    SERVICE_INSTANCES,
+    "selium::services::directory::instances"
+);
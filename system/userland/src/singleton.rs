@@ -2,11 +2,22 @@
 
 use selium_abi::{DependencyId, GuestResourceId, SingletonLookup, SingletonRegister};
 
-use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+use crate::{
+    DependencyDescriptor,
+    driver::{DriverError, DriverFuture, RkyvDecoder, encode_args},
+};
 
-/// Register a shared resource handle under the supplied dependency identifier.
-pub async fn register(id: DependencyId, resource: GuestResourceId) -> Result<(), DriverError> {
-    let args = encode_args(&SingletonRegister { id, resource })?;
+/// Register a shared resource handle under the supplied dependency. The host refuses the
+/// registration unless the descriptor's identifier is the hash of its name.
+pub async fn register(
+    dependency: DependencyDescriptor,
+    resource: GuestResourceId,
+) -> Result<(), DriverError> {
+    let args = encode_args(&SingletonRegister {
+        id: dependency.id,
+        name: dependency.name.to_string(),
+        resource,
+    })?;
     DriverFuture::<singleton_register::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
         .await?;
     Ok(())
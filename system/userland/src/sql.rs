@@ -0,0 +1,87 @@
+//! SQL statements run on database connections the host holds.
+//!
+//! The host opens each connection and publishes it under a name; guests granted `Sql` look it
+//! up and run statements on it, seeing only statement text and typed rows. Connection strings
+//! and credentials never enter guest memory.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{
+//!     io::DriverError,
+//!     sql::{Connection, SqlValue},
+//! };
+//!
+//! async fn open_orders(customer: i64) -> Result<usize, DriverError> {
+//!     let orders = Connection::lookup("sql/orders").await?;
+//!     let rows = orders
+//!         .query(
+//!             "SELECT id FROM orders WHERE customer = ? AND open",
+//!             &[SqlValue::Integer(customer)],
+//!         )
+//!         .await?;
+//!     Ok(rows.rows.len())
+//! }
+//! ```
+
+use selium_abi::{DependencyId, GuestResourceId, SqlStatement};
+pub use selium_abi::{SqlRows, SqlValue};
+
+use crate::{
+    driver::{DriverError, DriverFuture, RkyvDecoder, encode_args},
+    singleton,
+};
+
+/// Expected size of an encoded result set; larger ones are handed over in chunks.
+const ROWS_CAPACITY: usize = 4096;
+
+/// A database connection held by the host.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Connection(GuestResourceId);
+
+impl Connection {
+    /// Look up the connection the host published as `name`.
+    pub async fn lookup(name: &str) -> Result<Self, DriverError> {
+        singleton::lookup(DependencyId::from_name(name))
+            .await
+            .map(Self)
+    }
+
+    /// Access the underlying shared handle.
+    pub fn raw(&self) -> GuestResourceId {
+        self.0
+    }
+
+    /// Run `statement` with `params` bound to its placeholders and return the rows it yields.
+    pub async fn query(
+        &self,
+        statement: &str,
+        params: &[SqlValue],
+    ) -> Result<SqlRows, DriverError> {
+        let args = self.encode(statement, params)?;
+        DriverFuture::<sql_query::Module, RkyvDecoder<SqlRows>>::new(
+            &args,
+            ROWS_CAPACITY,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+
+    /// Run `statement` with `params` bound to its placeholders and return how many rows it
+    /// changed.
+    pub async fn execute(&self, statement: &str, params: &[SqlValue]) -> Result<u64, DriverError> {
+        let args = self.encode(statement, params)?;
+        DriverFuture::<sql_execute::Module, RkyvDecoder<u64>>::new(&args, 8, RkyvDecoder::new())?
+            .await
+    }
+
+    fn encode(&self, statement: &str, params: &[SqlValue]) -> Result<Vec<u8>, DriverError> {
+        encode_args(&SqlStatement {
+            connection: self.0,
+            statement: statement.to_string(),
+            params: params.to_vec(),
+        })
+    }
+}
+
+driver_module!(sql_query, SQL_QUERY, "selium::sql::query");
+driver_module!(sql_execute, SQL_EXECUTE, "selium::sql::execute");
@@ -0,0 +1,117 @@
+//! Barriers and latches shared between guest processes.
+//!
+//! Both are named and held by the host, so cooperating guests rendezvous without sharing memory
+//! or polling. A [`Barrier`] releases its parties together once the last of them arrives, and
+//! can be reused for each phase of work. A [`Latch`] opens once it has been counted down to zero
+//! and then stays open, which suits waiting for a set of workers to finish starting up.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{io::DriverError, sync};
+//!
+//! async fn worker() -> Result<(), DriverError> {
+//!     // ... load state ...
+//!     sync::latch("workers-ready", 4).count_down().await?;
+//!     sync::barrier("phase", 4).wait().await?;
+//!     Ok(())
+//! }
+//!
+//! async fn coordinator() -> Result<(), DriverError> {
+//!     sync::latch("workers-ready", 4).wait().await
+//! }
+//! ```
+
+use selium_abi::{BarrierWait, LatchRequest};
+
+use crate::driver::{DriverError, DriverFuture, RkyvDecoder, encode_args};
+
+/// A named barrier for a fixed number of parties.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Barrier {
+    name: String,
+    parties: u32,
+}
+
+/// A named latch that opens after a fixed number of counts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Latch {
+    name: String,
+    count: u32,
+}
+
+impl Barrier {
+    /// Wait until every party has arrived. Returns the order this caller arrived in, from `0`;
+    /// exactly one party per phase gets `parties - 1`, and may act on behalf of the others.
+    pub async fn wait(&self) -> Result<u32, DriverError> {
+        let args = encode_args(&BarrierWait {
+            name: self.name.clone(),
+            parties: self.parties,
+        })?;
+        DriverFuture::<sync_barrier_wait::Module, RkyvDecoder<u32>>::new(
+            &args,
+            4,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+}
+
+impl Latch {
+    /// Count the latch down by one and return how many counts remain before it opens.
+    pub async fn count_down(&self) -> Result<u32, DriverError> {
+        let args = encode_args(&self.request())?;
+        DriverFuture::<sync_latch_count_down::Module, RkyvDecoder<u32>>::new(
+            &args,
+            4,
+            RkyvDecoder::new(),
+        )?
+        .await
+    }
+
+    /// Wait for the latch to open.
+    pub async fn wait(&self) -> Result<(), DriverError> {
+        let args = encode_args(&self.request())?;
+        DriverFuture::<sync_latch_wait::Module, RkyvDecoder<()>>::new(&args, 0, RkyvDecoder::new())?
+            .await
+    }
+
+    fn request(&self) -> LatchRequest {
+        LatchRequest {
+            name: self.name.clone(),
+            count: self.count,
+        }
+    }
+}
+
+/// Handle to the barrier `name` for `parties` parties. Every party must name the same number.
+pub fn barrier(name: impl Into<String>, parties: u32) -> Barrier {
+    Barrier {
+        name: name.into(),
+        parties,
+    }
+}
+
+/// Handle to the latch `name`, which opens after `count` counts. Every user must name the same
+/// count.
+pub fn latch(name: impl Into<String>, count: u32) -> Latch {
+    Latch {
+        name: name.into(),
+        count,
+    }
+}
+
+driver_module!(
+    sync_barrier_wait,
+    SYNC_BARRIER_WAIT,
+    "selium::sync::barrier::wait"
+);
+driver_module!(
+    sync_latch_count_down,
+    SYNC_LATCH_COUNT_DOWN,
+    "selium::sync::latch::count_down"
+);
+driver_module!(
+    sync_latch_wait,
+    SYNC_LATCH_WAIT,
+    "selium::sync::latch::wait"
+);
@@ -0,0 +1,162 @@
+//! Values followed by many guests, with last-value semantics.
+//!
+//! A [`Watch`] holds a single rkyv-encoded value. Any guest holding its shared handle may
+//! replace the value, and every [`Subscriber`] is woken through its mailbox when it changes.
+//! Subscribers only ever see the latest value: one that falls behind skips the versions it
+//! missed rather than queueing them, which suits configuration and leader-election state.
+//!
+//! Hand the watch to other guests by registering [`Watch::raw`] as a singleton or service, or
+//! by sending it over a channel. A watch lives as long as the process that created it.
+//!
+//! # Examples
+//! ```no_run
+//! use selium_userland::{io::DriverError, watch::Watch};
+//!
+//! async fn follow_leader() -> Result<(), DriverError> {
+//!     let leader = Watch::create(&"node-a".to_string()).await?;
+//!     let mut subscriber = leader.subscribe();
+//!     assert_eq!(subscriber.changed().await?, "node-a");
+//!
+//!     leader.set(&"node-b".to_string()).await?;
+//!     assert_eq!(subscriber.changed().await?, "node-b");
+//!     Ok(())
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use selium_abi::{
+    GuestResourceId, RkyvEncode, WatchCreate, WatchSet, WatchSubscribe, WatchValue, decode_rkyv,
+    encode_rkyv,
+};
+
+use crate::{
+    FromHandle,
+    driver::{DriverError, DriverFuture, RkyvDecoder, encode_args},
+};
+
+/// Capacity of the buffer a value is read into; larger values arrive in chunks.
+const VALUE_CAPACITY: usize = 4 * 1024;
+
+/// Handle to a value shared across processes.
+pub struct Watch<T> {
+    handle: GuestResourceId,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Follows the value of a [`Watch`], remembering the last version it returned.
+pub struct Subscriber<T> {
+    watch: GuestResourceId,
+    seen: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Watch<T> {
+    /// Create a watch holding `initial`, owned by the calling process.
+    pub async fn create(initial: &T) -> Result<Self, DriverError>
+    where
+        T: RkyvEncode,
+    {
+        let args = encode_args(&WatchCreate {
+            value: encode_value(initial)?,
+        })?;
+        let handle = DriverFuture::<watch_create::Module, RkyvDecoder<GuestResourceId>>::new(
+            &args,
+            8,
+            RkyvDecoder::new(),
+        )?
+        .await?;
+        Ok(Self {
+            handle,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Return the shared handle of the watch, for handing to other guests.
+    pub fn raw(&self) -> GuestResourceId {
+        self.handle
+    }
+
+    /// Replace the value, waking every subscriber, and return its new version.
+    pub async fn set(&self, value: &T) -> Result<u64, DriverError>
+    where
+        T: RkyvEncode,
+    {
+        let args = encode_args(&WatchSet {
+            watch: self.handle,
+            value: encode_value(value)?,
+        })?;
+        DriverFuture::<watch_set::Module, RkyvDecoder<u64>>::new(&args, 8, RkyvDecoder::new())?
+            .await
+    }
+
+    /// Follow the value. The subscriber's first [`changed`](Subscriber::changed) returns the
+    /// current value straight away.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        Subscriber {
+            watch: self.handle,
+            seen: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Subscriber<T> {
+    /// Version of the value last returned by [`changed`](Self::changed), or `0` before the
+    /// first.
+    pub fn version(&self) -> u64 {
+        self.seen
+    }
+
+    /// Wait for a value newer than the last one returned, and decode it.
+    pub async fn changed(&mut self) -> Result<T, DriverError>
+    where
+        T: rkyv::Archive,
+        for<'a> T::Archived: 'a
+            + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>
+            + rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>,
+    {
+        let args = encode_args(&WatchSubscribe {
+            watch: self.watch,
+            seen: self.seen,
+        })?;
+        let current: WatchValue = DriverFuture::<watch_subscribe::Module, RkyvDecoder<_>>::new(
+            &args,
+            VALUE_CAPACITY,
+            RkyvDecoder::new(),
+        )?
+        .await?;
+        let value =
+            decode_rkyv(&current.value).map_err(|err| DriverError::Driver(err.to_string()))?;
+        self.seen = current.version;
+        Ok(value)
+    }
+}
+
+impl<T> Clone for Watch<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> FromHandle for Watch<T> {
+    type Handles = GuestResourceId;
+
+    unsafe fn from_handle(handle: Self::Handles) -> Self {
+        Self {
+            handle,
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn encode_value<T: RkyvEncode>(value: &T) -> Result<Vec<u8>, DriverError> {
+    encode_rkyv(value).map_err(|err| DriverError::Driver(err.to_string()))
+}
+
+driver_module!(watch_create, WATCH_CREATE, "selium::watch::create");
+driver_module!(watch_set, WATCH_SET, "selium::watch::set");
+driver_module!(watch_subscribe, WATCH_SUBSCRIBE, "selium::watch::subscribe");
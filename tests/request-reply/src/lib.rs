@@ -178,7 +178,7 @@ async fn singleton_stub() -> Result<()> {
         .context("create stub channel")?;
     let shared = channel.share().await.context("share stub channel")?;
 
-    singleton::register(StubSingleton::DESCRIPTOR.id, shared.raw())
+    singleton::register(StubSingleton::DESCRIPTOR, shared.raw())
         .await
         .context("register singleton")?;
 